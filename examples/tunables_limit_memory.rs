@@ -15,6 +15,10 @@ use wasmer_engine_universal::Universal;
 ///
 /// After adjusting the memory limits, it delegates all other logic
 /// to the base tunables.
+///
+/// Note that a [`wasmer::StoreLimits`] set with
+/// `Store::set_resource_limiter` is simpler to use to limit the total
+/// memory of the instances of a store.
 #[derive(MemoryUsage)]
 pub struct LimitingTunables<T: Tunables> {
     /// The maximum a linear memory is allowed to be (in Wasm pages, 64 KiB each).
@@ -7,6 +7,7 @@ use crate::js::resolver::Resolver;
 use crate::js::store::Store;
 use crate::js::trap::RuntimeError;
 use js_sys::WebAssembly;
+use std::convert::TryFrom;
 use std::fmt;
 #[cfg(feature = "std")]
 use thiserror::Error;
@@ -132,7 +133,7 @@ impl Instance {
                             &name
                         ))
                     })?;
-                let export: Export = (js_export, extern_type).into();
+                let export = Export::try_from((js_export, extern_type))?;
                 let extern_ = Extern::from_vm_export(store, export);
                 Ok((name.to_string(), extern_))
             })
@@ -1,4 +1,4 @@
-use crate::sys::externals::{Extern, Function, Global, Memory, Table};
+use crate::sys::externals::{Extern, Function, Global, Memory, Table, Tag};
 use crate::sys::import_object::LikeNamespace;
 use crate::sys::native::NativeFunc;
 use crate::sys::WasmTypeList;
@@ -131,6 +131,11 @@ impl Exports {
         self.get(name)
     }
 
+    /// Get an export as a `Tag`.
+    pub fn get_tag(&self, name: &str) -> Result<&Tag, ExportError> {
+        self.get(name)
+    }
+
     /// Get an export as a `Func`.
     pub fn get_function(&self, name: &str) -> Result<&Function, ExportError> {
         self.get(name)
@@ -266,6 +271,14 @@ where
             _ => None,
         })
     }
+
+    /// Get only the tags.
+    pub fn tags(self) -> impl Iterator<Item = (&'a String, &'a Tag)> + Sized {
+        self.iter.filter_map(|(name, export)| match export {
+            Extern::Tag(tag) => Some((name, tag)),
+            _ => None,
+        })
+    }
 }
 
 impl FromIterator<(String, Extern)> for Exports {
@@ -12,10 +12,12 @@ use loupe::MemoryUsage;
 use std::cmp::max;
 use std::ffi::c_void;
 use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
 use wasmer_engine::{Export, ExportFunction, ExportFunctionMetadata};
 use wasmer_vm::{
-    on_host_stack, raise_user_trap, resume_panic, wasmer_call_trampoline, ImportInitializerFuncPtr,
+    block_on_host_future, catch_traps_async, on_host_stack, raise_user_trap, resume_panic,
+    wasmer_call_trampoline, wasmer_call_trampoline_async, ImportInitializerFuncPtr,
     VMCallerCheckedAnyfunc, VMDynamicFunctionContext, VMFuncRef, VMFunction, VMFunctionBody,
     VMFunctionEnvironment, VMFunctionKind, VMTrampoline,
 };
@@ -200,6 +202,58 @@ impl Function {
     /// ```
     #[allow(clippy::cast_ptr_alignment)]
     pub fn new_with_env<FT, F, Env>(store: &Store, ty: FT, env: Env, func: F) -> Self
+    where
+        FT: Into<FunctionType>,
+        F: Fn(&Env, &[Val]) -> Result<Vec<Val>, RuntimeError> + 'static + Send + Sync,
+        Env: Sized + WasmerEnv + 'static,
+    {
+        Self::new_dynamic(store, ty, env, func, false)
+    }
+
+    /// Creates a new asynchronous host `Function` (dynamic) with the
+    /// provided signature.
+    ///
+    /// The function returns a future. When it is called by a WebAssembly
+    /// function running in [`Function::call_async`] or
+    /// [`NativeFunc::call_async`], the WebAssembly function is suspended
+    /// until the future resolves. Calling it in any other way, e.g. with
+    /// [`Function::call`], results in an error.
+    ///
+    /// The future is polled on the thread polling the calling
+    /// [`Function::call_async`], so it doesn't need to be `Send`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmer::{Function, FunctionType, Type, Store, Value};
+    /// # let store = Store::default();
+    /// #
+    /// let signature = FunctionType::new(vec![Type::I32, Type::I32], vec![Type::I32]);
+    ///
+    /// let f = Function::new_async(&store, &signature, |args| async move {
+    ///     let sum = args[0].unwrap_i32() + args[1].unwrap_i32();
+    ///     Ok(vec![Value::I32(sum)])
+    /// });
+    /// ```
+    pub fn new_async<FT, F, Fut>(store: &Store, ty: FT, func: F) -> Self
+    where
+        FT: Into<FunctionType>,
+        F: Fn(Vec<Val>) -> Fut + 'static + Send + Sync,
+        Fut: Future<Output = Result<Vec<Val>, RuntimeError>> + 'static,
+    {
+        let wrapped_func =
+            move |_env: &WithoutEnv, args: &[Val]| -> Result<Vec<Val>, RuntimeError> {
+                let mut future = Box::pin(on_host_stack(|| func(args.to_vec())));
+                block_on_host_future(future.as_mut()).unwrap_or_else(|| {
+                    Err(RuntimeError::new(
+                        "async host functions can only be called from `call_async`",
+                    ))
+                })
+            };
+        Self::new_dynamic(store, ty, WithoutEnv, wrapped_func, true)
+    }
+
+    fn new_dynamic<FT, F, Env>(store: &Store, ty: FT, env: Env, func: F, is_async: bool) -> Self
     where
         FT: Into<FunctionType>,
         F: Fn(&Env, &[Val]) -> Result<Vec<Val>, RuntimeError> + 'static + Send + Sync,
@@ -212,6 +266,7 @@ impl Function {
                 func: Arc::new(func),
                 store: store.clone(),
                 function_type: ty.clone(),
+                is_async,
             });
 
         let import_init_function_ptr: for<'a> fn(&'a mut _, &'a _) -> Result<(), _> =
@@ -382,12 +437,9 @@ impl Function {
         &self.store
     }
 
-    fn call_wasm(
-        &self,
-        trampoline: VMTrampoline,
-        params: &[Val],
-        results: &mut [Val],
-    ) -> Result<(), RuntimeError> {
+    /// Checks `params` and `results` against the signature of the function,
+    /// and stores the parameters into a new `values_vec` for its trampoline.
+    fn values_vec(&self, params: &[Val], results: &[Val]) -> Result<Vec<i128>, RuntimeError> {
         let format_types_for_error_message = |items: &[Val]| {
             items
                 .iter()
@@ -428,6 +480,17 @@ impl Function {
             }
         }
 
+        Ok(values_vec)
+    }
+
+    fn call_wasm(
+        &self,
+        trampoline: VMTrampoline,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<(), RuntimeError> {
+        let mut values_vec = self.values_vec(params, results)?;
+
         // Call the trampoline.
         if let Err(error) = unsafe {
             wasmer_call_trampoline(
@@ -441,15 +504,45 @@ impl Function {
             return Err(RuntimeError::from_trap(error));
         }
 
-        // Load the return values out of `values_vec`.
-        for (index, &value_type) in signature.results().iter().enumerate() {
+        self.load_results(&values_vec, results);
+        Ok(())
+    }
+
+    async fn call_wasm_async(
+        &self,
+        trampoline: VMTrampoline,
+        params: &[Val],
+        results: &mut [Val],
+    ) -> Result<(), RuntimeError> {
+        let mut values_vec = self.values_vec(params, results)?;
+
+        // Call the trampoline on a stack that can be suspended.
+        if let Err(error) = unsafe {
+            wasmer_call_trampoline_async(
+                &self.store,
+                self.exported.vm_function.vmctx,
+                trampoline,
+                self.exported.vm_function.address,
+                values_vec.as_mut_ptr() as *mut u8,
+            )
+        }
+        .await
+        {
+            return Err(RuntimeError::from_trap(error));
+        }
+
+        self.load_results(&values_vec, results);
+        Ok(())
+    }
+
+    /// Load the return values out of `values_vec`.
+    fn load_results(&self, values_vec: &[i128], results: &mut [Val]) {
+        for (index, &value_type) in self.ty().results().iter().enumerate() {
             unsafe {
                 let ptr = values_vec.as_ptr().add(index);
                 results[index] = Val::read_value_from(&self.store, ptr, value_type);
             }
         }
-
-        Ok(())
     }
 
     /// Returns the number of parameters that this function takes.
@@ -544,6 +637,137 @@ impl Function {
         }
     }
 
+    /// Call the `Function` function asynchronously.
+    ///
+    /// This works like [`Function::call`], except that WebAssembly code runs
+    /// on a separate stack which is suspended whenever it waits for an
+    /// asynchronous host function (see [`Function::new_async`]). The
+    /// returned future is pending in the meantime.
+    ///
+    /// Dropping the future before it completes cancels the call.
+    ///
+    /// The returned future is not `Send`: the suspended stack holds the
+    /// frames of the WebAssembly code and of the host functions it called,
+    /// which must be resumed on the thread that started them. It must be
+    /// polled by a single-threaded executor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmer::{imports, wat2wasm, Function, Instance, Module, Store, Type, Value};
+    /// # let store = Store::default();
+    /// # let wasm_bytes = wat2wasm(r#"
+    /// # (module
+    /// #   (func (export "sum") (param $x i32) (param $y i32) (result i32)
+    /// #     local.get $x
+    /// #     local.get $y
+    /// #     i32.add
+    /// #   ))
+    /// # "#.as_bytes()).unwrap();
+    /// # let module = Module::new(&store, wasm_bytes).unwrap();
+    /// # let import_object = imports! {};
+    /// # let instance = Instance::new(&module, &import_object).unwrap();
+    /// #
+    /// let sum = instance.exports.get_function("sum").unwrap();
+    ///
+    /// # let future = async {
+    /// assert_eq!(
+    ///     sum.call_async(&[Value::I32(1), Value::I32(2)]).await.unwrap().to_vec(),
+    ///     vec![Value::I32(3)],
+    /// );
+    /// # };
+    /// ```
+    ///
+    /// With a multi-threaded Tokio runtime, the call can be spawned on a
+    /// `LocalSet`, which polls its tasks on the thread running it:
+    ///
+    /// ```ignore
+    /// let local = tokio::task::LocalSet::new();
+    /// let sum = sum.clone();
+    /// let results = local
+    ///     .run_until(async move {
+    ///         tokio::task::spawn_local(async move {
+    ///             sum.call_async(&[Value::I32(1), Value::I32(2)]).await
+    ///         })
+    ///         .await
+    ///         .unwrap()
+    ///     })
+    ///     .await?;
+    /// assert_eq!(results.to_vec(), vec![Value::I32(3)]);
+    /// ```
+    pub async fn call_async(&self, params: &[Val]) -> Result<Box<[Val]>, RuntimeError> {
+        // If it's a function defined in the Wasm, it will always have a call_trampoline
+        if let Some(trampoline) = self.exported.vm_function.call_trampoline {
+            let mut results = vec![Val::null(); self.result_arity()];
+            self.call_wasm_async(trampoline, params, &mut results)
+                .await?;
+            return Ok(results.into_boxed_slice());
+        }
+
+        // If it's a function defined in the host
+        match self.exported.vm_function.kind {
+            VMFunctionKind::Dynamic => unsafe {
+                Ok(
+                    call_dynamic_async(&self.store, self.exported.vm_function.vmctx, params)
+                        .await?
+                        .into_boxed_slice(),
+                )
+            },
+            VMFunctionKind::Static => {
+                unimplemented!(
+                    "Native function definitions can't be directly called from the host yet"
+                );
+            }
+        }
+    }
+
+    /// Returns a handle to this function whose calls run on a stack of
+    /// `stack_size` bytes, instead of the stack size of its [`Store`] (see
+    /// [`Store::set_stack_size`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{imports, wat2wasm, Function, Instance, Module, Store, Type, Value};
+    /// # let store = Store::default();
+    /// # let wasm_bytes = wat2wasm(r#"
+    /// # (module
+    /// #   (func $count (export "count") (param $n i32) (result i32)
+    /// #     local.get $n
+    /// #     i32.eqz
+    /// #     if (result i32)
+    /// #       i32.const 0
+    /// #     else
+    /// #       local.get $n
+    /// #       i32.const 1
+    /// #       i32.sub
+    /// #       call $count
+    /// #       i32.const 1
+    /// #       i32.add
+    /// #     end))
+    /// # "#.as_bytes()).unwrap();
+    /// # let module = Module::new(&store, wasm_bytes).unwrap();
+    /// # let import_object = imports! {};
+    /// # let instance = Instance::new(&module, &import_object).unwrap();
+    /// #
+    /// let count = instance.exports.get_function("count").unwrap();
+    /// // Each call takes at least the 8 bytes of its return address, so
+    /// // 200 000 nested calls don't fit in the default stack of 1 MiB.
+    /// let deep = Value::I32(200_000);
+    ///
+    /// assert!(count.call(&[deep.clone()]).is_err());
+    /// assert_eq!(
+    ///     count.with_stack_size(64 << 20).call(&[deep]).unwrap().to_vec(),
+    ///     vec![Value::I32(200_000)],
+    /// );
+    /// ```
+    pub fn with_stack_size(&self, stack_size: usize) -> Self {
+        Self {
+            store: self.store.with_stack_size(stack_size),
+            ..self.clone()
+        }
+    }
+
     pub(crate) fn from_vm_export(store: &Store, wasmer_export: ExportFunction) -> Self {
         Self {
             store: store.clone(),
@@ -732,11 +956,31 @@ impl fmt::Debug for Function {
     }
 }
 
+/// Calls the dynamic host function of `vmctx` on a stack that can be
+/// suspended, so that it can be an asynchronous host function.
+///
+/// # Safety
+///
+/// `vmctx` must be the environment of a `VMFunctionKind::Dynamic`
+/// function.
+pub(crate) async unsafe fn call_dynamic_async(
+    store: &Store,
+    vmctx: VMFunctionEnvironment,
+    params: &[Val],
+) -> Result<Vec<Val>, RuntimeError> {
+    type VMContextWithEnv = VMDynamicFunctionContext<DynamicFunction<std::ffi::c_void>>;
+    let ctx = vmctx.host_env as *mut VMContextWithEnv;
+    catch_traps_async(store, || (*ctx).ctx.call(params))
+        .await
+        .map_err(RuntimeError::from_trap)?
+}
+
 /// This trait is one that all dynamic functions must fulfill.
 pub(crate) trait VMDynamicFunction: Send + Sync {
     fn call(&self, args: &[Val]) -> Result<Vec<Val>, RuntimeError>;
     fn function_type(&self) -> &FunctionType;
     fn store(&self) -> &Store;
+    fn is_async(&self) -> bool;
 }
 
 pub(crate) struct DynamicFunction<Env>
@@ -748,6 +992,7 @@ where
     func: Arc<dyn Fn(&Env, &[Val]) -> Result<Vec<Val>, RuntimeError> + 'static + Send + Sync>,
     store: Store,
     env: Box<Env>,
+    is_async: bool,
 }
 
 impl<Env: Sized + Clone + 'static + Send + Sync> Clone for DynamicFunction<Env> {
@@ -757,6 +1002,7 @@ impl<Env: Sized + Clone + 'static + Send + Sync> Clone for DynamicFunction<Env>
             function_type: self.function_type.clone(),
             store: self.store.clone(),
             func: self.func.clone(),
+            is_async: self.is_async,
         }
     }
 }
@@ -774,6 +1020,9 @@ where
     fn store(&self) -> &Store {
         &self.store
     }
+    fn is_async(&self) -> bool {
+        self.is_async
+    }
 }
 
 trait VMDynamicFunctionCall<T: VMDynamicFunction> {
@@ -803,7 +1052,7 @@ impl<T: VMDynamicFunction> VMDynamicFunctionCall<T> for VMDynamicFunctionContext
         values_vec: *mut i128,
     ) {
         use std::panic::{self, AssertUnwindSafe};
-        let result = on_host_stack(|| {
+        let call = || {
             panic::catch_unwind(AssertUnwindSafe(|| {
                 let func_ty = self.ctx.function_type();
                 let mut args = Vec::with_capacity(func_ty.params().len());
@@ -830,7 +1079,14 @@ impl<T: VMDynamicFunction> VMDynamicFunctionCall<T> for VMDynamicFunctionContext
             })) // We get extern ref drops at the end of this block that we don't need.
                 // By preventing extern ref incs in the code above we can save the work of
                 // incrementing and decrementing. However the logic as-is is correct.
-        });
+        };
+        // Async host functions may suspend the Wasm stack, so they must be
+        // called on it. They still poll their future on the host stack.
+        let result = if self.ctx.is_async() {
+            call()
+        } else {
+            on_host_stack(call)
+        };
 
         match result {
             Ok(Ok(())) => {}
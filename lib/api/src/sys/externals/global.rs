@@ -1,5 +1,5 @@
-use crate::sys::exports::{ExportError, Exportable};
-use crate::sys::externals::Extern;
+use crate::sys::exports::{ExportError, Exportable, ExportableWithGenerics};
+use crate::sys::externals::{Extern, FromToNativeWasmType};
 use crate::sys::store::{Store, StoreObject};
 use crate::sys::types::Val;
 use crate::sys::GlobalType;
@@ -7,8 +7,10 @@ use crate::sys::Mutability;
 use crate::sys::RuntimeError;
 use loupe::MemoryUsage;
 use std::fmt;
+use std::marker::PhantomData;
 use std::sync::Arc;
 use wasmer_engine::Export;
+use wasmer_types::NativeWasmType;
 use wasmer_vm::{Global as RuntimeGlobal, VMGlobal};
 
 /// A WebAssembly `global` instance.
@@ -186,6 +188,45 @@ impl Global {
         Ok(())
     }
 
+    /// Returns a view of the `Global` with the static type `T`, whose
+    /// `get` and `set` don't go through [`Val`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Global, Store, Value};
+    /// # let store = Store::default();
+    /// #
+    /// let g = Global::new_mut(&store, Value::I32(1));
+    /// let typed = g.typed::<u32>().unwrap();
+    ///
+    /// typed.set(2).unwrap();
+    ///
+    /// assert_eq!(typed.get(), 2);
+    /// assert_eq!(g.get(), Value::I32(2));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `T` doesn't match the type of the `Global`.
+    pub fn typed<T>(&self) -> Result<TypedGlobal<T>, RuntimeError>
+    where
+        T: FromToNativeWasmType,
+    {
+        let expected = self.ty().ty;
+        let given = T::Native::WASM_TYPE;
+        if expected != given {
+            return Err(RuntimeError::new(format!(
+                "given type (`{:?}`) for the global doesn't match the actual type (`{:?}`)",
+                given, expected,
+            )));
+        }
+        Ok(TypedGlobal {
+            global: self.clone(),
+            _phantom: PhantomData,
+        })
+    }
+
     pub(crate) fn from_vm_export(store: &Store, vm_global: VMGlobal) -> Self {
         Self {
             store: store.clone(),
@@ -263,3 +304,70 @@ impl<'a> Exportable<'a> for Global {
             .map(|v| *v = v.downgrade());
     }
 }
+
+/// A view of a [`Global`] with the static type `T`.
+///
+/// It is created with [`Global::typed`].
+pub struct TypedGlobal<T> {
+    global: Global,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: FromToNativeWasmType> TypedGlobal<T> {
+    /// Retrieves the current value of the global.
+    pub fn get(&self) -> T {
+        // Safety: the type of the global was checked by `Global::typed`.
+        T::from_native(unsafe { self.global.vm_global.from.get_native::<T::Native>() })
+    }
+
+    /// Sets the value of the global.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the global is immutable.
+    pub fn set(&self, val: T) -> Result<(), RuntimeError> {
+        // Safety: the type of the global was checked by `Global::typed`.
+        unsafe {
+            self.global
+                .vm_global
+                .from
+                .set_native(val.to_native())
+                .map_err(|e| RuntimeError::new(format!("{}", e)))
+        }
+    }
+
+    /// Returns the untyped [`Global`].
+    pub fn global(&self) -> &Global {
+        &self.global
+    }
+}
+
+impl<T> Clone for TypedGlobal<T> {
+    fn clone(&self) -> Self {
+        Self {
+            global: self.global.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TypedGlobal<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("TypedGlobal")
+            .field("global", &self.global)
+            .finish()
+    }
+}
+
+impl<'a, T: FromToNativeWasmType> ExportableWithGenerics<'a, (), ()> for TypedGlobal<T> {
+    fn get_self_from_extern_with_generics(_extern: &'a Extern) -> Result<Self, ExportError> {
+        Global::get_self_from_extern(_extern)?
+            .typed()
+            .map_err(|_| ExportError::IncompatibleType)
+    }
+
+    fn into_weak_instance_ref(&mut self) {
+        <Global as Exportable>::into_weak_instance_ref(&mut self.global);
+    }
+}
@@ -4,11 +4,12 @@ use crate::sys::store::Store;
 use crate::sys::{MemoryType, MemoryView};
 use loupe::MemoryUsage;
 use std::convert::TryInto;
+use std::mem::{self, MaybeUninit};
 use std::slice;
 use std::sync::Arc;
 use wasmer_engine::Export;
 use wasmer_types::{Pages, ValueType};
-use wasmer_vm::{MemoryError, VMMemory};
+use wasmer_vm::{MemoryAccessError, MemoryError, VMMemory};
 
 /// A WebAssembly `memory` instance.
 ///
@@ -184,7 +185,107 @@ impl Memory {
     where
         IntoPages: Into<Pages>,
     {
-        self.vm_memory.from.grow(delta.into())
+        self.vm_memory.grow(delta.into())
+    }
+
+    /// Safely reads bytes from the memory at the given offset.
+    ///
+    /// The full buffer will be filled, otherwise a `MemoryAccessError` is
+    /// returned to indicate an out-of-bounds access.
+    ///
+    /// This method is guaranteed to be safe (from the host side) in the face
+    /// of concurrent writes and of concurrent calls to [`Memory::grow`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Memory, MemoryAccessError, MemoryType, Store};
+    /// # let store = Store::default();
+    /// #
+    /// let m = Memory::new(&store, MemoryType::new(1, None, false)).unwrap();
+    /// m.write(0x10, b"hello").unwrap();
+    ///
+    /// let mut buf = [0; 5];
+    /// m.read(0x10, &mut buf).unwrap();
+    /// assert_eq!(&buf, b"hello");
+    ///
+    /// assert_eq!(m.read(0xfffe, &mut buf), Err(MemoryAccessError::HeapOutOfBounds));
+    /// ```
+    pub fn read(&self, offset: u64, buf: &mut [u8]) -> Result<(), MemoryAccessError> {
+        self.vm_memory.from.read(offset, buf)
+    }
+
+    /// Safely writes bytes to the memory at the given offset.
+    ///
+    /// If the write exceeds the bounds of the memory then a
+    /// `MemoryAccessError` is returned and nothing is written.
+    ///
+    /// This method is guaranteed to be safe (from the host side) in the face
+    /// of concurrent reads/writes and of concurrent calls to [`Memory::grow`].
+    pub fn write(&self, offset: u64, data: &[u8]) -> Result<(), MemoryAccessError> {
+        self.vm_memory.from.write(offset, data)
+    }
+
+    /// Safely reads `len` bytes from the memory at the given offset into a
+    /// new `Vec`.
+    pub fn read_to_vec(&self, offset: u64, len: u64) -> Result<Vec<u8>, MemoryAccessError> {
+        let end = offset.checked_add(len).ok_or(MemoryAccessError::Overflow)?;
+        // Check the bounds before allocating, so that a bogus length can't
+        // make the host allocate a huge buffer.
+        if end > self.data_size() {
+            return Err(MemoryAccessError::HeapOutOfBounds);
+        }
+        let mut buf = vec![0; len.try_into().map_err(|_| MemoryAccessError::Overflow)?];
+        self.read(offset, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Safely reads a UTF-8 string of `len` bytes from the memory at the
+    /// given offset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Memory, MemoryAccessError, MemoryType, Store};
+    /// # let store = Store::default();
+    /// #
+    /// let m = Memory::new(&store, MemoryType::new(1, None, false)).unwrap();
+    /// m.write(0, b"hello\xff").unwrap();
+    ///
+    /// assert_eq!(m.read_utf8_string(0, 5).unwrap(), "hello");
+    /// assert_eq!(m.read_utf8_string(0, 6), Err(MemoryAccessError::NonUtf8String));
+    /// ```
+    pub fn read_utf8_string(&self, offset: u64, len: u64) -> Result<String, MemoryAccessError> {
+        String::from_utf8(self.read_to_vec(offset, len)?)
+            .map_err(|_| MemoryAccessError::NonUtf8String)
+    }
+
+    /// Safely reads a value of type `T` from the memory at the given offset.
+    ///
+    /// The offset doesn't need to be aligned for `T`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Memory, MemoryType, Store};
+    /// # let store = Store::default();
+    /// #
+    /// let m = Memory::new(&store, MemoryType::new(1, None, false)).unwrap();
+    /// m.write(1, &42u32.to_ne_bytes()).unwrap();
+    ///
+    /// assert_eq!(m.read_pod::<u32>(1).unwrap(), 42);
+    /// ```
+    pub fn read_pod<T: ValueType>(&self, offset: u64) -> Result<T, MemoryAccessError> {
+        let mut value = MaybeUninit::<T>::zeroed();
+        // # Safety
+        // The bytes of `value` are initialized, and any bytes are a valid
+        // `T` since it is a `ValueType`.
+        unsafe {
+            let bytes =
+                slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, mem::size_of::<T>());
+            self.read(offset, bytes)?;
+            Ok(value.assume_init())
+        }
     }
 
     /// Return a "view" of the currently accessible memory. By
@@ -2,14 +2,16 @@ pub(crate) mod function;
 mod global;
 mod memory;
 mod table;
+mod tag;
 
 pub use self::function::{
     FromToNativeWasmType, Function, HostFunction, WasmTypeList, WithEnv, WithoutEnv,
 };
 
-pub use self::global::Global;
+pub use self::global::{Global, TypedGlobal};
 pub use self::memory::Memory;
-pub use self::table::Table;
+pub use self::table::{Table, TableElementType, TypedTable};
+pub use self::tag::Tag;
 
 use crate::sys::exports::{ExportError, Exportable};
 use crate::sys::store::{Store, StoreObject};
@@ -32,6 +34,8 @@ pub enum Extern {
     Table(Table),
     /// A external [`Memory`].
     Memory(Memory),
+    /// A external [`Tag`].
+    Tag(Tag),
 }
 
 impl Extern {
@@ -42,6 +46,7 @@ impl Extern {
             Self::Memory(ft) => ExternType::Memory(ft.ty()),
             Self::Table(tt) => ExternType::Table(*tt.ty()),
             Self::Global(gt) => ExternType::Global(*gt.ty()),
+            Self::Tag(tt) => ExternType::Tag(tt.ty().clone()),
         }
     }
 
@@ -52,6 +57,7 @@ impl Extern {
             Export::Memory(m) => Self::Memory(Memory::from_vm_export(store, m)),
             Export::Global(g) => Self::Global(Global::from_vm_export(store, g)),
             Export::Table(t) => Self::Table(Table::from_vm_export(store, t)),
+            Export::Tag(t) => Self::Tag(Tag::from_vm_export(store, t)),
         }
     }
 }
@@ -63,6 +69,7 @@ impl<'a> Exportable<'a> for Extern {
             Self::Global(g) => g.to_export(),
             Self::Memory(m) => m.to_export(),
             Self::Table(t) => t.to_export(),
+            Self::Tag(t) => t.to_export(),
         }
     }
 
@@ -77,6 +84,7 @@ impl<'a> Exportable<'a> for Extern {
             Self::Global(g) => g.into_weak_instance_ref(),
             Self::Memory(m) => m.into_weak_instance_ref(),
             Self::Table(t) => t.into_weak_instance_ref(),
+            Self::Tag(t) => t.into_weak_instance_ref(),
         }
     }
 }
@@ -88,6 +96,7 @@ impl StoreObject for Extern {
             Self::Global(g) => g.store(),
             Self::Memory(m) => m.store(),
             Self::Table(t) => t.store(),
+            Self::Tag(t) => t.store(),
         };
         Store::same(my_store, store)
     }
@@ -103,6 +112,7 @@ impl fmt::Debug for Extern {
                 Self::Global(_) => "Global(...)",
                 Self::Memory(_) => "Memory(...)",
                 Self::Table(_) => "Table(...)",
+                Self::Tag(_) => "Tag(...)",
             }
         )
     }
@@ -131,3 +141,9 @@ impl From<Table> for Extern {
         Self::Table(r)
     }
 }
+
+impl From<Tag> for Extern {
+    fn from(r: Tag) -> Self {
+        Self::Tag(r)
+    }
+}
@@ -1,13 +1,17 @@
-use crate::sys::exports::{ExportError, Exportable};
-use crate::sys::externals::Extern;
+use crate::sys::exports::{ExportError, Exportable, ExportableWithGenerics};
+use crate::sys::externals::{Extern, Function};
 use crate::sys::store::Store;
-use crate::sys::types::{Val, ValFuncRef};
+use crate::sys::types::{Val, ValFuncRef, ValType};
+#[cfg(feature = "experimental-reference-types-extern-ref")]
+use crate::sys::ExternRef;
 use crate::sys::RuntimeError;
 use crate::sys::TableType;
 use loupe::MemoryUsage;
+use std::fmt;
+use std::marker::PhantomData;
 use std::sync::Arc;
 use wasmer_engine::Export;
-use wasmer_vm::{Table as RuntimeTable, TableElement, VMTable};
+use wasmer_vm::{ResourceLimitError, Table as RuntimeTable, TableElement, VMFuncRef, VMTable};
 
 /// A WebAssembly `table` instance.
 ///
@@ -32,6 +36,20 @@ fn set_table_item(
     table.set(item_index, item).map_err(|e| e.into())
 }
 
+fn table_grow_result(
+    result: Result<Option<u32>, ResourceLimitError>,
+    delta: u32,
+) -> Result<u32, RuntimeError> {
+    match result {
+        Ok(Some(previous_size)) => Ok(previous_size),
+        Ok(None) => Err(RuntimeError::new(format!(
+            "failed to grow table by `{}`",
+            delta
+        ))),
+        Err(error) => Err(RuntimeError::user(Box::new(error))),
+    }
+}
+
 impl Table {
     /// Creates a new `Table` with the provided [`TableType`] definition.
     ///
@@ -99,10 +117,7 @@ impl Table {
     /// Returns an error if the `delta` is out of bounds for the table.
     pub fn grow(&self, delta: u32, init: Val) -> Result<u32, RuntimeError> {
         let item = init.into_table_reference(&self.store)?;
-        self.vm_table
-            .from
-            .grow(delta, item)
-            .ok_or_else(|| RuntimeError::new(format!("failed to grow table by `{}`", delta)))
+        table_grow_result(self.vm_table.grow(delta, item), delta)
     }
 
     /// Copies the `len` elements of `src_table` starting at `src_index`
@@ -135,6 +150,44 @@ impl Table {
         Ok(())
     }
 
+    /// Returns a view of the `Table` whose elements have the static type
+    /// `T`: `Option<Function>` for `funcref` tables, or `ExternRef` for
+    /// `externref` tables.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Function, Store, Table, TableType, Type, Value};
+    /// # let store = Store::default();
+    /// #
+    /// let ty = TableType::new(Type::FuncRef, 2, None);
+    /// let table = Table::new(&store, ty, Value::FuncRef(None)).unwrap();
+    /// let typed = table.typed::<Option<Function>>().unwrap();
+    ///
+    /// typed.set(1, Some(Function::new_native(&store, || 42))).unwrap();
+    ///
+    /// assert!(typed.get(0).unwrap().is_none());
+    /// assert!(typed.get(1).unwrap().is_some());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `T` doesn't match the element type of the `Table`.
+    pub fn typed<T: TableElementType>(&self) -> Result<TypedTable<T>, RuntimeError> {
+        let expected = self.ty().ty;
+        if expected != T::TYPE {
+            return Err(RuntimeError::new(format!(
+                "given type (`{:?}`) for the table elements doesn't match the actual type (`{:?}`)",
+                T::TYPE,
+                expected,
+            )));
+        }
+        Ok(TypedTable {
+            table: self.clone(),
+            _phantom: PhantomData,
+        })
+    }
+
     pub(crate) fn from_vm_export(store: &Store, vm_table: VMTable) -> Self {
         Self {
             store: store.clone(),
@@ -191,3 +244,128 @@ impl<'a> Exportable<'a> for Table {
             .map(|v| *v = v.downgrade());
     }
 }
+
+/// The Rust type of the elements of a table with a given element type.
+///
+/// It is implemented for `Option<Function>` (`funcref`) and for
+/// `ExternRef` (`externref`).
+pub trait TableElementType: Sized {
+    /// The element type of the tables this type can view.
+    const TYPE: ValType;
+
+    #[doc(hidden)]
+    fn from_table_element(item: TableElement, store: &Store) -> Self;
+
+    #[doc(hidden)]
+    fn into_table_element(self, store: &Store) -> Result<TableElement, RuntimeError>;
+}
+
+impl TableElementType for Option<Function> {
+    const TYPE: ValType = ValType::FuncRef;
+
+    fn from_table_element(item: TableElement, store: &Store) -> Self {
+        match item {
+            TableElement::FuncRef(func_ref) => match Val::from_vm_funcref(func_ref, store) {
+                Val::FuncRef(function) => function,
+                _ => unreachable!(),
+            },
+            TableElement::ExternRef(_) => unreachable!("externref in a funcref table"),
+        }
+    }
+
+    fn into_table_element(self, store: &Store) -> Result<TableElement, RuntimeError> {
+        Ok(TableElement::FuncRef(match self {
+            None => VMFuncRef::null(),
+            Some(function) => {
+                if !Store::same(store, function.store()) {
+                    return Err(RuntimeError::new("cross-`Store` values are not supported"));
+                }
+                function.vm_funcref()
+            }
+        }))
+    }
+}
+
+#[cfg(feature = "experimental-reference-types-extern-ref")]
+impl TableElementType for ExternRef {
+    const TYPE: ValType = ValType::ExternRef;
+
+    fn from_table_element(item: TableElement, _store: &Store) -> Self {
+        match item {
+            TableElement::ExternRef(extern_ref) => extern_ref,
+            TableElement::FuncRef(_) => unreachable!("funcref in an externref table"),
+        }
+    }
+
+    fn into_table_element(self, _store: &Store) -> Result<TableElement, RuntimeError> {
+        Ok(TableElement::ExternRef(self))
+    }
+}
+
+/// A view of a [`Table`] whose elements have the static type `T`.
+///
+/// It is created with [`Table::typed`].
+pub struct TypedTable<T> {
+    table: Table,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: TableElementType> TypedTable<T> {
+    /// Retrieves the element of the table at the provided `index`.
+    pub fn get(&self, index: u32) -> Option<T> {
+        let item = self.table.vm_table.from.get(index)?;
+        Some(T::from_table_element(item, &self.table.store))
+    }
+
+    /// Sets the element of the table at the provided `index`.
+    pub fn set(&self, index: u32, val: T) -> Result<(), RuntimeError> {
+        let item = val.into_table_element(&self.table.store)?;
+        set_table_item(self.table.vm_table.from.as_ref(), index, item)
+    }
+
+    /// Retrieves the size of the table (in elements).
+    pub fn size(&self) -> u32 {
+        self.table.size()
+    }
+
+    /// Grows the size of the table by `delta`, like [`Table::grow`].
+    pub fn grow(&self, delta: u32, init: T) -> Result<u32, RuntimeError> {
+        let item = init.into_table_element(&self.table.store)?;
+        table_grow_result(self.table.vm_table.grow(delta, item), delta)
+    }
+
+    /// Returns the untyped [`Table`].
+    pub fn table(&self) -> &Table {
+        &self.table
+    }
+}
+
+impl<T> Clone for TypedTable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TypedTable<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("TypedTable")
+            .field("ty", self.table.ty())
+            .finish()
+    }
+}
+
+impl<'a, T: TableElementType> ExportableWithGenerics<'a, (), ()> for TypedTable<T> {
+    fn get_self_from_extern_with_generics(_extern: &'a Extern) -> Result<Self, ExportError> {
+        Table::get_self_from_extern(_extern)?
+            .typed()
+            .map_err(|_| ExportError::IncompatibleType)
+    }
+
+    fn into_weak_instance_ref(&mut self) {
+        <Table as Exportable>::into_weak_instance_ref(&mut self.table);
+    }
+}
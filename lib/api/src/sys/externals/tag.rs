@@ -0,0 +1,148 @@
+use crate::sys::exports::{ExportError, Exportable};
+use crate::sys::externals::Extern;
+use crate::sys::store::Store;
+use crate::sys::types::Val;
+use crate::sys::{RuntimeError, TagType};
+use loupe::MemoryUsage;
+use std::fmt;
+use std::sync::Arc;
+use wasmer_engine::Export;
+use wasmer_vm::{Tag as RuntimeTag, VMTag};
+
+/// A WebAssembly `tag` instance, from the exception-handling proposal.
+///
+/// A tag identifies the exceptions thrown with it, and gives the types of
+/// the values they carry. Tags are compared by identity: a `catch` only
+/// catches the exceptions thrown with the very same tag.
+///
+/// An exception that no handler caught is returned to the host as a
+/// [`RuntimeError`], whose values can be read with
+/// [`Tag::exception_payload`].
+#[derive(MemoryUsage)]
+pub struct Tag {
+    store: Store,
+    vm_tag: VMTag,
+}
+
+impl Tag {
+    /// Create a new `Tag` of the given [`TagType`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Store, Tag, TagType, Type};
+    /// # let store = Store::default();
+    /// #
+    /// let tag = Tag::new(&store, TagType::new([Type::I32]));
+    ///
+    /// assert_eq!(tag.ty().params(), &[Type::I32]);
+    /// ```
+    pub fn new(store: &Store, ty: TagType) -> Self {
+        Self {
+            store: store.clone(),
+            vm_tag: VMTag {
+                from: Arc::new(RuntimeTag::new(ty)),
+                instance_ref: None,
+            },
+        }
+    }
+
+    /// Returns the [`TagType`] of the `Tag`.
+    pub fn ty(&self) -> &TagType {
+        self.vm_tag.ty()
+    }
+
+    /// Returns the [`Store`] where the `Tag` belongs.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Returns the values carried by the exception `error` is, if it was
+    /// thrown with this tag.
+    ///
+    /// Returns `None` if `error` isn't an exception, or if it was thrown
+    /// with another tag.
+    pub fn exception_payload(&self, error: &RuntimeError) -> Option<Vec<Val>> {
+        let exception = error.exception()?;
+        if !Arc::ptr_eq(exception.tag(), &self.vm_tag.from) {
+            return None;
+        }
+        let payload = exception.payload();
+        let values = self
+            .ty()
+            .params()
+            .iter()
+            .enumerate()
+            .map(|(index, &ty)| unsafe {
+                Val::read_value_from(&self.store, payload.as_ptr().add(index) as *const i128, ty)
+            })
+            .collect();
+        Some(values)
+    }
+
+    pub(crate) fn from_vm_export(store: &Store, vm_tag: VMTag) -> Self {
+        Self {
+            store: store.clone(),
+            vm_tag,
+        }
+    }
+
+    /// Returns whether or not these two tags are the same tag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Store, Tag, TagType};
+    /// # let store = Store::default();
+    /// #
+    /// let tag = Tag::new(&store, TagType::new([]));
+    /// let other = Tag::new(&store, TagType::new([]));
+    ///
+    /// assert!(tag.same(&tag));
+    /// assert!(!tag.same(&other));
+    /// ```
+    pub fn same(&self, other: &Self) -> bool {
+        self.vm_tag.same(&other.vm_tag)
+    }
+}
+
+impl Clone for Tag {
+    fn clone(&self) -> Self {
+        let mut vm_tag = self.vm_tag.clone();
+        vm_tag.upgrade_instance_ref().unwrap();
+
+        Self {
+            store: self.store.clone(),
+            vm_tag,
+        }
+    }
+}
+
+impl fmt::Debug for Tag {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("Tag")
+            .field("ty", &self.ty())
+            .finish()
+    }
+}
+
+impl<'a> Exportable<'a> for Tag {
+    fn to_export(&self) -> Export {
+        self.vm_tag.clone().into()
+    }
+
+    fn get_self_from_extern(_extern: &'a Extern) -> Result<&'a Self, ExportError> {
+        match _extern {
+            Extern::Tag(tag) => Ok(tag),
+            _ => Err(ExportError::IncompatibleType),
+        }
+    }
+
+    fn into_weak_instance_ref(&mut self) {
+        self.vm_tag
+            .instance_ref
+            .as_mut()
+            .map(|v| *v = v.downgrade());
+    }
+}
@@ -1,14 +1,14 @@
 use crate::sys::exports::Exports;
 use crate::sys::externals::Extern;
 use crate::sys::module::Module;
-use crate::sys::store::Store;
+use crate::sys::store::{InterruptHandle, Store};
 use crate::sys::{HostEnvInitError, LinkError, RuntimeError};
 use loupe::MemoryUsage;
 use std::fmt;
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
-use wasmer_engine::Resolver;
-use wasmer_vm::{InstanceHandle, VMContext};
+use wasmer_engine::{Export, Resolver};
+use wasmer_vm::{InstanceHandle, ResourceLimitError, VMContext};
 
 /// A WebAssembly Instance is a stateful, executable
 /// instance of a WebAssembly [`Module`].
@@ -38,6 +38,11 @@ mod send_test {
     fn instance_is_send() {
         assert!(is_send::<Instance>());
     }
+
+    #[test]
+    fn instance_pre_is_send() {
+        assert!(is_send::<InstancePre>());
+    }
 }
 
 /// An error while instantiating a module.
@@ -66,6 +71,11 @@ pub enum InstantiationError {
     /// Error occurred when initializing the host environment.
     #[error(transparent)]
     HostEnvInitialization(HostEnvInitError),
+
+    /// The resource limiter of the store denied the instance, or the
+    /// initial size of its memories or tables.
+    #[error("the resource limiter denied the instantiation: {0}")]
+    ResourceLimit(ResourceLimitError),
 }
 
 impl From<wasmer_engine::InstantiationError> for InstantiationError {
@@ -74,6 +84,7 @@ impl From<wasmer_engine::InstantiationError> for InstantiationError {
             wasmer_engine::InstantiationError::Link(e) => Self::Link(e),
             wasmer_engine::InstantiationError::Start(e) => Self::Start(e),
             wasmer_engine::InstantiationError::CpuFeature(e) => Self::CpuFeature(e),
+            wasmer_engine::InstantiationError::ResourceLimit(e) => Self::ResourceLimit(e),
         }
     }
 }
@@ -121,9 +132,19 @@ impl Instance {
     pub fn new(
         module: &Module,
         resolver: &(dyn Resolver + Send + Sync),
+    ) -> Result<Self, InstantiationError> {
+        let definitions = module
+            .resolve_imports(resolver)
+            .map_err(InstantiationError::Link)?;
+        Self::new_with_definitions(module, &definitions)
+    }
+
+    fn new_with_definitions(
+        module: &Module,
+        definitions: &[Export],
     ) -> Result<Self, InstantiationError> {
         let store = module.store();
-        let handle = module.instantiate(resolver)?;
+        let handle = module.instantiate(definitions)?;
         let exports = module
             .exports()
             .map(|export| {
@@ -169,12 +190,125 @@ impl Instance {
         self.module.store()
     }
 
+    /// Restores the instance to the state it had right after its
+    /// instantiation, which is cheaper than creating a new instance.
+    ///
+    /// The memories and tables defined by the instance shrink back to
+    /// their initial size and are initialized again from the data and
+    /// element segments of the module, and the globals it defines get
+    /// their initial values back. The start function of the module is
+    /// then invoked again. Imported memories, tables and globals are not
+    /// reset, and neither are the host environments of the imported
+    /// functions.
+    ///
+    /// On Linux, memories initialized copy-on-write from memory images
+    /// (see [`PoolingConfig::memory_init_cow`]) are reset by mapping the
+    /// images again, which discards the pages written by the instance.
+    ///
+    /// [`PoolingConfig::memory_init_cow`]: crate::PoolingConfig::memory_init_cow
+    ///
+    /// ```
+    /// # use wasmer::{imports, Instance, Module, Store, Value};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let store = Store::default();
+    /// let module = Module::new(&store, r#"
+    /// (module
+    ///   (global $counter (mut i32) (i32.const 0))
+    ///   (func (export "increment") (result i32)
+    ///     (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+    ///     (global.get $counter)))
+    /// "#)?;
+    /// let instance = Instance::new(&module, &imports! {})?;
+    /// let increment = instance.exports.get_function("increment")?;
+    ///
+    /// assert_eq!(increment.call(&[])?.to_vec(), vec![Value::I32(1)]);
+    /// assert_eq!(increment.call(&[])?.to_vec(), vec![Value::I32(2)]);
+    /// // Safety: no code of the instance is running and its memories and
+    /// // tables are not shared.
+    /// unsafe { instance.reset()? };
+    /// assert_eq!(increment.call(&[])?.to_vec(), vec![Value::I32(1)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if a memory or a table can't be reset, or if the
+    /// initialization or the start function traps. The instance must not
+    /// be used after an error.
+    ///
+    /// # Safety
+    ///
+    /// The memories and tables of the instance are shrunk and overwritten
+    /// in place, so:
+    ///
+    /// - the instance must not be executing, on any thread, including
+    ///   when `reset` is called from a host function it imports;
+    /// - the memories and tables it defines must not be in use by other
+    ///   instances or threads during the reset;
+    /// - the views of its memories and the pointers into them obtained
+    ///   before the reset must not be used after it.
+    pub unsafe fn reset(&self) -> Result<(), RuntimeError> {
+        let handle = self.handle.lock().unwrap();
+        self.module.reset_instance(&handle)
+    }
+
+    /// Returns a handle that can interrupt the WebAssembly code running
+    /// in this instance's [`Store`], from any thread.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.store().interrupt_handle()
+    }
+
     #[doc(hidden)]
     pub fn vmctx_ptr(&self) -> *mut VMContext {
         self.handle.lock().unwrap().vmctx_ptr()
     }
 }
 
+/// A [`Module`] whose imports are already resolved and type-checked,
+/// ready to be instantiated many times.
+///
+/// It is created with [`Module::pre_instantiate`]. Each instance gets its
+/// own copy of the environments of the imported host functions, as with
+/// [`Instance::new`], but the imports are not looked up again.
+#[derive(Clone)]
+pub struct InstancePre {
+    module: Module,
+    definitions: Arc<[Export]>,
+}
+
+impl InstancePre {
+    pub(crate) fn new(module: Module, definitions: Vec<Export>) -> Self {
+        Self {
+            module,
+            definitions: definitions.into(),
+        }
+    }
+
+    /// Creates a new `Instance` of the module with the resolved imports.
+    ///
+    /// ## Errors
+    ///
+    /// The imports can't fail to link anymore, but the instantiation can
+    /// still fail because the `start` function traps, for example.
+    pub fn instantiate(&self) -> Result<Instance, InstantiationError> {
+        Instance::new_with_definitions(&self.module, &self.definitions)
+    }
+
+    /// Gets the [`Module`] that is instantiated.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+}
+
+impl fmt::Debug for InstancePre {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InstancePre")
+            .field("module", &self.module)
+            .finish()
+    }
+}
+
 impl fmt::Debug for Instance {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Instance")
@@ -0,0 +1,192 @@
+use crate::sys::Pages;
+use std::sync::atomic::{AtomicU64, Ordering};
+use wasmer_vm::{ResourceLimitError, ResourceLimiter};
+
+/// A [`ResourceLimiter`] that limits the total number of memory pages,
+/// table elements and instances used by all the instances of a store.
+///
+/// # Usage
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use wasmer::{Pages, Store, StoreLimits};
+/// # let store = Store::default();
+/// let mut limits = StoreLimits::new();
+/// limits.memory_pages(Pages(160)).instances(10);
+/// let limits = Arc::new(limits);
+///
+/// store.set_resource_limiter(Some(limits.clone()));
+/// assert_eq!(limits.memory_pages_used(), 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct StoreLimits {
+    memory_pages: Option<u64>,
+    table_elements: Option<u64>,
+    instances: Option<u64>,
+    trap_on_limit: bool,
+    memory_pages_used: AtomicU64,
+    table_elements_used: AtomicU64,
+    instances_used: AtomicU64,
+}
+
+impl StoreLimits {
+    /// Creates limits that don't limit anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the total number of pages of the memories of the instances.
+    pub fn memory_pages(&mut self, limit: Pages) -> &mut Self {
+        self.memory_pages = Some(limit.0.into());
+        self
+    }
+
+    /// Limits the total number of elements of the tables of the instances.
+    pub fn table_elements(&mut self, limit: u32) -> &mut Self {
+        self.table_elements = Some(limit.into());
+        self
+    }
+
+    /// Limits the number of instances.
+    pub fn instances(&mut self, limit: u32) -> &mut Self {
+        self.instances = Some(limit.into());
+        self
+    }
+
+    /// Makes `memory.grow` and `table.grow` trap when a limit is exceeded,
+    /// instead of returning `-1`.
+    ///
+    /// This is disabled by default.
+    pub fn trap_on_limit(&mut self, enable: bool) -> &mut Self {
+        self.trap_on_limit = enable;
+        self
+    }
+
+    /// Returns the total number of memory pages in use.
+    pub fn memory_pages_used(&self) -> u64 {
+        self.memory_pages_used.load(Ordering::SeqCst)
+    }
+
+    /// Returns the total number of table elements in use.
+    pub fn table_elements_used(&self) -> u64 {
+        self.table_elements_used.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of instances alive.
+    pub fn instances_used(&self) -> u64 {
+        self.instances_used.load(Ordering::SeqCst)
+    }
+
+    fn acquire(
+        &self,
+        used: &AtomicU64,
+        amount: u64,
+        limit: Option<u64>,
+        resource: &str,
+    ) -> Result<(), ResourceLimitError> {
+        used.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+            used.checked_add(amount)
+                .filter(|&total| limit.map_or(true, |limit| total <= limit))
+        })
+        .map(|_| ())
+        .map_err(|_| {
+            if self.trap_on_limit {
+                ResourceLimitError::Trap(format!(
+                    "the limit of {} {} is exceeded",
+                    limit.unwrap_or(u64::MAX),
+                    resource
+                ))
+            } else {
+                ResourceLimitError::Fail
+            }
+        })
+    }
+
+    fn release(used: &AtomicU64, amount: u64) {
+        let _ = used.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+            Some(used.saturating_sub(amount))
+        });
+    }
+}
+
+impl ResourceLimiter for StoreLimits {
+    fn memory_growing(
+        &self,
+        current: Pages,
+        desired: Pages,
+        _maximum: Option<Pages>,
+    ) -> Result<(), ResourceLimitError> {
+        self.acquire(
+            &self.memory_pages_used,
+            (desired.0 - current.0).into(),
+            self.memory_pages,
+            "memory pages",
+        )
+    }
+
+    fn table_growing(
+        &self,
+        current: u32,
+        desired: u32,
+        _maximum: Option<u32>,
+    ) -> Result<(), ResourceLimitError> {
+        self.acquire(
+            &self.table_elements_used,
+            (desired - current).into(),
+            self.table_elements,
+            "table elements",
+        )
+    }
+
+    fn instance_creating(&self) -> Result<(), ResourceLimitError> {
+        self.acquire(&self.instances_used, 1, self.instances, "instances")
+    }
+
+    fn memory_released(&self, pages: Pages) {
+        Self::release(&self.memory_pages_used, pages.0.into());
+    }
+
+    fn table_released(&self, elements: u32) {
+        Self::release(&self.table_elements_used, elements.into());
+    }
+
+    fn instance_released(&self) {
+        Self::release(&self.instances_used, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_usage() {
+        let mut limits = StoreLimits::new();
+        limits.memory_pages(Pages(10));
+
+        assert_eq!(limits.memory_growing(Pages(0), Pages(6), None), Ok(()));
+        assert_eq!(
+            limits.memory_growing(Pages(0), Pages(5), None),
+            Err(ResourceLimitError::Fail)
+        );
+        assert_eq!(limits.memory_growing(Pages(2), Pages(6), None), Ok(()));
+        assert_eq!(limits.memory_pages_used(), 10);
+
+        limits.memory_released(Pages(6));
+        assert_eq!(limits.memory_pages_used(), 4);
+        assert_eq!(limits.memory_growing(Pages(0), Pages(6), None), Ok(()));
+
+        limits.trap_on_limit(true);
+        assert_eq!(
+            limits.memory_growing(Pages(6), Pages(7), None),
+            Err(ResourceLimitError::Trap(
+                "the limit of 10 memory pages is exceeded".to_string()
+            ))
+        );
+
+        // Tables and instances are not limited.
+        assert_eq!(limits.table_growing(0, u32::MAX, None), Ok(()));
+        assert_eq!(limits.instance_creating(), Ok(()));
+        assert_eq!(limits.instances_used(), 1);
+    }
+}
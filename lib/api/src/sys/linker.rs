@@ -0,0 +1,221 @@
+//! The linker module contains the [`Linker`], which links the imports of
+//! modules with host definitions and with the exports of other instances,
+//! by module and field name.
+use crate::sys::exports::{Exportable, Exports};
+use crate::sys::externals::Extern;
+use crate::sys::instance::{Instance, InstancePre, InstantiationError};
+use crate::sys::module::Module;
+use crate::sys::types::{ExternType, ImportType};
+use indexmap::IndexMap;
+use thiserror::Error;
+use wasmer_engine::{Export, NamedResolver};
+
+/// An error while defining items in a [`Linker`] or while instantiating
+/// a module with it.
+#[derive(Error, Debug)]
+pub enum LinkerError {
+    /// An item is already defined with this module and field name, and
+    /// shadowing is not allowed.
+    #[error("{0:?}.{1:?} is already defined")]
+    AlreadyDefined(String, String),
+
+    /// Some imports of the module are not defined in the linker.
+    #[error("missing imports: {}", format_imports(.0))]
+    MissingImports(Vec<ImportType<ExternType>>),
+
+    /// The instantiation failed.
+    #[error(transparent)]
+    Instantiation(#[from] InstantiationError),
+}
+
+fn format_imports(imports: &[ImportType<ExternType>]) -> String {
+    imports
+        .iter()
+        .map(|import| format!("{:?}.{:?}", import.module(), import.name()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A `Linker` resolves the imports of modules by name, with host
+/// definitions and with the exports of instances registered under a
+/// module name.
+///
+/// Unlike [`ImportObject`], a `Linker` reports all the missing imports of
+/// a module at once, and refuses to redefine an item unless shadowing is
+/// allowed with [`Linker::allow_shadowing`].
+///
+/// [`ImportObject`]: crate::ImportObject
+///
+/// # Usage
+///
+/// ```
+/// # use wasmer::{Function, Linker, Module, Store};
+/// # fn main() -> anyhow::Result<()> {
+/// # let store = Store::default();
+/// let mut linker = Linker::new();
+/// linker.define("host", "double", Function::new_native(&store, |n: i32| n * 2))?;
+///
+/// let library = Module::new(&store, r#"
+/// (module
+///   (import "host" "double" (func $double (param i32) (result i32)))
+///   (func (export "quadruple") (param i32) (result i32)
+///     (call $double (call $double (local.get 0)))))
+/// "#)?;
+/// linker.module("library", &library)?;
+///
+/// let main = Module::new(&store, r#"
+/// (module
+///   (import "library" "quadruple" (func $quadruple (param i32) (result i32)))
+///   (func (export "run") (result i32)
+///     (call $quadruple (i32.const 3))))
+/// "#)?;
+/// let instance = linker.instantiate(&main)?;
+///
+/// let run = instance.exports.get_native_function::<(), i32>("run")?;
+/// assert_eq!(run.call()?, 12);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Linker {
+    namespaces: IndexMap<String, Exports>,
+    allow_shadowing: bool,
+}
+
+impl Linker {
+    /// Creates a new, empty `Linker`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Allows items to be redefined, the last definition replacing the
+    /// previous one.
+    ///
+    /// This is disabled by default.
+    pub fn allow_shadowing(&mut self, allow: bool) -> &mut Self {
+        self.allow_shadowing = allow;
+        self
+    }
+
+    /// Defines an item with the given module and field name.
+    pub fn define<E>(&mut self, module: &str, name: &str, item: E) -> Result<&mut Self, LinkerError>
+    where
+        E: Into<Extern>,
+    {
+        self.check_shadowing(module, name)?;
+        self.namespace(module).insert(name, item);
+        Ok(self)
+    }
+
+    /// Defines all the items of `exports` under the given module name.
+    ///
+    /// Nothing is defined if one of the items is already defined and
+    /// shadowing is not allowed.
+    pub fn define_exports(
+        &mut self,
+        module: &str,
+        exports: &Exports,
+    ) -> Result<&mut Self, LinkerError> {
+        for (name, _) in exports.iter() {
+            self.check_shadowing(module, name)?;
+        }
+        let namespace = self.namespace(module);
+        for (name, extern_) in exports.iter() {
+            namespace.insert(name.clone(), extern_.clone());
+        }
+        Ok(self)
+    }
+
+    /// Defines all the exports of `instance` under the given module name.
+    pub fn instance(
+        &mut self,
+        module: &str,
+        instance: &Instance,
+    ) -> Result<&mut Self, LinkerError> {
+        self.define_exports(module, &instance.exports)
+    }
+
+    /// Instantiates `module` with this linker, and defines the exports of
+    /// the new instance under the given module name.
+    pub fn module(&mut self, name: &str, module: &Module) -> Result<Instance, LinkerError> {
+        let instance = self.instantiate(module)?;
+        self.instance(name, &instance)?;
+        Ok(instance)
+    }
+
+    /// Gets the item defined with the given module and field name.
+    pub fn get(&self, module: &str, name: &str) -> Option<&Extern> {
+        self.namespaces.get(module)?.get_extern(name)
+    }
+
+    /// Returns the imports of `module` that are not defined in this linker.
+    pub fn missing_imports(&self, module: &Module) -> Vec<ImportType<ExternType>> {
+        module
+            .imports()
+            .filter(|import| self.get(import.module(), import.name()).is_none())
+            .collect()
+    }
+
+    /// Creates a new `Instance` of `module`, with its imports resolved by
+    /// this linker.
+    ///
+    /// ## Errors
+    ///
+    /// Returns a [`LinkerError::MissingImports`] listing all the imports
+    /// that are not defined, or the error of the instantiation.
+    pub fn instantiate(&self, module: &Module) -> Result<Instance, LinkerError> {
+        self.check_imports(module)?;
+        Ok(Instance::new(module, self)?)
+    }
+
+    /// Resolves the imports of `module` with this linker, so that it can
+    /// be instantiated many times with [`InstancePre::instantiate`].
+    ///
+    /// Later changes to the linker don't affect the returned `InstancePre`.
+    pub fn pre_instantiate(&self, module: &Module) -> Result<InstancePre, LinkerError> {
+        self.check_imports(module)?;
+        Ok(module.pre_instantiate(self)?)
+    }
+
+    fn check_imports(&self, module: &Module) -> Result<(), LinkerError> {
+        let missing_imports = self.missing_imports(module);
+        if !missing_imports.is_empty() {
+            return Err(LinkerError::MissingImports(missing_imports));
+        }
+        Ok(())
+    }
+
+    fn check_shadowing(&self, module: &str, name: &str) -> Result<(), LinkerError> {
+        if !self.allow_shadowing && self.get(module, name).is_some() {
+            return Err(LinkerError::AlreadyDefined(
+                module.to_string(),
+                name.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn namespace(&mut self, module: &str) -> &mut Exports {
+        self.namespaces.entry(module.to_string()).or_default()
+    }
+}
+
+impl NamedResolver for Linker {
+    fn resolve_by_name(&self, module: &str, name: &str) -> Option<Export> {
+        self.get(module, name).map(|extern_| extern_.to_export())
+    }
+}
+
+#[cfg(test)]
+mod send_test {
+    use super::*;
+
+    fn is_send<T: Send + Sync>() -> bool {
+        true
+    }
+
+    #[test]
+    fn linker_is_send() {
+        assert!(is_send::<Linker>());
+    }
+}
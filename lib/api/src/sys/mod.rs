@@ -4,6 +4,8 @@ mod exports;
 mod externals;
 mod import_object;
 mod instance;
+mod limits;
+mod linker;
 mod module;
 mod native;
 mod ptr;
@@ -30,25 +32,28 @@ pub use crate::sys::cell::WasmCell;
 pub use crate::sys::env::{HostEnvInitError, LazyInit, WasmerEnv};
 pub use crate::sys::exports::{ExportError, Exportable, Exports, ExportsIterator};
 pub use crate::sys::externals::{
-    Extern, FromToNativeWasmType, Function, Global, HostFunction, Memory, Table, WasmTypeList,
+    Extern, FromToNativeWasmType, Function, Global, HostFunction, Memory, Table, TableElementType,
+    Tag, TypedGlobal, TypedTable, WasmTypeList,
 };
 pub use crate::sys::import_object::{ImportObject, ImportObjectIterator, LikeNamespace};
-pub use crate::sys::instance::{Instance, InstantiationError};
+pub use crate::sys::instance::{Instance, InstancePre, InstantiationError};
+pub use crate::sys::limits::StoreLimits;
+pub use crate::sys::linker::{Linker, LinkerError};
 pub use crate::sys::module::Module;
 pub use crate::sys::native::NativeFunc;
-pub use crate::sys::ptr::{Array, Item, WasmPtr};
-pub use crate::sys::store::{Store, StoreObject};
-pub use crate::sys::tunables::BaseTunables;
+pub use crate::sys::ptr::{Array, Item, MemoryOffset, WasmPtr, WasmPtr64};
+pub use crate::sys::store::{InterruptHandle, Store, StoreObject};
+pub use crate::sys::tunables::{BaseTunables, PoolingTunables};
 pub use crate::sys::types::{
     ExportType, ExternType, FunctionType, GlobalType, ImportType, MemoryType, Mutability,
-    TableType, Val, ValType,
+    TableType, TagType, Val, ValType,
 };
 pub use crate::sys::types::{Val as Value, ValType as Type};
 pub use target_lexicon::{Architecture, CallingConvention, OperatingSystem, Triple, HOST};
 #[cfg(feature = "compiler")]
 pub use wasmer_compiler::{
     wasmparser, CompilerConfig, FunctionMiddleware, MiddlewareError, MiddlewareReaderState,
-    ModuleMiddleware,
+    ModuleMiddleware, ModuleRewriter,
 };
 pub use wasmer_compiler::{
     CompileError, CpuFeature, Features, ParseCpuFeatureError, Target, WasmError, WasmResult,
@@ -61,12 +66,16 @@ pub use wasmer_types::is_wasm;
 #[cfg(feature = "experimental-reference-types-extern-ref")]
 pub use wasmer_types::ExternRef;
 pub use wasmer_types::{
-    Atomically, Bytes, ExportIndex, GlobalInit, LocalFunctionIndex, MemoryView, Pages, ValueType,
-    WASM_MAX_PAGES, WASM_MIN_PAGES, WASM_PAGE_SIZE,
+    Atomically, Bytes, ExportIndex, FunctionIndex, GlobalInit, LocalFunctionIndex, MemoryView,
+    Pages, SignatureIndex, TrapCode, ValueType, WASM_MAX_PAGES, WASM_MAX_PAGES64, WASM_MIN_PAGES,
+    WASM_PAGE_SIZE,
 };
 
 // TODO: should those be moved into wasmer::vm as well?
-pub use wasmer_vm::{raise_user_trap, MemoryError};
+pub use wasmer_vm::{
+    raise_user_trap, MemoryAccessError, MemoryError, PoolingAllocator, PoolingConfig,
+    ResourceLimitError, ResourceLimiter, DEFAULT_STACK_SIZE,
+};
 pub mod vm {
     //! The `vm` module re-exports wasmer-vm types.
 
@@ -1,6 +1,6 @@
 use crate::sys::store::Store;
 use crate::sys::types::{ExportType, ImportType};
-use crate::sys::InstantiationError;
+use crate::sys::{InstancePre, InstantiationError, LinkError, RuntimeError};
 use loupe::MemoryUsage;
 use std::fmt;
 use std::io;
@@ -10,9 +10,11 @@ use thiserror::Error;
 use wasmer_compiler::CompileError;
 #[cfg(feature = "wat")]
 use wasmer_compiler::WasmError;
-use wasmer_engine::{Artifact, DeserializeError, Resolver, SerializeError};
+use wasmer_engine::{
+    resolve_import_definitions, Artifact, DeserializeError, Export, Resolver, SerializeError,
+};
 use wasmer_types::{ExportsIterator, ImportsIterator, ModuleInfo};
-use wasmer_vm::InstanceHandle;
+use wasmer_vm::{InstanceHandle, ModuleMemoryImages};
 
 #[derive(Error, Debug)]
 pub enum IoCompileError {
@@ -49,6 +51,10 @@ pub struct Module {
     // In the future, this code should be refactored to properly describe the
     // ownership of the code and its metadata.
     artifact: Arc<dyn Artifact>,
+    // The images used to initialize the memories of the instances
+    // copy-on-write, if the tunables of the store enable it.
+    #[loupe(skip)]
+    memory_images: Option<Arc<ModuleMemoryImages>>,
     store: Store,
 }
 
@@ -268,21 +274,79 @@ impl Module {
     }
 
     fn from_artifact(store: &Store, artifact: Arc<dyn Artifact>) -> Self {
+        // Modules that can't use images, or whose images can't be
+        // created, are initialized by copying their data segments.
+        let memory_images = if store.tunables().memory_init_cow() {
+            artifact.create_memory_images().ok().flatten().map(Arc::new)
+        } else {
+            None
+        };
         Self {
             store: store.clone(),
             artifact,
+            memory_images,
         }
     }
 
-    pub(crate) fn instantiate(
+    /// Resolves and type-checks the imports of this module once, so that
+    /// it can then be instantiated many times without resolving them again.
+    ///
+    /// This is useful when many instances of the same module are created
+    /// with the same imports, for example one instance per request.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// # let store = Store::default();
+    /// let module = Module::new(&store, "(module (global (export \"g\") (mut i32) (i32.const 0)))")?;
+    /// let instance_pre = module.pre_instantiate(&imports! {})?;
+    ///
+    /// let first = instance_pre.instantiate()?;
+    /// let second = instance_pre.instantiate()?;
+    /// first.exports.get_global("g")?.set(Value::I32(1))?;
+    /// assert_eq!(second.exports.get_global("g")?.get(), Value::I32(0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ## Errors
+    ///
+    /// Returns an [`InstantiationError::Link`] if an import is missing or
+    /// doesn't have the expected type.
+    pub fn pre_instantiate(
+        &self,
+        resolver: &(dyn Resolver + Send + Sync),
+    ) -> Result<InstancePre, InstantiationError> {
+        let definitions = self
+            .resolve_imports(resolver)
+            .map_err(InstantiationError::Link)?;
+        Ok(InstancePre::new(self.clone(), definitions))
+    }
+
+    pub(crate) fn resolve_imports(
         &self,
         resolver: &dyn Resolver,
+    ) -> Result<Vec<Export>, LinkError> {
+        resolve_import_definitions(
+            self.artifact.module_ref(),
+            resolver,
+            self.artifact.memory_styles(),
+        )
+    }
+
+    pub(crate) fn instantiate(
+        &self,
+        definitions: &[Export],
     ) -> Result<InstanceHandle, InstantiationError> {
         unsafe {
-            let instance_handle = self.artifact.instantiate(
+            let instance_handle = self.artifact.instantiate_with_definitions(
                 self.store.tunables(),
-                resolver,
+                definitions,
                 Box::new(self.clone()),
+                self.store.interrupts().clone(),
+                self.store.resource_limiter(),
             )?;
 
             // After the instance handle is created, we need to initialize
@@ -290,13 +354,29 @@ impl Module {
             // of this steps traps, we still need to keep the instance alive
             // as some of the Instance elements may have placed in other
             // instance tables.
-            self.artifact
-                .finish_instantiation(&self.store, &instance_handle)?;
+            self.artifact.finish_instantiation(
+                &self.store,
+                &instance_handle,
+                self.memory_images.as_deref(),
+            )?;
 
             Ok(instance_handle)
         }
     }
 
+    /// # Safety
+    ///
+    /// See [`Instance::reset`](crate::Instance::reset).
+    pub(crate) unsafe fn reset_instance(
+        &self,
+        handle: &InstanceHandle,
+    ) -> Result<(), RuntimeError> {
+        // `handle` was created by `Module::instantiate`, which initialized
+        // it with the same data and memory images.
+        self.artifact
+            .reset_instance(&self.store, handle, self.memory_images.as_deref())
+    }
+
     /// Returns the name of the current module.
     ///
     /// This name is normally set in the WebAssembly bytecode by some
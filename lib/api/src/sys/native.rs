@@ -9,7 +9,7 @@
 //! ```
 use std::marker::PhantomData;
 
-use crate::sys::externals::function::{DynamicFunction, VMDynamicFunction};
+use crate::sys::externals::function::{call_dynamic_async, DynamicFunction, VMDynamicFunction};
 use crate::sys::{FromToNativeWasmType, Function, RuntimeError, Store, WasmTypeList};
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use wasmer_engine::ExportFunction;
@@ -39,6 +39,15 @@ where
         }
     }
 
+    /// Returns a handle to this function whose calls run on a stack of
+    /// `stack_size` bytes, like [`Function::with_stack_size`].
+    pub fn with_stack_size(&self, stack_size: usize) -> Self {
+        Self {
+            store: self.store.with_stack_size(stack_size),
+            ..self.clone()
+        }
+    }
+
     pub(crate) fn is_host(&self) -> bool {
         self.exported.vm_function.instance_ref.is_none()
     }
@@ -220,6 +229,73 @@ macro_rules! impl_native_traits {
                 }
             }
 
+            /// Call the typed func asynchronously and return results.
+            ///
+            /// See [`Function::call_async`]. Like it, the returned future is
+            /// not `Send`, and runs on a Tokio `LocalSet` the same way.
+            pub async fn call_async(&self, $( $x: $x, )* ) -> Result<Rets, RuntimeError> {
+                if !self.is_host() {
+                    // We assume the trampoline is always going to be present for
+                    // Wasm functions
+                    let trampoline = self.exported.vm_function.call_trampoline.expect("Call trampoline not found in wasm function");
+                    let mut params_list = [ $( $x.to_native().to_binary() ),* ];
+                    let mut rets_list_array = Rets::empty_array();
+                    let rets_list = rets_list_array.as_mut();
+                    let using_rets_array;
+                    let args_rets: &mut [i128] = if params_list.len() > rets_list.len() {
+                        using_rets_array = false;
+                        params_list.as_mut()
+                    } else {
+                        using_rets_array = true;
+                        for (i, &arg) in params_list.iter().enumerate() {
+                            rets_list[i] = arg;
+                        }
+                        rets_list.as_mut()
+                    };
+                    unsafe {
+                        wasmer_vm::wasmer_call_trampoline_async(
+                            &self.store,
+                            self.vmctx(),
+                            trampoline,
+                            self.address(),
+                            args_rets.as_mut_ptr() as *mut u8,
+                        )
+                    }.await?;
+                    let num_rets = rets_list.len();
+                    if !using_rets_array && num_rets > 0 {
+                        let src_pointer = params_list.as_ptr();
+                        let rets_list = &mut rets_list_array.as_mut()[0] as *mut i128;
+                        unsafe {
+                            // we know it's not overlapping because `using_rets_array` is false
+                            std::ptr::copy_nonoverlapping(src_pointer,
+                                                          rets_list,
+                                                          num_rets);
+                        }
+                    }
+                    Ok(Rets::from_array(rets_list_array))
+                }
+                else {
+                    match self.arg_kind() {
+                        // Static host functions can't wait for a future.
+                        VMFunctionKind::Static => self.call($( $x, )*),
+                        VMFunctionKind::Dynamic => {
+                            let params_list = [ $( $x.to_native().to_value() ),* ];
+                            let results = unsafe {
+                                call_dynamic_async(&self.store, self.vmctx(), &params_list).await?
+                            };
+                            let mut rets_list_array = Rets::empty_array();
+                            let mut_rets = rets_list_array.as_mut() as *mut [i128] as *mut i128;
+                            for (i, ret) in results.iter().enumerate() {
+                                unsafe {
+                                    ret.write_value_to(mut_rets.add(i));
+                                }
+                            }
+                            Ok(Rets::from_array(rets_list_array))
+                        }
+                    }
+                }
+            }
+
         }
 
         #[allow(unused_parens)]
@@ -8,6 +8,7 @@
 
 use crate::sys::cell::WasmCell;
 use crate::sys::{externals::Memory, FromToNativeWasmType};
+use std::convert::TryFrom;
 use std::{cell::Cell, fmt, marker::PhantomData, mem};
 use wasmer_types::ValueType;
 
@@ -18,6 +19,44 @@ pub struct Array;
 /// specified.
 pub struct Item;
 
+mod private {
+    pub trait Sealed {}
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+}
+
+/// The type of the offset of a [`WasmPtr`]: `u32` for the 32-bit memories of
+/// the core specification, the default, and `u64` for the 64-bit memories of
+/// the memory64 proposal.
+///
+/// This trait is sealed and can't be implemented outside of this crate.
+pub trait MemoryOffset:
+    Copy + Eq + fmt::Display + fmt::LowerHex + FromToNativeWasmType + private::Sealed
+{
+    /// Converts the offset to a `usize`, if it fits.
+    fn to_usize(self) -> Option<usize>;
+    /// Converts a `usize` to an offset, if it fits.
+    fn from_usize(value: usize) -> Option<Self>;
+}
+
+impl MemoryOffset for u32 {
+    fn to_usize(self) -> Option<usize> {
+        usize::try_from(self).ok()
+    }
+    fn from_usize(value: usize) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
+}
+
+impl MemoryOffset for u64 {
+    fn to_usize(self) -> Option<usize> {
+        usize::try_from(self).ok()
+    }
+    fn from_usize(value: usize) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
+}
+
 /// A zero-cost type that represents a pointer to something in Wasm linear
 /// memory.
 ///
@@ -62,17 +101,36 @@ pub struct Item;
 ///     derefed_ptr.set(inner_val);
 /// }
 /// ```
+///
+/// The offset is an `u32` by default. Pointers into 64-bit memories, as
+/// defined by the memory64 proposal, have an `u64` offset and are passed to
+/// and from Wasm as an `i64`, see [`WasmPtr64`].
 #[repr(transparent)]
-pub struct WasmPtr<T: Copy, Ty = Item> {
-    offset: u32,
+pub struct WasmPtr<T: Copy, Ty = Item, O = u32> {
+    offset: O,
     _phantom: PhantomData<(T, Ty)>,
 }
 
+/// A [`WasmPtr`] into a 64-bit Wasm linear memory, as defined by the
+/// memory64 proposal.
+///
+/// ```
+/// # use wasmer::Memory;
+/// # use wasmer::WasmPtr64;
+/// pub fn host_import(memory: Memory, ptr: WasmPtr64<u32>) {
+///     let derefed_ptr = ptr.deref(&memory).expect("pointer in bounds");
+///     let inner_val: u32 = derefed_ptr.get();
+///     println!("Got {} from Wasm memory address 0x{:X}", inner_val, ptr.offset());
+///     derefed_ptr.set(inner_val + 1);
+/// }
+/// ```
+pub type WasmPtr64<T, Ty = Item> = WasmPtr<T, Ty, u64>;
+
 /// Methods relevant to all types of `WasmPtr`.
-impl<T: Copy, Ty> WasmPtr<T, Ty> {
+impl<T: Copy, Ty, O: MemoryOffset> WasmPtr<T, Ty, O> {
     /// Create a new `WasmPtr` at the given offset.
     #[inline]
-    pub fn new(offset: u32) -> Self {
+    pub fn new(offset: O) -> Self {
         Self {
             offset,
             _phantom: PhantomData,
@@ -81,9 +139,20 @@ impl<T: Copy, Ty> WasmPtr<T, Ty> {
 
     /// Get the offset into Wasm linear memory for this `WasmPtr`.
     #[inline]
-    pub fn offset(self) -> u32 {
+    pub fn offset(self) -> O {
         self.offset
     }
+
+    /// Returns a pointer to the `len` bytes at the offset of this pointer,
+    /// if they are in the bounds of `memory`.
+    fn host_ptr(self, memory: &Memory, len: usize) -> Option<*mut u8> {
+        let offset = self.offset.to_usize()?;
+        let end = offset.checked_add(len)?;
+        if end > memory.size().bytes().0 {
+            return None;
+        }
+        Some(unsafe { memory.data_ptr().add(offset) })
+    }
 }
 
 #[inline(always)]
@@ -96,7 +165,7 @@ fn align_pointer(ptr: usize, align: usize) -> usize {
 /// Methods for `WasmPtr`s to data that can be dereferenced, namely to types
 /// that implement [`ValueType`], meaning that they're valid for all possible
 /// bit patterns.
-impl<T: Copy + ValueType> WasmPtr<T, Item> {
+impl<T: Copy + ValueType, O: MemoryOffset> WasmPtr<T, Item, O> {
     /// Dereference the `WasmPtr` getting access to a `&Cell<T>` allowing for
     /// reading and mutating of the inner value.
     ///
@@ -105,25 +174,19 @@ impl<T: Copy + ValueType> WasmPtr<T, Item> {
     /// This invariant will be enforced in the future.
     #[inline]
     pub fn deref<'a>(self, memory: &'a Memory) -> Option<WasmCell<'a, T>> {
-        let end = (self.offset as usize).checked_add(mem::size_of::<T>())?;
-        if end > memory.size().bytes().0 || mem::size_of::<T>() == 0 {
+        if mem::size_of::<T>() == 0 {
             return None;
         }
-
-        unsafe {
-            let cell_ptr = align_pointer(
-                memory.view::<u8>().as_ptr().add(self.offset as usize) as usize,
-                mem::align_of::<T>(),
-            ) as *const Cell<T>;
-            Some(WasmCell::new(&*cell_ptr))
-        }
+        let ptr = self.host_ptr(memory, mem::size_of::<T>())?;
+        let cell_ptr = align_pointer(ptr as usize, mem::align_of::<T>()) as *const Cell<T>;
+        Some(WasmCell::new(unsafe { &*cell_ptr }))
     }
 }
 
 /// Methods for `WasmPtr`s to arrays of data that can be dereferenced, namely to
 /// types that implement [`ValueType`], meaning that they're valid for all
 /// possible bit patterns.
-impl<T: Copy + ValueType> WasmPtr<T, Array> {
+impl<T: Copy + ValueType, O: MemoryOffset> WasmPtr<T, Array, O> {
     /// Dereference the `WasmPtr` getting access to a `&[Cell<T>]` allowing for
     /// reading and mutating of the inner values.
     ///
@@ -134,25 +197,22 @@ impl<T: Copy + ValueType> WasmPtr<T, Array> {
     pub fn deref<'a>(
         self,
         memory: &'a Memory,
-        index: u32,
-        length: u32,
+        index: O,
+        length: O,
     ) -> Option<Vec<WasmCell<'a, T>>> {
         // gets the size of the item in the array with padding added such that
         // for any index, we will always result an aligned memory access
         let item_size = mem::size_of::<T>();
-        let slice_full_len = (index as usize).checked_add(length as usize)?;
-        let memory_size = memory.size().bytes().0;
-        let end = (self.offset as usize).checked_add(item_size.checked_mul(slice_full_len)?)?;
-        if end > memory_size || item_size == 0 {
+        if item_size == 0 {
             return None;
         }
+        let index = index.to_usize()?;
+        let slice_full_len = index.checked_add(length.to_usize()?)?;
+        let ptr = self.host_ptr(memory, item_size.checked_mul(slice_full_len)?)?;
 
         let cell_ptrs = unsafe {
-            let cell_ptr = align_pointer(
-                memory.view::<u8>().as_ptr().add(self.offset as usize) as usize,
-                mem::align_of::<T>(),
-            ) as *const Cell<T>;
-            &std::slice::from_raw_parts(cell_ptr, slice_full_len)[index as usize..slice_full_len]
+            let cell_ptr = align_pointer(ptr as usize, mem::align_of::<T>()) as *const Cell<T>;
+            &std::slice::from_raw_parts(cell_ptr, slice_full_len)[index..slice_full_len]
         };
 
         let wasm_cells = cell_ptrs
@@ -179,33 +239,27 @@ impl<T: Copy + ValueType> WasmPtr<T, Array> {
     ///
     /// Additionally, if `memory` is dynamic, the caller must also ensure that `memory`
     /// is not grown while the reference is held.
-    pub unsafe fn get_utf8_str<'a>(self, memory: &'a Memory, str_len: u32) -> Option<&'a str> {
-        let end = self.offset.checked_add(str_len)?;
-        if end as usize > memory.size().bytes().0 {
-            return None;
-        }
-
-        let ptr = memory.view::<u8>().as_ptr().add(self.offset as usize) as *const u8;
-        let slice: &[u8] = std::slice::from_raw_parts(ptr, str_len as usize);
+    pub unsafe fn get_utf8_str<'a>(self, memory: &'a Memory, str_len: O) -> Option<&'a str> {
+        let str_len = str_len.to_usize()?;
+        let ptr = self.host_ptr(memory, str_len)?;
+        let slice: &[u8] = std::slice::from_raw_parts(ptr, str_len);
         std::str::from_utf8(slice).ok()
     }
 
     /// Get a UTF-8 `String` from the `WasmPtr` with the given length.
     ///
     /// an aliasing `WasmPtr` is used to mutate memory.
-    pub fn get_utf8_string(self, memory: &Memory, str_len: u32) -> Option<String> {
-        let end = self.offset.checked_add(str_len)?;
-        if end as usize > memory.size().bytes().0 {
-            return None;
-        }
+    pub fn get_utf8_string(self, memory: &Memory, str_len: O) -> Option<String> {
+        let str_len = str_len.to_usize()?;
+        self.host_ptr(memory, str_len)?;
 
         // TODO: benchmark the internals of this function: there is likely room for
         // micro-optimization here and this may be a fairly common function in user code.
         let view = memory.view::<u8>();
 
-        let mut vec: Vec<u8> = Vec::with_capacity(str_len as usize);
-        let base = self.offset as usize;
-        for i in 0..(str_len as usize) {
+        let mut vec: Vec<u8> = Vec::with_capacity(str_len);
+        let base = self.offset.to_usize()?;
+        for i in 0..str_len {
             let byte = view[base + i].get();
             vec.push(byte);
         }
@@ -222,11 +276,11 @@ impl<T: Copy + ValueType> WasmPtr<T, Array> {
     /// This method behaves similarly to [`WasmPtr::get_utf8_str`], all safety invariants on
     /// that method must also be upheld here.
     pub unsafe fn get_utf8_str_with_nul<'a>(self, memory: &'a Memory) -> Option<&'a str> {
-        memory.view::<u8>()[(self.offset as usize)..]
+        memory.view::<u8>()[self.offset.to_usize()?..]
             .iter()
             .map(|cell| cell.get())
             .position(|byte| byte == 0)
-            .and_then(|length| self.get_utf8_str(memory, length as u32))
+            .and_then(|length| self.get_utf8_str(memory, O::from_usize(length)?))
     }
 
     /// Get a UTF-8 `String` from the `WasmPtr`, where the string is nul-terminated.
@@ -238,23 +292,29 @@ impl<T: Copy + ValueType> WasmPtr<T, Array> {
     }
 }
 
-unsafe impl<T: Copy, Ty> FromToNativeWasmType for WasmPtr<T, Ty> {
-    type Native = i32;
+impl<T: Copy, Ty> From<WasmPtr<T, Ty>> for WasmPtr64<T, Ty> {
+    fn from(ptr: WasmPtr<T, Ty>) -> Self {
+        Self::new(ptr.offset().into())
+    }
+}
+
+unsafe impl<T: Copy, Ty, O: MemoryOffset> FromToNativeWasmType for WasmPtr<T, Ty, O> {
+    type Native = O::Native;
 
     fn to_native(self) -> Self::Native {
-        self.offset as i32
+        self.offset.to_native()
     }
     fn from_native(n: Self::Native) -> Self {
         Self {
-            offset: n as u32,
+            offset: O::from_native(n),
             _phantom: PhantomData,
         }
     }
 }
 
-unsafe impl<T: Copy, Ty> ValueType for WasmPtr<T, Ty> {}
+unsafe impl<T: Copy, Ty, O: MemoryOffset> ValueType for WasmPtr<T, Ty, O> {}
 
-impl<T: Copy, Ty> Clone for WasmPtr<T, Ty> {
+impl<T: Copy, Ty, O: MemoryOffset> Clone for WasmPtr<T, Ty, O> {
     fn clone(&self) -> Self {
         Self {
             offset: self.offset,
@@ -263,17 +323,17 @@ impl<T: Copy, Ty> Clone for WasmPtr<T, Ty> {
     }
 }
 
-impl<T: Copy, Ty> Copy for WasmPtr<T, Ty> {}
+impl<T: Copy, Ty, O: MemoryOffset> Copy for WasmPtr<T, Ty, O> {}
 
-impl<T: Copy, Ty> PartialEq for WasmPtr<T, Ty> {
+impl<T: Copy, Ty, O: MemoryOffset> PartialEq for WasmPtr<T, Ty, O> {
     fn eq(&self, other: &Self) -> bool {
         self.offset == other.offset
     }
 }
 
-impl<T: Copy, Ty> Eq for WasmPtr<T, Ty> {}
+impl<T: Copy, Ty, O: MemoryOffset> Eq for WasmPtr<T, Ty, O> {}
 
-impl<T: Copy, Ty> fmt::Debug for WasmPtr<T, Ty> {
+impl<T: Copy, Ty, O: MemoryOffset> fmt::Debug for WasmPtr<T, Ty, O> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
@@ -362,4 +422,37 @@ mod test {
             assert!(oob_end_array_ptr.deref(&memory, 1, 0).is_none());
         }
     }
+
+    /// Ensure that `WasmPtr64` accesses are bounds checked, including the
+    /// offsets that don't fit in 32 bits.
+    #[test]
+    fn wasm_ptr64_memory_bounds_checks_hold() {
+        let store = Store::default();
+        let memory = Memory::new(&store, MemoryType::new64(1, Some(1), false)).unwrap();
+        let memory_size = memory.size().bytes().0 as u64;
+
+        let ptr: WasmPtr64<u32> = WasmPtr64::new(memory_size - 4);
+        ptr.deref(&memory).unwrap().set(42);
+        assert_eq!(
+            WasmPtr64::<u32>::new(memory_size - 4)
+                .deref(&memory)
+                .unwrap()
+                .get(),
+            42
+        );
+        assert!(WasmPtr64::<u32>::new(memory_size - 3)
+            .deref(&memory)
+            .is_none());
+        assert!(WasmPtr64::<u8>::new(1 << 32).deref(&memory).is_none());
+        assert!(WasmPtr64::<u8>::new(u64::MAX).deref(&memory).is_none());
+
+        let array: WasmPtr64<u8, Array> = WasmPtr64::new(memory_size - 2);
+        assert_eq!(array.deref(&memory, 0, 2).unwrap().len(), 2);
+        assert!(array.deref(&memory, 1, 2).is_none());
+        assert!(array.deref(&memory, u64::MAX, 1).is_none());
+        assert!(array.get_utf8_string(&memory, 3).is_none());
+
+        let ptr: WasmPtr64<u8, Array> = WasmPtr::<u8, Array>::new(8).into();
+        assert_eq!(ptr.offset(), 8);
+    }
 }
@@ -1,11 +1,14 @@
 use crate::sys::tunables::BaseTunables;
 use loupe::MemoryUsage;
 use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 #[cfg(all(feature = "compiler", feature = "engine"))]
 use wasmer_compiler::CompilerConfig;
 use wasmer_engine::{Engine, Tunables};
-use wasmer_vm::{init_traps, TrapHandler, TrapHandlerFn};
+use wasmer_vm::{
+    init_traps, ResourceLimiter, TrapHandler, TrapHandlerFn, VMInterrupts, DEFAULT_STACK_SIZE,
+};
 
 /// The store represents all global state that can be manipulated by
 /// WebAssembly programs. It consists of the runtime representation
@@ -23,6 +26,14 @@ pub struct Store {
     tunables: Arc<dyn Tunables + Send + Sync>,
     #[loupe(skip)]
     trap_handler: Arc<RwLock<Option<Box<TrapHandlerFn>>>>,
+    interrupts: Arc<VMInterrupts>,
+    #[loupe(skip)]
+    limiter: Arc<RwLock<Option<Arc<dyn ResourceLimiter>>>>,
+    #[loupe(skip)]
+    stack_size: Arc<AtomicUsize>,
+    /// The stack size of the calls made through this handle of the store,
+    /// overriding `stack_size`.
+    call_stack_size: Option<usize>,
 }
 
 impl Store {
@@ -40,6 +51,49 @@ impl Store {
         *m = handler;
     }
 
+    /// Set the resource limiter of this store, see [`ResourceLimiter`].
+    ///
+    /// The limiter applies to the instances created after this call.
+    pub fn set_resource_limiter(&self, limiter: Option<Arc<dyn ResourceLimiter>>) {
+        let mut m = self.limiter.write().unwrap();
+        *m = limiter;
+    }
+
+    /// Sets the size in bytes of the stack that WebAssembly code runs on,
+    /// which is [`DEFAULT_STACK_SIZE`] by default.
+    ///
+    /// Every call from the host into WebAssembly runs on a separately
+    /// allocated stack of this size, followed by a guard page: a guest that
+    /// recurses too deeply traps with [`TrapCode::StackOverflow`] after the
+    /// same number of calls, whichever host thread calls it. The size
+    /// applies to the calls made after this call.
+    ///
+    /// The size of the stack of a single call can be set with
+    /// [`Function::with_stack_size`] or [`NativeFunc::with_stack_size`].
+    ///
+    /// [`TrapCode::StackOverflow`]: crate::TrapCode::StackOverflow
+    /// [`Function::with_stack_size`]: crate::Function::with_stack_size
+    /// [`NativeFunc::with_stack_size`]: crate::NativeFunc::with_stack_size
+    pub fn set_stack_size(&self, stack_size: usize) {
+        self.stack_size.store(stack_size, Ordering::Relaxed);
+    }
+
+    /// Returns the size in bytes of the stack that WebAssembly code called
+    /// through this `Store` runs on.
+    pub fn stack_size(&self) -> usize {
+        self.call_stack_size
+            .unwrap_or_else(|| self.stack_size.load(Ordering::Relaxed))
+    }
+
+    /// Returns a handle to this store whose calls into WebAssembly run on
+    /// a stack of `stack_size` bytes.
+    pub(crate) fn with_stack_size(&self, stack_size: usize) -> Self {
+        Self {
+            call_stack_size: Some(stack_size),
+            ..self.clone()
+        }
+    }
+
     /// Creates a new `Store` with a specific [`Engine`] and [`Tunables`].
     pub fn new_with_tunables<E>(engine: &E, tunables: impl Tunables + Send + Sync + 'static) -> Self
     where
@@ -53,6 +107,10 @@ impl Store {
             engine: engine.cloned(),
             tunables: Arc::new(tunables),
             trap_handler: Arc::new(RwLock::new(None)),
+            interrupts: Arc::new(VMInterrupts::new()),
+            limiter: Arc::new(RwLock::new(None)),
+            stack_size: Arc::new(AtomicUsize::new(DEFAULT_STACK_SIZE)),
+            call_stack_size: None,
         }
     }
 
@@ -66,6 +124,40 @@ impl Store {
         &self.engine
     }
 
+    /// Returns a handle that can interrupt the WebAssembly code running
+    /// in this store, from any thread.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle {
+            interrupts: self.interrupts.clone(),
+        }
+    }
+
+    /// Makes the WebAssembly code running in this store trap with
+    /// [`TrapCode::Interrupt`] once the epoch has been incremented `ticks`
+    /// times with [`InterruptHandle::increment_epoch`].
+    ///
+    /// The deadline stays in place after it is reached: the calls into
+    /// WebAssembly trap until a new deadline is set.
+    ///
+    /// [`TrapCode::Interrupt`]: crate::TrapCode::Interrupt
+    pub fn set_epoch_deadline(&self, ticks: u64) {
+        self.interrupts.set_epoch_deadline(ticks);
+    }
+
+    /// Removes the epoch deadline of this store, and clears the effect of
+    /// [`InterruptHandle::interrupt`].
+    pub fn clear_epoch_deadline(&self) {
+        self.interrupts.clear_epoch_deadline();
+    }
+
+    pub(crate) fn interrupts(&self) -> &Arc<VMInterrupts> {
+        &self.interrupts
+    }
+
+    pub(crate) fn resource_limiter(&self) -> Option<Arc<dyn ResourceLimiter>> {
+        self.limiter.read().unwrap().clone()
+    }
+
     /// Checks whether two stores are identical. A store is considered
     /// equal to another store if both have the same engine. The
     /// tunables are excluded from the logic.
@@ -88,6 +180,10 @@ unsafe impl TrapHandler for Store {
             false
         }
     }
+
+    fn stack_size(&self) -> usize {
+        Self::stack_size(self)
+    }
 }
 
 // This is required to be able to set the trap_handler in the
@@ -145,6 +241,40 @@ impl fmt::Debug for Store {
     }
 }
 
+/// A handle to interrupt the WebAssembly code running in a [`Store`],
+/// which can be sent to other threads.
+///
+/// WebAssembly code checks for interruptions when it enters a function
+/// and at the start of every loop iteration, if it was compiled with
+/// [`CompilerConfig::interruptible`] enabled. Other code runs to
+/// completion. Threads parked in `memory.atomic.wait` are always
+/// interrupted, within a few milliseconds.
+///
+/// [`CompilerConfig::interruptible`]: wasmer_compiler::CompilerConfig::interruptible
+#[derive(Clone, Debug)]
+pub struct InterruptHandle {
+    interrupts: Arc<VMInterrupts>,
+}
+
+impl InterruptHandle {
+    /// Makes the WebAssembly code running in the store trap with
+    /// [`TrapCode::Interrupt`] as soon as possible.
+    ///
+    /// If no code is running, the next call into WebAssembly traps. The
+    /// store stays interrupted until [`Store::clear_epoch_deadline`] or
+    /// [`Store::set_epoch_deadline`] is called.
+    ///
+    /// [`TrapCode::Interrupt`]: crate::TrapCode::Interrupt
+    pub fn interrupt(&self) {
+        self.interrupts.interrupt();
+    }
+
+    /// Increments the epoch of the store, see [`Store::set_epoch_deadline`].
+    pub fn increment_epoch(&self) {
+        self.interrupts.increment_epoch();
+    }
+}
+
 /// A trait represinting any object that lives in the `Store`.
 pub trait StoreObject {
     /// Return true if the object `Store` is the same as the provided `Store`.
@@ -1,14 +1,15 @@
-use crate::sys::{MemoryType, Pages, TableType};
+use crate::sys::{LinkError, MemoryType, Pages, TableType};
 use loupe::MemoryUsage;
 use std::ptr::NonNull;
 use std::sync::Arc;
 use target_lexicon::PointerWidth;
 use wasmer_compiler::Target;
 use wasmer_engine::Tunables;
+use wasmer_types::ModuleInfo;
 use wasmer_vm::MemoryError;
 use wasmer_vm::{
-    LinearMemory, LinearTable, Memory, MemoryStyle, Table, TableStyle, VMMemoryDefinition,
-    VMTableDefinition,
+    InstanceAllocator, LinearMemory, LinearTable, Memory, MemoryStyle, PoolingAllocator,
+    PoolingConfig, Table, TableStyle, VMMemoryDefinition, VMTableDefinition,
 };
 
 /// Tunable parameters for WebAssembly compilation.
@@ -74,8 +75,11 @@ impl Tunables for BaseTunables {
         // tunables make it static.
         //
         // If the module doesn't declare an explicit maximum treat it as 4GiB.
+        //
+        // The addresses of 64-bit memories can't be covered by guard pages,
+        // so they are always dynamic and bounds checked.
         let maximum = memory.maximum.unwrap_or_else(Pages::max_value);
-        if maximum <= self.static_memory_bound {
+        if maximum <= self.static_memory_bound && !memory.memory64 {
             MemoryStyle::Static {
                 // Bound can be larger than the maximum for performance reasons
                 bound: self.static_memory_bound,
@@ -148,6 +152,137 @@ impl Tunables for BaseTunables {
     }
 }
 
+/// Tunables that allocate the instances, and their memories and tables,
+/// in the slots of a [`PoolingAllocator`].
+///
+/// This makes instantiation cheaper when many instances are created and
+/// dropped, at the cost of limiting the number and the size of the
+/// instances that are alive at the same time. Memories and tables created
+/// by the host are not pooled.
+///
+/// # Usage
+///
+/// ```
+/// # use wasmer::{PoolingConfig, PoolingTunables, Store, Universal};
+/// # fn main() -> Result<(), String> {
+/// let mut config = PoolingConfig::default();
+/// config.max_instances = 10;
+/// let tunables = PoolingTunables::new(config)?;
+///
+/// let engine = Universal::headless().engine();
+/// let store = Store::new_with_tunables(&engine, tunables);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, MemoryUsage)]
+pub struct PoolingTunables {
+    #[loupe(skip)]
+    allocator: Arc<PoolingAllocator>,
+}
+
+impl PoolingTunables {
+    /// Creates tunables with a new [`PoolingAllocator`], which reserves the
+    /// slots described by `config`.
+    pub fn new(config: PoolingConfig) -> Result<Self, String> {
+        Ok(Self {
+            allocator: Arc::new(PoolingAllocator::new(config)?),
+        })
+    }
+
+    /// Returns the allocator of the instances.
+    pub fn allocator(&self) -> &PoolingAllocator {
+        &self.allocator
+    }
+}
+
+impl Tunables for PoolingTunables {
+    /// Get a `MemoryStyle` for the provided `MemoryType`, that fits in the
+    /// slots of the pool if the maximum of the memory does.
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        self.allocator.memory_style(memory)
+    }
+
+    /// Get a [`TableStyle`] for the provided [`TableType`].
+    fn table_style(&self, _table: &TableType) -> TableStyle {
+        TableStyle::CallerChecksSignature
+    }
+
+    /// Create a memory owned by the host given a [`MemoryType`] and a [`MemoryStyle`].
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+    ) -> Result<Arc<dyn Memory>, MemoryError> {
+        Ok(Arc::new(LinearMemory::new(ty, style)?))
+    }
+
+    /// Create a memory owned by the VM in a slot of the pool.
+    ///
+    /// # Safety
+    /// - `vm_definition_location` must point to a valid, owned `VMMemoryDefinition`,
+    ///   for example in `VMContext`.
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<Arc<dyn Memory>, MemoryError> {
+        Ok(Arc::new(self.allocator.create_vm_memory(
+            ty,
+            style,
+            vm_definition_location,
+        )?))
+    }
+
+    /// Create a table owned by the host given a [`TableType`] and a [`TableStyle`].
+    fn create_host_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+    ) -> Result<Arc<dyn Table>, String> {
+        Ok(Arc::new(LinearTable::new(ty, style)?))
+    }
+
+    /// Create a table owned by the VM in a slot of the pool.
+    ///
+    /// # Safety
+    /// - `vm_definition_location` must point to a valid, owned `VMTableDefinition`,
+    ///   for example in `VMContext`.
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<Arc<dyn Table>, String> {
+        Ok(Arc::new(self.allocator.create_vm_table(
+            ty,
+            style,
+            vm_definition_location,
+        )?))
+    }
+
+    /// Allocate the data of an instance in a slot of the pool.
+    fn allocate_instance(
+        &self,
+        module: &ModuleInfo,
+    ) -> Result<
+        (
+            InstanceAllocator,
+            Vec<NonNull<VMMemoryDefinition>>,
+            Vec<NonNull<VMTableDefinition>>,
+        ),
+        LinkError,
+    > {
+        self.allocator
+            .allocate_instance(module)
+            .map_err(|e| LinkError::Resource(format!("Failed to allocate instance: {}", e)))
+    }
+
+    fn memory_init_cow(&self) -> bool {
+        self.allocator.config().memory_init_cow
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,230 @@
+#[cfg(feature = "sys")]
+mod sys {
+    use anyhow::Result;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use wasmer::*;
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polls `future` once.
+    fn poll_once<F: Future + ?Sized>(future: Pin<&mut F>) -> Poll<F::Output> {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        future.poll(&mut Context::from_waker(&waker))
+    }
+
+    /// Polls `future` until it is ready, returning the number of polls
+    /// and its output.
+    fn run<F: Future>(future: F) -> (usize, F::Output) {
+        let mut future = Box::pin(future);
+        let mut polls = 1;
+        loop {
+            if let Poll::Ready(output) = poll_once(future.as_mut()) {
+                return (polls, output);
+            }
+            polls += 1;
+        }
+    }
+
+    /// A future that is pending `n` times before it is ready.
+    struct Yield {
+        n: usize,
+    }
+
+    impl Future for Yield {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.n == 0 {
+                return Poll::Ready(());
+            }
+            self.n -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    const WAT: &str = r#"
+    (module
+      (import "host" "sleep" (func $sleep (param i32) (result i32)))
+      (func (export "add") (param i32 i32) (result i32)
+        (i32.add (local.get 0) (local.get 1)))
+      (func (export "sleep_twice") (param i32) (result i32)
+        (i32.add
+          (call $sleep (local.get 0))
+          (call $sleep (local.get 0))))
+      (func (export "trap")
+        unreachable))
+    "#;
+
+    fn instance(store: &Store, sleeps: Arc<AtomicUsize>) -> Result<Instance> {
+        let module = Module::new(store, WAT)?;
+        let sleep = Function::new_async(
+            store,
+            FunctionType::new(vec![Type::I32], vec![Type::I32]),
+            move |args| {
+                let sleeps = sleeps.clone();
+                async move {
+                    let n = args[0].unwrap_i32();
+                    Yield { n: n as usize }.await;
+                    sleeps.fetch_add(1, Ordering::SeqCst);
+                    Ok(vec![Value::I32(n)])
+                }
+            },
+        );
+        let import_object = imports! {
+            "host" => {
+                "sleep" => sleep,
+            },
+        };
+
+        Ok(Instance::new(&module, &import_object)?)
+    }
+
+    #[test]
+    fn call_async_without_suspending() -> Result<()> {
+        let store = Store::default();
+        let instance = instance(&store, Arc::new(AtomicUsize::new(0)))?;
+
+        let add = instance.exports.get_function("add")?;
+        let (polls, results) = run(add.call_async(&[Value::I32(1), Value::I32(2)]));
+        assert_eq!(polls, 1);
+        assert_eq!(results?.to_vec(), vec![Value::I32(3)]);
+
+        let add: NativeFunc<(i32, i32), i32> = instance.exports.get_native_function("add")?;
+        let (polls, result) = run(add.call_async(3, 4));
+        assert_eq!(polls, 1);
+        assert_eq!(result?, 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn async_host_function_suspends_the_guest() -> Result<()> {
+        let store = Store::default();
+        let sleeps = Arc::new(AtomicUsize::new(0));
+        let instance = instance(&store, sleeps.clone())?;
+
+        let sleep_twice = instance.exports.get_function("sleep_twice")?;
+        let (polls, results) = run(sleep_twice.call_async(&[Value::I32(3)]));
+        assert_eq!(polls, 7);
+        assert_eq!(results?.to_vec(), vec![Value::I32(6)]);
+        assert_eq!(sleeps.load(Ordering::SeqCst), 2);
+
+        let sleep_twice: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("sleep_twice")?;
+        let (polls, result) = run(sleep_twice.call_async(1));
+        assert_eq!(polls, 3);
+        assert_eq!(result?, 2);
+        assert_eq!(sleeps.load(Ordering::SeqCst), 4);
+
+        // Async host functions can be called directly too.
+        let sleep = Function::new_async(
+            &store,
+            FunctionType::new(vec![], vec![Type::I32]),
+            |_| async {
+                Yield { n: 1 }.await;
+                Ok(vec![Value::I32(42)])
+            },
+        );
+        let (polls, results) = run(sleep.call_async(&[]));
+        assert_eq!(polls, 2);
+        assert_eq!(results?.to_vec(), vec![Value::I32(42)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn async_host_function_needs_call_async() -> Result<()> {
+        let store = Store::default();
+        let sleeps = Arc::new(AtomicUsize::new(0));
+        let instance = instance(&store, sleeps.clone())?;
+
+        let sleep_twice = instance.exports.get_function("sleep_twice")?;
+        let error = sleep_twice.call(&[Value::I32(1)]).unwrap_err();
+        assert_eq!(
+            error.message(),
+            "async host functions can only be called from `call_async`"
+        );
+        assert_eq!(sleeps.load(Ordering::SeqCst), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn call_async_traps() -> Result<()> {
+        let store = Store::default();
+        let instance = instance(&store, Arc::new(AtomicUsize::new(0)))?;
+
+        let trap = instance.exports.get_function("trap")?;
+        let (_, results) = run(trap.call_async(&[]));
+        assert!(results.unwrap_err().message().contains("unreachable"));
+
+        // The stack can be reused after a trap.
+        let add = instance.exports.get_function("add")?;
+        let (_, results) = run(add.call_async(&[Value::I32(1), Value::I32(2)]));
+        assert_eq!(results?.to_vec(), vec![Value::I32(3)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn dropping_a_suspended_call_cancels_it() -> Result<()> {
+        struct SetOnDrop(Arc<AtomicBool>);
+
+        impl Drop for SetOnDrop {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let store = Store::default();
+        let module = Module::new(
+            &store,
+            r#"
+            (module
+              (import "host" "wait" (func $wait))
+              (func (export "run")
+                call $wait))
+            "#,
+        )?;
+        let dropped = Arc::new(AtomicBool::new(false));
+        let wait = {
+            let dropped = dropped.clone();
+            Function::new_async(&store, FunctionType::new(vec![], vec![]), move |_| {
+                let guard = SetOnDrop(dropped.clone());
+                async move {
+                    let _guard = guard;
+                    std::future::pending::<()>().await;
+                    Ok(vec![])
+                }
+            })
+        };
+        let instance = Instance::new(
+            &module,
+            &imports! {
+                "host" => {
+                    "wait" => wait,
+                },
+            },
+        )?;
+
+        let run = instance.exports.get_function("run")?;
+        let mut call = Box::pin(run.call_async(&[]));
+        assert!(poll_once(call.as_mut()).is_pending());
+        assert!(poll_once(call.as_mut()).is_pending());
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        drop(call);
+        assert!(dropped.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+}
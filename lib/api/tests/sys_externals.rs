@@ -60,6 +60,29 @@ mod sys {
         Ok(())
     }
 
+    #[test]
+    fn global_typed() -> Result<()> {
+        let store = Store::default();
+        let global_i64 = Global::new_mut(&store, Value::I64(-1));
+        assert!(global_i64.typed::<i32>().is_err());
+        assert!(global_i64.typed::<f64>().is_err());
+
+        let typed = global_i64.typed::<u64>()?;
+        assert_eq!(typed.get(), u64::MAX);
+        typed.set(20)?;
+        assert_eq!(typed.get(), 20);
+        assert_eq!(global_i64.get(), Value::I64(20));
+
+        let global_f32 = Global::new(&store, Value::F32(1.5));
+        let typed = global_f32.typed::<f32>()?;
+        assert_eq!(typed.get(), 1.5);
+        // Set on a constant should error
+        assert!(typed.set(2.5).is_err());
+        assert_eq!(typed.get(), 1.5);
+
+        Ok(())
+    }
+
     #[test]
     fn table_new() -> Result<()> {
         let store = Store::default();
@@ -129,6 +152,37 @@ mod sys {
         Ok(())
     }
 
+    #[test]
+    fn table_typed() -> Result<()> {
+        let store = Store::default();
+        let table_type = TableType {
+            ty: Type::FuncRef,
+            minimum: 1,
+            maximum: Some(3),
+        };
+        let table = Table::new(&store, table_type, Value::FuncRef(None))?;
+        let typed = table.typed::<Option<Function>>()?;
+        assert_eq!(typed.size(), 1);
+        assert!(typed.get(0).unwrap().is_none());
+        assert!(typed.get(1).is_none());
+
+        let f = Function::new_native(&store, |num: i32| num + 1);
+        typed.set(0, Some(f.clone()))?;
+        assert_eq!(typed.grow(2, Some(f))?, 1);
+        assert!(typed.grow(1, None).is_err());
+        assert_eq!(table.size(), 3);
+        for i in 0..3 {
+            let f = typed.get(i).unwrap().unwrap();
+            assert_eq!(f.native::<i32, i32>()?.call(i as i32)?, i as i32 + 1);
+        }
+
+        let other_store = Store::default();
+        let other_f = Function::new_native(&other_store, || {});
+        assert!(typed.set(0, Some(other_f)).is_err());
+
+        Ok(())
+    }
+
     #[test]
     #[ignore]
     fn table_copy() -> Result<()> {
@@ -143,6 +197,7 @@ mod sys {
             shared: false,
             minimum: Pages(0),
             maximum: Some(Pages(10)),
+            memory64: false,
         };
         let memory = Memory::new(&store, memory_type)?;
         assert_eq!(memory.size(), Pages(0));
@@ -179,6 +234,46 @@ mod sys {
         Ok(())
     }
 
+    #[test]
+    fn memory_read_write() -> Result<()> {
+        let store = Store::default();
+        let memory = Memory::new(&store, MemoryType::new(Pages(1), Some(Pages(2)), false))?;
+
+        memory.write(0xfffb, b"hello")?;
+        assert_eq!(memory.read_to_vec(0xfffb, 5)?, b"hello");
+        assert_eq!(memory.read_utf8_string(0xfffb, 5)?, "hello");
+        assert_eq!(
+            memory.read_pod::<u32>(0xfffc)?,
+            u32::from_ne_bytes(*b"ello")
+        );
+
+        assert_eq!(
+            memory.write(0xfffc, b"hello"),
+            Err(MemoryAccessError::HeapOutOfBounds)
+        );
+        assert_eq!(memory.read_to_vec(0xfffb, 5)?, b"hello");
+        assert_eq!(
+            memory.read_to_vec(0, u64::MAX),
+            Err(MemoryAccessError::HeapOutOfBounds)
+        );
+        assert_eq!(
+            memory.read(u64::MAX, &mut [0; 2]),
+            Err(MemoryAccessError::Overflow)
+        );
+        assert_eq!(
+            memory.read_pod::<u64>(0xfffc),
+            Err(MemoryAccessError::HeapOutOfBounds)
+        );
+
+        // The memory can be accessed up to its new size after growing.
+        memory.grow(Pages(1))?;
+        memory.write(0x1fffb, b"world")?;
+        assert_eq!(memory.read_to_vec(0xfffb, 5)?, b"hello");
+        assert_eq!(memory.read_utf8_string(0x1fffb, 5)?, "world");
+
+        Ok(())
+    }
+
     #[test]
     fn function_new() -> Result<()> {
         let store = Store::default();
@@ -464,4 +559,56 @@ mod sys {
 
         Ok(())
     }
+
+    #[test]
+    fn wasmer_env_with_typed_exports() -> Result<()> {
+        let store = Store::default();
+        let wat = r#"(module
+  (import "env" "bump" (func $bump))
+  (global $counter (export "counter") (mut i32) (i32.const 0))
+  (table (export "functions") 1 funcref)
+  (elem (i32.const 0) $get)
+  (func $get (result i32)
+    global.get $counter)
+  (func (export "run") (result i32)
+    call $bump
+    call $bump
+    global.get $counter))
+"#;
+
+        #[derive(WasmerEnv, Clone)]
+        struct Env {
+            #[wasmer(export)]
+            counter: LazyInit<TypedGlobal<i32>>,
+            #[wasmer(export)]
+            functions: LazyInit<TypedTable<Option<Function>>>,
+        }
+
+        fn bump(env: &Env) {
+            let counter = env.counter_ref().unwrap();
+            let get = env.functions_ref().unwrap().get(0).unwrap().unwrap();
+            let current: i32 = get.native::<(), i32>().unwrap().call().unwrap();
+            counter.set(current + 1).unwrap();
+        }
+
+        let module = Module::new(&store, wat)?;
+        let env = Env {
+            counter: LazyInit::new(),
+            functions: LazyInit::new(),
+        };
+        let instance = Instance::new(
+            &module,
+            &imports! {
+                "env" => {
+                    "bump" => Function::new_native_with_env(&store, env, bump),
+                },
+            },
+        )?;
+        let run: NativeFunc<(), i32> = instance.exports.get_native_function("run")?;
+        assert_eq!(run.call()?, 2);
+        let counter: TypedGlobal<i32> = instance.exports.get_with_generics("counter")?;
+        assert_eq!(counter.get(), 2);
+
+        Ok(())
+    }
 }
@@ -67,4 +67,147 @@ mod sys {
 
         Ok(())
     }
+
+    #[test]
+    fn instance_pre_instantiates_many_times() -> Result<()> {
+        let store = Store::default();
+        let module = Module::new(
+            &store,
+            r#"
+    (module
+      (import "host" "load" (func $load (result i32)))
+      (memory (export "memory") 1)
+      (func (export "store_and_load") (param i32) (result i32)
+        (i32.store8 (i32.const 0) (local.get 0))
+        (call $load)))
+"#,
+        )?;
+
+        #[derive(WasmerEnv, Clone, Default)]
+        struct Env {
+            #[wasmer(export)]
+            memory: LazyInit<Memory>,
+        }
+
+        fn load(env: &Env) -> i32 {
+            let memory = env.memory_ref().unwrap();
+            memory.view::<u8>()[0].get() as i32
+        }
+
+        let import_object = imports! {
+            "host" => {
+                "load" => Function::new_native_with_env(&store, Env::default(), load),
+            },
+        };
+        let instance_pre = module.pre_instantiate(&import_object)?;
+        drop(import_object);
+
+        let first = instance_pre.instantiate()?;
+        let second = instance_pre.instantiate()?;
+        let first_store_and_load: NativeFunc<i32, i32> =
+            first.exports.get_native_function("store_and_load")?;
+        let second_store_and_load: NativeFunc<i32, i32> =
+            second.exports.get_native_function("store_and_load")?;
+
+        // Each instance has its own memory, and its own copy of the env.
+        assert_eq!(first_store_and_load.call(1)?, 1);
+        assert_eq!(second_store_and_load.call(2)?, 2);
+        assert_eq!(first.exports.get_memory("memory")?.view::<u8>()[0].get(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn instance_pre_reports_link_errors() -> Result<()> {
+        let store = Store::default();
+        let module = Module::new(&store, r#"(module (import "host" "global" (global i32)))"#)?;
+
+        let error = module.pre_instantiate(&imports! {}).unwrap_err();
+        assert!(matches!(error, InstantiationError::Link(_)));
+
+        let import_object = imports! {
+            "host" => {
+                "global" => Global::new(&store, Value::I64(1)),
+            },
+        };
+        let error = module.pre_instantiate(&import_object).unwrap_err();
+        assert!(matches!(error, InstantiationError::Link(_)));
+
+        Ok(())
+    }
+
+    const RESET_WAT: &str = r#"
+    (module
+      (memory (export "memory") 1 4)
+      (table (export "table") 1 4 funcref)
+      (global $counter (export "counter") (mut i32) (i32.const 0))
+      (global $starts (export "starts") (mut i32) (i32.const 0))
+      (data (i32.const 16) "hello")
+      (data $passive "world")
+      (func $start
+        (global.set $starts (i32.add (global.get $starts) (i32.const 1))))
+      (start $start)
+      (func (export "run") (result i32)
+        (i32.store8 (i32.const 16) (i32.const 106))
+        (i32.store8 (i32.const 100) (i32.const 1))
+        (memory.init $passive (i32.const 200) (i32.const 0) (i32.const 5))
+        (data.drop $passive)
+        (drop (memory.grow (i32.const 2)))
+        (drop (table.grow (ref.null func) (i32.const 2)))
+        (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+        (global.get $counter)))
+    "#;
+
+    fn check_reset(store: &Store) -> Result<()> {
+        let module = Module::new(store, RESET_WAT)?;
+        let instance = Instance::new(&module, &imports! {})?;
+        let run: NativeFunc<(), i32> = instance.exports.get_native_function("run")?;
+        let memory = instance.exports.get_memory("memory")?;
+        let table = instance.exports.get_table("table")?;
+        let starts = instance.exports.get_global("starts")?;
+
+        for _ in 0..3 {
+            assert_eq!(run.call()?, 1);
+            assert_eq!(memory.size(), Pages(3));
+            assert_eq!(table.size(), 3);
+            assert_eq!(starts.get(), Value::I32(1));
+            unsafe {
+                assert_eq!(&memory.data_unchecked()[16..21], b"jello");
+                assert_eq!(&memory.data_unchecked()[200..205], b"world");
+            }
+
+            unsafe { instance.reset()? };
+            assert_eq!(memory.size(), Pages(1));
+            assert_eq!(table.size(), 1);
+            unsafe {
+                assert_eq!(&memory.data_unchecked()[16..21], b"hello");
+                assert_eq!(memory.data_unchecked()[100], 0);
+                assert_eq!(&memory.data_unchecked()[200..205], &[0; 5]);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reset_restores_the_initial_state() -> Result<()> {
+        check_reset(&Store::default())
+    }
+
+    #[test]
+    fn reset_restores_pooled_instances() -> Result<()> {
+        for memory_init_cow in [false, true] {
+            let config = PoolingConfig {
+                max_instances: 1,
+                memory_pages: Pages(4),
+                memory_init_cow,
+                ..Default::default()
+            };
+            let tunables = PoolingTunables::new(config).map_err(anyhow::Error::msg)?;
+            let engine = Store::default().engine().clone();
+            check_reset(&Store::new_with_tunables(&*engine, tunables))?;
+        }
+
+        Ok(())
+    }
 }
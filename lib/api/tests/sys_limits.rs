@@ -0,0 +1,204 @@
+#[cfg(feature = "sys")]
+mod sys {
+    use anyhow::Result;
+    use std::sync::Arc;
+    use wasmer::*;
+
+    const WAT: &str = r#"
+    (module
+      (memory (export "memory") 1)
+      (table (export "table") 1 funcref)
+      (func (export "memory_grow") (param i32) (result i32)
+        (memory.grow (local.get 0)))
+      (func (export "table_grow") (param i32) (result i32)
+        (table.grow (ref.null func) (local.get 0))))
+    "#;
+
+    fn store_with_limits(limits: StoreLimits) -> (Store, Arc<StoreLimits>) {
+        let store = Store::default();
+        let limits = Arc::new(limits);
+        store.set_resource_limiter(Some(limits.clone()));
+        (store, limits)
+    }
+
+    #[test]
+    fn memory_grow_is_limited() -> Result<()> {
+        let mut limits = StoreLimits::new();
+        limits.memory_pages(Pages(4));
+        let (store, limits) = store_with_limits(limits);
+        let module = Module::new(&store, WAT)?;
+
+        let instance = Instance::new(&module, &imports! {})?;
+        assert_eq!(limits.memory_pages_used(), 1);
+
+        let memory_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("memory_grow")?;
+        assert_eq!(memory_grow.call(2)?, 1);
+        assert_eq!(memory_grow.call(2)?, -1);
+        assert_eq!(limits.memory_pages_used(), 3);
+
+        // The limit is shared by all the instances of the store.
+        let other = Instance::new(&module, &imports! {})?;
+        assert_eq!(limits.memory_pages_used(), 4);
+        let error = Instance::new(&module, &imports! {}).unwrap_err();
+        assert!(matches!(error, InstantiationError::ResourceLimit(_)));
+        assert_eq!(limits.memory_pages_used(), 4);
+
+        drop(memory_grow);
+        drop(instance);
+        assert_eq!(limits.memory_pages_used(), 1);
+        drop(other);
+        assert_eq!(limits.memory_pages_used(), 0);
+        assert_eq!(limits.instances_used(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_grow_is_limited() -> Result<()> {
+        let mut limits = StoreLimits::new();
+        limits.table_elements(3);
+        let (store, limits) = store_with_limits(limits);
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&module, &imports! {})?;
+
+        let table_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("table_grow")?;
+        assert_eq!(table_grow.call(2)?, 1);
+        assert_eq!(table_grow.call(1)?, -1);
+        assert_eq!(limits.table_elements_used(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn limits_can_trap() -> Result<()> {
+        let mut limits = StoreLimits::new();
+        limits.memory_pages(Pages(2)).trap_on_limit(true);
+        let (store, _) = store_with_limits(limits);
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&module, &imports! {})?;
+
+        let memory_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("memory_grow")?;
+        assert_eq!(memory_grow.call(1)?, 1);
+        let error = memory_grow.call(1).unwrap_err();
+        assert_eq!(
+            error.downcast::<ResourceLimitError>()?,
+            ResourceLimitError::Trap("the limit of 2 memory pages is exceeded".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn instances_are_limited() -> Result<()> {
+        let mut limits = StoreLimits::new();
+        limits.instances(1);
+        let (store, limits) = store_with_limits(limits);
+        let module = Module::new(&store, WAT)?;
+
+        let instance = Instance::new(&module, &imports! {})?;
+        let error = Instance::new(&module, &imports! {}).unwrap_err();
+        assert!(matches!(
+            error,
+            InstantiationError::ResourceLimit(ResourceLimitError::Fail)
+        ));
+        drop(instance);
+        assert_eq!(limits.instances_used(), 0);
+        Instance::new(&module, &imports! {})?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn reset_releases_grown_resources() -> Result<()> {
+        let (store, limits) = store_with_limits(StoreLimits::new());
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&module, &imports! {})?;
+
+        let memory_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("memory_grow")?;
+        let table_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("table_grow")?;
+        memory_grow.call(2)?;
+        table_grow.call(2)?;
+        assert_eq!(limits.memory_pages_used(), 3);
+        assert_eq!(limits.table_elements_used(), 3);
+
+        unsafe { instance.reset()? };
+        assert_eq!(limits.memory_pages_used(), 1);
+        assert_eq!(limits.table_elements_used(), 1);
+        assert_eq!(limits.instances_used(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn host_grows_are_limited() -> Result<()> {
+        let mut limits = StoreLimits::new();
+        limits.memory_pages(Pages(3)).table_elements(2);
+        let (store, limits) = store_with_limits(limits);
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&module, &imports! {})?;
+
+        let memory = instance.exports.get_memory("memory")?;
+        assert_eq!(memory.grow(2)?, Pages(1));
+        assert!(matches!(
+            memory.grow(1),
+            Err(MemoryError::ResourceLimit(ResourceLimitError::Fail))
+        ));
+        assert_eq!(limits.memory_pages_used(), 3);
+
+        let table = instance.exports.get_table("table")?;
+        assert_eq!(table.grow(1, Val::FuncRef(None))?, 1);
+        let error = table.grow(1, Val::FuncRef(None)).unwrap_err();
+        assert_eq!(
+            error.downcast::<ResourceLimitError>()?,
+            ResourceLimitError::Fail
+        );
+        assert_eq!(limits.table_elements_used(), 2);
+
+        drop(instance);
+        assert_eq!(limits.memory_pages_used(), 0);
+        assert_eq!(limits.table_elements_used(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn host_memories_are_charged_for_their_growth() -> Result<()> {
+        let (store, limits) = store_with_limits(StoreLimits::new());
+        let module = Module::new(
+            &store,
+            r#"
+            (module
+              (import "host" "memory" (memory 1))
+              (func (export "memory_grow") (param i32) (result i32)
+                (memory.grow (local.get 0))))
+            "#,
+        )?;
+        let memory = Memory::new(&store, MemoryType::new(1, None, false))?;
+        let instance = Instance::new(
+            &module,
+            &imports! {
+                "host" => {
+                    "memory" => memory.clone(),
+                },
+            },
+        )?;
+        assert_eq!(limits.memory_pages_used(), 0);
+
+        let memory_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("memory_grow")?;
+        assert_eq!(memory_grow.call(2)?, 1);
+        memory.grow(1)?;
+        assert_eq!(limits.memory_pages_used(), 2);
+
+        drop(memory_grow);
+        drop(instance);
+        assert_eq!(limits.memory_pages_used(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(feature = "sys")]
+mod sys {
+    use anyhow::Result;
+    use wasmer::*;
+
+    const COUNTER: &str = r#"
+    (module
+      (global $count (mut i32) (i32.const 0))
+      (func (export "increment") (result i32)
+        (global.set $count (i32.add (global.get $count) (i32.const 1)))
+        (global.get $count)))
+    "#;
+
+    const USER: &str = r#"
+    (module
+      (import "counter" "increment" (func $increment (result i32)))
+      (import "host" "offset" (global $offset i32))
+      (func (export "run") (result i32)
+        (drop (call $increment))
+        (i32.add (call $increment) (global.get $offset))))
+    "#;
+
+    #[test]
+    fn links_instances_by_name() -> Result<()> {
+        let store = Store::default();
+        let mut linker = Linker::new();
+        linker.define("host", "offset", Global::new(&store, Value::I32(100)))?;
+        let counter = linker.module("counter", &Module::new(&store, COUNTER)?)?;
+
+        let user = Module::new(&store, USER)?;
+        let first = linker.instantiate(&user)?;
+        let second = linker.instantiate(&user)?;
+
+        // Both users share the same counter instance.
+        let run: NativeFunc<(), i32> = first.exports.get_native_function("run")?;
+        assert_eq!(run.call()?, 102);
+        let run: NativeFunc<(), i32> = second.exports.get_native_function("run")?;
+        assert_eq!(run.call()?, 104);
+        let increment: NativeFunc<(), i32> = counter.exports.get_native_function("increment")?;
+        assert_eq!(increment.call()?, 5);
+
+        let instance_pre = linker.pre_instantiate(&user)?;
+        let run: NativeFunc<(), i32> = instance_pre
+            .instantiate()?
+            .exports
+            .get_native_function("run")?;
+        assert_eq!(run.call()?, 107);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reports_all_missing_imports() -> Result<()> {
+        let store = Store::default();
+        let linker = Linker::new();
+        let user = Module::new(&store, USER)?;
+
+        let error = linker.instantiate(&user).unwrap_err();
+        match &error {
+            LinkerError::MissingImports(imports) => {
+                let names = imports
+                    .iter()
+                    .map(|import| (import.module(), import.name()))
+                    .collect::<Vec<_>>();
+                assert_eq!(names, vec![("counter", "increment"), ("host", "offset")]);
+            }
+            _ => panic!("unexpected error: {}", error),
+        }
+        assert_eq!(
+            error.to_string(),
+            r#"missing imports: "counter"."increment", "host"."offset""#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reports_incompatible_imports() -> Result<()> {
+        let store = Store::default();
+        let mut linker = Linker::new();
+        linker.define("host", "offset", Global::new(&store, Value::I64(100)))?;
+        linker.module("counter", &Module::new(&store, COUNTER)?)?;
+
+        let error = linker.instantiate(&Module::new(&store, USER)?).unwrap_err();
+        assert!(matches!(
+            error,
+            LinkerError::Instantiation(InstantiationError::Link(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn shadowing() -> Result<()> {
+        let store = Store::default();
+        let mut linker = Linker::new();
+        linker.define("host", "offset", Global::new(&store, Value::I32(1)))?;
+
+        let error = linker
+            .define("host", "offset", Global::new(&store, Value::I32(2)))
+            .unwrap_err();
+        assert!(matches!(error, LinkerError::AlreadyDefined(_, _)));
+
+        // Nothing is defined when one of the exports is already defined.
+        let mut exports = Exports::new();
+        exports.insert("other", Global::new(&store, Value::I32(3)));
+        exports.insert("offset", Global::new(&store, Value::I32(4)));
+        assert!(linker.define_exports("host", &exports).is_err());
+        assert!(linker.get("host", "other").is_none());
+
+        linker.allow_shadowing(true);
+        linker.define_exports("host", &exports)?;
+        let offset = match linker.get("host", "offset") {
+            Some(Extern::Global(global)) => global.get(),
+            _ => panic!("expected a global"),
+        };
+        assert_eq!(offset, Value::I32(4));
+
+        Ok(())
+    }
+}
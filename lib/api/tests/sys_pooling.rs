@@ -0,0 +1,127 @@
+#[cfg(feature = "sys")]
+mod sys {
+    use anyhow::Result;
+    use wasmer::*;
+
+    const WAT: &str = r#"
+    (module
+      (memory (export "memory") 1 4)
+      (table 1 4 funcref)
+      (data (i32.const 16) "hello")
+      (func (export "load") (param i32) (result i32)
+        (i32.load8_u (local.get 0)))
+      (func (export "store") (param i32 i32)
+        (i32.store8 (local.get 0) (local.get 1)))
+      (func (export "memory_grow") (param i32) (result i32)
+        (memory.grow (local.get 0)))
+      (func (export "table_grow") (param i32) (result i32)
+        (table.grow (ref.null func) (local.get 0))))
+    "#;
+
+    fn pooling_store(config: PoolingConfig) -> Result<(Store, PoolingTunables)> {
+        let tunables = PoolingTunables::new(config).map_err(anyhow::Error::msg)?;
+        let engine = Store::default().engine().clone();
+        let store = Store::new_with_tunables(&*engine, tunables.clone());
+        Ok((store, tunables))
+    }
+
+    fn small_config() -> PoolingConfig {
+        PoolingConfig {
+            max_instances: 2,
+            max_memories: 2,
+            max_tables: 2,
+            memory_pages: Pages(4),
+            table_elements: 4,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn slots_are_reused() -> Result<()> {
+        let (store, tunables) = pooling_store(small_config())?;
+        let module = Module::new(&store, WAT)?;
+        let allocator = tunables.allocator();
+
+        for _ in 0..10 {
+            let instance = Instance::new(&module, &imports! {})?;
+            assert_eq!(allocator.available_instances(), 1);
+            assert_eq!(allocator.available_memories(), 1);
+            assert_eq!(allocator.available_tables(), 1);
+            drop(instance);
+        }
+        assert_eq!(allocator.available_instances(), 2);
+        assert_eq!(allocator.available_memories(), 2);
+        assert_eq!(allocator.available_tables(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn exhausted_pool_fails_to_instantiate() -> Result<()> {
+        let (store, _) = pooling_store(small_config())?;
+        let module = Module::new(&store, WAT)?;
+
+        let first = Instance::new(&module, &imports! {})?;
+        let _second = Instance::new(&module, &imports! {})?;
+        let error = Instance::new(&module, &imports! {}).unwrap_err();
+        assert!(matches!(error, InstantiationError::Link(_)));
+
+        drop(first);
+        Instance::new(&module, &imports! {})?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn memories_are_initialized_for_each_instance() -> Result<()> {
+        for memory_init_cow in [false, true] {
+            let config = PoolingConfig {
+                memory_init_cow,
+                ..small_config()
+            };
+            let (store, _) = pooling_store(config)?;
+            let module = Module::new(&store, WAT)?;
+
+            for _ in 0..3 {
+                let instance = Instance::new(&module, &imports! {})?;
+                let load: NativeFunc<i32, i32> = instance.exports.get_native_function("load")?;
+                let store: NativeFunc<(i32, i32), ()> =
+                    instance.exports.get_native_function("store")?;
+                assert_eq!(load.call(16)?, i32::from(b'h'));
+                assert_eq!(load.call(100)?, 0);
+
+                // Writes must not be visible to the next instance.
+                store.call(16, i32::from(b'j'))?;
+                store.call(100, 1)?;
+                assert_eq!(load.call(16)?, i32::from(b'j'));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn memories_and_tables_grow_in_their_slots() -> Result<()> {
+        let (store, _) = pooling_store(small_config())?;
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&module, &imports! {})?;
+
+        let memory_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("memory_grow")?;
+        let table_grow: NativeFunc<i32, i32> =
+            instance.exports.get_native_function("table_grow")?;
+        let memory = instance.exports.get_memory("memory")?;
+
+        assert_eq!(memory_grow.call(3)?, 1);
+        assert_eq!(memory.size(), Pages(4));
+        assert_eq!(memory_grow.call(1)?, -1);
+        unsafe {
+            memory.data_unchecked_mut()[4 * 0x1_0000 - 1] = 1;
+        }
+
+        assert_eq!(table_grow.call(3)?, 1);
+        assert_eq!(table_grow.call(1)?, -1);
+
+        Ok(())
+    }
+}
@@ -145,6 +145,7 @@ impl From<Extern> for wasm_extern_t {
                     global: mem::ManuallyDrop::new(wasm_global_t::new(global)),
                 },
             },
+            Extern::Tag(_) => unreachable!("tags are left out of the C API"),
         }
     }
 }
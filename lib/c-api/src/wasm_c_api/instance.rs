@@ -73,7 +73,8 @@ pub unsafe extern "C" fn wasm_instance_new(
             return None;
         }
 
-        Err(e @ InstantiationError::CpuFeature(_)) => {
+        Err(e @ InstantiationError::CpuFeature(_))
+        | Err(e @ InstantiationError::ResourceLimit(_)) => {
             crate::error::update_last_error(e);
 
             return None;
@@ -180,6 +181,12 @@ pub unsafe extern "C" fn wasm_instance_delete(_instance: Option<Box<wasm_instanc
 /// * [`wasm_extern_as_global`][super::externals::wasm_extern_as_global],
 /// * [`wasm_extern_as_table`][super::externals::wasm_extern_as_table],
 /// * [`wasm_extern_as_memory`][super::externals::wasm_extern_as_memory].
+///
+/// # Notes
+///
+/// The C API has no extern kind for the tags of the exception-handling
+/// proposal: exported tags are left out, as they are by
+/// [`wasm_module_exports`][super::module::wasm_module_exports].
 #[no_mangle]
 pub unsafe extern "C" fn wasm_instance_exports(
     instance: &wasm_instance_t,
@@ -190,6 +197,7 @@ pub unsafe extern "C" fn wasm_instance_exports(
     let extern_vec = instance
         .exports
         .iter()
+        .filter(|(_name, r#extern)| !matches!(r#extern, Extern::Tag(_)))
         .map(|(_name, r#extern)| Some(Box::new(r#extern.clone().into())))
         .collect();
 
@@ -3,7 +3,7 @@ use super::types::{wasm_byte_vec_t, wasm_exporttype_vec_t, wasm_importtype_vec_t
 use crate::error::update_last_error;
 use std::ptr::NonNull;
 use std::sync::Arc;
-use wasmer_api::Module;
+use wasmer_api::{ExternType, Module};
 
 /// Opaque type representing a WebAssembly module.
 #[allow(non_camel_case_types)]
@@ -220,6 +220,11 @@ pub unsafe extern "C" fn wasm_module_validate(
 /// #    .success();
 /// # }
 /// ```
+///
+/// # Notes
+///
+/// The C API has no extern kind for the tags of the exception-handling
+/// proposal: exported tags are left out.
 #[no_mangle]
 pub unsafe extern "C" fn wasm_module_exports(
     module: &wasm_module_t,
@@ -229,6 +234,7 @@ pub unsafe extern "C" fn wasm_module_exports(
     let exports = module
         .inner
         .exports()
+        .filter(|export| !matches!(export.ty(), ExternType::Tag(_)))
         .map(|export| Some(Box::new(export.into())))
         .collect();
 
@@ -360,6 +366,12 @@ pub unsafe extern "C" fn wasm_module_exports(
 /// #    .success();
 /// # }
 /// ```
+///
+/// # Notes
+///
+/// The C API has no extern kind for the tags of the exception-handling
+/// proposal: imported tags are left out, and a module that imports tags
+/// can't be instantiated with [`wasm_instance_new`][super::instance::wasm_instance_new].
 #[no_mangle]
 pub unsafe extern "C" fn wasm_module_imports(
     module: &wasm_module_t,
@@ -369,6 +381,7 @@ pub unsafe extern "C" fn wasm_module_imports(
     let imports = module
         .inner
         .imports()
+        .filter(|import| !matches!(import.ty(), ExternType::Tag(_)))
         .map(|import| Some(Box::new(import.into())))
         .collect();
 
@@ -97,6 +97,9 @@ impl Compile {
         println!("Engine: {}", engine_type.to_string());
         println!("Compiler: {}", compiler_type.to_string());
         println!("Target: {}", target.triple());
+        if let Some(fuel) = self.store.fuel() {
+            println!("Fuel: {} points", fuel);
+        }
 
         let module = Module::from_file(&store, &self.path)?;
         let _ = module.serialize_to_file(&self.output)?;
@@ -127,7 +127,7 @@ impl Run {
                     }
                 };
 
-                run_emscripten_instance(
+                let result = run_emscripten_instance(
                     &mut instance,
                     &mut em_env,
                     &mut emscripten_globals,
@@ -138,7 +138,9 @@ impl Run {
                     },
                     self.args.iter().map(|arg| arg.as_str()).collect(),
                     None, //run.em_entrypoint.clone(),
-                )?;
+                );
+                self.report_fuel(&instance);
+                result?;
                 return Ok(());
             }
         }
@@ -191,16 +193,20 @@ impl Run {
 
         // If this module exports an _initialize function, run that first.
         if let Ok(initialize) = instance.exports.get_function("_initialize") {
-            initialize
-                .call(&[])
-                .with_context(|| "failed to run _initialize function")?;
+            let result = initialize.call(&[]);
+            if result.is_err() {
+                self.report_fuel(&instance);
+            }
+            result.with_context(|| "failed to run _initialize function")?;
         }
 
         // Do we want to invoke a function?
         if let Some(ref invoke) = self.invoke {
             let imports = imports! {};
             let instance = Instance::new(&module, &imports)?;
-            let result = self.invoke_function(&instance, &invoke, &self.args)?;
+            let result = self.invoke_function(&instance, &invoke, &self.args);
+            self.report_fuel(&instance);
+            let result = result?;
             println!(
                 "{}",
                 result
@@ -212,6 +218,7 @@ impl Run {
         } else {
             let start: Function = self.try_find_function(&instance, "_start", &[])?;
             let result = start.call(&[]);
+            self.report_fuel(&instance);
             #[cfg(feature = "wasi")]
             self.wasi.handle_result(result)?;
             #[cfg(not(feature = "wasi"))]
@@ -221,8 +228,59 @@ impl Run {
         Ok(())
     }
 
+    /// Reports the fuel consumed by `instance` when running with
+    /// `--fuel` or a module precompiled with `--fuel`, and exits with
+    /// `FUEL_EXHAUSTED_EXIT_CODE` if it ran out of fuel.
+    #[allow(unused_variables)]
+    fn report_fuel(&self, instance: &Instance) {
+        #[cfg(feature = "compiler")]
+        if let Some(fuel) = self
+            .store
+            .fuel()
+            .or_else(|| Self::precompiled_fuel(instance.module()))
+        {
+            use crate::metering::FUEL_EXHAUSTED_EXIT_CODE;
+            use colored::*;
+            use wasmer_middlewares::metering::{get_remaining_points, MeteringPoints};
+
+            match get_remaining_points(instance) {
+                MeteringPoints::Remaining(remaining) => {
+                    eprintln!("Fuel consumed: {} of {} points", fuel - remaining, fuel)
+                }
+                MeteringPoints::Exhausted => {
+                    eprintln!(
+                        "{}: all {} points of fuel were consumed",
+                        "error".red().bold(),
+                        fuel
+                    );
+                    std::process::exit(FUEL_EXHAUSTED_EXIT_CODE);
+                }
+            }
+        }
+    }
+
+    /// The fuel limit of a module precompiled with `--fuel`, which is
+    /// the initial value of the remaining points global that the
+    /// metering middleware exports.
+    #[cfg(feature = "compiler")]
+    fn precompiled_fuel(module: &Module) -> Option<u64> {
+        let info = module.info();
+        let global = match info.exports.get("wasmer_metering_remaining_points")? {
+            ExportIndex::Global(global) => *global,
+            _ => return None,
+        };
+        let global = info.local_global_index(global)?;
+        match info.global_initializers.get(global)? {
+            GlobalInit::I64Const(fuel) => Some(*fuel as u64),
+            _ => None,
+        }
+    }
+
     fn get_module(&self) -> Result<Module> {
         let contents = std::fs::read(self.path.clone())?;
+        if self.store.fuel().is_some() && Self::is_precompiled(&contents) {
+            bail!("`--fuel` can't be applied to a precompiled module, pass it to `wasmer compile` instead");
+        }
         #[cfg(feature = "dylib")]
         {
             if wasmer_engine_dylib::DylibArtifact::is_deserializable(&contents) {
@@ -242,12 +300,15 @@ impl Run {
             }
         }
         let (store, engine_type, compiler_type) = self.store.get_store()?;
+        // Modules compiled with metering are not cached, since the cache
+        // key doesn't depend on the metering options.
         #[cfg(feature = "cache")]
-        let module_result: Result<Module> = if !self.disable_cache && contents.len() > 0x1000 {
-            self.get_module_from_cache(&store, &contents, &engine_type, &compiler_type)
-        } else {
-            Module::new(&store, &contents).map_err(|e| e.into())
-        };
+        let module_result: Result<Module> =
+            if !self.disable_cache && self.store.fuel().is_none() && contents.len() > 0x1000 {
+                self.get_module_from_cache(&store, &contents, &engine_type, &compiler_type)
+            } else {
+                Module::new(&store, &contents).map_err(|e| e.into())
+            };
         #[cfg(not(feature = "cache"))]
         let module_result = Module::new(&store, &contents);
 
@@ -264,6 +325,21 @@ impl Run {
         Ok(module)
     }
 
+    /// Whether `contents` is a module precompiled by one of the
+    /// engines, rather than WebAssembly.
+    #[allow(unused_variables)]
+    fn is_precompiled(contents: &[u8]) -> bool {
+        #[cfg(feature = "dylib")]
+        if wasmer_engine_dylib::DylibArtifact::is_deserializable(contents) {
+            return true;
+        }
+        #[cfg(feature = "universal")]
+        if wasmer_engine_universal::UniversalArtifact::is_deserializable(contents) {
+            return true;
+        }
+        false
+    }
+
     #[cfg(feature = "cache")]
     fn get_module_from_cache(
         &self,
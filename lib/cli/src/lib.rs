@@ -24,6 +24,8 @@ pub mod c_gen;
 pub mod cli;
 #[cfg(feature = "debug")]
 pub mod logging;
+#[cfg(feature = "compiler")]
+pub mod metering;
 pub mod store;
 pub mod suggestions;
 pub mod utils;
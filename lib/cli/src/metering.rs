@@ -0,0 +1,120 @@
+//! Metering support for the CLI: the `--fuel` and `--cost-table`
+//! options and the cost tables they use.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use structopt::StructOpt;
+use wasmer::wasmparser::Operator;
+use wasmer::CompilerConfig;
+use wasmer_middlewares::operator_policy::operator_name;
+use wasmer_middlewares::Metering;
+
+/// The exit code of `wasmer run` when the execution runs out of fuel.
+pub const FUEL_EXHAUSTED_EXIT_CODE: i32 = 124;
+
+/// The key of a cost table giving the cost of the operators not
+/// listed in it.
+const DEFAULT_COST_KEY: &str = "default";
+
+#[derive(Debug, StructOpt, Clone, Default)]
+/// The metering options that can be passed through the Command Line
+/// args.
+pub struct MeteringOptions {
+    /// Limit the execution to the given number of points of fuel.
+    /// By default, every operator costs one point.
+    #[structopt(long = "fuel")]
+    pub fuel: Option<u64>,
+
+    /// Load the cost of each operator from a JSON or TOML file mapping
+    /// operator names (e.g. `I32Add`) to costs. The `default` key sets
+    /// the cost of the operators that are not listed.
+    #[structopt(long = "cost-table", parse(from_os_str), requires = "fuel")]
+    pub cost_table: Option<PathBuf>,
+}
+
+impl MeteringOptions {
+    /// Adds the metering middleware for the current options, if any, to
+    /// `compiler_config`.
+    pub fn push_middleware(
+        &self,
+        mut compiler_config: Box<dyn CompilerConfig>,
+    ) -> Result<Box<dyn CompilerConfig>> {
+        let fuel = match self.fuel {
+            Some(fuel) => fuel,
+            None => return Ok(compiler_config),
+        };
+        let cost_table = match &self.cost_table {
+            Some(path) => CostTable::from_file(path)?,
+            None => CostTable::default(),
+        };
+
+        compiler_config.push_middleware(Arc::new(Metering::new(fuel, move |operator| {
+            cost_table.cost(operator)
+        })));
+        Ok(compiler_config)
+    }
+}
+
+/// The cost in points of each operator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostTable {
+    /// The cost of the operators not in `costs`.
+    default: u64,
+
+    /// The cost of the operators, by name.
+    costs: HashMap<String, u64>,
+}
+
+impl Default for CostTable {
+    /// The built-in cost table: every operator costs one point.
+    fn default() -> Self {
+        Self {
+            default: 1,
+            costs: HashMap::new(),
+        }
+    }
+}
+
+impl CostTable {
+    /// Loads a cost table from a `.json` or `.toml` file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read the cost table `{}`", path.display()))?;
+        let costs: HashMap<String, u64> = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&contents).map_err(anyhow::Error::from),
+            Some("toml") => toml::from_str(&contents).map_err(anyhow::Error::from),
+            _ => bail!(
+                "the cost table `{}` must be a `.json` or a `.toml` file",
+                path.display()
+            ),
+        }
+        .with_context(|| format!("failed to parse the cost table `{}`", path.display()))?;
+
+        Ok(Self::from_costs(costs))
+    }
+
+    /// Creates a cost table from costs by operator name, where the
+    /// `default` key gives the cost of the operators that are not
+    /// listed.
+    pub fn from_costs(mut costs: HashMap<String, u64>) -> Self {
+        let default = costs
+            .remove(DEFAULT_COST_KEY)
+            .unwrap_or_else(|| Self::default().default);
+
+        Self { default, costs }
+    }
+
+    /// The cost of `operator`, in points.
+    pub fn cost(&self, operator: &Operator) -> u64 {
+        if self.costs.is_empty() {
+            return self.default;
+        }
+
+        self.costs
+            .get(operator_name(operator))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
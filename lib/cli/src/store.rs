@@ -5,6 +5,8 @@ use anyhow::Result;
 
 #[allow(unused_imports)]
 use crate::common::WasmFeatures;
+#[cfg(feature = "compiler")]
+use crate::metering::MeteringOptions;
 #[allow(unused_imports)]
 use std::path::PathBuf;
 use std::string::ToString;
@@ -74,6 +76,9 @@ pub struct CompilerOptions {
 
     #[structopt(flatten)]
     features: WasmFeatures,
+
+    #[structopt(flatten)]
+    metering: MeteringOptions,
 }
 
 #[cfg(feature = "compiler")]
@@ -309,7 +314,7 @@ impl CompilerOptions {
         };
 
         #[allow(unreachable_code)]
-        Ok((compiler_config, compiler))
+        Ok((self.metering.push_middleware(compiler_config)?, compiler))
     }
 }
 
@@ -372,6 +377,16 @@ impl ToString for EngineType {
     }
 }
 
+impl StoreOptions {
+    /// The fuel limit given with `--fuel`, if any.
+    pub fn fuel(&self) -> Option<u64> {
+        #[cfg(feature = "compiler")]
+        return self.compiler.metering.fuel;
+        #[cfg(not(feature = "compiler"))]
+        return None;
+    }
+}
+
 #[cfg(all(feature = "compiler", feature = "engine"))]
 impl StoreOptions {
     /// Gets the store for the host target, with the engine name and compiler name selected
@@ -22,7 +22,8 @@ use loupe::MemoryUsage;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use std::sync::Arc;
 use wasmer_compiler::{
-    CallingConvention, ModuleTranslationState, RelocationTarget, Target, TrapInformation,
+    Architecture, CallingConvention, ModuleTranslationState, RelocationTarget, Target,
+    TrapInformation,
 };
 use wasmer_compiler::{
     Compilation, CompileModuleInfo, CompiledFunction, CompiledFunctionFrameInfo,
@@ -74,6 +75,10 @@ impl Compiler for CraneliftCompiler {
             .isa(target)
             .map_err(|error| CompileError::Codegen(error.to_string()))?;
         let frontend_config = isa.frontend_config();
+        // Only x86-64 has native instructions for the relaxed SIMD operators
+        // that are faster than their deterministic lowering.
+        let relaxed_simd_deterministic = self.config.enable_relaxed_simd_deterministic
+            || target.triple().architecture != Architecture::X86_64;
         let memory_styles = &compile_info.memory_styles;
         let table_styles = &compile_info.table_styles;
         let module = &compile_info.module;
@@ -122,6 +127,9 @@ impl Compiler for CraneliftCompiler {
                     &signatures,
                     &memory_styles,
                     &table_styles,
+                    &compile_info.features,
+                    relaxed_simd_deterministic,
+                    self.config.enable_interrupts,
                 );
                 context.func.name = get_function_name(func_index);
                 context.func.signature = signatures[module.functions[func_index]].clone();
@@ -130,10 +138,14 @@ impl Compiler for CraneliftCompiler {
                 // }
                 let mut reader =
                     MiddlewareBinaryReader::new_with_offset(input.data, input.module_offset);
+                reader.allow_memarg64(module.memories.values().any(|memory| memory.memory64));
                 reader.set_middleware_chain(
                     self.config
                         .middlewares
-                        .generate_function_middleware_chain(*i),
+                        .generate_function_middleware_chain_with_state(
+                            *i,
+                            module_translation_state,
+                        ),
                 );
 
                 func_translator.translate(
@@ -311,7 +323,8 @@ fn translate_ir_trapcode(trap: ir::TrapCode) -> TrapCode {
     match trap {
         ir::TrapCode::StackOverflow => TrapCode::StackOverflow,
         ir::TrapCode::HeapOutOfBounds => TrapCode::HeapAccessOutOfBounds,
-        ir::TrapCode::HeapMisaligned => TrapCode::HeapMisaligned,
+        // The translator only checks the alignment of atomic accesses.
+        ir::TrapCode::HeapMisaligned => TrapCode::UnalignedAtomic,
         ir::TrapCode::TableOutOfBounds => TrapCode::TableAccessOutOfBounds,
         ir::TrapCode::IndirectCallToNull => TrapCode::IndirectCallToNull,
         ir::TrapCode::BadSignature => TrapCode::BadSignature,
@@ -319,9 +332,8 @@ fn translate_ir_trapcode(trap: ir::TrapCode) -> TrapCode {
         ir::TrapCode::IntegerDivisionByZero => TrapCode::IntegerDivisionByZero,
         ir::TrapCode::BadConversionToInteger => TrapCode::BadConversionToInteger,
         ir::TrapCode::UnreachableCodeReached => TrapCode::UnreachableCodeReached,
-        ir::TrapCode::Interrupt => unimplemented!("Interrupts not supported"),
+        ir::TrapCode::Interrupt => TrapCode::Interrupt,
         ir::TrapCode::User(_user_code) => unimplemented!("User trap code not supported"),
-        // ir::TrapCode::Interrupt => TrapCode::Interrupt,
         // ir::TrapCode::User(user_code) => TrapCode::User(user_code),
     }
 }
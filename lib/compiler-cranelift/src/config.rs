@@ -34,6 +34,8 @@ pub struct Cranelift {
     enable_nan_canonicalization: bool,
     enable_verifier: bool,
     enable_pic: bool,
+    pub(crate) enable_interrupts: bool,
+    pub(crate) enable_relaxed_simd_deterministic: bool,
     opt_level: CraneliftOptLevel,
     /// The middleware chain.
     pub(crate) middlewares: Vec<Arc<dyn ModuleMiddleware>>,
@@ -48,6 +50,8 @@ impl Cranelift {
             enable_verifier: false,
             opt_level: CraneliftOptLevel::Speed,
             enable_pic: false,
+            enable_interrupts: false,
+            enable_relaxed_simd_deterministic: false,
             middlewares: vec![],
         }
     }
@@ -61,6 +65,26 @@ impl Cranelift {
         self
     }
 
+    /// Enable the deterministic lowering of the relaxed SIMD operators.
+    ///
+    /// The relaxed SIMD operators are allowed to return different results
+    /// on different architectures, to use the fastest native instructions.
+    /// This setting only controls `f32x4.relaxed_min`, `f32x4.relaxed_max`,
+    /// `f64x2.relaxed_min` and `f64x2.relaxed_max`: when enabled, they
+    /// return the results of `fNxM.min` and `fNxM.max`, otherwise those of
+    /// `fNxM.pmin` and `fNxM.pmax`, which compile to a single x86
+    /// instruction.
+    ///
+    /// The other relaxed operators always return the results of their
+    /// deterministic counterparts, and the fused multiply-adds are always
+    /// computed unfused, with two roundings. On targets other than x86-64
+    /// the relaxed operators are always deterministic, whatever this
+    /// setting.
+    pub fn relaxed_simd_deterministic(&mut self, enable: bool) -> &mut Self {
+        self.enable_relaxed_simd_deterministic = enable;
+        self
+    }
+
     /// The optimization levels when optimizing the IR.
     pub fn opt_level(&mut self, opt_level: CraneliftOptLevel) -> &mut Self {
         self.opt_level = opt_level;
@@ -200,6 +224,10 @@ impl CompilerConfig for Cranelift {
         self.enable_nan_canonicalization = enable;
     }
 
+    fn interruptible(&mut self, enable: bool) {
+        self.enable_interrupts = enable;
+    }
+
     /// Transform it into the compiler
     fn compiler(self: Box<Self>) -> Box<dyn Compiler> {
         Box::new(CraneliftCompiler::new(*self))
@@ -4,7 +4,7 @@
 use crate::translator::{
     type_to_irtype, FuncEnvironment as BaseFuncEnvironment, GlobalVariable, TargetEnvironment,
 };
-use cranelift_codegen::cursor::FuncCursor;
+use cranelift_codegen::cursor::{Cursor, FuncCursor};
 use cranelift_codegen::ir;
 use cranelift_codegen::ir::condcodes::*;
 use cranelift_codegen::ir::immediates::{Offset32, Uimm64};
@@ -13,17 +13,18 @@ use cranelift_codegen::ir::{AbiParam, ArgumentPurpose, Function, InstBuilder, Si
 use cranelift_codegen::isa::TargetFrontendConfig;
 use cranelift_frontend::{FunctionBuilder, Variable};
 use std::convert::TryFrom;
+use std::mem;
 use wasmer_compiler::wasmparser::Type;
 use wasmer_compiler::{WasmError, WasmResult};
 use wasmer_types::entity::EntityRef;
 use wasmer_types::entity::PrimaryMap;
 use wasmer_types::{
-    FunctionIndex, FunctionType, GlobalIndex, LocalFunctionIndex, MemoryIndex, ModuleInfo,
-    SignatureIndex, TableIndex, Type as WasmerType,
+    Features, FunctionIndex, FunctionType, GlobalIndex, LocalFunctionIndex, MemoryIndex,
+    ModuleInfo, SignatureIndex, TableIndex, TagIndex, Type as WasmerType,
 };
 use wasmer_vm::VMBuiltinFunctionIndex;
 use wasmer_vm::VMOffsets;
-use wasmer_vm::{MemoryStyle, TableStyle};
+use wasmer_vm::{MemoryStyle, TableStyle, MAX_TAIL_CALL_VALUES};
 
 /// Compute an `ir::ExternalName` for a given wasm function index.
 pub fn get_function_name(func_index: FunctionIndex) -> ir::ExternalName {
@@ -52,10 +53,20 @@ pub struct FuncEnvironment<'module_environment> {
     /// The Cranelift global holding the vmctx address.
     vmctx: Option<ir::GlobalValue>,
 
+    /// The function being translated.
+    function_index: Option<FunctionIndex>,
+
+    /// Whether the function was called by a tail call, once checked on entry.
+    tail_call_entered: Option<ir::Value>,
+
     /// The external function signature for implementing wasm's `memory.size`
     /// for locally-defined 32-bit memories.
     memory32_size_sig: Option<ir::SigRef>,
 
+    /// The external function signature for implementing wasm's `memory.size`
+    /// for 64-bit memories.
+    memory64_size_sig: Option<ir::SigRef>,
+
     /// The external function signature for implementing wasm's `table.size`
     /// for locally-defined tables.
     table_size_sig: Option<ir::SigRef>,
@@ -64,6 +75,10 @@ pub struct FuncEnvironment<'module_environment> {
     /// for locally-defined memories.
     memory_grow_sig: Option<ir::SigRef>,
 
+    /// The external function signature for implementing wasm's `memory.grow`
+    /// for 64-bit memories.
+    memory64_grow_sig: Option<ir::SigRef>,
+
     /// The external function signature for implementing wasm's `table.grow`
     /// for locally-defined tables.
     table_grow_sig: Option<ir::SigRef>,
@@ -82,16 +97,45 @@ pub struct FuncEnvironment<'module_environment> {
     /// (it's the same for both local and imported memories).
     memory_copy_sig: Option<ir::SigRef>,
 
+    /// The external function signature for implementing wasm's `memory.copy`
+    /// for 64-bit memories.
+    memory64_copy_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's `memory.copy`
+    /// between two different memories.
+    memory_copy_between_sig: Option<ir::SigRef>,
+
     /// The external function signature for implementing wasm's `memory.fill`
     /// (it's the same for both local and imported memories).
     memory_fill_sig: Option<ir::SigRef>,
 
+    /// The external function signature for implementing wasm's `memory.fill`
+    /// for 64-bit memories.
+    memory64_fill_sig: Option<ir::SigRef>,
+
     /// The external function signature for implementing wasm's `memory.init`.
     memory_init_sig: Option<ir::SigRef>,
 
+    /// The external function signature for implementing wasm's `memory.init`
+    /// for 64-bit memories.
+    memory64_init_sig: Option<ir::SigRef>,
+
     /// The external function signature for implementing wasm's `data.drop`.
     data_drop_sig: Option<ir::SigRef>,
 
+    /// The external function signature for implementing wasm's
+    /// `memory.atomic.wait32` (it's the same for both local and imported
+    /// memories).
+    memory_atomic_wait32_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's
+    /// `memory.atomic.wait64`.
+    memory_atomic_wait64_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's
+    /// `memory.atomic.notify`.
+    memory_atomic_notify_sig: Option<ir::SigRef>,
+
     /// The external function signature for implementing wasm's `table.get`.
     table_get_sig: Option<ir::SigRef>,
 
@@ -109,6 +153,36 @@ pub struct FuncEnvironment<'module_environment> {
 
     /// The external function signature for implementing reference decrement for `extern.ref`.
     externref_dec_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's `return_call` and
+    /// `return_call_indirect`.
+    tail_call_sig: Option<ir::SigRef>,
+
+    /// The external function signature for checking on entry whether a function that makes
+    /// tail calls was called by a tail call.
+    tail_call_entered_sig: Option<ir::SigRef>,
+
+    /// The external function signature for checking whether an exception is being thrown.
+    exception_thrown_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's `throw`.
+    throw_sig: Option<ir::SigRef>,
+
+    /// The external function signature for catching the exception being thrown.
+    exception_catch_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's `catch`.
+    exception_matches_sig: Option<ir::SigRef>,
+
+    /// The external function signature for reading the values an exception carries.
+    exception_payload_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's `rethrow`.
+    rethrow_sig: Option<ir::SigRef>,
+
+    /// The external function signature for releasing a caught exception.
+    exception_sig: Option<ir::SigRef>,
+
     /// Offsets to struct fields accessed by JIT code.
     offsets: VMOffsets,
 
@@ -117,6 +191,16 @@ pub struct FuncEnvironment<'module_environment> {
 
     /// The table styles
     table_styles: &'module_environment PrimaryMap<TableIndex, TableStyle>,
+
+    /// The enabled WebAssembly features.
+    features: &'module_environment Features,
+
+    /// Whether the relaxed SIMD `min` and `max` operators get their
+    /// deterministic results.
+    relaxed_simd_deterministic: bool,
+
+    /// Whether to check for interruptions at function entries and loops.
+    interruptible: bool,
 }
 
 impl<'module_environment> FuncEnvironment<'module_environment> {
@@ -126,6 +210,9 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         signatures: &'module_environment PrimaryMap<SignatureIndex, ir::Signature>,
         memory_styles: &'module_environment PrimaryMap<MemoryIndex, MemoryStyle>,
         table_styles: &'module_environment PrimaryMap<TableIndex, TableStyle>,
+        features: &'module_environment Features,
+        relaxed_simd_deterministic: bool,
+        interruptible: bool,
     ) -> Self {
         Self {
             target_config,
@@ -133,26 +220,49 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             signatures,
             type_stack: vec![],
             vmctx: None,
+            function_index: None,
+            tail_call_entered: None,
             memory32_size_sig: None,
+            memory64_size_sig: None,
             table_size_sig: None,
             memory_grow_sig: None,
+            memory64_grow_sig: None,
             table_grow_sig: None,
             table_copy_sig: None,
             table_init_sig: None,
             elem_drop_sig: None,
             memory_copy_sig: None,
+            memory_copy_between_sig: None,
+            memory64_copy_sig: None,
             memory_fill_sig: None,
+            memory64_fill_sig: None,
             memory_init_sig: None,
+            memory64_init_sig: None,
             table_get_sig: None,
             table_set_sig: None,
             data_drop_sig: None,
+            memory_atomic_wait32_sig: None,
+            memory_atomic_wait64_sig: None,
+            memory_atomic_notify_sig: None,
             func_ref_sig: None,
             table_fill_sig: None,
             externref_inc_sig: None,
             externref_dec_sig: None,
+            tail_call_sig: None,
+            tail_call_entered_sig: None,
+            exception_thrown_sig: None,
+            throw_sig: None,
+            exception_catch_sig: None,
+            exception_matches_sig: None,
+            exception_payload_sig: None,
+            rethrow_sig: None,
+            exception_sig: None,
             offsets: VMOffsets::new(target_config.pointer_bytes(), module),
             memory_styles,
             table_styles,
+            features,
+            relaxed_simd_deterministic,
+            interruptible,
         }
     }
 
@@ -168,6 +278,28 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         })
     }
 
+    /// Traps with `Interrupt` if the store was interrupted, or if its epoch
+    /// reached the epoch deadline.
+    fn translate_interrupt_check(&mut self, mut pos: FuncCursor) {
+        let pointer_type = self.pointer_type();
+        let vmctx = self.vmctx(&mut pos.func);
+        let base = pos.ins().global_value(pointer_type, vmctx);
+        let mem_flags = ir::MemFlags::trusted();
+
+        let interrupts_offset = i32::try_from(self.offsets.vmctx_interrupts()).unwrap();
+        let interrupts = pos
+            .ins()
+            .load(pointer_type, mem_flags, base, interrupts_offset);
+        let epoch_offset = i32::from(self.offsets.vminterrupts_epoch());
+        let epoch = pos.ins().load(I64, mem_flags, interrupts, epoch_offset);
+        let deadline_offset = i32::from(self.offsets.vminterrupts_epoch_deadline());
+        let deadline = pos.ins().load(I64, mem_flags, interrupts, deadline_offset);
+        let interrupted = pos
+            .ins()
+            .icmp(IntCC::UnsignedGreaterThanOrEqual, epoch, deadline);
+        pos.ins().trapnz(interrupted, ir::TrapCode::Interrupt);
+    }
+
     fn get_table_fill_sig(&mut self, func: &mut Function) -> ir::SigRef {
         let sig = self.table_fill_sig.unwrap_or_else(|| {
             func.import_signature(Signature {
@@ -386,19 +518,37 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         }
     }
 
-    fn get_memory_grow_sig(&mut self, func: &mut Function) -> ir::SigRef {
-        let sig = self.memory_grow_sig.unwrap_or_else(|| {
+    /// The type of the addresses used to index into the given memory.
+    fn memory_index_type(&self, index: MemoryIndex) -> ir::Type {
+        if self.module.memories[index].memory64 {
+            I64
+        } else {
+            I32
+        }
+    }
+
+    fn get_memory_grow_sig(&mut self, func: &mut Function, index_type: ir::Type) -> ir::SigRef {
+        let cached = if index_type == I64 {
+            self.memory64_grow_sig
+        } else {
+            self.memory_grow_sig
+        };
+        let sig = cached.unwrap_or_else(|| {
             func.import_signature(Signature {
                 params: vec![
                     AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
-                    AbiParam::new(I32),
+                    AbiParam::new(index_type),
                     AbiParam::new(I32),
                 ],
-                returns: vec![AbiParam::new(I32)],
+                returns: vec![AbiParam::new(index_type)],
                 call_conv: self.target_config.default_call_conv,
             })
         });
-        self.memory_grow_sig = Some(sig);
+        if index_type == I64 {
+            self.memory64_grow_sig = Some(sig);
+        } else {
+            self.memory_grow_sig = Some(sig);
+        }
         sig
     }
 
@@ -409,18 +559,29 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         func: &mut Function,
         index: MemoryIndex,
     ) -> (ir::SigRef, usize, VMBuiltinFunctionIndex) {
-        if self.module.is_imported_memory(index) {
-            (
-                self.get_memory_grow_sig(func),
+        let index_type = self.memory_index_type(index);
+        let sig = self.get_memory_grow_sig(func, index_type);
+        match (self.module.is_imported_memory(index), index_type == I64) {
+            (true, false) => (
+                sig,
                 index.index(),
                 VMBuiltinFunctionIndex::get_imported_memory32_grow_index(),
-            )
-        } else {
-            (
-                self.get_memory_grow_sig(func),
+            ),
+            (true, true) => (
+                sig,
+                index.index(),
+                VMBuiltinFunctionIndex::get_imported_memory64_grow_index(),
+            ),
+            (false, false) => (
+                sig,
                 self.module.local_memory_index(index).unwrap().index(),
                 VMBuiltinFunctionIndex::get_memory32_grow_index(),
-            )
+            ),
+            (false, true) => (
+                sig,
+                self.module.local_memory_index(index).unwrap().index(),
+                VMBuiltinFunctionIndex::get_memory64_grow_index(),
+            ),
         }
     }
 
@@ -461,18 +622,27 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         }
     }
 
-    fn get_memory32_size_sig(&mut self, func: &mut Function) -> ir::SigRef {
-        let sig = self.memory32_size_sig.unwrap_or_else(|| {
+    fn get_memory_size_sig(&mut self, func: &mut Function, index_type: ir::Type) -> ir::SigRef {
+        let cached = if index_type == I64 {
+            self.memory64_size_sig
+        } else {
+            self.memory32_size_sig
+        };
+        let sig = cached.unwrap_or_else(|| {
             func.import_signature(Signature {
                 params: vec![
                     AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
                     AbiParam::new(I32),
                 ],
-                returns: vec![AbiParam::new(I32)],
+                returns: vec![AbiParam::new(index_type)],
                 call_conv: self.target_config.default_call_conv,
             })
         });
-        self.memory32_size_sig = Some(sig);
+        if index_type == I64 {
+            self.memory64_size_sig = Some(sig);
+        } else {
+            self.memory32_size_sig = Some(sig);
+        }
         sig
     }
 
@@ -483,18 +653,29 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         func: &mut Function,
         index: MemoryIndex,
     ) -> (ir::SigRef, usize, VMBuiltinFunctionIndex) {
-        if self.module.is_imported_memory(index) {
-            (
-                self.get_memory32_size_sig(func),
+        let index_type = self.memory_index_type(index);
+        let sig = self.get_memory_size_sig(func, index_type);
+        match (self.module.is_imported_memory(index), index_type == I64) {
+            (true, false) => (
+                sig,
                 index.index(),
                 VMBuiltinFunctionIndex::get_imported_memory32_size_index(),
-            )
-        } else {
-            (
-                self.get_memory32_size_sig(func),
+            ),
+            (true, true) => (
+                sig,
+                index.index(),
+                VMBuiltinFunctionIndex::get_imported_memory64_size_index(),
+            ),
+            (false, false) => (
+                sig,
                 self.module.local_memory_index(index).unwrap().index(),
                 VMBuiltinFunctionIndex::get_memory32_size_index(),
-            )
+            ),
+            (false, true) => (
+                sig,
+                self.module.local_memory_index(index).unwrap().index(),
+                VMBuiltinFunctionIndex::get_memory64_size_index(),
+            ),
         }
     }
 
@@ -596,25 +777,115 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         (sig, VMBuiltinFunctionIndex::get_elem_drop_index())
     }
 
-    fn get_memory_copy_sig(&mut self, func: &mut Function) -> ir::SigRef {
-        let sig = self.memory_copy_sig.unwrap_or_else(|| {
+    fn get_memory_atomic_wait_sig(&mut self, func: &mut Function, ty: ir::Type) -> ir::SigRef {
+        let cached = if ty == I64 {
+            self.memory_atomic_wait64_sig
+        } else {
+            self.memory_atomic_wait32_sig
+        };
+        let sig = cached.unwrap_or_else(|| {
             func.import_signature(Signature {
                 params: vec![
                     AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
                     // Memory index.
                     AbiParam::new(I32),
-                    // Destination address.
+                    // Effective address.
+                    AbiParam::new(I64),
+                    // Expected value.
+                    AbiParam::new(ty),
+                    // Timeout.
+                    AbiParam::new(I64),
+                ],
+                returns: vec![AbiParam::new(I32)],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        if ty == I64 {
+            self.memory_atomic_wait64_sig = Some(sig);
+        } else {
+            self.memory_atomic_wait32_sig = Some(sig);
+        }
+        sig
+    }
+
+    fn get_memory_atomic_wait_func(
+        &mut self,
+        func: &mut Function,
+        ty: ir::Type,
+    ) -> (ir::SigRef, VMBuiltinFunctionIndex) {
+        let sig = self.get_memory_atomic_wait_sig(func, ty);
+        if ty == I64 {
+            (
+                sig,
+                VMBuiltinFunctionIndex::get_memory_atomic_wait64_index(),
+            )
+        } else {
+            (
+                sig,
+                VMBuiltinFunctionIndex::get_memory_atomic_wait32_index(),
+            )
+        }
+    }
+
+    fn get_memory_atomic_notify_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.memory_atomic_notify_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Memory index.
                     AbiParam::new(I32),
-                    // Source address.
+                    // Effective address.
+                    AbiParam::new(I64),
+                    // Count.
                     AbiParam::new(I32),
-                    // Length.
+                ],
+                returns: vec![AbiParam::new(I32)],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.memory_atomic_notify_sig = Some(sig);
+        sig
+    }
+
+    fn get_memory_atomic_notify_func(
+        &mut self,
+        func: &mut Function,
+    ) -> (ir::SigRef, VMBuiltinFunctionIndex) {
+        let sig = self.get_memory_atomic_notify_sig(func);
+        (
+            sig,
+            VMBuiltinFunctionIndex::get_memory_atomic_notify_index(),
+        )
+    }
+
+    fn get_memory_copy_sig(&mut self, func: &mut Function, index_type: ir::Type) -> ir::SigRef {
+        let cached = if index_type == I64 {
+            self.memory64_copy_sig
+        } else {
+            self.memory_copy_sig
+        };
+        let sig = cached.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Memory index.
                     AbiParam::new(I32),
+                    // Destination address.
+                    AbiParam::new(index_type),
+                    // Source address.
+                    AbiParam::new(index_type),
+                    // Length.
+                    AbiParam::new(index_type),
                 ],
                 returns: vec![],
                 call_conv: self.target_config.default_call_conv,
             })
         });
-        self.memory_copy_sig = Some(sig);
+        if index_type == I64 {
+            self.memory64_copy_sig = Some(sig);
+        } else {
+            self.memory_copy_sig = Some(sig);
+        }
         sig
     }
 
@@ -623,41 +894,87 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         func: &mut Function,
         memory_index: MemoryIndex,
     ) -> (ir::SigRef, usize, VMBuiltinFunctionIndex) {
-        let sig = self.get_memory_copy_sig(func);
-        if let Some(local_memory_index) = self.module.local_memory_index(memory_index) {
-            (
+        let index_type = self.memory_index_type(memory_index);
+        let sig = self.get_memory_copy_sig(func, index_type);
+        match (
+            self.module.local_memory_index(memory_index),
+            index_type == I64,
+        ) {
+            (Some(local_memory_index), false) => (
                 sig,
                 local_memory_index.index(),
                 VMBuiltinFunctionIndex::get_memory_copy_index(),
-            )
-        } else {
-            (
+            ),
+            (Some(local_memory_index), true) => (
+                sig,
+                local_memory_index.index(),
+                VMBuiltinFunctionIndex::get_memory64_copy_index(),
+            ),
+            (None, false) => (
                 sig,
                 memory_index.index(),
                 VMBuiltinFunctionIndex::get_imported_memory_copy_index(),
-            )
+            ),
+            (None, true) => (
+                sig,
+                memory_index.index(),
+                VMBuiltinFunctionIndex::get_imported_memory64_copy_index(),
+            ),
         }
     }
 
-    fn get_memory_fill_sig(&mut self, func: &mut Function) -> ir::SigRef {
-        let sig = self.memory_fill_sig.unwrap_or_else(|| {
+    fn get_memory_copy_between_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.memory_copy_between_sig.unwrap_or_else(|| {
             func.import_signature(Signature {
                 params: vec![
                     AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
-                    // Memory index.
+                    // Destination memory index.
+                    AbiParam::new(I32),
+                    // Source memory index.
                     AbiParam::new(I32),
                     // Destination address.
+                    AbiParam::new(I64),
+                    // Source address.
+                    AbiParam::new(I64),
+                    // Length.
+                    AbiParam::new(I64),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.memory_copy_between_sig = Some(sig);
+        sig
+    }
+
+    fn get_memory_fill_sig(&mut self, func: &mut Function, index_type: ir::Type) -> ir::SigRef {
+        let cached = if index_type == I64 {
+            self.memory64_fill_sig
+        } else {
+            self.memory_fill_sig
+        };
+        let sig = cached.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Memory index.
                     AbiParam::new(I32),
+                    // Destination address.
+                    AbiParam::new(index_type),
                     // Value.
                     AbiParam::new(I32),
                     // Length.
-                    AbiParam::new(I32),
+                    AbiParam::new(index_type),
                 ],
                 returns: vec![],
                 call_conv: self.target_config.default_call_conv,
             })
         });
-        self.memory_fill_sig = Some(sig);
+        if index_type == I64 {
+            self.memory64_fill_sig = Some(sig);
+        } else {
+            self.memory_fill_sig = Some(sig);
+        }
         sig
     }
 
@@ -666,73 +983,449 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         func: &mut Function,
         memory_index: MemoryIndex,
     ) -> (ir::SigRef, usize, VMBuiltinFunctionIndex) {
-        let sig = self.get_memory_fill_sig(func);
-        if let Some(local_memory_index) = self.module.local_memory_index(memory_index) {
-            (
+        let index_type = self.memory_index_type(memory_index);
+        let sig = self.get_memory_fill_sig(func, index_type);
+        match (
+            self.module.local_memory_index(memory_index),
+            index_type == I64,
+        ) {
+            (Some(local_memory_index), false) => (
                 sig,
                 local_memory_index.index(),
                 VMBuiltinFunctionIndex::get_memory_fill_index(),
-            )
-        } else {
-            (
+            ),
+            (Some(local_memory_index), true) => (
+                sig,
+                local_memory_index.index(),
+                VMBuiltinFunctionIndex::get_memory64_fill_index(),
+            ),
+            (None, false) => (
                 sig,
                 memory_index.index(),
                 VMBuiltinFunctionIndex::get_imported_memory_fill_index(),
-            )
+            ),
+            (None, true) => (
+                sig,
+                memory_index.index(),
+                VMBuiltinFunctionIndex::get_imported_memory64_fill_index(),
+            ),
         }
     }
 
-    fn get_memory_init_sig(&mut self, func: &mut Function) -> ir::SigRef {
-        let sig = self.memory_init_sig.unwrap_or_else(|| {
-            func.import_signature(Signature {
-                params: vec![
-                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
-                    // Memory index.
-                    AbiParam::new(I32),
-                    // Data index.
-                    AbiParam::new(I32),
-                    // Destination address.
-                    AbiParam::new(I32),
-                    // Source index within the data segment.
-                    AbiParam::new(I32),
-                    // Length.
-                    AbiParam::new(I32),
-                ],
-                returns: vec![],
-                call_conv: self.target_config.default_call_conv,
-            })
-        });
-        self.memory_init_sig = Some(sig);
-        sig
-    }
+    fn get_memory_init_sig(&mut self, func: &mut Function, index_type: ir::Type) -> ir::SigRef {
+        let cached = if index_type == I64 {
+            self.memory64_init_sig
+        } else {
+            self.memory_init_sig
+        };
+        let sig = cached.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Memory index.
+                    AbiParam::new(I32),
+                    // Data index.
+                    AbiParam::new(I32),
+                    // Destination address.
+                    AbiParam::new(index_type),
+                    // Source index within the data segment.
+                    AbiParam::new(I32),
+                    // Length.
+                    AbiParam::new(I32),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        if index_type == I64 {
+            self.memory64_init_sig = Some(sig);
+        } else {
+            self.memory_init_sig = Some(sig);
+        }
+        sig
+    }
+
+    fn get_memory_init_func(
+        &mut self,
+        func: &mut Function,
+        memory_index: MemoryIndex,
+    ) -> (ir::SigRef, VMBuiltinFunctionIndex) {
+        let index_type = self.memory_index_type(memory_index);
+        let sig = self.get_memory_init_sig(func, index_type);
+        if index_type == I64 {
+            (sig, VMBuiltinFunctionIndex::get_memory64_init_index())
+        } else {
+            (sig, VMBuiltinFunctionIndex::get_memory_init_index())
+        }
+    }
+
+    fn get_data_drop_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.data_drop_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Data index.
+                    AbiParam::new(I32),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.data_drop_sig = Some(sig);
+        sig
+    }
+
+    fn get_data_drop_func(&mut self, func: &mut Function) -> (ir::SigRef, VMBuiltinFunctionIndex) {
+        let sig = self.get_data_drop_sig(func);
+        (sig, VMBuiltinFunctionIndex::get_data_drop_index())
+    }
+
+    fn get_tail_call_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.tail_call_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Signature index of the callee.
+                    AbiParam::new(I32),
+                    // Callee body.
+                    AbiParam::new(self.pointer_type()),
+                    // Callee vmctx.
+                    AbiParam::new(self.pointer_type()),
+                    // Arguments and results.
+                    AbiParam::new(self.pointer_type()),
+                    // Whether the caller was called by a tail call.
+                    AbiParam::new(I32),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.tail_call_sig = Some(sig);
+        sig
+    }
+
+    fn get_tail_call_entered_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.tail_call_entered_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    // Body of the function.
+                    AbiParam::new(self.pointer_type()),
+                ],
+                returns: vec![AbiParam::new(I32)],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.tail_call_entered_sig = Some(sig);
+        sig
+    }
+
+    fn get_exception_thrown_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.exception_thrown_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![],
+                returns: vec![AbiParam::new(I32)],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.exception_thrown_sig = Some(sig);
+        sig
+    }
+
+    fn get_throw_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.throw_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Tag index.
+                    AbiParam::new(I32),
+                    // Values the exception carries.
+                    AbiParam::new(self.pointer_type()),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.throw_sig = Some(sig);
+        sig
+    }
+
+    fn get_exception_catch_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.exception_catch_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![],
+                returns: vec![AbiParam::new(self.pointer_type())],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.exception_catch_sig = Some(sig);
+        sig
+    }
+
+    fn get_exception_matches_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.exception_matches_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Exception.
+                    AbiParam::new(self.pointer_type()),
+                    // Tag index.
+                    AbiParam::new(I32),
+                ],
+                returns: vec![AbiParam::new(I32)],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.exception_matches_sig = Some(sig);
+        sig
+    }
+
+    fn get_exception_payload_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.exception_payload_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    // Exception.
+                    AbiParam::new(self.pointer_type()),
+                    // Values the exception carries.
+                    AbiParam::new(self.pointer_type()),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.exception_payload_sig = Some(sig);
+        sig
+    }
+
+    fn get_rethrow_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.rethrow_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Exception.
+                    AbiParam::new(self.pointer_type()),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.rethrow_sig = Some(sig);
+        sig
+    }
+
+    fn get_exception_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.exception_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    // Exception.
+                    AbiParam::new(self.pointer_type()),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.exception_sig = Some(sig);
+        sig
+    }
+
+    /// Creates a stack slot with room for `num_values` values of an
+    /// exception, and returns its address.
+    fn make_exception_values(&mut self, pos: &mut FuncCursor<'_>, num_values: usize) -> ir::Value {
+        let value_size = mem::size_of::<u128>() as u32;
+        let values_slot = pos.func.create_stack_slot(ir::StackSlotData::new(
+            ir::StackSlotKind::ExplicitSlot,
+            num_values.max(1) as u32 * value_size,
+        ));
+        pos.ins().stack_addr(self.pointer_type(), values_slot, 0)
+    }
+
+    /// Returns whether the function was called by a tail call, which is
+    /// checked on entry to the functions that make tail calls.
+    fn translate_tail_call_entered(&mut self, func: &mut Function) -> WasmResult<ir::Value> {
+        if let Some(entered) = self.tail_call_entered {
+            return Ok(entered);
+        }
+
+        // The check must come before anything else the function does, so
+        // it goes at the start of the entry block.
+        let function_index = self.function_index.unwrap();
+        let function = self.make_direct_func(func, function_index)?;
+        let func_sig = self.get_tail_call_entered_sig(func);
+        let entry_block = func.layout.entry_block().unwrap();
+        let mut pos = FuncCursor::new(func).at_first_insertion_point(entry_block);
+        let body = pos.ins().func_addr(self.pointer_type(), function);
+        let (_, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_tail_call_entered_index(),
+        );
+        let call_inst = pos.ins().call_indirect(func_sig, func_addr, &[body]);
+        let entered = *pos.func.dfg.inst_results(call_inst).first().unwrap();
+        self.tail_call_entered = Some(entered);
+        Ok(entered)
+    }
+
+    /// Translates a tail call to `callee`, of the signature `sig_index`,
+    /// and returns the values the calling function returns.
+    fn translate_tail_call(
+        &mut self,
+        pos: &mut FuncCursor<'_>,
+        sig_index: SignatureIndex,
+        sig_ref: ir::SigRef,
+        callee: ir::Value,
+        callee_vmctx: ir::Value,
+        call_args: &[ir::Value],
+    ) -> WasmResult<Vec<ir::Value>> {
+        let func_type = &self.module.signatures[sig_index];
+        if func_type.params().len() > MAX_TAIL_CALL_VALUES
+            || func_type.results().len() > MAX_TAIL_CALL_VALUES
+        {
+            return Err(WasmError::Unsupported(format!(
+                "tail call to a function with more than {} parameters or results",
+                MAX_TAIL_CALL_VALUES
+            )));
+        }
+        let entered = self.translate_tail_call_entered(pos.func)?;
+        let pointer_type = self.pointer_type();
+
+        // The arguments and results are passed in memory, laid out like
+        // the ones of a function call trampoline.
+        let value_size = mem::size_of::<u128>() as u32;
+        let result_types = pos.func.dfg.signatures[sig_ref]
+            .returns
+            .iter()
+            .map(|result| result.value_type)
+            .collect::<Vec<_>>();
+        let num_values = call_args.len().max(result_types.len()).max(1) as u32;
+        let values_slot = pos.func.create_stack_slot(ir::StackSlotData::new(
+            ir::StackSlotKind::ExplicitSlot,
+            num_values * value_size,
+        ));
+        let values = pos.ins().stack_addr(pointer_type, values_slot, 0);
+        let mem_flags = ir::MemFlags::trusted();
+        for (i, arg) in call_args.iter().enumerate() {
+            pos.ins()
+                .store(mem_flags, *arg, values, (i as u32 * value_size) as i32);
+        }
+
+        let func_sig = self.get_tail_call_sig(pos.func);
+        let sig_index_arg = pos.ins().iconst(I32, sig_index.as_u32() as i64);
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+            pos,
+            VMBuiltinFunctionIndex::get_tail_call_index(),
+        );
+        pos.ins().call_indirect(
+            func_sig,
+            func_addr,
+            &[vmctx, sig_index_arg, callee, callee_vmctx, values, entered],
+        );
+
+        Ok(result_types
+            .into_iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                pos.ins()
+                    .load(ty, mem_flags, values, (i as u32 * value_size) as i32)
+            })
+            .collect())
+    }
+
+    /// Loads the body and the vmctx of the imported function `callee_index`.
+    fn translate_load_imported_callee(
+        &mut self,
+        pos: &mut FuncCursor<'_>,
+        callee_index: FunctionIndex,
+    ) -> (ir::Value, ir::Value) {
+        let pointer_type = self.pointer_type();
+        let vmctx = self.vmctx(&mut pos.func);
+        let base = pos.ins().global_value(pointer_type, vmctx);
+
+        let mem_flags = ir::MemFlags::trusted();
+
+        // Load the callee address.
+        let body_offset =
+            i32::try_from(self.offsets.vmctx_vmfunction_import_body(callee_index)).unwrap();
+        let func_addr = pos.ins().load(pointer_type, mem_flags, base, body_offset);
+
+        // Load the callee vmctx address.
+        let vmctx_offset =
+            i32::try_from(self.offsets.vmctx_vmfunction_import_vmctx(callee_index)).unwrap();
+        let vmctx = pos.ins().load(pointer_type, mem_flags, base, vmctx_offset);
+
+        (func_addr, vmctx)
+    }
+
+    /// Loads the body and the vmctx of the function `table` holds at `callee`,
+    /// trapping if it doesn't have the signature `sig_index`.
+    fn translate_load_table_callee(
+        &mut self,
+        pos: &mut FuncCursor<'_>,
+        table_index: TableIndex,
+        table: ir::Table,
+        sig_index: SignatureIndex,
+        callee: ir::Value,
+    ) -> (ir::Value, ir::Value) {
+        let pointer_type = self.pointer_type();
+
+        let table_entry_addr = pos.ins().table_addr(pointer_type, table, callee, 0);
+
+        // Dereference table_entry_addr to get the function address.
+        let mem_flags = ir::MemFlags::trusted();
+        let table_entry_addr = pos.ins().load(
+            pointer_type,
+            mem_flags,
+            table_entry_addr,
+            i32::from(self.offsets.vm_funcref_anyfunc_ptr()),
+        );
+
+        // check if the funcref is null
+        pos.ins()
+            .trapz(table_entry_addr, ir::TrapCode::IndirectCallToNull);
+
+        let func_addr = pos.ins().load(
+            pointer_type,
+            mem_flags,
+            table_entry_addr,
+            i32::from(self.offsets.vmcaller_checked_anyfunc_func_ptr()),
+        );
+
+        // If necessary, check the signature.
+        match self.table_styles[table_index] {
+            TableStyle::CallerChecksSignature => {
+                let sig_id_size = self.offsets.size_of_vmshared_signature_index();
+                let sig_id_type = ir::Type::int(u16::from(sig_id_size) * 8).unwrap();
+                let vmctx = self.vmctx(pos.func);
+                let base = pos.ins().global_value(pointer_type, vmctx);
+                let offset =
+                    i32::try_from(self.offsets.vmctx_vmshared_signature_id(sig_index)).unwrap();
+
+                // Load the caller ID.
+                let mut mem_flags = ir::MemFlags::trusted();
+                mem_flags.set_readonly();
+                let caller_sig_id = pos.ins().load(sig_id_type, mem_flags, base, offset);
+
+                // Load the callee ID.
+                let mem_flags = ir::MemFlags::trusted();
+                let callee_sig_id = pos.ins().load(
+                    sig_id_type,
+                    mem_flags,
+                    table_entry_addr,
+                    i32::from(self.offsets.vmcaller_checked_anyfunc_type_index()),
+                );
 
-    fn get_memory_init_func(
-        &mut self,
-        func: &mut Function,
-    ) -> (ir::SigRef, VMBuiltinFunctionIndex) {
-        let sig = self.get_memory_init_sig(func);
-        (sig, VMBuiltinFunctionIndex::get_memory_init_index())
-    }
+                // Check that they match.
+                let cmp = pos.ins().icmp(IntCC::Equal, callee_sig_id, caller_sig_id);
+                pos.ins().trapz(cmp, ir::TrapCode::BadSignature);
+            }
+        }
 
-    fn get_data_drop_sig(&mut self, func: &mut Function) -> ir::SigRef {
-        let sig = self.data_drop_sig.unwrap_or_else(|| {
-            func.import_signature(Signature {
-                params: vec![
-                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
-                    // Data index.
-                    AbiParam::new(I32),
-                ],
-                returns: vec![],
-                call_conv: self.target_config.default_call_conv,
-            })
-        });
-        self.data_drop_sig = Some(sig);
-        sig
-    }
+        // Load the callee vmctx address.
+        let vmctx = pos.ins().load(
+            pointer_type,
+            mem_flags,
+            table_entry_addr,
+            i32::from(self.offsets.vmcaller_checked_anyfunc_vmctx()),
+        );
 
-    fn get_data_drop_func(&mut self, func: &mut Function) -> (ir::SigRef, VMBuiltinFunctionIndex) {
-        let sig = self.get_data_drop_sig(func);
-        (sig, VMBuiltinFunctionIndex::get_data_drop_index())
+        (func_addr, vmctx)
     }
 
     /// Translates load of builtin function and returns a pair of values `vmctx`
@@ -1078,7 +1771,7 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
             min_size: 0.into(),
             offset_guard_size,
             style: heap_style,
-            index_type: I32,
+            index_type: self.memory_index_type(index),
         }))
     }
 
@@ -1146,69 +1839,12 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         callee: ir::Value,
         call_args: &[ir::Value],
     ) -> WasmResult<ir::Inst> {
-        let pointer_type = self.pointer_type();
-
-        let table_entry_addr = pos.ins().table_addr(pointer_type, table, callee, 0);
-
-        // Dereference table_entry_addr to get the function address.
-        let mem_flags = ir::MemFlags::trusted();
-        let table_entry_addr = pos.ins().load(
-            pointer_type,
-            mem_flags,
-            table_entry_addr,
-            i32::from(self.offsets.vm_funcref_anyfunc_ptr()),
-        );
-
-        // check if the funcref is null
-        pos.ins()
-            .trapz(table_entry_addr, ir::TrapCode::IndirectCallToNull);
-
-        let func_addr = pos.ins().load(
-            pointer_type,
-            mem_flags,
-            table_entry_addr,
-            i32::from(self.offsets.vmcaller_checked_anyfunc_func_ptr()),
-        );
-
-        // If necessary, check the signature.
-        match self.table_styles[table_index] {
-            TableStyle::CallerChecksSignature => {
-                let sig_id_size = self.offsets.size_of_vmshared_signature_index();
-                let sig_id_type = ir::Type::int(u16::from(sig_id_size) * 8).unwrap();
-                let vmctx = self.vmctx(pos.func);
-                let base = pos.ins().global_value(pointer_type, vmctx);
-                let offset =
-                    i32::try_from(self.offsets.vmctx_vmshared_signature_id(sig_index)).unwrap();
-
-                // Load the caller ID.
-                let mut mem_flags = ir::MemFlags::trusted();
-                mem_flags.set_readonly();
-                let caller_sig_id = pos.ins().load(sig_id_type, mem_flags, base, offset);
-
-                // Load the callee ID.
-                let mem_flags = ir::MemFlags::trusted();
-                let callee_sig_id = pos.ins().load(
-                    sig_id_type,
-                    mem_flags,
-                    table_entry_addr,
-                    i32::from(self.offsets.vmcaller_checked_anyfunc_type_index()),
-                );
-
-                // Check that they match.
-                let cmp = pos.ins().icmp(IntCC::Equal, callee_sig_id, caller_sig_id);
-                pos.ins().trapz(cmp, ir::TrapCode::BadSignature);
-            }
-        }
+        let (func_addr, vmctx) =
+            self.translate_load_table_callee(&mut pos, table_index, table, sig_index, callee);
 
         let mut real_call_args = Vec::with_capacity(call_args.len() + 2);
 
         // First append the callee vmctx address.
-        let vmctx = pos.ins().load(
-            pointer_type,
-            mem_flags,
-            table_entry_addr,
-            i32::from(self.offsets.vmcaller_checked_anyfunc_vmctx()),
-        );
         real_call_args.push(vmctx);
 
         // Then append the regular call arguments.
@@ -1242,22 +1878,10 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
 
         // Handle direct calls to imported functions. We use an indirect call
         // so that we don't have to patch the code at runtime.
-        let pointer_type = self.pointer_type();
         let sig_ref = pos.func.dfg.ext_funcs[callee].signature;
-        let vmctx = self.vmctx(&mut pos.func);
-        let base = pos.ins().global_value(pointer_type, vmctx);
-
-        let mem_flags = ir::MemFlags::trusted();
-
-        // Load the callee address.
-        let body_offset =
-            i32::try_from(self.offsets.vmctx_vmfunction_import_body(callee_index)).unwrap();
-        let func_addr = pos.ins().load(pointer_type, mem_flags, base, body_offset);
+        let (func_addr, vmctx) = self.translate_load_imported_callee(&mut pos, callee_index);
 
         // First append the callee vmctx address.
-        let vmctx_offset =
-            i32::try_from(self.offsets.vmctx_vmfunction_import_vmctx(callee_index)).unwrap();
-        let vmctx = pos.ins().load(pointer_type, mem_flags, base, vmctx_offset);
         real_call_args.push(vmctx);
 
         // Then append the regular call arguments.
@@ -1266,6 +1890,198 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         Ok(pos.ins().call_indirect(sig_ref, func_addr, &real_call_args))
     }
 
+    fn translate_return_call_indirect(
+        &mut self,
+        mut pos: FuncCursor<'_>,
+        table_index: TableIndex,
+        table: ir::Table,
+        sig_index: SignatureIndex,
+        sig_ref: ir::SigRef,
+        callee: ir::Value,
+        call_args: &[ir::Value],
+    ) -> WasmResult<Vec<ir::Value>> {
+        let (func_addr, vmctx) =
+            self.translate_load_table_callee(&mut pos, table_index, table, sig_index, callee);
+        self.translate_tail_call(&mut pos, sig_index, sig_ref, func_addr, vmctx, call_args)
+    }
+
+    fn translate_return_call(
+        &mut self,
+        mut pos: FuncCursor<'_>,
+        callee_index: FunctionIndex,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> WasmResult<Vec<ir::Value>> {
+        let sig_index = self.module.functions[callee_index];
+        let sig_ref = pos.func.dfg.ext_funcs[callee].signature;
+        let (func_addr, vmctx) = if self.module.is_imported_function(callee_index) {
+            self.translate_load_imported_callee(&mut pos, callee_index)
+        } else {
+            let pointer_type = self.pointer_type();
+            let func_addr = pos.ins().func_addr(pointer_type, callee);
+            let vmctx = pos.func.special_param(ArgumentPurpose::VMContext).unwrap();
+            (func_addr, vmctx)
+        };
+        self.translate_tail_call(&mut pos, sig_index, sig_ref, func_addr, vmctx, call_args)
+    }
+
+    fn relaxed_simd_deterministic(&self) -> bool {
+        self.relaxed_simd_deterministic
+    }
+
+    fn exceptions_enabled(&self) -> bool {
+        self.features.exceptions
+    }
+
+    fn translate_load_thrown_exceptions(&mut self, mut pos: FuncCursor) -> WasmResult<ir::Value> {
+        let pointer_type = self.pointer_type();
+        let vmctx = self.vmctx(&mut pos.func);
+        let base = pos.ins().global_value(pointer_type, vmctx);
+
+        let mut mem_flags = ir::MemFlags::trusted();
+        mem_flags.set_readonly();
+        let thrown_offset = i32::try_from(self.offsets.vmctx_thrown_exceptions()).unwrap();
+        let thrown = pos.ins().load(pointer_type, mem_flags, base, thrown_offset);
+        // The count changes behind our back, so it isn't read-only.
+        Ok(pos
+            .ins()
+            .load(pointer_type, ir::MemFlags::trusted(), thrown, 0))
+    }
+
+    fn translate_exception_thrown(&mut self, mut pos: FuncCursor) -> WasmResult<ir::Value> {
+        let func_sig = self.get_exception_thrown_sig(&mut pos.func);
+        let (_, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_exception_thrown_index(),
+        );
+        let call_inst = pos.ins().call_indirect(func_sig, func_addr, &[]);
+        Ok(*pos.func.dfg.inst_results(call_inst).first().unwrap())
+    }
+
+    fn translate_throw(
+        &mut self,
+        mut pos: FuncCursor,
+        tag_index: TagIndex,
+        args: &[ir::Value],
+    ) -> WasmResult<()> {
+        let value_size = mem::size_of::<u128>() as u32;
+        let values = self.make_exception_values(&mut pos, args.len());
+        let mem_flags = ir::MemFlags::trusted();
+        for (i, arg) in args.iter().enumerate() {
+            pos.ins()
+                .store(mem_flags, *arg, values, (i as u32 * value_size) as i32);
+        }
+
+        let func_sig = self.get_throw_sig(&mut pos.func);
+        let tag_index_arg = pos.ins().iconst(I32, tag_index.as_u32() as i64);
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_throw_index(),
+        );
+        pos.ins()
+            .call_indirect(func_sig, func_addr, &[vmctx, tag_index_arg, values]);
+
+        Ok(())
+    }
+
+    fn translate_exception_catch(&mut self, mut pos: FuncCursor) -> WasmResult<ir::Value> {
+        let func_sig = self.get_exception_catch_sig(&mut pos.func);
+        let (_, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_exception_catch_index(),
+        );
+        let call_inst = pos.ins().call_indirect(func_sig, func_addr, &[]);
+        Ok(*pos.func.dfg.inst_results(call_inst).first().unwrap())
+    }
+
+    fn translate_exception_matches(
+        &mut self,
+        mut pos: FuncCursor,
+        exception: ir::Value,
+        tag_index: TagIndex,
+    ) -> WasmResult<ir::Value> {
+        let func_sig = self.get_exception_matches_sig(&mut pos.func);
+        let tag_index_arg = pos.ins().iconst(I32, tag_index.as_u32() as i64);
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_exception_matches_index(),
+        );
+        let call_inst =
+            pos.ins()
+                .call_indirect(func_sig, func_addr, &[vmctx, exception, tag_index_arg]);
+        Ok(*pos.func.dfg.inst_results(call_inst).first().unwrap())
+    }
+
+    fn translate_exception_payload(
+        &mut self,
+        mut pos: FuncCursor,
+        exception: ir::Value,
+        tag_index: TagIndex,
+    ) -> WasmResult<Vec<ir::Value>> {
+        let param_types = self.module.tag_type(tag_index).params().to_vec();
+        let value_size = mem::size_of::<u128>() as u32;
+        let values = self.make_exception_values(&mut pos, param_types.len());
+
+        let func_sig = self.get_exception_payload_sig(&mut pos.func);
+        let (_, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_exception_payload_index(),
+        );
+        pos.ins()
+            .call_indirect(func_sig, func_addr, &[exception, values]);
+
+        let mem_flags = ir::MemFlags::trusted();
+        param_types
+            .into_iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                let ty = type_to_irtype(ty, self.target_config())?;
+                Ok(pos
+                    .ins()
+                    .load(ty, mem_flags, values, (i as u32 * value_size) as i32))
+            })
+            .collect()
+    }
+
+    fn translate_rethrow(&mut self, mut pos: FuncCursor, exception: ir::Value) -> WasmResult<()> {
+        let func_sig = self.get_rethrow_sig(&mut pos.func);
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_rethrow_index(),
+        );
+        pos.ins()
+            .call_indirect(func_sig, func_addr, &[vmctx, exception]);
+        Ok(())
+    }
+
+    fn translate_exception_drop(
+        &mut self,
+        mut pos: FuncCursor,
+        exception: ir::Value,
+    ) -> WasmResult<()> {
+        let func_sig = self.get_exception_sig(&mut pos.func);
+        let (_, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_exception_drop_index(),
+        );
+        pos.ins().call_indirect(func_sig, func_addr, &[exception]);
+        Ok(())
+    }
+
+    fn translate_function_entry(&mut self, pos: FuncCursor) -> WasmResult<()> {
+        if self.interruptible {
+            self.translate_interrupt_check(pos);
+        }
+        Ok(())
+    }
+
+    fn translate_loop_header(&mut self, pos: FuncCursor) -> WasmResult<()> {
+        if self.interruptible {
+            self.translate_interrupt_check(pos);
+        }
+        Ok(())
+    }
+
     fn translate_memory_grow(
         &mut self,
         mut pos: FuncCursor<'_>,
@@ -1302,12 +2118,37 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         mut pos: FuncCursor,
         src_index: MemoryIndex,
         _src_heap: ir::Heap,
-        _dst_index: MemoryIndex,
+        dst_index: MemoryIndex,
         _dst_heap: ir::Heap,
         dst: ir::Value,
         src: ir::Value,
         len: ir::Value,
     ) -> WasmResult<()> {
+        if src_index != dst_index {
+            let func_sig = self.get_memory_copy_between_sig(&mut pos.func);
+            let func_idx = VMBuiltinFunctionIndex::get_memory_copy_between_index();
+            let dst_index_arg = pos.ins().iconst(I32, dst_index.index() as i64);
+            let src_index_arg = pos.ins().iconst(I32, src_index.index() as i64);
+            // The two memories may have different index types, so every
+            // address and the length are passed as 64-bit values.
+            let mut extend = |value: ir::Value| {
+                if pos.func.dfg.value_type(value) == I32 {
+                    pos.ins().uextend(I64, value)
+                } else {
+                    value
+                }
+            };
+            let (dst, src, len) = (extend(dst), extend(src), extend(len));
+            let (vmctx, func_addr) =
+                self.translate_load_builtin_function_address(&mut pos, func_idx);
+            pos.ins().call_indirect(
+                func_sig,
+                func_addr,
+                &[vmctx, dst_index_arg, src_index_arg, dst, src, len],
+            );
+            return Ok(());
+        }
+
         let (func_sig, src_index, func_idx) = self.get_memory_copy_func(&mut pos.func, src_index);
 
         let src_index_arg = pos.ins().iconst(I32, src_index as i64);
@@ -1355,7 +2196,7 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         src: ir::Value,
         len: ir::Value,
     ) -> WasmResult<()> {
-        let (func_sig, func_idx) = self.get_memory_init_func(&mut pos.func);
+        let (func_sig, func_idx) = self.get_memory_init_func(&mut pos.func, memory_index);
 
         let memory_index_arg = pos.ins().iconst(I32, memory_index.index() as i64);
         let seg_index_arg = pos.ins().iconst(I32, seg_index as i64);
@@ -1472,29 +2313,58 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
 
     fn translate_atomic_wait(
         &mut self,
-        _pos: FuncCursor,
-        _index: MemoryIndex,
+        mut pos: FuncCursor,
+        memory_index: MemoryIndex,
         _heap: ir::Heap,
-        _addr: ir::Value,
-        _expected: ir::Value,
-        _timeout: ir::Value,
+        addr: ir::Value,
+        expected: ir::Value,
+        timeout: ir::Value,
     ) -> WasmResult<ir::Value> {
-        Err(WasmError::Unsupported(
-            "wasm atomics (fn translate_atomic_wait)".to_string(),
-        ))
+        let ty = pos.func.dfg.value_type(expected);
+        let (func_sig, func_idx) = self.get_memory_atomic_wait_func(&mut pos.func, ty);
+
+        let memory_index_arg = pos.ins().iconst(I32, memory_index.index() as i64);
+        let addr = if pos.func.dfg.value_type(addr) == I32 {
+            pos.ins().uextend(I64, addr)
+        } else {
+            addr
+        };
+
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(&mut pos, func_idx);
+
+        let call_inst = pos.ins().call_indirect(
+            func_sig,
+            func_addr,
+            &[vmctx, memory_index_arg, addr, expected, timeout],
+        );
+
+        Ok(*pos.func.dfg.inst_results(call_inst).first().unwrap())
     }
 
     fn translate_atomic_notify(
         &mut self,
-        _pos: FuncCursor,
-        _index: MemoryIndex,
+        mut pos: FuncCursor,
+        memory_index: MemoryIndex,
         _heap: ir::Heap,
-        _addr: ir::Value,
-        _count: ir::Value,
+        addr: ir::Value,
+        count: ir::Value,
     ) -> WasmResult<ir::Value> {
-        Err(WasmError::Unsupported(
-            "wasm atomics (fn translate_atomic_notify)".to_string(),
-        ))
+        let (func_sig, func_idx) = self.get_memory_atomic_notify_func(&mut pos.func);
+
+        let memory_index_arg = pos.ins().iconst(I32, memory_index.index() as i64);
+        let addr = if pos.func.dfg.value_type(addr) == I32 {
+            pos.ins().uextend(I64, addr)
+        } else {
+            addr
+        };
+
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(&mut pos, func_idx);
+
+        let call_inst =
+            pos.ins()
+                .call_indirect(func_sig, func_addr, &[vmctx, memory_index_arg, addr, count]);
+
+        Ok(*pos.func.dfg.inst_results(call_inst).first().unwrap())
     }
 
     fn get_global_type(&self, global_index: GlobalIndex) -> Option<WasmerType> {
@@ -1507,6 +2377,7 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
 
     fn push_params_on_stack(&mut self, function_index: LocalFunctionIndex) {
         let func_index = self.module.func_index(function_index);
+        self.function_index = Some(func_index);
         let sig_idx = self.module.functions[func_index];
         let signature = &self.module.signatures[sig_idx];
         for param in signature.params() {
@@ -1531,6 +2402,11 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         self.module.signatures.get(sig_index)
     }
 
+    fn get_tag_type(&self, tag_index: TagIndex) -> Option<&FunctionType> {
+        let sig_idx = self.module.tags.get(tag_index)?;
+        Some(&self.module.signatures[*sig_idx])
+    }
+
     fn translate_drop_locals(&mut self, builder: &mut FunctionBuilder) -> WasmResult<()> {
         // TODO: this allocation can be removed without too much effort but it will require
         //       maneuvering around the borrow checker
@@ -96,7 +96,8 @@ use wasmer_compiler::wasmparser::{MemoryImmediate, Operator, Type as WPType};
 use wasmer_compiler::WasmResult;
 use wasmer_compiler::{wasm_unsupported, ModuleTranslationState};
 use wasmer_types::{
-    FunctionIndex, GlobalIndex, MemoryIndex, SignatureIndex, TableIndex, Type as WasmerType,
+    FunctionIndex, GlobalIndex, MemoryIndex, SignatureIndex, TableIndex, TagIndex,
+    Type as WasmerType,
 };
 
 // Clippy warns about "align: _" but its important to document that the align field is ignored
@@ -431,6 +432,14 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
                 _ => unreachable!(),
             }
         }
+        Operator::End
+            if matches!(
+                state.control_stack.last(),
+                Some(ControlStackFrame::Try { .. })
+            ) =>
+        {
+            translate_end_try(None, builder, state, environ)?;
+        }
         Operator::End => {
             let frame = state.control_stack.pop().unwrap();
             let next_block = frame.following_code();
@@ -483,6 +492,7 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
          ***********************************************************************************/
         Operator::Br { relative_depth } => {
             let i = state.control_stack.len() - 1 - (*relative_depth as usize);
+            translate_exception_drops(i, builder, state, environ)?;
             let (return_count, br_destination) = {
                 let frame = &mut state.control_stack[i];
                 // We signal that all the code that follows until the next End is unreachable
@@ -499,7 +509,9 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             state.popn(return_count);
             state.reachable = false;
         }
-        Operator::BrIf { relative_depth } => translate_br_if(*relative_depth, builder, state),
+        Operator::BrIf { relative_depth } => {
+            translate_br_if(*relative_depth, builder, state, environ)?;
+        }
         Operator::BrTable { table } => {
             let default = table.default();
             let mut min_depth = default;
@@ -518,9 +530,16 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
                     min_depth_frame.num_return_values()
                 }
             };
+            // The exceptions caught by the handlers a target leaves are released on the way, in
+            // a block of its own.
+            let mut drops_exceptions = false;
+            for depth in table.targets().chain(Some(Ok(default))) {
+                let i = state.control_stack.len() - 1 - (depth? as usize);
+                drops_exceptions |= has_exception_drops(i, state);
+            }
             let (val, _) = state.pop1();
             let mut data = JumpTableData::with_capacity(table.len() as usize);
-            if jump_args_count == 0 {
+            if jump_args_count == 0 && !drops_exceptions {
                 // No jump arguments
                 for depth in table.targets() {
                     let depth = depth?;
@@ -571,8 +590,9 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
                 for (depth, dest_block) in dest_block_sequence {
                     builder.switch_to_block(dest_block);
                     builder.seal_block(dest_block);
+                    let i = state.control_stack.len() - 1 - depth;
+                    translate_exception_drops(i, builder, state, environ)?;
                     let real_dest_block = {
-                        let i = state.control_stack.len() - 1 - depth;
                         let frame = &mut state.control_stack[i];
                         frame.set_branched_to_exit();
                         frame.br_destination()
@@ -584,45 +604,45 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             }
             state.reachable = false;
         }
-        Operator::Return => {
-            let (return_count, br_destination) = {
-                let frame = &mut state.control_stack[0];
-                if environ.return_mode() == ReturnMode::FallthroughReturn {
-                    frame.set_branched_to_exit();
-                }
-                let return_count = frame.num_return_values();
-                (return_count, frame.br_destination())
-            };
-            {
-                let (return_args, return_args_metadata) = state.peekn_mut(return_count);
-                // TODO(reftypes): maybe ref count here?
-                let return_types = wasm_param_types(&builder.func.signature.returns, |i| {
-                    environ.is_wasm_return(&builder.func.signature, i)
-                });
-                bitcast_arguments(return_args, &return_types, builder);
-                match environ.return_mode() {
-                    ReturnMode::NormalReturns => builder.ins().return_(return_args),
-                    ReturnMode::FallthroughReturn => canonicalise_then_jump(
-                        builder,
-                        br_destination,
-                        (&*return_args, &*return_args_metadata),
-                    ),
-                };
-            }
-            state.popn(return_count);
+        Operator::Return => translate_return(builder, state, environ)?,
+        /********************************** Exception handing **********************************/
+        Operator::Try { ty } => {
+            let (params, results) = module_translation_state.blocktype_params_results(*ty)?;
+            let next = block_with_params(builder, results, environ)?;
+            state.push_try(next, params.len(), results.len());
+        }
+        Operator::Catch { index } => {
+            translate_catch(Some(TagIndex::from_u32(*index)), builder, state, environ)?;
+        }
+        Operator::CatchAll => translate_catch(None, builder, state, environ)?,
+        Operator::Delegate { relative_depth } => {
+            translate_end_try(Some(*relative_depth), builder, state, environ)?;
+        }
+        Operator::Throw { index } => {
+            let tag_index = TagIndex::from_u32(*index);
+            let num_args = environ.get_tag_type(tag_index).unwrap().params().len();
+            let (args, _args_metadata) = state.peekn(num_args);
+            environ.translate_throw(builder.cursor(), tag_index, args)?;
+            state.popn(num_args);
+            let i = state.control_stack.len() - 1;
+            let target = exception_target(i, builder, state);
+            builder.ins().jump(target, &[]);
             state.reachable = false;
         }
-        /********************************** Exception handing **********************************/
-        Operator::Try { .. }
-        | Operator::Catch { .. }
-        | Operator::Throw { .. }
-        | Operator::Rethrow { .. }
-        | Operator::Delegate { .. }
-        | Operator::CatchAll => {
-            return Err(wasm_unsupported!(
-                "proposed exception handling operator {:?}",
-                op
-            ));
+        Operator::Rethrow { relative_depth } => {
+            let i = state.control_stack.len() - 1 - (*relative_depth as usize);
+            let exception = match state.control_stack[i] {
+                ControlStackFrame::Try {
+                    exception: Some(exception),
+                    ..
+                } => exception,
+                _ => unreachable!(),
+            };
+            environ.translate_rethrow(builder.cursor(), exception)?;
+            let i = state.control_stack.len() - 1;
+            let target = exception_target(i, builder, state);
+            builder.ins().jump(target, &[]);
+            state.reachable = false;
         }
         /************************************ Calls ****************************************
          * The call instructions pop off their arguments from the stack and append their
@@ -666,6 +686,7 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             }
             state.popn(num_args);
             state.pushn(inst_results, &results_metadata);
+            translate_exception_check(builder, state, environ)?;
         }
         Operator::CallIndirect { index, table_index } => {
             // `index` is the index of the function's signature and `table_index` is the index of
@@ -714,14 +735,62 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             }
             state.popn(num_args);
             state.pushn(inst_results, &results_metadata);
+            translate_exception_check(builder, state, environ)?;
+        }
+        Operator::ReturnCall { function_index } => {
+            let (fref, num_args) = state.get_direct_func(builder.func, *function_index, environ)?;
+
+            let (args, _args_metadata) = state.peekn_mut(num_args);
+
+            // Bitcast any vector arguments to their default type, I8X16, before calling.
+            let callee_signature =
+                &builder.func.dfg.signatures[builder.func.dfg.ext_funcs[fref].signature];
+            let types = wasm_param_types(&callee_signature.params, |i| {
+                environ.is_wasm_parameter(&callee_signature, i)
+            });
+            bitcast_arguments(args, &types, builder);
+            let func_index = FunctionIndex::from_u32(*function_index);
+
+            let results =
+                environ.translate_return_call(builder.cursor(), func_index, fref, args)?;
+            state.popn(num_args);
+            state.pushn(&results, &vec![Default::default(); results.len()]);
+            translate_return(builder, state, environ)?;
+        }
+        Operator::ReturnCallIndirect { index, table_index } => {
+            // `index` is the index of the function's signature and `table_index` is the index of
+            // the table to search the function in.
+            let (sigref, num_args) = state.get_indirect_sig(builder.func, *index, environ)?;
+            let table = state.get_or_create_table(builder.func, *table_index, environ)?;
+            let (callee, _) = state.pop1();
+
+            // Bitcast any vector arguments to their default type, I8X16, before calling.
+            let callee_signature = &builder.func.dfg.signatures[sigref];
+            let (args, _) = state.peekn_mut(num_args);
+            let types = wasm_param_types(&callee_signature.params, |i| {
+                environ.is_wasm_parameter(&callee_signature, i)
+            });
+            bitcast_arguments(args, &types, builder);
+
+            let (args, _args_metadata) = state.peekn(num_args);
+            let results = environ.translate_return_call_indirect(
+                builder.cursor(),
+                TableIndex::from_u32(*table_index),
+                table,
+                SignatureIndex::from_u32(*index),
+                sigref,
+                callee,
+                args,
+            )?;
+            state.popn(num_args);
+            state.pushn(&results, &vec![Default::default(); results.len()]);
+            translate_return(builder, state, environ)?;
         }
         /******************************* Memory management ***********************************
          * Memory management is handled by environment. It is usually translated into calls to
          * special functions.
          ************************************************************************************/
         Operator::MemoryGrow { mem, mem_byte: _ } => {
-            // The WebAssembly MVP only supports one linear memory, but we expect the reserved
-            // argument to be a memory index.
             let heap_index = MemoryIndex::from_u32(*mem);
             let heap = state.get_heap(builder.func, *mem, environ)?;
             let (val, _) = state.pop1();
@@ -1138,9 +1207,6 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             state.push1(environ.translate_ref_func(builder.cursor(), index)?);
         }
         Operator::MemoryAtomicWait32 { memarg } | Operator::MemoryAtomicWait64 { memarg } => {
-            // The WebAssembly MVP only supports one linear memory and
-            // wasmparser will ensure that the memory indices specified are
-            // zero.
             let implied_ty = match op {
                 Operator::MemoryAtomicWait64 { .. } => I64,
                 Operator::MemoryAtomicWait32 { .. } => I32,
@@ -1625,7 +1691,7 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             // to WASM using the less specific v128 type for certain operations and more specific
             // types (e.g. i8x16) for others.
         }
-        Operator::I8x16Swizzle => {
+        Operator::I8x16Swizzle | Operator::I8x16RelaxedSwizzle => {
             let (a, b) = pop2_with_bitcast(state, I8X16, builder);
             state.push1(builder.ins().swizzle(I8X16, a, b))
         }
@@ -1732,7 +1798,11 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let b_mod_bitwidth = builder.ins().band_imm(b, bitwidth - 1);
             state.push1(builder.ins().sshr(bitcast_a, b_mod_bitwidth))
         }
-        Operator::V128Bitselect => {
+        Operator::V128Bitselect
+        | Operator::I8x16LaneSelect
+        | Operator::I16x8LaneSelect
+        | Operator::I32x4LaneSelect
+        | Operator::I64x2LaneSelect => {
             let ((a, _), (b, _), (c, _)) = state.pop3();
             let bitcast_a = optionally_bitcast_vector(a, I8X16, builder);
             let bitcast_b = optionally_bitcast_vector(b, I8X16, builder);
@@ -1844,6 +1914,37 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let (a, b) = pop2_with_bitcast(state, type_of(op), builder);
             state.push1(builder.ins().fmin_pseudo(a, b))
         }
+        Operator::F32x4RelaxedMax | Operator::F64x2RelaxedMax => {
+            let (a, b) = pop2_with_bitcast(state, type_of(op), builder);
+            if environ.relaxed_simd_deterministic() {
+                state.push1(builder.ins().fmax(a, b))
+            } else {
+                state.push1(builder.ins().fmax_pseudo(a, b))
+            }
+        }
+        Operator::F32x4RelaxedMin | Operator::F64x2RelaxedMin => {
+            let (a, b) = pop2_with_bitcast(state, type_of(op), builder);
+            if environ.relaxed_simd_deterministic() {
+                state.push1(builder.ins().fmin(a, b))
+            } else {
+                state.push1(builder.ins().fmin_pseudo(a, b))
+            }
+        }
+        Operator::F32x4Fma | Operator::F64x2Fma | Operator::F32x4Fms | Operator::F64x2Fms => {
+            let ((a, _), (b, _), (c, _)) = state.pop3();
+            let a = optionally_bitcast_vector(a, type_of(op), builder);
+            let b = optionally_bitcast_vector(b, type_of(op), builder);
+            let c = optionally_bitcast_vector(c, type_of(op), builder);
+            // Cranelift can't fuse vector multiply-adds, but the relaxed
+            // semantics allow the unfused result on every target.
+            let product = builder.ins().fmul(a, b);
+            match op {
+                Operator::F32x4Fma | Operator::F64x2Fma => {
+                    state.push1(builder.ins().fadd(product, c))
+                }
+                _ => state.push1(builder.ins().fsub(c, product)),
+            }
+        }
         Operator::F32x4Sqrt | Operator::F64x2Sqrt => {
             let a = pop1_with_bitcast(state, type_of(op), builder);
             state.push1(builder.ins().sqrt(a))
@@ -1881,11 +1982,11 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let a = pop1_with_bitcast(state, F64X2, builder);
             state.push1(builder.ins().fvdemote(a));
         }
-        Operator::I32x4TruncSatF32x4S => {
+        Operator::I32x4TruncSatF32x4S | Operator::I32x4RelaxedTruncSatF32x4S => {
             let a = pop1_with_bitcast(state, F32X4, builder);
             state.push1(builder.ins().fcvt_to_sint_sat(I32X4, a))
         }
-        Operator::I32x4TruncSatF64x2SZero => {
+        Operator::I32x4TruncSatF64x2SZero | Operator::I32x4RelaxedTruncSatF64x2SZero => {
             let a = pop1_with_bitcast(state, F64X2, builder);
             let converted_a = builder.ins().fcvt_to_sint_sat(I64X2, a);
             let handle = builder.func.dfg.constants.insert(vec![0u8; 16].into());
@@ -1893,11 +1994,11 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
 
             state.push1(builder.ins().snarrow(converted_a, zero));
         }
-        Operator::I32x4TruncSatF32x4U => {
+        Operator::I32x4TruncSatF32x4U | Operator::I32x4RelaxedTruncSatF32x4U => {
             let a = pop1_with_bitcast(state, F32X4, builder);
             state.push1(builder.ins().fcvt_to_uint_sat(I32X4, a))
         }
-        Operator::I32x4TruncSatF64x2UZero => {
+        Operator::I32x4TruncSatF64x2UZero | Operator::I32x4RelaxedTruncSatF64x2UZero => {
             let a = pop1_with_bitcast(state, F64X2, builder);
             let converted_a = builder.ins().fcvt_to_uint_sat(I64X2, a);
             let handle = builder.func.dfg.constants.insert(vec![0u8; 16].into());
@@ -2097,28 +2198,6 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let b_high = builder.ins().uwiden_high(b);
             state.push1(builder.ins().imul(a_high, b_high));
         }
-        Operator::ReturnCall { .. } | Operator::ReturnCallIndirect { .. } => {
-            return Err(wasm_unsupported!("proposed tail-call operator {:?}", op));
-        }
-        Operator::I8x16RelaxedSwizzle
-        | Operator::I32x4RelaxedTruncSatF32x4S
-        | Operator::I32x4RelaxedTruncSatF32x4U
-        | Operator::I32x4RelaxedTruncSatF64x2SZero
-        | Operator::I32x4RelaxedTruncSatF64x2UZero
-        | Operator::F32x4Fma
-        | Operator::F32x4Fms
-        | Operator::F64x2Fma
-        | Operator::F64x2Fms
-        | Operator::I8x16LaneSelect
-        | Operator::I16x8LaneSelect
-        | Operator::I32x4LaneSelect
-        | Operator::I64x2LaneSelect
-        | Operator::F32x4RelaxedMin
-        | Operator::F32x4RelaxedMax
-        | Operator::F64x2RelaxedMin
-        | Operator::F64x2RelaxedMax => {
-            return Err(wasm_unsupported!("proposed relaxed-simd operator {:?}", op));
-        }
     };
     Ok(())
 }
@@ -2150,9 +2229,43 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
                 ty,
             );
         }
-        Operator::Loop { ty: _ } | Operator::Block { ty: _ } => {
+        Operator::Loop { ty: _ } | Operator::Block { ty: _ } | Operator::Try { ty: _ } => {
             state.push_block(ir::Block::reserved_value(), 0, 0);
         }
+        // Only a reachable `try` pushes a frame of its own, whose handlers
+        // or end may be reachable.
+        Operator::Catch { index }
+            if matches!(
+                state.control_stack.last(),
+                Some(ControlStackFrame::Try { .. })
+            ) =>
+        {
+            translate_catch(Some(TagIndex::from_u32(index)), builder, state, environ)?;
+        }
+        Operator::CatchAll
+            if matches!(
+                state.control_stack.last(),
+                Some(ControlStackFrame::Try { .. })
+            ) =>
+        {
+            translate_catch(None, builder, state, environ)?;
+        }
+        Operator::End
+            if matches!(
+                state.control_stack.last(),
+                Some(ControlStackFrame::Try { .. })
+            ) =>
+        {
+            translate_end_try(None, builder, state, environ)?;
+        }
+        Operator::Delegate { relative_depth }
+            if matches!(
+                state.control_stack.last(),
+                Some(ControlStackFrame::Try { .. })
+            ) =>
+        {
+            translate_end_try(Some(relative_depth), builder, state, environ)?;
+        }
         Operator::Else => {
             let i = state.control_stack.len() - 1;
             match state.control_stack[i] {
@@ -2201,7 +2314,7 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
                 _ => unreachable!(),
             }
         }
-        Operator::End => {
+        Operator::End | Operator::Delegate { .. } => {
             let stack = &mut state.stack;
             let control_stack = &mut state.control_stack;
             let frame = control_stack.pop().unwrap();
@@ -2260,13 +2373,28 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
 fn get_heap_addr(
     heap: ir::Heap,
     addr32: ir::Value,
-    offset: u32,
+    offset: u64,
     width: u32,
     addr_ty: Type,
     builder: &mut FunctionBuilder,
 ) -> (ir::Value, i32) {
     let offset_guard_size: u64 = builder.func.heaps[heap].offset_guard_size.into();
 
+    // The offset of an access to a 64-bit memory may not fit in the offset
+    // taken by `heap_addr`, in which case it's added to the address up
+    // front, trapping if that overflows.
+    let (addr32, offset) = match u32::try_from(offset) {
+        Ok(offset) => (addr32, offset),
+        Err(_) => {
+            let addr = builder.ins().iadd_imm(addr32, offset as i64);
+            let overflow = builder.ins().icmp(IntCC::UnsignedLessThan, addr, addr32);
+            builder
+                .ins()
+                .trapnz(overflow, ir::TrapCode::HeapOutOfBounds);
+            (addr, 0)
+        }
+    };
+
     // How exactly the bounds check is performed here and what it's performed
     // on is a bit tricky. Generally we want to rely on access violations (e.g.
     // segfaults) to generate traps since that means we don't have to bounds
@@ -2338,6 +2466,297 @@ fn get_heap_addr(
     }
 }
 
+/// Translate a return from the function, of the values on top of the stack.
+fn translate_return<FE: FuncEnvironment + ?Sized>(
+    builder: &mut FunctionBuilder,
+    state: &mut FuncTranslationState,
+    environ: &mut FE,
+) -> WasmResult<()> {
+    translate_exception_drops(0, builder, state, environ)?;
+    let (return_count, br_destination) = {
+        let frame = &mut state.control_stack[0];
+        if environ.return_mode() == ReturnMode::FallthroughReturn {
+            frame.set_branched_to_exit();
+        }
+        let return_count = frame.num_return_values();
+        (return_count, frame.br_destination())
+    };
+    {
+        let (return_args, return_args_metadata) = state.peekn_mut(return_count);
+        // TODO(reftypes): maybe ref count here?
+        let return_types = wasm_param_types(&builder.func.signature.returns, |i| {
+            environ.is_wasm_return(&builder.func.signature, i)
+        });
+        bitcast_arguments(return_args, &return_types, builder);
+        match environ.return_mode() {
+            ReturnMode::NormalReturns => builder.ins().return_(return_args),
+            ReturnMode::FallthroughReturn => canonicalise_then_jump(
+                builder,
+                br_destination,
+                (&*return_args, &*return_args_metadata),
+            ),
+        };
+    }
+    state.popn(return_count);
+    state.reachable = false;
+    Ok(())
+}
+
+/// Returns the block that the exceptions thrown by the code in the control frame `index` land
+/// on: the handlers of the innermost `try` around that code, or the return from the function.
+fn exception_target(
+    index: usize,
+    builder: &mut FunctionBuilder,
+    state: &mut FuncTranslationState,
+) -> ir::Block {
+    for frame in state.control_stack[..=index].iter_mut().rev() {
+        if let ControlStackFrame::Try {
+            landing,
+            exception,
+            unwind,
+            ..
+        } = frame
+        {
+            // An exception thrown by a handler releases the one it handles
+            // before leaving the `try`.
+            let target = if exception.is_some() { unwind } else { landing };
+            return *target.get_or_insert_with(|| builder.create_block());
+        }
+    }
+    *state
+        .propagate_block
+        .get_or_insert_with(|| builder.create_block())
+}
+
+/// Checks whether the call just translated threw an exception, in which case
+/// control goes to the block the exception lands on.
+fn translate_exception_check<FE: FuncEnvironment + ?Sized>(
+    builder: &mut FunctionBuilder,
+    state: &mut FuncTranslationState,
+    environ: &mut FE,
+) -> WasmResult<()> {
+    if !environ.exceptions_enabled() {
+        return Ok(());
+    }
+
+    // Exceptions are rare, so the count of the ones thrown in the store rules
+    // them out before asking about the current thread.
+    let thrown_exceptions = environ.translate_load_thrown_exceptions(builder.cursor())?;
+    let check_block = builder.create_block();
+    let next_block = builder.create_block();
+    builder.ins().brnz(thrown_exceptions, check_block, &[]);
+    builder.ins().jump(next_block, &[]);
+    builder.seal_block(check_block); // The only predecessor is the current block.
+    builder.switch_to_block(check_block);
+
+    let thrown = environ.translate_exception_thrown(builder.cursor())?;
+    let i = state.control_stack.len() - 1;
+    let target = exception_target(i, builder, state);
+    builder.ins().brnz(thrown, target, &[]);
+    builder.ins().jump(next_block, &[]);
+    builder.seal_block(next_block);
+    builder.switch_to_block(next_block);
+    Ok(())
+}
+
+/// Whether a branch to the control frame `index` leaves the handlers of a `try`.
+fn has_exception_drops(index: usize, state: &FuncTranslationState) -> bool {
+    state.control_stack[index..].iter().any(|frame| {
+        matches!(
+            frame,
+            ControlStackFrame::Try {
+                exception: Some(_),
+                ..
+            }
+        )
+    })
+}
+
+/// Releases the exceptions handled by the handlers that a branch to the
+/// control frame `index` leaves.
+fn translate_exception_drops<FE: FuncEnvironment + ?Sized>(
+    index: usize,
+    builder: &mut FunctionBuilder,
+    state: &FuncTranslationState,
+    environ: &mut FE,
+) -> WasmResult<()> {
+    for frame in &state.control_stack[index..] {
+        if let ControlStackFrame::Try {
+            exception: Some(exception),
+            ..
+        } = *frame
+        {
+            environ.translate_exception_drop(builder.cursor(), exception)?;
+        }
+    }
+    Ok(())
+}
+
+/// Translate a `catch` of the tag `tag_index`, or a `catch_all` if `None`, of the `try` on top
+/// of the control stack.
+fn translate_catch<FE: FuncEnvironment + ?Sized>(
+    tag_index: Option<TagIndex>,
+    builder: &mut FunctionBuilder,
+    state: &mut FuncTranslationState,
+    environ: &mut FE,
+) -> WasmResult<()> {
+    let i = state.control_stack.len() - 1;
+    if state.reachable {
+        // The end of the body or of the previous handler leaves the `try`.
+        let (exception, destination, return_count) = match state.control_stack[i] {
+            ControlStackFrame::Try {
+                exception,
+                destination,
+                num_return_values,
+                ref mut exit_is_branched_to,
+                ..
+            } => {
+                *exit_is_branched_to = true;
+                (exception, destination, num_return_values)
+            }
+            _ => unreachable!(),
+        };
+        if let Some(exception) = exception {
+            environ.translate_exception_drop(builder.cursor(), exception)?;
+        }
+        canonicalise_then_jump(builder, destination, state.peekn(return_count));
+    }
+    let frame = &state.control_stack[i];
+    frame.truncate_value_stack_to_original_size(&mut state.stack);
+
+    let exception = match state.control_stack[i] {
+        // The first clause takes the exception that landed.
+        ControlStackFrame::Try {
+            landing: Some(landing),
+            exception: None,
+            ..
+        } => {
+            builder.switch_to_block(landing);
+            builder.seal_block(landing);
+            environ.translate_exception_catch(builder.cursor())?
+        }
+        // The next ones are reached when the tag of the previous one didn't match.
+        ControlStackFrame::Try {
+            exception: Some(exception),
+            next_test: Some(next_test),
+            ..
+        } => {
+            builder.switch_to_block(next_test);
+            exception
+        }
+        // Nothing is thrown in the body, or a `catch_all` handles everything.
+        _ => {
+            state.reachable = false;
+            return Ok(());
+        }
+    };
+    state.reachable = true;
+
+    let next_test = match tag_index {
+        Some(tag_index) => {
+            let matches =
+                environ.translate_exception_matches(builder.cursor(), exception, tag_index)?;
+            let handler_block = builder.create_block();
+            let next_test = builder.create_block();
+            builder.ins().brnz(matches, handler_block, &[]);
+            builder.ins().jump(next_test, &[]);
+            builder.seal_block(handler_block);
+            builder.seal_block(next_test);
+            builder.switch_to_block(handler_block);
+
+            let payload =
+                environ.translate_exception_payload(builder.cursor(), exception, tag_index)?;
+            state.pushn(&payload, &vec![Default::default(); payload.len()]);
+            Some(next_test)
+        }
+        None => None,
+    };
+    if let ControlStackFrame::Try {
+        exception: ref mut frame_exception,
+        next_test: ref mut frame_next_test,
+        ..
+    } = state.control_stack[i]
+    {
+        *frame_exception = Some(exception);
+        *frame_next_test = next_test;
+    }
+    Ok(())
+}
+
+/// Translate the `end` of the `try` on top of the control stack, or its `delegate` to the
+/// handlers of the control frame `relative_depth` around it if `Some`.
+fn translate_end_try<FE: FuncEnvironment + ?Sized>(
+    delegate: Option<u32>,
+    builder: &mut FunctionBuilder,
+    state: &mut FuncTranslationState,
+    environ: &mut FE,
+) -> WasmResult<()> {
+    let frame = state.control_stack.pop().unwrap();
+    let (destination, landing, exception, next_test, mut unwind) = match frame {
+        ControlStackFrame::Try {
+            destination,
+            landing,
+            exception,
+            next_test,
+            unwind,
+            ..
+        } => (destination, landing, exception, next_test, unwind),
+        _ => unreachable!(),
+    };
+    let mut exit_is_branched_to = frame.exit_is_branched_to();
+    if state.reachable {
+        if let Some(exception) = exception {
+            environ.translate_exception_drop(builder.cursor(), exception)?;
+        }
+        let return_args = state.peekn(frame.num_return_values());
+        canonicalise_then_jump(builder, destination, return_args);
+        exit_is_branched_to = true;
+    }
+    frame.truncate_value_stack_to_original_size(&mut state.stack);
+
+    // The exceptions that the `try` doesn't handle go on to the handlers
+    // around it.
+    let outer = state.control_stack.len() - 1 - delegate.unwrap_or(0) as usize;
+    match exception {
+        None => {
+            if let Some(landing) = landing {
+                builder.switch_to_block(landing);
+                builder.seal_block(landing);
+                let target = exception_target(outer, builder, state);
+                builder.ins().jump(target, &[]);
+            }
+        }
+        Some(exception) => {
+            if let Some(next_test) = next_test {
+                // No tag matched, so the exception is thrown again.
+                builder.switch_to_block(next_test);
+                environ.translate_rethrow(builder.cursor(), exception)?;
+                let unwind = *unwind.get_or_insert_with(|| builder.create_block());
+                builder.ins().jump(unwind, &[]);
+            }
+            if let Some(unwind) = unwind {
+                builder.switch_to_block(unwind);
+                builder.seal_block(unwind);
+                environ.translate_exception_drop(builder.cursor(), exception)?;
+                let target = exception_target(outer, builder, state);
+                builder.ins().jump(target, &[]);
+            }
+        }
+    }
+
+    if exit_is_branched_to {
+        builder.switch_to_block(destination);
+        builder.seal_block(destination);
+        state
+            .stack
+            .extend_from_slice(builder.block_params(destination));
+        state.reachable = true;
+    } else {
+        state.reachable = false;
+    }
+    Ok(())
+}
+
 /// Prepare for a load; factors out common functionality between load and load_extend operations.
 fn prepare_load<FE: FuncEnvironment + ?Sized>(
     memarg: &MemoryImmediate,
@@ -2352,7 +2771,7 @@ fn prepare_load<FE: FuncEnvironment + ?Sized>(
     let (base, offset) = get_heap_addr(
         heap,
         addr32,
-        memarg.offset as u32,
+        memarg.offset,
         loaded_bytes,
         environ.pointer_type(),
         builder,
@@ -2404,7 +2823,7 @@ fn translate_store<FE: FuncEnvironment + ?Sized>(
     let (base, offset) = get_heap_addr(
         heap,
         addr32,
-        memarg.offset as u32,
+        memarg.offset,
         mem_op_size(opcode, val_ty),
         environ.pointer_type(),
         builder,
@@ -2441,7 +2860,20 @@ fn fold_atomic_mem_addr(
     builder: &mut FunctionBuilder,
 ) -> Value {
     let access_ty_bytes = access_ty.bytes();
-    let final_lma = if memarg.offset > 0 {
+    let final_lma = if memarg.offset > 0 && builder.func.dfg.value_type(linear_mem_addr) == I64 {
+        // The address of a 64-bit memory, where adding the offset must not
+        // overflow.
+        let a = builder
+            .ins()
+            .iadd_imm(linear_mem_addr, memarg.offset as i64);
+        let overflow = builder
+            .ins()
+            .icmp(IntCC::UnsignedLessThan, a, linear_mem_addr);
+        builder
+            .ins()
+            .trapnz(overflow, ir::TrapCode::HeapOutOfBounds);
+        a
+    } else if memarg.offset > 0 {
         assert!(builder.func.dfg.value_type(linear_mem_addr) == I32);
         let linear_mem_addr = builder.ins().uextend(I64, linear_mem_addr);
         let a = builder
@@ -2733,20 +3165,37 @@ fn translate_vector_fcmp(
     state.push1(builder.ins().fcmp(cc, bitcast_a, bitcast_b))
 }
 
-fn translate_br_if(
+fn translate_br_if<FE: FuncEnvironment + ?Sized>(
     relative_depth: u32,
     builder: &mut FunctionBuilder,
     state: &mut FuncTranslationState,
-) {
+    environ: &mut FE,
+) -> WasmResult<()> {
     let (val, _) = state.pop1();
-    let (br_destination, inputs) = translate_br_if_args(relative_depth, state);
-    let inputs = (&*inputs.0, &*inputs.1);
-    canonicalise_then_brnz(builder, val, br_destination, inputs);
-
+    let i = state.control_stack.len() - 1 - (relative_depth as usize);
     let next_block = builder.create_block();
-    canonicalise_then_jump(builder, next_block, (&[], &[]));
+    if has_exception_drops(i, state) {
+        // The exceptions handled by the handlers the branch leaves are
+        // released on the way, in a block of its own.
+        let drop_block = builder.create_block();
+        builder.ins().brnz(val, drop_block, &[]);
+        canonicalise_then_jump(builder, next_block, (&[], &[]));
+        builder.seal_block(drop_block); // The only predecessor is the current block.
+        builder.switch_to_block(drop_block);
+        translate_exception_drops(i, builder, state, environ)?;
+        let (br_destination, inputs) = translate_br_if_args(relative_depth, state);
+        let inputs = (&*inputs.0, &*inputs.1);
+        canonicalise_then_jump(builder, br_destination, inputs);
+    } else {
+        let (br_destination, inputs) = translate_br_if_args(relative_depth, state);
+        let inputs = (&*inputs.0, &*inputs.1);
+        canonicalise_then_brnz(builder, val, br_destination, inputs);
+        canonicalise_then_jump(builder, next_block, (&[], &[]));
+    }
+
     builder.seal_block(next_block); // The only predecessor is the current block.
     builder.switch_to_block(next_block);
+    Ok(())
 }
 
 fn translate_br_if_args(
@@ -2937,6 +3386,10 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::F32x4Max
         | Operator::F32x4PMin
         | Operator::F32x4PMax
+        | Operator::F32x4RelaxedMin
+        | Operator::F32x4RelaxedMax
+        | Operator::F32x4Fma
+        | Operator::F32x4Fms
         | Operator::F32x4ConvertI32x4S
         | Operator::F32x4ConvertI32x4U
         | Operator::F32x4Ceil
@@ -2964,6 +3417,10 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::F64x2Max
         | Operator::F64x2PMin
         | Operator::F64x2PMax
+        | Operator::F64x2RelaxedMin
+        | Operator::F64x2RelaxedMax
+        | Operator::F64x2Fma
+        | Operator::F64x2Fms
         | Operator::F64x2Ceil
         | Operator::F64x2Floor
         | Operator::F64x2Trunc
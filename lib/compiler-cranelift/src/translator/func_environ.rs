@@ -16,7 +16,7 @@ use wasmer_compiler::wasmparser::{Operator, Type};
 use wasmer_compiler::WasmResult;
 use wasmer_types::{
     FunctionIndex, FunctionType, GlobalIndex, LocalFunctionIndex, MemoryIndex, SignatureIndex,
-    TableIndex, Type as WasmerType,
+    TableIndex, TagIndex, Type as WasmerType,
 };
 
 /// The value of a WebAssembly global variable.
@@ -101,6 +101,14 @@ pub trait FuncEnvironment: TargetEnvironment {
         ReturnMode::NormalReturns
     }
 
+    /// Should the relaxed SIMD `min` and `max` operators be translated to
+    /// their deterministic counterparts, rather than to whatever the target
+    /// computes fastest? The other relaxed operators are always translated
+    /// deterministically.
+    fn relaxed_simd_deterministic(&self) -> bool {
+        true
+    }
+
     /// Set up the necessary preamble definitions in `func` to access the global variable
     /// identified by `index`.
     ///
@@ -196,6 +204,38 @@ pub trait FuncEnvironment: TargetEnvironment {
         Ok(pos.ins().call(callee, call_args))
     }
 
+    /// Translate a `return_call_indirect` WebAssembly instruction at `pos`.
+    ///
+    /// Insert instructions at `pos` for a tail call to the function `callee` in the table
+    /// `table_index` with WebAssembly signature `sig_index`, like `translate_call_indirect()`.
+    ///
+    /// Return the values the calling function returns.
+    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+    fn translate_return_call_indirect(
+        &mut self,
+        pos: FuncCursor,
+        table_index: TableIndex,
+        table: ir::Table,
+        sig_index: SignatureIndex,
+        sig_ref: ir::SigRef,
+        callee: ir::Value,
+        call_args: &[ir::Value],
+    ) -> WasmResult<Vec<ir::Value>>;
+
+    /// Translate a `return_call` WebAssembly instruction at `pos`.
+    ///
+    /// Insert instructions at `pos` for a tail call to the function `callee_index`, like
+    /// `translate_call()`.
+    ///
+    /// Return the values the calling function returns.
+    fn translate_return_call(
+        &mut self,
+        pos: FuncCursor,
+        callee_index: FunctionIndex,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> WasmResult<Vec<ir::Value>>;
+
     /// Translate a `memory.grow` WebAssembly instruction.
     ///
     /// The `index` provided identifies the linear memory to grow, and `heap` is the heap reference
@@ -454,6 +494,77 @@ pub trait FuncEnvironment: TargetEnvironment {
         count: ir::Value,
     ) -> WasmResult<ir::Value>;
 
+    /// Whether the exception-handling proposal is enabled, in which case
+    /// every call is followed by a check for a thrown exception.
+    fn exceptions_enabled(&self) -> bool {
+        false
+    }
+
+    /// Translate a load of the number of exceptions being thrown by all the
+    /// threads, which is a cheap way to rule out an exception after a call.
+    fn translate_load_thrown_exceptions(&mut self, pos: FuncCursor) -> WasmResult<ir::Value>;
+
+    /// Translate a check of whether an exception is being thrown by the
+    /// current thread.
+    ///
+    /// Returns an i32, which is nonzero if an exception is being thrown.
+    fn translate_exception_thrown(&mut self, pos: FuncCursor) -> WasmResult<ir::Value>;
+
+    /// Translate a `throw` WebAssembly instruction, which leaves an exception
+    /// of the tag `tag_index` carrying `args` pending.
+    fn translate_throw(
+        &mut self,
+        pos: FuncCursor,
+        tag_index: TagIndex,
+        args: &[ir::Value],
+    ) -> WasmResult<()>;
+
+    /// Translate the catching of the exception being thrown, at the start of
+    /// the handlers of a `try`.
+    ///
+    /// Returns the caught exception, which must be released with
+    /// `translate_exception_drop()`.
+    fn translate_exception_catch(&mut self, pos: FuncCursor) -> WasmResult<ir::Value>;
+
+    /// Translate a test of whether `exception` was thrown with the tag
+    /// `tag_index`, for a `catch` WebAssembly instruction.
+    ///
+    /// Returns an i32, which is nonzero if the tag matches.
+    fn translate_exception_matches(
+        &mut self,
+        pos: FuncCursor,
+        exception: ir::Value,
+        tag_index: TagIndex,
+    ) -> WasmResult<ir::Value>;
+
+    /// Translate a read of the values `exception` carries, knowing it was
+    /// thrown with the tag `tag_index`.
+    fn translate_exception_payload(
+        &mut self,
+        pos: FuncCursor,
+        exception: ir::Value,
+        tag_index: TagIndex,
+    ) -> WasmResult<Vec<ir::Value>>;
+
+    /// Translate a `rethrow` WebAssembly instruction, which leaves the caught
+    /// `exception` pending again.
+    fn translate_rethrow(&mut self, pos: FuncCursor, exception: ir::Value) -> WasmResult<()>;
+
+    /// Translate the release of the caught `exception`, when control leaves
+    /// the handler that caught it.
+    fn translate_exception_drop(&mut self, pos: FuncCursor, exception: ir::Value)
+        -> WasmResult<()>;
+
+    /// Emit code at the beginning of every wasm function, after the locals
+    /// are declared.
+    ///
+    /// This can be used to insert explicit interrupt checking at function
+    /// entries.
+    fn translate_function_entry(&mut self, _pos: FuncCursor) -> WasmResult<()> {
+        // By default, don't emit anything.
+        Ok(())
+    }
+
     /// Emit code at the beginning of every wasm loop.
     ///
     /// This can be used to insert explicit interrupt or safepoint checking at
@@ -506,6 +617,10 @@ pub trait FuncEnvironment: TargetEnvironment {
     /// Get the type of a function with the given signature index.
     fn get_function_sig(&self, sig_index: SignatureIndex) -> Option<&FunctionType>;
 
+    /// Get the signature of the tag at the given index, whose params are the values its
+    /// exceptions carry.
+    fn get_tag_type(&self, tag_index: TagIndex) -> Option<&FunctionType>;
+
     /// Drops all locals that need to be dropped. Useful for returning from functions.
     fn translate_drop_locals(&mut self, builder: &mut FunctionBuilder) -> WasmResult<()>;
 }
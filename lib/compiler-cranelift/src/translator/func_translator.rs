@@ -110,6 +110,7 @@ impl FuncTranslator {
         self.state.initialize(&builder.func.signature, exit_block);
 
         parse_local_decls(reader, &mut builder, num_params, environ)?;
+        environ.translate_function_entry(builder.cursor())?;
         parse_function_body(
             module_translation_state,
             reader,
@@ -278,6 +279,36 @@ fn parse_function_body<FE: FuncEnvironment + ?Sized>(
         }
     }
 
+    // The exceptions that no `try` of the function handles leave it, in which
+    // case the caller ignores the values returned.
+    if let Some(propagate_block) = state.propagate_block.take() {
+        builder.switch_to_block(propagate_block);
+        builder.seal_block(propagate_block);
+        environ.translate_drop_locals(builder)?;
+
+        let return_types = wasm_param_types(&builder.func.signature.returns, |i| {
+            environ.is_wasm_return(&builder.func.signature, i)
+        });
+        let return_values = return_types
+            .into_iter()
+            .map(|ty| match ty {
+                ir::types::F32 => builder.ins().f32const(ir::immediates::Ieee32::with_bits(0)),
+                ir::types::F64 => builder.ins().f64const(ir::immediates::Ieee64::with_bits(0)),
+                ty if ty.is_vector() => {
+                    let constant_handle =
+                        builder.func.dfg.constants.insert([0; 16].to_vec().into());
+                    builder.ins().vconst(ty, constant_handle)
+                }
+                ty if ty.is_ref() => builder.ins().null(ty),
+                ty => builder.ins().iconst(ty, 0),
+            })
+            .collect::<Vec<_>>();
+        match environ.return_mode() {
+            ReturnMode::NormalReturns => builder.ins().return_(&return_values),
+            ReturnMode::FallthroughReturn => builder.ins().fallthrough_return(&return_values),
+        };
+    }
+
     // Discard any remaining values on the stack. Either we just returned them,
     // or the end of the function is unreachable.
     state.stack.clear();
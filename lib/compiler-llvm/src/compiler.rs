@@ -224,6 +224,24 @@ impl Compiler for LLVMCompiler {
         module_translation: &ModuleTranslationState,
         function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'data>>,
     ) -> Result<Compilation, CompileError> {
+        if compile_info.features.memory64 {
+            return Err(CompileError::UnsupportedFeature("memory64".to_string()));
+        }
+        if compile_info.features.multi_memory {
+            return Err(CompileError::UnsupportedFeature("multi-memory".to_string()));
+        }
+        if compile_info.features.tail_call {
+            return Err(CompileError::UnsupportedFeature("tail calls".to_string()));
+        }
+        if compile_info.features.exceptions {
+            return Err(CompileError::UnsupportedFeature(
+                "exception handling".to_string(),
+            ));
+        }
+        if compile_info.features.relaxed_simd {
+            return Err(CompileError::UnsupportedFeature("relaxed SIMD".to_string()));
+        }
+
         //let data = Arc::new(Mutex::new(0));
         let memory_styles = &compile_info.memory_styles;
         let table_styles = &compile_info.table_styles;
@@ -94,8 +94,56 @@ where
         LibCall::ImportedMemory32Fill,
     );
     libcalls.insert("wasmer_vm_memory32_init".to_string(), LibCall::Memory32Init);
+    libcalls.insert("wasmer_vm_memory64_size".to_string(), LibCall::Memory64Size);
+    libcalls.insert(
+        "wasmer_vm_imported_memory64_size".to_string(),
+        LibCall::ImportedMemory64Size,
+    );
+    libcalls.insert("wasmer_vm_memory64_copy".to_string(), LibCall::Memory64Copy);
+    libcalls.insert(
+        "wasmer_vm_imported_memory64_copy".to_string(),
+        LibCall::ImportedMemory64Copy,
+    );
+    libcalls.insert("wasmer_vm_memory64_fill".to_string(), LibCall::Memory64Fill);
+    libcalls.insert(
+        "wasmer_vm_imported_memory64_fill".to_string(),
+        LibCall::ImportedMemory64Fill,
+    );
+    libcalls.insert("wasmer_vm_memory64_init".to_string(), LibCall::Memory64Init);
+    libcalls.insert(
+        "wasmer_vm_memory_copy_between".to_string(),
+        LibCall::MemoryCopyBetween,
+    );
     libcalls.insert("wasmer_vm_data_drop".to_string(), LibCall::DataDrop);
+    libcalls.insert("wasmer_vm_tail_call".to_string(), LibCall::TailCall);
+    libcalls.insert(
+        "wasmer_vm_tail_call_entered".to_string(),
+        LibCall::TailCallEntered,
+    );
+    libcalls.insert("wasmer_vm_throw".to_string(), LibCall::Throw);
+    libcalls.insert(
+        "wasmer_vm_exception_thrown".to_string(),
+        LibCall::ExceptionThrown,
+    );
+    libcalls.insert(
+        "wasmer_vm_exception_catch".to_string(),
+        LibCall::ExceptionCatch,
+    );
+    libcalls.insert(
+        "wasmer_vm_exception_matches".to_string(),
+        LibCall::ExceptionMatches,
+    );
+    libcalls.insert(
+        "wasmer_vm_exception_payload".to_string(),
+        LibCall::ExceptionPayload,
+    );
+    libcalls.insert("wasmer_vm_rethrow".to_string(), LibCall::Rethrow);
+    libcalls.insert(
+        "wasmer_vm_exception_drop".to_string(),
+        LibCall::ExceptionDrop,
+    );
     libcalls.insert("wasmer_vm_raise_trap".to_string(), LibCall::RaiseTrap);
+    libcalls.insert("wasmer_vm_stack_limit".to_string(), LibCall::StackLimit);
 
     let elf = object::File::parse(contents).map_err(map_object_err)?;
 
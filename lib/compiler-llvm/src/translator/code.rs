@@ -1,6 +1,7 @@
 use super::{
     intrinsics::{
-        tbaa_label, type_to_llvm, CtxType, FunctionCache, GlobalCache, Intrinsics, MemoryCache,
+        tbaa_label, type_to_llvm, type_to_llvm_ptr, CtxType, FunctionCache, GlobalCache,
+        Intrinsics, MemoryCache,
     },
     // stackmap::{StackmapEntry, StackmapEntryKind, StackmapRegistry, ValueSemantic},
     state::{ControlFrame, ExtraInfo, IfElseState, State},
@@ -136,10 +137,14 @@ impl FuncTranslator {
             function_body.data,
             function_body.module_offset,
         );
+        reader.allow_memarg64(wasm_module.memories.values().any(|memory| memory.memory64));
         reader.set_middleware_chain(
             config
                 .middlewares
-                .generate_function_middleware_chain(*local_func_index),
+                .generate_function_middleware_chain_with_state(
+                    *local_func_index,
+                    module_translation,
+                ),
         );
 
         let mut params = vec![];
@@ -198,6 +203,7 @@ impl FuncTranslator {
             unreachable_depth: 0,
             memory_styles,
             _table_styles,
+            tail_call_entered: None,
             module: &module,
             module_translation,
             wasm_module,
@@ -211,6 +217,9 @@ impl FuncTranslator {
             fcg.ctx.basic(),
             &func_attrs,
         );
+        if fcg.config.enable_interrupts {
+            fcg.trap_if_interrupted();
+        }
 
         while fcg.state.has_control_frames() {
             let pos = reader.current_position() as u32;
@@ -732,6 +741,70 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
         self.builder.position_at_end(shouldnt_trap_block);
     }
 
+    /// Traps with `Interrupt` if the store was interrupted, or if its epoch
+    /// reached the epoch deadline.
+    fn trap_if_interrupted(&mut self) {
+        let interrupts = self.ctx.interrupts(self.intrinsics);
+        let offsets = self.ctx.get_offsets();
+        let epoch_offset = offsets.vminterrupts_epoch();
+        let deadline_offset = offsets.vminterrupts_epoch_deadline();
+
+        // The loads are volatile so that they are not hoisted out of loops:
+        // the epoch and its deadline are updated by other threads.
+        let load = |offset: u8, name| {
+            let offset = self.intrinsics.i32_ty.const_int(offset.into(), false);
+            let ptr = unsafe { self.builder.build_gep(interrupts, &[offset], "") };
+            let ptr = self
+                .builder
+                .build_bitcast(ptr, self.intrinsics.i64_ptr_ty, "")
+                .into_pointer_value();
+            let value = self.builder.build_load(ptr, name).into_int_value();
+            value
+                .as_instruction_value()
+                .unwrap()
+                .set_volatile(true)
+                .unwrap();
+            value
+        };
+        let epoch = load(epoch_offset, "epoch");
+        let deadline = load(deadline_offset, "epoch_deadline");
+
+        let should_trap =
+            self.builder
+                .build_int_compare(IntPredicate::UGE, epoch, deadline, "interrupted");
+        let should_trap = self
+            .builder
+            .build_call(
+                self.intrinsics.expect_i1,
+                &[
+                    should_trap.into(),
+                    self.intrinsics.i1_ty.const_zero().into(),
+                ],
+                "interrupted_expect",
+            )
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+
+        let shouldnt_trap_block = self
+            .context
+            .append_basic_block(self.function, "not_interrupted_block");
+        let should_trap_block = self
+            .context
+            .append_basic_block(self.function, "interrupted_block");
+        self.builder
+            .build_conditional_branch(should_trap, should_trap_block, shouldnt_trap_block);
+        self.builder.position_at_end(should_trap_block);
+        self.builder.build_call(
+            self.intrinsics.throw_trap,
+            &[self.intrinsics.trap_interrupt.into()],
+            "throw",
+        );
+        self.builder.build_unreachable();
+        self.builder.position_at_end(shouldnt_trap_block);
+    }
+
     fn trap_if_zero(&self, value: IntValue) {
         let int_type = value.get_type();
         let should_trap = self.builder.build_int_compare(
@@ -1058,8 +1131,9 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
         let function = &self.function;
 
         // Compute the offset into the storage.
+        let memory64 = self.wasm_module.memories[memory_index].memory64;
         let imm_offset = intrinsics.i64_ty.const_int(memarg.offset as u64, false);
-        let var_offset = builder.build_int_z_extend(var_offset, intrinsics.i64_ty, "");
+        let var_offset = builder.build_int_z_extend_or_bit_cast(var_offset, intrinsics.i64_ty, "");
         let offset = builder.build_int_add(var_offset, imm_offset, "");
 
         // Look up the memory base (as pointer) and bounds (as unsigned integer).
@@ -1075,7 +1149,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                     // Bounds check it.
                     let minimum = self.wasm_module.memories[memory_index].minimum;
                     let value_size_v = intrinsics.i64_ty.const_int(value_size as u64, false);
-                    let ptr_in_bounds = if offset.is_const() {
+                    let ptr_in_bounds = if offset.is_const() && !memory64 {
                         // When the offset is constant, if it's below the minimum
                         // memory size, we've statically shown that it's safe.
                         let load_offset_end = offset.const_add(value_size_v);
@@ -1106,12 +1180,25 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                         let current_length =
                             builder.build_int_z_extend(current_length, intrinsics.i64_ty, "");
 
-                        builder.build_int_compare(
+                        let ptr_in_bounds = builder.build_int_compare(
                             IntPredicate::ULE,
                             load_offset_end,
                             current_length,
                             "",
-                        )
+                        );
+                        if memory64 {
+                            // A 64-bit address can wrap around when the
+                            // offset and the access size are added to it.
+                            let no_overflow = builder.build_int_compare(
+                                IntPredicate::UGE,
+                                load_offset_end,
+                                var_offset,
+                                "",
+                            );
+                            builder.build_and(ptr_in_bounds, no_overflow, "")
+                        } else {
+                            ptr_in_bounds
+                        }
                     });
                     if !ptr_in_bounds.is_constant_int()
                         || ptr_in_bounds.get_zero_extended_constant().unwrap() != 1
@@ -1170,14 +1257,32 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             .into_pointer_value())
     }
 
+    /// Returns `addr + memarg.offset` as an `i64`, without bounds checks:
+    /// the libcalls taking an effective address check it themselves.
+    fn effective_address_i64(
+        &self,
+        memarg: &MemoryImmediate,
+        addr: IntValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        let addr = self
+            .builder
+            .build_int_z_extend_or_bit_cast(addr, self.intrinsics.i64_ty, "");
+        let offset = self.intrinsics.i64_ty.const_int(memarg.offset, false);
+        self.builder.build_int_add(addr, offset, "")
+    }
+
     fn trap_if_misaligned(&self, memarg: &MemoryImmediate, ptr: PointerValue<'ctx>) {
-        let align = memarg.align;
+        // `memarg.align` is the log2 of the alignment.
+        if memarg.align == 0 {
+            return;
+        }
+        let align = 1u64 << memarg.align;
         let value = self
             .builder
             .build_ptr_to_int(ptr, self.intrinsics.i64_ty, "");
         let and = self.builder.build_and(
             value,
-            self.intrinsics.i64_ty.const_int((align - 1).into(), false),
+            self.intrinsics.i64_ty.const_int(align - 1, false),
             "misaligncheck",
         );
         let aligned =
@@ -1218,6 +1323,345 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
         self.builder.position_at_end(continue_block);
     }
 
+    /// Returns the values on top of the stack from the function.
+    fn translate_return(&mut self) -> Result<(), CompileError> {
+        let current_block = self
+            .builder
+            .get_insert_block()
+            .ok_or_else(|| CompileError::Codegen("not currently in a block".to_string()))?;
+
+        let frame = self.state.outermost_frame()?;
+        for phi in frame.phis().to_vec().iter().rev() {
+            let (arg, info) = self.state.pop1_extra()?;
+            let arg = self.apply_pending_canonicalization(arg, info);
+            phi.add_incoming(&[(&arg, current_block)]);
+        }
+        let frame = self.state.outermost_frame()?;
+        self.builder.build_unconditional_branch(*frame.br_dest());
+
+        self.state.reachable = false;
+        Ok(())
+    }
+
+    /// Returns whether this function was called by a tail call. This is
+    /// checked once, on entry, the first time a tail call needs it.
+    fn tail_call_entered(&mut self) -> IntValue<'ctx> {
+        if let Some(entered) = self.tail_call_entered {
+            return entered;
+        }
+        let body = self.alloca_builder.build_pointer_cast(
+            self.function.as_global_value().as_pointer_value(),
+            self.intrinsics.i8_ptr_ty,
+            "body",
+        );
+        let entered = self
+            .alloca_builder
+            .build_call(
+                self.intrinsics.tail_call_entered,
+                &[body.into()],
+                "tail_call_entered",
+            )
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+        self.tail_call_entered = Some(entered);
+        entered
+    }
+
+    /// Makes a tail call to `callee`, of signature `sigindex`, with the
+    /// arguments on top of the stack, and returns the results.
+    fn translate_tail_call(
+        &mut self,
+        sigindex: SignatureIndex,
+        callee: PointerValue<'ctx>,
+        callee_vmctx: BasicValueEnum<'ctx>,
+    ) -> Result<(), CompileError> {
+        let wasm_module = self.wasm_module;
+        let func_type = &wasm_module.signatures[sigindex];
+        let entered = self.tail_call_entered();
+
+        // The arguments and the results are passed in a buffer of 16-byte
+        // values, as the runtime calls the callee through its trampoline.
+        let values_len = func_type.params().len().max(func_type.results().len());
+        let values = self.alloca_builder.build_alloca(
+            self.intrinsics.i128_ty.array_type(values_len as u32),
+            "tail_call_values",
+        );
+        let values = self.builder.build_pointer_cast(
+            values,
+            self.intrinsics.i128_ptr_ty,
+            "tail_call_values_ptr",
+        );
+        let intrinsics = self.intrinsics;
+        let value_ptr = |builder: &Builder<'ctx>, index: usize, ty: PointerType<'ctx>| {
+            let ptr = unsafe {
+                builder.build_in_bounds_gep(
+                    values,
+                    &[intrinsics.i32_ty.const_int(index as u64, false)],
+                    "",
+                )
+            };
+            builder.build_pointer_cast(ptr, ty, "")
+        };
+
+        let params = self.state.popn_save_extra(func_type.params().len())?;
+        for (index, ((v, info), wasm_ty)) in params.iter().zip(func_type.params()).enumerate() {
+            let v = match wasm_ty {
+                Type::F32 => self.builder.build_bitcast(
+                    self.apply_pending_canonicalization(*v, *info),
+                    self.intrinsics.f32_ty,
+                    "",
+                ),
+                Type::F64 => self.builder.build_bitcast(
+                    self.apply_pending_canonicalization(*v, *info),
+                    self.intrinsics.f64_ty,
+                    "",
+                ),
+                Type::V128 => self.apply_pending_canonicalization(*v, *info),
+                _ => *v,
+            };
+            let ptr = value_ptr(
+                &self.builder,
+                index,
+                v.get_type().ptr_type(AddressSpace::Generic),
+            );
+            self.builder.build_store(ptr, v);
+        }
+
+        let callee = self
+            .builder
+            .build_pointer_cast(callee, self.intrinsics.i8_ptr_ty, "");
+        let callee_vmctx = self.builder.build_pointer_cast(
+            callee_vmctx.into_pointer_value(),
+            self.intrinsics.ctx_ptr_ty,
+            "",
+        );
+        self.builder.build_call(
+            self.intrinsics.tail_call,
+            &[
+                self.ctx.basic().into(),
+                self.intrinsics
+                    .i32_ty
+                    .const_int(sigindex.as_u32() as u64, false)
+                    .into(),
+                callee.into(),
+                callee_vmctx.into(),
+                values.into(),
+                entered.into(),
+            ],
+            "",
+        );
+
+        for (index, wasm_ty) in func_type.results().iter().enumerate() {
+            let ty = type_to_llvm_ptr(self.intrinsics, *wasm_ty)?;
+            let ptr = value_ptr(&self.builder, index, ty);
+            let result = self.builder.build_load(ptr, "");
+            self.state.push1(result);
+        }
+        self.translate_return()
+    }
+
+    /// Loads the function at `func_index` in the table, checking that it's
+    /// there and of signature `sigindex`, and returns it with its vmctx.
+    fn translate_load_table_callee(
+        &mut self,
+        sigindex: SignatureIndex,
+        table_index: u32,
+        func_index: IntValue<'ctx>,
+    ) -> (PointerValue<'ctx>, BasicValueEnum<'ctx>) {
+        let expected_dynamic_sigindex =
+            self.ctx
+                .dynamic_sigindex(sigindex, self.intrinsics, self.module);
+        let (table_base, table_bound) = self.ctx.table(
+            TableIndex::from_u32(table_index),
+            self.intrinsics,
+            self.module,
+        );
+
+        let truncated_table_bounds = self.builder.build_int_truncate(
+            table_bound,
+            self.intrinsics.i32_ty,
+            "truncated_table_bounds",
+        );
+
+        // First, check if the index is outside of the table bounds.
+        let index_in_bounds = self.builder.build_int_compare(
+            IntPredicate::ULT,
+            func_index,
+            truncated_table_bounds,
+            "index_in_bounds",
+        );
+
+        let index_in_bounds = self
+            .builder
+            .build_call(
+                self.intrinsics.expect_i1,
+                &[
+                    index_in_bounds.into(),
+                    self.intrinsics.i1_ty.const_int(1, false).into(),
+                ],
+                "index_in_bounds_expect",
+            )
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+
+        let in_bounds_continue_block = self
+            .context
+            .append_basic_block(self.function, "in_bounds_continue_block");
+        let not_in_bounds_block = self
+            .context
+            .append_basic_block(self.function, "not_in_bounds_block");
+        self.builder.build_conditional_branch(
+            index_in_bounds,
+            in_bounds_continue_block,
+            not_in_bounds_block,
+        );
+        self.builder.position_at_end(not_in_bounds_block);
+        self.builder.build_call(
+            self.intrinsics.throw_trap,
+            &[self.intrinsics.trap_table_access_oob.into()],
+            "throw",
+        );
+        self.builder.build_unreachable();
+        self.builder.position_at_end(in_bounds_continue_block);
+
+        // We assume the table has the `funcref` (pointer to `anyfunc`)
+        // element type.
+        let casted_table_base = self.builder.build_pointer_cast(
+            table_base,
+            self.intrinsics.funcref_ty.ptr_type(AddressSpace::Generic),
+            "casted_table_base",
+        );
+
+        let funcref_ptr = unsafe {
+            self.builder
+                .build_in_bounds_gep(casted_table_base, &[func_index], "funcref_ptr")
+        };
+
+        // a funcref (pointer to `anyfunc`)
+        let anyfunc_struct_ptr = self
+            .builder
+            .build_load(funcref_ptr, "anyfunc_struct_ptr")
+            .into_pointer_value();
+
+        // trap if we're trying to call a null funcref
+        {
+            let funcref_not_null = self
+                .builder
+                .build_is_not_null(anyfunc_struct_ptr, "null funcref check");
+
+            let funcref_continue_deref_block = self
+                .context
+                .append_basic_block(self.function, "funcref_continue deref_block");
+
+            let funcref_is_null_block = self
+                .context
+                .append_basic_block(self.function, "funcref_is_null_block");
+            self.builder.build_conditional_branch(
+                funcref_not_null,
+                funcref_continue_deref_block,
+                funcref_is_null_block,
+            );
+            self.builder.position_at_end(funcref_is_null_block);
+            self.builder.build_call(
+                self.intrinsics.throw_trap,
+                &[self.intrinsics.trap_call_indirect_null.into()],
+                "throw",
+            );
+            self.builder.build_unreachable();
+            self.builder.position_at_end(funcref_continue_deref_block);
+        }
+
+        // Load things from the anyfunc data structure.
+        let (func_ptr, found_dynamic_sigindex, ctx_ptr) = (
+            self.builder
+                .build_load(
+                    self.builder
+                        .build_struct_gep(anyfunc_struct_ptr, 0, "func_ptr_ptr")
+                        .unwrap(),
+                    "func_ptr",
+                )
+                .into_pointer_value(),
+            self.builder
+                .build_load(
+                    self.builder
+                        .build_struct_gep(anyfunc_struct_ptr, 1, "sigindex_ptr")
+                        .unwrap(),
+                    "sigindex",
+                )
+                .into_int_value(),
+            self.builder.build_load(
+                self.builder
+                    .build_struct_gep(anyfunc_struct_ptr, 2, "ctx_ptr_ptr")
+                    .unwrap(),
+                "ctx_ptr",
+            ),
+        );
+
+        // Next, check if the table element is initialized.
+
+        // TODO: we may not need this check anymore
+        let elem_initialized = self.builder.build_is_not_null(func_ptr, "");
+
+        // Next, check if the signature id is correct.
+
+        let sigindices_equal = self.builder.build_int_compare(
+            IntPredicate::EQ,
+            expected_dynamic_sigindex,
+            found_dynamic_sigindex,
+            "sigindices_equal",
+        );
+
+        let initialized_and_sigindices_match =
+            self.builder
+                .build_and(elem_initialized, sigindices_equal, "");
+
+        // Tell llvm that `expected_dynamic_sigindex` should equal `found_dynamic_sigindex`.
+        let initialized_and_sigindices_match = self
+            .builder
+            .build_call(
+                self.intrinsics.expect_i1,
+                &[
+                    initialized_and_sigindices_match.into(),
+                    self.intrinsics.i1_ty.const_int(1, false).into(),
+                ],
+                "initialized_and_sigindices_match_expect",
+            )
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+
+        let continue_block = self
+            .context
+            .append_basic_block(self.function, "continue_block");
+        let sigindices_notequal_block = self
+            .context
+            .append_basic_block(self.function, "sigindices_notequal_block");
+        self.builder.build_conditional_branch(
+            initialized_and_sigindices_match,
+            continue_block,
+            sigindices_notequal_block,
+        );
+
+        self.builder.position_at_end(sigindices_notequal_block);
+        let trap_code = self.builder.build_select(
+            elem_initialized,
+            self.intrinsics.trap_call_indirect_sig,
+            self.intrinsics.trap_call_indirect_null,
+            "",
+        );
+        self.builder
+            .build_call(self.intrinsics.throw_trap, &[trap_code.into()], "throw");
+        self.builder.build_unreachable();
+        self.builder.position_at_end(continue_block);
+
+        (func_ptr, ctx_ptr)
+    }
+
     fn finalize(&mut self, wasm_fn_type: &FunctionType) -> Result<(), CompileError> {
         let func_type = self.function.get_type();
 
@@ -1362,6 +1806,7 @@ pub struct LLVMFunctionCodeGenerator<'ctx, 'a> {
     unreachable_depth: usize,
     memory_styles: &'a PrimaryMap<MemoryIndex, MemoryStyle>,
     _table_styles: &'a PrimaryMap<TableIndex, TableStyle>,
+    tail_call_entered: Option<IntValue<'ctx>>,
 
     // This is support for stackmaps:
     /*
@@ -1484,6 +1929,10 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                     self.state.push1(phi.as_basic_value());
                 }
 
+                if self.config.enable_interrupts {
+                    self.trap_if_interrupted();
+                }
+
                 /*
                 if self.track_state {
                     if let Some(offset) = opcode_offset {
@@ -1818,21 +2267,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 }
             }
             Operator::Return => {
-                let current_block = self
-                    .builder
-                    .get_insert_block()
-                    .ok_or_else(|| CompileError::Codegen("not currently in a block".to_string()))?;
-
-                let frame = self.state.outermost_frame()?;
-                for phi in frame.phis().to_vec().iter().rev() {
-                    let (arg, info) = self.state.pop1_extra()?;
-                    let arg = self.apply_pending_canonicalization(arg, info);
-                    phi.add_incoming(&[(&arg, current_block)]);
-                }
-                let frame = self.state.outermost_frame()?;
-                self.builder.build_unconditional_branch(*frame.br_dest());
-
-                self.state.reachable = false;
+                self.translate_return()?;
             }
 
             Operator::Unreachable => {
@@ -2266,199 +2701,10 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::CallIndirect { index, table_index } => {
                 let sigindex = SignatureIndex::from_u32(index);
-                let func_type = &self.wasm_module.signatures[sigindex];
-                let expected_dynamic_sigindex =
-                    self.ctx
-                        .dynamic_sigindex(sigindex, self.intrinsics, self.module);
-                let (table_base, table_bound) = self.ctx.table(
-                    TableIndex::from_u32(table_index),
-                    self.intrinsics,
-                    self.module,
-                );
                 let func_index = self.state.pop1()?.into_int_value();
-
-                let truncated_table_bounds = self.builder.build_int_truncate(
-                    table_bound,
-                    self.intrinsics.i32_ty,
-                    "truncated_table_bounds",
-                );
-
-                // First, check if the index is outside of the table bounds.
-                let index_in_bounds = self.builder.build_int_compare(
-                    IntPredicate::ULT,
-                    func_index,
-                    truncated_table_bounds,
-                    "index_in_bounds",
-                );
-
-                let index_in_bounds = self
-                    .builder
-                    .build_call(
-                        self.intrinsics.expect_i1,
-                        &[
-                            index_in_bounds.into(),
-                            self.intrinsics.i1_ty.const_int(1, false).into(),
-                        ],
-                        "index_in_bounds_expect",
-                    )
-                    .try_as_basic_value()
-                    .left()
-                    .unwrap()
-                    .into_int_value();
-
-                let in_bounds_continue_block = self
-                    .context
-                    .append_basic_block(self.function, "in_bounds_continue_block");
-                let not_in_bounds_block = self
-                    .context
-                    .append_basic_block(self.function, "not_in_bounds_block");
-                self.builder.build_conditional_branch(
-                    index_in_bounds,
-                    in_bounds_continue_block,
-                    not_in_bounds_block,
-                );
-                self.builder.position_at_end(not_in_bounds_block);
-                self.builder.build_call(
-                    self.intrinsics.throw_trap,
-                    &[self.intrinsics.trap_table_access_oob.into()],
-                    "throw",
-                );
-                self.builder.build_unreachable();
-                self.builder.position_at_end(in_bounds_continue_block);
-
-                // We assume the table has the `funcref` (pointer to `anyfunc`)
-                // element type.
-                let casted_table_base = self.builder.build_pointer_cast(
-                    table_base,
-                    self.intrinsics.funcref_ty.ptr_type(AddressSpace::Generic),
-                    "casted_table_base",
-                );
-
-                let funcref_ptr = unsafe {
-                    self.builder.build_in_bounds_gep(
-                        casted_table_base,
-                        &[func_index],
-                        "funcref_ptr",
-                    )
-                };
-
-                // a funcref (pointer to `anyfunc`)
-                let anyfunc_struct_ptr = self
-                    .builder
-                    .build_load(funcref_ptr, "anyfunc_struct_ptr")
-                    .into_pointer_value();
-
-                // trap if we're trying to call a null funcref
-                {
-                    let funcref_not_null = self
-                        .builder
-                        .build_is_not_null(anyfunc_struct_ptr, "null funcref check");
-
-                    let funcref_continue_deref_block = self
-                        .context
-                        .append_basic_block(self.function, "funcref_continue deref_block");
-
-                    let funcref_is_null_block = self
-                        .context
-                        .append_basic_block(self.function, "funcref_is_null_block");
-                    self.builder.build_conditional_branch(
-                        funcref_not_null,
-                        funcref_continue_deref_block,
-                        funcref_is_null_block,
-                    );
-                    self.builder.position_at_end(funcref_is_null_block);
-                    self.builder.build_call(
-                        self.intrinsics.throw_trap,
-                        &[self.intrinsics.trap_call_indirect_null.into()],
-                        "throw",
-                    );
-                    self.builder.build_unreachable();
-                    self.builder.position_at_end(funcref_continue_deref_block);
-                }
-
-                // Load things from the anyfunc data structure.
-                let (func_ptr, found_dynamic_sigindex, ctx_ptr) = (
-                    self.builder
-                        .build_load(
-                            self.builder
-                                .build_struct_gep(anyfunc_struct_ptr, 0, "func_ptr_ptr")
-                                .unwrap(),
-                            "func_ptr",
-                        )
-                        .into_pointer_value(),
-                    self.builder
-                        .build_load(
-                            self.builder
-                                .build_struct_gep(anyfunc_struct_ptr, 1, "sigindex_ptr")
-                                .unwrap(),
-                            "sigindex",
-                        )
-                        .into_int_value(),
-                    self.builder.build_load(
-                        self.builder
-                            .build_struct_gep(anyfunc_struct_ptr, 2, "ctx_ptr_ptr")
-                            .unwrap(),
-                        "ctx_ptr",
-                    ),
-                );
-
-                // Next, check if the table element is initialized.
-
-                // TODO: we may not need this check anymore
-                let elem_initialized = self.builder.build_is_not_null(func_ptr, "");
-
-                // Next, check if the signature id is correct.
-
-                let sigindices_equal = self.builder.build_int_compare(
-                    IntPredicate::EQ,
-                    expected_dynamic_sigindex,
-                    found_dynamic_sigindex,
-                    "sigindices_equal",
-                );
-
-                let initialized_and_sigindices_match =
-                    self.builder
-                        .build_and(elem_initialized, sigindices_equal, "");
-
-                // Tell llvm that `expected_dynamic_sigindex` should equal `found_dynamic_sigindex`.
-                let initialized_and_sigindices_match = self
-                    .builder
-                    .build_call(
-                        self.intrinsics.expect_i1,
-                        &[
-                            initialized_and_sigindices_match.into(),
-                            self.intrinsics.i1_ty.const_int(1, false).into(),
-                        ],
-                        "initialized_and_sigindices_match_expect",
-                    )
-                    .try_as_basic_value()
-                    .left()
-                    .unwrap()
-                    .into_int_value();
-
-                let continue_block = self
-                    .context
-                    .append_basic_block(self.function, "continue_block");
-                let sigindices_notequal_block = self
-                    .context
-                    .append_basic_block(self.function, "sigindices_notequal_block");
-                self.builder.build_conditional_branch(
-                    initialized_and_sigindices_match,
-                    continue_block,
-                    sigindices_notequal_block,
-                );
-
-                self.builder.position_at_end(sigindices_notequal_block);
-                let trap_code = self.builder.build_select(
-                    elem_initialized,
-                    self.intrinsics.trap_call_indirect_sig,
-                    self.intrinsics.trap_call_indirect_null,
-                    "",
-                );
-                self.builder
-                    .build_call(self.intrinsics.throw_trap, &[trap_code.into()], "throw");
-                self.builder.build_unreachable();
-                self.builder.position_at_end(continue_block);
+                let (func_ptr, ctx_ptr) =
+                    self.translate_load_table_callee(sigindex, table_index, func_index);
+                let func_type = &self.wasm_module.signatures[sigindex];
 
                 let (llvm_func_type, llvm_func_attrs) = self.abi.func_type_to_llvm(
                     &self.context,
@@ -2558,7 +2804,45 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                     .iter()
                     .for_each(|ret| self.state.push1(*ret));
             }
+            Operator::ReturnCall { function_index } => {
+                let func_index = FunctionIndex::from_u32(function_index);
+                let sigindex = self.wasm_module.functions[func_index];
+                let func_type = &self.wasm_module.signatures[sigindex];
+
+                let FunctionCache {
+                    func,
+                    vmctx: callee_vmctx,
+                    ..
+                } = if let Some(local_func_index) = self.wasm_module.local_func_index(func_index) {
+                    let function_name = self
+                        .symbol_registry
+                        .symbol_to_name(Symbol::LocalFunction(local_func_index));
+                    self.ctx.local_func(
+                        local_func_index,
+                        func_index,
+                        self.intrinsics,
+                        self.module,
+                        self.context,
+                        func_type,
+                        &function_name,
+                    )?
+                } else {
+                    self.ctx
+                        .func(func_index, self.intrinsics, self.context, func_type)?
+                };
+                let func = *func;
+                let callee_vmctx = *callee_vmctx;
 
+                self.translate_tail_call(sigindex, func, callee_vmctx)?;
+            }
+            Operator::ReturnCallIndirect { index, table_index } => {
+                let sigindex = SignatureIndex::from_u32(index);
+                let func_index = self.state.pop1()?.into_int_value();
+                let (func_ptr, ctx_ptr) =
+                    self.translate_load_table_callee(sigindex, table_index, func_index);
+
+                self.translate_tail_call(sigindex, func_ptr, ctx_ptr)?;
+            }
             /***************************
              * Integer Arithmetic instructions.
              * https://github.com/sunfishcode/wasm-reference-manual/blob/master/WebAssembly.md#integer-arithmetic-instructions
@@ -7612,7 +7896,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
              ***************************/
             Operator::I32Load { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7631,7 +7915,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64Load { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7650,7 +7934,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::F32Load { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7669,7 +7953,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::F64Load { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7688,7 +7972,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7805,7 +8089,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32Store { ref memarg } => {
                 let value = self.state.pop1()?;
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7826,7 +8110,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64Store { ref memarg } => {
                 let value = self.state.pop1()?;
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7848,7 +8132,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let (v, i) = self.state.pop1_extra()?;
                 let v = self.apply_pending_canonicalization(v, i);
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7870,7 +8154,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let (v, i) = self.state.pop1_extra()?;
                 let v = self.apply_pending_canonicalization(v, i);
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -7892,7 +8176,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let (v, i) = self.state.pop1_extra()?;
                 let v = self.apply_pending_canonicalization(v, i);
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8012,7 +8296,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I32Load8S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8036,7 +8320,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I32Load16S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8060,7 +8344,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64Load8S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8085,7 +8369,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64Load16S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8110,7 +8394,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64Load32S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8135,7 +8419,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
 
             Operator::I32Load8U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8159,7 +8443,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I32Load16U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8183,7 +8467,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64Load8U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8207,7 +8491,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64Load16U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8231,7 +8515,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64Load32U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8257,7 +8541,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32Store8 { ref memarg } | Operator::I64Store8 { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8281,7 +8565,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32Store16 { ref memarg } | Operator::I64Store16 { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8305,7 +8589,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64Store32 { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8687,7 +8971,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load8x8S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8708,7 +8992,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load8x8U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8729,7 +9013,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load16x4S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8750,7 +9034,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load16x4U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8771,7 +9055,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load32x2S { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8792,7 +9076,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load32x2U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8813,7 +9097,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load32Zero { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8837,7 +9121,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load64Zero { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8861,7 +9145,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load8Splat { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8882,7 +9166,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load16Splat { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8903,7 +9187,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load32Splat { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8924,7 +9208,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::V128Load64Splat { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8943,6 +9227,13 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
                 self.state.push1(res);
             }
+            Operator::MemoryAtomicWait32 { .. }
+            | Operator::MemoryAtomicWait64 { .. }
+            | Operator::MemoryAtomicNotify { .. } => {
+                return Err(CompileError::UnsupportedFeature(
+                    "atomic wait and notify".to_string(),
+                ));
+            }
             Operator::AtomicFence { flags: _ } => {
                 // Fence is a nop.
                 //
@@ -8954,7 +9245,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I32AtomicLoad { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8972,7 +9263,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64AtomicLoad { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -8990,7 +9281,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I32AtomicLoad8U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9014,7 +9305,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I32AtomicLoad16U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9038,7 +9329,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64AtomicLoad8U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9062,7 +9353,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64AtomicLoad16U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9086,7 +9377,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
             Operator::I64AtomicLoad32U { ref memarg } => {
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9111,7 +9402,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicStore { ref memarg } => {
                 let value = self.state.pop1()?;
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9129,7 +9420,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicStore { ref memarg } => {
                 let value = self.state.pop1()?;
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9147,7 +9438,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicStore8 { ref memarg } | Operator::I64AtomicStore8 { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9169,7 +9460,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             | Operator::I64AtomicStore16 { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9190,7 +9481,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicStore32 { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9211,7 +9502,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw8AddU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9246,7 +9537,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw16AddU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9281,7 +9572,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmwAdd { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9310,7 +9601,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw8AddU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9345,7 +9636,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw16AddU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9380,7 +9671,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw32AddU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9415,7 +9706,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmwAdd { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9444,7 +9735,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw8SubU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9479,7 +9770,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw16SubU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9514,7 +9805,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmwSub { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9543,7 +9834,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw8SubU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9578,7 +9869,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw16SubU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9613,7 +9904,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw32SubU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9648,7 +9939,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmwSub { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9677,7 +9968,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw8AndU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9712,7 +10003,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw16AndU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9747,7 +10038,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmwAnd { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9776,7 +10067,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw8AndU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9811,7 +10102,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw16AndU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9846,7 +10137,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw32AndU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9881,7 +10172,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmwAnd { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9910,7 +10201,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw8OrU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9945,7 +10236,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw16OrU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -9980,7 +10271,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmwOr { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10012,7 +10303,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw8OrU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10047,7 +10338,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw16OrU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10082,7 +10373,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw32OrU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10117,7 +10408,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmwOr { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10146,7 +10437,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw8XorU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10181,7 +10472,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw16XorU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10216,7 +10507,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmwXor { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10245,7 +10536,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw8XorU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10280,7 +10571,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw16XorU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10315,7 +10606,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw32XorU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10350,7 +10641,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmwXor { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10379,7 +10670,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw8XchgU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10414,7 +10705,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmw16XchgU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10449,7 +10740,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I32AtomicRmwXchg { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10478,7 +10769,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw8XchgU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10513,7 +10804,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw16XchgU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10548,7 +10839,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmw32XchgU { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10583,7 +10874,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             Operator::I64AtomicRmwXchg { ref memarg } => {
                 let value = self.state.pop1()?.into_int_value();
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10615,7 +10906,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let new = self.apply_pending_canonicalization(new, new_info);
                 let (cmp, new) = (cmp.into_int_value(), new.into_int_value());
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10662,7 +10953,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let new = self.apply_pending_canonicalization(new, new_info);
                 let (cmp, new) = (cmp.into_int_value(), new.into_int_value());
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10709,7 +11000,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let new = self.apply_pending_canonicalization(new, new_info);
                 let (cmp, new) = (cmp.into_int_value(), new.into_int_value());
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10743,7 +11034,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let new = self.apply_pending_canonicalization(new, new_info);
                 let (cmp, new) = (cmp.into_int_value(), new.into_int_value());
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10790,7 +11081,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let new = self.apply_pending_canonicalization(new, new_info);
                 let (cmp, new) = (cmp.into_int_value(), new.into_int_value());
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10837,7 +11128,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let new = self.apply_pending_canonicalization(new, new_info);
                 let (cmp, new) = (cmp.into_int_value(), new.into_int_value());
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10884,7 +11175,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let new = self.apply_pending_canonicalization(new, new_info);
                 let (cmp, new) = (cmp.into_int_value(), new.into_int_value());
                 let offset = self.state.pop1()?.into_int_value();
-                let memory_index = MemoryIndex::from_u32(0);
+                let memory_index = MemoryIndex::from_u32(memarg.memory);
                 let effective_address = self.resolve_memory_ptr(
                     memory_index,
                     memarg,
@@ -10945,11 +11236,17 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 self.state.push1(size.try_as_basic_value().left().unwrap());
             }
             Operator::MemoryInit { segment, mem } => {
+                let memory_init = if self.wasm_module.memories[MemoryIndex::from_u32(mem)].memory64
+                {
+                    self.intrinsics.memory64_init
+                } else {
+                    self.intrinsics.memory_init
+                };
                 let (dest, src, len) = self.state.pop3()?;
                 let mem = self.intrinsics.i32_ty.const_int(mem.into(), false);
                 let segment = self.intrinsics.i32_ty.const_int(segment.into(), false);
                 self.builder.build_call(
-                    self.intrinsics.memory_init,
+                    memory_init,
                     &[
                         vmctx.as_basic_value_enum().into(),
                         mem.into(),
@@ -10969,16 +11266,48 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                     "",
                 );
             }
-            Operator::MemoryCopy { src, dst } => {
-                // ignored until we support multiple memories
-                let _dst = dst;
-                let (memory_copy, src) = if let Some(local_memory_index) = self
-                    .wasm_module
-                    .local_memory_index(MemoryIndex::from_u32(src))
-                {
-                    (self.intrinsics.memory_copy, local_memory_index.as_u32())
-                } else {
-                    (self.intrinsics.imported_memory_copy, src)
+            Operator::MemoryCopy { src, dst } if src != dst => {
+                // The two memories may have different index types, so every
+                // address and the length are passed as 64-bit values.
+                let (dest_pos, src_pos, len) = self.state.pop3()?;
+                let extend = |value: BasicValueEnum<'ctx>| {
+                    self.builder.build_int_z_extend_or_bit_cast(
+                        value.into_int_value(),
+                        self.intrinsics.i64_ty,
+                        "",
+                    )
+                };
+                let (dest_pos, src_pos, len) = (extend(dest_pos), extend(src_pos), extend(len));
+                let dst_index = self.intrinsics.i32_ty.const_int(dst.into(), false);
+                let src_index = self.intrinsics.i32_ty.const_int(src.into(), false);
+                self.builder.build_call(
+                    self.intrinsics.memory_copy_between,
+                    &[
+                        vmctx.as_basic_value_enum().into(),
+                        dst_index.into(),
+                        src_index.into(),
+                        dest_pos.into(),
+                        src_pos.into(),
+                        len.into(),
+                    ],
+                    "",
+                );
+            }
+            Operator::MemoryCopy { src, dst: _ } => {
+                let memory64 = self.wasm_module.memories[MemoryIndex::from_u32(src)].memory64;
+                let (memory_copy, src) = match (
+                    self.wasm_module
+                        .local_memory_index(MemoryIndex::from_u32(src)),
+                    memory64,
+                ) {
+                    (Some(local_memory_index), false) => {
+                        (self.intrinsics.memory_copy, local_memory_index.as_u32())
+                    }
+                    (Some(local_memory_index), true) => {
+                        (self.intrinsics.memory64_copy, local_memory_index.as_u32())
+                    }
+                    (None, false) => (self.intrinsics.imported_memory_copy, src),
+                    (None, true) => (self.intrinsics.imported_memory64_copy, src),
                 };
 
                 let (dest_pos, src_pos, len) = self.state.pop3()?;
@@ -10996,13 +11325,20 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 );
             }
             Operator::MemoryFill { mem } => {
-                let (memory_fill, mem) = if let Some(local_memory_index) = self
-                    .wasm_module
-                    .local_memory_index(MemoryIndex::from_u32(mem))
-                {
-                    (self.intrinsics.memory_fill, local_memory_index.as_u32())
-                } else {
-                    (self.intrinsics.imported_memory_fill, mem)
+                let memory64 = self.wasm_module.memories[MemoryIndex::from_u32(mem)].memory64;
+                let (memory_fill, mem) = match (
+                    self.wasm_module
+                        .local_memory_index(MemoryIndex::from_u32(mem)),
+                    memory64,
+                ) {
+                    (Some(local_memory_index), false) => {
+                        (self.intrinsics.memory_fill, local_memory_index.as_u32())
+                    }
+                    (Some(local_memory_index), true) => {
+                        (self.intrinsics.memory64_fill, local_memory_index.as_u32())
+                    }
+                    (None, false) => (self.intrinsics.imported_memory_fill, mem),
+                    (None, true) => (self.intrinsics.imported_memory64_fill, mem),
                 };
 
                 let (dst, val, len) = self.state.pop3()?;
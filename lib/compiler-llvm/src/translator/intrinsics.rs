@@ -218,6 +218,7 @@ pub struct Intrinsics<'ctx> {
     pub trap_bad_conversion_to_integer: BasicValueEnum<'ctx>,
     pub trap_unaligned_atomic: BasicValueEnum<'ctx>,
     pub trap_table_access_oob: BasicValueEnum<'ctx>,
+    pub trap_interrupt: BasicValueEnum<'ctx>,
 
     pub experimental_stackmap: FunctionValue<'ctx>,
 
@@ -241,6 +242,14 @@ pub struct Intrinsics<'ctx> {
     pub imported_memory_copy: FunctionValue<'ctx>,
     pub memory_fill: FunctionValue<'ctx>,
     pub imported_memory_fill: FunctionValue<'ctx>,
+    pub memory64_init: FunctionValue<'ctx>,
+    pub memory64_copy: FunctionValue<'ctx>,
+    pub imported_memory64_copy: FunctionValue<'ctx>,
+    pub memory64_fill: FunctionValue<'ctx>,
+    pub imported_memory64_fill: FunctionValue<'ctx>,
+    pub memory_copy_between: FunctionValue<'ctx>,
+    pub tail_call: FunctionValue<'ctx>,
+    pub tail_call_entered: FunctionValue<'ctx>,
 
     pub throw_trap: FunctionValue<'ctx>,
 
@@ -257,6 +266,10 @@ pub struct Intrinsics<'ctx> {
     pub imported_memory32_grow_ptr_ty: PointerType<'ctx>,
     pub memory32_size_ptr_ty: PointerType<'ctx>,
     pub imported_memory32_size_ptr_ty: PointerType<'ctx>,
+    pub memory64_grow_ptr_ty: PointerType<'ctx>,
+    pub imported_memory64_grow_ptr_ty: PointerType<'ctx>,
+    pub memory64_size_ptr_ty: PointerType<'ctx>,
+    pub imported_memory64_size_ptr_ty: PointerType<'ctx>,
 
     // Pointer to the VM.
     pub ctx_ptr_ty: PointerType<'ctx>,
@@ -345,6 +358,8 @@ impl<'ctx> Intrinsics<'ctx> {
         let f32x4_ty_basic_md: BasicMetadataTypeEnum = f32x4_ty.into();
         let f64x2_ty_basic_md: BasicMetadataTypeEnum = f64x2_ty.into();
         let md_ty_basic_md: BasicMetadataTypeEnum = md_ty.into();
+        let i8_ptr_ty_basic_md: BasicMetadataTypeEnum = i8_ptr_ty.into();
+        let i128_ptr_ty_basic_md: BasicMetadataTypeEnum = i128_ptr_ty.into();
 
         let ctx_ty = i8_ty;
         let ctx_ptr_ty = ctx_ty.ptr_type(AddressSpace::Generic);
@@ -781,6 +796,9 @@ impl<'ctx> Intrinsics<'ctx> {
             trap_table_access_oob: i32_ty
                 .const_int(TrapCode::TableAccessOutOfBounds as _, false)
                 .as_basic_value_enum(),
+            trap_interrupt: i32_ty
+                .const_int(TrapCode::Interrupt as _, false)
+                .as_basic_value_enum(),
 
             experimental_stackmap: module.add_function(
                 "llvm.experimental.stackmap",
@@ -988,6 +1006,112 @@ impl<'ctx> Intrinsics<'ctx> {
                 ),
                 None,
             ),
+            memory64_init: module.add_function(
+                "wasmer_vm_memory64_init",
+                void_ty.fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                        i32_ty_basic_md,
+                        i32_ty_basic_md,
+                    ],
+                    false,
+                ),
+                None,
+            ),
+            memory64_copy: module.add_function(
+                "wasmer_vm_memory64_copy",
+                void_ty.fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                        i64_ty_basic_md,
+                        i64_ty_basic_md,
+                    ],
+                    false,
+                ),
+                None,
+            ),
+            imported_memory64_copy: module.add_function(
+                "wasmer_vm_imported_memory64_copy",
+                void_ty.fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                        i64_ty_basic_md,
+                        i64_ty_basic_md,
+                    ],
+                    false,
+                ),
+                None,
+            ),
+            memory64_fill: module.add_function(
+                "wasmer_vm_memory64_fill",
+                void_ty.fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                    ],
+                    false,
+                ),
+                None,
+            ),
+            imported_memory64_fill: module.add_function(
+                "wasmer_vm_imported_memory64_fill",
+                void_ty.fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                    ],
+                    false,
+                ),
+                None,
+            ),
+            memory_copy_between: module.add_function(
+                "wasmer_vm_memory_copy_between",
+                void_ty.fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i32_ty_basic_md,
+                        i64_ty_basic_md,
+                        i64_ty_basic_md,
+                        i64_ty_basic_md,
+                    ],
+                    false,
+                ),
+                None,
+            ),
+            tail_call: module.add_function(
+                "wasmer_vm_tail_call",
+                void_ty.fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i8_ptr_ty_basic_md,
+                        ctx_ptr_ty_basic_md,
+                        i128_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                    ],
+                    false,
+                ),
+                None,
+            ),
+            tail_call_entered: module.add_function(
+                "wasmer_vm_tail_call_entered",
+                i32_ty.fn_type(&[i8_ptr_ty_basic_md], false),
+                None,
+            ),
             data_drop: module.add_function(
                 "wasmer_vm_data_drop",
                 void_ty.fn_type(&[ctx_ptr_ty_basic_md, i32_ty_basic_md], false),
@@ -1039,6 +1163,24 @@ impl<'ctx> Intrinsics<'ctx> {
             imported_memory32_size_ptr_ty: i32_ty
                 .fn_type(&[ctx_ptr_ty_basic_md, i32_ty_basic_md], false)
                 .ptr_type(AddressSpace::Generic),
+            memory64_grow_ptr_ty: i64_ty
+                .fn_type(
+                    &[ctx_ptr_ty_basic_md, i64_ty_basic_md, i32_ty_basic_md],
+                    false,
+                )
+                .ptr_type(AddressSpace::Generic),
+            imported_memory64_grow_ptr_ty: i64_ty
+                .fn_type(
+                    &[ctx_ptr_ty_basic_md, i64_ty_basic_md, i32_ty_basic_md],
+                    false,
+                )
+                .ptr_type(AddressSpace::Generic),
+            memory64_size_ptr_ty: i64_ty
+                .fn_type(&[ctx_ptr_ty_basic_md, i32_ty_basic_md], false)
+                .ptr_type(AddressSpace::Generic),
+            imported_memory64_size_ptr_ty: i64_ty
+                .fn_type(&[ctx_ptr_ty_basic_md, i32_ty_basic_md], false)
+                .ptr_type(AddressSpace::Generic),
 
             ctx_ptr_ty,
         };
@@ -1099,6 +1241,7 @@ pub struct CtxType<'ctx, 'a> {
     cached_functions: HashMap<FunctionIndex, FunctionCache<'ctx>>,
     cached_memory_grow: HashMap<MemoryIndex, PointerValue<'ctx>>,
     cached_memory_size: HashMap<MemoryIndex, PointerValue<'ctx>>,
+    cached_interrupts: Option<PointerValue<'ctx>>,
 
     offsets: VMOffsets,
 }
@@ -1124,6 +1267,7 @@ impl<'ctx, 'a> CtxType<'ctx, 'a> {
             cached_functions: HashMap::new(),
             cached_memory_grow: HashMap::new(),
             cached_memory_size: HashMap::new(),
+            cached_interrupts: None,
 
             // TODO: pointer width
             offsets: VMOffsets::new(8, &wasm_module),
@@ -1588,16 +1732,26 @@ impl<'ctx, 'a> CtxType<'ctx, 'a> {
             &self.ctx_ptr_value,
         );
         *cached_memory_grow.entry(memory_index).or_insert_with(|| {
-            let (grow_fn, grow_fn_ty) = if wasm_module.local_memory_index(memory_index).is_some() {
-                (
+            let (grow_fn, grow_fn_ty) = match (
+                wasm_module.local_memory_index(memory_index).is_some(),
+                wasm_module.memories[memory_index].memory64,
+            ) {
+                (true, false) => (
                     VMBuiltinFunctionIndex::get_memory32_grow_index(),
                     intrinsics.memory32_grow_ptr_ty,
-                )
-            } else {
-                (
+                ),
+                (true, true) => (
+                    VMBuiltinFunctionIndex::get_memory64_grow_index(),
+                    intrinsics.memory64_grow_ptr_ty,
+                ),
+                (false, false) => (
                     VMBuiltinFunctionIndex::get_imported_memory32_grow_index(),
                     intrinsics.imported_memory32_grow_ptr_ty,
-                )
+                ),
+                (false, true) => (
+                    VMBuiltinFunctionIndex::get_imported_memory64_grow_index(),
+                    intrinsics.imported_memory64_grow_ptr_ty,
+                ),
             };
             let offset = offsets.vmctx_builtin_function(grow_fn);
             let offset = intrinsics.i32_ty.const_int(offset.into(), false);
@@ -1629,16 +1783,26 @@ impl<'ctx, 'a> CtxType<'ctx, 'a> {
             &self.ctx_ptr_value,
         );
         *cached_memory_size.entry(memory_index).or_insert_with(|| {
-            let (size_fn, size_fn_ty) = if wasm_module.local_memory_index(memory_index).is_some() {
-                (
+            let (size_fn, size_fn_ty) = match (
+                wasm_module.local_memory_index(memory_index).is_some(),
+                wasm_module.memories[memory_index].memory64,
+            ) {
+                (true, false) => (
                     VMBuiltinFunctionIndex::get_memory32_size_index(),
                     intrinsics.memory32_size_ptr_ty,
-                )
-            } else {
-                (
+                ),
+                (true, true) => (
+                    VMBuiltinFunctionIndex::get_memory64_size_index(),
+                    intrinsics.memory64_size_ptr_ty,
+                ),
+                (false, false) => (
                     VMBuiltinFunctionIndex::get_imported_memory32_size_index(),
                     intrinsics.imported_memory32_size_ptr_ty,
-                )
+                ),
+                (false, true) => (
+                    VMBuiltinFunctionIndex::get_imported_memory64_size_index(),
+                    intrinsics.imported_memory64_size_ptr_ty,
+                ),
             };
             let offset = offsets.vmctx_builtin_function(size_fn);
             let offset = intrinsics.i32_ty.const_int(offset.into(), false);
@@ -1658,6 +1822,33 @@ impl<'ctx, 'a> CtxType<'ctx, 'a> {
         })
     }
 
+    /// Returns a pointer to the `VMInterrupts` of the store.
+    pub fn interrupts(&mut self, intrinsics: &Intrinsics<'ctx>) -> PointerValue<'ctx> {
+        let (cached_interrupts, offsets, cache_builder, ctx_ptr_value) = (
+            &mut self.cached_interrupts,
+            &self.offsets,
+            &self.cache_builder,
+            &self.ctx_ptr_value,
+        );
+        *cached_interrupts.get_or_insert_with(|| {
+            let offset = intrinsics
+                .i32_ty
+                .const_int(offsets.vmctx_interrupts().into(), false);
+            let interrupts_ptr_ptr =
+                unsafe { cache_builder.build_gep(*ctx_ptr_value, &[offset], "") };
+            let interrupts_ptr_ptr = cache_builder
+                .build_bitcast(
+                    interrupts_ptr_ptr,
+                    intrinsics.i8_ptr_ty.ptr_type(AddressSpace::Generic),
+                    "",
+                )
+                .into_pointer_value();
+            cache_builder
+                .build_load(interrupts_ptr_ptr, "interrupts")
+                .into_pointer_value()
+        })
+    }
+
     pub fn get_offsets(&self) -> &VMOffsets {
         &self.offsets
     }
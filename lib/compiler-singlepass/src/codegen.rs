@@ -2,7 +2,10 @@ use crate::address_map::get_function_address_map;
 #[cfg(feature = "unwind")]
 use crate::dwarf::WriterRelocate;
 use crate::location::{Location, Reg};
-use crate::machine::{CodegenError, Label, Machine, MachineStackOffset, NATIVE_PAGE_SIZE};
+use crate::machine::{
+    CodegenError, Label, Machine, MachineStackOffset, MemoryImmediate, NATIVE_PAGE_SIZE,
+    STACK_CHECK_RESERVE,
+};
 use crate::unwind::UnwindFrame;
 use crate::{common_decl::*, config::Singlepass};
 #[cfg(feature = "unwind")]
@@ -62,6 +65,10 @@ pub struct FuncGen<'a, M: Machine> {
 
     stack_offset: MachineStackOffset,
 
+    /// Stack offsets of the value stack slots holding a `v128`, which take
+    /// 16 bytes instead of 8. `v128` values never live in registers.
+    v128_stack_slots: Vec<usize>,
+
     save_area_offset: Option<MachineStackOffset>,
 
     state: MachineState,
@@ -94,6 +101,8 @@ struct SpecialLabelSet {
     table_access_oob: Label,
     indirect_call_null: Label,
     bad_signature: Label,
+    interrupt: Label,
+    stack_overflow: Label,
 }
 
 /// Metadata about a floating-point value.
@@ -272,14 +281,19 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 WpType::F32 | WpType::F64 => self.machine.pick_simd().map(Location::SIMD),
                 WpType::I32 | WpType::I64 => self.machine.pick_gpr().map(Location::GPR),
                 WpType::FuncRef | WpType::ExternRef => self.machine.pick_gpr().map(Location::GPR),
+                WpType::V128 => None,
                 _ => unreachable!("can't acquire location for type {:?}", ty),
             };
 
             let loc = if let Some(x) = loc {
                 x
             } else {
-                self.stack_offset.0 += 8;
-                delta_stack_offset += 8;
+                let size = if *ty == WpType::V128 { 16 } else { 8 };
+                self.stack_offset.0 += size;
+                delta_stack_offset += size;
+                if *ty == WpType::V128 {
+                    self.v128_stack_slots.push(self.stack_offset.0);
+                }
                 self.machine.local_on_stack(self.stack_offset.0 as i32)
             };
             if let Location::GPR(x) = loc {
@@ -301,12 +315,139 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         }
         if zeroed {
             for i in 0..tys.len() {
-                self.machine.zero_location(Size::S64, ret[i]);
+                if tys[i].0 == WpType::V128 {
+                    let [low, high] = Self::v128_halves(ret[i]);
+                    self.machine.zero_location(Size::S64, low);
+                    self.machine.zero_location(Size::S64, high);
+                } else {
+                    self.machine.zero_location(Size::S64, ret[i]);
+                }
             }
         }
         ret
     }
 
+    /// Returns the size of the stack slot at `offset` on top of the value
+    /// stack, forgetting about it if it holds a `v128`.
+    fn pop_stack_slot(&mut self, offset: usize) -> usize {
+        if self.v128_stack_slots.last() == Some(&offset) {
+            self.v128_stack_slots.pop();
+            16
+        } else {
+            8
+        }
+    }
+
+    /// Whether a value stack location holds a `v128`.
+    fn is_v128(&self, loc: Location<M::GPR, M::SIMD>) -> bool {
+        match loc {
+            Location::Memory(y, x) if y == self.machine.local_pointer() && x < 0 => {
+                self.v128_stack_slots.contains(&((-x) as usize))
+            }
+            _ => false,
+        }
+    }
+
+    /// Splits the memory location of a `v128` into the locations of its low
+    /// and high 64 bits.
+    fn v128_halves(loc: Location<M::GPR, M::SIMD>) -> [Location<M::GPR, M::SIMD>; 2] {
+        match loc {
+            Location::Memory(base, offset) => [
+                Location::Memory(base, offset),
+                Location::Memory(base, offset + 8),
+            ],
+            _ => unreachable!("v128 values live in memory, not in {:?}", loc),
+        }
+    }
+
+    /// Moves a `v128` between two memory locations.
+    fn move_v128(&mut self, src: Location<M::GPR, M::SIMD>, dst: Location<M::GPR, M::SIMD>) {
+        if src == dst {
+            return;
+        }
+        let [src_low, src_high] = Self::v128_halves(src);
+        let [dst_low, dst_high] = Self::v128_halves(dst);
+        self.machine.emit_relaxed_mov(Size::S64, src_low, dst_low);
+        self.machine.emit_relaxed_mov(Size::S64, src_high, dst_high);
+    }
+
+    /// Moves a `v128` returned in the return registers to `dst`.
+    fn move_v128_from_ret(&mut self, dst: Location<M::GPR, M::SIMD>) {
+        let [low, high] = Self::v128_halves(dst);
+        self.machine.move_location(
+            Size::S64,
+            Location::GPR(self.machine.get_gpr_for_ret()),
+            low,
+        );
+        self.machine.move_location(
+            Size::S64,
+            Location::GPR(self.machine.get_gpr_for_ret_high()),
+            high,
+        );
+    }
+
+    /// Pops the float value popped from the value stack at `loc` off the FP stack,
+    /// canonicalizing it in place if needed.
+    fn canonicalize_popped_float(
+        &mut self,
+        sz: Size,
+        loc: Location<M::GPR, M::SIMD>,
+    ) -> Result<(), CodegenError> {
+        let fp = self.fp_stack.pop1()?;
+        if self.machine.arch_supports_canonicalize_nan()
+            && self.config.enable_nan_canonicalization
+            && fp.canonicalization.is_some()
+        {
+            self.machine.canonicalize_nan(sz, loc, loc);
+        }
+        Ok(())
+    }
+
+    /// Canonicalizes the NaN lanes of the result of a SIMD floating-point arithmetic operator.
+    fn canonicalize_v128_result(&mut self, op: &Operator, ret: Location<M::GPR, M::SIMD>) {
+        if !self.machine.arch_supports_canonicalize_nan()
+            || !self.config.enable_nan_canonicalization
+        {
+            return;
+        }
+        let sz = match op {
+            Operator::F32x4Ceil
+            | Operator::F32x4Floor
+            | Operator::F32x4Trunc
+            | Operator::F32x4Nearest
+            | Operator::F32x4Sqrt
+            | Operator::F32x4Add
+            | Operator::F32x4Sub
+            | Operator::F32x4Mul
+            | Operator::F32x4Div
+            | Operator::F32x4Min
+            | Operator::F32x4Max
+            | Operator::F32x4RelaxedMin
+            | Operator::F32x4RelaxedMax
+            | Operator::F32x4Fma
+            | Operator::F32x4Fms
+            | Operator::F32x4DemoteF64x2Zero => Size::S32,
+            Operator::F64x2Ceil
+            | Operator::F64x2Floor
+            | Operator::F64x2Trunc
+            | Operator::F64x2Nearest
+            | Operator::F64x2Sqrt
+            | Operator::F64x2Add
+            | Operator::F64x2Sub
+            | Operator::F64x2Mul
+            | Operator::F64x2Div
+            | Operator::F64x2Min
+            | Operator::F64x2Max
+            | Operator::F64x2RelaxedMin
+            | Operator::F64x2RelaxedMax
+            | Operator::F64x2Fma
+            | Operator::F64x2Fms
+            | Operator::F64x2PromoteLowF32x4 => Size::S64,
+            _ => return,
+        };
+        self.machine.v128_canonicalize_nan(sz, ret);
+    }
+
     /// Releases locations used for stack value.
     fn release_locations(&mut self, locs: &[Location<M::GPR, M::SIMD>]) {
         let mut delta_stack_offset: usize = 0;
@@ -332,8 +473,9 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         if offset != self.stack_offset.0 {
                             unreachable!();
                         }
-                        self.stack_offset.0 -= 8;
-                        delta_stack_offset += 8;
+                        let size = self.pop_stack_slot(offset);
+                        self.stack_offset.0 -= size;
+                        delta_stack_offset += size;
                         self.state.stack_values.pop().unwrap();
                     }
                 }
@@ -372,8 +514,14 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         if offset != self.stack_offset.0 {
                             unreachable!();
                         }
-                        self.stack_offset.0 -= 8;
-                        delta_stack_offset += 8;
+                        let size = if self.v128_stack_slots.last() == Some(&offset) {
+                            self.v128_stack_slots.pop();
+                            16
+                        } else {
+                            8
+                        };
+                        self.stack_offset.0 -= size;
+                        delta_stack_offset += size;
                         self.state.stack_values.pop().unwrap();
                     }
                 }
@@ -420,8 +568,9 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     if offset != self.stack_offset.0 {
                         unreachable!();
                     }
-                    self.stack_offset.0 -= 8;
-                    delta_stack_offset += 8;
+                    let size = self.pop_stack_slot(offset);
+                    self.stack_offset.0 -= size;
+                    delta_stack_offset += size;
                     self.state.stack_values.pop().unwrap();
                 }
             }
@@ -459,8 +608,13 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     if offset != stack_offset {
                         unreachable!();
                     }
-                    stack_offset -= 8;
-                    delta_stack_offset += 8;
+                    let size = if self.v128_stack_slots.contains(&offset) {
+                        16
+                    } else {
+                        8
+                    };
+                    stack_offset -= size;
+                    delta_stack_offset += size;
                 }
             }
         }
@@ -471,6 +625,64 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         }
     }
 
+    /// Emits the explicit stack check of the function prolog, for a frame of
+    /// `size` bytes.
+    ///
+    /// The stack limit is the one of the call running on the current thread,
+    /// which only a libcall can read: the parameters, still in their
+    /// registers, are saved around it.
+    fn emit_stack_check(&mut self, calling_convention: CallingConvention, size: u32) {
+        let params: Vec<M::GPR> = (0..)
+            .map(|i| {
+                self.machine
+                    .get_simple_param_location(i, calling_convention)
+            })
+            .take_while(|loc| matches!(loc, Location::GPR(_)))
+            .map(|loc| match loc {
+                Location::GPR(x) => x,
+                _ => unreachable!(),
+            })
+            .collect();
+        let vmctx = params[0];
+
+        let pushed = self.machine.push_used_gpr(&params);
+        let mut padding = (16 - pushed % 16) % 16;
+        if calling_convention == CallingConvention::WindowsFastcall {
+            padding += 32;
+        }
+        if padding > 0 {
+            self.machine.adjust_stack(padding as u32);
+        }
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                vmctx,
+                self.vmoffsets
+                    .vmctx_builtin_function(VMBuiltinFunctionIndex::get_stack_limit_index())
+                    as i32,
+            ),
+            Location::GPR(self.machine.get_grp_for_call()),
+        );
+        self.machine
+            .emit_call_register(self.machine.get_grp_for_call());
+        // The register used for calls doesn't hold parameters.
+        let limit = self.machine.get_grp_for_call();
+        if self.machine.get_gpr_for_ret() != limit {
+            self.machine.move_location(
+                Size::S64,
+                Location::GPR(self.machine.get_gpr_for_ret()),
+                Location::GPR(limit),
+            );
+        }
+        if padding > 0 {
+            self.machine.restore_stack(padding as u32);
+        }
+        self.machine.pop_used_gpr(&params);
+
+        self.machine
+            .emit_stack_check(limit, size, self.special_labels.stack_overflow);
+    }
+
     fn init_locals(
         &mut self,
         n: usize,
@@ -482,6 +694,13 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             .filter(|&x| self.machine.is_local_on_stack(x))
             .count();
 
+        // `v128` locals don't fit in a register, they get 16-byte slots of their own.
+        let v128_locals: Vec<bool> = (0..n)
+            .map(|i| self.local_types[i] == WpType::V128)
+            .collect();
+        let is_v128 = |i: usize| v128_locals[i];
+        let num_v128_slots = (0..n).filter(|&x| is_v128(x)).count();
+
         // Total size (in bytes) of the pre-allocated "static area" for this function's
         // locals and callee-saved registers.
         let mut static_area_size: usize = 0;
@@ -490,7 +709,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         // Keep this consistent with the "Save callee-saved registers" code below.
         for i in 0..n {
             // If a local is not stored on stack, then it is allocated to a callee-saved register.
-            if !self.machine.is_local_on_stack(i) {
+            if !self.machine.is_local_on_stack(i) && !is_v128(i) {
                 static_area_size += 8;
             }
         }
@@ -505,25 +724,44 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         let callee_saved_regs_size = static_area_size;
 
         // Now we can determine concrete locations for locals.
+        // The `v128` locals come right after the callee-saved registers, then the other ones.
+        let v128_area_size = num_v128_slots * 16;
+        let mut v128_area_offset = callee_saved_regs_size;
         let locations: Vec<Location<M::GPR, M::SIMD>> = (0..n)
-            .map(|i| self.machine.get_local_location(i, callee_saved_regs_size))
+            .map(|i| {
+                if is_v128(i) {
+                    v128_area_offset += 16;
+                    self.machine.local_on_stack(v128_area_offset as i32)
+                } else {
+                    self.machine
+                        .get_local_location(i, callee_saved_regs_size + v128_area_size)
+                }
+            })
             .collect();
 
         // Add size of locals on stack.
-        static_area_size += num_mem_slots * 8;
+        static_area_size += v128_area_size + num_mem_slots * 8;
 
         // Allocate save area, without actually writing to it.
         static_area_size = self.machine.round_stack_adjust(static_area_size);
 
+        // Explicit stack check, before anything is written to the frame.
+        if self.config.enable_stack_checks {
+            self.emit_stack_check(
+                calling_convention,
+                (static_area_size + STACK_CHECK_RESERVE) as u32,
+            );
+        }
+
         // Stack probe.
         //
         // `rep stosq` writes data from low address to high address and may skip the stack guard page.
         // so here we probe it explicitly when needed.
-        for i in (sig.params().len()..n)
-            .step_by(NATIVE_PAGE_SIZE / 8)
-            .skip(1)
+        for offset in (callee_saved_regs_size + NATIVE_PAGE_SIZE..=static_area_size)
+            .step_by(NATIVE_PAGE_SIZE)
         {
-            self.machine.zero_location(Size::S64, locations[i]);
+            let location = self.machine.local_on_stack(offset as i32);
+            self.machine.zero_location(Size::S64, location);
         }
 
         self.machine.adjust_stack(static_area_size as _);
@@ -577,21 +815,37 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         // Locals are allocated on the stack from higher address to lower address,
         // so we won't skip the stack guard page here.
         let mut stack_offset: usize = 0;
+        // A `v128` parameter takes two parameter slots: its low then its high 64 bits.
+        let mut param_slot = 1;
         for (i, param) in sig.params().iter().enumerate() {
             let sz = match *param {
                 Type::I32 | Type::F32 => Size::S32,
                 Type::I64 | Type::F64 => Size::S64,
                 Type::ExternRef | Type::FuncRef => Size::S64,
-                _ => unimplemented!(),
+                Type::V128 => {
+                    let [low, high] = Self::v128_halves(locations[i]);
+                    for half in [low, high] {
+                        let loc = self.machine.get_call_param_location(
+                            param_slot,
+                            Size::S64,
+                            &mut stack_offset,
+                            calling_convention,
+                        );
+                        self.machine.emit_relaxed_mov(Size::S64, loc, half);
+                        param_slot += 1;
+                    }
+                    continue;
+                }
             };
             let loc = self.machine.get_call_param_location(
-                i + 1,
+                param_slot,
                 sz,
                 &mut stack_offset,
                 calling_convention,
             );
             self.machine
                 .move_location_extend(sz, false, loc, Size::S64, locations[i]);
+            param_slot += 1;
         }
 
         // Load vmctx into it's GPR.
@@ -606,13 +860,29 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         let mut init_stack_loc_cnt = 0;
         let mut last_stack_loc = Location::Memory(self.machine.local_pointer(), i32::MAX);
         for i in sig.params().len()..n {
-            match locations[i] {
+            let location = if is_v128(i) {
+                let [low, high] = Self::v128_halves(locations[i]);
+                self.machine.zero_location(Size::S64, low);
+                self.machine.zero_location(Size::S64, high);
+                // The slot this local would have had is cleared with the others,
+                // so that they stay contiguous.
+                match self
+                    .machine
+                    .get_local_location(i, callee_saved_regs_size + v128_area_size)
+                {
+                    Location::GPR(_) => continue,
+                    location => location,
+                }
+            } else {
+                locations[i]
+            };
+            match location {
                 Location::Memory(_, _) => {
                     init_stack_loc_cnt += 1;
-                    last_stack_loc = cmp::min(last_stack_loc, locations[i]);
+                    last_stack_loc = cmp::min(last_stack_loc, location);
                 }
                 Location::GPR(_) => {
-                    self.machine.zero_location(Size::S64, locations[i]);
+                    self.machine.zero_location(Size::S64, location);
                 }
                 _ => unreachable!(),
             }
@@ -715,6 +985,96 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         );
     }
 
+    /// Emits a call to the `memory.atomic.wait`/`memory.atomic.notify`
+    /// builtin `builtin`, whose operands after the address are popped from
+    /// the value stack with the types `operand_types`, and pushes its `i32`
+    /// result.
+    fn emit_memory_atomic_call(
+        &mut self,
+        memarg: &MemoryImmediate,
+        builtin: VMBuiltinFunctionIndex,
+        operand_types: &[WpType],
+    ) -> Result<(), CodegenError> {
+        let mut operands = (0..operand_types.len())
+            .map(|_| self.value_stack.pop().unwrap())
+            .collect::<Vec<_>>();
+        operands.reverse();
+        let dst = self.value_stack.pop().unwrap();
+
+        // The builtins take the effective address as an `i64`, which is
+        // computed in place, unless the address is a constant.
+        let addr = if let Location::Imm32(dst) = dst {
+            Location::Imm64(dst as u64 + memarg.offset)
+        } else {
+            let tmp_addr = self.machine.acquire_temp_gpr().unwrap();
+            self.machine
+                .move_location(Size::S32, dst, Location::GPR(tmp_addr));
+            if memarg.offset != 0 {
+                let tmp_offset = self.machine.acquire_temp_gpr().unwrap();
+                self.machine.move_location(
+                    Size::S64,
+                    Location::Imm64(memarg.offset),
+                    Location::GPR(tmp_offset),
+                );
+                self.machine.location_add(
+                    Size::S64,
+                    Location::GPR(tmp_offset),
+                    Location::GPR(tmp_addr),
+                    false,
+                );
+                self.machine.release_gpr(tmp_offset);
+            }
+            self.machine
+                .move_location(Size::S64, Location::GPR(tmp_addr), dst);
+            self.machine.release_gpr(tmp_addr);
+            dst
+        };
+
+        let mut locs = vec![dst];
+        locs.extend_from_slice(&operands);
+        self.release_locations_only_regs(&locs);
+
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                self.machine.get_vmctx_reg(),
+                self.vmoffsets.vmctx_builtin_function(builtin) as i32,
+            ),
+            Location::GPR(self.machine.get_grp_for_call()),
+        );
+
+        self.release_locations_only_osr_state(locs.len());
+
+        let memory_index = MemoryIndex::new(memarg.memory as usize);
+        // [vmctx, memory_index, addr, operands...] -> u32
+        let mut params = vec![Location::Imm32(memory_index.index() as u32), addr];
+        params.extend_from_slice(&operands);
+        let mut params_type = vec![WpType::I32, WpType::I64];
+        params_type.extend_from_slice(operand_types);
+        self.emit_call_native(
+            |this| {
+                this.machine
+                    .emit_call_register(this.machine.get_grp_for_call());
+            },
+            params.into_iter(),
+            params_type.into_iter(),
+        )?;
+
+        self.release_locations_only_stack(&locs);
+
+        let ret = self.acquire_locations(
+            &[(WpType::I32, MachineValue::WasmStack(self.value_stack.len()))],
+            false,
+        )[0];
+        self.value_stack.push(ret);
+        self.machine.move_location(
+            Size::S32,
+            Location::GPR(self.machine.get_gpr_for_ret()),
+            ret,
+        );
+        Ok(())
+    }
+
     /// Emits a Native ABI call sequence.
     ///
     /// The caller MUST NOT hold any temporary registers allocated by `acquire_temp_gpr` when calling
@@ -732,12 +1092,26 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         // Values pushed in this function are above the shadow region.
         self.state.stack_values.push(MachineValue::ExplicitShadow);
 
-        let params: Vec<_> = params.collect();
-        let params_size: Vec<_> = params_type
-            .map(|x| match x {
-                WpType::F32 | WpType::I32 => Size::S32,
-                WpType::V128 => unimplemented!(),
-                _ => Size::S64,
+        // A `v128` parameter is passed as two 64-bit parameters: its low then its high half.
+        let mut params_size = vec![];
+        let params: Vec<_> = params
+            .zip(params_type)
+            .flat_map(|(param, ty)| -> SmallVec<[_; 2]> {
+                match ty {
+                    WpType::F32 | WpType::I32 => {
+                        params_size.push(Size::S32);
+                        smallvec![param]
+                    }
+                    WpType::V128 => {
+                        let [low, high] = Self::v128_halves(param);
+                        params_size.extend([Size::S64, Size::S64]);
+                        smallvec![low, high]
+                    }
+                    _ => {
+                        params_size.push(Size::S64);
+                        smallvec![param]
+                    }
+                }
             })
             .collect();
 
@@ -981,6 +1355,44 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         );
     }
 
+    /// Emits a check that traps with `Interrupt` if the store was
+    /// interrupted, or if its epoch reached the epoch deadline.
+    fn emit_interrupt_check(&mut self) {
+        let epoch = self.machine.acquire_temp_gpr().unwrap();
+        let deadline = self.machine.acquire_temp_gpr().unwrap();
+
+        // Load the `VMInterrupts` pointer, then the epoch and its deadline.
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                self.machine.get_vmctx_reg(),
+                self.vmoffsets.vmctx_interrupts() as i32,
+            ),
+            Location::GPR(deadline),
+        );
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(deadline, self.vmoffsets.vminterrupts_epoch() as i32),
+            Location::GPR(epoch),
+        );
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                deadline,
+                self.vmoffsets.vminterrupts_epoch_deadline() as i32,
+            ),
+            Location::GPR(deadline),
+        );
+
+        self.machine
+            .location_cmp(Size::S64, Location::GPR(deadline), Location::GPR(epoch));
+        self.machine
+            .jmp_on_aboveequal(self.special_labels.interrupt);
+
+        self.machine.release_gpr(deadline);
+        self.machine.release_gpr(epoch);
+    }
+
     pub fn get_state_diff(&mut self) -> usize {
         if !self.track_state {
             return std::usize::MAX;
@@ -1009,7 +1421,6 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         self.state.register_values[self.machine.index_from_gpr(self.machine.get_vmctx_reg()).0] =
             MachineValue::Vmctx;
 
-        // TODO: Explicit stack check is not supported for now.
         let diff = self.state.diff(&self.machine.new_machine_state());
         let state_diff_id = self.fsm.diffs.len();
         self.fsm.diffs.push(diff);
@@ -1033,7 +1444,9 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             state_diff_id,
         });
 
-        // TODO: Full preemption by explicit signal checking
+        if self.config.enable_interrupts {
+            self.emit_interrupt_check();
+        }
 
         // We insert set StackOverflow as the default trap that can happen
         // anywhere in the function prologue.
@@ -1077,6 +1490,8 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             table_access_oob: machine.get_label(),
             indirect_call_null: machine.get_label(),
             bad_signature: machine.get_label(),
+            interrupt: machine.get_label(),
+            stack_overflow: machine.get_label(),
         };
 
         let fsm = FunctionStateMap::new(
@@ -1101,6 +1516,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             fp_stack: vec![],
             control_stack: vec![],
             stack_offset: MachineStackOffset(0),
+            v128_stack_slots: vec![],
             save_area_offset: None,
             state: machine.new_machine_state(),
             track_state: true,
@@ -1195,7 +1611,11 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     Location::Memory(tmp, 0)
                 };
 
-                self.machine.emit_relaxed_mov(Size::S64, src, loc);
+                if ty == WpType::V128 {
+                    self.move_v128(src, loc);
+                } else {
+                    self.machine.emit_relaxed_mov(Size::S64, src, loc);
+                }
 
                 self.machine.release_gpr(tmp);
             }
@@ -1244,6 +1664,8 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     } else {
                         self.machine.emit_relaxed_mov(Size::S64, loc, dst);
                     }
+                } else if ty == WpType::V128 {
+                    self.move_v128(loc, dst);
                 } else {
                     self.machine.emit_relaxed_mov(Size::S64, loc, dst);
                 }
@@ -1251,12 +1673,20 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             }
             Operator::LocalGet { local_index } => {
                 let local_index = local_index as usize;
+                let ty = match self.local_types[local_index] {
+                    WpType::V128 => WpType::V128,
+                    _ => WpType::I64,
+                };
                 let ret = self.acquire_locations(
-                    &[(WpType::I64, MachineValue::WasmStack(self.value_stack.len()))],
+                    &[(ty, MachineValue::WasmStack(self.value_stack.len()))],
                     false,
                 )[0];
-                self.machine
-                    .emit_relaxed_mov(Size::S64, self.locals[local_index], ret);
+                if ty == WpType::V128 {
+                    self.move_v128(self.locals[local_index], ret);
+                } else {
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, self.locals[local_index], ret);
+                }
                 self.value_stack.push(ret);
                 if self.local_types[local_index].is_float() {
                     self.fp_stack
@@ -1286,6 +1716,8 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         self.machine
                             .emit_relaxed_mov(Size::S64, loc, self.locals[local_index]);
                     }
+                } else if self.local_types[local_index] == WpType::V128 {
+                    self.move_v128(loc, self.locals[local_index]);
                 } else {
                     self.machine
                         .emit_relaxed_mov(Size::S64, loc, self.locals[local_index]);
@@ -1314,6 +1746,8 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         self.machine
                             .emit_relaxed_mov(Size::S64, loc, self.locals[local_index]);
                     }
+                } else if self.local_types[local_index] == WpType::V128 {
+                    self.move_v128(loc, self.locals[local_index]);
                 } else {
                     self.machine
                         .emit_relaxed_mov(Size::S64, loc, self.locals[local_index]);
@@ -2639,6 +3073,8 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         );
                         self.fp_stack
                             .push(FloatValue::new(self.value_stack.len() - 1));
+                    } else if return_types[0] == WpType::V128 {
+                        self.move_v128_from_ret(ret);
                     } else {
                         self.machine.move_location(
                             Size::S64,
@@ -2863,6 +3299,8 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         );
                         self.fp_stack
                             .push(FloatValue::new(self.value_stack.len() - 1));
+                    } else if return_types[0] == WpType::V128 {
+                        self.move_v128_from_ret(ret);
                     } else {
                         self.machine.move_location(
                             Size::S64,
@@ -2943,6 +3381,11 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             // `TypedSelect` must be used for extern refs so ref counting should
             // be done with TypedSelect. But otherwise they're the same.
             Operator::TypedSelect { .. } | Operator::Select => {
+                let ty = if self.is_v128(self.value_stack[self.value_stack.len() - 2]) {
+                    WpType::V128
+                } else {
+                    WpType::I64
+                };
                 let cond = self.pop_value_released();
                 let v_b = self.pop_value_released();
                 let v_a = self.pop_value_released();
@@ -2959,7 +3402,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         None
                     };
                 let ret = self.acquire_locations(
-                    &[(WpType::I64, MachineValue::WasmStack(self.value_stack.len()))],
+                    &[(ty, MachineValue::WasmStack(self.value_stack.len()))],
                     false,
                 )[0];
                 self.value_stack.push(ret);
@@ -2977,6 +3420,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     {
                         self.machine.canonicalize_nan(fp.to_size(), v_a, ret);
                     }
+                    _ if ty == WpType::V128 => self.move_v128(v_a, ret),
                     _ => {
                         if v_a != ret {
                             self.machine.emit_relaxed_mov(Size::S64, v_a, ret);
@@ -2992,6 +3436,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     {
                         self.machine.canonicalize_nan(fp.to_size(), v_b, ret);
                     }
+                    _ if ty == WpType::V128 => self.move_v128(v_b, ret),
                     _ => {
                         if v_b != ret {
                             self.machine.emit_relaxed_mov(Size::S64, v_b, ret);
@@ -3049,7 +3494,9 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 });
                 self.machine.emit_label(label);
 
-                // TODO: Re-enable interrupt signal check without branching
+                if self.config.enable_interrupts {
+                    self.emit_interrupt_check();
+                }
             }
             Operator::Nop => {}
             Operator::MemorySize { mem, mem_byte: _ } => {
@@ -4027,11 +4474,15 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             )],
                             false,
                         )[0];
-                        self.machine.move_location(
-                            Size::S64,
-                            Location::GPR(self.machine.get_gpr_for_ret()),
-                            loc,
-                        );
+                        if frame.returns[0] == WpType::V128 {
+                            self.move_v128_from_ret(loc);
+                        } else {
+                            self.machine.move_location(
+                                Size::S64,
+                                Location::GPR(self.machine.get_gpr_for_ret()),
+                                loc,
+                            );
+                        }
                         self.value_stack.push(loc);
                         if frame.returns[0].is_float() {
                             self.fp_stack
@@ -4041,6 +4492,27 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     }
                 }
             }
+            Operator::MemoryAtomicWait32 { ref memarg } => {
+                self.emit_memory_atomic_call(
+                    memarg,
+                    VMBuiltinFunctionIndex::get_memory_atomic_wait32_index(),
+                    &[WpType::I32, WpType::I64],
+                )?;
+            }
+            Operator::MemoryAtomicWait64 { ref memarg } => {
+                self.emit_memory_atomic_call(
+                    memarg,
+                    VMBuiltinFunctionIndex::get_memory_atomic_wait64_index(),
+                    &[WpType::I64, WpType::I64],
+                )?;
+            }
+            Operator::MemoryAtomicNotify { ref memarg } => {
+                self.emit_memory_atomic_call(
+                    memarg,
+                    VMBuiltinFunctionIndex::get_memory_atomic_notify_index(),
+                    &[WpType::I32],
+                )?;
+            }
             Operator::AtomicFence { flags: _ } => {
                 // Fence is a nop.
                 //
@@ -5843,6 +6315,451 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     [WpType::I32].iter().cloned(),
                 )?;
             }
+            Operator::V128Const { value } => {
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine
+                    .v128_const(u128::from_le_bytes(*value.bytes()), ret);
+            }
+            Operator::V128Load { ref memarg }
+            | Operator::V128Load8x8S { ref memarg }
+            | Operator::V128Load8x8U { ref memarg }
+            | Operator::V128Load16x4S { ref memarg }
+            | Operator::V128Load16x4U { ref memarg }
+            | Operator::V128Load32x2S { ref memarg }
+            | Operator::V128Load32x2U { ref memarg }
+            | Operator::V128Load8Splat { ref memarg }
+            | Operator::V128Load16Splat { ref memarg }
+            | Operator::V128Load32Splat { ref memarg }
+            | Operator::V128Load64Splat { ref memarg }
+            | Operator::V128Load32Zero { ref memarg }
+            | Operator::V128Load64Zero { ref memarg } => {
+                let target = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.op_memory(
+                    |this, need_check, imported_memories, offset, heap_access_oob| {
+                        this.machine.v128_load(
+                            &op,
+                            target,
+                            memarg,
+                            ret,
+                            need_check,
+                            imported_memories,
+                            offset,
+                            heap_access_oob,
+                        );
+                    },
+                );
+            }
+            Operator::V128Store { ref memarg } => {
+                let target_value = self.pop_value_released();
+                let target_addr = self.pop_value_released();
+                self.op_memory(
+                    |this, need_check, imported_memories, offset, heap_access_oob| {
+                        this.machine.v128_save(
+                            target_value,
+                            memarg,
+                            target_addr,
+                            need_check,
+                            imported_memories,
+                            offset,
+                            heap_access_oob,
+                        );
+                    },
+                );
+            }
+            Operator::V128Load8Lane { ref memarg, lane }
+            | Operator::V128Load16Lane { ref memarg, lane }
+            | Operator::V128Load32Lane { ref memarg, lane }
+            | Operator::V128Load64Lane { ref memarg, lane } => {
+                let value = self.pop_value_released();
+                let target = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.op_memory(
+                    |this, need_check, imported_memories, offset, heap_access_oob| {
+                        this.machine.v128_load_lane(
+                            &op,
+                            lane,
+                            value,
+                            target,
+                            memarg,
+                            ret,
+                            need_check,
+                            imported_memories,
+                            offset,
+                            heap_access_oob,
+                        );
+                    },
+                );
+            }
+            Operator::V128Store8Lane { ref memarg, lane }
+            | Operator::V128Store16Lane { ref memarg, lane }
+            | Operator::V128Store32Lane { ref memarg, lane }
+            | Operator::V128Store64Lane { ref memarg, lane } => {
+                let target_value = self.pop_value_released();
+                let target_addr = self.pop_value_released();
+                self.op_memory(
+                    |this, need_check, imported_memories, offset, heap_access_oob| {
+                        this.machine.v128_save_lane(
+                            &op,
+                            lane,
+                            target_value,
+                            memarg,
+                            target_addr,
+                            need_check,
+                            imported_memories,
+                            offset,
+                            heap_access_oob,
+                        );
+                    },
+                );
+            }
+            Operator::I8x16Splat
+            | Operator::I16x8Splat
+            | Operator::I32x4Splat
+            | Operator::I64x2Splat
+            | Operator::F32x4Splat
+            | Operator::F64x2Splat => {
+                let loc = self.pop_value_released();
+                match op {
+                    Operator::F32x4Splat => self.canonicalize_popped_float(Size::S32, loc)?,
+                    Operator::F64x2Splat => self.canonicalize_popped_float(Size::S64, loc)?,
+                    _ => {}
+                }
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_splat(&op, loc, ret);
+            }
+            Operator::I8x16ExtractLaneS { lane }
+            | Operator::I8x16ExtractLaneU { lane }
+            | Operator::I16x8ExtractLaneS { lane }
+            | Operator::I16x8ExtractLaneU { lane }
+            | Operator::I32x4ExtractLane { lane }
+            | Operator::I64x2ExtractLane { lane }
+            | Operator::F32x4ExtractLane { lane }
+            | Operator::F64x2ExtractLane { lane } => {
+                let ty = match op {
+                    Operator::I64x2ExtractLane { .. } => WpType::I64,
+                    Operator::F32x4ExtractLane { .. } => WpType::F32,
+                    Operator::F64x2ExtractLane { .. } => WpType::F64,
+                    _ => WpType::I32,
+                };
+                let loc = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(ty, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                if ty.is_float() {
+                    self.fp_stack
+                        .push(FloatValue::new(self.value_stack.len() - 1));
+                }
+                self.machine.v128_extract_lane(&op, lane, loc, ret);
+            }
+            Operator::I8x16ReplaceLane { lane }
+            | Operator::I16x8ReplaceLane { lane }
+            | Operator::I32x4ReplaceLane { lane }
+            | Operator::I64x2ReplaceLane { lane }
+            | Operator::F32x4ReplaceLane { lane }
+            | Operator::F64x2ReplaceLane { lane } => {
+                let value = self.pop_value_released();
+                match op {
+                    Operator::F32x4ReplaceLane { .. } => {
+                        self.canonicalize_popped_float(Size::S32, value)?
+                    }
+                    Operator::F64x2ReplaceLane { .. } => {
+                        self.canonicalize_popped_float(Size::S64, value)?
+                    }
+                    _ => {}
+                }
+                let loc = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_replace_lane(&op, lane, loc, value, ret);
+            }
+            Operator::I8x16Shuffle { ref lanes } => {
+                let loc_b = self.pop_value_released();
+                let loc_a = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_shuffle(lanes, loc_a, loc_b, ret);
+            }
+            Operator::V128AnyTrue
+            | Operator::I8x16AllTrue
+            | Operator::I16x8AllTrue
+            | Operator::I32x4AllTrue
+            | Operator::I64x2AllTrue
+            | Operator::I8x16Bitmask
+            | Operator::I16x8Bitmask
+            | Operator::I32x4Bitmask
+            | Operator::I64x2Bitmask => {
+                let loc = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::I32, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_test(&op, loc, ret);
+            }
+            Operator::I8x16Shl
+            | Operator::I8x16ShrS
+            | Operator::I8x16ShrU
+            | Operator::I16x8Shl
+            | Operator::I16x8ShrS
+            | Operator::I16x8ShrU
+            | Operator::I32x4Shl
+            | Operator::I32x4ShrS
+            | Operator::I32x4ShrU
+            | Operator::I64x2Shl
+            | Operator::I64x2ShrS
+            | Operator::I64x2ShrU => {
+                let count = self.pop_value_released();
+                let loc = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_shift(&op, loc, count, ret);
+            }
+            Operator::V128Not
+            | Operator::I8x16Abs
+            | Operator::I8x16Neg
+            | Operator::I8x16Popcnt
+            | Operator::I16x8ExtAddPairwiseI8x16S
+            | Operator::I16x8ExtAddPairwiseI8x16U
+            | Operator::I16x8Abs
+            | Operator::I16x8Neg
+            | Operator::I16x8ExtendLowI8x16S
+            | Operator::I16x8ExtendHighI8x16S
+            | Operator::I16x8ExtendLowI8x16U
+            | Operator::I16x8ExtendHighI8x16U
+            | Operator::I32x4ExtAddPairwiseI16x8S
+            | Operator::I32x4ExtAddPairwiseI16x8U
+            | Operator::I32x4Abs
+            | Operator::I32x4Neg
+            | Operator::I32x4ExtendLowI16x8S
+            | Operator::I32x4ExtendHighI16x8S
+            | Operator::I32x4ExtendLowI16x8U
+            | Operator::I32x4ExtendHighI16x8U
+            | Operator::I64x2Abs
+            | Operator::I64x2Neg
+            | Operator::I64x2ExtendLowI32x4S
+            | Operator::I64x2ExtendHighI32x4S
+            | Operator::I64x2ExtendLowI32x4U
+            | Operator::I64x2ExtendHighI32x4U
+            | Operator::F32x4Ceil
+            | Operator::F32x4Floor
+            | Operator::F32x4Trunc
+            | Operator::F32x4Nearest
+            | Operator::F32x4Abs
+            | Operator::F32x4Neg
+            | Operator::F32x4Sqrt
+            | Operator::F64x2Ceil
+            | Operator::F64x2Floor
+            | Operator::F64x2Trunc
+            | Operator::F64x2Nearest
+            | Operator::F64x2Abs
+            | Operator::F64x2Neg
+            | Operator::F64x2Sqrt
+            | Operator::I32x4TruncSatF32x4S
+            | Operator::I32x4TruncSatF32x4U
+            | Operator::F32x4ConvertI32x4S
+            | Operator::F32x4ConvertI32x4U
+            | Operator::I32x4TruncSatF64x2SZero
+            | Operator::I32x4TruncSatF64x2UZero
+            | Operator::F64x2ConvertLowI32x4S
+            | Operator::F64x2ConvertLowI32x4U
+            | Operator::F32x4DemoteF64x2Zero
+            | Operator::F64x2PromoteLowF32x4
+            | Operator::I32x4RelaxedTruncSatF32x4S
+            | Operator::I32x4RelaxedTruncSatF32x4U
+            | Operator::I32x4RelaxedTruncSatF64x2SZero
+            | Operator::I32x4RelaxedTruncSatF64x2UZero => {
+                let loc = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_unop(&op, loc, ret);
+                self.canonicalize_v128_result(&op, ret);
+            }
+            Operator::I8x16Swizzle
+            | Operator::I8x16Eq
+            | Operator::I8x16Ne
+            | Operator::I8x16LtS
+            | Operator::I8x16LtU
+            | Operator::I8x16GtS
+            | Operator::I8x16GtU
+            | Operator::I8x16LeS
+            | Operator::I8x16LeU
+            | Operator::I8x16GeS
+            | Operator::I8x16GeU
+            | Operator::I16x8Eq
+            | Operator::I16x8Ne
+            | Operator::I16x8LtS
+            | Operator::I16x8LtU
+            | Operator::I16x8GtS
+            | Operator::I16x8GtU
+            | Operator::I16x8LeS
+            | Operator::I16x8LeU
+            | Operator::I16x8GeS
+            | Operator::I16x8GeU
+            | Operator::I32x4Eq
+            | Operator::I32x4Ne
+            | Operator::I32x4LtS
+            | Operator::I32x4LtU
+            | Operator::I32x4GtS
+            | Operator::I32x4GtU
+            | Operator::I32x4LeS
+            | Operator::I32x4LeU
+            | Operator::I32x4GeS
+            | Operator::I32x4GeU
+            | Operator::I64x2Eq
+            | Operator::I64x2Ne
+            | Operator::I64x2LtS
+            | Operator::I64x2GtS
+            | Operator::I64x2LeS
+            | Operator::I64x2GeS
+            | Operator::F32x4Eq
+            | Operator::F32x4Ne
+            | Operator::F32x4Lt
+            | Operator::F32x4Gt
+            | Operator::F32x4Le
+            | Operator::F32x4Ge
+            | Operator::F64x2Eq
+            | Operator::F64x2Ne
+            | Operator::F64x2Lt
+            | Operator::F64x2Gt
+            | Operator::F64x2Le
+            | Operator::F64x2Ge
+            | Operator::V128And
+            | Operator::V128AndNot
+            | Operator::V128Or
+            | Operator::V128Xor
+            | Operator::I8x16NarrowI16x8S
+            | Operator::I8x16NarrowI16x8U
+            | Operator::I8x16Add
+            | Operator::I8x16AddSatS
+            | Operator::I8x16AddSatU
+            | Operator::I8x16Sub
+            | Operator::I8x16SubSatS
+            | Operator::I8x16SubSatU
+            | Operator::I8x16MinS
+            | Operator::I8x16MinU
+            | Operator::I8x16MaxS
+            | Operator::I8x16MaxU
+            | Operator::I8x16RoundingAverageU
+            | Operator::I16x8Q15MulrSatS
+            | Operator::I16x8NarrowI32x4S
+            | Operator::I16x8NarrowI32x4U
+            | Operator::I16x8Add
+            | Operator::I16x8AddSatS
+            | Operator::I16x8AddSatU
+            | Operator::I16x8Sub
+            | Operator::I16x8SubSatS
+            | Operator::I16x8SubSatU
+            | Operator::I16x8Mul
+            | Operator::I16x8MinS
+            | Operator::I16x8MinU
+            | Operator::I16x8MaxS
+            | Operator::I16x8MaxU
+            | Operator::I16x8RoundingAverageU
+            | Operator::I16x8ExtMulLowI8x16S
+            | Operator::I16x8ExtMulHighI8x16S
+            | Operator::I16x8ExtMulLowI8x16U
+            | Operator::I16x8ExtMulHighI8x16U
+            | Operator::I32x4Add
+            | Operator::I32x4Sub
+            | Operator::I32x4Mul
+            | Operator::I32x4MinS
+            | Operator::I32x4MinU
+            | Operator::I32x4MaxS
+            | Operator::I32x4MaxU
+            | Operator::I32x4DotI16x8S
+            | Operator::I32x4ExtMulLowI16x8S
+            | Operator::I32x4ExtMulHighI16x8S
+            | Operator::I32x4ExtMulLowI16x8U
+            | Operator::I32x4ExtMulHighI16x8U
+            | Operator::I64x2Add
+            | Operator::I64x2Sub
+            | Operator::I64x2Mul
+            | Operator::I64x2ExtMulLowI32x4S
+            | Operator::I64x2ExtMulHighI32x4S
+            | Operator::I64x2ExtMulLowI32x4U
+            | Operator::I64x2ExtMulHighI32x4U
+            | Operator::F32x4Add
+            | Operator::F32x4Sub
+            | Operator::F32x4Mul
+            | Operator::F32x4Div
+            | Operator::F32x4Min
+            | Operator::F32x4Max
+            | Operator::F32x4PMin
+            | Operator::F32x4PMax
+            | Operator::F64x2Add
+            | Operator::F64x2Sub
+            | Operator::F64x2Mul
+            | Operator::F64x2Div
+            | Operator::F64x2Min
+            | Operator::F64x2Max
+            | Operator::F64x2PMin
+            | Operator::F64x2PMax
+            | Operator::I8x16RelaxedSwizzle
+            | Operator::F32x4RelaxedMin
+            | Operator::F32x4RelaxedMax
+            | Operator::F64x2RelaxedMin
+            | Operator::F64x2RelaxedMax => {
+                let loc_b = self.pop_value_released();
+                let loc_a = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_binop(&op, loc_a, loc_b, ret);
+                self.canonicalize_v128_result(&op, ret);
+            }
+            Operator::V128Bitselect
+            | Operator::F32x4Fma
+            | Operator::F32x4Fms
+            | Operator::F64x2Fma
+            | Operator::F64x2Fms
+            | Operator::I8x16LaneSelect
+            | Operator::I16x8LaneSelect
+            | Operator::I32x4LaneSelect
+            | Operator::I64x2LaneSelect => {
+                let loc_c = self.pop_value_released();
+                let loc_b = self.pop_value_released();
+                let loc_a = self.pop_value_released();
+                let ret = self.acquire_locations(
+                    &[(WpType::V128, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )[0];
+                self.value_stack.push(ret);
+                self.machine.v128_ternop(&op, loc_a, loc_b, loc_c, ret);
+                self.canonicalize_v128_result(&op, ret);
+            }
             _ => {
                 return Err(CodegenError {
                     message: format!("not yet implemented: {:?}", op),
@@ -5880,6 +6797,43 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         self.machine.emit_label(self.special_labels.bad_signature);
         self.machine.emit_illegal_op(TrapCode::BadSignature);
 
+        self.machine.emit_label(self.special_labels.interrupt);
+        self.machine.emit_illegal_op(TrapCode::Interrupt);
+
+        if self.config.enable_stack_checks {
+            // The explicit stack check jumps here from the function prolog,
+            // with vmctx still in the first parameter. Raise the trap from a
+            // libcall rather than a signal.
+            self.machine.emit_label(self.special_labels.stack_overflow);
+            let vmctx = self
+                .machine
+                .get_simple_param_location(0, self.calling_convention);
+            let vmctx_reg = match vmctx {
+                Location::GPR(x) => x,
+                _ => unreachable!(),
+            };
+            self.machine.move_location(
+                Size::S64,
+                Location::Memory(
+                    vmctx_reg,
+                    self.vmoffsets
+                        .vmctx_builtin_function(VMBuiltinFunctionIndex::get_raise_trap_index())
+                        as i32,
+                ),
+                Location::GPR(self.machine.get_grp_for_call()),
+            );
+            self.machine.move_location(
+                Size::S32,
+                Location::Imm32(TrapCode::StackOverflow as u32),
+                vmctx,
+            );
+            if self.calling_convention == CallingConvention::WindowsFastcall {
+                self.machine.adjust_stack(32);
+            }
+            self.machine
+                .emit_call_register(self.machine.get_grp_for_call());
+        }
+
         // Notify the assembler backend to generate necessary code at end of function.
         self.machine.finalize_function();
 
@@ -64,7 +64,7 @@ impl Compiler for SinglepassCompiler {
         &self,
         target: &Target,
         compile_info: &CompileModuleInfo,
-        _module_translation: &ModuleTranslationState,
+        module_translation: &ModuleTranslationState,
         function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
     ) -> Result<Compilation, CompileError> {
         match target.triple().architecture {
@@ -94,6 +94,22 @@ impl Compiler for SinglepassCompiler {
         if compile_info.features.multi_value {
             return Err(CompileError::UnsupportedFeature("multivalue".to_string()));
         }
+        if compile_info
+            .module
+            .memories
+            .values()
+            .any(|memory| memory.memory64)
+        {
+            return Err(CompileError::UnsupportedFeature("memory64".to_string()));
+        }
+        if compile_info.module.memories.len() > 1 {
+            return Err(CompileError::UnsupportedFeature("multi-memory".to_string()));
+        }
+        if compile_info.features.exceptions {
+            return Err(CompileError::UnsupportedFeature(
+                "exception handling".to_string(),
+            ));
+        }
         let calling_convention = match target.triple().default_calling_convention() {
             Ok(CallingConvention::WindowsFastcall) => CallingConvention::WindowsFastcall,
             Ok(CallingConvention::SystemV) => CallingConvention::SystemV,
@@ -152,7 +168,7 @@ impl Compiler for SinglepassCompiler {
                 let middleware_chain = self
                     .config
                     .middlewares
-                    .generate_function_middleware_chain(i);
+                    .generate_function_middleware_chain_with_state(i, module_translation);
                 let mut reader =
                     MiddlewareBinaryReader::new_with_offset(input.data, input.module_offset);
                 reader.set_middleware_chain(middleware_chain);
@@ -10,6 +10,8 @@ use wasmer_types::Features;
 #[derive(Debug, Clone, MemoryUsage)]
 pub struct Singlepass {
     pub(crate) enable_nan_canonicalization: bool,
+    pub(crate) enable_interrupts: bool,
+    pub(crate) enable_stack_checks: bool,
     /// The middleware chain.
     pub(crate) middlewares: Vec<Arc<dyn ModuleMiddleware>>,
 }
@@ -20,6 +22,8 @@ impl Singlepass {
     pub fn new() -> Self {
         Self {
             enable_nan_canonicalization: true,
+            enable_interrupts: false,
+            enable_stack_checks: false,
             middlewares: vec![],
         }
     }
@@ -32,6 +36,20 @@ impl Singlepass {
         self.enable_nan_canonicalization = enable;
         self
     }
+
+    /// Enable or disable the explicit stack check in the prolog of every
+    /// function, disabled by default.
+    ///
+    /// With the check, a stack overflow traps with
+    /// `TrapCode::StackOverflow` without going through the guard page and
+    /// the signal handler, for hosts that install their own `SIGSEGV`
+    /// handling. The check reads the stack limit of the current thread with
+    /// a libcall, saving the parameters around it, which makes every call
+    /// noticeably slower.
+    pub fn explicit_stack_checks(&mut self, enable: bool) -> &mut Self {
+        self.enable_stack_checks = enable;
+        self
+    }
 }
 
 impl CompilerConfig for Singlepass {
@@ -40,6 +58,10 @@ impl CompilerConfig for Singlepass {
         // PIC code.
     }
 
+    fn interruptible(&mut self, enable: bool) {
+        self.enable_interrupts = enable;
+    }
+
     /// Transform it into the compiler
     fn compiler(self: Box<Self>) -> Box<dyn Compiler> {
         Box::new(SinglepassCompiler::new(*self))
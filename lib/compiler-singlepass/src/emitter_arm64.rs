@@ -83,6 +83,202 @@ pub enum GPROrMemory {
     Memory(GPR, i32),
 }
 
+/// A NEON instruction on 128 bits vectors, as `dst = op(src1, src2)`.
+///
+/// The suffix is the size of the lanes, of the narrow ones for the widening
+/// instructions. `Bsl` and `Umlal` also read `dst`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NeonOp {
+    AddB,
+    AddH,
+    AddS,
+    AddD,
+    SubB,
+    SubH,
+    SubS,
+    SubD,
+    SqaddB,
+    SqaddH,
+    UqaddB,
+    UqaddH,
+    SqsubB,
+    SqsubH,
+    UqsubB,
+    UqsubH,
+    MulH,
+    MulS,
+    SqrdmulhH,
+    SminB,
+    SminH,
+    SminS,
+    UminB,
+    UminH,
+    UminS,
+    SmaxB,
+    SmaxH,
+    SmaxS,
+    UmaxB,
+    UmaxH,
+    UmaxS,
+    UrhaddB,
+    UrhaddH,
+    CmeqB,
+    CmeqH,
+    CmeqS,
+    CmeqD,
+    CmgtB,
+    CmgtH,
+    CmgtS,
+    CmgtD,
+    CmgeB,
+    CmgeH,
+    CmgeS,
+    CmgeD,
+    CmhiB,
+    CmhiH,
+    CmhiS,
+    CmhsB,
+    CmhsH,
+    CmhsS,
+    SshlB,
+    SshlH,
+    SshlS,
+    SshlD,
+    UshlB,
+    UshlH,
+    UshlS,
+    UshlD,
+    AddpB,
+    AddpS,
+    AddpD,
+    FaddS,
+    FaddD,
+    FsubS,
+    FsubD,
+    FmulS,
+    FmulD,
+    FdivS,
+    FdivD,
+    FminS,
+    FminD,
+    FmaxS,
+    FmaxD,
+    FcmeqS,
+    FcmeqD,
+    FcmgtS,
+    FcmgtD,
+    FcmgeS,
+    FcmgeD,
+    And,
+    Bic,
+    Orr,
+    Eor,
+    Bsl,
+    Tbl,
+    SmullB,
+    Smull2B,
+    SmullH,
+    Smull2H,
+    SmullS,
+    Smull2S,
+    UmullB,
+    Umull2B,
+    UmullH,
+    Umull2H,
+    UmullS,
+    Umull2S,
+    UmlalS,
+}
+
+/// A NEON instruction on a 128 bits vector, as `dst = op(src)`.
+///
+/// The suffix is the size of the lanes, of the narrow ones for the widening
+/// and narrowing instructions. The `2` narrowing variants write the high half
+/// of `dst`, keeping its low half.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NeonUnOp {
+    Not,
+    Cnt,
+    AbsB,
+    AbsH,
+    AbsS,
+    AbsD,
+    NegB,
+    NegH,
+    NegS,
+    NegD,
+    CmeqZeroB,
+    CmeqZeroH,
+    CmeqZeroS,
+    CmeqZeroD,
+    Rev64S,
+    FabsS,
+    FabsD,
+    FnegS,
+    FnegD,
+    FsqrtS,
+    FsqrtD,
+    FrintpS,
+    FrintpD,
+    FrintmS,
+    FrintmD,
+    FrintzS,
+    FrintzD,
+    FrintnS,
+    FrintnD,
+    FcvtzsS,
+    FcvtzsD,
+    FcvtzuS,
+    FcvtzuD,
+    ScvtfS,
+    ScvtfD,
+    UcvtfS,
+    UcvtfD,
+    SaddlpB,
+    SaddlpH,
+    UaddlpB,
+    UaddlpH,
+    UaddlpS,
+    SxtlB,
+    Sxtl2B,
+    SxtlH,
+    Sxtl2H,
+    SxtlS,
+    Sxtl2S,
+    UxtlB,
+    Uxtl2B,
+    UxtlH,
+    Uxtl2H,
+    UxtlS,
+    Uxtl2S,
+    SqxtnB,
+    Sqxtn2B,
+    SqxtnH,
+    Sqxtn2H,
+    SqxtnS,
+    SqxtunB,
+    Sqxtun2B,
+    SqxtunH,
+    Sqxtun2H,
+    UqxtnS,
+    XtnS,
+    Fcvtn,
+    Fcvtl,
+    UmaxvB,
+    AddvH,
+    AddvS,
+}
+
+/// A NEON shift of a 128 bits vector by an immediate, as `dst = src op imm`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NeonShift {
+    SshrB,
+    SshrH,
+    SshrS,
+    SshrD,
+    ShlD,
+}
+
 pub trait EmitterARM64 {
     fn get_label(&mut self) -> Label;
     fn get_offset(&self) -> Offset;
@@ -197,6 +393,17 @@ pub trait EmitterARM64 {
     fn emit_fcvtzs(&mut self, sz_in: Size, src: Location, sz_out: Size, dst: Location);
     fn emit_fcvtzu(&mut self, sz_in: Size, src: Location, sz_out: Size, dst: Location);
 
+    fn emit_vldr(&mut self, reg: NEON, addr: Location);
+    fn emit_vstr(&mut self, reg: NEON, addr: Location);
+    fn emit_ld1r(&mut self, sz: Size, addr: GPR, dst: NEON);
+    fn emit_ins(&mut self, sz: Size, src: GPR, lane: u32, dst: NEON);
+    fn emit_umov(&mut self, sz: Size, src: NEON, lane: u32, dst: GPR);
+    fn emit_smov(&mut self, sz: Size, src: NEON, lane: u32, dst: GPR);
+    fn emit_dup(&mut self, sz: Size, src: GPR, dst: NEON);
+    fn emit_neon(&mut self, op: NeonOp, src1: NEON, src2: NEON, dst: NEON);
+    fn emit_neon_unop(&mut self, op: NeonUnOp, src: NEON, dst: NEON);
+    fn emit_neon_shift(&mut self, op: NeonShift, imm: u32, src: NEON, dst: NEON);
+
     fn emit_read_fpcr(&mut self, reg: GPR);
     fn emit_write_fpcr(&mut self, reg: GPR);
     fn emit_read_fpsr(&mut self, reg: GPR);
@@ -2387,6 +2594,282 @@ impl EmitterARM64 for Assembler {
     }
 
     // 1 011 0100 0100 000 => fpcr
+    fn emit_vldr(&mut self, reg: NEON, addr: Location) {
+        let reg = reg.into_index() as u32;
+        match addr {
+            Location::Memory(addr, disp) => {
+                assert!((disp >= -255) && (disp <= 255));
+                let addr = addr.into_index() as u32;
+                dynasm!(self ; ldur Q(reg), [X(addr), disp]);
+            }
+            Location::Memory2(addr, r2, Multiplier::One, 0) => {
+                let addr = addr.into_index() as u32;
+                let r2 = r2.into_index() as u32;
+                dynasm!(self ; ldr Q(reg), [X(addr), X(r2)]);
+            }
+            _ => panic!("singlepass can't emit LDR Q{:?}, {:?}", reg, addr),
+        }
+    }
+    fn emit_vstr(&mut self, reg: NEON, addr: Location) {
+        let reg = reg.into_index() as u32;
+        match addr {
+            Location::Memory(addr, disp) => {
+                assert!((disp >= -255) && (disp <= 255));
+                let addr = addr.into_index() as u32;
+                dynasm!(self ; stur Q(reg), [X(addr), disp]);
+            }
+            Location::Memory2(addr, r2, Multiplier::One, 0) => {
+                let addr = addr.into_index() as u32;
+                let r2 = r2.into_index() as u32;
+                dynasm!(self ; str Q(reg), [X(addr), X(r2)]);
+            }
+            _ => panic!("singlepass can't emit STR Q{:?}, {:?}", reg, addr),
+        }
+    }
+    fn emit_ld1r(&mut self, sz: Size, addr: GPR, dst: NEON) {
+        let addr = addr.into_index() as u32;
+        let dst = dst.into_index() as u32;
+        match sz {
+            Size::S8 => dynasm!(self ; ld1r {V(dst).B16 * 1}, [X(addr)]),
+            Size::S16 => dynasm!(self ; ld1r {V(dst).H8 * 1}, [X(addr)]),
+            Size::S32 => dynasm!(self ; ld1r {V(dst).S4 * 1}, [X(addr)]),
+            Size::S64 => dynasm!(self ; ld1r {V(dst).D2 * 1}, [X(addr)]),
+        }
+    }
+    fn emit_ins(&mut self, sz: Size, src: GPR, lane: u32, dst: NEON) {
+        let src = src.into_index() as u32;
+        let dst = dst.into_index() as u32;
+        match sz {
+            Size::S8 => dynasm!(self ; ins V(dst).B[lane], W(src)),
+            Size::S16 => dynasm!(self ; ins V(dst).H[lane], W(src)),
+            Size::S32 => dynasm!(self ; ins V(dst).S[lane], W(src)),
+            Size::S64 => dynasm!(self ; ins V(dst).D[lane], X(src)),
+        }
+    }
+    fn emit_umov(&mut self, sz: Size, src: NEON, lane: u32, dst: GPR) {
+        let src = src.into_index() as u32;
+        let dst = dst.into_index() as u32;
+        match sz {
+            Size::S8 => dynasm!(self ; umov W(dst), V(src).B[lane]),
+            Size::S16 => dynasm!(self ; umov W(dst), V(src).H[lane]),
+            Size::S32 => dynasm!(self ; umov W(dst), V(src).S[lane]),
+            Size::S64 => dynasm!(self ; umov X(dst), V(src).D[lane]),
+        }
+    }
+    fn emit_smov(&mut self, sz: Size, src: NEON, lane: u32, dst: GPR) {
+        let src = src.into_index() as u32;
+        let dst = dst.into_index() as u32;
+        match sz {
+            Size::S8 => dynasm!(self ; smov W(dst), V(src).B[lane]),
+            Size::S16 => dynasm!(self ; smov W(dst), V(src).H[lane]),
+            _ => panic!("singlepass can't emit SMOV {:?}", sz),
+        }
+    }
+    fn emit_dup(&mut self, sz: Size, src: GPR, dst: NEON) {
+        let src = src.into_index() as u32;
+        let dst = dst.into_index() as u32;
+        match sz {
+            Size::S8 => dynasm!(self ; dup V(dst).B16, W(src)),
+            Size::S16 => dynasm!(self ; dup V(dst).H8, W(src)),
+            Size::S32 => dynasm!(self ; dup V(dst).S4, W(src)),
+            Size::S64 => dynasm!(self ; dup V(dst).D2, X(src)),
+        }
+    }
+
+    fn emit_neon(&mut self, op: NeonOp, src1: NEON, src2: NEON, dst: NEON) {
+        let (src1, src2, dst) = (
+            src1.into_index() as u32,
+            src2.into_index() as u32,
+            dst.into_index() as u32,
+        );
+        match op {
+            NeonOp::AddB => dynasm!(self ; add V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::AddH => dynasm!(self ; add V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::AddS => dynasm!(self ; add V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::AddD => dynasm!(self ; add V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::SubB => dynasm!(self ; sub V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::SubH => dynasm!(self ; sub V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::SubS => dynasm!(self ; sub V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::SubD => dynasm!(self ; sub V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::SqaddB => dynasm!(self ; sqadd V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::SqaddH => dynasm!(self ; sqadd V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::UqaddB => dynasm!(self ; uqadd V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::UqaddH => dynasm!(self ; uqadd V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::SqsubB => dynasm!(self ; sqsub V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::SqsubH => dynasm!(self ; sqsub V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::UqsubB => dynasm!(self ; uqsub V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::UqsubH => dynasm!(self ; uqsub V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::MulH => dynasm!(self ; mul V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::MulS => dynasm!(self ; mul V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::SqrdmulhH => dynasm!(self ; sqrdmulh V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::SminB => dynasm!(self ; smin V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::SminH => dynasm!(self ; smin V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::SminS => dynasm!(self ; smin V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::UminB => dynasm!(self ; umin V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::UminH => dynasm!(self ; umin V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::UminS => dynasm!(self ; umin V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::SmaxB => dynasm!(self ; smax V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::SmaxH => dynasm!(self ; smax V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::SmaxS => dynasm!(self ; smax V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::UmaxB => dynasm!(self ; umax V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::UmaxH => dynasm!(self ; umax V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::UmaxS => dynasm!(self ; umax V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::UrhaddB => dynasm!(self ; urhadd V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::UrhaddH => dynasm!(self ; urhadd V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::CmeqB => dynasm!(self ; cmeq V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::CmeqH => dynasm!(self ; cmeq V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::CmeqS => dynasm!(self ; cmeq V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::CmeqD => dynasm!(self ; cmeq V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::CmgtB => dynasm!(self ; cmgt V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::CmgtH => dynasm!(self ; cmgt V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::CmgtS => dynasm!(self ; cmgt V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::CmgtD => dynasm!(self ; cmgt V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::CmgeB => dynasm!(self ; cmge V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::CmgeH => dynasm!(self ; cmge V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::CmgeS => dynasm!(self ; cmge V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::CmgeD => dynasm!(self ; cmge V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::CmhiB => dynasm!(self ; cmhi V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::CmhiH => dynasm!(self ; cmhi V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::CmhiS => dynasm!(self ; cmhi V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::CmhsB => dynasm!(self ; cmhs V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::CmhsH => dynasm!(self ; cmhs V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::CmhsS => dynasm!(self ; cmhs V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::SshlB => dynasm!(self ; sshl V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::SshlH => dynasm!(self ; sshl V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::SshlS => dynasm!(self ; sshl V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::SshlD => dynasm!(self ; sshl V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::UshlB => dynasm!(self ; ushl V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::UshlH => dynasm!(self ; ushl V(dst).H8, V(src1).H8, V(src2).H8),
+            NeonOp::UshlS => dynasm!(self ; ushl V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::UshlD => dynasm!(self ; ushl V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::AddpB => dynasm!(self ; addp V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::AddpS => dynasm!(self ; addp V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::AddpD => dynasm!(self ; addp V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FaddS => dynasm!(self ; fadd V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FaddD => dynasm!(self ; fadd V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FsubS => dynasm!(self ; fsub V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FsubD => dynasm!(self ; fsub V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FmulS => dynasm!(self ; fmul V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FmulD => dynasm!(self ; fmul V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FdivS => dynasm!(self ; fdiv V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FdivD => dynasm!(self ; fdiv V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FminS => dynasm!(self ; fmin V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FminD => dynasm!(self ; fmin V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FmaxS => dynasm!(self ; fmax V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FmaxD => dynasm!(self ; fmax V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FcmeqS => dynasm!(self ; fcmeq V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FcmeqD => dynasm!(self ; fcmeq V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FcmgtS => dynasm!(self ; fcmgt V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FcmgtD => dynasm!(self ; fcmgt V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::FcmgeS => dynasm!(self ; fcmge V(dst).S4, V(src1).S4, V(src2).S4),
+            NeonOp::FcmgeD => dynasm!(self ; fcmge V(dst).D2, V(src1).D2, V(src2).D2),
+            NeonOp::And => dynasm!(self ; and V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::Bic => dynasm!(self ; bic V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::Orr => dynasm!(self ; orr V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::Eor => dynasm!(self ; eor V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::Bsl => dynasm!(self ; bsl V(dst).B16, V(src1).B16, V(src2).B16),
+            NeonOp::Tbl => dynasm!(self ; tbl V(dst).B16, {V(src1).B16 * 1}, V(src2).B16),
+            NeonOp::SmullB => dynasm!(self ; smull V(dst).H8, V(src1).B8, V(src2).B8),
+            NeonOp::Smull2B => dynasm!(self ; smull2 V(dst).H8, V(src1).B16, V(src2).B16),
+            NeonOp::SmullH => dynasm!(self ; smull V(dst).S4, V(src1).H4, V(src2).H4),
+            NeonOp::Smull2H => dynasm!(self ; smull2 V(dst).S4, V(src1).H8, V(src2).H8),
+            NeonOp::SmullS => dynasm!(self ; smull V(dst).D2, V(src1).S2, V(src2).S2),
+            NeonOp::Smull2S => dynasm!(self ; smull2 V(dst).D2, V(src1).S4, V(src2).S4),
+            NeonOp::UmullB => dynasm!(self ; umull V(dst).H8, V(src1).B8, V(src2).B8),
+            NeonOp::Umull2B => dynasm!(self ; umull2 V(dst).H8, V(src1).B16, V(src2).B16),
+            NeonOp::UmullH => dynasm!(self ; umull V(dst).S4, V(src1).H4, V(src2).H4),
+            NeonOp::Umull2H => dynasm!(self ; umull2 V(dst).S4, V(src1).H8, V(src2).H8),
+            NeonOp::UmullS => dynasm!(self ; umull V(dst).D2, V(src1).S2, V(src2).S2),
+            NeonOp::Umull2S => dynasm!(self ; umull2 V(dst).D2, V(src1).S4, V(src2).S4),
+            NeonOp::UmlalS => dynasm!(self ; umlal V(dst).D2, V(src1).S2, V(src2).S2),
+        }
+    }
+    fn emit_neon_unop(&mut self, op: NeonUnOp, src: NEON, dst: NEON) {
+        let (src, dst) = (src.into_index() as u32, dst.into_index() as u32);
+        match op {
+            NeonUnOp::Not => dynasm!(self ; not V(dst).B16, V(src).B16),
+            NeonUnOp::Cnt => dynasm!(self ; cnt V(dst).B16, V(src).B16),
+            NeonUnOp::AbsB => dynasm!(self ; abs V(dst).B16, V(src).B16),
+            NeonUnOp::AbsH => dynasm!(self ; abs V(dst).H8, V(src).H8),
+            NeonUnOp::AbsS => dynasm!(self ; abs V(dst).S4, V(src).S4),
+            NeonUnOp::AbsD => dynasm!(self ; abs V(dst).D2, V(src).D2),
+            NeonUnOp::NegB => dynasm!(self ; neg V(dst).B16, V(src).B16),
+            NeonUnOp::NegH => dynasm!(self ; neg V(dst).H8, V(src).H8),
+            NeonUnOp::NegS => dynasm!(self ; neg V(dst).S4, V(src).S4),
+            NeonUnOp::NegD => dynasm!(self ; neg V(dst).D2, V(src).D2),
+            NeonUnOp::CmeqZeroB => dynasm!(self ; cmeq V(dst).B16, V(src).B16, 0),
+            NeonUnOp::CmeqZeroH => dynasm!(self ; cmeq V(dst).H8, V(src).H8, 0),
+            NeonUnOp::CmeqZeroS => dynasm!(self ; cmeq V(dst).S4, V(src).S4, 0),
+            NeonUnOp::CmeqZeroD => dynasm!(self ; cmeq V(dst).D2, V(src).D2, 0),
+            NeonUnOp::Rev64S => dynasm!(self ; rev64 V(dst).S4, V(src).S4),
+            NeonUnOp::FabsS => dynasm!(self ; fabs V(dst).S4, V(src).S4),
+            NeonUnOp::FabsD => dynasm!(self ; fabs V(dst).D2, V(src).D2),
+            NeonUnOp::FnegS => dynasm!(self ; fneg V(dst).S4, V(src).S4),
+            NeonUnOp::FnegD => dynasm!(self ; fneg V(dst).D2, V(src).D2),
+            NeonUnOp::FsqrtS => dynasm!(self ; fsqrt V(dst).S4, V(src).S4),
+            NeonUnOp::FsqrtD => dynasm!(self ; fsqrt V(dst).D2, V(src).D2),
+            NeonUnOp::FrintpS => dynasm!(self ; frintp V(dst).S4, V(src).S4),
+            NeonUnOp::FrintpD => dynasm!(self ; frintp V(dst).D2, V(src).D2),
+            NeonUnOp::FrintmS => dynasm!(self ; frintm V(dst).S4, V(src).S4),
+            NeonUnOp::FrintmD => dynasm!(self ; frintm V(dst).D2, V(src).D2),
+            NeonUnOp::FrintzS => dynasm!(self ; frintz V(dst).S4, V(src).S4),
+            NeonUnOp::FrintzD => dynasm!(self ; frintz V(dst).D2, V(src).D2),
+            NeonUnOp::FrintnS => dynasm!(self ; frintn V(dst).S4, V(src).S4),
+            NeonUnOp::FrintnD => dynasm!(self ; frintn V(dst).D2, V(src).D2),
+            NeonUnOp::FcvtzsS => dynasm!(self ; fcvtzs V(dst).S4, V(src).S4),
+            NeonUnOp::FcvtzsD => dynasm!(self ; fcvtzs V(dst).D2, V(src).D2),
+            NeonUnOp::FcvtzuS => dynasm!(self ; fcvtzu V(dst).S4, V(src).S4),
+            NeonUnOp::FcvtzuD => dynasm!(self ; fcvtzu V(dst).D2, V(src).D2),
+            NeonUnOp::ScvtfS => dynasm!(self ; scvtf V(dst).S4, V(src).S4),
+            NeonUnOp::ScvtfD => dynasm!(self ; scvtf V(dst).D2, V(src).D2),
+            NeonUnOp::UcvtfS => dynasm!(self ; ucvtf V(dst).S4, V(src).S4),
+            NeonUnOp::UcvtfD => dynasm!(self ; ucvtf V(dst).D2, V(src).D2),
+            NeonUnOp::SaddlpB => dynasm!(self ; saddlp V(dst).H8, V(src).B16),
+            NeonUnOp::SaddlpH => dynasm!(self ; saddlp V(dst).S4, V(src).H8),
+            NeonUnOp::UaddlpB => dynasm!(self ; uaddlp V(dst).H8, V(src).B16),
+            NeonUnOp::UaddlpH => dynasm!(self ; uaddlp V(dst).S4, V(src).H8),
+            NeonUnOp::UaddlpS => dynasm!(self ; uaddlp V(dst).D2, V(src).S4),
+            NeonUnOp::SxtlB => dynasm!(self ; sxtl V(dst).H8, V(src).B8),
+            NeonUnOp::Sxtl2B => dynasm!(self ; sxtl2 V(dst).H8, V(src).B16),
+            NeonUnOp::SxtlH => dynasm!(self ; sxtl V(dst).S4, V(src).H4),
+            NeonUnOp::Sxtl2H => dynasm!(self ; sxtl2 V(dst).S4, V(src).H8),
+            NeonUnOp::SxtlS => dynasm!(self ; sxtl V(dst).D2, V(src).S2),
+            NeonUnOp::Sxtl2S => dynasm!(self ; sxtl2 V(dst).D2, V(src).S4),
+            NeonUnOp::UxtlB => dynasm!(self ; uxtl V(dst).H8, V(src).B8),
+            NeonUnOp::Uxtl2B => dynasm!(self ; uxtl2 V(dst).H8, V(src).B16),
+            NeonUnOp::UxtlH => dynasm!(self ; uxtl V(dst).S4, V(src).H4),
+            NeonUnOp::Uxtl2H => dynasm!(self ; uxtl2 V(dst).S4, V(src).H8),
+            NeonUnOp::UxtlS => dynasm!(self ; uxtl V(dst).D2, V(src).S2),
+            NeonUnOp::Uxtl2S => dynasm!(self ; uxtl2 V(dst).D2, V(src).S4),
+            NeonUnOp::SqxtnB => dynasm!(self ; sqxtn V(dst).B8, V(src).H8),
+            NeonUnOp::Sqxtn2B => dynasm!(self ; sqxtn2 V(dst).B16, V(src).H8),
+            NeonUnOp::SqxtnH => dynasm!(self ; sqxtn V(dst).H4, V(src).S4),
+            NeonUnOp::Sqxtn2H => dynasm!(self ; sqxtn2 V(dst).H8, V(src).S4),
+            NeonUnOp::SqxtnS => dynasm!(self ; sqxtn V(dst).S2, V(src).D2),
+            NeonUnOp::SqxtunB => dynasm!(self ; sqxtun V(dst).B8, V(src).H8),
+            NeonUnOp::Sqxtun2B => dynasm!(self ; sqxtun2 V(dst).B16, V(src).H8),
+            NeonUnOp::SqxtunH => dynasm!(self ; sqxtun V(dst).H4, V(src).S4),
+            NeonUnOp::Sqxtun2H => dynasm!(self ; sqxtun2 V(dst).H8, V(src).S4),
+            NeonUnOp::UqxtnS => dynasm!(self ; uqxtn V(dst).S2, V(src).D2),
+            NeonUnOp::XtnS => dynasm!(self ; xtn V(dst).S2, V(src).D2),
+            NeonUnOp::Fcvtn => dynasm!(self ; fcvtn V(dst).S2, V(src).D2),
+            NeonUnOp::Fcvtl => dynasm!(self ; fcvtl V(dst).D2, V(src).S2),
+            NeonUnOp::UmaxvB => dynasm!(self ; umaxv B(dst), V(src).B16),
+            NeonUnOp::AddvH => dynasm!(self ; addv H(dst), V(src).H8),
+            NeonUnOp::AddvS => dynasm!(self ; addv S(dst), V(src).S4),
+        }
+    }
+    fn emit_neon_shift(&mut self, op: NeonShift, imm: u32, src: NEON, dst: NEON) {
+        let (src, dst) = (src.into_index() as u32, dst.into_index() as u32);
+        match op {
+            NeonShift::SshrB => dynasm!(self ; sshr V(dst).B16, V(src).B16, imm),
+            NeonShift::SshrH => dynasm!(self ; sshr V(dst).H8, V(src).H8, imm),
+            NeonShift::SshrS => dynasm!(self ; sshr V(dst).S4, V(src).S4, imm),
+            NeonShift::SshrD => dynasm!(self ; sshr V(dst).D2, V(src).D2, imm),
+            NeonShift::ShlD => dynasm!(self ; shl V(dst).D2, V(src).D2, imm),
+        }
+    }
+
     fn emit_read_fpcr(&mut self, reg: GPR) {
         dynasm!(self ; mrs X(reg as u32), 0b1_011_0100_0100_000);
     }
@@ -2420,7 +2903,19 @@ pub fn gen_std_trampoline_arm64(
         ; mov X(args as u32), x2
     );
 
-    let stack_args = sig.params().len().saturating_sub(7); //1st arg is ctx, not an actual arg
+    // The params with their offsets in args_rets: `v128` are passed in two slots,
+    // for their low and high halves.
+    let params: Vec<(Type, usize)> = sig
+        .params()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, ty)| match ty {
+            Type::V128 => vec![(Type::I64, i * 16), (Type::I64, i * 16 + 8)],
+            _ => vec![(*ty, i * 16)],
+        })
+        .collect();
+
+    let stack_args = params.len().saturating_sub(7); //1st arg is ctx, not an actual arg
     let mut stack_offset = stack_args as u32 * 8;
     if stack_args > 0 {
         if stack_offset % 16 != 0 {
@@ -2433,7 +2928,7 @@ pub fn gen_std_trampoline_arm64(
     // Move arguments to their locations.
     // `callee_vmctx` is already in the first argument register, so no need to move.
     let mut caller_stack_offset: i32 = 0;
-    for (i, param) in sig.params().iter().enumerate() {
+    for (i, (param, offset)) in params.iter().enumerate() {
         let sz = match *param {
             Type::I32 | Type::F32 => Size::S32,
             Type::I64 | Type::F64 => Size::S64,
@@ -2449,7 +2944,7 @@ pub fn gen_std_trampoline_arm64(
                 a.emit_ldr(
                     sz,
                     Location::GPR(GPR::from_index(i + 1).unwrap()),
-                    Location::Memory(args, (i * 16) as i32),
+                    Location::Memory(args, *offset as i32),
                 );
             }
             _ => {
@@ -2480,7 +2975,7 @@ pub fn gen_std_trampoline_arm64(
                 a.emit_ldr(
                     sz,
                     Location::GPR(GPR::X16),
-                    Location::Memory(args, (i * 16) as i32),
+                    Location::Memory(args, *offset as i32),
                 );
                 a.emit_str(
                     sz,
@@ -2509,6 +3004,9 @@ pub fn gen_std_trampoline_arm64(
     // Write return value.
     if !sig.results().is_empty() {
         a.emit_str(Size::S64, Location::GPR(GPR::X0), Location::Memory(args, 0));
+        if sig.results()[0] == Type::V128 {
+            a.emit_str(Size::S64, Location::GPR(GPR::X1), Location::Memory(args, 8));
+        }
     }
 
     // Restore stack.
@@ -2569,47 +3067,59 @@ pub fn gen_std_dynamic_import_trampoline_arm64(
         let mut stack_param_count: usize = 0;
 
         for (i, ty) in sig.params().iter().enumerate() {
-            let source_loc = match argalloc.next(*ty, calling_convention) {
-                Some(ARM64Register::GPR(gpr)) => Location::GPR(gpr),
-                Some(ARM64Register::NEON(neon)) => Location::SIMD(neon),
-                None => {
-                    let sz = match calling_convention {
-                        CallingConvention::AppleAarch64 => match *ty {
-                            Type::I32 | Type::F32 => Size::S32,
-                            _ => {
-                                if stack_param_count & 7 != 0 {
-                                    stack_param_count = (stack_param_count + 7) & !7;
-                                };
-                                Size::S64
-                            }
-                        },
-                        _ => Size::S64,
-                    };
-                    a.emit_ldr(
-                        sz,
-                        Location::GPR(GPR::X26),
-                        Location::Memory(GPR::XzrSp, (stack_offset + 16 + stack_param_count) as _),
-                    );
-                    stack_param_count += match sz {
-                        Size::S32 => 4,
-                        Size::S64 => 8,
-                        _ => unreachable!(),
-                    };
-                    Location::GPR(GPR::X26)
-                }
+            // `v128` params are passed in two slots, for their low and high halves.
+            let (halves, ty) = match ty {
+                Type::V128 => (2, Type::I64),
+                _ => (1, *ty),
             };
-            a.emit_str(
-                Size::S64,
-                source_loc,
-                Location::Memory(GPR::XzrSp, (i * 16) as _),
-            );
+            for half in 0..halves {
+                let source_loc = match argalloc.next(ty, calling_convention) {
+                    Some(ARM64Register::GPR(gpr)) => Location::GPR(gpr),
+                    Some(ARM64Register::NEON(neon)) => Location::SIMD(neon),
+                    None => {
+                        let sz = match calling_convention {
+                            CallingConvention::AppleAarch64 => match ty {
+                                Type::I32 | Type::F32 => Size::S32,
+                                _ => {
+                                    if stack_param_count & 7 != 0 {
+                                        stack_param_count = (stack_param_count + 7) & !7;
+                                    };
+                                    Size::S64
+                                }
+                            },
+                            _ => Size::S64,
+                        };
+                        a.emit_ldr(
+                            sz,
+                            Location::GPR(GPR::X26),
+                            Location::Memory(
+                                GPR::XzrSp,
+                                (stack_offset + 16 + stack_param_count) as _,
+                            ),
+                        );
+                        stack_param_count += match sz {
+                            Size::S32 => 4,
+                            Size::S64 => 8,
+                            _ => unreachable!(),
+                        };
+                        Location::GPR(GPR::X26)
+                    }
+                };
+                a.emit_str(
+                    Size::S64,
+                    source_loc,
+                    Location::Memory(GPR::XzrSp, (i * 16 + half * 8) as _),
+                );
+            }
 
-            // Zero upper 64 bits.
-            a.emit_str(
-                Size::S64,
-                Location::GPR(GPR::XzrSp),                       // XZR here
-                Location::Memory(GPR::XzrSp, (i * 16 + 8) as _), // XSP here
-            );
+            if halves == 1 {
+                // Zero upper 64 bits.
+                a.emit_str(
+                    Size::S64,
+                    Location::GPR(GPR::XzrSp), // XZR here
+                    Location::Memory(GPR::XzrSp, (i * 16 + 8) as _), // XSP here
+                );
+            }
         }
     }
 
@@ -2639,6 +3149,13 @@ pub fn gen_std_dynamic_import_trampoline_arm64(
             Location::GPR(GPR::X0),
             Location::Memory(GPR::XzrSp, 0),
         );
+        if sig.results()[0] == Type::V128 {
+            a.emit_ldr(
+                Size::S64,
+                Location::GPR(GPR::X1),
+                Location::Memory(GPR::XzrSp, 8),
+            );
+        }
     }
 
     // Release values array.
@@ -2685,24 +3202,30 @@ pub fn gen_import_call_trampoline_arm64(
 ) -> CustomSection {
     let mut a = Assembler::new(0);
 
+    // `v128` params are passed as two integers, for their low and high halves.
+    let params: Vec<Type> = sig
+        .params()
+        .iter()
+        .flat_map(|ty| match ty {
+            Type::V128 => vec![Type::I64, Type::I64],
+            _ => vec![*ty],
+        })
+        .collect();
+
     // Singlepass internally treats all arguments as integers
     // For the standard System V calling convention requires
     //  floating point arguments to be passed in NEON registers.
     //  Translation is expensive, so only do it if needed.
-    if sig
-        .params()
-        .iter()
-        .any(|&x| x == Type::F32 || x == Type::F64)
-    {
+    if params.iter().any(|&x| x == Type::F32 || x == Type::F64) {
         match calling_convention {
             _ => {
                 let mut param_locations: Vec<Location> = vec![];
 
                 // Allocate stack space for arguments.
-                let stack_offset: i32 = if sig.params().len() > 7 {
+                let stack_offset: i32 = if params.len() > 7 {
                     7 * 8
                 } else {
-                    (sig.params().len() as i32) * 8
+                    (params.len() as i32) * 8
                 };
                 let stack_offset = if stack_offset & 15 != 0 {
                     stack_offset + 8
@@ -2729,7 +3252,7 @@ pub fn gen_import_call_trampoline_arm64(
                 }
 
                 // Store all arguments to the stack to prevent overwrite.
-                for i in 0..sig.params().len() {
+                for i in 0..params.len() {
                     let loc = match i {
                         0..=6 => {
                             static PARAM_REGS: &[GPR] = &[
@@ -2754,7 +3277,7 @@ pub fn gen_import_call_trampoline_arm64(
                 let mut caller_stack_offset: i32 = 0;
                 let mut argalloc = ArgumentRegisterAllocator::default();
                 argalloc.next(Type::I64, calling_convention).unwrap(); // skip VMContext
-                for (i, ty) in sig.params().iter().enumerate() {
+                for (i, ty) in params.iter().enumerate() {
                     let prev_loc = param_locations[i];
                     let targ = match argalloc.next(*ty, calling_convention) {
                         Some(ARM64Register::GPR(gpr)) => Location::GPR(gpr),
@@ -60,6 +60,141 @@ pub enum Precision {
     Double,
 }
 
+/// A packed SSE instruction working on two XMM registers, as `dst = dst op src`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PackedOp {
+    Paddb,
+    Paddw,
+    Paddd,
+    Paddq,
+    Paddsb,
+    Paddsw,
+    Paddusb,
+    Paddusw,
+    Psubb,
+    Psubw,
+    Psubd,
+    Psubq,
+    Psubsb,
+    Psubsw,
+    Psubusb,
+    Psubusw,
+    Pmullw,
+    Pmulld,
+    Pmulhw,
+    Pmulhuw,
+    Pmulhrsw,
+    Pmuludq,
+    Pmuldq,
+    Pmaddwd,
+    Pmaddubsw,
+    Pminsb,
+    Pminsw,
+    Pminsd,
+    Pminub,
+    Pminuw,
+    Pminud,
+    Pmaxsb,
+    Pmaxsw,
+    Pmaxsd,
+    Pmaxub,
+    Pmaxuw,
+    Pmaxud,
+    Pavgb,
+    Pavgw,
+    Pabsb,
+    Pabsw,
+    Pabsd,
+    Pcmpeqb,
+    Pcmpeqw,
+    Pcmpeqd,
+    Pcmpeqq,
+    Pcmpgtb,
+    Pcmpgtw,
+    Pcmpgtd,
+    Pcmpgtq,
+    Pand,
+    Pandn,
+    Por,
+    Pxor,
+    Packsswb,
+    Packssdw,
+    Packuswb,
+    Packusdw,
+    Punpcklbw,
+    Punpckhbw,
+    Punpcklwd,
+    Punpckhwd,
+    Punpcklqdq,
+    Pmovsxbw,
+    Pmovsxwd,
+    Pmovsxdq,
+    Pmovzxbw,
+    Pmovzxwd,
+    Pmovzxdq,
+    Pshufb,
+    Psllw,
+    Pslld,
+    Psllq,
+    Psrlw,
+    Psrld,
+    Psrlq,
+    Psraw,
+    Psrad,
+    Addps,
+    Addpd,
+    Subps,
+    Subpd,
+    Mulps,
+    Mulpd,
+    Divps,
+    Divpd,
+    Minps,
+    Minpd,
+    Maxps,
+    Maxpd,
+    Sqrtps,
+    Sqrtpd,
+    Andnps,
+    Andnpd,
+    Orps,
+    Orpd,
+    Xorps,
+    Xorpd,
+    Unpcklps,
+    Cvtdq2ps,
+    Cvttps2dq,
+    Cvtdq2pd,
+    Cvttpd2dq,
+    Cvtps2pd,
+    Cvtpd2ps,
+}
+
+/// A packed SSE instruction working on two XMM registers with an immediate,
+/// as `dst = op(dst, src, imm)`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PackedImmOp {
+    Pshufd,
+    Pshuflw,
+    Shufps,
+    Pblendw,
+    Roundps,
+    Roundpd,
+    Cmpps,
+    Cmppd,
+}
+
+/// A packed SSE shift of an XMM register by an immediate.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PackedShift {
+    Psllq,
+    Psrlw,
+    Psrld,
+    Psrlq,
+    Psrad,
+    Psrldq,
+}
+
 pub trait EmitterX64 {
     fn get_simd_arch(&self) -> Option<&CpuFeature>;
     fn get_label(&mut self) -> Label;
@@ -195,6 +330,15 @@ pub trait EmitterX64 {
     fn emit_vblendvps(&mut self, src1: XMM, src2: XMMOrMemory, mask: XMM, dst: XMM);
     fn emit_vblendvpd(&mut self, src1: XMM, src2: XMMOrMemory, mask: XMM, dst: XMM);
 
+    fn emit_movdqu(&mut self, src: XMMOrMemory, dst: XMMOrMemory);
+    fn emit_packed(&mut self, op: PackedOp, src: XMM, dst: XMM);
+    fn emit_packed_imm(&mut self, op: PackedImmOp, imm: u8, src: XMM, dst: XMM);
+    fn emit_packed_shift(&mut self, op: PackedShift, imm: u8, dst: XMM);
+    fn emit_pinsr(&mut self, sz: Size, src: GPROrMemory, lane: u8, dst: XMM);
+    fn emit_pextr(&mut self, sz: Size, src: XMM, lane: u8, dst: GPROrMemory);
+    fn emit_movmsk(&mut self, sz: Size, src: XMM, dst: GPR);
+    fn emit_ptest(&mut self, src: XMM, dst: XMM);
+
     fn emit_test_gpr_64(&mut self, reg: GPR);
 
     fn emit_ud2(&mut self);
@@ -1847,6 +1991,211 @@ impl EmitterX64 for AssemblerX64 {
         }
     }
 
+    // The packed instructions use the legacy SSE encodings, available on all
+    // the supported targets: the AVX ones never leave the upper halves of the
+    // YMM registers dirty, so mixing both doesn't cost a transition.
+    fn emit_movdqu(&mut self, src: XMMOrMemory, dst: XMMOrMemory) {
+        match (src, dst) {
+            (XMMOrMemory::XMM(src), XMMOrMemory::XMM(dst)) => {
+                dynasm!(self ; movdqu Rx(dst as u8), Rx(src as u8))
+            }
+            (XMMOrMemory::Memory(base, disp), XMMOrMemory::XMM(dst)) => {
+                dynasm!(self ; movdqu Rx(dst as u8), [Rq(base as u8) + disp])
+            }
+            (XMMOrMemory::XMM(src), XMMOrMemory::Memory(base, disp)) => {
+                dynasm!(self ; movdqu [Rq(base as u8) + disp], Rx(src as u8))
+            }
+            _ => panic!("singlepass can't emit MOVDQU {:?} {:?}", src, dst),
+        };
+    }
+    fn emit_packed(&mut self, op: PackedOp, src: XMM, dst: XMM) {
+        let (src, dst) = (src as u8, dst as u8);
+        match op {
+            PackedOp::Paddb => dynasm!(self ; paddb Rx(dst), Rx(src)),
+            PackedOp::Paddw => dynasm!(self ; paddw Rx(dst), Rx(src)),
+            PackedOp::Paddd => dynasm!(self ; paddd Rx(dst), Rx(src)),
+            PackedOp::Paddq => dynasm!(self ; paddq Rx(dst), Rx(src)),
+            PackedOp::Paddsb => dynasm!(self ; paddsb Rx(dst), Rx(src)),
+            PackedOp::Paddsw => dynasm!(self ; paddsw Rx(dst), Rx(src)),
+            PackedOp::Paddusb => dynasm!(self ; paddusb Rx(dst), Rx(src)),
+            PackedOp::Paddusw => dynasm!(self ; paddusw Rx(dst), Rx(src)),
+            PackedOp::Psubb => dynasm!(self ; psubb Rx(dst), Rx(src)),
+            PackedOp::Psubw => dynasm!(self ; psubw Rx(dst), Rx(src)),
+            PackedOp::Psubd => dynasm!(self ; psubd Rx(dst), Rx(src)),
+            PackedOp::Psubq => dynasm!(self ; psubq Rx(dst), Rx(src)),
+            PackedOp::Psubsb => dynasm!(self ; psubsb Rx(dst), Rx(src)),
+            PackedOp::Psubsw => dynasm!(self ; psubsw Rx(dst), Rx(src)),
+            PackedOp::Psubusb => dynasm!(self ; psubusb Rx(dst), Rx(src)),
+            PackedOp::Psubusw => dynasm!(self ; psubusw Rx(dst), Rx(src)),
+            PackedOp::Pmullw => dynasm!(self ; pmullw Rx(dst), Rx(src)),
+            PackedOp::Pmulld => dynasm!(self ; pmulld Rx(dst), Rx(src)),
+            PackedOp::Pmulhw => dynasm!(self ; pmulhw Rx(dst), Rx(src)),
+            PackedOp::Pmulhuw => dynasm!(self ; pmulhuw Rx(dst), Rx(src)),
+            PackedOp::Pmulhrsw => dynasm!(self ; pmulhrsw Rx(dst), Rx(src)),
+            PackedOp::Pmuludq => dynasm!(self ; pmuludq Rx(dst), Rx(src)),
+            PackedOp::Pmuldq => dynasm!(self ; pmuldq Rx(dst), Rx(src)),
+            PackedOp::Pmaddwd => dynasm!(self ; pmaddwd Rx(dst), Rx(src)),
+            PackedOp::Pmaddubsw => dynasm!(self ; pmaddubsw Rx(dst), Rx(src)),
+            PackedOp::Pminsb => dynasm!(self ; pminsb Rx(dst), Rx(src)),
+            PackedOp::Pminsw => dynasm!(self ; pminsw Rx(dst), Rx(src)),
+            PackedOp::Pminsd => dynasm!(self ; pminsd Rx(dst), Rx(src)),
+            PackedOp::Pminub => dynasm!(self ; pminub Rx(dst), Rx(src)),
+            PackedOp::Pminuw => dynasm!(self ; pminuw Rx(dst), Rx(src)),
+            PackedOp::Pminud => dynasm!(self ; pminud Rx(dst), Rx(src)),
+            PackedOp::Pmaxsb => dynasm!(self ; pmaxsb Rx(dst), Rx(src)),
+            PackedOp::Pmaxsw => dynasm!(self ; pmaxsw Rx(dst), Rx(src)),
+            PackedOp::Pmaxsd => dynasm!(self ; pmaxsd Rx(dst), Rx(src)),
+            PackedOp::Pmaxub => dynasm!(self ; pmaxub Rx(dst), Rx(src)),
+            PackedOp::Pmaxuw => dynasm!(self ; pmaxuw Rx(dst), Rx(src)),
+            PackedOp::Pmaxud => dynasm!(self ; pmaxud Rx(dst), Rx(src)),
+            PackedOp::Pavgb => dynasm!(self ; pavgb Rx(dst), Rx(src)),
+            PackedOp::Pavgw => dynasm!(self ; pavgw Rx(dst), Rx(src)),
+            PackedOp::Pabsb => dynasm!(self ; pabsb Rx(dst), Rx(src)),
+            PackedOp::Pabsw => dynasm!(self ; pabsw Rx(dst), Rx(src)),
+            PackedOp::Pabsd => dynasm!(self ; pabsd Rx(dst), Rx(src)),
+            PackedOp::Pcmpeqb => dynasm!(self ; pcmpeqb Rx(dst), Rx(src)),
+            PackedOp::Pcmpeqw => dynasm!(self ; pcmpeqw Rx(dst), Rx(src)),
+            PackedOp::Pcmpeqd => dynasm!(self ; pcmpeqd Rx(dst), Rx(src)),
+            PackedOp::Pcmpeqq => dynasm!(self ; pcmpeqq Rx(dst), Rx(src)),
+            PackedOp::Pcmpgtb => dynasm!(self ; pcmpgtb Rx(dst), Rx(src)),
+            PackedOp::Pcmpgtw => dynasm!(self ; pcmpgtw Rx(dst), Rx(src)),
+            PackedOp::Pcmpgtd => dynasm!(self ; pcmpgtd Rx(dst), Rx(src)),
+            PackedOp::Pcmpgtq => dynasm!(self ; pcmpgtq Rx(dst), Rx(src)),
+            PackedOp::Pand => dynasm!(self ; pand Rx(dst), Rx(src)),
+            PackedOp::Pandn => dynasm!(self ; pandn Rx(dst), Rx(src)),
+            PackedOp::Por => dynasm!(self ; por Rx(dst), Rx(src)),
+            PackedOp::Pxor => dynasm!(self ; pxor Rx(dst), Rx(src)),
+            PackedOp::Packsswb => dynasm!(self ; packsswb Rx(dst), Rx(src)),
+            PackedOp::Packssdw => dynasm!(self ; packssdw Rx(dst), Rx(src)),
+            PackedOp::Packuswb => dynasm!(self ; packuswb Rx(dst), Rx(src)),
+            PackedOp::Packusdw => dynasm!(self ; packusdw Rx(dst), Rx(src)),
+            PackedOp::Punpcklbw => dynasm!(self ; punpcklbw Rx(dst), Rx(src)),
+            PackedOp::Punpckhbw => dynasm!(self ; punpckhbw Rx(dst), Rx(src)),
+            PackedOp::Punpcklwd => dynasm!(self ; punpcklwd Rx(dst), Rx(src)),
+            PackedOp::Punpckhwd => dynasm!(self ; punpckhwd Rx(dst), Rx(src)),
+            PackedOp::Punpcklqdq => dynasm!(self ; punpcklqdq Rx(dst), Rx(src)),
+            PackedOp::Pmovsxbw => dynasm!(self ; pmovsxbw Rx(dst), Rx(src)),
+            PackedOp::Pmovsxwd => dynasm!(self ; pmovsxwd Rx(dst), Rx(src)),
+            PackedOp::Pmovsxdq => dynasm!(self ; pmovsxdq Rx(dst), Rx(src)),
+            PackedOp::Pmovzxbw => dynasm!(self ; pmovzxbw Rx(dst), Rx(src)),
+            PackedOp::Pmovzxwd => dynasm!(self ; pmovzxwd Rx(dst), Rx(src)),
+            PackedOp::Pmovzxdq => dynasm!(self ; pmovzxdq Rx(dst), Rx(src)),
+            PackedOp::Pshufb => dynasm!(self ; pshufb Rx(dst), Rx(src)),
+            PackedOp::Psllw => dynasm!(self ; psllw Rx(dst), Rx(src)),
+            PackedOp::Pslld => dynasm!(self ; pslld Rx(dst), Rx(src)),
+            PackedOp::Psllq => dynasm!(self ; psllq Rx(dst), Rx(src)),
+            PackedOp::Psrlw => dynasm!(self ; psrlw Rx(dst), Rx(src)),
+            PackedOp::Psrld => dynasm!(self ; psrld Rx(dst), Rx(src)),
+            PackedOp::Psrlq => dynasm!(self ; psrlq Rx(dst), Rx(src)),
+            PackedOp::Psraw => dynasm!(self ; psraw Rx(dst), Rx(src)),
+            PackedOp::Psrad => dynasm!(self ; psrad Rx(dst), Rx(src)),
+            PackedOp::Addps => dynasm!(self ; addps Rx(dst), Rx(src)),
+            PackedOp::Addpd => dynasm!(self ; addpd Rx(dst), Rx(src)),
+            PackedOp::Subps => dynasm!(self ; subps Rx(dst), Rx(src)),
+            PackedOp::Subpd => dynasm!(self ; subpd Rx(dst), Rx(src)),
+            PackedOp::Mulps => dynasm!(self ; mulps Rx(dst), Rx(src)),
+            PackedOp::Mulpd => dynasm!(self ; mulpd Rx(dst), Rx(src)),
+            PackedOp::Divps => dynasm!(self ; divps Rx(dst), Rx(src)),
+            PackedOp::Divpd => dynasm!(self ; divpd Rx(dst), Rx(src)),
+            PackedOp::Minps => dynasm!(self ; minps Rx(dst), Rx(src)),
+            PackedOp::Minpd => dynasm!(self ; minpd Rx(dst), Rx(src)),
+            PackedOp::Maxps => dynasm!(self ; maxps Rx(dst), Rx(src)),
+            PackedOp::Maxpd => dynasm!(self ; maxpd Rx(dst), Rx(src)),
+            PackedOp::Sqrtps => dynasm!(self ; sqrtps Rx(dst), Rx(src)),
+            PackedOp::Sqrtpd => dynasm!(self ; sqrtpd Rx(dst), Rx(src)),
+            PackedOp::Andnps => dynasm!(self ; andnps Rx(dst), Rx(src)),
+            PackedOp::Andnpd => dynasm!(self ; andnpd Rx(dst), Rx(src)),
+            PackedOp::Orps => dynasm!(self ; orps Rx(dst), Rx(src)),
+            PackedOp::Orpd => dynasm!(self ; orpd Rx(dst), Rx(src)),
+            PackedOp::Xorps => dynasm!(self ; xorps Rx(dst), Rx(src)),
+            PackedOp::Xorpd => dynasm!(self ; xorpd Rx(dst), Rx(src)),
+            PackedOp::Unpcklps => dynasm!(self ; unpcklps Rx(dst), Rx(src)),
+            PackedOp::Cvtdq2ps => dynasm!(self ; cvtdq2ps Rx(dst), Rx(src)),
+            PackedOp::Cvttps2dq => dynasm!(self ; cvttps2dq Rx(dst), Rx(src)),
+            PackedOp::Cvtdq2pd => dynasm!(self ; cvtdq2pd Rx(dst), Rx(src)),
+            PackedOp::Cvttpd2dq => dynasm!(self ; cvttpd2dq Rx(dst), Rx(src)),
+            PackedOp::Cvtps2pd => dynasm!(self ; cvtps2pd Rx(dst), Rx(src)),
+            PackedOp::Cvtpd2ps => dynasm!(self ; cvtpd2ps Rx(dst), Rx(src)),
+        }
+    }
+    fn emit_packed_imm(&mut self, op: PackedImmOp, imm: u8, src: XMM, dst: XMM) {
+        let (src, dst, imm) = (src as u8, dst as u8, imm as i8);
+        match op {
+            PackedImmOp::Pshufd => dynasm!(self ; pshufd Rx(dst), Rx(src), imm),
+            PackedImmOp::Pshuflw => dynasm!(self ; pshuflw Rx(dst), Rx(src), imm),
+            PackedImmOp::Shufps => dynasm!(self ; shufps Rx(dst), Rx(src), imm),
+            PackedImmOp::Pblendw => dynasm!(self ; pblendw Rx(dst), Rx(src), imm),
+            PackedImmOp::Roundps => dynasm!(self ; roundps Rx(dst), Rx(src), imm),
+            PackedImmOp::Roundpd => dynasm!(self ; roundpd Rx(dst), Rx(src), imm),
+            PackedImmOp::Cmpps => dynasm!(self ; cmpps Rx(dst), Rx(src), imm),
+            PackedImmOp::Cmppd => dynasm!(self ; cmppd Rx(dst), Rx(src), imm),
+        }
+    }
+    fn emit_packed_shift(&mut self, op: PackedShift, imm: u8, dst: XMM) {
+        let (dst, imm) = (dst as u8, imm as i8);
+        match op {
+            PackedShift::Psllq => dynasm!(self ; psllq Rx(dst), imm),
+            PackedShift::Psrlw => dynasm!(self ; psrlw Rx(dst), imm),
+            PackedShift::Psrld => dynasm!(self ; psrld Rx(dst), imm),
+            PackedShift::Psrlq => dynasm!(self ; psrlq Rx(dst), imm),
+            PackedShift::Psrad => dynasm!(self ; psrad Rx(dst), imm),
+            PackedShift::Psrldq => dynasm!(self ; psrldq Rx(dst), imm),
+        }
+    }
+    fn emit_pinsr(&mut self, sz: Size, src: GPROrMemory, lane: u8, dst: XMM) {
+        let (dst, lane) = (dst as u8, lane as i8);
+        match (sz, src) {
+            (Size::S8, GPROrMemory::GPR(src)) => dynasm!(self ; pinsrb Rx(dst), Rd(src as u8), lane),
+            (Size::S8, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pinsrb Rx(dst), BYTE [Rq(base as u8) + disp], lane)
+            }
+            (Size::S16, GPROrMemory::GPR(src)) => dynasm!(self ; pinsrw Rx(dst), Rd(src as u8), lane),
+            (Size::S16, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pinsrw Rx(dst), WORD [Rq(base as u8) + disp], lane)
+            }
+            (Size::S32, GPROrMemory::GPR(src)) => dynasm!(self ; pinsrd Rx(dst), Rd(src as u8), lane),
+            (Size::S32, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pinsrd Rx(dst), DWORD [Rq(base as u8) + disp], lane)
+            }
+            (Size::S64, GPROrMemory::GPR(src)) => dynasm!(self ; pinsrq Rx(dst), Rq(src as u8), lane),
+            (Size::S64, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pinsrq Rx(dst), QWORD [Rq(base as u8) + disp], lane)
+            }
+        }
+    }
+    fn emit_pextr(&mut self, sz: Size, src: XMM, lane: u8, dst: GPROrMemory) {
+        let (src, lane) = (src as u8, lane as i8);
+        match (sz, dst) {
+            (Size::S8, GPROrMemory::GPR(dst)) => dynasm!(self ; pextrb Rd(dst as u8), Rx(src), lane),
+            (Size::S8, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pextrb BYTE [Rq(base as u8) + disp], Rx(src), lane)
+            }
+            (Size::S16, GPROrMemory::GPR(dst)) => dynasm!(self ; pextrw Rd(dst as u8), Rx(src), lane),
+            (Size::S16, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pextrw WORD [Rq(base as u8) + disp], Rx(src), lane)
+            }
+            (Size::S32, GPROrMemory::GPR(dst)) => dynasm!(self ; pextrd Rd(dst as u8), Rx(src), lane),
+            (Size::S32, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pextrd DWORD [Rq(base as u8) + disp], Rx(src), lane)
+            }
+            (Size::S64, GPROrMemory::GPR(dst)) => dynasm!(self ; pextrq Rq(dst as u8), Rx(src), lane),
+            (Size::S64, GPROrMemory::Memory(base, disp)) => {
+                dynasm!(self ; pextrq QWORD [Rq(base as u8) + disp], Rx(src), lane)
+            }
+        }
+    }
+    fn emit_movmsk(&mut self, sz: Size, src: XMM, dst: GPR) {
+        match sz {
+            Size::S8 => dynasm!(self ; pmovmskb Rd(dst as u8), Rx(src as u8)),
+            Size::S32 => dynasm!(self ; movmskps Rd(dst as u8), Rx(src as u8)),
+            Size::S64 => dynasm!(self ; movmskpd Rd(dst as u8), Rx(src as u8)),
+            _ => panic!("singlepass can't emit MOVMSK {:?} {:?} {:?}", sz, src, dst),
+        }
+    }
+    fn emit_ptest(&mut self, src: XMM, dst: XMM) {
+        dynasm!(self ; ptest Rx(dst as u8), Rx(src as u8));
+    }
+
     fn emit_test_gpr_64(&mut self, reg: GPR) {
         dynasm!(self ; test Rq(reg as u8), Rq(reg as u8));
     }
@@ -7,7 +7,7 @@ use dynasmrt::{AssemblyOffset, DynamicLabel};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 pub use wasmer_compiler::wasmparser::MemoryImmediate;
-use wasmer_compiler::wasmparser::Type as WpType;
+use wasmer_compiler::wasmparser::{Operator, Type as WpType};
 use wasmer_compiler::{
     Architecture, CallingConvention, CpuFeature, CustomSection, FunctionBody,
     InstructionAddressMap, Relocation, RelocationTarget, Target, TrapInformation,
@@ -49,6 +49,11 @@ pub struct TrapTable {
 // all machine seems to have a page this size, so not per arch for now
 pub const NATIVE_PAGE_SIZE: usize = 4096;
 
+/// The stack space the explicit stack check leaves below the frame of each
+/// function, for the values it pushes and for the host code it calls, which
+/// includes reading the stack limit and raising the stack overflow trap.
+pub const STACK_CHECK_RESERVE: usize = 32 * 1024;
+
 pub struct MachineStackOffset(pub usize);
 
 pub trait Machine {
@@ -234,6 +239,11 @@ pub trait Machine {
     fn emit_function_prolog(&mut self);
     /// emit native function epilog (depending on the calling Convention, like "MOV RBP, RSP / POP RBP")
     fn emit_function_epilog(&mut self);
+    /// Emit the explicit stack check of the function prolog: jumps to `label`
+    /// if the stack pointer is above the stack limit, in `limit`, by less than
+    /// `size` bytes.
+    /// The parameters are still in their registers and must be preserved.
+    fn emit_stack_check(&mut self, limit: Self::GPR, size: u32, label: Label);
     /// handle return value, with optionnal cannonicalization if wanted
     fn emit_function_return_value(
         &mut self,
@@ -277,6 +287,8 @@ pub trait Machine {
     fn emit_call_location(&mut self, location: Location<Self::GPR, Self::SIMD>);
     /// get the gpr for the return of generic values
     fn get_gpr_for_ret(&self) -> Self::GPR;
+    /// get the gpr for the high 64 bits of a returned v128 (the low ones are in `get_gpr_for_ret`)
+    fn get_gpr_for_ret_high(&self) -> Self::GPR;
     /// get the simd for the return of float/double values
     fn get_simd_for_ret(&self) -> Self::SIMD;
 
@@ -2169,6 +2181,135 @@ pub trait Machine {
         ret: Location<Self::GPR, Self::SIMD>,
     );
 
+    // The SIMD operators. `v128` values always live in 16-byte stack slots,
+    // and `op` selects the variant of the operation for the ones sharing a shape.
+
+    /// Materialize a V128 constant
+    fn v128_const(&mut self, value: u128, ret: Location<Self::GPR, Self::SIMD>);
+    /// V128 load, also extending or splatting a narrower value
+    fn v128_load(
+        &mut self,
+        op: &Operator,
+        addr: Location<Self::GPR, Self::SIMD>,
+        memarg: &MemoryImmediate,
+        ret: Location<Self::GPR, Self::SIMD>,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    );
+    /// V128 save
+    fn v128_save(
+        &mut self,
+        value: Location<Self::GPR, Self::SIMD>,
+        memarg: &MemoryImmediate,
+        addr: Location<Self::GPR, Self::SIMD>,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    );
+    /// Load a lane of a V128 from memory
+    fn v128_load_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        value: Location<Self::GPR, Self::SIMD>,
+        addr: Location<Self::GPR, Self::SIMD>,
+        memarg: &MemoryImmediate,
+        ret: Location<Self::GPR, Self::SIMD>,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    );
+    /// Save a lane of a V128 to memory
+    fn v128_save_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        value: Location<Self::GPR, Self::SIMD>,
+        memarg: &MemoryImmediate,
+        addr: Location<Self::GPR, Self::SIMD>,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    );
+    /// Splat a scalar to all the lanes of a V128
+    fn v128_splat(
+        &mut self,
+        op: &Operator,
+        loc: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Extract a lane of a V128 to a scalar
+    fn v128_extract_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        loc: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Replace a lane of a V128 with a scalar
+    fn v128_replace_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        loc: Location<Self::GPR, Self::SIMD>,
+        value: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Shuffle the bytes of 2 V128 values
+    fn v128_shuffle(
+        &mut self,
+        lanes: &[u8; 16],
+        loc_a: Location<Self::GPR, Self::SIMD>,
+        loc_b: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Test the lanes of a V128, result in a GPR (`any_true`, `all_true` and `bitmask`)
+    fn v128_test(
+        &mut self,
+        op: &Operator,
+        loc: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Shift the lanes of a V128 by a scalar
+    fn v128_shift(
+        &mut self,
+        op: &Operator,
+        loc: Location<Self::GPR, Self::SIMD>,
+        count: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Unary operator on a V128
+    fn v128_unop(
+        &mut self,
+        op: &Operator,
+        loc: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Binary operator on 2 V128 values
+    fn v128_binop(
+        &mut self,
+        op: &Operator,
+        loc_a: Location<Self::GPR, Self::SIMD>,
+        loc_b: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Ternary operator on 3 V128 values
+    fn v128_ternop(
+        &mut self,
+        op: &Operator,
+        loc_a: Location<Self::GPR, Self::SIMD>,
+        loc_b: Location<Self::GPR, Self::SIMD>,
+        loc_c: Location<Self::GPR, Self::SIMD>,
+        ret: Location<Self::GPR, Self::SIMD>,
+    );
+    /// Cannonicalize the NaN lanes of a V128 holding F32 (`sz` is S32) or F64 (`sz` is S64) values
+    fn v128_canonicalize_nan(&mut self, sz: Size, loc: Location<Self::GPR, Self::SIMD>);
+
     /// Standard function Trampoline generation
     fn gen_std_trampoline(
         &self,
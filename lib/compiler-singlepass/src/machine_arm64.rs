@@ -9,7 +9,7 @@ use crate::unwind::{UnwindInstructions, UnwindOps};
 use dynasmrt::{aarch64::Aarch64Relocation, VecAssembler};
 #[cfg(feature = "unwind")]
 use gimli::{write::CallFrameInstruction, AArch64};
-use wasmer_compiler::wasmparser::Type as WpType;
+use wasmer_compiler::wasmparser::{Operator, Type as WpType};
 use wasmer_compiler::{
     CallingConvention, CustomSection, FunctionBody, InstructionAddressMap, Relocation,
     RelocationKind, RelocationTarget, SourceLoc, TrapInformation,
@@ -977,15 +977,18 @@ impl MachineARM64 {
         self.release_gpr(tmp_bound);
         self.release_gpr(tmp_base);
 
+        // `memarg.align` is the log2 of the alignment.
         let align = memarg.align;
-        if check_alignment && align != 1 {
+        if check_alignment && align != 0 {
+            let aligned = self.get_label();
             self.assembler.emit_tst(
                 Size::S64,
-                Location::Imm32((align - 1).into()),
+                Location::Imm32((1 << align) - 1),
                 Location::GPR(tmp_addr),
             );
-            self.assembler
-                .emit_bcond_label_far(Condition::Ne, heap_access_oob);
+            self.assembler.emit_bcond_label_far(Condition::Eq, aligned);
+            self.emit_illegal_op(TrapCode::UnalignedAtomic);
+            self.emit_label(aligned);
         }
         let begin = self.assembler.get_offset().0;
         cb(self, tmp_addr);
@@ -1243,6 +1246,61 @@ impl MachineARM64 {
     fn emit_illegal_op_internal(&mut self, trap: TrapCode) {
         self.assembler.emit_udf(0xc0 | (trap as u8) as u16);
     }
+    /// Address of the `v128` at `loc`, for `emit_vldr` and `emit_vstr`.
+    fn v128_address(&mut self, loc: Location) -> Location {
+        match loc {
+            Location::Memory(addr, offs) => {
+                if self.compatible_imm(offs as i64, ImmType::UnscaledOffset) {
+                    loc
+                } else {
+                    let tmp = GPR::X17;
+                    self.assembler
+                        .emit_mov_imm(Location::GPR(tmp), (offs as i64) as u64);
+                    Location::Memory2(addr, tmp, Multiplier::One, 0)
+                }
+            }
+            _ => panic!("singlepass can't access a v128 at {:?}", loc),
+        }
+    }
+    /// Loads the `v128` stored at `loc` in a NEON register.
+    fn v128_to_neon(&mut self, loc: Location, dst: NEON) {
+        let addr = self.v128_address(loc);
+        self.assembler.emit_vldr(dst, addr);
+    }
+    /// Stores a NEON register to the `v128` at `loc`.
+    fn neon_to_v128(&mut self, src: NEON, loc: Location) {
+        let addr = self.v128_address(loc);
+        self.assembler.emit_vstr(src, addr);
+    }
+    /// Materializes a `v128` constant in a NEON register.
+    fn emit_neon_const(&mut self, value: u128, dst: NEON) {
+        if value == 0 {
+            self.assembler.emit_neon(NeonOp::Eor, dst, dst, dst);
+            return;
+        }
+        if value == u128::MAX {
+            self.assembler.emit_neon(NeonOp::CmeqB, dst, dst, dst);
+            return;
+        }
+        let (low, high) = (value as u64, (value >> 64) as u64);
+        let tmp = self.acquire_temp_gpr().unwrap();
+        self.assembler.emit_mov_imm(Location::GPR(tmp), low);
+        if high == low {
+            self.assembler.emit_dup(Size::S64, tmp, dst);
+        } else {
+            self.assembler.emit_ins(Size::S64, tmp, 0, dst);
+            self.assembler.emit_mov_imm(Location::GPR(tmp), high);
+            self.assembler.emit_ins(Size::S64, tmp, 1, dst);
+        }
+        self.release_gpr(tmp);
+    }
+    /// Materializes a `v128` with all its lanes of size `sz` set to `value`.
+    fn emit_neon_splat_const(&mut self, sz: Size, value: u64, dst: NEON) {
+        let tmp = self.acquire_temp_gpr().unwrap();
+        self.assembler.emit_mov_imm(Location::GPR(tmp), value);
+        self.assembler.emit_dup(sz, tmp, dst);
+        self.release_gpr(tmp);
+    }
 }
 
 impl Machine for MachineARM64 {
@@ -2108,8 +2166,41 @@ impl Machine for MachineARM64 {
         self.emit_double_pop(Size::S64, Location::GPR(GPR::X29), Location::GPR(GPR::X30));
     }
 
+    fn emit_stack_check(&mut self, limit: GPR, size: u32, label: Label) {
+        // X9 and X10 don't hold parameters.
+        // cannot use mov, because XSP is XZR there. Need to use ADD with #0
+        self.assembler.emit_add(
+            Size::S64,
+            Location::GPR(GPR::XzrSp),
+            Location::Imm8(0),
+            Location::GPR(GPR::X10),
+        );
+        self.assembler.emit_sub(
+            Size::S64,
+            Location::GPR(GPR::X10),
+            Location::GPR(limit),
+            Location::GPR(GPR::X10),
+        );
+        self.assembler
+            .emit_mov_imm(Location::GPR(GPR::X9), size as u64);
+        self.assembler
+            .emit_cmp(Size::S64, Location::GPR(GPR::X9), Location::GPR(GPR::X10));
+        self.assembler.emit_bcond_label_far(Condition::Cc, label);
+    }
+
     fn emit_function_return_value(&mut self, ty: WpType, canonicalize: bool, loc: Location) {
-        if canonicalize {
+        if ty == WpType::V128 {
+            if let Location::Memory(base, offset) = loc {
+                self.emit_relaxed_mov(Size::S64, loc, Location::GPR(GPR::X0));
+                self.emit_relaxed_mov(
+                    Size::S64,
+                    Location::Memory(base, offset + 8),
+                    Location::GPR(GPR::X1),
+                );
+            } else {
+                unreachable!();
+            }
+        } else if canonicalize {
             self.canonicalize_nan(
                 match ty {
                     WpType::F32 => Size::S32,
@@ -2195,6 +2286,9 @@ impl Machine for MachineARM64 {
     fn get_gpr_for_ret(&self) -> GPR {
         GPR::X0
     }
+    fn get_gpr_for_ret_high(&self) -> GPR {
+        GPR::X1
+    }
     fn get_simd_for_ret(&self) -> NEON {
         NEON::V0
     }
@@ -5168,6 +5262,763 @@ impl Machine for MachineARM64 {
         );
     }
 
+    fn v128_const(&mut self, value: u128, ret: Location) {
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.emit_neon_const(value, tmp);
+        self.neon_to_v128(tmp, ret);
+        self.release_simd(tmp);
+    }
+    fn v128_load(
+        &mut self,
+        op: &Operator,
+        addr: Location,
+        memarg: &MemoryImmediate,
+        ret: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let value_size = match op {
+            Operator::V128Load { .. } => 16,
+            Operator::V128Load8Splat { .. } => 1,
+            Operator::V128Load16Splat { .. } => 2,
+            Operator::V128Load32Splat { .. } | Operator::V128Load32Zero { .. } => 4,
+            _ => 8,
+        };
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            value_size,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                let extend = match op {
+                    Operator::V128Load { .. } => {
+                        this.assembler.emit_vldr(tmp, Location::Memory(addr, 0));
+                        None
+                    }
+                    Operator::V128Load8Splat { .. } => {
+                        this.assembler.emit_ld1r(Size::S8, addr, tmp);
+                        None
+                    }
+                    Operator::V128Load16Splat { .. } => {
+                        this.assembler.emit_ld1r(Size::S16, addr, tmp);
+                        None
+                    }
+                    Operator::V128Load32Splat { .. } => {
+                        this.assembler.emit_ld1r(Size::S32, addr, tmp);
+                        None
+                    }
+                    Operator::V128Load64Splat { .. } => {
+                        this.assembler.emit_ld1r(Size::S64, addr, tmp);
+                        None
+                    }
+                    Operator::V128Load32Zero { .. } => {
+                        this.assembler.emit_ldr(
+                            Size::S32,
+                            Location::SIMD(tmp),
+                            Location::Memory(addr, 0),
+                        );
+                        None
+                    }
+                    _ => {
+                        this.assembler.emit_ldr(
+                            Size::S64,
+                            Location::SIMD(tmp),
+                            Location::Memory(addr, 0),
+                        );
+                        match op {
+                            Operator::V128Load8x8S { .. } => Some(NeonUnOp::SxtlB),
+                            Operator::V128Load8x8U { .. } => Some(NeonUnOp::UxtlB),
+                            Operator::V128Load16x4S { .. } => Some(NeonUnOp::SxtlH),
+                            Operator::V128Load16x4U { .. } => Some(NeonUnOp::UxtlH),
+                            Operator::V128Load32x2S { .. } => Some(NeonUnOp::SxtlS),
+                            Operator::V128Load32x2U { .. } => Some(NeonUnOp::UxtlS),
+                            _ => None,
+                        }
+                    }
+                };
+                if let Some(extend) = extend {
+                    this.assembler.emit_neon_unop(extend, tmp, tmp);
+                }
+                this.neon_to_v128(tmp, ret);
+            },
+        );
+        self.release_simd(tmp);
+    }
+    fn v128_save(
+        &mut self,
+        value: Location,
+        memarg: &MemoryImmediate,
+        addr: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(value, tmp);
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            16,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                this.assembler.emit_vstr(tmp, Location::Memory(addr, 0));
+            },
+        );
+        self.release_simd(tmp);
+    }
+    fn v128_load_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        value: Location,
+        addr: Location,
+        memarg: &MemoryImmediate,
+        ret: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let (sz, value_size) = match op {
+            Operator::V128Load8Lane { .. } => (Size::S8, 1),
+            Operator::V128Load16Lane { .. } => (Size::S16, 2),
+            Operator::V128Load32Lane { .. } => (Size::S32, 4),
+            _ => (Size::S64, 8),
+        };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(value, tmp);
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            value_size,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                let (dst, src) = (Location::GPR(gpr), Location::Memory(addr, 0));
+                match sz {
+                    Size::S8 => this.assembler.emit_ldrb(Size::S32, dst, src),
+                    Size::S16 => this.assembler.emit_ldrh(Size::S32, dst, src),
+                    _ => this.assembler.emit_ldr(sz, dst, src),
+                }
+            },
+        );
+        self.assembler.emit_ins(sz, gpr, lane as u32, tmp);
+        self.neon_to_v128(tmp, ret);
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_save_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        value: Location,
+        memarg: &MemoryImmediate,
+        addr: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let (sz, value_size) = match op {
+            Operator::V128Store8Lane { .. } => (Size::S8, 1),
+            Operator::V128Store16Lane { .. } => (Size::S16, 2),
+            Operator::V128Store32Lane { .. } => (Size::S32, 4),
+            _ => (Size::S64, 8),
+        };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(value, tmp);
+        self.assembler.emit_umov(sz, tmp, lane as u32, gpr);
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            value_size,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                let (src, dst) = (Location::GPR(gpr), Location::Memory(addr, 0));
+                match sz {
+                    Size::S8 => this.assembler.emit_strb(Size::S32, src, dst),
+                    Size::S16 => this.assembler.emit_strh(Size::S32, src, dst),
+                    _ => this.assembler.emit_str(sz, src, dst),
+                }
+            },
+        );
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_splat(&mut self, op: &Operator, loc: Location, ret: Location) {
+        let sz = match op {
+            Operator::I8x16Splat => Size::S8,
+            Operator::I16x8Splat => Size::S16,
+            Operator::I32x4Splat | Operator::F32x4Splat => Size::S32,
+            _ => Size::S64,
+        };
+        let mov_sz = if sz == Size::S64 {
+            Size::S64
+        } else {
+            Size::S32
+        };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.move_location(mov_sz, loc, Location::GPR(gpr));
+        self.assembler.emit_dup(sz, gpr, tmp);
+        self.neon_to_v128(tmp, ret);
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_extract_lane(&mut self, op: &Operator, lane: u8, loc: Location, ret: Location) {
+        let (sz, signed) = match op {
+            Operator::I8x16ExtractLaneS { .. } => (Size::S8, true),
+            Operator::I8x16ExtractLaneU { .. } => (Size::S8, false),
+            Operator::I16x8ExtractLaneS { .. } => (Size::S16, true),
+            Operator::I16x8ExtractLaneU { .. } => (Size::S16, false),
+            Operator::I32x4ExtractLane { .. } | Operator::F32x4ExtractLane { .. } => {
+                (Size::S32, false)
+            }
+            _ => (Size::S64, false),
+        };
+        let tmp = self.acquire_temp_simd().unwrap();
+        let gpr = self.acquire_temp_gpr().unwrap();
+        self.v128_to_neon(loc, tmp);
+        if signed {
+            self.assembler.emit_smov(sz, tmp, lane as u32, gpr);
+        } else {
+            self.assembler.emit_umov(sz, tmp, lane as u32, gpr);
+        }
+        let mov_sz = if sz == Size::S64 {
+            Size::S64
+        } else {
+            Size::S32
+        };
+        self.move_location(mov_sz, Location::GPR(gpr), ret);
+        self.release_gpr(gpr);
+        self.release_simd(tmp);
+    }
+    fn v128_replace_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        loc: Location,
+        value: Location,
+        ret: Location,
+    ) {
+        let sz = match op {
+            Operator::I8x16ReplaceLane { .. } => Size::S8,
+            Operator::I16x8ReplaceLane { .. } => Size::S16,
+            Operator::I32x4ReplaceLane { .. } | Operator::F32x4ReplaceLane { .. } => Size::S32,
+            _ => Size::S64,
+        };
+        let mov_sz = if sz == Size::S64 {
+            Size::S64
+        } else {
+            Size::S32
+        };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.move_location(mov_sz, value, Location::GPR(gpr));
+        self.v128_to_neon(loc, tmp);
+        self.assembler.emit_ins(sz, gpr, lane as u32, tmp);
+        self.neon_to_v128(tmp, ret);
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_shuffle(&mut self, lanes: &[u8; 16], loc_a: Location, loc_b: Location, ret: Location) {
+        // Each input is looked up with the lanes it provides, the others
+        // being out of its range and zeroed.
+        let mask_a = *lanes;
+        let mask_b = lanes.map(|lane| lane.wrapping_sub(16));
+        let tmp_a = self.acquire_temp_simd().unwrap();
+        let tmp_b = self.acquire_temp_simd().unwrap();
+        let mask = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(loc_a, tmp_a);
+        self.v128_to_neon(loc_b, tmp_b);
+        self.emit_neon_const(u128::from_le_bytes(mask_a), mask);
+        self.assembler.emit_neon(NeonOp::Tbl, tmp_a, mask, tmp_a);
+        self.emit_neon_const(u128::from_le_bytes(mask_b), mask);
+        self.assembler.emit_neon(NeonOp::Tbl, tmp_b, mask, tmp_b);
+        self.assembler.emit_neon(NeonOp::Orr, tmp_a, tmp_b, tmp_a);
+        self.neon_to_v128(tmp_a, ret);
+        self.release_simd(mask);
+        self.release_simd(tmp_b);
+        self.release_simd(tmp_a);
+    }
+    fn v128_test(&mut self, op: &Operator, loc: Location, ret: Location) {
+        let tmp = self.acquire_temp_simd().unwrap();
+        let gpr = self.acquire_temp_gpr().unwrap();
+        self.v128_to_neon(loc, tmp);
+        match op {
+            Operator::V128AnyTrue
+            | Operator::I8x16AllTrue
+            | Operator::I16x8AllTrue
+            | Operator::I32x4AllTrue
+            | Operator::I64x2AllTrue => {
+                // All the lanes are non-zero if none of them compares equal to zero.
+                let (zero, cond) = match op {
+                    Operator::I8x16AllTrue => (Some(NeonUnOp::CmeqZeroB), Condition::Eq),
+                    Operator::I16x8AllTrue => (Some(NeonUnOp::CmeqZeroH), Condition::Eq),
+                    Operator::I32x4AllTrue => (Some(NeonUnOp::CmeqZeroS), Condition::Eq),
+                    Operator::I64x2AllTrue => (Some(NeonUnOp::CmeqZeroD), Condition::Eq),
+                    _ => (None, Condition::Ne),
+                };
+                if let Some(zero) = zero {
+                    self.assembler.emit_neon_unop(zero, tmp, tmp);
+                }
+                self.assembler.emit_neon_unop(NeonUnOp::UmaxvB, tmp, tmp);
+                self.assembler.emit_umov(Size::S32, tmp, 0, gpr);
+                self.assembler
+                    .emit_cmp(Size::S32, Location::Imm32(0), Location::GPR(gpr));
+                self.assembler
+                    .emit_cset(Size::S32, Location::GPR(gpr), cond);
+            }
+            Operator::I8x16Bitmask
+            | Operator::I16x8Bitmask
+            | Operator::I32x4Bitmask
+            | Operator::I64x2Bitmask => {
+                // The lanes become all ones or zeros, are masked with their
+                // bit in the result, and added together.
+                let (sz, sign, bits) = match op {
+                    Operator::I8x16Bitmask => (Size::S8, NeonShift::SshrB, 8),
+                    Operator::I16x8Bitmask => (Size::S16, NeonShift::SshrH, 16),
+                    Operator::I32x4Bitmask => (Size::S32, NeonShift::SshrS, 32),
+                    _ => (Size::S64, NeonShift::SshrD, 64),
+                };
+                let weights =
+                    (0..128 / bits).fold(0u128, |acc, i| acc | 1u128 << (i % 8) << (i * bits));
+                let mask = self.acquire_temp_simd().unwrap();
+                self.assembler.emit_neon_shift(sign, bits - 1, tmp, tmp);
+                self.emit_neon_const(weights, mask);
+                self.assembler.emit_neon(NeonOp::And, tmp, mask, tmp);
+                match sz {
+                    Size::S8 => {
+                        // The low and high halves are summed in the first 2 bytes
+                        for _ in 0..3 {
+                            self.assembler.emit_neon(NeonOp::AddpB, tmp, tmp, tmp);
+                        }
+                        self.assembler.emit_umov(Size::S16, tmp, 0, gpr);
+                    }
+                    Size::S16 => {
+                        self.assembler.emit_neon_unop(NeonUnOp::AddvH, tmp, tmp);
+                        self.assembler.emit_umov(Size::S16, tmp, 0, gpr);
+                    }
+                    Size::S32 => {
+                        self.assembler.emit_neon_unop(NeonUnOp::AddvS, tmp, tmp);
+                        self.assembler.emit_umov(Size::S32, tmp, 0, gpr);
+                    }
+                    Size::S64 => {
+                        self.assembler.emit_neon(NeonOp::AddpD, tmp, tmp, tmp);
+                        self.assembler.emit_umov(Size::S32, tmp, 0, gpr);
+                    }
+                }
+                self.release_simd(mask);
+            }
+            _ => unreachable!("{:?} is not a v128 test", op),
+        }
+        self.move_location(Size::S32, Location::GPR(gpr), ret);
+        self.release_gpr(gpr);
+        self.release_simd(tmp);
+    }
+    fn v128_shift(&mut self, op: &Operator, loc: Location, count: Location, ret: Location) {
+        // The shifts are by a vector of counts, negative ones shifting right.
+        let (sz, bits, shift, neg) = match op {
+            Operator::I8x16Shl => (Size::S8, 8, NeonOp::UshlB, None),
+            Operator::I8x16ShrS => (Size::S8, 8, NeonOp::SshlB, Some(NeonUnOp::NegB)),
+            Operator::I8x16ShrU => (Size::S8, 8, NeonOp::UshlB, Some(NeonUnOp::NegB)),
+            Operator::I16x8Shl => (Size::S16, 16, NeonOp::UshlH, None),
+            Operator::I16x8ShrS => (Size::S16, 16, NeonOp::SshlH, Some(NeonUnOp::NegH)),
+            Operator::I16x8ShrU => (Size::S16, 16, NeonOp::UshlH, Some(NeonUnOp::NegH)),
+            Operator::I32x4Shl => (Size::S32, 32, NeonOp::UshlS, None),
+            Operator::I32x4ShrS => (Size::S32, 32, NeonOp::SshlS, Some(NeonUnOp::NegS)),
+            Operator::I32x4ShrU => (Size::S32, 32, NeonOp::UshlS, Some(NeonUnOp::NegS)),
+            Operator::I64x2Shl => (Size::S64, 64, NeonOp::UshlD, None),
+            Operator::I64x2ShrS => (Size::S64, 64, NeonOp::SshlD, Some(NeonUnOp::NegD)),
+            _ => (Size::S64, 64, NeonOp::UshlD, Some(NeonUnOp::NegD)),
+        };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        let tmp_count = self.acquire_temp_simd().unwrap();
+        self.move_location(Size::S32, count, Location::GPR(gpr));
+        self.assembler.emit_and(
+            Size::S32,
+            Location::GPR(gpr),
+            Location::Imm32(bits - 1),
+            Location::GPR(gpr),
+        );
+        self.v128_to_neon(loc, tmp);
+        self.assembler.emit_dup(sz, gpr, tmp_count);
+        if let Some(neg) = neg {
+            self.assembler.emit_neon_unop(neg, tmp_count, tmp_count);
+        }
+        self.assembler.emit_neon(shift, tmp, tmp_count, tmp);
+        self.neon_to_v128(tmp, ret);
+        self.release_simd(tmp_count);
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_unop(&mut self, op: &Operator, loc: Location, ret: Location) {
+        let x = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(loc, x);
+        let ops: &[NeonUnOp] = match op {
+            Operator::V128Not => &[NeonUnOp::Not],
+            Operator::I8x16Abs => &[NeonUnOp::AbsB],
+            Operator::I16x8Abs => &[NeonUnOp::AbsH],
+            Operator::I32x4Abs => &[NeonUnOp::AbsS],
+            Operator::I64x2Abs => &[NeonUnOp::AbsD],
+            Operator::I8x16Neg => &[NeonUnOp::NegB],
+            Operator::I16x8Neg => &[NeonUnOp::NegH],
+            Operator::I32x4Neg => &[NeonUnOp::NegS],
+            Operator::I64x2Neg => &[NeonUnOp::NegD],
+            Operator::I8x16Popcnt => &[NeonUnOp::Cnt],
+            Operator::I16x8ExtAddPairwiseI8x16S => &[NeonUnOp::SaddlpB],
+            Operator::I16x8ExtAddPairwiseI8x16U => &[NeonUnOp::UaddlpB],
+            Operator::I32x4ExtAddPairwiseI16x8S => &[NeonUnOp::SaddlpH],
+            Operator::I32x4ExtAddPairwiseI16x8U => &[NeonUnOp::UaddlpH],
+            Operator::I16x8ExtendLowI8x16S => &[NeonUnOp::SxtlB],
+            Operator::I16x8ExtendHighI8x16S => &[NeonUnOp::Sxtl2B],
+            Operator::I16x8ExtendLowI8x16U => &[NeonUnOp::UxtlB],
+            Operator::I16x8ExtendHighI8x16U => &[NeonUnOp::Uxtl2B],
+            Operator::I32x4ExtendLowI16x8S => &[NeonUnOp::SxtlH],
+            Operator::I32x4ExtendHighI16x8S => &[NeonUnOp::Sxtl2H],
+            Operator::I32x4ExtendLowI16x8U => &[NeonUnOp::UxtlH],
+            Operator::I32x4ExtendHighI16x8U => &[NeonUnOp::Uxtl2H],
+            Operator::I64x2ExtendLowI32x4S => &[NeonUnOp::SxtlS],
+            Operator::I64x2ExtendHighI32x4S => &[NeonUnOp::Sxtl2S],
+            Operator::I64x2ExtendLowI32x4U => &[NeonUnOp::UxtlS],
+            Operator::I64x2ExtendHighI32x4U => &[NeonUnOp::Uxtl2S],
+            Operator::F32x4Abs => &[NeonUnOp::FabsS],
+            Operator::F64x2Abs => &[NeonUnOp::FabsD],
+            Operator::F32x4Neg => &[NeonUnOp::FnegS],
+            Operator::F64x2Neg => &[NeonUnOp::FnegD],
+            Operator::F32x4Sqrt => &[NeonUnOp::FsqrtS],
+            Operator::F64x2Sqrt => &[NeonUnOp::FsqrtD],
+            Operator::F32x4Ceil => &[NeonUnOp::FrintpS],
+            Operator::F64x2Ceil => &[NeonUnOp::FrintpD],
+            Operator::F32x4Floor => &[NeonUnOp::FrintmS],
+            Operator::F64x2Floor => &[NeonUnOp::FrintmD],
+            Operator::F32x4Trunc => &[NeonUnOp::FrintzS],
+            Operator::F64x2Trunc => &[NeonUnOp::FrintzD],
+            Operator::F32x4Nearest => &[NeonUnOp::FrintnS],
+            Operator::F64x2Nearest => &[NeonUnOp::FrintnD],
+            // The conversions to integers saturate, NaN giving 0
+            Operator::I32x4TruncSatF32x4S | Operator::I32x4RelaxedTruncSatF32x4S => {
+                &[NeonUnOp::FcvtzsS]
+            }
+            Operator::I32x4TruncSatF32x4U | Operator::I32x4RelaxedTruncSatF32x4U => {
+                &[NeonUnOp::FcvtzuS]
+            }
+            Operator::I32x4TruncSatF64x2SZero | Operator::I32x4RelaxedTruncSatF64x2SZero => {
+                &[NeonUnOp::FcvtzsD, NeonUnOp::SqxtnS]
+            }
+            Operator::I32x4TruncSatF64x2UZero | Operator::I32x4RelaxedTruncSatF64x2UZero => {
+                &[NeonUnOp::FcvtzuD, NeonUnOp::UqxtnS]
+            }
+            Operator::F32x4ConvertI32x4S => &[NeonUnOp::ScvtfS],
+            Operator::F32x4ConvertI32x4U => &[NeonUnOp::UcvtfS],
+            Operator::F64x2ConvertLowI32x4S => &[NeonUnOp::SxtlS, NeonUnOp::ScvtfD],
+            Operator::F64x2ConvertLowI32x4U => &[NeonUnOp::UxtlS, NeonUnOp::UcvtfD],
+            Operator::F32x4DemoteF64x2Zero => &[NeonUnOp::Fcvtn],
+            Operator::F64x2PromoteLowF32x4 => &[NeonUnOp::Fcvtl],
+            _ => unreachable!("{:?} is not a v128 unary operator", op),
+        };
+        for &op in ops {
+            self.assembler.emit_neon_unop(op, x, x);
+        }
+        self.neon_to_v128(x, ret);
+        self.release_simd(x);
+    }
+    fn v128_binop(&mut self, op: &Operator, loc_a: Location, loc_b: Location, ret: Location) {
+        let a = self.acquire_temp_simd().unwrap();
+        let b = self.acquire_temp_simd().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(loc_a, a);
+        self.v128_to_neon(loc_b, b);
+        let neon = |op: &Operator| -> Option<(NeonOp, bool)> {
+            // The instruction computing `a op b`, or `b op a` (the `bool` being set).
+            Some(match op {
+                Operator::I8x16Eq => (NeonOp::CmeqB, false),
+                Operator::I16x8Eq => (NeonOp::CmeqH, false),
+                Operator::I32x4Eq => (NeonOp::CmeqS, false),
+                Operator::I64x2Eq => (NeonOp::CmeqD, false),
+                Operator::I8x16GtS => (NeonOp::CmgtB, false),
+                Operator::I16x8GtS => (NeonOp::CmgtH, false),
+                Operator::I32x4GtS => (NeonOp::CmgtS, false),
+                Operator::I64x2GtS => (NeonOp::CmgtD, false),
+                Operator::I8x16LtS => (NeonOp::CmgtB, true),
+                Operator::I16x8LtS => (NeonOp::CmgtH, true),
+                Operator::I32x4LtS => (NeonOp::CmgtS, true),
+                Operator::I64x2LtS => (NeonOp::CmgtD, true),
+                Operator::I8x16GeS => (NeonOp::CmgeB, false),
+                Operator::I16x8GeS => (NeonOp::CmgeH, false),
+                Operator::I32x4GeS => (NeonOp::CmgeS, false),
+                Operator::I64x2GeS => (NeonOp::CmgeD, false),
+                Operator::I8x16LeS => (NeonOp::CmgeB, true),
+                Operator::I16x8LeS => (NeonOp::CmgeH, true),
+                Operator::I32x4LeS => (NeonOp::CmgeS, true),
+                Operator::I64x2LeS => (NeonOp::CmgeD, true),
+                Operator::I8x16GtU => (NeonOp::CmhiB, false),
+                Operator::I16x8GtU => (NeonOp::CmhiH, false),
+                Operator::I32x4GtU => (NeonOp::CmhiS, false),
+                Operator::I8x16LtU => (NeonOp::CmhiB, true),
+                Operator::I16x8LtU => (NeonOp::CmhiH, true),
+                Operator::I32x4LtU => (NeonOp::CmhiS, true),
+                Operator::I8x16GeU => (NeonOp::CmhsB, false),
+                Operator::I16x8GeU => (NeonOp::CmhsH, false),
+                Operator::I32x4GeU => (NeonOp::CmhsS, false),
+                Operator::I8x16LeU => (NeonOp::CmhsB, true),
+                Operator::I16x8LeU => (NeonOp::CmhsH, true),
+                Operator::I32x4LeU => (NeonOp::CmhsS, true),
+                Operator::F32x4Eq => (NeonOp::FcmeqS, false),
+                Operator::F64x2Eq => (NeonOp::FcmeqD, false),
+                Operator::F32x4Gt => (NeonOp::FcmgtS, false),
+                Operator::F64x2Gt => (NeonOp::FcmgtD, false),
+                Operator::F32x4Lt => (NeonOp::FcmgtS, true),
+                Operator::F64x2Lt => (NeonOp::FcmgtD, true),
+                Operator::F32x4Ge => (NeonOp::FcmgeS, false),
+                Operator::F64x2Ge => (NeonOp::FcmgeD, false),
+                Operator::F32x4Le => (NeonOp::FcmgeS, true),
+                Operator::F64x2Le => (NeonOp::FcmgeD, true),
+                Operator::V128And => (NeonOp::And, false),
+                Operator::V128AndNot => (NeonOp::Bic, false),
+                Operator::V128Or => (NeonOp::Orr, false),
+                Operator::V128Xor => (NeonOp::Eor, false),
+                Operator::I8x16Swizzle | Operator::I8x16RelaxedSwizzle => (NeonOp::Tbl, false),
+                Operator::I8x16Add => (NeonOp::AddB, false),
+                Operator::I8x16AddSatS => (NeonOp::SqaddB, false),
+                Operator::I8x16AddSatU => (NeonOp::UqaddB, false),
+                Operator::I8x16Sub => (NeonOp::SubB, false),
+                Operator::I8x16SubSatS => (NeonOp::SqsubB, false),
+                Operator::I8x16SubSatU => (NeonOp::UqsubB, false),
+                Operator::I8x16MinS => (NeonOp::SminB, false),
+                Operator::I8x16MinU => (NeonOp::UminB, false),
+                Operator::I8x16MaxS => (NeonOp::SmaxB, false),
+                Operator::I8x16MaxU => (NeonOp::UmaxB, false),
+                Operator::I8x16RoundingAverageU => (NeonOp::UrhaddB, false),
+                Operator::I16x8Add => (NeonOp::AddH, false),
+                Operator::I16x8AddSatS => (NeonOp::SqaddH, false),
+                Operator::I16x8AddSatU => (NeonOp::UqaddH, false),
+                Operator::I16x8Sub => (NeonOp::SubH, false),
+                Operator::I16x8SubSatS => (NeonOp::SqsubH, false),
+                Operator::I16x8SubSatU => (NeonOp::UqsubH, false),
+                Operator::I16x8Mul => (NeonOp::MulH, false),
+                Operator::I16x8MinS => (NeonOp::SminH, false),
+                Operator::I16x8MinU => (NeonOp::UminH, false),
+                Operator::I16x8MaxS => (NeonOp::SmaxH, false),
+                Operator::I16x8MaxU => (NeonOp::UmaxH, false),
+                Operator::I16x8RoundingAverageU => (NeonOp::UrhaddH, false),
+                Operator::I16x8Q15MulrSatS => (NeonOp::SqrdmulhH, false),
+                Operator::I16x8ExtMulLowI8x16S => (NeonOp::SmullB, false),
+                Operator::I16x8ExtMulHighI8x16S => (NeonOp::Smull2B, false),
+                Operator::I16x8ExtMulLowI8x16U => (NeonOp::UmullB, false),
+                Operator::I16x8ExtMulHighI8x16U => (NeonOp::Umull2B, false),
+                Operator::I32x4Add => (NeonOp::AddS, false),
+                Operator::I32x4Sub => (NeonOp::SubS, false),
+                Operator::I32x4Mul => (NeonOp::MulS, false),
+                Operator::I32x4MinS => (NeonOp::SminS, false),
+                Operator::I32x4MinU => (NeonOp::UminS, false),
+                Operator::I32x4MaxS => (NeonOp::SmaxS, false),
+                Operator::I32x4MaxU => (NeonOp::UmaxS, false),
+                Operator::I32x4ExtMulLowI16x8S => (NeonOp::SmullH, false),
+                Operator::I32x4ExtMulHighI16x8S => (NeonOp::Smull2H, false),
+                Operator::I32x4ExtMulLowI16x8U => (NeonOp::UmullH, false),
+                Operator::I32x4ExtMulHighI16x8U => (NeonOp::Umull2H, false),
+                Operator::I64x2Add => (NeonOp::AddD, false),
+                Operator::I64x2Sub => (NeonOp::SubD, false),
+                Operator::I64x2ExtMulLowI32x4S => (NeonOp::SmullS, false),
+                Operator::I64x2ExtMulHighI32x4S => (NeonOp::Smull2S, false),
+                Operator::I64x2ExtMulLowI32x4U => (NeonOp::UmullS, false),
+                Operator::I64x2ExtMulHighI32x4U => (NeonOp::Umull2S, false),
+                Operator::F32x4Add => (NeonOp::FaddS, false),
+                Operator::F32x4Sub => (NeonOp::FsubS, false),
+                Operator::F32x4Mul => (NeonOp::FmulS, false),
+                Operator::F32x4Div => (NeonOp::FdivS, false),
+                Operator::F32x4Min | Operator::F32x4RelaxedMin => (NeonOp::FminS, false),
+                Operator::F32x4Max | Operator::F32x4RelaxedMax => (NeonOp::FmaxS, false),
+                Operator::F64x2Add => (NeonOp::FaddD, false),
+                Operator::F64x2Sub => (NeonOp::FsubD, false),
+                Operator::F64x2Mul => (NeonOp::FmulD, false),
+                Operator::F64x2Div => (NeonOp::FdivD, false),
+                Operator::F64x2Min | Operator::F64x2RelaxedMin => (NeonOp::FminD, false),
+                Operator::F64x2Max | Operator::F64x2RelaxedMax => (NeonOp::FmaxD, false),
+                _ => return None,
+            })
+        };
+        let res = if let Some((neon, swapped)) = neon(op) {
+            if swapped {
+                self.assembler.emit_neon(neon, b, a, a);
+            } else {
+                self.assembler.emit_neon(neon, a, b, a);
+            }
+            a
+        } else {
+            match op {
+                Operator::I8x16Ne
+                | Operator::I16x8Ne
+                | Operator::I32x4Ne
+                | Operator::I64x2Ne
+                | Operator::F32x4Ne
+                | Operator::F64x2Ne => {
+                    let eq = match op {
+                        Operator::I8x16Ne => NeonOp::CmeqB,
+                        Operator::I16x8Ne => NeonOp::CmeqH,
+                        Operator::I32x4Ne => NeonOp::CmeqS,
+                        Operator::I64x2Ne => NeonOp::CmeqD,
+                        Operator::F32x4Ne => NeonOp::FcmeqS,
+                        _ => NeonOp::FcmeqD,
+                    };
+                    self.assembler.emit_neon(eq, a, b, a);
+                    self.assembler.emit_neon_unop(NeonUnOp::Not, a, a);
+                    a
+                }
+                Operator::I8x16NarrowI16x8S
+                | Operator::I8x16NarrowI16x8U
+                | Operator::I16x8NarrowI32x4S
+                | Operator::I16x8NarrowI32x4U => {
+                    let (low, high) = match op {
+                        Operator::I8x16NarrowI16x8S => (NeonUnOp::SqxtnB, NeonUnOp::Sqxtn2B),
+                        Operator::I8x16NarrowI16x8U => (NeonUnOp::SqxtunB, NeonUnOp::Sqxtun2B),
+                        Operator::I16x8NarrowI32x4S => (NeonUnOp::SqxtnH, NeonUnOp::Sqxtn2H),
+                        _ => (NeonUnOp::SqxtunH, NeonUnOp::Sqxtun2H),
+                    };
+                    self.assembler.emit_neon_unop(low, a, tmp);
+                    self.assembler.emit_neon_unop(high, b, tmp);
+                    tmp
+                }
+                Operator::I32x4DotI16x8S => {
+                    self.assembler.emit_neon(NeonOp::SmullH, a, b, tmp);
+                    self.assembler.emit_neon(NeonOp::Smull2H, a, b, a);
+                    self.assembler.emit_neon(NeonOp::AddpS, tmp, a, a);
+                    a
+                }
+                Operator::I64x2Mul => {
+                    // a * b = lo(a) * lo(b) + ((hi(a) * lo(b) + lo(a) * hi(b)) << 32)
+                    self.assembler.emit_neon_unop(NeonUnOp::Rev64S, b, tmp);
+                    self.assembler.emit_neon(NeonOp::MulS, tmp, a, tmp);
+                    self.assembler.emit_neon_unop(NeonUnOp::UaddlpS, tmp, tmp);
+                    self.assembler
+                        .emit_neon_shift(NeonShift::ShlD, 32, tmp, tmp);
+                    self.assembler.emit_neon_unop(NeonUnOp::XtnS, a, a);
+                    self.assembler.emit_neon_unop(NeonUnOp::XtnS, b, b);
+                    self.assembler.emit_neon(NeonOp::UmlalS, a, b, tmp);
+                    tmp
+                }
+                Operator::F32x4PMin | Operator::F64x2PMin => {
+                    // b < a ? b : a
+                    let gt = match op {
+                        Operator::F32x4PMin => NeonOp::FcmgtS,
+                        _ => NeonOp::FcmgtD,
+                    };
+                    self.assembler.emit_neon(gt, a, b, tmp);
+                    self.assembler.emit_neon(NeonOp::Bsl, b, a, tmp);
+                    tmp
+                }
+                Operator::F32x4PMax | Operator::F64x2PMax => {
+                    // a < b ? b : a
+                    let gt = match op {
+                        Operator::F32x4PMax => NeonOp::FcmgtS,
+                        _ => NeonOp::FcmgtD,
+                    };
+                    self.assembler.emit_neon(gt, b, a, tmp);
+                    self.assembler.emit_neon(NeonOp::Bsl, b, a, tmp);
+                    tmp
+                }
+                _ => unreachable!("{:?} is not a v128 binary operator", op),
+            }
+        };
+        self.neon_to_v128(res, ret);
+        self.release_simd(tmp);
+        self.release_simd(b);
+        self.release_simd(a);
+    }
+    fn v128_ternop(
+        &mut self,
+        op: &Operator,
+        loc_a: Location,
+        loc_b: Location,
+        loc_c: Location,
+        ret: Location,
+    ) {
+        let a = self.acquire_temp_simd().unwrap();
+        let b = self.acquire_temp_simd().unwrap();
+        let c = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(loc_a, a);
+        self.v128_to_neon(loc_b, b);
+        self.v128_to_neon(loc_c, c);
+        let res = match op {
+            Operator::V128Bitselect
+            | Operator::I8x16LaneSelect
+            | Operator::I16x8LaneSelect
+            | Operator::I32x4LaneSelect
+            | Operator::I64x2LaneSelect => {
+                // (a & c) | (b & !c)
+                self.assembler.emit_neon(NeonOp::Bsl, a, b, c);
+                c
+            }
+            Operator::F32x4Fma => {
+                self.assembler.emit_neon(NeonOp::FmulS, a, b, a);
+                self.assembler.emit_neon(NeonOp::FaddS, a, c, a);
+                a
+            }
+            Operator::F64x2Fma => {
+                self.assembler.emit_neon(NeonOp::FmulD, a, b, a);
+                self.assembler.emit_neon(NeonOp::FaddD, a, c, a);
+                a
+            }
+            Operator::F32x4Fms => {
+                self.assembler.emit_neon(NeonOp::FmulS, a, b, a);
+                self.assembler.emit_neon(NeonOp::FsubS, c, a, a);
+                a
+            }
+            Operator::F64x2Fms => {
+                self.assembler.emit_neon(NeonOp::FmulD, a, b, a);
+                self.assembler.emit_neon(NeonOp::FsubD, c, a, a);
+                a
+            }
+            _ => unreachable!("{:?} is not a v128 ternary operator", op),
+        };
+        self.neon_to_v128(res, ret);
+        self.release_simd(c);
+        self.release_simd(b);
+        self.release_simd(a);
+    }
+    fn v128_canonicalize_nan(&mut self, sz: Size, loc: Location) {
+        let (eq, nan) = match sz {
+            Size::S32 => (NeonOp::FcmeqS, 0x7fc0_0000),
+            Size::S64 => (NeonOp::FcmeqD, 0x7ff8_0000_0000_0000),
+            _ => unreachable!(),
+        };
+        let value = self.acquire_temp_simd().unwrap();
+        let mask = self.acquire_temp_simd().unwrap();
+        let canonical = self.acquire_temp_simd().unwrap();
+        self.v128_to_neon(loc, value);
+        // ordered lanes keep their value
+        self.assembler.emit_neon(eq, value, value, mask);
+        self.emit_neon_splat_const(sz, nan, canonical);
+        self.assembler
+            .emit_neon(NeonOp::Bsl, value, canonical, mask);
+        self.neon_to_v128(mask, loc);
+        self.release_simd(canonical);
+        self.release_simd(mask);
+        self.release_simd(value);
+    }
     fn gen_std_trampoline(
         &self,
         sig: &FunctionType,
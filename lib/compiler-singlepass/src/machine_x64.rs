@@ -12,7 +12,7 @@ use dynasmrt::{x64::X64Relocation, DynasmError, VecAssembler};
 #[cfg(feature = "unwind")]
 use gimli::{write::CallFrameInstruction, X86_64};
 use std::ops::{Deref, DerefMut};
-use wasmer_compiler::wasmparser::Type as WpType;
+use wasmer_compiler::wasmparser::{Operator, Type as WpType};
 use wasmer_compiler::{
     CallingConvention, CpuFeature, CustomSection, CustomSectionProtection, FunctionBody,
     InstructionAddressMap, Relocation, RelocationKind, RelocationTarget, SectionBody, SourceLoc,
@@ -151,6 +151,11 @@ impl MachineX86_64 {
                 RelaxMode::Direct
             }
             _ if (op as *const u8 == AssemblerX64::emit_imul as *const u8) => RelaxMode::BothToGPR,
+            (Location::Imm32(_), _) | (Location::Imm64(_), _)
+                if (op as *const u8 == AssemblerX64::emit_xchg as *const u8) =>
+            {
+                RelaxMode::SrcToGPR
+            }
 
             (Location::Memory(_, _), Location::Memory(_, _)) => RelaxMode::SrcToGPR,
             (Location::Imm64(_), Location::Imm64(_)) | (Location::Imm64(_), Location::Imm32(_)) => {
@@ -463,14 +468,20 @@ impl MachineX86_64 {
         };
 
         let tmp_base = self.acquire_temp_gpr().unwrap();
-        let tmp_bound = self.acquire_temp_gpr().unwrap();
+        // The atomic operations hold a temporary register of their own, so
+        // the bound only takes one when it's needed.
+        let tmp_bound = if need_check {
+            Some(self.acquire_temp_gpr().unwrap())
+        } else {
+            None
+        };
 
         // Load base into temporary register.
         self.assembler
             .emit_mov(Size::S64, base_loc, Location::GPR(tmp_base));
 
         // Load bound into temporary register, if needed.
-        if need_check {
+        if let Some(tmp_bound) = tmp_bound {
             self.assembler
                 .emit_mov(Size::S64, bound_loc, Location::GPR(tmp_bound));
 
@@ -511,7 +522,7 @@ impl MachineX86_64 {
         self.assembler
             .emit_add(Size::S64, Location::GPR(tmp_base), Location::GPR(tmp_addr));
 
-        if need_check {
+        if let Some(tmp_bound) = tmp_bound {
             // Trap if the end address of the requested area is above that of the linear memory.
             self.assembler
                 .emit_cmp(Size::S64, Location::GPR(tmp_bound), Location::GPR(tmp_addr));
@@ -520,25 +531,23 @@ impl MachineX86_64 {
             self.assembler.emit_jmp(Condition::Above, heap_access_oob);
         }
 
-        self.release_gpr(tmp_bound);
+        if let Some(tmp_bound) = tmp_bound {
+            self.release_gpr(tmp_bound);
+        }
         self.release_gpr(tmp_base);
 
+        // `memarg.align` is the log2 of the alignment.
         let align = memarg.align;
-        if check_alignment && align != 1 {
-            let tmp_aligncheck = self.acquire_temp_gpr().unwrap();
-            self.assembler.emit_mov(
+        if check_alignment && align != 0 {
+            let aligned = self.get_label();
+            self.assembler.emit_test(
                 Size::S32,
+                Location::Imm32((1 << align) - 1),
                 Location::GPR(tmp_addr),
-                Location::GPR(tmp_aligncheck),
             );
-            self.assembler.emit_and(
-                Size::S64,
-                Location::Imm32((align - 1).into()),
-                Location::GPR(tmp_aligncheck),
-            );
-            self.assembler
-                .emit_jmp(Condition::NotEqual, heap_access_oob);
-            self.release_gpr(tmp_aligncheck);
+            self.assembler.emit_jmp(Condition::Equal, aligned);
+            self.emit_illegal_op(TrapCode::UnalignedAtomic);
+            self.emit_label(aligned);
         }
         let begin = self.assembler.get_offset().0;
         cb(self, tmp_addr);
@@ -575,11 +584,12 @@ impl MachineX86_64 {
         };
         self.assembler.emit_push(Size::S64, Location::GPR(value));
 
-        self.move_location(stack_sz, loc, Location::GPR(value));
-
         let retry = self.assembler.get_label();
         self.emit_label(retry);
 
+        // The callback clobbers `value`, so reload it on every attempt.
+        self.move_location(stack_sz, loc, Location::GPR(value));
+
         self.memory_op(
             target,
             memarg,
@@ -1655,6 +1665,138 @@ impl MachineX86_64 {
         let v = trap as u8;
         self.assembler.emit_ud1_payload(v);
     }
+    /// Loads the `v128` stored at `loc` in an XMM register.
+    fn v128_to_xmm(&mut self, loc: Location, dst: XMM) {
+        match loc {
+            Location::Memory(base, disp) => self
+                .assembler
+                .emit_movdqu(XMMOrMemory::Memory(base, disp), XMMOrMemory::XMM(dst)),
+            _ => panic!("singlepass can't load a v128 from {:?}", loc),
+        }
+    }
+    /// Stores an XMM register to the `v128` at `loc`.
+    fn xmm_to_v128(&mut self, src: XMM, loc: Location) {
+        match loc {
+            Location::Memory(base, disp) => self
+                .assembler
+                .emit_movdqu(XMMOrMemory::XMM(src), XMMOrMemory::Memory(base, disp)),
+            _ => panic!("singlepass can't store a v128 to {:?}", loc),
+        }
+    }
+    /// Materializes a `v128` constant in an XMM register.
+    fn emit_xmm_const(&mut self, value: u128, dst: XMM) {
+        if value == 0 {
+            self.assembler.emit_packed(PackedOp::Pxor, dst, dst);
+            return;
+        }
+        if value == u128::MAX {
+            self.assembler.emit_packed(PackedOp::Pcmpeqd, dst, dst);
+            return;
+        }
+        let (low, high) = (value as u64, (value >> 64) as u64);
+        let tmp = self.acquire_temp_gpr().unwrap();
+        self.assembler
+            .emit_mov(Size::S64, Location::Imm64(low), Location::GPR(tmp));
+        self.assembler
+            .emit_mov(Size::S64, Location::GPR(tmp), Location::SIMD(dst));
+        if high == low {
+            self.assembler.emit_packed(PackedOp::Punpcklqdq, dst, dst);
+        } else {
+            self.assembler
+                .emit_mov(Size::S64, Location::Imm64(high), Location::GPR(tmp));
+            self.assembler
+                .emit_pinsr(Size::S64, GPROrMemory::GPR(tmp), 1, dst);
+        }
+        self.release_gpr(tmp);
+    }
+    /// Materializes a constant with all its lanes of size `sz` set to `value` in an XMM register.
+    fn emit_xmm_splat_const(&mut self, sz: Size, value: u64, dst: XMM) {
+        let bits = match sz {
+            Size::S8 => 8,
+            Size::S16 => 16,
+            Size::S32 => 32,
+            Size::S64 => 64,
+        };
+        let lane = u128::from(value) & (u128::MAX >> (128 - bits));
+        let value = (0..128 / bits).fold(0, |acc, i| acc | lane << (i * bits));
+        self.emit_xmm_const(value, dst);
+    }
+    /// Sets `dst` to the lanes of `a` that are `>=` (`max`) or `<=` (`min`) the ones of `b`,
+    /// unsigned, by comparing `a` with `pmaxu`/`pminu` of both.
+    fn emit_xmm_cmp_unsigned(&mut self, op: PackedOp, eq: PackedOp, a: XMM, b: XMM, tmp: XMM) {
+        self.assembler
+            .emit_vmovaps(XMMOrMemory::XMM(a), XMMOrMemory::XMM(tmp));
+        self.assembler.emit_packed(op, b, tmp);
+        self.assembler.emit_packed(eq, tmp, a);
+    }
+    /// Inverts all the bits of `dst`.
+    fn emit_xmm_not(&mut self, dst: XMM, tmp: XMM) {
+        self.assembler.emit_packed(PackedOp::Pcmpeqd, tmp, tmp);
+        self.assembler.emit_packed(PackedOp::Pxor, tmp, dst);
+    }
+    /// Wasm `min` of packed floats (`single` for F32) in `a` and `b`, to `b`.
+    ///
+    /// `minps` doesn't propagate the NaNs and `-0` of its first operand: it's
+    /// done both ways, the results are merged and their NaNs canonicalized.
+    fn emit_xmm_fmin(&mut self, single: bool, a: XMM, b: XMM, tmp: XMM) {
+        let (min, or, cmp, andn) = if single {
+            (PackedOp::Minps, PackedOp::Orps, PackedImmOp::Cmpps, PackedOp::Andnps)
+        } else {
+            (PackedOp::Minpd, PackedOp::Orpd, PackedImmOp::Cmppd, PackedOp::Andnpd)
+        };
+        self.assembler
+            .emit_vmovaps(XMMOrMemory::XMM(a), XMMOrMemory::XMM(tmp));
+        self.assembler.emit_packed(min, b, tmp);
+        self.assembler.emit_packed(min, a, b);
+        self.assembler.emit_packed(or, b, tmp);
+        // unordered
+        self.assembler.emit_packed_imm(cmp, 3, tmp, b);
+        self.assembler.emit_packed(or, b, tmp);
+        if single {
+            self.assembler.emit_packed_shift(PackedShift::Psrld, 10, b);
+        } else {
+            self.assembler.emit_packed_shift(PackedShift::Psrlq, 13, b);
+        }
+        self.assembler.emit_packed(andn, tmp, b);
+    }
+    /// Wasm `max` of packed floats (`single` for F32) in `a` and `b`, to `b`.
+    fn emit_xmm_fmax(&mut self, single: bool, a: XMM, b: XMM, tmp: XMM) {
+        let (max, xor, or, sub, cmp, andn) = if single {
+            (
+                PackedOp::Maxps,
+                PackedOp::Xorps,
+                PackedOp::Orps,
+                PackedOp::Subps,
+                PackedImmOp::Cmpps,
+                PackedOp::Andnps,
+            )
+        } else {
+            (
+                PackedOp::Maxpd,
+                PackedOp::Xorpd,
+                PackedOp::Orpd,
+                PackedOp::Subpd,
+                PackedImmOp::Cmppd,
+                PackedOp::Andnpd,
+            )
+        };
+        self.assembler
+            .emit_vmovaps(XMMOrMemory::XMM(a), XMMOrMemory::XMM(tmp));
+        self.assembler.emit_packed(max, b, tmp);
+        self.assembler.emit_packed(max, a, b);
+        // Find the discrepancies, and propagate the NaNs and the sign of the zeros.
+        self.assembler.emit_packed(xor, tmp, b);
+        self.assembler.emit_packed(or, b, tmp);
+        self.assembler.emit_packed(sub, b, tmp);
+        // unordered
+        self.assembler.emit_packed_imm(cmp, 3, tmp, b);
+        if single {
+            self.assembler.emit_packed_shift(PackedShift::Psrld, 10, b);
+        } else {
+            self.assembler.emit_packed_shift(PackedShift::Psrlq, 13, b);
+        }
+        self.assembler.emit_packed(andn, tmp, b);
+    }
 }
 
 impl Machine for MachineX86_64 {
@@ -2162,6 +2304,31 @@ impl Machine for MachineX86_64 {
                     }
                 }
             }
+            // movzx/movsx have no immediate form, so extend constants here.
+            Location::Imm8(_) | Location::Imm32(_) | Location::Imm64(_) => {
+                let imm = match source {
+                    Location::Imm8(x) => x as u64,
+                    Location::Imm32(x) => x as u64,
+                    Location::Imm64(x) => x,
+                    _ => unreachable!(),
+                };
+                let value = match (size_val, signed) {
+                    (Size::S8, false) => imm as u8 as u64,
+                    (Size::S8, true) => imm as i8 as i64 as u64,
+                    (Size::S16, false) => imm as u16 as u64,
+                    (Size::S16, true) => imm as i16 as i64 as u64,
+                    (Size::S32, _) => imm as u32 as u64,
+                    (Size::S64, _) => imm,
+                };
+                match size_op {
+                    Size::S64 => self
+                        .assembler
+                        .emit_mov(Size::S64, Location::Imm64(value), dst),
+                    _ => self
+                        .assembler
+                        .emit_mov(Size::S32, Location::Imm32(value as u32), dst),
+                }
+            }
             _ => unreachable!(),
         }
         if dst != dest {
@@ -2245,8 +2412,30 @@ impl Machine for MachineX86_64 {
         self.emit_pop(Size::S64, Location::GPR(GPR::RBP));
     }
 
+    fn emit_stack_check(&mut self, limit: GPR, size: u32, label: Label) {
+        // R11 doesn't hold parameters in any calling convention.
+        self.assembler
+            .emit_mov(Size::S64, Location::GPR(GPR::RSP), Location::GPR(GPR::R11));
+        self.assembler
+            .emit_sub(Size::S64, Location::GPR(limit), Location::GPR(GPR::R11));
+        self.assembler
+            .emit_cmp(Size::S64, Location::Imm32(size), Location::GPR(GPR::R11));
+        self.assembler.emit_jmp(Condition::Below, label);
+    }
+
     fn emit_function_return_value(&mut self, ty: WpType, canonicalize: bool, loc: Location) {
-        if canonicalize {
+        if ty == WpType::V128 {
+            if let Location::Memory(base, offset) = loc {
+                self.emit_relaxed_mov(Size::S64, loc, Location::GPR(GPR::RAX));
+                self.emit_relaxed_mov(
+                    Size::S64,
+                    Location::Memory(base, offset + 8),
+                    Location::GPR(GPR::RDX),
+                );
+            } else {
+                unreachable!();
+            }
+        } else if canonicalize {
             self.canonicalize_nan(
                 match ty {
                     WpType::F32 => Size::S32,
@@ -2351,6 +2540,9 @@ impl Machine for MachineX86_64 {
     fn get_gpr_for_ret(&self) -> GPR {
         GPR::RAX
     }
+    fn get_gpr_for_ret_high(&self) -> GPR {
+        GPR::RDX
+    }
     fn get_simd_for_ret(&self) -> XMM {
         XMM::XMM0
     }
@@ -3732,8 +3924,7 @@ impl Machine for MachineX86_64 {
         heap_access_oob: Label,
     ) {
         let value = self.acquire_temp_gpr().unwrap();
-        self.assembler
-            .emit_movzx(Size::S8, loc, Size::S32, Location::GPR(value));
+        self.move_location_extend(Size::S8, false, loc, Size::S32, Location::GPR(value));
         self.memory_op(
             target,
             memarg,
@@ -3764,8 +3955,7 @@ impl Machine for MachineX86_64 {
         heap_access_oob: Label,
     ) {
         let value = self.acquire_temp_gpr().unwrap();
-        self.assembler
-            .emit_movzx(Size::S16, loc, Size::S32, Location::GPR(value));
+        self.move_location_extend(Size::S16, false, loc, Size::S32, Location::GPR(value));
         self.memory_op(
             target,
             memarg,
@@ -4840,7 +5030,7 @@ impl Machine for MachineX86_64 {
             heap_access_oob,
             |this, addr| {
                 this.assembler.emit_lock_xadd(
-                    Size::S32,
+                    Size::S64,
                     Location::GPR(value),
                     Location::Memory(addr, 0),
                 );
@@ -5486,8 +5676,7 @@ impl Machine for MachineX86_64 {
         heap_access_oob: Label,
     ) {
         let value = self.acquire_temp_gpr().unwrap();
-        self.assembler
-            .emit_movzx(Size::S8, loc, Size::S64, Location::GPR(value));
+        self.move_location_extend(Size::S8, false, loc, Size::S64, Location::GPR(value));
         self.memory_op(
             target,
             memarg,
@@ -5518,8 +5707,7 @@ impl Machine for MachineX86_64 {
         heap_access_oob: Label,
     ) {
         let value = self.acquire_temp_gpr().unwrap();
-        self.assembler
-            .emit_movzx(Size::S16, loc, Size::S64, Location::GPR(value));
+        self.move_location_extend(Size::S16, false, loc, Size::S64, Location::GPR(value));
         self.memory_op(
             target,
             memarg,
@@ -5553,8 +5741,7 @@ impl Machine for MachineX86_64 {
         heap_access_oob: Label,
     ) {
         let value = self.acquire_temp_gpr().unwrap();
-        self.assembler
-            .emit_movzx(Size::S32, loc, Size::S64, Location::GPR(value));
+        self.move_location_extend(Size::S32, false, loc, Size::S64, Location::GPR(value));
         self.memory_op(
             target,
             memarg,
@@ -5768,12 +5955,14 @@ impl Machine for MachineX86_64 {
             heap_access_oob,
             |this, addr| {
                 this.assembler.emit_lock_cmpxchg(
-                    Size::S16,
+                    Size::S32,
                     Location::GPR(value),
                     Location::Memory(addr, 0),
                 );
                 this.assembler
-                    .emit_mov(Size::S32, Location::GPR(compare), ret);
+                    .emit_mov(Size::S32, Location::GPR(compare), Location::GPR(compare));
+                this.assembler
+                    .emit_mov(Size::S64, Location::GPR(compare), ret);
             },
         );
         self.assembler.emit_pop(Size::S64, Location::GPR(value));
@@ -6749,6 +6938,1131 @@ impl Machine for MachineX86_64 {
         self.emit_relaxed_avx(AssemblerX64::emit_vdivss, loc_a, loc_b, ret);
     }
 
+    fn v128_const(&mut self, value: u128, ret: Location) {
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.emit_xmm_const(value, tmp);
+        self.xmm_to_v128(tmp, ret);
+        self.release_simd(tmp);
+    }
+    fn v128_load(
+        &mut self,
+        op: &Operator,
+        addr: Location,
+        memarg: &MemoryImmediate,
+        ret: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let value_size = match op {
+            Operator::V128Load { .. } => 16,
+            Operator::V128Load8Splat { .. } => 1,
+            Operator::V128Load16Splat { .. } => 2,
+            Operator::V128Load32Splat { .. } | Operator::V128Load32Zero { .. } => 4,
+            _ => 8,
+        };
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            value_size,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                let extend = match op {
+                    Operator::V128Load { .. } => {
+                        this.assembler.emit_movdqu(
+                            XMMOrMemory::Memory(addr, 0),
+                            XMMOrMemory::XMM(tmp),
+                        );
+                        None
+                    }
+                    Operator::V128Load8Splat { .. } => {
+                        this.assembler
+                            .emit_pinsr(Size::S8, GPROrMemory::Memory(addr, 0), 0, tmp);
+                        let zero = this.acquire_temp_simd().unwrap();
+                        this.assembler.emit_packed(PackedOp::Pxor, zero, zero);
+                        this.assembler.emit_packed(PackedOp::Pshufb, zero, tmp);
+                        this.release_simd(zero);
+                        None
+                    }
+                    Operator::V128Load16Splat { .. } => {
+                        this.assembler
+                            .emit_pinsr(Size::S16, GPROrMemory::Memory(addr, 0), 0, tmp);
+                        this.assembler
+                            .emit_packed_imm(PackedImmOp::Pshuflw, 0, tmp, tmp);
+                        this.assembler
+                            .emit_packed_imm(PackedImmOp::Pshufd, 0, tmp, tmp);
+                        None
+                    }
+                    Operator::V128Load32Splat { .. } => {
+                        this.assembler.emit_mov(
+                            Size::S32,
+                            Location::Memory(addr, 0),
+                            Location::SIMD(tmp),
+                        );
+                        this.assembler
+                            .emit_packed_imm(PackedImmOp::Pshufd, 0, tmp, tmp);
+                        None
+                    }
+                    Operator::V128Load32Zero { .. } => {
+                        this.assembler.emit_mov(
+                            Size::S32,
+                            Location::Memory(addr, 0),
+                            Location::SIMD(tmp),
+                        );
+                        None
+                    }
+                    _ => {
+                        this.assembler.emit_mov(
+                            Size::S64,
+                            Location::Memory(addr, 0),
+                            Location::SIMD(tmp),
+                        );
+                        match op {
+                            Operator::V128Load8x8S { .. } => Some(PackedOp::Pmovsxbw),
+                            Operator::V128Load8x8U { .. } => Some(PackedOp::Pmovzxbw),
+                            Operator::V128Load16x4S { .. } => Some(PackedOp::Pmovsxwd),
+                            Operator::V128Load16x4U { .. } => Some(PackedOp::Pmovzxwd),
+                            Operator::V128Load32x2S { .. } => Some(PackedOp::Pmovsxdq),
+                            Operator::V128Load32x2U { .. } => Some(PackedOp::Pmovzxdq),
+                            Operator::V128Load64Splat { .. } => Some(PackedOp::Punpcklqdq),
+                            _ => None,
+                        }
+                    }
+                };
+                if let Some(extend) = extend {
+                    this.assembler.emit_packed(extend, tmp, tmp);
+                }
+                this.xmm_to_v128(tmp, ret);
+            },
+        );
+        self.release_simd(tmp);
+    }
+    fn v128_save(
+        &mut self,
+        value: Location,
+        memarg: &MemoryImmediate,
+        addr: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(value, tmp);
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            16,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                this.assembler
+                    .emit_movdqu(XMMOrMemory::XMM(tmp), XMMOrMemory::Memory(addr, 0));
+            },
+        );
+        self.release_simd(tmp);
+    }
+    fn v128_load_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        value: Location,
+        addr: Location,
+        memarg: &MemoryImmediate,
+        ret: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let (sz, value_size) = match op {
+            Operator::V128Load8Lane { .. } => (Size::S8, 1),
+            Operator::V128Load16Lane { .. } => (Size::S16, 2),
+            Operator::V128Load32Lane { .. } => (Size::S32, 4),
+            _ => (Size::S64, 8),
+        };
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(value, tmp);
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            value_size,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                this.assembler
+                    .emit_pinsr(sz, GPROrMemory::Memory(addr, 0), lane, tmp);
+            },
+        );
+        self.xmm_to_v128(tmp, ret);
+        self.release_simd(tmp);
+    }
+    fn v128_save_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        value: Location,
+        memarg: &MemoryImmediate,
+        addr: Location,
+        need_check: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+    ) {
+        let (sz, value_size) = match op {
+            Operator::V128Store8Lane { .. } => (Size::S8, 1),
+            Operator::V128Store16Lane { .. } => (Size::S16, 2),
+            Operator::V128Store32Lane { .. } => (Size::S32, 4),
+            _ => (Size::S64, 8),
+        };
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(value, tmp);
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            value_size,
+            need_check,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            |this, addr| {
+                this.assembler
+                    .emit_pextr(sz, tmp, lane, GPROrMemory::Memory(addr, 0));
+            },
+        );
+        self.release_simd(tmp);
+    }
+    fn v128_splat(&mut self, op: &Operator, loc: Location, ret: Location) {
+        let sz = match op {
+            Operator::I8x16Splat => Size::S8,
+            Operator::I16x8Splat => Size::S16,
+            Operator::I32x4Splat | Operator::F32x4Splat => Size::S32,
+            _ => Size::S64,
+        };
+        let mov_sz = if sz == Size::S64 { Size::S64 } else { Size::S32 };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.emit_relaxed_mov(mov_sz, loc, Location::GPR(gpr));
+        self.assembler
+            .emit_mov(mov_sz, Location::GPR(gpr), Location::SIMD(tmp));
+        match sz {
+            Size::S8 => {
+                let zero = self.acquire_temp_simd().unwrap();
+                self.assembler.emit_packed(PackedOp::Pxor, zero, zero);
+                self.assembler.emit_packed(PackedOp::Pshufb, zero, tmp);
+                self.release_simd(zero);
+            }
+            Size::S16 => {
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Pshuflw, 0, tmp, tmp);
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Pshufd, 0, tmp, tmp);
+            }
+            Size::S32 => self
+                .assembler
+                .emit_packed_imm(PackedImmOp::Pshufd, 0, tmp, tmp),
+            Size::S64 => self.assembler.emit_packed(PackedOp::Punpcklqdq, tmp, tmp),
+        }
+        self.xmm_to_v128(tmp, ret);
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_extract_lane(&mut self, op: &Operator, lane: u8, loc: Location, ret: Location) {
+        let (sz, signed) = match op {
+            Operator::I8x16ExtractLaneS { .. } => (Size::S8, true),
+            Operator::I8x16ExtractLaneU { .. } => (Size::S8, false),
+            Operator::I16x8ExtractLaneS { .. } => (Size::S16, true),
+            Operator::I16x8ExtractLaneU { .. } => (Size::S16, false),
+            Operator::I32x4ExtractLane { .. } | Operator::F32x4ExtractLane { .. } => {
+                (Size::S32, false)
+            }
+            _ => (Size::S64, false),
+        };
+        let tmp = self.acquire_temp_simd().unwrap();
+        let gpr = self.acquire_temp_gpr().unwrap();
+        self.v128_to_xmm(loc, tmp);
+        self.assembler
+            .emit_pextr(sz, tmp, lane, GPROrMemory::GPR(gpr));
+        if signed {
+            self.assembler
+                .emit_movsx(sz, Location::GPR(gpr), Size::S32, Location::GPR(gpr));
+        }
+        let mov_sz = if sz == Size::S64 { Size::S64 } else { Size::S32 };
+        self.emit_relaxed_mov(mov_sz, Location::GPR(gpr), ret);
+        self.release_gpr(gpr);
+        self.release_simd(tmp);
+    }
+    fn v128_replace_lane(
+        &mut self,
+        op: &Operator,
+        lane: u8,
+        loc: Location,
+        value: Location,
+        ret: Location,
+    ) {
+        let sz = match op {
+            Operator::I8x16ReplaceLane { .. } => Size::S8,
+            Operator::I16x8ReplaceLane { .. } => Size::S16,
+            Operator::I32x4ReplaceLane { .. } | Operator::F32x4ReplaceLane { .. } => Size::S32,
+            _ => Size::S64,
+        };
+        let mov_sz = if sz == Size::S64 { Size::S64 } else { Size::S32 };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.emit_relaxed_mov(mov_sz, value, Location::GPR(gpr));
+        self.v128_to_xmm(loc, tmp);
+        self.assembler
+            .emit_pinsr(sz, GPROrMemory::GPR(gpr), lane, tmp);
+        self.xmm_to_v128(tmp, ret);
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_shuffle(&mut self, lanes: &[u8; 16], loc_a: Location, loc_b: Location, ret: Location) {
+        // Each input is shuffled with the lanes it provides, the others being
+        // zeroed by setting the high bit of their index.
+        let mut mask_a = [0x80u8; 16];
+        let mut mask_b = [0x80u8; 16];
+        for (i, &lane) in lanes.iter().enumerate() {
+            if lane < 16 {
+                mask_a[i] = lane;
+            } else {
+                mask_b[i] = lane - 16;
+            }
+        }
+        let tmp_a = self.acquire_temp_simd().unwrap();
+        let tmp_b = self.acquire_temp_simd().unwrap();
+        let mask = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(loc_a, tmp_a);
+        self.v128_to_xmm(loc_b, tmp_b);
+        self.emit_xmm_const(u128::from_le_bytes(mask_a), mask);
+        self.assembler.emit_packed(PackedOp::Pshufb, mask, tmp_a);
+        self.emit_xmm_const(u128::from_le_bytes(mask_b), mask);
+        self.assembler.emit_packed(PackedOp::Pshufb, mask, tmp_b);
+        self.assembler.emit_packed(PackedOp::Por, tmp_b, tmp_a);
+        self.xmm_to_v128(tmp_a, ret);
+        self.release_simd(mask);
+        self.release_simd(tmp_b);
+        self.release_simd(tmp_a);
+    }
+    fn v128_test(&mut self, op: &Operator, loc: Location, ret: Location) {
+        let tmp = self.acquire_temp_simd().unwrap();
+        let gpr = self.acquire_temp_gpr().unwrap();
+        self.v128_to_xmm(loc, tmp);
+        match op {
+            Operator::V128AnyTrue => {
+                self.assembler
+                    .emit_mov(Size::S32, Location::Imm32(0), Location::GPR(gpr));
+                self.assembler.emit_ptest(tmp, tmp);
+                self.assembler.emit_set(Condition::NotEqual, gpr);
+            }
+            Operator::I8x16AllTrue
+            | Operator::I16x8AllTrue
+            | Operator::I32x4AllTrue
+            | Operator::I64x2AllTrue => {
+                let cmp = match op {
+                    Operator::I8x16AllTrue => PackedOp::Pcmpeqb,
+                    Operator::I16x8AllTrue => PackedOp::Pcmpeqw,
+                    Operator::I32x4AllTrue => PackedOp::Pcmpeqd,
+                    _ => PackedOp::Pcmpeqq,
+                };
+                // All the lanes are non-zero if none of them compares equal to zero.
+                let zeros = self.acquire_temp_simd().unwrap();
+                self.assembler.emit_packed(PackedOp::Pxor, zeros, zeros);
+                self.assembler.emit_packed(cmp, tmp, zeros);
+                self.assembler
+                    .emit_mov(Size::S32, Location::Imm32(0), Location::GPR(gpr));
+                self.assembler.emit_ptest(zeros, zeros);
+                self.assembler.emit_set(Condition::Equal, gpr);
+                self.release_simd(zeros);
+            }
+            Operator::I8x16Bitmask => self.assembler.emit_movmsk(Size::S8, tmp, gpr),
+            Operator::I16x8Bitmask => {
+                self.assembler.emit_packed(PackedOp::Packsswb, tmp, tmp);
+                self.assembler.emit_movmsk(Size::S8, tmp, gpr);
+                self.assembler
+                    .emit_and(Size::S32, Location::Imm32(0xff), Location::GPR(gpr));
+            }
+            Operator::I32x4Bitmask => self.assembler.emit_movmsk(Size::S32, tmp, gpr),
+            Operator::I64x2Bitmask => self.assembler.emit_movmsk(Size::S64, tmp, gpr),
+            _ => unreachable!("{:?} is not a v128 test", op),
+        }
+        self.emit_relaxed_mov(Size::S32, Location::GPR(gpr), ret);
+        self.release_gpr(gpr);
+        self.release_simd(tmp);
+    }
+    fn v128_shift(&mut self, op: &Operator, loc: Location, count: Location, ret: Location) {
+        let (bits, shift) = match op {
+            Operator::I8x16Shl | Operator::I8x16ShrS | Operator::I8x16ShrU => (8, None),
+            Operator::I16x8Shl => (16, Some(PackedOp::Psllw)),
+            Operator::I16x8ShrS => (16, Some(PackedOp::Psraw)),
+            Operator::I16x8ShrU => (16, Some(PackedOp::Psrlw)),
+            Operator::I32x4Shl => (32, Some(PackedOp::Pslld)),
+            Operator::I32x4ShrS => (32, Some(PackedOp::Psrad)),
+            Operator::I32x4ShrU => (32, Some(PackedOp::Psrld)),
+            Operator::I64x2Shl => (64, Some(PackedOp::Psllq)),
+            Operator::I64x2ShrS => (64, None),
+            _ => (64, Some(PackedOp::Psrlq)),
+        };
+        let gpr = self.acquire_temp_gpr().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        let tmp_count = self.acquire_temp_simd().unwrap();
+        self.emit_relaxed_mov(Size::S32, count, Location::GPR(gpr));
+        self.assembler
+            .emit_and(Size::S32, Location::Imm32(bits - 1), Location::GPR(gpr));
+        self.v128_to_xmm(loc, tmp);
+        self.assembler
+            .emit_mov(Size::S32, Location::GPR(gpr), Location::SIMD(tmp_count));
+        match (op, shift) {
+            (_, Some(shift)) => self.assembler.emit_packed(shift, tmp_count, tmp),
+            (Operator::I64x2ShrS, _) => {
+                // There is no arithmetic shift of 64 bits lanes: it's
+                // `((x >>u n) ^ m) - m`, with `m` the sign bit shifted by `n`.
+                let sign = self.acquire_temp_simd().unwrap();
+                self.emit_xmm_splat_const(Size::S64, 1 << 63, sign);
+                self.assembler.emit_packed(PackedOp::Psrlq, tmp_count, sign);
+                self.assembler.emit_packed(PackedOp::Psrlq, tmp_count, tmp);
+                self.assembler.emit_packed(PackedOp::Pxor, sign, tmp);
+                self.assembler.emit_packed(PackedOp::Psubq, sign, tmp);
+                self.release_simd(sign);
+            }
+            (Operator::I8x16ShrS, _) => {
+                // Each byte goes to the high half of a 16 bits lane, so it's
+                // shifted by `n + 8` and narrowed back.
+                let high = self.acquire_temp_simd().unwrap();
+                self.assembler
+                    .emit_vmovaps(XMMOrMemory::XMM(tmp), XMMOrMemory::XMM(high));
+                self.assembler.emit_packed(PackedOp::Punpckhbw, high, high);
+                self.assembler.emit_packed(PackedOp::Punpcklbw, tmp, tmp);
+                self.assembler
+                    .emit_add(Size::S32, Location::Imm32(8), Location::GPR(gpr));
+                self.assembler
+                    .emit_mov(Size::S32, Location::GPR(gpr), Location::SIMD(tmp_count));
+                self.assembler.emit_packed(PackedOp::Psraw, tmp_count, tmp);
+                self.assembler.emit_packed(PackedOp::Psraw, tmp_count, high);
+                self.assembler.emit_packed(PackedOp::Packsswb, high, tmp);
+                self.release_simd(high);
+            }
+            _ => {
+                // The 8 bits shifts are done on 16 bits lanes, masking out the
+                // bits crossing the bytes: `0xff >> n` is built in the bytes by
+                // shifting all ones by `n + 8` and narrowing it.
+                let mask = self.acquire_temp_simd().unwrap();
+                if let Operator::I8x16ShrU = op {
+                    self.assembler.emit_packed(PackedOp::Psrlw, tmp_count, tmp);
+                }
+                self.assembler
+                    .emit_add(Size::S32, Location::Imm32(8), Location::GPR(gpr));
+                self.assembler
+                    .emit_mov(Size::S32, Location::GPR(gpr), Location::SIMD(tmp_count));
+                self.assembler.emit_packed(PackedOp::Pcmpeqd, mask, mask);
+                self.assembler.emit_packed(PackedOp::Psrlw, tmp_count, mask);
+                self.assembler.emit_packed(PackedOp::Packuswb, mask, mask);
+                self.assembler.emit_packed(PackedOp::Pand, mask, tmp);
+                if let Operator::I8x16Shl = op {
+                    self.assembler
+                        .emit_sub(Size::S32, Location::Imm32(8), Location::GPR(gpr));
+                    self.assembler
+                        .emit_mov(Size::S32, Location::GPR(gpr), Location::SIMD(tmp_count));
+                    self.assembler.emit_packed(PackedOp::Psllw, tmp_count, tmp);
+                }
+                self.release_simd(mask);
+            }
+        }
+        self.xmm_to_v128(tmp, ret);
+        self.release_simd(tmp_count);
+        self.release_simd(tmp);
+        self.release_gpr(gpr);
+    }
+    fn v128_unop(&mut self, op: &Operator, loc: Location, ret: Location) {
+        let x = self.acquire_temp_simd().unwrap();
+        let tmp1 = self.acquire_temp_simd().unwrap();
+        let tmp2 = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(loc, x);
+        let res = match op {
+            Operator::V128Not => {
+                self.emit_xmm_not(x, tmp1);
+                x
+            }
+            Operator::I8x16Abs | Operator::I16x8Abs | Operator::I32x4Abs => {
+                let abs = match op {
+                    Operator::I8x16Abs => PackedOp::Pabsb,
+                    Operator::I16x8Abs => PackedOp::Pabsw,
+                    _ => PackedOp::Pabsd,
+                };
+                self.assembler.emit_packed(abs, x, x);
+                x
+            }
+            Operator::I64x2Abs => {
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, tmp1);
+                self.assembler.emit_packed(PackedOp::Pcmpgtq, x, tmp1);
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, x);
+                self.assembler.emit_packed(PackedOp::Psubq, tmp1, x);
+                x
+            }
+            Operator::I8x16Neg | Operator::I16x8Neg | Operator::I32x4Neg | Operator::I64x2Neg => {
+                let sub = match op {
+                    Operator::I8x16Neg => PackedOp::Psubb,
+                    Operator::I16x8Neg => PackedOp::Psubw,
+                    Operator::I32x4Neg => PackedOp::Psubd,
+                    _ => PackedOp::Psubq,
+                };
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, tmp1);
+                self.assembler.emit_packed(sub, x, tmp1);
+                tmp1
+            }
+            Operator::I8x16Popcnt => {
+                // Look up the counts of the low and high nibbles of the bytes.
+                let counts = (0..16u32).fold(0u128, |acc, i| {
+                    acc | u128::from(i.count_ones()) << (i * 8)
+                });
+                self.emit_xmm_splat_const(Size::S8, 0x0f, tmp1);
+                self.assembler
+                    .emit_vmovaps(XMMOrMemory::XMM(x), XMMOrMemory::XMM(tmp2));
+                self.assembler.emit_packed_shift(PackedShift::Psrlw, 4, tmp2);
+                self.assembler.emit_packed(PackedOp::Pand, tmp1, tmp2);
+                self.assembler.emit_packed(PackedOp::Pand, tmp1, x);
+                self.emit_xmm_const(counts, tmp1);
+                self.assembler.emit_packed(PackedOp::Pshufb, x, tmp1);
+                self.emit_xmm_const(counts, x);
+                self.assembler.emit_packed(PackedOp::Pshufb, tmp2, x);
+                self.assembler.emit_packed(PackedOp::Paddb, tmp1, x);
+                x
+            }
+            Operator::I16x8ExtAddPairwiseI8x16S => {
+                self.emit_xmm_splat_const(Size::S8, 1, tmp1);
+                self.assembler.emit_packed(PackedOp::Pmaddubsw, x, tmp1);
+                tmp1
+            }
+            Operator::I16x8ExtAddPairwiseI8x16U => {
+                self.emit_xmm_splat_const(Size::S8, 1, tmp1);
+                self.assembler.emit_packed(PackedOp::Pmaddubsw, tmp1, x);
+                x
+            }
+            Operator::I32x4ExtAddPairwiseI16x8S => {
+                self.emit_xmm_splat_const(Size::S16, 1, tmp1);
+                self.assembler.emit_packed(PackedOp::Pmaddwd, tmp1, x);
+                x
+            }
+            Operator::I32x4ExtAddPairwiseI16x8U => {
+                // Signed pairwise add of the lanes biased by `-0x8000`, then unbiased.
+                self.emit_xmm_splat_const(Size::S16, 0x8000, tmp1);
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, x);
+                self.emit_xmm_splat_const(Size::S16, 1, tmp1);
+                self.assembler.emit_packed(PackedOp::Pmaddwd, tmp1, x);
+                self.emit_xmm_splat_const(Size::S32, 0x10000, tmp1);
+                self.assembler.emit_packed(PackedOp::Paddd, tmp1, x);
+                x
+            }
+            Operator::I16x8ExtendLowI8x16S
+            | Operator::I16x8ExtendHighI8x16S
+            | Operator::I16x8ExtendLowI8x16U
+            | Operator::I16x8ExtendHighI8x16U
+            | Operator::I32x4ExtendLowI16x8S
+            | Operator::I32x4ExtendHighI16x8S
+            | Operator::I32x4ExtendLowI16x8U
+            | Operator::I32x4ExtendHighI16x8U
+            | Operator::I64x2ExtendLowI32x4S
+            | Operator::I64x2ExtendHighI32x4S
+            | Operator::I64x2ExtendLowI32x4U
+            | Operator::I64x2ExtendHighI32x4U => {
+                let (extend, high) = match op {
+                    Operator::I16x8ExtendLowI8x16S => (PackedOp::Pmovsxbw, false),
+                    Operator::I16x8ExtendHighI8x16S => (PackedOp::Pmovsxbw, true),
+                    Operator::I16x8ExtendLowI8x16U => (PackedOp::Pmovzxbw, false),
+                    Operator::I16x8ExtendHighI8x16U => (PackedOp::Pmovzxbw, true),
+                    Operator::I32x4ExtendLowI16x8S => (PackedOp::Pmovsxwd, false),
+                    Operator::I32x4ExtendHighI16x8S => (PackedOp::Pmovsxwd, true),
+                    Operator::I32x4ExtendLowI16x8U => (PackedOp::Pmovzxwd, false),
+                    Operator::I32x4ExtendHighI16x8U => (PackedOp::Pmovzxwd, true),
+                    Operator::I64x2ExtendLowI32x4S => (PackedOp::Pmovsxdq, false),
+                    Operator::I64x2ExtendHighI32x4S => (PackedOp::Pmovsxdq, true),
+                    Operator::I64x2ExtendLowI32x4U => (PackedOp::Pmovzxdq, false),
+                    _ => (PackedOp::Pmovzxdq, true),
+                };
+                if high {
+                    self.assembler.emit_packed_shift(PackedShift::Psrldq, 8, x);
+                }
+                self.assembler.emit_packed(extend, x, x);
+                x
+            }
+            Operator::F32x4Abs => {
+                self.emit_xmm_splat_const(Size::S32, 0x7fff_ffff, tmp1);
+                self.assembler.emit_packed(PackedOp::Pand, tmp1, x);
+                x
+            }
+            Operator::F64x2Abs => {
+                self.emit_xmm_splat_const(Size::S64, 0x7fff_ffff_ffff_ffff, tmp1);
+                self.assembler.emit_packed(PackedOp::Pand, tmp1, x);
+                x
+            }
+            Operator::F32x4Neg => {
+                self.emit_xmm_splat_const(Size::S32, 0x8000_0000, tmp1);
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, x);
+                x
+            }
+            Operator::F64x2Neg => {
+                self.emit_xmm_splat_const(Size::S64, 0x8000_0000_0000_0000, tmp1);
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, x);
+                x
+            }
+            Operator::F32x4Ceil
+            | Operator::F32x4Floor
+            | Operator::F32x4Trunc
+            | Operator::F32x4Nearest
+            | Operator::F64x2Ceil
+            | Operator::F64x2Floor
+            | Operator::F64x2Trunc
+            | Operator::F64x2Nearest => {
+                let (round, mode) = match op {
+                    Operator::F32x4Ceil => (PackedImmOp::Roundps, 2),
+                    Operator::F32x4Floor => (PackedImmOp::Roundps, 1),
+                    Operator::F32x4Trunc => (PackedImmOp::Roundps, 3),
+                    Operator::F32x4Nearest => (PackedImmOp::Roundps, 0),
+                    Operator::F64x2Ceil => (PackedImmOp::Roundpd, 2),
+                    Operator::F64x2Floor => (PackedImmOp::Roundpd, 1),
+                    Operator::F64x2Trunc => (PackedImmOp::Roundpd, 3),
+                    _ => (PackedImmOp::Roundpd, 0),
+                };
+                self.assembler.emit_packed_imm(round, mode, x, x);
+                x
+            }
+            Operator::F32x4Sqrt => {
+                self.assembler.emit_packed(PackedOp::Sqrtps, x, x);
+                x
+            }
+            Operator::F64x2Sqrt => {
+                self.assembler.emit_packed(PackedOp::Sqrtpd, x, x);
+                x
+            }
+            Operator::I32x4TruncSatF32x4S | Operator::I32x4RelaxedTruncSatF32x4S => {
+                // NaN to 0
+                self.assembler
+                    .emit_vmovaps(XMMOrMemory::XMM(x), XMMOrMemory::XMM(tmp1));
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Cmpps, 0, tmp1, tmp1);
+                self.assembler.emit_packed(PackedOp::Pand, tmp1, x);
+                // Positive lanes have their top bit set
+                self.assembler.emit_packed(PackedOp::Pxor, x, tmp1);
+                self.assembler.emit_packed(PackedOp::Cvttps2dq, x, x);
+                // Positive lanes that overflowed to 0x80000000 become 0x7fffffff
+                self.assembler.emit_packed(PackedOp::Pand, x, tmp1);
+                self.assembler.emit_packed_shift(PackedShift::Psrad, 31, tmp1);
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, x);
+                x
+            }
+            Operator::I32x4TruncSatF32x4U | Operator::I32x4RelaxedTruncSatF32x4U => {
+                // NaN and negative lanes to 0
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, tmp1);
+                self.assembler.emit_packed(PackedOp::Maxps, tmp1, x);
+                // tmp1 is 2^31 as a float, the lanes at least that big are
+                // converted in tmp2 after subtracting it.
+                self.assembler.emit_packed(PackedOp::Pcmpeqd, tmp1, tmp1);
+                self.assembler.emit_packed_shift(PackedShift::Psrld, 1, tmp1);
+                self.assembler.emit_packed(PackedOp::Cvtdq2ps, tmp1, tmp1);
+                self.assembler
+                    .emit_vmovaps(XMMOrMemory::XMM(x), XMMOrMemory::XMM(tmp2));
+                self.assembler.emit_packed(PackedOp::Subps, tmp1, tmp2);
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Cmpps, 2, tmp2, tmp1);
+                self.assembler.emit_packed(PackedOp::Cvttps2dq, tmp2, tmp2);
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, tmp2);
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, tmp1);
+                self.assembler.emit_packed(PackedOp::Pmaxsd, tmp1, tmp2);
+                // The lanes at least 2^31 are 0x80000000 here
+                self.assembler.emit_packed(PackedOp::Cvttps2dq, x, x);
+                self.assembler.emit_packed(PackedOp::Paddd, tmp2, x);
+                x
+            }
+            Operator::F32x4ConvertI32x4S => {
+                self.assembler.emit_packed(PackedOp::Cvtdq2ps, x, x);
+                x
+            }
+            Operator::F32x4ConvertI32x4U => {
+                // The low 16 bits and the high part are converted exactly on
+                // their own, then added with a single rounding.
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, tmp1);
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Pblendw, 0x55, x, tmp1);
+                self.assembler.emit_packed(PackedOp::Psubd, tmp1, x);
+                self.assembler.emit_packed(PackedOp::Cvtdq2ps, tmp1, tmp1);
+                self.assembler.emit_packed_shift(PackedShift::Psrld, 1, x);
+                self.assembler.emit_packed(PackedOp::Cvtdq2ps, x, x);
+                self.assembler.emit_packed(PackedOp::Addps, x, x);
+                self.assembler.emit_packed(PackedOp::Addps, tmp1, x);
+                x
+            }
+            Operator::I32x4TruncSatF64x2SZero | Operator::I32x4RelaxedTruncSatF64x2SZero => {
+                // NaN lanes are clamped to 0, the others to i32::MAX
+                self.assembler
+                    .emit_vmovaps(XMMOrMemory::XMM(x), XMMOrMemory::XMM(tmp1));
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Cmppd, 0, x, tmp1);
+                self.emit_xmm_splat_const(Size::S64, 2147483647f64.to_bits(), tmp2);
+                self.assembler.emit_packed(PackedOp::Pand, tmp2, tmp1);
+                self.assembler.emit_packed(PackedOp::Minpd, tmp1, x);
+                self.assembler.emit_packed(PackedOp::Cvttpd2dq, x, x);
+                x
+            }
+            Operator::I32x4TruncSatF64x2UZero | Operator::I32x4RelaxedTruncSatF64x2UZero => {
+                // Clamped and truncated, the lanes are the low 32 bits of their
+                // mantissa once 2^52 is added.
+                self.assembler.emit_packed(PackedOp::Pxor, tmp1, tmp1);
+                self.assembler.emit_packed(PackedOp::Maxpd, tmp1, x);
+                self.emit_xmm_splat_const(Size::S64, 4294967295f64.to_bits(), tmp2);
+                self.assembler.emit_packed(PackedOp::Minpd, tmp2, x);
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Roundpd, 3, x, x);
+                self.emit_xmm_splat_const(Size::S64, 4503599627370496f64.to_bits(), tmp2);
+                self.assembler.emit_packed(PackedOp::Addpd, tmp2, x);
+                self.assembler
+                    .emit_packed_imm(PackedImmOp::Shufps, 0x88, tmp1, x);
+                x
+            }
+            Operator::F64x2ConvertLowI32x4S => {
+                self.assembler.emit_packed(PackedOp::Cvtdq2pd, x, x);
+                x
+            }
+            Operator::F64x2ConvertLowI32x4U => {
+                // 2^52 + the lane, exactly, minus 2^52
+                self.emit_xmm_splat_const(Size::S32, 0x4330_0000, tmp1);
+                self.assembler.emit_packed(PackedOp::Unpcklps, tmp1, x);
+                self.emit_xmm_splat_const(Size::S64, 4503599627370496f64.to_bits(), tmp1);
+                self.assembler.emit_packed(PackedOp::Subpd, tmp1, x);
+                x
+            }
+            Operator::F32x4DemoteF64x2Zero => {
+                self.assembler.emit_packed(PackedOp::Cvtpd2ps, x, x);
+                x
+            }
+            Operator::F64x2PromoteLowF32x4 => {
+                self.assembler.emit_packed(PackedOp::Cvtps2pd, x, x);
+                x
+            }
+            _ => unreachable!("{:?} is not a v128 unary operator", op),
+        };
+        self.xmm_to_v128(res, ret);
+        self.release_simd(tmp2);
+        self.release_simd(tmp1);
+        self.release_simd(x);
+    }
+    fn v128_binop(&mut self, op: &Operator, loc_a: Location, loc_b: Location, ret: Location) {
+        let a = self.acquire_temp_simd().unwrap();
+        let b = self.acquire_temp_simd().unwrap();
+        let tmp = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(loc_a, a);
+        self.v128_to_xmm(loc_b, b);
+        let packed = |op: &Operator| -> Option<(PackedOp, bool)> {
+            // The instruction computing `a op b` in `a`, or `b op a` in `b`
+            // (the `bool` being set).
+            Some(match op {
+                Operator::I8x16Eq => (PackedOp::Pcmpeqb, false),
+                Operator::I16x8Eq => (PackedOp::Pcmpeqw, false),
+                Operator::I32x4Eq => (PackedOp::Pcmpeqd, false),
+                Operator::I64x2Eq => (PackedOp::Pcmpeqq, false),
+                Operator::I8x16GtS => (PackedOp::Pcmpgtb, false),
+                Operator::I16x8GtS => (PackedOp::Pcmpgtw, false),
+                Operator::I32x4GtS => (PackedOp::Pcmpgtd, false),
+                Operator::I64x2GtS => (PackedOp::Pcmpgtq, false),
+                Operator::I8x16LtS => (PackedOp::Pcmpgtb, true),
+                Operator::I16x8LtS => (PackedOp::Pcmpgtw, true),
+                Operator::I32x4LtS => (PackedOp::Pcmpgtd, true),
+                Operator::I64x2LtS => (PackedOp::Pcmpgtq, true),
+                Operator::V128And => (PackedOp::Pand, false),
+                Operator::V128AndNot => (PackedOp::Pandn, true),
+                Operator::V128Or => (PackedOp::Por, false),
+                Operator::V128Xor => (PackedOp::Pxor, false),
+                Operator::I8x16NarrowI16x8S => (PackedOp::Packsswb, false),
+                Operator::I8x16NarrowI16x8U => (PackedOp::Packuswb, false),
+                Operator::I16x8NarrowI32x4S => (PackedOp::Packssdw, false),
+                Operator::I16x8NarrowI32x4U => (PackedOp::Packusdw, false),
+                Operator::I8x16Add => (PackedOp::Paddb, false),
+                Operator::I8x16AddSatS => (PackedOp::Paddsb, false),
+                Operator::I8x16AddSatU => (PackedOp::Paddusb, false),
+                Operator::I8x16Sub => (PackedOp::Psubb, false),
+                Operator::I8x16SubSatS => (PackedOp::Psubsb, false),
+                Operator::I8x16SubSatU => (PackedOp::Psubusb, false),
+                Operator::I8x16MinS => (PackedOp::Pminsb, false),
+                Operator::I8x16MinU => (PackedOp::Pminub, false),
+                Operator::I8x16MaxS => (PackedOp::Pmaxsb, false),
+                Operator::I8x16MaxU => (PackedOp::Pmaxub, false),
+                Operator::I8x16RoundingAverageU => (PackedOp::Pavgb, false),
+                Operator::I16x8Add => (PackedOp::Paddw, false),
+                Operator::I16x8AddSatS => (PackedOp::Paddsw, false),
+                Operator::I16x8AddSatU => (PackedOp::Paddusw, false),
+                Operator::I16x8Sub => (PackedOp::Psubw, false),
+                Operator::I16x8SubSatS => (PackedOp::Psubsw, false),
+                Operator::I16x8SubSatU => (PackedOp::Psubusw, false),
+                Operator::I16x8Mul => (PackedOp::Pmullw, false),
+                Operator::I16x8MinS => (PackedOp::Pminsw, false),
+                Operator::I16x8MinU => (PackedOp::Pminuw, false),
+                Operator::I16x8MaxS => (PackedOp::Pmaxsw, false),
+                Operator::I16x8MaxU => (PackedOp::Pmaxuw, false),
+                Operator::I16x8RoundingAverageU => (PackedOp::Pavgw, false),
+                Operator::I32x4Add => (PackedOp::Paddd, false),
+                Operator::I32x4Sub => (PackedOp::Psubd, false),
+                Operator::I32x4Mul => (PackedOp::Pmulld, false),
+                Operator::I32x4MinS => (PackedOp::Pminsd, false),
+                Operator::I32x4MinU => (PackedOp::Pminud, false),
+                Operator::I32x4MaxS => (PackedOp::Pmaxsd, false),
+                Operator::I32x4MaxU => (PackedOp::Pmaxud, false),
+                Operator::I32x4DotI16x8S => (PackedOp::Pmaddwd, false),
+                Operator::I64x2Add => (PackedOp::Paddq, false),
+                Operator::I64x2Sub => (PackedOp::Psubq, false),
+                Operator::F32x4Add => (PackedOp::Addps, false),
+                Operator::F32x4Sub => (PackedOp::Subps, false),
+                Operator::F32x4Mul => (PackedOp::Mulps, false),
+                Operator::F32x4Div => (PackedOp::Divps, false),
+                Operator::F32x4PMin => (PackedOp::Minps, true),
+                Operator::F32x4PMax => (PackedOp::Maxps, true),
+                Operator::F64x2Add => (PackedOp::Addpd, false),
+                Operator::F64x2Sub => (PackedOp::Subpd, false),
+                Operator::F64x2Mul => (PackedOp::Mulpd, false),
+                Operator::F64x2Div => (PackedOp::Divpd, false),
+                Operator::F64x2PMin => (PackedOp::Minpd, true),
+                Operator::F64x2PMax => (PackedOp::Maxpd, true),
+                _ => return None,
+            })
+        };
+        let res = if let Some((packed, swapped)) = packed(op) {
+            if swapped {
+                self.assembler.emit_packed(packed, a, b);
+                b
+            } else {
+                self.assembler.emit_packed(packed, b, a);
+                a
+            }
+        } else {
+            match op {
+                Operator::I8x16Swizzle | Operator::I8x16RelaxedSwizzle => {
+                    // Out of range indices get their high bit set, zeroing their lane.
+                    self.emit_xmm_splat_const(Size::S8, 0x70, tmp);
+                    self.assembler.emit_packed(PackedOp::Paddusb, tmp, b);
+                    self.assembler.emit_packed(PackedOp::Pshufb, b, a);
+                    a
+                }
+                Operator::I8x16Ne | Operator::I16x8Ne | Operator::I32x4Ne | Operator::I64x2Ne => {
+                    let eq = match op {
+                        Operator::I8x16Ne => PackedOp::Pcmpeqb,
+                        Operator::I16x8Ne => PackedOp::Pcmpeqw,
+                        Operator::I32x4Ne => PackedOp::Pcmpeqd,
+                        _ => PackedOp::Pcmpeqq,
+                    };
+                    self.assembler.emit_packed(eq, b, a);
+                    self.emit_xmm_not(a, tmp);
+                    a
+                }
+                Operator::I8x16LeS | Operator::I16x8LeS | Operator::I32x4LeS | Operator::I64x2LeS => {
+                    let gt = match op {
+                        Operator::I8x16LeS => PackedOp::Pcmpgtb,
+                        Operator::I16x8LeS => PackedOp::Pcmpgtw,
+                        Operator::I32x4LeS => PackedOp::Pcmpgtd,
+                        _ => PackedOp::Pcmpgtq,
+                    };
+                    self.assembler.emit_packed(gt, b, a);
+                    self.emit_xmm_not(a, tmp);
+                    a
+                }
+                Operator::I8x16GeS | Operator::I16x8GeS | Operator::I32x4GeS | Operator::I64x2GeS => {
+                    let gt = match op {
+                        Operator::I8x16GeS => PackedOp::Pcmpgtb,
+                        Operator::I16x8GeS => PackedOp::Pcmpgtw,
+                        Operator::I32x4GeS => PackedOp::Pcmpgtd,
+                        _ => PackedOp::Pcmpgtq,
+                    };
+                    self.assembler.emit_packed(gt, a, b);
+                    self.emit_xmm_not(b, tmp);
+                    b
+                }
+                Operator::I8x16LtU
+                | Operator::I8x16GtU
+                | Operator::I8x16LeU
+                | Operator::I8x16GeU
+                | Operator::I16x8LtU
+                | Operator::I16x8GtU
+                | Operator::I16x8LeU
+                | Operator::I16x8GeU
+                | Operator::I32x4LtU
+                | Operator::I32x4GtU
+                | Operator::I32x4LeU
+                | Operator::I32x4GeU => {
+                    // `a >= b` is `max(a, b) == a`, and `a <= b` is `min(a, b) == a`
+                    let (max, min, eq) = match op {
+                        Operator::I8x16LtU
+                        | Operator::I8x16GtU
+                        | Operator::I8x16LeU
+                        | Operator::I8x16GeU => {
+                            (PackedOp::Pmaxub, PackedOp::Pminub, PackedOp::Pcmpeqb)
+                        }
+                        Operator::I16x8LtU
+                        | Operator::I16x8GtU
+                        | Operator::I16x8LeU
+                        | Operator::I16x8GeU => {
+                            (PackedOp::Pmaxuw, PackedOp::Pminuw, PackedOp::Pcmpeqw)
+                        }
+                        _ => (PackedOp::Pmaxud, PackedOp::Pminud, PackedOp::Pcmpeqd),
+                    };
+                    let (cmp, not) = match op {
+                        Operator::I8x16GeU | Operator::I16x8GeU | Operator::I32x4GeU => {
+                            (max, false)
+                        }
+                        Operator::I8x16LeU | Operator::I16x8LeU | Operator::I32x4LeU => {
+                            (min, false)
+                        }
+                        Operator::I8x16LtU | Operator::I16x8LtU | Operator::I32x4LtU => {
+                            (max, true)
+                        }
+                        _ => (min, true),
+                    };
+                    self.emit_xmm_cmp_unsigned(cmp, eq, a, b, tmp);
+                    if not {
+                        self.emit_xmm_not(a, tmp);
+                    }
+                    a
+                }
+                Operator::F32x4Eq
+                | Operator::F32x4Ne
+                | Operator::F32x4Lt
+                | Operator::F32x4Le
+                | Operator::F32x4Gt
+                | Operator::F32x4Ge
+                | Operator::F64x2Eq
+                | Operator::F64x2Ne
+                | Operator::F64x2Lt
+                | Operator::F64x2Le
+                | Operator::F64x2Gt
+                | Operator::F64x2Ge => {
+                    let cmp = match op {
+                        Operator::F32x4Eq
+                        | Operator::F32x4Ne
+                        | Operator::F32x4Lt
+                        | Operator::F32x4Le
+                        | Operator::F32x4Gt
+                        | Operator::F32x4Ge => PackedImmOp::Cmpps,
+                        _ => PackedImmOp::Cmppd,
+                    };
+                    // `gt` and `ge` are `lt` and `le` with swapped operands
+                    match op {
+                        Operator::F32x4Eq | Operator::F64x2Eq => {
+                            self.assembler.emit_packed_imm(cmp, 0, b, a);
+                            a
+                        }
+                        Operator::F32x4Ne | Operator::F64x2Ne => {
+                            self.assembler.emit_packed_imm(cmp, 4, b, a);
+                            a
+                        }
+                        Operator::F32x4Lt | Operator::F64x2Lt => {
+                            self.assembler.emit_packed_imm(cmp, 1, b, a);
+                            a
+                        }
+                        Operator::F32x4Le | Operator::F64x2Le => {
+                            self.assembler.emit_packed_imm(cmp, 2, b, a);
+                            a
+                        }
+                        Operator::F32x4Gt | Operator::F64x2Gt => {
+                            self.assembler.emit_packed_imm(cmp, 1, a, b);
+                            b
+                        }
+                        _ => {
+                            self.assembler.emit_packed_imm(cmp, 2, a, b);
+                            b
+                        }
+                    }
+                }
+                Operator::I16x8Q15MulrSatS => {
+                    // Only `0x8000 * 0x8000` overflows, to `0x8000` instead of `0x7fff`
+                    self.assembler.emit_packed(PackedOp::Pmulhrsw, b, a);
+                    self.emit_xmm_splat_const(Size::S16, 0x8000, tmp);
+                    self.assembler.emit_packed(PackedOp::Pcmpeqw, a, tmp);
+                    self.assembler.emit_packed(PackedOp::Pxor, tmp, a);
+                    a
+                }
+                Operator::I16x8ExtMulLowI8x16S
+                | Operator::I16x8ExtMulHighI8x16S
+                | Operator::I16x8ExtMulLowI8x16U
+                | Operator::I16x8ExtMulHighI8x16U => {
+                    let (extend, high) = match op {
+                        Operator::I16x8ExtMulLowI8x16S => (PackedOp::Pmovsxbw, false),
+                        Operator::I16x8ExtMulHighI8x16S => (PackedOp::Pmovsxbw, true),
+                        Operator::I16x8ExtMulLowI8x16U => (PackedOp::Pmovzxbw, false),
+                        _ => (PackedOp::Pmovzxbw, true),
+                    };
+                    if high {
+                        self.assembler.emit_packed_shift(PackedShift::Psrldq, 8, a);
+                        self.assembler.emit_packed_shift(PackedShift::Psrldq, 8, b);
+                    }
+                    self.assembler.emit_packed(extend, a, a);
+                    self.assembler.emit_packed(extend, b, b);
+                    self.assembler.emit_packed(PackedOp::Pmullw, b, a);
+                    a
+                }
+                Operator::I32x4ExtMulLowI16x8S
+                | Operator::I32x4ExtMulHighI16x8S
+                | Operator::I32x4ExtMulLowI16x8U
+                | Operator::I32x4ExtMulHighI16x8U => {
+                    // The low and high halves of the 32 bits products are interleaved
+                    let (mulh, unpack) = match op {
+                        Operator::I32x4ExtMulLowI16x8S => (PackedOp::Pmulhw, PackedOp::Punpcklwd),
+                        Operator::I32x4ExtMulHighI16x8S => {
+                            (PackedOp::Pmulhw, PackedOp::Punpckhwd)
+                        }
+                        Operator::I32x4ExtMulLowI16x8U => {
+                            (PackedOp::Pmulhuw, PackedOp::Punpcklwd)
+                        }
+                        _ => (PackedOp::Pmulhuw, PackedOp::Punpckhwd),
+                    };
+                    self.assembler
+                        .emit_vmovaps(XMMOrMemory::XMM(a), XMMOrMemory::XMM(tmp));
+                    self.assembler.emit_packed(PackedOp::Pmullw, b, tmp);
+                    self.assembler.emit_packed(mulh, b, a);
+                    self.assembler.emit_packed(unpack, a, tmp);
+                    tmp
+                }
+                Operator::I64x2ExtMulLowI32x4S
+                | Operator::I64x2ExtMulHighI32x4S
+                | Operator::I64x2ExtMulLowI32x4U
+                | Operator::I64x2ExtMulHighI32x4U => {
+                    // The multiplied lanes are moved to the low halves of the 64 bits lanes
+                    let (mul, lanes) = match op {
+                        Operator::I64x2ExtMulLowI32x4S => (PackedOp::Pmuldq, 0x50),
+                        Operator::I64x2ExtMulHighI32x4S => (PackedOp::Pmuldq, 0xfa),
+                        Operator::I64x2ExtMulLowI32x4U => (PackedOp::Pmuludq, 0x50),
+                        _ => (PackedOp::Pmuludq, 0xfa),
+                    };
+                    self.assembler
+                        .emit_packed_imm(PackedImmOp::Pshufd, lanes, a, a);
+                    self.assembler
+                        .emit_packed_imm(PackedImmOp::Pshufd, lanes, b, b);
+                    self.assembler.emit_packed(mul, b, a);
+                    a
+                }
+                Operator::I64x2Mul => {
+                    // a * b = lo(a) * lo(b) + ((hi(a) * lo(b) + lo(a) * hi(b)) << 32)
+                    self.assembler
+                        .emit_vmovaps(XMMOrMemory::XMM(a), XMMOrMemory::XMM(tmp));
+                    self.assembler.emit_packed_shift(PackedShift::Psrlq, 32, tmp);
+                    self.assembler.emit_packed(PackedOp::Pmuludq, b, tmp);
+                    self.assembler.emit_packed_shift(PackedShift::Psrlq, 32, b);
+                    self.assembler.emit_packed(PackedOp::Pmuludq, a, b);
+                    self.assembler.emit_packed(PackedOp::Paddq, b, tmp);
+                    self.assembler.emit_packed_shift(PackedShift::Psllq, 32, tmp);
+                    self.v128_to_xmm(loc_b, b);
+                    self.assembler.emit_packed(PackedOp::Pmuludq, b, a);
+                    self.assembler.emit_packed(PackedOp::Paddq, tmp, a);
+                    a
+                }
+                Operator::F32x4Min | Operator::F32x4RelaxedMin => {
+                    self.emit_xmm_fmin(true, a, b, tmp);
+                    b
+                }
+                Operator::F64x2Min | Operator::F64x2RelaxedMin => {
+                    self.emit_xmm_fmin(false, a, b, tmp);
+                    b
+                }
+                Operator::F32x4Max | Operator::F32x4RelaxedMax => {
+                    self.emit_xmm_fmax(true, a, b, tmp);
+                    b
+                }
+                Operator::F64x2Max | Operator::F64x2RelaxedMax => {
+                    self.emit_xmm_fmax(false, a, b, tmp);
+                    b
+                }
+                _ => unreachable!("{:?} is not a v128 binary operator", op),
+            }
+        };
+        self.xmm_to_v128(res, ret);
+        self.release_simd(tmp);
+        self.release_simd(b);
+        self.release_simd(a);
+    }
+    fn v128_ternop(
+        &mut self,
+        op: &Operator,
+        loc_a: Location,
+        loc_b: Location,
+        loc_c: Location,
+        ret: Location,
+    ) {
+        let a = self.acquire_temp_simd().unwrap();
+        let b = self.acquire_temp_simd().unwrap();
+        let c = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(loc_a, a);
+        self.v128_to_xmm(loc_b, b);
+        self.v128_to_xmm(loc_c, c);
+        let res = match op {
+            Operator::V128Bitselect
+            | Operator::I8x16LaneSelect
+            | Operator::I16x8LaneSelect
+            | Operator::I32x4LaneSelect
+            | Operator::I64x2LaneSelect => {
+                // ((a ^ b) & c) ^ b
+                self.assembler.emit_packed(PackedOp::Pxor, b, a);
+                self.assembler.emit_packed(PackedOp::Pand, c, a);
+                self.assembler.emit_packed(PackedOp::Pxor, b, a);
+                a
+            }
+            Operator::F32x4Fma => {
+                self.assembler.emit_packed(PackedOp::Mulps, b, a);
+                self.assembler.emit_packed(PackedOp::Addps, c, a);
+                a
+            }
+            Operator::F64x2Fma => {
+                self.assembler.emit_packed(PackedOp::Mulpd, b, a);
+                self.assembler.emit_packed(PackedOp::Addpd, c, a);
+                a
+            }
+            Operator::F32x4Fms => {
+                self.assembler.emit_packed(PackedOp::Mulps, b, a);
+                self.assembler.emit_packed(PackedOp::Subps, a, c);
+                c
+            }
+            Operator::F64x2Fms => {
+                self.assembler.emit_packed(PackedOp::Mulpd, b, a);
+                self.assembler.emit_packed(PackedOp::Subpd, a, c);
+                c
+            }
+            _ => unreachable!("{:?} is not a v128 ternary operator", op),
+        };
+        self.xmm_to_v128(res, ret);
+        self.release_simd(c);
+        self.release_simd(b);
+        self.release_simd(a);
+    }
+    fn v128_canonicalize_nan(&mut self, sz: Size, loc: Location) {
+        let (cmp, nan) = match sz {
+            Size::S32 => (PackedImmOp::Cmpps, 0x7fc0_0000),
+            Size::S64 => (PackedImmOp::Cmppd, 0x7ff8_0000_0000_0000),
+            _ => unreachable!(),
+        };
+        let value = self.acquire_temp_simd().unwrap();
+        let mask = self.acquire_temp_simd().unwrap();
+        let canonical = self.acquire_temp_simd().unwrap();
+        self.v128_to_xmm(loc, value);
+        // unordered
+        self.assembler
+            .emit_vmovaps(XMMOrMemory::XMM(value), XMMOrMemory::XMM(mask));
+        self.assembler.emit_packed_imm(cmp, 3, mask, mask);
+        self.emit_xmm_splat_const(sz, nan, canonical);
+        self.assembler.emit_packed(PackedOp::Pand, mask, canonical);
+        self.assembler.emit_packed(PackedOp::Pandn, value, mask);
+        self.assembler.emit_packed(PackedOp::Por, canonical, mask);
+        self.xmm_to_v128(mask, loc);
+        self.release_simd(canonical);
+        self.release_simd(mask);
+        self.release_simd(value);
+    }
     fn gen_std_trampoline(
         &self,
         sig: &FunctionType,
@@ -6757,9 +8071,21 @@ impl Machine for MachineX86_64 {
         // the cpu feature here is irrelevant
         let mut a = AssemblerX64::new(0, None);
 
+        // The offsets in args_rets of the params: `v128` are passed in two slots,
+        // for their low and high halves.
+        let param_offsets: Vec<usize> = sig
+            .params()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, ty)| match ty {
+                Type::V128 => vec![i * 16, i * 16 + 8],
+                _ => vec![i * 16],
+            })
+            .collect();
+
         // Calculate stack offset.
         let mut stack_offset: u32 = 0;
-        for (i, _param) in sig.params().iter().enumerate() {
+        for i in 0..param_offsets.len() {
             if let Location::Memory(_, _) =
                 self.get_simple_param_location(1 + i, calling_convention)
             {
@@ -6803,8 +8129,8 @@ impl Machine for MachineX86_64 {
         // `callee_vmctx` is already in the first argument register, so no need to move.
         {
             let mut n_stack_args: usize = 0;
-            for (i, _param) in sig.params().iter().enumerate() {
-                let src_loc = Location::Memory(GPR::R14, (i * 16) as _); // args_rets[i]
+            for (i, offset) in param_offsets.iter().enumerate() {
+                let src_loc = Location::Memory(GPR::R14, *offset as _); // args_rets[i]
                 let dst_loc = self.get_simple_param_location(1 + i, calling_convention);
 
                 match dst_loc {
@@ -6847,6 +8173,13 @@ impl Machine for MachineX86_64 {
                 Location::GPR(GPR::RAX),
                 Location::Memory(GPR::R14, 0),
             );
+            if sig.results()[0] == Type::V128 {
+                a.emit_mov(
+                    Size::S64,
+                    Location::GPR(GPR::RDX),
+                    Location::Memory(GPR::R14, 8),
+                );
+            }
         }
 
         // Restore callee-saved registers.
@@ -6890,34 +8223,44 @@ impl Machine for MachineX86_64 {
             let mut stack_param_count: usize = 0;
 
             for (i, ty) in sig.params().iter().enumerate() {
-                let source_loc = match argalloc.next(*ty, calling_convention) {
-                    Some(X64Register::GPR(gpr)) => Location::GPR(gpr),
-                    Some(X64Register::XMM(xmm)) => Location::SIMD(xmm),
-                    None => {
-                        a.emit_mov(
-                            Size::S64,
-                            Location::Memory(
-                                GPR::RSP,
-                                (stack_padding * 2 + stack_offset + 8 + stack_param_count * 8) as _,
-                            ),
-                            Location::GPR(GPR::RAX),
-                        );
-                        stack_param_count += 1;
-                        Location::GPR(GPR::RAX)
-                    }
+                // `v128` params are passed in two slots, for their low and high halves.
+                let (halves, ty) = match ty {
+                    Type::V128 => (2, Type::I64),
+                    _ => (1, *ty),
                 };
-                a.emit_mov(
-                    Size::S64,
-                    source_loc,
-                    Location::Memory(GPR::RSP, (stack_padding + i * 16) as _),
-                );
+                for half in 0..halves {
+                    let source_loc = match argalloc.next(ty, calling_convention) {
+                        Some(X64Register::GPR(gpr)) => Location::GPR(gpr),
+                        Some(X64Register::XMM(xmm)) => Location::SIMD(xmm),
+                        None => {
+                            a.emit_mov(
+                                Size::S64,
+                                Location::Memory(
+                                    GPR::RSP,
+                                    (stack_padding * 2 + stack_offset + 8 + stack_param_count * 8)
+                                        as _,
+                                ),
+                                Location::GPR(GPR::RAX),
+                            );
+                            stack_param_count += 1;
+                            Location::GPR(GPR::RAX)
+                        }
+                    };
+                    a.emit_mov(
+                        Size::S64,
+                        source_loc,
+                        Location::Memory(GPR::RSP, (stack_padding + i * 16 + half * 8) as _),
+                    );
+                }
 
-                // Zero upper 64 bits.
-                a.emit_mov(
-                    Size::S64,
-                    Location::Imm32(0),
-                    Location::Memory(GPR::RSP, (stack_padding + i * 16 + 8) as _),
-                );
+                if halves == 1 {
+                    // Zero upper 64 bits.
+                    a.emit_mov(
+                        Size::S64,
+                        Location::Imm32(0),
+                        Location::Memory(GPR::RSP, (stack_padding + i * 16 + 8) as _),
+                    );
+                }
             }
         }
 
@@ -6965,6 +8308,13 @@ impl Machine for MachineX86_64 {
                 Location::Memory(GPR::RSP, stack_padding as i32),
                 Location::GPR(GPR::RAX),
             );
+            if sig.results()[0] == Type::V128 {
+                a.emit_mov(
+                    Size::S64,
+                    Location::Memory(GPR::RSP, stack_padding as i32 + 8),
+                    Location::GPR(GPR::RDX),
+                );
+            }
         }
 
         // Release values array.
@@ -6995,6 +8345,16 @@ impl Machine for MachineX86_64 {
 
         // TODO: ARM entry trampoline is not emitted.
 
+        // `v128` params are passed as two integers, for their low and high halves.
+        let params: Vec<Type> = sig
+            .params()
+            .iter()
+            .flat_map(|ty| match ty {
+                Type::V128 => vec![Type::I64, Type::I64],
+                _ => vec![*ty],
+            })
+            .collect();
+
         // Singlepass internally treats all arguments as integers
         // For the standard Windows calling convention requires
         //  floating point arguments to be passed in XMM registers for the 4 first arguments only
@@ -7002,15 +8362,12 @@ impl Machine for MachineX86_64 {
         // For the standard System V calling convention requires
         //  floating point arguments to be passed in XMM registers.
         //  Translation is expensive, so only do it if needed.
-        if sig
-            .params()
-            .iter()
-            .any(|&x| x == Type::F32 || x == Type::F64)
+        if params.iter().any(|&x| x == Type::F32 || x == Type::F64)
         {
             match calling_convention {
                 CallingConvention::WindowsFastcall => {
                     let mut param_locations: Vec<Location> = vec![];
-                    for i in 0..sig.params().len() {
+                    for i in 0..params.len() {
                         let loc = match i {
                             0..=2 => {
                                 static PARAM_REGS: &[GPR] = &[GPR::RDX, GPR::R8, GPR::R9];
@@ -7022,7 +8379,7 @@ impl Machine for MachineX86_64 {
                     }
                     // Copy Float arguments to XMM from GPR.
                     let mut argalloc = ArgumentRegisterAllocator::default();
-                    for (i, ty) in sig.params().iter().enumerate() {
+                    for (i, ty) in params.iter().enumerate() {
                         let prev_loc = param_locations[i];
                         match argalloc.next(*ty, calling_convention) {
                             Some(X64Register::GPR(_gpr)) => continue,
@@ -7037,10 +8394,10 @@ impl Machine for MachineX86_64 {
                     let mut param_locations: Vec<Location> = vec![];
 
                     // Allocate stack space for arguments.
-                    let stack_offset: i32 = if sig.params().len() > 5 {
+                    let stack_offset: i32 = if params.len() > 5 {
                         5 * 8
                     } else {
-                        (sig.params().len() as i32) * 8
+                        (params.len() as i32) * 8
                     };
                     if stack_offset > 0 {
                         a.emit_sub(
@@ -7051,7 +8408,7 @@ impl Machine for MachineX86_64 {
                     }
 
                     // Store all arguments to the stack to prevent overwrite.
-                    for i in 0..sig.params().len() {
+                    for i in 0..params.len() {
                         let loc = match i {
                             0..=4 => {
                                 static PARAM_REGS: &[GPR] =
@@ -7071,7 +8428,7 @@ impl Machine for MachineX86_64 {
                     let mut argalloc = ArgumentRegisterAllocator::default();
                     argalloc.next(Type::I64, calling_convention).unwrap(); // skip VMContext
                     let mut caller_stack_offset: i32 = 0;
-                    for (i, ty) in sig.params().iter().enumerate() {
+                    for (i, ty) in params.iter().enumerate() {
                         let prev_loc = param_locations[i];
                         let targ = match argalloc.next(*ty, calling_convention) {
                             Some(X64Register::GPR(gpr)) => Location::GPR(gpr),
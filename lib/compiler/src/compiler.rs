@@ -56,6 +56,17 @@ pub trait CompilerConfig {
         // in case they create an IR that they can verify.
     }
 
+    /// Enable the interrupt checks.
+    ///
+    /// The compiled code then checks for interruptions when it enters a
+    /// function and at the start of every loop iteration. Code compiled
+    /// without them can't be stopped by an `InterruptHandle` or an
+    /// epoch deadline. This is `false` by default.
+    fn interruptible(&mut self, _enable: bool) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they support interrupt checks.
+    }
+
     /// Gets the custom compiler config
     fn compiler(self: Box<Self>) -> Box<dyn Compiler>;
 
@@ -86,7 +86,7 @@ pub use crate::target::{
 pub use crate::translator::{
     translate_module, wptype_to_type, FunctionBinaryReader, FunctionBodyData, FunctionMiddleware,
     MiddlewareBinaryReader, MiddlewareReaderState, ModuleEnvironment, ModuleMiddleware,
-    ModuleMiddlewareChain, ModuleTranslationState,
+    ModuleMiddlewareChain, ModuleRewriter, ModuleTranslationState,
 };
 pub use crate::trap::TrapInformation;
 pub use crate::unwind::CompiledFunctionUnwindInfo;
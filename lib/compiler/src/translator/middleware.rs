@@ -6,11 +6,16 @@ use smallvec::SmallVec;
 use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::ops::Deref;
-use wasmer_types::{LocalFunctionIndex, ModuleInfo};
+use wasmer_types::entity::{EntityRef, PrimaryMap};
+use wasmer_types::{
+    ExportIndex, FunctionIndex, FunctionType, GlobalInit, ImportIndex, LocalFunctionIndex,
+    ModuleInfo, SignatureIndex,
+};
 use wasmparser::{BinaryReader, Operator, Range, Type};
 
 use crate::error::{MiddlewareError, WasmResult};
-use crate::translator::environ::FunctionBinaryReader;
+use crate::translator::environ::{FunctionBinaryReader, FunctionBodyData};
+use crate::translator::state::ModuleTranslationState;
 
 /// A shared builder for function middlewares.
 pub trait ModuleMiddleware: Debug + Send + Sync + MemoryUsage {
@@ -26,6 +31,15 @@ pub trait ModuleMiddleware: Debug + Send + Sync + MemoryUsage {
 
     /// Transforms a `ModuleInfo` struct in-place. This is called before application on functions begins.
     fn transform_module_info(&self, _: &mut ModuleInfo) {}
+
+    /// Rewrites the module, possibly adding imports, exports and
+    /// functions to it. This is called before application on functions
+    /// begins.
+    ///
+    /// The default implementation calls `transform_module_info`.
+    fn transform_module(&self, module: &mut ModuleRewriter) {
+        self.transform_module_info(module.module_info_mut());
+    }
 }
 
 /// A function middleware specialized for a single function.
@@ -59,35 +73,422 @@ pub struct MiddlewareReaderState<'a> {
 
     /// The pending operations added by the middleware.
     pending_operations: VecDeque<Operator<'a>>,
+
+    /// The offset in the module of the raw operator being fed.
+    operator_offset: usize,
+}
+
+/// A view on the module being transformed by a `ModuleMiddleware`,
+/// allowing to add imports, exports and functions to it.
+///
+/// Adding a function import shifts the index of every local function.
+/// The references held by the `ModuleInfo` are remapped by the
+/// rewriter, and the references in the function bodies are remapped
+/// before the operators reach the `FunctionMiddleware` of the
+/// middleware that added the import. Function indexes are thus
+/// always expressed in the index space of the module as transformed
+/// by the current middleware and all the previous ones.
+#[derive(Debug)]
+pub struct ModuleRewriter<'a> {
+    module_info: &'a mut ModuleInfo,
+    module_translation_state: &'a mut ModuleTranslationState,
+    middleware_index: usize,
+}
+
+/// The changes made to the module by a middleware chain through
+/// `ModuleRewriter`s.
+#[derive(Debug, Default)]
+pub(crate) struct MiddlewareRewrites {
+    /// The function imports added by each middleware, with their index
+    /// at the time they were added.
+    function_imports: Vec<Vec<FunctionIndex>>,
+
+    /// The functions added by the middlewares, with the index of the
+    /// middleware that added them and their encoded body.
+    functions: Vec<(LocalFunctionIndex, usize, Vec<u8>)>,
+}
+
+impl<'a> ModuleRewriter<'a> {
+    /// The module being transformed.
+    pub fn module_info(&self) -> &ModuleInfo {
+        self.module_info
+    }
+
+    /// The module being transformed, for changes that do not need any
+    /// remapping, such as adding globals.
+    ///
+    /// Function imports and functions must be added with
+    /// `push_function_import` and `push_function` instead.
+    pub fn module_info_mut(&mut self) -> &mut ModuleInfo {
+        self.module_info
+    }
+
+    /// Adds a signature to the module, or returns the index of an
+    /// identical one.
+    ///
+    /// The returned index can also be used as the type of a
+    /// multi-value block in the function bodies.
+    pub fn push_signature(&mut self, ty: FunctionType) -> SignatureIndex {
+        if let Some((index, _)) = self
+            .module_info
+            .signatures
+            .iter()
+            .find(|(_, signature)| **signature == ty)
+        {
+            return index;
+        }
+
+        // Keep the raw signatures used to translate blocks in sync,
+        // in case signatures were pushed directly to the `ModuleInfo`.
+        let wasm_types = &mut self.module_translation_state.wasm_types;
+        for (_, signature) in self.module_info.signatures.iter().skip(wasm_types.len()) {
+            wasm_types.push(wasm_signature(signature));
+        }
+        wasm_types.push(wasm_signature(&ty));
+
+        self.module_info.signatures.push(ty)
+    }
+
+    /// Adds an imported function to the module, and returns its index.
+    ///
+    /// The new import comes after all the existing function imports,
+    /// so every local function, including the ones added with
+    /// `push_function`, is shifted up by one.
+    pub fn push_function_import(
+        &mut self,
+        module: &str,
+        field: &str,
+        ty: FunctionType,
+    ) -> FunctionIndex {
+        let signature_index = self.push_signature(ty);
+        let module_info = &mut *self.module_info;
+        let new_index = FunctionIndex::new(module_info.num_imported_functions);
+        let remap = |index: FunctionIndex| remap_function_index(new_index, index);
+
+        let mut functions = PrimaryMap::with_capacity(module_info.functions.len() + 1);
+        for (index, signature) in module_info.functions.iter() {
+            if index == new_index {
+                functions.push(signature_index);
+            }
+            functions.push(*signature);
+        }
+        if functions.len() == module_info.functions.len() {
+            functions.push(signature_index);
+        }
+        module_info.functions = functions;
+
+        for export in module_info.exports.values_mut() {
+            if let ExportIndex::Function(index) = export {
+                *index = remap(*index);
+            }
+        }
+
+        if let Some(start_function) = module_info.start_function.as_mut() {
+            *start_function = remap(*start_function);
+        }
+
+        for initializer in module_info.table_initializers.iter_mut() {
+            for element in initializer.elements.iter_mut() {
+                *element = remap(*element);
+            }
+        }
+
+        for elements in module_info.passive_elements.values_mut() {
+            for element in elements.iter_mut() {
+                *element = remap(*element);
+            }
+        }
+
+        for (_, initializer) in module_info.global_initializers.iter_mut() {
+            if let GlobalInit::RefFunc(index) = initializer {
+                *index = remap(*index);
+            }
+        }
+
+        module_info.function_names = module_info
+            .function_names
+            .drain()
+            .map(|(index, name)| (remap(index), name))
+            .collect();
+
+        let import_index = module_info.imports.len() as u32;
+        module_info.imports.insert(
+            (module.to_string(), field.to_string(), import_index),
+            ImportIndex::Function(new_index),
+        );
+        module_info.num_imported_functions += 1;
+
+        self.module_translation_state
+            .middleware_rewrites
+            .function_imports[self.middleware_index]
+            .push(new_index);
+
+        new_index
+    }
+
+    /// Adds a local function to the module, with the given local
+    /// variables, and returns its index.
+    ///
+    /// The body of the new function only holds the final `end`
+    /// operator: the `FunctionMiddleware` generated by the current
+    /// middleware for this function must feed the actual body before
+    /// it. The middlewares before the current one in the chain are not
+    /// applied on this function.
+    ///
+    /// The `FunctionIndex` of the new function is given by
+    /// `ModuleInfo::func_index`, once all the function imports have
+    /// been added.
+    pub fn push_function(
+        &mut self,
+        ty: FunctionType,
+        locals: &[(u32, wasmer_types::Type)],
+    ) -> LocalFunctionIndex {
+        let signature_index = self.push_signature(ty);
+        let function_index = self.module_info.functions.push(signature_index);
+        let local_function_index = self
+            .module_info
+            .local_func_index(function_index)
+            .expect("a pushed function is always local");
+
+        let mut body = vec![];
+        write_var_u32(&mut body, locals.len() as u32);
+        for (count, ty) in locals {
+            write_var_u32(&mut body, *count);
+            body.push(type_code(*ty));
+        }
+        body.push(0x0b); // end
+
+        self.module_translation_state
+            .middleware_rewrites
+            .functions
+            .push((local_function_index, self.middleware_index, body));
+
+        local_function_index
+    }
+
+    /// Adds an export to the module. Returns the export previously
+    /// registered under the same name, if any.
+    pub fn push_export(&mut self, name: &str, index: ExportIndex) -> Option<ExportIndex> {
+        self.module_info.exports.insert(name.to_string(), index)
+    }
+}
+
+/// A function middleware remapping the function indexes after the
+/// imports added by its module middleware, before feeding the
+/// operators to it.
+#[derive(Debug)]
+struct FunctionIndexRemapper {
+    function_imports: Vec<FunctionIndex>,
+    inner: Box<dyn FunctionMiddleware>,
+}
+
+impl FunctionMiddleware for FunctionIndexRemapper {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        let remap = |function_index: u32| {
+            self.function_imports
+                .iter()
+                .fold(FunctionIndex::from_u32(function_index), |index, import| {
+                    remap_function_index(*import, index)
+                })
+                .as_u32()
+        };
+
+        let operator = match operator {
+            Operator::Call { function_index } => Operator::Call {
+                function_index: remap(function_index),
+            },
+            Operator::ReturnCall { function_index } => Operator::ReturnCall {
+                function_index: remap(function_index),
+            },
+            Operator::RefFunc { function_index } => Operator::RefFunc {
+                function_index: remap(function_index),
+            },
+            operator => operator,
+        };
+
+        self.inner.feed(operator, state)
+    }
+}
+
+/// Returns the new index of `index` once a function import has been
+/// inserted at `inserted`.
+fn remap_function_index(inserted: FunctionIndex, index: FunctionIndex) -> FunctionIndex {
+    if index.index() >= inserted.index() {
+        FunctionIndex::new(index.index() + 1)
+    } else {
+        index
+    }
+}
+
+fn wasm_signature(ty: &FunctionType) -> (Box<[Type]>, Box<[Type]>) {
+    let wasm_types = |types: &[wasmer_types::Type]| {
+        types
+            .iter()
+            .map(|ty| match ty {
+                wasmer_types::Type::I32 => Type::I32,
+                wasmer_types::Type::I64 => Type::I64,
+                wasmer_types::Type::F32 => Type::F32,
+                wasmer_types::Type::F64 => Type::F64,
+                wasmer_types::Type::V128 => Type::V128,
+                wasmer_types::Type::ExternRef => Type::ExternRef,
+                wasmer_types::Type::FuncRef => Type::FuncRef,
+            })
+            .collect()
+    };
+
+    (wasm_types(ty.params()), wasm_types(ty.results()))
+}
+
+fn type_code(ty: wasmer_types::Type) -> u8 {
+    match ty {
+        wasmer_types::Type::I32 => 0x7f,
+        wasmer_types::Type::I64 => 0x7e,
+        wasmer_types::Type::F32 => 0x7d,
+        wasmer_types::Type::F64 => 0x7c,
+        wasmer_types::Type::V128 => 0x7b,
+        wasmer_types::Type::FuncRef => 0x70,
+        wasmer_types::Type::ExternRef => 0x6f,
+    }
+}
+
+fn write_var_u32(bytes: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
 }
 
 /// Trait for generating middleware chains from "prototype" (generator) chains.
 pub trait ModuleMiddlewareChain {
     /// Generates a function middleware chain.
+    ///
+    /// This doesn't remap the function indexes shifted by the imports
+    /// added by the middlewares, nor knows about the functions they
+    /// added.
+    #[deprecated(
+        since = "2.3.0",
+        note = "use `generate_function_middleware_chain_with_state` instead"
+    )]
     fn generate_function_middleware_chain(
         &self,
         local_function_index: LocalFunctionIndex,
-    ) -> Vec<Box<dyn FunctionMiddleware>>;
+    ) -> Vec<Box<dyn FunctionMiddleware>> {
+        self.generate_function_middleware_chain_with_state(
+            local_function_index,
+            &ModuleTranslationState::new(),
+        )
+    }
 
     /// Applies the chain on a `ModuleInfo` struct.
-    fn apply_on_module_info(&self, module_info: &mut ModuleInfo);
+    ///
+    /// The bodies of the functions added by the middlewares are
+    /// dropped, so this is only correct for chains that don't add
+    /// functions or function imports.
+    #[deprecated(
+        since = "2.3.0",
+        note = "use `apply_on_module_info_with_state` instead"
+    )]
+    fn apply_on_module_info(&self, module_info: &mut ModuleInfo) {
+        self.apply_on_module_info_with_state(module_info, &mut ModuleTranslationState::new())
+    }
+
+    /// Generates a function middleware chain.
+    ///
+    /// `module_translation_state` must have been passed to
+    /// `apply_on_module_info_with_state` beforehand.
+    fn generate_function_middleware_chain_with_state(
+        &self,
+        local_function_index: LocalFunctionIndex,
+        module_translation_state: &ModuleTranslationState,
+    ) -> Vec<Box<dyn FunctionMiddleware>>;
+
+    /// Applies the chain on a `ModuleInfo` struct, recording the
+    /// changes needed to generate the function middleware chains in
+    /// `module_translation_state`.
+    fn apply_on_module_info_with_state(
+        &self,
+        module_info: &mut ModuleInfo,
+        module_translation_state: &mut ModuleTranslationState,
+    );
 }
 
 impl<T: Deref<Target = dyn ModuleMiddleware>> ModuleMiddlewareChain for [T] {
     /// Generates a function middleware chain.
-    fn generate_function_middleware_chain(
+    fn generate_function_middleware_chain_with_state(
         &self,
         local_function_index: LocalFunctionIndex,
+        module_translation_state: &ModuleTranslationState,
     ) -> Vec<Box<dyn FunctionMiddleware>> {
+        let rewrites = &module_translation_state.middleware_rewrites;
+
+        // Functions added by a middleware are only seen by that
+        // middleware and the following ones.
+        let first = rewrites
+            .functions
+            .iter()
+            .find(|(index, _, _)| *index == local_function_index)
+            .map_or(0, |(_, middleware_index, _)| *middleware_index);
+
         self.iter()
-            .map(|x| x.generate_function_middleware(local_function_index))
+            .enumerate()
+            .skip(first)
+            .map(|(middleware_index, x)| {
+                let inner = x.generate_function_middleware(local_function_index);
+                match rewrites.function_imports.get(middleware_index) {
+                    Some(function_imports) if !function_imports.is_empty() => {
+                        Box::new(FunctionIndexRemapper {
+                            function_imports: function_imports.clone(),
+                            inner,
+                        })
+                    }
+                    _ => inner,
+                }
+            })
             .collect()
     }
 
     /// Applies the chain on a `ModuleInfo` struct.
-    fn apply_on_module_info(&self, module_info: &mut ModuleInfo) {
-        for item in self {
-            item.transform_module_info(module_info);
+    fn apply_on_module_info_with_state(
+        &self,
+        module_info: &mut ModuleInfo,
+        module_translation_state: &mut ModuleTranslationState,
+    ) {
+        module_translation_state
+            .middleware_rewrites
+            .function_imports = vec![vec![]; self.len()];
+
+        for (middleware_index, item) in self.iter().enumerate() {
+            item.transform_module(&mut ModuleRewriter {
+                module_info,
+                module_translation_state,
+                middleware_index,
+            });
+        }
+    }
+}
+
+impl ModuleTranslationState {
+    /// Appends the bodies of the functions added by the middlewares to
+    /// `function_body_inputs`.
+    pub fn push_middleware_function_bodies<'a>(
+        &'a self,
+        function_body_inputs: &mut PrimaryMap<LocalFunctionIndex, FunctionBodyData<'a>>,
+    ) {
+        for (local_function_index, _, body) in &self.middleware_rewrites.functions {
+            let index = function_body_inputs.push(FunctionBodyData {
+                data: body,
+                module_offset: 0,
+            });
+            debug_assert_eq!(index, *local_function_index);
         }
     }
 }
@@ -97,6 +498,13 @@ impl<'a> MiddlewareReaderState<'a> {
     pub fn push_operator(&mut self, operator: Operator<'a>) {
         self.pending_operations.push_back(operator);
     }
+
+    /// The offset in the module of the raw operator being fed through
+    /// the chain, i.e. of the operator from which the fed operators
+    /// originate.
+    pub fn operator_offset(&self) -> usize {
+        self.operator_offset
+    }
 }
 
 impl<'a> Extend<Operator<'a>> for MiddlewareReaderState<'a> {
@@ -119,11 +527,18 @@ impl<'a> MiddlewareBinaryReader<'a> {
             state: MiddlewareReaderState {
                 inner,
                 pending_operations: VecDeque::new(),
+                operator_offset: original_offset,
             },
             chain: vec![],
         }
     }
 
+    /// Sets whether memory immediates may hold 64-bit offsets, as they do
+    /// in modules with 64-bit memories.
+    pub fn allow_memarg64(&mut self, allow: bool) {
+        self.state.inner.allow_memarg64(allow);
+    }
+
     /// Replaces the middleware chain with a new one.
     pub fn set_middleware_chain(&mut self, stages: Vec<Box<dyn FunctionMiddleware>>) {
         self.chain = stages;
@@ -149,6 +564,7 @@ impl<'a> FunctionBinaryReader<'a> for MiddlewareBinaryReader<'a> {
 
         // Try to fill the `self.pending_operations` buffer, until it is non-empty.
         while self.state.pending_operations.is_empty() {
+            self.state.operator_offset = self.state.inner.original_position();
             let raw_op = self.state.inner.read_operator()?;
 
             // Fill the initial raw operator into pending buffer.
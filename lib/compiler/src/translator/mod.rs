@@ -16,7 +16,7 @@ mod sections;
 pub use self::environ::{FunctionBinaryReader, FunctionBodyData, ModuleEnvironment};
 pub use self::middleware::{
     FunctionMiddleware, MiddlewareBinaryReader, MiddlewareReaderState, ModuleMiddleware,
-    ModuleMiddlewareChain,
+    ModuleMiddlewareChain, ModuleRewriter,
 };
 pub use self::module::translate_module;
 pub use self::sections::wptype_to_type;
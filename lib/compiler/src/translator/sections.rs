@@ -21,15 +21,17 @@ use std::vec::Vec;
 use wasmer_types::entity::packed_option::ReservedValue;
 use wasmer_types::entity::EntityRef;
 use wasmer_types::{
-    DataIndex, ElemIndex, FunctionIndex, FunctionType, GlobalIndex, GlobalInit, GlobalType,
-    MemoryIndex, MemoryType, Pages, SignatureIndex, TableIndex, TableType, Type, V128,
+    ConstOperator, DataIndex, ElemIndex, FunctionIndex, FunctionType, GlobalIndex, GlobalInit,
+    GlobalType, MemoryIndex, MemoryType, Pages, SignatureIndex, TableIndex, TableType, TagIndex,
+    Type, V128,
 };
 use wasmparser::{
     self, Data, DataKind, DataSectionReader, Element, ElementItem, ElementItems, ElementKind,
     ElementSectionReader, Export, ExportSectionReader, ExternalKind, FuncType as WPFunctionType,
     FunctionSectionReader, GlobalSectionReader, GlobalType as WPGlobalType, ImportSectionEntryType,
-    ImportSectionReader, MemorySectionReader, MemoryType as WPMemoryType, NameSectionReader,
-    Naming, NamingReader, Operator, TableSectionReader, TypeDef, TypeSectionReader,
+    ImportSectionReader, InitExpr, MemorySectionReader, MemoryType as WPMemoryType,
+    NameSectionReader, Naming, NamingReader, Operator, TableSectionReader, TagSectionReader,
+    TypeDef, TypeSectionReader,
 };
 
 /// Helper function translating wasmparser types to Wasm Type.
@@ -49,6 +51,27 @@ pub fn wptype_to_type(ty: wasmparser::Type) -> WasmResult<Type> {
     }
 }
 
+/// Helper function translating wasmparser memory types to Wasm memory types.
+fn wpmemorytype_to_memorytype(ty: WPMemoryType) -> WasmResult<MemoryType> {
+    let WPMemoryType {
+        shared,
+        memory64,
+        initial,
+        maximum,
+    } = ty;
+    let pages = |count: u64| {
+        u32::try_from(count)
+            .map(Pages)
+            .map_err(|_| wasm_unsupported!("memories of {} pages", count))
+    };
+    Ok(MemoryType {
+        minimum: pages(initial)?,
+        maximum: maximum.map(pages).transpose()?,
+        shared,
+        memory64,
+    })
+}
+
 /// Parses the Type section of the wasm module.
 pub fn parse_type_section(
     types: TypeSectionReader,
@@ -59,7 +82,7 @@ pub fn parse_type_section(
     environ.reserve_signatures(count)?;
 
     for entry in types {
-        if let Ok(TypeDef::Func(WPFunctionType { params, returns })) = entry {
+        if let TypeDef::Func(WPFunctionType { params, returns }) = entry? {
             let sig_params: Vec<Type> = params
                 .iter()
                 .map(|ty| {
@@ -78,7 +101,7 @@ pub fn parse_type_section(
             environ.declare_signature(sig)?;
             module_translation_state.wasm_types.push((params, returns));
         } else {
-            unimplemented!("module linking not implemented yet")
+            return Err(wasm_unsupported!("module linking"));
         }
     }
 
@@ -106,26 +129,18 @@ pub fn parse_import_section<'data>(
                 )?;
             }
             ImportSectionEntryType::Module(_) | ImportSectionEntryType::Instance(_) => {
-                unimplemented!("module linking not implemented yet")
+                return Err(wasm_unsupported!("module linking"));
             }
-            ImportSectionEntryType::Tag(_) => {
-                unimplemented!("exception handling not implemented yet")
+            ImportSectionEntryType::Tag(ty) => {
+                environ.declare_tag_import(
+                    SignatureIndex::from_u32(ty.type_index),
+                    module_name,
+                    field_name.unwrap_or_default(),
+                )?;
             }
-            ImportSectionEntryType::Memory(WPMemoryType {
-                shared,
-                memory64,
-                initial,
-                maximum,
-            }) => {
-                if memory64 {
-                    unimplemented!("64bit memory not implemented yet");
-                }
+            ImportSectionEntryType::Memory(ty) => {
                 environ.declare_memory_import(
-                    MemoryType {
-                        minimum: Pages(initial as u32),
-                        maximum: maximum.map(|p| Pages(p as u32)),
-                        shared,
-                    },
+                    wpmemorytype_to_memorytype(ty)?,
                     module_name,
                     field_name.unwrap_or_default(),
                 )?;
@@ -206,20 +221,22 @@ pub fn parse_memory_section(
     environ.reserve_memories(memories.get_count())?;
 
     for entry in memories {
-        let WPMemoryType {
-            shared,
-            memory64,
-            initial,
-            maximum,
-        } = entry?;
-        if memory64 {
-            unimplemented!("64bit memory not implemented yet");
-        }
-        environ.declare_memory(MemoryType {
-            minimum: Pages(initial as u32),
-            maximum: maximum.map(|p| Pages(p as u32)),
-            shared,
-        })?;
+        environ.declare_memory(wpmemorytype_to_memorytype(entry?)?)?;
+    }
+
+    Ok(())
+}
+
+/// Parses the Tag section of the wasm module.
+pub fn parse_tag_section(
+    tags: TagSectionReader,
+    environ: &mut ModuleEnvironment,
+) -> WasmResult<()> {
+    environ.reserve_tags(tags.get_count())?;
+
+    for entry in tags {
+        let ty = entry?;
+        environ.declare_tag(SignatureIndex::from_u32(ty.type_index))?;
     }
 
     Ok(())
@@ -241,24 +258,30 @@ pub fn parse_global_section(
             init_expr,
         } = entry?;
         let mut init_expr_reader = init_expr.get_binary_reader();
-        let initializer = match init_expr_reader.read_operator()? {
-            Operator::I32Const { value } => GlobalInit::I32Const(value),
-            Operator::I64Const { value } => GlobalInit::I64Const(value),
-            Operator::F32Const { value } => GlobalInit::F32Const(f32::from_bits(value.bits())),
-            Operator::F64Const { value } => GlobalInit::F64Const(f64::from_bits(value.bits())),
-            Operator::V128Const { value } => GlobalInit::V128Const(V128::from(*value.bytes())),
-            Operator::RefNull { ty: _ } => GlobalInit::RefNullConst,
-            Operator::RefFunc { function_index } => {
-                GlobalInit::RefFunc(FunctionIndex::from_u32(function_index))
-            }
-            Operator::GlobalGet { global_index } => {
-                GlobalInit::GetGlobal(GlobalIndex::from_u32(global_index))
-            }
-            ref s => {
-                return Err(wasm_unsupported!(
-                    "unsupported init expr in global section: {:?}",
-                    s
-                ));
+        let operator = init_expr_reader.read_operator()?;
+        let initializer = if !matches!(init_expr_reader.read_operator()?, Operator::End) {
+            let ops = read_const_operators(&init_expr, "global")?;
+            GlobalInit::Expr(ops.into_boxed_slice())
+        } else {
+            match operator {
+                Operator::I32Const { value } => GlobalInit::I32Const(value),
+                Operator::I64Const { value } => GlobalInit::I64Const(value),
+                Operator::F32Const { value } => GlobalInit::F32Const(f32::from_bits(value.bits())),
+                Operator::F64Const { value } => GlobalInit::F64Const(f64::from_bits(value.bits())),
+                Operator::V128Const { value } => GlobalInit::V128Const(V128::from(*value.bytes())),
+                Operator::RefNull { ty: _ } => GlobalInit::RefNullConst,
+                Operator::RefFunc { function_index } => {
+                    GlobalInit::RefFunc(FunctionIndex::from_u32(function_index))
+                }
+                Operator::GlobalGet { global_index } => {
+                    GlobalInit::GetGlobal(GlobalIndex::from_u32(global_index))
+                }
+                ref s => {
+                    return Err(wasm_unsupported!(
+                        "unsupported init expr in global section: {:?}",
+                        s
+                    ));
+                }
             }
         };
         let global = GlobalType {
@@ -301,11 +324,9 @@ pub fn parse_export_section<'data>(
                 environ.declare_global_export(GlobalIndex::new(index), field)?
             }
             ExternalKind::Type | ExternalKind::Module | ExternalKind::Instance => {
-                unimplemented!("module linking not implemented yet")
-            }
-            ExternalKind::Tag => {
-                unimplemented!("exception handling not implemented yet")
+                return Err(wasm_unsupported!("module linking"));
             }
+            ExternalKind::Tag => environ.declare_tag_export(TagIndex::new(index), field)?,
         }
     }
 
@@ -319,6 +340,94 @@ pub fn parse_start_section(index: u32, environ: &mut ModuleEnvironment) -> WasmR
     Ok(())
 }
 
+/// Reads the operators of an extended constant expression, up to its `end`.
+fn read_const_operators(init_expr: &InitExpr, section: &str) -> WasmResult<Vec<ConstOperator>> {
+    let mut reader = init_expr.get_operators_reader();
+    let mut ops = Vec::new();
+    loop {
+        let op = match reader.read()? {
+            Operator::End => return Ok(ops),
+            Operator::I32Const { value } => ConstOperator::I32Const(value),
+            Operator::I64Const { value } => ConstOperator::I64Const(value),
+            Operator::GlobalGet { global_index } => {
+                ConstOperator::GetGlobal(GlobalIndex::from_u32(global_index))
+            }
+            Operator::I32Add => ConstOperator::I32Add,
+            Operator::I32Sub => ConstOperator::I32Sub,
+            Operator::I32Mul => ConstOperator::I32Mul,
+            Operator::I64Add => ConstOperator::I64Add,
+            Operator::I64Sub => ConstOperator::I64Sub,
+            Operator::I64Mul => ConstOperator::I64Mul,
+            ref s => {
+                return Err(wasm_unsupported!(
+                    "unsupported init expr in {} section: {:?}",
+                    section,
+                    s
+                ));
+            }
+        };
+        ops.push(op);
+    }
+}
+
+/// Parses the offset of an element or data segment into an optional
+/// global base, plus a constant offset.
+///
+/// Extended constant expressions are folded into this form, as long as
+/// they add or subtract constants to at most one global.
+fn parse_offset_expr(
+    init_expr: &InitExpr,
+    section: &str,
+) -> WasmResult<(Option<GlobalIndex>, usize)> {
+    let ops = read_const_operators(init_expr, section)?;
+    let mut stack: Vec<(Option<GlobalIndex>, u64)> = Vec::with_capacity(ops.len());
+    let mut is_64 = false;
+    for op in &ops {
+        let value = match *op {
+            ConstOperator::I32Const(value) => (None, u64::from(value as u32)),
+            ConstOperator::I64Const(value) => {
+                is_64 = true;
+                (None, value as u64)
+            }
+            ConstOperator::GetGlobal(index) => (Some(index), 0),
+            binop => {
+                let (b_base, b) = stack.pop().unwrap();
+                let (a_base, a) = stack.pop().unwrap();
+                match (binop, a_base, b_base) {
+                    (ConstOperator::I32Add | ConstOperator::I64Add, base, None)
+                    | (ConstOperator::I32Add | ConstOperator::I64Add, None, base) => {
+                        (base, a.wrapping_add(b))
+                    }
+                    (ConstOperator::I32Sub | ConstOperator::I64Sub, base, None) => {
+                        (base, a.wrapping_sub(b))
+                    }
+                    (ConstOperator::I32Mul | ConstOperator::I64Mul, None, None) => {
+                        (None, a.wrapping_mul(b))
+                    }
+                    _ => {
+                        return Err(wasm_unsupported!(
+                            "unsupported offset expression in {} section: {:?}",
+                            section,
+                            ops
+                        ));
+                    }
+                }
+            }
+        };
+        stack.push(value);
+    }
+    let (base, offset) = stack.pop().unwrap();
+    // The arithmetic of `i32` offsets wraps around at 32 bits.
+    let offset = if is_64 {
+        offset
+    } else {
+        u64::from(offset as u32)
+    };
+    let offset = usize::try_from(offset)
+        .map_err(|_| wasm_unsupported!("{} segment offset {} out of range", section, offset))?;
+    Ok((base, offset))
+}
+
 fn read_elems(items: &ElementItems) -> WasmResult<Box<[FunctionIndex]>> {
     let items_reader = items.get_items_reader()?;
     let mut elems = Vec::with_capacity(usize::try_from(items_reader.get_count()).unwrap());
@@ -367,19 +476,7 @@ pub fn parse_element_section<'data>(
                 table_index,
                 init_expr,
             } => {
-                let mut init_expr_reader = init_expr.get_binary_reader();
-                let (base, offset) = match init_expr_reader.read_operator()? {
-                    Operator::I32Const { value } => (None, value as u32 as usize),
-                    Operator::GlobalGet { global_index } => {
-                        (Some(GlobalIndex::from_u32(global_index)), 0)
-                    }
-                    ref s => {
-                        return Err(wasm_unsupported!(
-                            "unsupported init expr in element section: {:?}",
-                            s
-                        ));
-                    }
-                };
+                let (base, offset) = parse_offset_expr(&init_expr, "element")?;
                 environ.declare_table_initializers(
                     TableIndex::from_u32(table_index),
                     base,
@@ -415,19 +512,7 @@ pub fn parse_data_section<'data>(
                 memory_index,
                 init_expr,
             } => {
-                let mut init_expr_reader = init_expr.get_binary_reader();
-                let (base, offset) = match init_expr_reader.read_operator()? {
-                    Operator::I32Const { value } => (None, value as u32 as usize),
-                    Operator::GlobalGet { global_index } => {
-                        (Some(GlobalIndex::from_u32(global_index)), 0)
-                    }
-                    ref s => {
-                        return Err(wasm_unsupported!(
-                            "unsupported init expr in data section: {:?}",
-                            s
-                        ))
-                    }
-                };
+                let (base, offset) = parse_offset_expr(&init_expr, "data")?;
                 environ.declare_data_initialization(
                     MemoryIndex::from_u32(memory_index),
                     base,
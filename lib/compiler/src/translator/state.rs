@@ -1,6 +1,7 @@
 // This file contains code from external sources.
 // Attributions: https://github.com/wasmerio/wasmer/blob/master/ATTRIBUTIONS.md
 
+use crate::translator::middleware::MiddlewareRewrites;
 use crate::{wasm_unsupported, WasmResult};
 use std::boxed::Box;
 use wasmer_types::entity::PrimaryMap;
@@ -23,6 +24,11 @@ pub struct ModuleTranslationState {
     /// This is used for translating multi-value Wasm blocks inside functions,
     /// which are encoded to refer to their type signature via index.
     pub(crate) wasm_types: WasmTypes,
+
+    /// The changes made to the module by the middlewares.
+    ///
+    /// This is used to generate the function middleware chains.
+    pub(crate) middleware_rewrites: MiddlewareRewrites,
 }
 
 impl ModuleTranslationState {
@@ -30,6 +36,7 @@ impl ModuleTranslationState {
     pub fn new() -> Self {
         Self {
             wasm_types: PrimaryMap::new(),
+            middleware_rewrites: MiddlewareRewrites::default(),
         }
     }
 
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
-use wasmer::{Function, Global, LazyInit, Memory, NativeFunc, Table, WasmerEnv};
+use wasmer::{
+    Function, Global, LazyInit, Memory, NativeFunc, Table, TypedGlobal, TypedTable, WasmerEnv,
+};
 
 #[derive(WasmerEnv, Clone)]
 struct MyEnv {
@@ -55,6 +57,14 @@ struct MyEnvWithEverything {
     functions: LazyInit<Table>,
 }
 
+#[derive(WasmerEnv, Clone)]
+struct MyEnvWithTypedExports {
+    #[wasmer(export)]
+    counter: LazyInit<TypedGlobal<i32>>,
+    #[wasmer(export(optional = true))]
+    functions: LazyInit<TypedTable<Option<Function>>>,
+}
+
 #[derive(WasmerEnv, Clone)]
 struct MyEnvWithLifetime<'a> {
     name: &'a str,
@@ -79,6 +89,7 @@ fn test_derive_with_attribute() {
     assert!(impls_wasmer_env::<MyEnvWithMemory>());
     assert!(impls_wasmer_env::<MyEnvWithFuncs>());
     assert!(impls_wasmer_env::<MyEnvWithEverything>());
+    assert!(impls_wasmer_env::<MyEnvWithTypedExports>());
     assert!(impls_wasmer_env::<MyEnvWithLifetime>());
     assert!(impls_wasmer_env::<MyUnitStruct>());
     assert!(impls_wasmer_env::<MyTupleStruct>());
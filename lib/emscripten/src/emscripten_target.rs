@@ -1,9 +1,11 @@
 #![allow(non_snake_case)]
 
 use crate::env::get_emscripten_data;
+use crate::exception::{find_matching_catch, resume_exception, CxaException};
 use crate::EmEnv;
 #[cfg(target_os = "linux")]
 use libc::getdtablesize;
+use wasmer::RuntimeError;
 
 pub fn asm_const_i(_ctx: &EmEnv, _val: i32) -> i32 {
     debug!("emscripten::asm_const_i: {}", _val);
@@ -50,19 +52,20 @@ pub fn __Unwind_GetIPInfo(_ctx: &EmEnv, _a: i32, _b: i32) -> i32 {
     debug!("emscripten::__Unwind_GetIPInfo");
     0
 }
-pub fn ___cxa_find_matching_catch_2(_ctx: &EmEnv) -> i32 {
+pub fn ___cxa_find_matching_catch_2(ctx: &EmEnv) -> Result<i32, RuntimeError> {
     debug!("emscripten::___cxa_find_matching_catch_2");
-    0
+    find_matching_catch(ctx, &[])
 }
-pub fn ___cxa_find_matching_catch_3(_ctx: &EmEnv, _a: i32) -> i32 {
+pub fn ___cxa_find_matching_catch_3(ctx: &EmEnv, a: i32) -> Result<i32, RuntimeError> {
     debug!("emscripten::___cxa_find_matching_catch_3");
-    0
+    find_matching_catch(ctx, &[a as u32])
 }
 pub fn ___cxa_free_exception(_ctx: &EmEnv, _a: i32) {
     debug!("emscripten::___cxa_free_exception");
 }
-pub fn ___resumeException(_ctx: &EmEnv, _a: i32) {
+pub fn ___resumeException(ctx: &EmEnv, a: i32) -> Result<(), CxaException> {
     debug!("emscripten::___resumeException");
+    resume_exception(ctx, a as u32)
 }
 pub fn _dladdr(_ctx: &EmEnv, _a: i32, _b: i32) -> i32 {
     debug!("emscripten::_dladdr");
@@ -1,6 +1,77 @@
-use super::env;
-use super::process::_abort;
-use crate::EmEnv;
+use super::env::{self, get_emscripten_data};
+use crate::{EmEnv, EmscriptenData};
+use std::error::Error;
+use std::fmt;
+use wasmer::RuntimeError;
+
+/// The bookkeeping of a C++ exception thrown with `___cxa_throw`, which
+/// lives until its reference count drops to zero.
+#[derive(Debug, Clone, Default)]
+pub struct ExceptionInfo {
+    ty: u32,
+    destructor: u32,
+    refcount: u32,
+    caught: bool,
+    rethrown: bool,
+    /// The addresses of the subobjects the exception was caught as.
+    adjusted: Vec<u32>,
+}
+
+/// The error a C++ exception unwinds the stack with, up to the `invoke_*`
+/// function that handles it.
+#[derive(Debug, Clone, Copy)]
+pub struct CxaException;
+
+impl fmt::Display for CxaException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "C++ exception")
+    }
+}
+
+impl Error for CxaException {}
+
+/// Returns the address of the exception that `ptr`, the address it was
+/// caught as, belongs to.
+fn de_adjust(data: &EmscriptenData, ptr: u32) -> u32 {
+    if ptr == 0 || data.exceptions.contains_key(&ptr) {
+        return ptr;
+    }
+    data.exceptions
+        .iter()
+        .find(|(_, info)| info.adjusted.contains(&ptr))
+        .map_or(ptr, |(exception, _)| *exception)
+}
+
+/// Releases a reference to the exception at `ptr`, destroying it once it
+/// has none left.
+fn release_exception(ctx: &EmEnv, ptr: u32) -> Result<(), RuntimeError> {
+    let (ptr, destructor) = {
+        let mut data = get_emscripten_data(ctx);
+        let ptr = de_adjust(&data, ptr);
+        let info = match data.exceptions.get_mut(&ptr) {
+            Some(info) => info,
+            None => return Ok(()),
+        };
+        info.refcount = info.refcount.saturating_sub(1);
+        if info.refcount > 0 || info.rethrown {
+            return Ok(());
+        }
+        let destructor = info.destructor;
+        data.exceptions.remove(&ptr);
+        (ptr, destructor)
+    };
+    if destructor != 0 {
+        let dyn_call_vi = get_emscripten_data(ctx).dyn_call_vi_ref().cloned();
+        if let Some(dyn_call_vi) = dyn_call_vi {
+            dyn_call_vi.call(destructor as i32, ptr as i32)?;
+        }
+    }
+    let free = get_emscripten_data(ctx).free_ref().cloned();
+    if let Some(free) = free {
+        free.call(ptr)?;
+    }
+    Ok(())
+}
 
 /// emscripten: ___cxa_allocate_exception
 pub fn ___cxa_allocate_exception(ctx: &EmEnv, size: u32) -> u32 {
@@ -8,46 +79,100 @@ pub fn ___cxa_allocate_exception(ctx: &EmEnv, size: u32) -> u32 {
     env::call_malloc(ctx, size as _)
 }
 
-pub fn ___cxa_current_primary_exception(_ctx: &EmEnv) -> u32 {
+pub fn ___cxa_current_primary_exception(ctx: &EmEnv) -> u32 {
     debug!("emscripten::___cxa_current_primary_exception");
-    unimplemented!("emscripten::___cxa_current_primary_exception")
+    let mut data = get_emscripten_data(ctx);
+    let ptr = match data.caught_exceptions.last() {
+        Some(ptr) => *ptr,
+        None => return 0,
+    };
+    if let Some(info) = data.exceptions.get_mut(&ptr) {
+        info.refcount += 1;
+    }
+    ptr
 }
 
-pub fn ___cxa_decrement_exception_refcount(_ctx: &EmEnv, _a: u32) {
-    debug!("emscripten::___cxa_decrement_exception_refcount({})", _a);
-    unimplemented!("emscripten::___cxa_decrement_exception_refcount({})", _a)
+pub fn ___cxa_decrement_exception_refcount(ctx: &EmEnv, ptr: u32) -> Result<(), RuntimeError> {
+    debug!("emscripten::___cxa_decrement_exception_refcount({})", ptr);
+    release_exception(ctx, ptr)
 }
 
-pub fn ___cxa_increment_exception_refcount(_ctx: &EmEnv, _a: u32) {
-    debug!("emscripten::___cxa_increment_exception_refcount({})", _a);
-    unimplemented!("emscripten::___cxa_increment_exception_refcount({})", _a)
+pub fn ___cxa_increment_exception_refcount(ctx: &EmEnv, ptr: u32) {
+    debug!("emscripten::___cxa_increment_exception_refcount({})", ptr);
+    let mut data = get_emscripten_data(ctx);
+    let ptr = de_adjust(&data, ptr);
+    if let Some(info) = data.exceptions.get_mut(&ptr) {
+        info.refcount += 1;
+    }
 }
 
-pub fn ___cxa_rethrow_primary_exception(_ctx: &EmEnv, _a: u32) {
-    debug!("emscripten::___cxa_rethrow_primary_exception({})", _a);
-    unimplemented!("emscripten::___cxa_rethrow_primary_exception({})", _a)
+pub fn ___cxa_rethrow_primary_exception(ctx: &EmEnv, ptr: u32) -> Result<(), CxaException> {
+    debug!("emscripten::___cxa_rethrow_primary_exception({})", ptr);
+    let mut data = get_emscripten_data(ctx);
+    let ptr = de_adjust(&data, ptr);
+    let info = match data.exceptions.get_mut(&ptr) {
+        Some(info) => info,
+        None => return Ok(()),
+    };
+    info.caught = false;
+    info.rethrown = true;
+    data.last_thrown_exception = ptr;
+    data.uncaught_exceptions += 1;
+    Err(CxaException)
 }
 
 /// emscripten: ___cxa_throw
-/// TODO: We don't have support for exceptions yet
-pub fn ___cxa_throw(ctx: &EmEnv, _ptr: u32, _ty: u32, _destructor: u32) {
+pub fn ___cxa_throw(ctx: &EmEnv, ptr: u32, ty: u32, destructor: u32) -> Result<(), CxaException> {
     debug!("emscripten::___cxa_throw");
-    eprintln!("Throwing exceptions not yet implemented: aborting!");
-    _abort(ctx);
+    let mut data = get_emscripten_data(ctx);
+    data.exceptions.insert(
+        ptr,
+        ExceptionInfo {
+            ty,
+            destructor,
+            ..Default::default()
+        },
+    );
+    data.last_thrown_exception = ptr;
+    data.uncaught_exceptions += 1;
+    Err(CxaException)
 }
 
-pub fn ___cxa_begin_catch(_ctx: &EmEnv, _exception_object_ptr: u32) -> i32 {
+pub fn ___cxa_begin_catch(ctx: &EmEnv, exception_object_ptr: u32) -> i32 {
     debug!("emscripten::___cxa_begin_catch");
-    -1
+    let mut data = get_emscripten_data(ctx);
+    let ptr = de_adjust(&data, exception_object_ptr);
+    let info = match data.exceptions.get_mut(&ptr) {
+        Some(info) => info,
+        None => return exception_object_ptr as i32,
+    };
+    let newly_caught = !info.caught;
+    info.caught = true;
+    info.rethrown = false;
+    info.refcount += 1;
+    if newly_caught {
+        data.uncaught_exceptions -= 1;
+    }
+    data.caught_exceptions.push(ptr);
+    exception_object_ptr as i32
 }
 
-pub fn ___cxa_end_catch(_ctx: &EmEnv) {
+pub fn ___cxa_end_catch(ctx: &EmEnv) -> Result<(), RuntimeError> {
     debug!("emscripten::___cxa_end_catch");
+    let ptr = {
+        let mut data = get_emscripten_data(ctx);
+        data.last_thrown_exception = 0;
+        data.caught_exceptions.pop()
+    };
+    match ptr {
+        Some(ptr) => release_exception(ctx, ptr),
+        None => Ok(()),
+    }
 }
 
-pub fn ___cxa_uncaught_exception(_ctx: &EmEnv) -> i32 {
+pub fn ___cxa_uncaught_exception(ctx: &EmEnv) -> i32 {
     debug!("emscripten::___cxa_uncaught_exception");
-    -1
+    (get_emscripten_data(ctx).uncaught_exceptions > 0) as i32
 }
 
 pub fn ___cxa_pure_virtual(_ctx: &EmEnv) {
@@ -55,3 +180,244 @@ pub fn ___cxa_pure_virtual(_ctx: &EmEnv) {
     // ABORT = true
     panic!("Pure virtual function called!");
 }
+
+/// Returns the exception being thrown, for a landing pad with the catch
+/// clauses `catch_types`, and sets the temporary return value to the type
+/// the landing pad matches it with.
+///
+/// If one of `catch_types` catches the exception, like a base class of
+/// its type, that type is the temporary return value, and the exception
+/// is returned as that type: `___cxa_can_catch` adjusts its address.
+/// Otherwise the temporary return value is the type the exception was
+/// thrown with, which the landing pad doesn't handle.
+pub(crate) fn find_matching_catch(ctx: &EmEnv, catch_types: &[u32]) -> Result<i32, RuntimeError> {
+    let (thrown, thrown_ty) = {
+        let mut data = get_emscripten_data(ctx);
+        let thrown = data.last_thrown_exception;
+        let thrown_ty = data.exceptions.get(&thrown).map_or(0, |info| info.ty);
+        if thrown_ty == 0 {
+            data.temp_ret_0 = 0;
+            return Ok(thrown as i32);
+        }
+        (thrown, thrown_ty)
+    };
+    for &catch_ty in catch_types.iter().filter(|&&ty| ty != 0) {
+        if catch_ty == thrown_ty {
+            get_emscripten_data(ctx).temp_ret_0 = catch_ty as i32;
+            return Ok(thrown as i32);
+        }
+        if let Some(adjusted) = can_catch(ctx, catch_ty, thrown_ty, thrown)? {
+            let mut data = get_emscripten_data(ctx);
+            if let Some(info) = data.exceptions.get_mut(&thrown) {
+                if adjusted != thrown && !info.adjusted.contains(&adjusted) {
+                    info.adjusted.push(adjusted);
+                }
+            }
+            data.temp_ret_0 = catch_ty as i32;
+            return Ok(adjusted as i32);
+        }
+    }
+    get_emscripten_data(ctx).temp_ret_0 = thrown_ty as i32;
+    Ok(thrown as i32)
+}
+
+/// Returns whether a catch clause of type `catch_ty` catches the exception
+/// at `thrown`, of type `thrown_ty`, with the guest's `___cxa_can_catch`,
+/// and the address of the exception as `catch_ty` if it does.
+fn can_catch(
+    ctx: &EmEnv,
+    catch_ty: u32,
+    thrown_ty: u32,
+    thrown: u32,
+) -> Result<Option<u32>, RuntimeError> {
+    let (cxa_can_catch, malloc, buffer) = {
+        let data = get_emscripten_data(ctx);
+        match data.cxa_can_catch_ref().cloned() {
+            Some(cxa_can_catch) => (cxa_can_catch, data.malloc_ref().cloned(), data.catch_buffer),
+            None => return Ok(None),
+        }
+    };
+    let buffer = match (buffer, malloc) {
+        (0, Some(malloc)) => {
+            let buffer = malloc.call(4)?;
+            get_emscripten_data(ctx).catch_buffer = buffer;
+            buffer
+        }
+        (0, None) => return Ok(None),
+        (buffer, _) => buffer,
+    };
+    let memory = ctx.memory(0);
+    let to_runtime_error = |e: wasmer::MemoryAccessError| RuntimeError::new(e.to_string());
+    memory
+        .write(buffer.into(), &thrown.to_le_bytes())
+        .map_err(to_runtime_error)?;
+    if cxa_can_catch.call(catch_ty, thrown_ty, buffer)? == 0 {
+        return Ok(None);
+    }
+    let adjusted = memory
+        .read_pod::<u32>(buffer.into())
+        .map_err(to_runtime_error)?;
+    Ok(Some(adjusted))
+}
+
+/// Resumes the unwinding of the exception at `ptr`, out of a landing pad
+/// that didn't handle it.
+pub(crate) fn resume_exception(ctx: &EmEnv, ptr: u32) -> Result<(), CxaException> {
+    let mut data = get_emscripten_data(ctx);
+    if data.last_thrown_exception == 0 {
+        data.last_thrown_exception = ptr;
+    }
+    Err(CxaException)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EmscriptenGlobalsData;
+    use std::collections::HashMap;
+    use wasmer::{imports, Instance, Module, Store, WasmerEnv};
+
+    const EXCEPTION: u32 = 1024;
+    const TYPE_INFO: u32 = 64;
+    const BASE: u32 = 80;
+    const DERIVED: u32 = 96;
+
+    fn env() -> EmEnv {
+        EmEnv::new(&EmscriptenGlobalsData::default(), HashMap::new())
+    }
+
+    /// An environment with a guest providing the parts of the C++ runtime
+    /// the exceptions call into: `DERIVED` derives from `BASE`, whose
+    /// subobject is 8 bytes into the object, and destructors trap.
+    fn guest_env() -> (Instance, EmEnv) {
+        let store = Store::default();
+        let module = Module::new(
+            &store,
+            format!(
+                r#"
+                (module
+                  (memory (export "memory") 1)
+                  (global $next (mut i32) (i32.const 4096))
+                  (func (export "_malloc") (param i32) (result i32)
+                    (global.get $next)
+                    (global.set $next (i32.add (global.get $next) (local.get 0))))
+                  (func (export "___cxa_can_catch")
+                    (param $catch i32) (param $thrown i32) (param $ptr i32) (result i32)
+                    (if (i32.and (i32.eq (local.get $catch) (i32.const {base}))
+                                 (i32.eq (local.get $thrown) (i32.const {derived})))
+                      (then
+                        (i32.store (local.get $ptr)
+                          (i32.add (i32.load (local.get $ptr)) (i32.const 8)))
+                        (return (i32.const 1))))
+                    (i32.const 0))
+                  (func (export "dynCall_vi") (param i32 i32)
+                    (unreachable)))
+                "#,
+                base = BASE,
+                derived = DERIVED
+            ),
+        )
+        .unwrap();
+        let instance = Instance::new(&module, &imports! {}).unwrap();
+        let mut ctx = env();
+        ctx.set_memory(instance.exports.get_memory("memory").unwrap().clone());
+        ctx.init_with_instance(&instance).unwrap();
+        (instance, ctx)
+    }
+
+    fn is_alive(ctx: &EmEnv, ptr: u32) -> bool {
+        get_emscripten_data(ctx).exceptions.contains_key(&ptr)
+    }
+
+    #[test]
+    fn throw_and_catch() {
+        let ctx = env();
+
+        assert!(___cxa_throw(&ctx, EXCEPTION, TYPE_INFO, 0).is_err());
+        assert_eq!(___cxa_uncaught_exception(&ctx), 1);
+
+        // The landing pad finds the exception and its type.
+        assert_eq!(find_matching_catch(&ctx, &[]).unwrap(), EXCEPTION as i32);
+        assert_eq!(get_emscripten_data(&ctx).temp_ret_0, TYPE_INFO as i32);
+
+        assert_eq!(___cxa_begin_catch(&ctx, EXCEPTION), EXCEPTION as i32);
+        assert_eq!(___cxa_uncaught_exception(&ctx), 0);
+        assert_eq!(___cxa_current_primary_exception(&ctx), EXCEPTION);
+        ___cxa_decrement_exception_refcount(&ctx, EXCEPTION).unwrap();
+
+        ___cxa_end_catch(&ctx).unwrap();
+        assert!(!is_alive(&ctx, EXCEPTION));
+        assert_eq!(get_emscripten_data(&ctx).last_thrown_exception, 0);
+    }
+
+    #[test]
+    fn exception_outlives_its_handler_while_referenced() {
+        let ctx = env();
+
+        assert!(___cxa_throw(&ctx, EXCEPTION, TYPE_INFO, 0).is_err());
+        ___cxa_begin_catch(&ctx, EXCEPTION);
+        // `std::current_exception` in the handler.
+        assert_eq!(___cxa_current_primary_exception(&ctx), EXCEPTION);
+        ___cxa_end_catch(&ctx).unwrap();
+        assert!(is_alive(&ctx, EXCEPTION));
+
+        // `std::rethrow_exception` out of the handler.
+        assert!(___cxa_rethrow_primary_exception(&ctx, EXCEPTION).is_err());
+        assert_eq!(___cxa_uncaught_exception(&ctx), 1);
+        ___cxa_decrement_exception_refcount(&ctx, EXCEPTION).unwrap();
+        assert!(is_alive(&ctx, EXCEPTION));
+
+        ___cxa_begin_catch(&ctx, EXCEPTION);
+        ___cxa_end_catch(&ctx).unwrap();
+        assert!(!is_alive(&ctx, EXCEPTION));
+    }
+
+    #[test]
+    fn unknown_exceptions_are_ignored() {
+        let ctx = env();
+
+        assert_eq!(___cxa_current_primary_exception(&ctx), 0);
+        assert!(___cxa_rethrow_primary_exception(&ctx, EXCEPTION).is_ok());
+        ___cxa_increment_exception_refcount(&ctx, EXCEPTION);
+        ___cxa_decrement_exception_refcount(&ctx, EXCEPTION).unwrap();
+        assert_eq!(___cxa_begin_catch(&ctx, EXCEPTION), EXCEPTION as i32);
+        ___cxa_end_catch(&ctx).unwrap();
+        assert_eq!(___cxa_uncaught_exception(&ctx), 0);
+    }
+
+    #[test]
+    fn catch_as_a_base_class() {
+        let (_instance, ctx) = guest_env();
+
+        assert!(___cxa_throw(&ctx, EXCEPTION, DERIVED, 0).is_err());
+
+        // A landing pad that doesn't handle the type gets the thrown type.
+        assert_eq!(
+            find_matching_catch(&ctx, &[TYPE_INFO]).unwrap(),
+            EXCEPTION as i32
+        );
+        assert_eq!(get_emscripten_data(&ctx).temp_ret_0, DERIVED as i32);
+
+        // `catch (const Base&)` gets the `Base` subobject.
+        let adjusted = EXCEPTION + 8;
+        assert_eq!(find_matching_catch(&ctx, &[BASE]).unwrap(), adjusted as i32);
+        assert_eq!(get_emscripten_data(&ctx).temp_ret_0, BASE as i32);
+
+        assert_eq!(___cxa_begin_catch(&ctx, adjusted), adjusted as i32);
+        assert_eq!(___cxa_uncaught_exception(&ctx), 0);
+        assert_eq!(___cxa_current_primary_exception(&ctx), EXCEPTION);
+        ___cxa_decrement_exception_refcount(&ctx, EXCEPTION).unwrap();
+        ___cxa_end_catch(&ctx).unwrap();
+        assert!(!is_alive(&ctx, EXCEPTION));
+    }
+
+    #[test]
+    fn destructor_traps_are_returned() {
+        let (_instance, ctx) = guest_env();
+
+        assert!(___cxa_throw(&ctx, EXCEPTION, TYPE_INFO, 1).is_err());
+        ___cxa_begin_catch(&ctx, EXCEPTION);
+        assert!(___cxa_end_catch(&ctx).is_err());
+        assert!(!is_alive(&ctx, EXCEPTION));
+    }
+}
@@ -13,6 +13,7 @@
 #[macro_use]
 extern crate log;
 
+use crate::exception::ExceptionInfo;
 use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::f64;
@@ -272,7 +273,21 @@ pub struct EmscriptenData {
     pub stack_restore: LazyInit<NativeFunc<i32>>,
     #[wasmer(export(name = "setThrew", alias = "_setThrew", optional = true))]
     pub set_threw: LazyInit<NativeFunc<(i32, i32)>>,
+    #[wasmer(export(name = "___cxa_can_catch", alias = "__cxa_can_catch", optional = true))]
+    pub cxa_can_catch: LazyInit<NativeFunc<(u32, u32, u32), i32>>,
     pub mapped_dirs: HashMap<String, PathBuf>,
+
+    /// The C++ exceptions thrown and not yet destroyed, by address.
+    pub exceptions: HashMap<u32, ExceptionInfo>,
+    /// The C++ exceptions being handled, the innermost last.
+    pub caught_exceptions: Vec<u32>,
+    /// The last C++ exception thrown, for the landing pads.
+    pub last_thrown_exception: u32,
+    /// The number of C++ exceptions thrown and not yet caught.
+    pub uncaught_exceptions: i32,
+    /// The address of the pointer `___cxa_can_catch` adjusts, allocated on
+    /// first use.
+    pub catch_buffer: u32,
 }
 
 impl EmscriptenData {
@@ -142,8 +142,11 @@ impl DylibArtifact {
 
         // We try to apply the middleware first
         let mut module = translation.module;
+        let mut module_translation_state = translation.module_translation_state;
         let middlewares = compiler.get_middlewares();
-        middlewares.apply_on_module_info(&mut module);
+        if let Some(module_translation_state) = module_translation_state.as_mut() {
+            middlewares.apply_on_module_info_with_state(&mut module, module_translation_state);
+        }
 
         let memory_styles: PrimaryMap<MemoryIndex, MemoryStyle> = module
             .memories
@@ -166,7 +169,7 @@ impl DylibArtifact {
             compile_info,
             translation.function_body_inputs,
             translation.data_initializers,
-            translation.module_translation_state,
+            module_translation_state,
         ))
     }
 
@@ -183,6 +186,10 @@ impl DylibArtifact {
         let compiler = engine_inner.compiler()?;
         let (compile_info, function_body_inputs, data_initializers, module_translation) =
             Self::generate_metadata(data, engine_inner.features(), compiler, tunables)?;
+        let mut function_body_inputs = function_body_inputs;
+        if let Some(module_translation) = module_translation.as_ref() {
+            module_translation.push_middleware_function_bodies(&mut function_body_inputs);
+        }
 
         let data_initializers = data_initializers
             .iter()
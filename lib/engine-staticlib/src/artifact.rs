@@ -125,8 +125,11 @@ impl StaticlibArtifact {
 
         // We try to apply the middleware first
         let mut module = translation.module;
+        let mut module_translation_state = translation.module_translation_state;
         let middlewares = compiler.get_middlewares();
-        middlewares.apply_on_module_info(&mut module);
+        if let Some(module_translation_state) = module_translation_state.as_mut() {
+            middlewares.apply_on_module_info_with_state(&mut module, module_translation_state);
+        }
 
         let memory_styles: PrimaryMap<MemoryIndex, MemoryStyle> = module
             .memories
@@ -149,7 +152,7 @@ impl StaticlibArtifact {
             compile_info,
             translation.function_body_inputs,
             translation.data_initializers,
-            translation.module_translation_state,
+            module_translation_state,
         ))
     }
 
@@ -166,6 +169,10 @@ impl StaticlibArtifact {
         let compiler = engine_inner.compiler()?;
         let (compile_info, function_body_inputs, data_initializers, module_translation) =
             Self::generate_metadata(data, engine_inner.features(), compiler, tunables)?;
+        let mut function_body_inputs = function_body_inputs;
+        if let Some(module_translation) = module_translation.as_ref() {
+            module_translation.push_middleware_function_bodies(&mut function_body_inputs);
+        }
 
         let data_initializers = data_initializers
             .iter()
@@ -216,11 +223,6 @@ impl StaticlibArtifact {
 
         let (compile_info, symbol_registry) = metadata.split();
 
-        let mut module = (*compile_info.module).clone();
-        let middlewares = compiler.get_middlewares();
-        middlewares.apply_on_module_info(&mut module);
-        compile_info.module = Arc::new(module);
-
         let maybe_obj_bytes = compiler.experimental_native_compile_module(
             &target,
             &compile_info,
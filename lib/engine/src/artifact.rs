@@ -1,14 +1,18 @@
-use crate::{resolve_imports, InstantiationError, Resolver, RuntimeError, Tunables};
+use crate::{
+    imports_from_definitions, resolve_import_definitions, Export, InstantiationError, Resolver,
+    RuntimeError, Tunables,
+};
 use loupe::MemoryUsage;
 use std::any::Any;
+use std::sync::Arc;
 pub use wasmer_artifact::MetadataHeader;
 use wasmer_artifact::{ArtifactCreate, Upcastable};
 use wasmer_compiler::CpuFeature;
 use wasmer_types::entity::BoxedSlice;
 use wasmer_types::{DataInitializer, FunctionIndex, LocalFunctionIndex, SignatureIndex};
 use wasmer_vm::{
-    FuncDataRegistry, FunctionBodyPtr, InstanceAllocator, InstanceHandle, TrapHandler,
-    VMSharedSignatureIndex, VMTrampoline,
+    FuncDataRegistry, FunctionBodyPtr, InstanceHandle, ModuleMemoryImages, ResourceLimiter,
+    TrapHandler, VMInterrupts, VMSharedSignatureIndex, VMTrampoline,
 };
 
 /// An `Artifact` is the product that the `Engine`
@@ -48,7 +52,7 @@ pub trait Artifact: Send + Sync + Upcastable + MemoryUsage + ArtifactCreate {
     fn preinstantiate(&self) -> Result<(), InstantiationError> {
         Ok(())
     }
-    /// Crate an `Instance` from this `Artifact`.
+    /// Create an `Instance` from this `Artifact`.
     ///
     /// # Safety
     ///
@@ -58,6 +62,27 @@ pub trait Artifact: Send + Sync + Upcastable + MemoryUsage + ArtifactCreate {
         tunables: &dyn Tunables,
         resolver: &dyn Resolver,
         host_state: Box<dyn Any>,
+        interrupts: Arc<VMInterrupts>,
+        limiter: Option<Arc<dyn ResourceLimiter>>,
+    ) -> Result<InstanceHandle, InstantiationError> {
+        let definitions =
+            resolve_import_definitions(&self.module(), resolver, self.memory_styles())
+                .map_err(InstantiationError::Link)?;
+        self.instantiate_with_definitions(tunables, &definitions, host_state, interrupts, limiter)
+    }
+    /// Create an `Instance` from this `Artifact`, with the import definitions
+    /// previously returned by [`resolve_import_definitions`] for it.
+    ///
+    /// # Safety
+    ///
+    /// See [`InstanceHandle::new`].
+    unsafe fn instantiate_with_definitions(
+        &self,
+        tunables: &dyn Tunables,
+        definitions: &[Export],
+        host_state: Box<dyn Any>,
+        interrupts: Arc<VMInterrupts>,
+        limiter: Option<Arc<dyn ResourceLimiter>>,
     ) -> Result<InstanceHandle, InstantiationError> {
         // Validate the CPU features this module was compiled with against the
         // host CPU features.
@@ -73,14 +98,11 @@ pub trait Artifact: Send + Sync + Upcastable + MemoryUsage + ArtifactCreate {
 
         let module = self.module();
         let (imports, import_function_envs) = {
-            let mut imports = resolve_imports(
+            let mut imports = imports_from_definitions(
                 &module,
-                resolver,
+                definitions,
                 &self.finished_dynamic_function_trampolines(),
-                self.memory_styles(),
-                self.table_styles(),
-            )
-            .map_err(InstantiationError::Link)?;
+            );
 
             // Get the `WasmerEnv::init_with_instance` function pointers and the pointers
             // to the envs to call it on.
@@ -92,8 +114,9 @@ pub trait Artifact: Send + Sync + Upcastable + MemoryUsage + ArtifactCreate {
         // Get pointers to where metadata about local memories should live in VM memory.
         // Get pointers to where metadata about local tables should live in VM memory.
 
-        let (allocator, memory_definition_locations, table_definition_locations) =
-            InstanceAllocator::new(&*module);
+        let (allocator, memory_definition_locations, table_definition_locations) = tunables
+            .allocate_instance(&*module)
+            .map_err(InstantiationError::Link)?;
         let finished_memories = tunables
             .create_memories(&module, self.memory_styles(), &memory_definition_locations)
             .map_err(InstantiationError::Link)?
@@ -121,10 +144,19 @@ pub trait Artifact: Send + Sync + Upcastable + MemoryUsage + ArtifactCreate {
             self.signatures().clone(),
             host_state,
             import_function_envs,
+            interrupts,
+            limiter,
         )
-        .map_err(|trap| InstantiationError::Start(RuntimeError::from_trap(trap)))?;
+        .map_err(InstantiationError::ResourceLimit)?;
         Ok(handle)
     }
+    /// Creates the memory images used to initialize the memories of the
+    /// instances of this `Artifact` copy-on-write.
+    ///
+    /// Returns `None` if the memories can't be initialized with images.
+    fn create_memory_images(&self) -> Result<Option<ModuleMemoryImages>, String> {
+        ModuleMemoryImages::new(self.module_ref(), &borrowed_data_initializers(self))
+    }
     /// Finishes the instantiation of a just created `InstanceHandle`.
     ///
     /// # Safety
@@ -134,19 +166,48 @@ pub trait Artifact: Send + Sync + Upcastable + MemoryUsage + ArtifactCreate {
         &self,
         trap_handler: &(dyn TrapHandler + 'static),
         handle: &InstanceHandle,
+        memory_images: Option<&ModuleMemoryImages>,
     ) -> Result<(), InstantiationError> {
-        let data_initializers = self
-            .data_initializers()
-            .iter()
-            .map(|init| DataInitializer {
-                location: init.location.clone(),
-                data: &*init.data,
-            })
-            .collect::<Vec<_>>();
         handle
-            .finish_instantiation(trap_handler, &data_initializers)
+            .finish_instantiation(
+                trap_handler,
+                &borrowed_data_initializers(self),
+                memory_images,
+            )
             .map_err(|trap| InstantiationError::Start(RuntimeError::from_trap(trap)))
     }
+    /// Restores an instance of this artifact to the state it had right
+    /// after its instantiation.
+    ///
+    /// # Safety
+    ///
+    /// See [`InstanceHandle::reset`].
+    unsafe fn reset_instance(
+        &self,
+        trap_handler: &(dyn TrapHandler + 'static),
+        handle: &InstanceHandle,
+        memory_images: Option<&ModuleMemoryImages>,
+    ) -> Result<(), RuntimeError> {
+        handle
+            .reset(
+                trap_handler,
+                &borrowed_data_initializers(self),
+                memory_images,
+            )
+            .map_err(RuntimeError::from_trap)
+    }
+}
+
+/// Returns the data initializers of `artifact`, borrowing their data.
+fn borrowed_data_initializers<A: Artifact + ?Sized>(artifact: &A) -> Vec<DataInitializer<'_>> {
+    artifact
+        .data_initializers()
+        .iter()
+        .map(|init| DataInitializer {
+            location: init.location.clone(),
+            data: &*init.data,
+        })
+        .collect()
 }
 
 impl dyn Artifact + 'static {
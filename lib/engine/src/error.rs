@@ -2,6 +2,7 @@
 use crate::trap::RuntimeError;
 use thiserror::Error;
 pub use wasmer_artifact::{DeserializeError, ImportError, SerializeError};
+use wasmer_vm::ResourceLimitError;
 
 /// The WebAssembly.LinkError object indicates an error during
 /// module instantiation (besides traps from the start function).
@@ -46,4 +47,9 @@ pub enum InstantiationError {
     /// A runtime error occured while invoking the start function
     #[error(transparent)]
     Start(RuntimeError),
+
+    /// The resource limiter of the store denied the instance, or the
+    /// initial size of its memories or tables.
+    #[error("the resource limiter denied the instantiation: {0}")]
+    ResourceLimit(ResourceLimitError),
 }
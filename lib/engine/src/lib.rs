@@ -36,8 +36,8 @@ pub use crate::engine::{Engine, EngineId};
 pub use crate::error::{InstantiationError, LinkError};
 pub use crate::export::{Export, ExportFunction, ExportFunctionMetadata};
 pub use crate::resolver::{
-    resolve_imports, ChainableNamedResolver, NamedResolver, NamedResolverChain, NullResolver,
-    Resolver,
+    imports_from_definitions, resolve_import_definitions, resolve_imports, ChainableNamedResolver,
+    NamedResolver, NamedResolverChain, NullResolver, Resolver,
 };
 pub use crate::trap::*;
 pub use crate::tunables::Tunables;
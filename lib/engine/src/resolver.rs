@@ -105,6 +105,7 @@ fn get_extern_from_import(module: &ModuleInfo, import_index: &ImportIndex) -> Ex
             let global = module.globals[*index];
             ExternType::Global(global)
         }
+        ImportIndex::Tag(index) => ExternType::Tag(module.tag_type(*index)),
     }
 }
 
@@ -118,6 +119,7 @@ fn get_extern_from_export(_module: &ModuleInfo, export: &Export) -> ExternType {
             let global = g.from.ty();
             ExternType::Global(*global)
         }
+        Export::Tag(ref t) => ExternType::Tag(t.ty().clone()),
     }
 }
 
@@ -132,12 +134,26 @@ pub fn resolve_imports(
     memory_styles: &PrimaryMap<MemoryIndex, MemoryStyle>,
     _table_styles: &PrimaryMap<TableIndex, TableStyle>,
 ) -> Result<Imports, LinkError> {
-    let mut function_imports = PrimaryMap::with_capacity(module.num_imported_functions);
-    let mut host_function_env_initializers =
-        PrimaryMap::with_capacity(module.num_imported_functions);
-    let mut table_imports = PrimaryMap::with_capacity(module.num_imported_tables);
-    let mut memory_imports = PrimaryMap::with_capacity(module.num_imported_memories);
-    let mut global_imports = PrimaryMap::with_capacity(module.num_imported_globals);
+    let definitions = resolve_import_definitions(module, resolver, memory_styles)?;
+    Ok(imports_from_definitions(
+        module,
+        &definitions,
+        finished_dynamic_function_trampolines,
+    ))
+}
+
+/// Finds the definitions of all the imports of a `ModuleInfo` with a
+/// `Resolver`, and checks that their types match the imports.
+///
+/// The definitions are returned in the order of the imports. They can be
+/// turned into the `Imports` of as many instances as needed with
+/// [`imports_from_definitions`], without resolving them again.
+pub fn resolve_import_definitions(
+    module: &ModuleInfo,
+    resolver: &dyn Resolver,
+    memory_styles: &PrimaryMap<MemoryIndex, MemoryStyle>,
+) -> Result<Vec<Export>, LinkError> {
+    let mut definitions = Vec::with_capacity(module.imports.len());
 
     for ((module_name, field, import_idx), import_index) in module.imports.iter() {
         let resolved = resolver.resolve(*import_idx, module_name, field);
@@ -161,6 +177,76 @@ pub fn resolve_imports(
             ));
         }
         match resolved {
+            Export::Function(_) | Export::Global(_) | Export::Tag(_) => {}
+            Export::Table(ref t) => match import_index {
+                ImportIndex::Table(index) => {
+                    let import_table_ty = t.from.ty();
+                    let expected_table_ty = &module.tables[*index];
+                    if import_table_ty.ty != expected_table_ty.ty {
+                        return Err(LinkError::Import(
+                            module_name.to_string(),
+                            field.to_string(),
+                            ImportError::IncompatibleType(import_extern, export_extern),
+                        ));
+                    }
+                }
+                _ => {
+                    unreachable!("Table resolution did not match");
+                }
+            },
+            Export::Memory(ref m) => {
+                match import_index {
+                    ImportIndex::Memory(index) => {
+                        // Sanity-check: Ensure that the imported memory has at least
+                        // guard-page protections the importing module expects it to have.
+                        let export_memory_style = m.style();
+                        let import_memory_style = &memory_styles[*index];
+                        if let (
+                            MemoryStyle::Static { bound, .. },
+                            MemoryStyle::Static {
+                                bound: import_bound,
+                                ..
+                            },
+                        ) = (export_memory_style.clone(), &import_memory_style)
+                        {
+                            assert_ge!(bound, *import_bound);
+                        }
+                        assert_ge!(
+                            export_memory_style.offset_guard_size(),
+                            import_memory_style.offset_guard_size()
+                        );
+                    }
+                    _ => {
+                        // This should never be reached, as we did compatibility
+                        // checks before
+                        panic!("Memory resolution didn't matched");
+                    }
+                }
+            }
+        }
+        definitions.push(resolved);
+    }
+
+    Ok(definitions)
+}
+
+/// Creates the `Imports` of a new instance from the definitions returned by
+/// [`resolve_import_definitions`].
+pub fn imports_from_definitions(
+    module: &ModuleInfo,
+    definitions: &[Export],
+    finished_dynamic_function_trampolines: &BoxedSlice<FunctionIndex, FunctionBodyPtr>,
+) -> Imports {
+    let mut function_imports = PrimaryMap::with_capacity(module.num_imported_functions);
+    let mut host_function_env_initializers =
+        PrimaryMap::with_capacity(module.num_imported_functions);
+    let mut table_imports = PrimaryMap::with_capacity(module.num_imported_tables);
+    let mut memory_imports = PrimaryMap::with_capacity(module.num_imported_memories);
+    let mut global_imports = PrimaryMap::with_capacity(module.num_imported_globals);
+    let mut tag_imports = PrimaryMap::with_capacity(module.num_imported_tags);
+
+    for resolved in definitions {
+        match *resolved {
             Export::Function(ref f) => {
                 let address = match f.vm_function.kind {
                     VMFunctionKind::Dynamic => {
@@ -218,78 +304,38 @@ pub fn resolve_imports(
 
                 host_function_env_initializers.push(import_function_env);
             }
-            Export::Table(ref t) => match import_index {
-                ImportIndex::Table(index) => {
-                    let import_table_ty = t.from.ty();
-                    let expected_table_ty = &module.tables[*index];
-                    if import_table_ty.ty != expected_table_ty.ty {
-                        return Err(LinkError::Import(
-                            module_name.to_string(),
-                            field.to_string(),
-                            ImportError::IncompatibleType(import_extern, export_extern),
-                        ));
-                    }
-
-                    table_imports.push(VMTableImport {
-                        definition: t.from.vmtable(),
-                        from: t.from.clone(),
-                    });
-                }
-                _ => {
-                    unreachable!("Table resolution did not match");
-                }
-            },
+            Export::Table(ref t) => {
+                table_imports.push(VMTableImport {
+                    definition: t.from.vmtable(),
+                    from: t.from.clone(),
+                });
+            }
             Export::Memory(ref m) => {
-                match import_index {
-                    ImportIndex::Memory(index) => {
-                        // Sanity-check: Ensure that the imported memory has at least
-                        // guard-page protections the importing module expects it to have.
-                        let export_memory_style = m.style();
-                        let import_memory_style = &memory_styles[*index];
-                        if let (
-                            MemoryStyle::Static { bound, .. },
-                            MemoryStyle::Static {
-                                bound: import_bound,
-                                ..
-                            },
-                        ) = (export_memory_style.clone(), &import_memory_style)
-                        {
-                            assert_ge!(bound, *import_bound);
-                        }
-                        assert_ge!(
-                            export_memory_style.offset_guard_size(),
-                            import_memory_style.offset_guard_size()
-                        );
-                    }
-                    _ => {
-                        // This should never be reached, as we did compatibility
-                        // checks before
-                        panic!("Memory resolution didn't matched");
-                    }
-                }
-
                 memory_imports.push(VMMemoryImport {
                     definition: m.from.vmmemory(),
                     from: m.from.clone(),
                 });
             }
-
             Export::Global(ref g) => {
                 global_imports.push(VMGlobalImport {
                     definition: g.from.vmglobal(),
                     from: g.from.clone(),
                 });
             }
+            Export::Tag(ref t) => {
+                tag_imports.push(t.from.clone());
+            }
         }
     }
 
-    Ok(Imports::new(
+    Imports::new(
         function_imports,
         host_function_env_initializers,
         table_imports,
         memory_imports,
         global_imports,
-    ))
+        tag_imports,
+    )
 }
 
 /// A [`Resolver`] that links two resolvers together in a chain.
@@ -3,7 +3,7 @@ use backtrace::Backtrace;
 use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
-use wasmer_vm::{raise_user_trap, Trap, TrapCode};
+use wasmer_vm::{raise_user_trap, Trap, TrapCode, VMException};
 
 /// A struct representing an aborted instruction execution, with a message
 /// indicating the cause.
@@ -19,6 +19,7 @@ enum RuntimeErrorSource {
     OOM,
     User(Box<dyn Error + Send + Sync>),
     Trap(TrapCode),
+    Exception(VMException),
 }
 
 impl fmt::Display for RuntimeErrorSource {
@@ -28,6 +29,7 @@ impl fmt::Display for RuntimeErrorSource {
             Self::User(s) => write!(f, "{}", s),
             Self::OOM => write!(f, "Wasmer VM out of memory"),
             Self::Trap(s) => write!(f, "{}", s.message()),
+            Self::Exception(_) => write!(f, "uncaught exception"),
         }
     }
 }
@@ -103,6 +105,16 @@ impl RuntimeError {
                 trap_code,
                 backtrace,
             } => Self::new_with_trace(&info, None, RuntimeErrorSource::Trap(trap_code), backtrace),
+            // An exception thrown by Wasm code that no handler caught
+            Trap::Exception {
+                exception,
+                backtrace,
+            } => Self::new_with_trace(
+                &info,
+                None,
+                RuntimeErrorSource::Exception(exception),
+                backtrace,
+            ),
         }
     }
 
@@ -212,6 +224,21 @@ impl RuntimeError {
         }
     }
 
+    /// Returns true if the `RuntimeError` is an exception thrown by Wasm
+    /// code that no handler caught.
+    pub fn is_exception(&self) -> bool {
+        matches!(self.inner.source, RuntimeErrorSource::Exception(_))
+    }
+
+    /// Returns the exception, if the `RuntimeError` is an exception thrown
+    /// by Wasm code that no handler caught.
+    pub fn exception(&self) -> Option<&VMException> {
+        match &self.inner.source {
+            RuntimeErrorSource::Exception(exception) => Some(exception),
+            _ => None,
+        }
+    }
+
     /// Returns true if the `RuntimeError` is the same as T
     pub fn is<T: Error + 'static>(&self) -> bool {
         match &self.inner.source {
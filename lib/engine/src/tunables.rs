@@ -8,7 +8,7 @@ use wasmer_types::{
     ModuleInfo, TableIndex, TableType,
 };
 use wasmer_vm::MemoryError;
-use wasmer_vm::{Global, Memory, Table};
+use wasmer_vm::{Global, InstanceAllocator, Memory, Table};
 use wasmer_vm::{MemoryStyle, TableStyle};
 use wasmer_vm::{VMMemoryDefinition, VMTableDefinition};
 
@@ -57,6 +57,31 @@ pub trait Tunables: MemoryUsage {
         vm_definition_location: NonNull<VMTableDefinition>,
     ) -> Result<Arc<dyn Table>, String>;
 
+    /// Allocate the data of an instance of `module`, with the locations of
+    /// its memory and table definitions.
+    fn allocate_instance(
+        &self,
+        module: &ModuleInfo,
+    ) -> Result<
+        (
+            InstanceAllocator,
+            Vec<NonNull<VMMemoryDefinition>>,
+            Vec<NonNull<VMTableDefinition>>,
+        ),
+        LinkError,
+    > {
+        Ok(InstanceAllocator::new(module))
+    }
+
+    /// Whether to initialize the memories of instances copy-on-write from
+    /// images of their data segments, instead of copying the data segments.
+    ///
+    /// The memories created by these tunables must support
+    /// [`Memory::map_image`] for this to have any effect.
+    fn memory_init_cow(&self) -> bool {
+        false
+    }
+
     /// Create a global with an unset value.
     fn create_global(&self, ty: GlobalType) -> Result<Arc<Global>, String> {
         Ok(Arc::new(Global::new(ty)))
@@ -76,7 +101,7 @@ pub trait Tunables: MemoryUsage {
             let mi = MemoryIndex::new(index);
             let ty = &module.memories[mi];
             let style = &memory_styles[mi];
-            let mdl = memory_definition_locations[index];
+            let mdl = memory_definition_locations[index - num_imports];
             memories.push(
                 self.create_vm_memory(ty, style, mdl)
                     .map_err(|e| LinkError::Resource(format!("Failed to create memory: {}", e)))?,
@@ -99,7 +124,7 @@ pub trait Tunables: MemoryUsage {
             let ti = TableIndex::new(index);
             let ty = &module.tables[ti];
             let style = &table_styles[ti];
-            let tdl = table_definition_locations[index];
+            let tdl = table_definition_locations[index - num_imports];
             tables.push(
                 self.create_vm_table(ty, style, tdl)
                     .map_err(LinkError::Resource)?,
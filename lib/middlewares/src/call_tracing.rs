@@ -0,0 +1,857 @@
+//! `call_tracing` is a middleware calling host functions each time a
+//! WebAssembly function is entered or exited, with the arguments or
+//! the results of the call. It can be used to build call traces or
+//! flamegraphs of the guest execution without recompiling it.
+//!
+//! The instrumented module imports functions from the
+//! [`CALL_TRACING_IMPORT_MODULE`] namespace: one entry hook per
+//! distinct list of parameter types and one exit hook per distinct
+//! list of result types among the traced functions. Entry hooks
+//! receive the index of the function, in the original
+//! (non-instrumented) module, followed by its arguments. Exit hooks
+//! receive the index of the function followed by its results.
+//!
+//! A function exiting with a tail call (`return_call` or
+//! `return_call_indirect`) is reported before its callee is entered,
+//! by the exit hook without results: its results are the ones of the
+//! callee, which are reported when the callee exits.
+//!
+//! Those imports are best built with [`call_tracing_imports`].
+//!
+//! Note that functions exiting because of a trap are not reported.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use wasmer::CompilerConfig;
+//! use wasmer_middlewares::CallTracing;
+//!
+//! fn create_call_tracing_middleware(compiler_config: &mut dyn CompilerConfig) {
+//!     // Only trace the functions whose names start with `fib`.
+//!     let call_tracing = Arc::new(CallTracing::new().with_name_pattern("fib*"));
+//!
+//!     compiler_config.push_middleware(call_tracing);
+//! }
+//! ```
+
+use crate::utils::{push_scratch_global, FunctionFilter};
+use loupe::{MemoryUsage, MemoryUsageTracker};
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use wasmer::wasmparser::{Operator, Type as WpType, TypeOrFuncType as WpTypeOrFuncType};
+use wasmer::{
+    ExportIndex, Exports, Function, FunctionMiddleware, FunctionType, ImportObject,
+    LocalFunctionIndex, MiddlewareError, MiddlewareReaderState, Module, ModuleMiddleware,
+    ModuleRewriter, Type, Val,
+};
+use wasmer_types::entity::EntityRef;
+use wasmer_types::{FunctionIndex, GlobalIndex, SignatureIndex};
+
+/// The namespace of the functions imported by modules instrumented
+/// with [`CallTracing`].
+pub const CALL_TRACING_IMPORT_MODULE: &str = "wasmer_call_tracing";
+
+/// The prefix of the names of the entry hooks.
+const ENTER_PREFIX: &str = "on_enter";
+
+/// The prefix of the names of the exit hooks.
+const EXIT_PREFIX: &str = "on_exit";
+
+/// Whether a function is entered or exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallEventKind {
+    /// The function is entered, the values are its arguments.
+    Enter,
+
+    /// The function returns, the values are its results. They are
+    /// empty if the function exits with a tail call.
+    Exit,
+}
+
+/// A traced call, as received by the callback given to
+/// [`call_tracing_imports`].
+#[derive(Debug)]
+pub struct CallEvent<'a> {
+    /// Whether the function is entered or exited.
+    pub kind: CallEventKind,
+
+    /// The index of the function in the original module.
+    pub function_index: FunctionIndex,
+
+    /// The arguments or the results of the function.
+    pub values: &'a [Val],
+}
+
+/// How the body of a traced function is wrapped, so that branches
+/// to the function body label go through the exit hook.
+#[derive(Clone, Debug, MemoryUsage)]
+enum ExitBlock {
+    /// The function has no result.
+    Empty,
+
+    /// The function has a single result.
+    Value(Type),
+
+    /// The function has several results, described by a signature
+    /// without parameters.
+    Signature(SignatureIndex),
+}
+
+#[derive(Clone, Debug, MemoryUsage)]
+struct TracedFunction {
+    /// The entry hook for this function.
+    enter: FunctionIndex,
+
+    /// The exit hook for this function.
+    exit: FunctionIndex,
+
+    /// The exit hook without results, for the tail calls made by this
+    /// function.
+    tail_call_exit: FunctionIndex,
+
+    /// The number of parameters of this function.
+    num_params: usize,
+
+    /// The result types of this function.
+    results: Vec<Type>,
+
+    /// The block wrapping the body.
+    exit_block: ExitBlock,
+}
+
+#[derive(Clone, Debug, MemoryUsage)]
+struct CallTracingIndexes {
+    /// The globals used to spill the results before calling an exit
+    /// hook.
+    scratch: Vec<(Type, GlobalIndex)>,
+
+    /// The number of imported functions before instrumentation.
+    num_imported_functions: usize,
+
+    /// The hooks of each local function, if it is traced.
+    traced_functions: Vec<Option<TracedFunction>>,
+}
+
+/// The module-level call tracing middleware.
+///
+/// # Panic
+///
+/// An instance of `CallTracing` should _not_ be shared among
+/// different modules, since it tracks module-specific information
+/// like the indexes of the imported hooks. Attempts to use a
+/// `CallTracing` instance from multiple modules will result in a
+/// panic.
+pub struct CallTracing {
+    /// Only the functions whose name match this pattern are traced.
+    name_pattern: Option<String>,
+
+    /// Decides which functions are traced.
+    function_filter: Option<FunctionFilter>,
+
+    /// The indexes of the items added to the module.
+    indexes: Mutex<Option<CallTracingIndexes>>,
+}
+
+/// The function-level call tracing middleware.
+pub struct FunctionCallTracing {
+    /// The index of the current function in the original module.
+    function_index: FunctionIndex,
+
+    /// The hooks of the current function, if it is traced.
+    traced: Option<TracedFunction>,
+
+    /// The globals used to spill the results.
+    scratch: Vec<(Type, GlobalIndex)>,
+
+    /// Whether the entry hook has been emitted.
+    entered: bool,
+
+    /// The current control-flow depth, not counting the function body.
+    depth: u32,
+}
+
+impl CallTracing {
+    /// Creates a `CallTracing` middleware tracing all the functions.
+    pub fn new() -> Self {
+        Self {
+            name_pattern: None,
+            function_filter: None,
+            indexes: Mutex::new(None),
+        }
+    }
+
+    /// Only traces the functions whose name matches `pattern`.
+    ///
+    /// `*` matches any sequence of characters and `?` matches any
+    /// single character. The name of a function is taken from the
+    /// `name` custom section, or else from its first export. Functions
+    /// without names are not traced.
+    pub fn with_name_pattern(mut self, pattern: &str) -> Self {
+        self.name_pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Only traces the functions for which `filter` returns `true`.
+    ///
+    /// The filter receives the index of the function in the original
+    /// module and its name, as for [`Self::with_name_pattern`]. When
+    /// both a pattern and a filter are set, a function must satisfy
+    /// both to be traced.
+    pub fn with_function_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(FunctionIndex, Option<&str>) -> bool + Send + Sync + 'static,
+    {
+        self.function_filter = Some(Arc::new(filter));
+        self
+    }
+
+    fn is_traced(&self, index: FunctionIndex, name: Option<&str>) -> bool {
+        let matches_pattern = match (&self.name_pattern, name) {
+            (Some(pattern), Some(name)) => matches_pattern(pattern, name),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let matches_filter = match &self.function_filter {
+            Some(filter) => filter(index, name),
+            None => true,
+        };
+
+        matches_pattern && matches_filter
+    }
+}
+
+impl Default for CallTracing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for CallTracing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallTracing")
+            .field("name_pattern", &self.name_pattern)
+            .field(
+                "function_filter",
+                &self.function_filter.as_ref().map(|_| "<function>"),
+            )
+            .field("indexes", &self.indexes)
+            .finish()
+    }
+}
+
+impl ModuleMiddleware for CallTracing {
+    /// Generates a `FunctionMiddleware` for a given function.
+    fn generate_function_middleware(
+        &self,
+        local_function_index: LocalFunctionIndex,
+    ) -> Box<dyn FunctionMiddleware> {
+        let indexes = self.indexes.lock().unwrap();
+        let indexes = indexes.as_ref().unwrap();
+
+        Box::new(FunctionCallTracing {
+            function_index: FunctionIndex::new(
+                indexes.num_imported_functions + local_function_index.index(),
+            ),
+            traced: indexes.traced_functions[local_function_index.index()].clone(),
+            scratch: indexes.scratch.clone(),
+            entered: false,
+            depth: 0,
+        })
+    }
+
+    /// Rewrites the module to import the hooks. This is called before application on functions begins.
+    fn transform_module(&self, module: &mut ModuleRewriter) {
+        let mut indexes = self.indexes.lock().unwrap();
+
+        if indexes.is_some() {
+            panic!("CallTracing::transform_module: Attempting to use a `CallTracing` middleware from multiple modules.");
+        }
+
+        let module_info = module.module_info();
+
+        // Decide which functions are traced, and collect their
+        // signatures, before the function indexes are shifted by the
+        // new imports.
+        let num_imported_functions = module_info.num_imported_functions;
+        let mut export_names = HashMap::new();
+        for (name, export) in module_info.exports.iter() {
+            if let ExportIndex::Function(index) = export {
+                export_names.entry(*index).or_insert(name.as_str());
+            }
+        }
+        let traced_signatures = (num_imported_functions..module_info.functions.len())
+            .map(FunctionIndex::new)
+            .map(|index| {
+                let name = module_info
+                    .function_names
+                    .get(&index)
+                    .map(String::as_str)
+                    .or_else(|| export_names.get(&index).copied());
+
+                if self.is_traced(index, name) {
+                    Some(module_info.signatures[module_info.functions[index]].clone())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut enter_hooks = HashMap::new();
+        let mut exit_hooks = HashMap::new();
+        let mut scratch_counts = HashMap::<Type, usize>::new();
+        let mut traced_functions = vec![];
+
+        for signature in traced_signatures {
+            let signature = match signature {
+                Some(signature) => signature,
+                None => {
+                    traced_functions.push(None);
+                    continue;
+                }
+            };
+
+            let mut push_hook = |prefix: &str, types: &[Type]| {
+                let mut params = vec![Type::I32];
+                params.extend_from_slice(types);
+                module.push_function_import(
+                    CALL_TRACING_IMPORT_MODULE,
+                    &hook_name(prefix, types),
+                    FunctionType::new(params, vec![]),
+                )
+            };
+            let enter = *enter_hooks
+                .entry(signature.params().to_vec())
+                .or_insert_with(|| push_hook(ENTER_PREFIX, signature.params()));
+            let exit = *exit_hooks
+                .entry(signature.results().to_vec())
+                .or_insert_with(|| push_hook(EXIT_PREFIX, signature.results()));
+            let tail_call_exit = *exit_hooks
+                .entry(vec![])
+                .or_insert_with(|| push_hook(EXIT_PREFIX, &[]));
+
+            // Multi-value bodies are wrapped in a block typed by a
+            // signature without parameters.
+            let exit_block = match signature.results() {
+                [] => ExitBlock::Empty,
+                [result] => ExitBlock::Value(*result),
+                results => ExitBlock::Signature(
+                    module.push_signature(FunctionType::new(vec![], results.to_vec())),
+                ),
+            };
+
+            for ty in signature.results() {
+                let needed = signature.results().iter().filter(|t| *t == ty).count();
+                let count = scratch_counts.entry(*ty).or_default();
+                *count = (*count).max(needed);
+            }
+
+            traced_functions.push(Some(TracedFunction {
+                enter,
+                exit,
+                tail_call_exit,
+                num_params: signature.params().len(),
+                results: signature.results().to_vec(),
+                exit_block,
+            }));
+        }
+
+        let module_info = module.module_info_mut();
+        let mut scratch = vec![];
+        for (ty, count) in scratch_counts {
+            for _ in 0..count {
+                scratch.push((ty, push_scratch_global(module_info, ty)));
+            }
+        }
+
+        *indexes = Some(CallTracingIndexes {
+            scratch,
+            num_imported_functions,
+            traced_functions,
+        });
+    }
+}
+
+impl MemoryUsage for CallTracing {
+    fn size_of_val(&self, tracker: &mut dyn MemoryUsageTracker) -> usize {
+        mem::size_of_val(self) + self.name_pattern.size_of_val(tracker)
+            - mem::size_of_val(&self.name_pattern)
+            + self.indexes.size_of_val(tracker)
+            - mem::size_of_val(&self.indexes)
+    }
+}
+
+impl FunctionCallTracing {
+    /// The scratch global for the `slot`-th result of type `ty`.
+    fn scratch(&self, ty: Type, slot: usize) -> GlobalIndex {
+        self.scratch
+            .iter()
+            .filter(|(scratch_ty, _)| *scratch_ty == ty)
+            .nth(slot)
+            .map(|(_, index)| *index)
+            .expect("no scratch global available for this result")
+    }
+
+    /// Pushes a call to the exit hook, keeping the results on the
+    /// stack.
+    fn push_exit<'a>(&self, traced: &TracedFunction, state: &mut MiddlewareReaderState<'a>) {
+        let scratch = traced
+            .results
+            .iter()
+            .enumerate()
+            .map(|(nth, ty)| {
+                let slot = traced.results[..nth].iter().filter(|t| *t == ty).count();
+                self.scratch(*ty, slot).as_u32()
+            })
+            .collect::<Vec<_>>();
+
+        // Spill the results and push them back before calling the
+        // host, so that they are kept even if the host function
+        // re-enters the instance.
+        for global_index in scratch.iter().rev() {
+            state.push_operator(Operator::GlobalSet {
+                global_index: *global_index,
+            });
+        }
+        for global_index in scratch.iter() {
+            state.push_operator(Operator::GlobalGet {
+                global_index: *global_index,
+            });
+        }
+        state.push_operator(Operator::I32Const {
+            value: self.function_index.as_u32() as i32,
+        });
+        for global_index in scratch.iter() {
+            state.push_operator(Operator::GlobalGet {
+                global_index: *global_index,
+            });
+        }
+        state.push_operator(Operator::Call {
+            function_index: traced.exit.as_u32(),
+        });
+    }
+}
+
+impl fmt::Debug for FunctionCallTracing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionCallTracing")
+            .field("function_index", &self.function_index)
+            .field("traced", &self.traced)
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+impl FunctionMiddleware for FunctionCallTracing {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        let traced = match self.traced.take() {
+            Some(traced) => traced,
+            None => {
+                state.push_operator(operator);
+                return Ok(());
+            }
+        };
+
+        if !self.entered {
+            self.entered = true;
+
+            state.push_operator(Operator::I32Const {
+                value: self.function_index.as_u32() as i32,
+            });
+            for local_index in 0..traced.num_params as u32 {
+                state.push_operator(Operator::LocalGet { local_index });
+            }
+            state.push_operator(Operator::Call {
+                function_index: traced.enter.as_u32(),
+            });
+
+            state.push_operator(Operator::Block {
+                ty: match traced.exit_block {
+                    ExitBlock::Empty => WpTypeOrFuncType::Type(WpType::EmptyBlockType),
+                    ExitBlock::Value(ty) => WpTypeOrFuncType::Type(wp_type(ty)),
+                    ExitBlock::Signature(index) => WpTypeOrFuncType::FuncType(index.as_u32()),
+                },
+            });
+        }
+
+        match operator {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. } => {
+                self.depth += 1;
+                state.push_operator(operator);
+            }
+            Operator::End | Operator::Delegate { .. } if self.depth > 0 => {
+                self.depth -= 1;
+                state.push_operator(operator);
+            }
+            Operator::End => {
+                // The end of the function body: close the wrapping
+                // block and exit.
+                state.push_operator(Operator::End);
+                self.push_exit(&traced, state);
+                state.push_operator(operator);
+            }
+            Operator::Return => {
+                self.push_exit(&traced, state);
+                state.push_operator(operator);
+            }
+            Operator::ReturnCall { .. } | Operator::ReturnCallIndirect { .. } => {
+                // The arguments of the callee are on the stack and stay
+                // there: the exit hook only takes the function index.
+                state.push_operator(Operator::I32Const {
+                    value: self.function_index.as_u32() as i32,
+                });
+                state.push_operator(Operator::Call {
+                    function_index: traced.tail_call_exit.as_u32(),
+                });
+                state.push_operator(operator);
+            }
+            operator => state.push_operator(operator),
+        }
+
+        self.traced = Some(traced);
+
+        Ok(())
+    }
+}
+
+/// Builds the imports needed by a module instrumented with
+/// [`CallTracing`], calling `callback` on each traced event.
+///
+/// # Example
+///
+/// ```rust
+/// use wasmer::{Instance, Module};
+/// use wasmer_middlewares::call_tracing::{call_tracing_imports, CallEventKind};
+///
+/// fn instantiate_with_call_tracing(module: &Module) -> Instance {
+///     let import_object = call_tracing_imports(module, |event| {
+///         if event.kind == CallEventKind::Enter {
+///             println!("calling {:?}{:?}", event.function_index, event.values);
+///         }
+///     });
+///
+///     Instance::new(module, &import_object).unwrap()
+/// }
+/// ```
+pub fn call_tracing_imports<F>(module: &Module, callback: F) -> ImportObject
+where
+    F: Fn(CallEvent) + Send + Sync + 'static,
+{
+    let callback = Arc::new(callback);
+    let mut namespace = Exports::new();
+
+    for import in module.imports().functions() {
+        if import.module() != CALL_TRACING_IMPORT_MODULE {
+            continue;
+        }
+
+        let kind = if import.name().starts_with(ENTER_PREFIX) {
+            CallEventKind::Enter
+        } else {
+            CallEventKind::Exit
+        };
+        let callback = callback.clone();
+        let function = Function::new(module.store(), import.ty(), move |values| {
+            callback(CallEvent {
+                kind,
+                function_index: FunctionIndex::from_u32(values[0].unwrap_i32() as u32),
+                values: &values[1..],
+            });
+            Ok(vec![])
+        });
+
+        namespace.insert(import.name(), function);
+    }
+
+    let mut import_object = ImportObject::new();
+    import_object.register(CALL_TRACING_IMPORT_MODULE, namespace);
+    import_object
+}
+
+/// Returns the name of the hook for a list of value types, such as
+/// `on_enter_i32_f64`.
+fn hook_name(prefix: &str, types: &[Type]) -> String {
+    let mut name = prefix.to_string();
+    for ty in types {
+        name.push('_');
+        name.push_str(&ty.to_string().to_lowercase());
+    }
+    name
+}
+
+fn wp_type(ty: Type) -> WpType {
+    match ty {
+        Type::I32 => WpType::I32,
+        Type::I64 => WpType::I64,
+        Type::F32 => WpType::F32,
+        Type::F64 => WpType::F64,
+        Type::V128 => WpType::V128,
+        Type::ExternRef => WpType::ExternRef,
+        Type::FuncRef => WpType::FuncRef,
+    }
+}
+
+/// Matches `name` against a pattern where `*` matches any sequence of
+/// characters and `?` matches any single character.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `*` in the pattern, and the position
+    // in the name it has been matched up to.
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use wasmer::{
+        wat2wasm, CompilerConfig, Cranelift, Features, Instance, Store, Universal, Value,
+    };
+
+    /// A reported event: kind, function and integer values.
+    type Record = (CallEventKind, u32, Vec<i64>);
+
+    fn bytecode() -> Vec<u8> {
+        wat2wasm(
+            br#"
+            (module
+            (func $double (param $value i64) (result i64)
+                local.get $value
+                i64.const 2
+                i64.mul)
+            (func $double_twice (param $value i64) (result i64)
+                local.get $value
+                call $double
+                call $double)
+            (func $clamp (param $value i32) (result i32)
+                local.get $value
+                i32.const 0
+                local.get $value
+                i32.const 0
+                i32.lt_s
+                br_if 0
+                drop
+                local.get $value
+                i32.const 100
+                i32.gt_s
+                if
+                    i32.const 100
+                    return
+                end)
+            (func $divmod (param $dividend i32) (param $divisor i32) (result i32 i32)
+                local.get $dividend
+                local.get $divisor
+                i32.div_u
+                local.get $dividend
+                local.get $divisor
+                i32.rem_u)
+            (func $count_down (param $value i32) (result i32)
+                local.get $value
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get $value
+                    i32.const 1
+                    i32.sub
+                    return_call $count_down
+                end)
+            (export "double_twice" (func $double_twice))
+            (export "clamp" (func $clamp))
+            (export "divmod" (func $divmod))
+            (export "count_down" (func $count_down)))
+            "#,
+        )
+        .unwrap()
+        .into()
+    }
+
+    fn instantiate(call_tracing: CallTracing) -> (Instance, Arc<Mutex<Vec<Record>>>) {
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(Arc::new(call_tracing));
+        let mut features = Features::default();
+        features.tail_call(true);
+        let store = Store::new(&Universal::new(compiler_config).features(features).engine());
+        let module = Module::new(&store, bytecode()).unwrap();
+
+        let records = Arc::new(Mutex::new(vec![]));
+        let import_object = call_tracing_imports(&module, {
+            let records = records.clone();
+            move |event| {
+                records.lock().unwrap().push((
+                    event.kind,
+                    event.function_index.as_u32(),
+                    event
+                        .values
+                        .iter()
+                        .map(|value| match value {
+                            Value::I32(value) => *value as i64,
+                            Value::I64(value) => *value,
+                            _ => unreachable!(),
+                        })
+                        .collect(),
+                ))
+            }
+        });
+
+        (Instance::new(&module, &import_object).unwrap(), records)
+    }
+
+    #[test]
+    fn traces_calls() {
+        let (instance, records) = instantiate(CallTracing::new());
+        let double_twice = instance
+            .exports
+            .get_function("double_twice")
+            .unwrap()
+            .native::<i64, i64>()
+            .unwrap();
+
+        assert_eq!(double_twice.call(3).unwrap(), 12);
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                (CallEventKind::Enter, 1, vec![3]),
+                (CallEventKind::Enter, 0, vec![3]),
+                (CallEventKind::Exit, 0, vec![6]),
+                (CallEventKind::Enter, 0, vec![6]),
+                (CallEventKind::Exit, 0, vec![12]),
+                (CallEventKind::Exit, 1, vec![12]),
+            ]
+        );
+    }
+
+    #[test]
+    fn traces_branches_and_returns() {
+        let (instance, records) = instantiate(CallTracing::new());
+        let clamp = instance
+            .exports
+            .get_function("clamp")
+            .unwrap()
+            .native::<i32, i32>()
+            .unwrap();
+
+        assert_eq!(clamp.call(-5).unwrap(), 0);
+        assert_eq!(clamp.call(500).unwrap(), 100);
+        assert_eq!(clamp.call(50).unwrap(), 50);
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                (CallEventKind::Enter, 2, vec![-5]),
+                (CallEventKind::Exit, 2, vec![0]),
+                (CallEventKind::Enter, 2, vec![500]),
+                (CallEventKind::Exit, 2, vec![100]),
+                (CallEventKind::Enter, 2, vec![50]),
+                (CallEventKind::Exit, 2, vec![50]),
+            ]
+        );
+    }
+
+    #[test]
+    fn traces_multi_value_functions() {
+        let (instance, records) = instantiate(CallTracing::new());
+        let divmod = instance
+            .exports
+            .get_function("divmod")
+            .unwrap()
+            .native::<(i32, i32), (i32, i32)>()
+            .unwrap();
+
+        assert_eq!(divmod.call(17, 5).unwrap(), (3, 2));
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                (CallEventKind::Enter, 3, vec![17, 5]),
+                (CallEventKind::Exit, 3, vec![3, 2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn traces_tail_calls() {
+        let (instance, records) = instantiate(CallTracing::new());
+        let count_down = instance
+            .exports
+            .get_function("count_down")
+            .unwrap()
+            .native::<i32, i32>()
+            .unwrap();
+
+        assert_eq!(count_down.call(2).unwrap(), 0);
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                (CallEventKind::Enter, 4, vec![2]),
+                (CallEventKind::Exit, 4, vec![]),
+                (CallEventKind::Enter, 4, vec![1]),
+                (CallEventKind::Exit, 4, vec![]),
+                (CallEventKind::Enter, 4, vec![0]),
+                (CallEventKind::Exit, 4, vec![0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_by_name() {
+        let (instance, records) = instantiate(CallTracing::new().with_name_pattern("double_*"));
+        let double_twice = instance
+            .exports
+            .get_function("double_twice")
+            .unwrap()
+            .native::<i64, i64>()
+            .unwrap();
+
+        assert_eq!(double_twice.call(1).unwrap(), 4);
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                (CallEventKind::Enter, 1, vec![1]),
+                (CallEventKind::Exit, 1, vec![4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn pattern_matching() {
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("fib*", "fibonacci"));
+        assert!(matches_pattern("*_alloc", "rust_alloc"));
+        assert!(matches_pattern("a?c*d", "abcxxd"));
+        assert!(!matches_pattern("a?c*d", "abcxxe"));
+        assert!(!matches_pattern("fib", "fibonacci"));
+    }
+}
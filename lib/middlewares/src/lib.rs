@@ -1,6 +1,14 @@
+pub mod call_tracing;
+pub mod memory_tracing;
 pub mod metering;
+pub mod operator_policy;
+
+mod utils;
 
 // The most commonly used symbol are exported at top level of the
 // module. Others are available via modules,
 // e.g. `wasmer_middlewares::metering::get_remaining_points`
+pub use call_tracing::CallTracing;
+pub use memory_tracing::MemoryTracing;
 pub use metering::Metering;
+pub use operator_policy::OperatorPolicy;
@@ -0,0 +1,1115 @@
+//! `memory_tracing` is a middleware reporting every access the
+//! WebAssembly code makes to its linear memory to a host function.
+//! Loads, stores, atomic operations and bulk memory operations are
+//! all instrumented, which makes it possible to track down guest
+//! memory corruptions from the host.
+//!
+//! The instrumented module imports a function named
+//! [`MEMORY_TRACING_IMPORT_FIELD`] from the
+//! [`MEMORY_TRACING_IMPORT_MODULE`] namespace, with the following
+//! signature:
+//!
+//! ```text
+//! (func (param $function i32) (param $address i64) (param $size i32) (param $kind i32))
+//! ```
+//!
+//! * `function` is the index of the function performing the access,
+//!   in the original (non-instrumented) module,
+//! * `address` is the effective address of the access, i.e. the
+//!   dynamic address plus the static offset of the operator,
+//! * `size` is the number of accessed bytes,
+//! * `kind` is a [`MemoryAccessKind`].
+//!
+//! Only the accesses to the first memory of the module can be
+//! traced, and only if it is a 32-bit memory. Instrumenting an access
+//! to another memory, or to a 64-bit memory, fails with a
+//! [`MiddlewareError`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use wasmer::CompilerConfig;
+//! use wasmer_middlewares::MemoryTracing;
+//!
+//! fn create_memory_tracing_middleware(compiler_config: &mut dyn CompilerConfig) {
+//!     // Only trace the accesses to the first 64 KiB of the memory.
+//!     let memory_tracing = Arc::new(MemoryTracing::new().with_address_range(0..0x10000));
+//!
+//!     compiler_config.push_middleware(memory_tracing);
+//! }
+//! ```
+
+use crate::utils::{push_scratch_global, FunctionFilter};
+use loupe::{MemoryUsage, MemoryUsageTracker};
+use std::convert::TryFrom;
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use wasmer::wasmparser::{
+    MemoryImmediate, Operator, Type as WpType, TypeOrFuncType as WpTypeOrFuncType,
+};
+use wasmer::{
+    FunctionMiddleware, FunctionType, LocalFunctionIndex, MiddlewareError, MiddlewareReaderState,
+    ModuleMiddleware, ModuleRewriter, Type,
+};
+use wasmer_types::entity::EntityRef;
+use wasmer_types::{FunctionIndex, GlobalIndex, MemoryIndex};
+
+/// The namespace of the function imported by modules instrumented
+/// with [`MemoryTracing`].
+pub const MEMORY_TRACING_IMPORT_MODULE: &str = "wasmer_memory_tracing";
+
+/// The name of the function imported by modules instrumented with
+/// [`MemoryTracing`].
+pub const MEMORY_TRACING_IMPORT_FIELD: &str = "on_memory_access";
+
+/// Types of the scratch globals used to spill the operands of an
+/// instrumented operator while the effective address is computed.
+const SCRATCH_TYPES: [Type; 7] = [
+    Type::I32,
+    Type::I32,
+    Type::I64,
+    Type::I64,
+    Type::F32,
+    Type::F64,
+    Type::V128,
+];
+
+/// The kind of a memory access, as reported to the host function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MemoryAccessKind {
+    /// The memory is read, e.g. by `i32.load`.
+    Read = 0,
+
+    /// The memory is written, e.g. by `i32.store` or `memory.fill`.
+    Write = 1,
+
+    /// The memory is read and written atomically, e.g. by
+    /// `i32.atomic.rmw.add`.
+    ReadWrite = 2,
+}
+
+impl TryFrom<u32> for MemoryAccessKind {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Read),
+            1 => Ok(Self::Write),
+            2 => Ok(Self::ReadWrite),
+            _ => Err(value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, MemoryUsage)]
+struct MemoryTracingIndexes {
+    /// The imported host function.
+    hook: FunctionIndex,
+
+    /// The global used to spill the address operand.
+    address: GlobalIndex,
+
+    /// The globals used to spill the other operands, one per entry
+    /// of `SCRATCH_TYPES`.
+    scratch: Vec<GlobalIndex>,
+
+    /// The number of imported functions before instrumentation.
+    num_imported_functions: usize,
+
+    /// Whether each local function must be traced.
+    traced_functions: Vec<bool>,
+
+    /// Whether the first memory of the module is a 64-bit memory.
+    memory64: bool,
+}
+
+impl MemoryTracingIndexes {
+    /// The scratch global for the `slot`-th operand of type `ty`.
+    fn scratch(&self, ty: Type, slot: usize) -> GlobalIndex {
+        SCRATCH_TYPES
+            .iter()
+            .zip(self.scratch.iter())
+            .filter(|(scratch_ty, _)| **scratch_ty == ty)
+            .nth(slot)
+            .map(|(_, index)| *index)
+            .expect("no scratch global available for this operand")
+    }
+}
+
+/// The module-level memory tracing middleware.
+///
+/// # Panic
+///
+/// An instance of `MemoryTracing` should _not_ be shared among
+/// different modules, since it tracks module-specific information
+/// like the index of the imported host function. Attempts to use a
+/// `MemoryTracing` instance from multiple modules will result in a
+/// panic.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use wasmer::CompilerConfig;
+/// use wasmer_middlewares::MemoryTracing;
+///
+/// fn create_memory_tracing_middleware(compiler_config: &mut dyn CompilerConfig) {
+///     // Only trace the functions exported under a name starting with `alloc`.
+///     let memory_tracing = Arc::new(MemoryTracing::new().with_function_filter(
+///         |_index, name| matches!(name, Some(name) if name.starts_with("alloc")),
+///     ));
+///
+///     compiler_config.push_middleware(memory_tracing);
+/// }
+/// ```
+pub struct MemoryTracing {
+    /// Only accesses overlapping one of these ranges are reported. All
+    /// accesses are reported if empty.
+    address_ranges: Vec<Range<u64>>,
+
+    /// Decides which functions are instrumented.
+    function_filter: Option<FunctionFilter>,
+
+    /// The indexes of the items added to the module.
+    indexes: Mutex<Option<MemoryTracingIndexes>>,
+}
+
+/// The function-level memory tracing middleware.
+pub struct FunctionMemoryTracing {
+    /// The index of the current function in the original module.
+    function_index: FunctionIndex,
+
+    /// Whether the current function must be traced.
+    traced: bool,
+
+    /// See [`MemoryTracing::address_ranges`].
+    address_ranges: Vec<Range<u64>>,
+
+    /// The indexes of the items added to the module.
+    indexes: MemoryTracingIndexes,
+}
+
+impl MemoryTracing {
+    /// Creates a `MemoryTracing` middleware reporting all the memory
+    /// accesses of all the functions.
+    pub fn new() -> Self {
+        Self {
+            address_ranges: vec![],
+            function_filter: None,
+            indexes: Mutex::new(None),
+        }
+    }
+
+    /// Only reports the accesses overlapping `range`.
+    ///
+    /// This can be called several times, in which case the accesses
+    /// overlapping any of the ranges are reported. The check is done
+    /// by the instrumented code, so filtered-out accesses never reach
+    /// the host.
+    pub fn with_address_range(mut self, range: Range<u64>) -> Self {
+        self.address_ranges.push(range);
+        self
+    }
+
+    /// Only instruments the functions for which `filter` returns
+    /// `true`.
+    ///
+    /// The filter receives the index of the function in the original
+    /// module and its name from the `name` custom section, if any.
+    pub fn with_function_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(FunctionIndex, Option<&str>) -> bool + Send + Sync + 'static,
+    {
+        self.function_filter = Some(Arc::new(filter));
+        self
+    }
+}
+
+impl Default for MemoryTracing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MemoryTracing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryTracing")
+            .field("address_ranges", &self.address_ranges)
+            .field(
+                "function_filter",
+                &self.function_filter.as_ref().map(|_| "<function>"),
+            )
+            .field("indexes", &self.indexes)
+            .finish()
+    }
+}
+
+impl ModuleMiddleware for MemoryTracing {
+    /// Generates a `FunctionMiddleware` for a given function.
+    fn generate_function_middleware(
+        &self,
+        local_function_index: LocalFunctionIndex,
+    ) -> Box<dyn FunctionMiddleware> {
+        let indexes = self.indexes.lock().unwrap().clone().unwrap();
+
+        Box::new(FunctionMemoryTracing {
+            function_index: FunctionIndex::new(
+                indexes.num_imported_functions + local_function_index.index(),
+            ),
+            traced: indexes.traced_functions[local_function_index.index()],
+            address_ranges: self.address_ranges.clone(),
+            indexes,
+        })
+    }
+
+    /// Rewrites the module to import the hook. This is called before application on functions begins.
+    fn transform_module(&self, module: &mut ModuleRewriter) {
+        let mut indexes = self.indexes.lock().unwrap();
+
+        if indexes.is_some() {
+            panic!("MemoryTracing::transform_module: Attempting to use a `MemoryTracing` middleware from multiple modules.");
+        }
+
+        let module_info = module.module_info();
+
+        // Decide which functions are traced before the function
+        // indexes are shifted by the new import.
+        let num_imported_functions = module_info.num_imported_functions;
+        let traced_functions = (num_imported_functions..module_info.functions.len())
+            .map(FunctionIndex::new)
+            .map(|index| match &self.function_filter {
+                Some(filter) => filter(
+                    index,
+                    module_info.function_names.get(&index).map(String::as_str),
+                ),
+                None => true,
+            })
+            .collect();
+
+        let memory64 = matches!(
+            module_info.memories.get(MemoryIndex::new(0)),
+            Some(memory) if memory.memory64
+        );
+
+        let hook = module.push_function_import(
+            MEMORY_TRACING_IMPORT_MODULE,
+            MEMORY_TRACING_IMPORT_FIELD,
+            FunctionType::new(vec![Type::I32, Type::I64, Type::I32, Type::I32], vec![]),
+        );
+
+        let module_info = module.module_info_mut();
+        let address = push_scratch_global(module_info, Type::I32);
+        let scratch = SCRATCH_TYPES
+            .iter()
+            .map(|ty| push_scratch_global(module_info, *ty))
+            .collect();
+
+        *indexes = Some(MemoryTracingIndexes {
+            hook,
+            address,
+            scratch,
+            num_imported_functions,
+            traced_functions,
+            memory64,
+        });
+    }
+}
+
+impl MemoryUsage for MemoryTracing {
+    fn size_of_val(&self, tracker: &mut dyn MemoryUsageTracker) -> usize {
+        mem::size_of_val(self)
+            + self.address_ranges.capacity() * mem::size_of::<Range<u64>>()
+            + self.indexes.size_of_val(tracker)
+            - mem::size_of_val(&self.indexes)
+    }
+}
+
+/// How an operator accesses the linear memory.
+enum Access {
+    /// The operator pops an address followed by `operands`, and
+    /// accesses `size` bytes at the address plus `offset` in `memory`.
+    Static {
+        memory: u32,
+        offset: u64,
+        size: u32,
+        kind: MemoryAccessKind,
+        operands: &'static [Type],
+    },
+
+    /// The operator pops a destination, a source (or value) and a
+    /// length, and writes the destination in `memory`. `memory.copy`
+    /// also reads the source in `source_memory`.
+    Bulk {
+        copy: bool,
+        memory: u32,
+        source_memory: u32,
+    },
+}
+
+impl Access {
+    fn read(memarg: &MemoryImmediate, size: u32) -> Self {
+        Self::Static {
+            memory: memarg.memory,
+            offset: memarg.offset,
+            size,
+            kind: MemoryAccessKind::Read,
+            operands: &[],
+        }
+    }
+
+    fn write(memarg: &MemoryImmediate, size: u32, operand: &'static [Type]) -> Self {
+        Self::Static {
+            memory: memarg.memory,
+            offset: memarg.offset,
+            size,
+            kind: MemoryAccessKind::Write,
+            operands: operand,
+        }
+    }
+
+    fn read_write(memarg: &MemoryImmediate, size: u32, operands: &'static [Type]) -> Self {
+        Self::Static {
+            memory: memarg.memory,
+            offset: memarg.offset,
+            size,
+            kind: MemoryAccessKind::ReadWrite,
+            operands,
+        }
+    }
+
+    /// Returns how `operator` accesses the memory, if it does.
+    fn of(operator: &Operator) -> Option<Self> {
+        const I32: &[Type] = &[Type::I32];
+        const I64: &[Type] = &[Type::I64];
+        const F32: &[Type] = &[Type::F32];
+        const F64: &[Type] = &[Type::F64];
+        const V128: &[Type] = &[Type::V128];
+        const I32_I32: &[Type] = &[Type::I32, Type::I32];
+        const I64_I64: &[Type] = &[Type::I64, Type::I64];
+
+        Some(match operator {
+            Operator::I32Load8S { memarg }
+            | Operator::I32Load8U { memarg }
+            | Operator::I64Load8S { memarg }
+            | Operator::I64Load8U { memarg }
+            | Operator::I32AtomicLoad8U { memarg }
+            | Operator::I64AtomicLoad8U { memarg }
+            | Operator::V128Load8Splat { memarg } => Self::read(memarg, 1),
+            Operator::I32Load16S { memarg }
+            | Operator::I32Load16U { memarg }
+            | Operator::I64Load16S { memarg }
+            | Operator::I64Load16U { memarg }
+            | Operator::I32AtomicLoad16U { memarg }
+            | Operator::I64AtomicLoad16U { memarg }
+            | Operator::V128Load16Splat { memarg } => Self::read(memarg, 2),
+            Operator::I32Load { memarg }
+            | Operator::F32Load { memarg }
+            | Operator::I64Load32S { memarg }
+            | Operator::I64Load32U { memarg }
+            | Operator::I32AtomicLoad { memarg }
+            | Operator::I64AtomicLoad32U { memarg }
+            | Operator::V128Load32Splat { memarg }
+            | Operator::V128Load32Zero { memarg } => Self::read(memarg, 4),
+            Operator::I64Load { memarg }
+            | Operator::F64Load { memarg }
+            | Operator::I64AtomicLoad { memarg }
+            | Operator::V128Load8x8S { memarg }
+            | Operator::V128Load8x8U { memarg }
+            | Operator::V128Load16x4S { memarg }
+            | Operator::V128Load16x4U { memarg }
+            | Operator::V128Load32x2S { memarg }
+            | Operator::V128Load32x2U { memarg }
+            | Operator::V128Load64Splat { memarg }
+            | Operator::V128Load64Zero { memarg } => Self::read(memarg, 8),
+            Operator::V128Load { memarg } => Self::read(memarg, 16),
+
+            // Lane loads also pop the vector to update.
+            Operator::V128Load8Lane { memarg, .. } => Self::Static {
+                memory: memarg.memory,
+                offset: memarg.offset,
+                size: 1,
+                kind: MemoryAccessKind::Read,
+                operands: V128,
+            },
+            Operator::V128Load16Lane { memarg, .. } => Self::Static {
+                memory: memarg.memory,
+                offset: memarg.offset,
+                size: 2,
+                kind: MemoryAccessKind::Read,
+                operands: V128,
+            },
+            Operator::V128Load32Lane { memarg, .. } => Self::Static {
+                memory: memarg.memory,
+                offset: memarg.offset,
+                size: 4,
+                kind: MemoryAccessKind::Read,
+                operands: V128,
+            },
+            Operator::V128Load64Lane { memarg, .. } => Self::Static {
+                memory: memarg.memory,
+                offset: memarg.offset,
+                size: 8,
+                kind: MemoryAccessKind::Read,
+                operands: V128,
+            },
+
+            Operator::I32Store8 { memarg } | Operator::I32AtomicStore8 { memarg } => {
+                Self::write(memarg, 1, I32)
+            }
+            Operator::I64Store8 { memarg } | Operator::I64AtomicStore8 { memarg } => {
+                Self::write(memarg, 1, I64)
+            }
+            Operator::I32Store16 { memarg } | Operator::I32AtomicStore16 { memarg } => {
+                Self::write(memarg, 2, I32)
+            }
+            Operator::I64Store16 { memarg } | Operator::I64AtomicStore16 { memarg } => {
+                Self::write(memarg, 2, I64)
+            }
+            Operator::I32Store { memarg } | Operator::I32AtomicStore { memarg } => {
+                Self::write(memarg, 4, I32)
+            }
+            Operator::I64Store32 { memarg } | Operator::I64AtomicStore32 { memarg } => {
+                Self::write(memarg, 4, I64)
+            }
+            Operator::F32Store { memarg } => Self::write(memarg, 4, F32),
+            Operator::I64Store { memarg } | Operator::I64AtomicStore { memarg } => {
+                Self::write(memarg, 8, I64)
+            }
+            Operator::F64Store { memarg } => Self::write(memarg, 8, F64),
+            Operator::V128Store { memarg } => Self::write(memarg, 16, V128),
+            Operator::V128Store8Lane { memarg, .. } => Self::write(memarg, 1, V128),
+            Operator::V128Store16Lane { memarg, .. } => Self::write(memarg, 2, V128),
+            Operator::V128Store32Lane { memarg, .. } => Self::write(memarg, 4, V128),
+            Operator::V128Store64Lane { memarg, .. } => Self::write(memarg, 8, V128),
+
+            Operator::I32AtomicRmw8AddU { memarg }
+            | Operator::I32AtomicRmw8SubU { memarg }
+            | Operator::I32AtomicRmw8AndU { memarg }
+            | Operator::I32AtomicRmw8OrU { memarg }
+            | Operator::I32AtomicRmw8XorU { memarg }
+            | Operator::I32AtomicRmw8XchgU { memarg } => Self::read_write(memarg, 1, I32),
+            Operator::I64AtomicRmw8AddU { memarg }
+            | Operator::I64AtomicRmw8SubU { memarg }
+            | Operator::I64AtomicRmw8AndU { memarg }
+            | Operator::I64AtomicRmw8OrU { memarg }
+            | Operator::I64AtomicRmw8XorU { memarg }
+            | Operator::I64AtomicRmw8XchgU { memarg } => Self::read_write(memarg, 1, I64),
+            Operator::I32AtomicRmw16AddU { memarg }
+            | Operator::I32AtomicRmw16SubU { memarg }
+            | Operator::I32AtomicRmw16AndU { memarg }
+            | Operator::I32AtomicRmw16OrU { memarg }
+            | Operator::I32AtomicRmw16XorU { memarg }
+            | Operator::I32AtomicRmw16XchgU { memarg } => Self::read_write(memarg, 2, I32),
+            Operator::I64AtomicRmw16AddU { memarg }
+            | Operator::I64AtomicRmw16SubU { memarg }
+            | Operator::I64AtomicRmw16AndU { memarg }
+            | Operator::I64AtomicRmw16OrU { memarg }
+            | Operator::I64AtomicRmw16XorU { memarg }
+            | Operator::I64AtomicRmw16XchgU { memarg } => Self::read_write(memarg, 2, I64),
+            Operator::I32AtomicRmwAdd { memarg }
+            | Operator::I32AtomicRmwSub { memarg }
+            | Operator::I32AtomicRmwAnd { memarg }
+            | Operator::I32AtomicRmwOr { memarg }
+            | Operator::I32AtomicRmwXor { memarg }
+            | Operator::I32AtomicRmwXchg { memarg } => Self::read_write(memarg, 4, I32),
+            Operator::I64AtomicRmw32AddU { memarg }
+            | Operator::I64AtomicRmw32SubU { memarg }
+            | Operator::I64AtomicRmw32AndU { memarg }
+            | Operator::I64AtomicRmw32OrU { memarg }
+            | Operator::I64AtomicRmw32XorU { memarg }
+            | Operator::I64AtomicRmw32XchgU { memarg } => Self::read_write(memarg, 4, I64),
+            Operator::I64AtomicRmwAdd { memarg }
+            | Operator::I64AtomicRmwSub { memarg }
+            | Operator::I64AtomicRmwAnd { memarg }
+            | Operator::I64AtomicRmwOr { memarg }
+            | Operator::I64AtomicRmwXor { memarg }
+            | Operator::I64AtomicRmwXchg { memarg } => Self::read_write(memarg, 8, I64),
+
+            // Compare-exchanges pop the expected and the replacement values.
+            Operator::I32AtomicRmw8CmpxchgU { memarg } => Self::read_write(memarg, 1, I32_I32),
+            Operator::I64AtomicRmw8CmpxchgU { memarg } => Self::read_write(memarg, 1, I64_I64),
+            Operator::I32AtomicRmw16CmpxchgU { memarg } => Self::read_write(memarg, 2, I32_I32),
+            Operator::I64AtomicRmw16CmpxchgU { memarg } => Self::read_write(memarg, 2, I64_I64),
+            Operator::I32AtomicRmwCmpxchg { memarg } => Self::read_write(memarg, 4, I32_I32),
+            Operator::I64AtomicRmw32CmpxchgU { memarg } => Self::read_write(memarg, 4, I64_I64),
+            Operator::I64AtomicRmwCmpxchg { memarg } => Self::read_write(memarg, 8, I64_I64),
+
+            Operator::MemoryFill { mem } | Operator::MemoryInit { mem, .. } => Self::Bulk {
+                copy: false,
+                memory: *mem,
+                source_memory: *mem,
+            },
+            Operator::MemoryCopy { src, dst } => Self::Bulk {
+                copy: true,
+                memory: *dst,
+                source_memory: *src,
+            },
+
+            _ => return None,
+        })
+    }
+}
+
+/// The number of accessed bytes, as passed to the host function.
+#[derive(Clone, Copy)]
+enum Length {
+    /// Statically known.
+    Const(u32),
+
+    /// Held by an `i32` global.
+    Global(GlobalIndex),
+}
+
+impl FunctionMemoryTracing {
+    /// Pushes the computation of the effective address `base + offset`
+    /// as an `i64`.
+    fn push_address<'a>(
+        &self,
+        state: &mut MiddlewareReaderState<'a>,
+        base: GlobalIndex,
+        offset: u64,
+    ) {
+        state.extend(&[
+            Operator::GlobalGet {
+                global_index: base.as_u32(),
+            },
+            Operator::I64ExtendI32U,
+        ]);
+
+        if offset != 0 {
+            state.extend(&[
+                Operator::I64Const {
+                    value: offset as i64,
+                },
+                Operator::I64Add,
+            ]);
+        }
+    }
+
+    /// Pushes a call to the host function for the access of `length`
+    /// bytes at `base + offset`, guarded by the address ranges if any.
+    fn push_hook<'a>(
+        &self,
+        state: &mut MiddlewareReaderState<'a>,
+        base: GlobalIndex,
+        offset: u64,
+        length: Length,
+        kind: MemoryAccessKind,
+    ) {
+        if !self.address_ranges.is_empty() {
+            for (nth, range) in self.address_ranges.iter().enumerate() {
+                // address < range.end && address + length > range.start
+                self.push_address(state, base, offset);
+                state.extend(&[
+                    Operator::I64Const {
+                        value: range.end as i64,
+                    },
+                    Operator::I64LtU,
+                ]);
+                self.push_address(state, base, offset);
+                match length {
+                    Length::Const(length) => state.push_operator(Operator::I64Const {
+                        value: length as i64,
+                    }),
+                    Length::Global(global) => state.extend(&[
+                        Operator::GlobalGet {
+                            global_index: global.as_u32(),
+                        },
+                        Operator::I64ExtendI32U,
+                    ]),
+                }
+                state.extend(&[
+                    Operator::I64Add,
+                    Operator::I64Const {
+                        value: range.start as i64,
+                    },
+                    Operator::I64GtU,
+                    Operator::I32And,
+                ]);
+
+                if nth > 0 {
+                    state.push_operator(Operator::I32Or);
+                }
+            }
+
+            state.push_operator(Operator::If {
+                ty: WpTypeOrFuncType::Type(WpType::EmptyBlockType),
+            });
+        }
+
+        state.push_operator(Operator::I32Const {
+            value: self.function_index.as_u32() as i32,
+        });
+        self.push_address(state, base, offset);
+        state.push_operator(match length {
+            Length::Const(length) => Operator::I32Const {
+                value: length as i32,
+            },
+            Length::Global(global) => Operator::GlobalGet {
+                global_index: global.as_u32(),
+            },
+        });
+        state.extend(&[
+            Operator::I32Const { value: kind as i32 },
+            Operator::Call {
+                function_index: self.indexes.hook.as_u32(),
+            },
+        ]);
+
+        if !self.address_ranges.is_empty() {
+            state.push_operator(Operator::End);
+        }
+    }
+}
+
+impl fmt::Debug for FunctionMemoryTracing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionMemoryTracing")
+            .field("function_index", &self.function_index)
+            .field("traced", &self.traced)
+            .field("address_ranges", &self.address_ranges)
+            .field("indexes", &self.indexes)
+            .finish()
+    }
+}
+
+impl FunctionMiddleware for FunctionMemoryTracing {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        let access = match Access::of(&operator) {
+            Some(access) if self.traced => access,
+            _ => {
+                state.push_operator(operator);
+                return Ok(());
+            }
+        };
+
+        // Addresses are spilled to `i32` globals and reported without
+        // the memory index, so only the accesses to a 32-bit first
+        // memory can be traced.
+        let (memory, source_memory) = match access {
+            Access::Static { memory, .. } => (memory, memory),
+            Access::Bulk {
+                memory,
+                source_memory,
+                ..
+            } => (memory, source_memory),
+        };
+        if memory != 0 || source_memory != 0 {
+            return Err(MiddlewareError::new(
+                "memory_tracing",
+                format!(
+                    "accesses to memory {} cannot be traced, only the first memory is supported",
+                    memory.max(source_memory)
+                ),
+            ));
+        }
+        if self.indexes.memory64 {
+            return Err(MiddlewareError::new(
+                "memory_tracing",
+                "accesses to 64-bit memories cannot be traced",
+            ));
+        }
+
+        // Spill the operands to the scratch globals, then push them
+        // back before calling the host, so that the operator still
+        // sees them even if the host function re-enters the instance.
+        let address = self.indexes.address;
+        match access {
+            Access::Static {
+                offset,
+                size,
+                kind,
+                operands,
+                ..
+            } => {
+                let scratch = |nth: usize| {
+                    let ty = operands[nth];
+                    let slot = operands[..nth].iter().filter(|t| **t == ty).count();
+                    self.indexes.scratch(ty, slot)
+                };
+
+                for nth in (0..operands.len()).rev() {
+                    state.push_operator(Operator::GlobalSet {
+                        global_index: scratch(nth).as_u32(),
+                    });
+                }
+                state.extend(&[
+                    Operator::GlobalSet {
+                        global_index: address.as_u32(),
+                    },
+                    Operator::GlobalGet {
+                        global_index: address.as_u32(),
+                    },
+                ]);
+                for nth in 0..operands.len() {
+                    state.push_operator(Operator::GlobalGet {
+                        global_index: scratch(nth).as_u32(),
+                    });
+                }
+
+                self.push_hook(state, address, offset, Length::Const(size), kind);
+            }
+
+            Access::Bulk { copy, .. } => {
+                let source = self.indexes.scratch(Type::I32, 0);
+                let length = self.indexes.scratch(Type::I32, 1);
+
+                state.extend(&[
+                    Operator::GlobalSet {
+                        global_index: length.as_u32(),
+                    },
+                    Operator::GlobalSet {
+                        global_index: source.as_u32(),
+                    },
+                    Operator::GlobalSet {
+                        global_index: address.as_u32(),
+                    },
+                    Operator::GlobalGet {
+                        global_index: address.as_u32(),
+                    },
+                    Operator::GlobalGet {
+                        global_index: source.as_u32(),
+                    },
+                    Operator::GlobalGet {
+                        global_index: length.as_u32(),
+                    },
+                ]);
+
+                if copy {
+                    // The host function may re-enter the instance and
+                    // overwrite the scratch globals, so the destination
+                    // and the length are kept on the stack across the
+                    // first call and spilled again for the second.
+                    state.extend(&[
+                        Operator::GlobalGet {
+                            global_index: address.as_u32(),
+                        },
+                        Operator::GlobalGet {
+                            global_index: length.as_u32(),
+                        },
+                    ]);
+                    self.push_hook(
+                        state,
+                        source,
+                        0,
+                        Length::Global(length),
+                        MemoryAccessKind::Read,
+                    );
+                    state.extend(&[
+                        Operator::GlobalSet {
+                            global_index: length.as_u32(),
+                        },
+                        Operator::GlobalSet {
+                            global_index: address.as_u32(),
+                        },
+                    ]);
+                }
+                self.push_hook(
+                    state,
+                    address,
+                    0,
+                    Length::Global(length),
+                    MemoryAccessKind::Write,
+                );
+            }
+        }
+
+        state.push_operator(operator);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::convert::TryInto;
+    use wasmer::{
+        imports, wat2wasm, CompileError, CompilerConfig, Cranelift, Features, Function, Instance,
+        Module, Store, Universal, WasmerEnv,
+    };
+
+    /// A reported access: function, address, size and kind.
+    type Record = (u32, u64, u32, MemoryAccessKind);
+
+    #[derive(Clone, Default, WasmerEnv)]
+    struct Accesses {
+        accesses: Arc<Mutex<Vec<Record>>>,
+    }
+
+    fn bytecode() -> Vec<u8> {
+        wat2wasm(
+            br#"
+            (module
+            (memory 1)
+            (func $store (param $address i32) (param $value i64)
+                local.get $address
+                local.get $value
+                i64.store offset=4)
+            (func $load (param $address i32) (result i32)
+                local.get $address
+                i32.load8_u)
+            (func $copy (param $to i32) (param $from i32) (param $length i32)
+                local.get $to
+                local.get $from
+                local.get $length
+                memory.copy)
+            (func $store_and_load (param $address i32) (param $value i64) (result i32)
+                local.get $address
+                local.get $value
+                call $store
+                local.get $address
+                call $load)
+            (func $fill (param $to i32) (param $value i32) (param $length i32)
+                local.get $to
+                local.get $value
+                local.get $length
+                memory.fill)
+            (export "store_and_load" (func $store_and_load))
+            (export "copy" (func $copy))
+            (export "fill" (func $fill)))
+            "#,
+        )
+        .unwrap()
+        .into()
+    }
+
+    fn compile(memory_tracing: MemoryTracing, wat: &str) -> Result<Module, CompileError> {
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(Arc::new(memory_tracing));
+        let mut features = Features::default();
+        features.memory64(true).multi_memory(true);
+        let store = Store::new(&Universal::new(compiler_config).features(features).engine());
+        Module::new(&store, wat)
+    }
+
+    fn instantiate(memory_tracing: MemoryTracing) -> (Instance, Accesses) {
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(Arc::new(memory_tracing));
+        let store = Store::new(&Universal::new(compiler_config).engine());
+        let module = Module::new(&store, bytecode()).unwrap();
+
+        let env = Accesses::default();
+        let on_memory_access = Function::new_native_with_env(
+            &store,
+            env.clone(),
+            |env: &Accesses, function: u32, address: u64, size: u32, kind: u32| {
+                env.accesses.lock().unwrap().push((
+                    function,
+                    address,
+                    size,
+                    kind.try_into().unwrap(),
+                ));
+            },
+        );
+        let import_object = imports! {
+            MEMORY_TRACING_IMPORT_MODULE => {
+                MEMORY_TRACING_IMPORT_FIELD => on_memory_access,
+            },
+        };
+
+        (Instance::new(&module, &import_object).unwrap(), env)
+    }
+
+    #[test]
+    fn reports_loads_and_stores() {
+        let (instance, env) = instantiate(MemoryTracing::new());
+        let store_and_load = instance
+            .exports
+            .get_function("store_and_load")
+            .unwrap()
+            .native::<(i32, i64), i32>()
+            .unwrap();
+
+        // The value is stored at `address + 4`, and the byte at
+        // `address` is loaded back.
+        assert_eq!(store_and_load.call(16, 0x2a).unwrap(), 0);
+        assert_eq!(store_and_load.call(12, 0x2a).unwrap(), 0);
+        assert_eq!(
+            *env.accesses.lock().unwrap(),
+            vec![
+                (0, 20, 8, MemoryAccessKind::Write),
+                (1, 16, 1, MemoryAccessKind::Read),
+                (0, 16, 8, MemoryAccessKind::Write),
+                (1, 12, 1, MemoryAccessKind::Read),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_bulk_operations() {
+        let (instance, env) = instantiate(MemoryTracing::new());
+        let copy = instance
+            .exports
+            .get_function("copy")
+            .unwrap()
+            .native::<(i32, i32, i32), ()>()
+            .unwrap();
+
+        copy.call(100, 200, 10).unwrap();
+        assert_eq!(
+            *env.accesses.lock().unwrap(),
+            vec![
+                (2, 200, 10, MemoryAccessKind::Read),
+                (2, 100, 10, MemoryAccessKind::Write),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_bulk_operations_when_the_host_re_enters() {
+        #[derive(Clone, Default, WasmerEnv)]
+        struct ReEntrant {
+            accesses: Accesses,
+            fill: Arc<Mutex<Option<Function>>>,
+        }
+
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(Arc::new(MemoryTracing::new()));
+        let store = Store::new(&Universal::new(compiler_config).engine());
+        let module = Module::new(&store, bytecode()).unwrap();
+
+        // The first read calls `fill`, whose own access overwrites the
+        // scratch globals of the `memory.copy` being reported.
+        let env = ReEntrant::default();
+        let on_memory_access = Function::new_native_with_env(
+            &store,
+            env.clone(),
+            |env: &ReEntrant, function: u32, address: u64, size: u32, kind: u32| {
+                let kind = kind.try_into().unwrap();
+                env.accesses
+                    .accesses
+                    .lock()
+                    .unwrap()
+                    .push((function, address, size, kind));
+                if kind == MemoryAccessKind::Read {
+                    let fill = env.fill.lock().unwrap().take();
+                    if let Some(fill) = fill {
+                        fill.call(&[300.into(), 0.into(), 5.into()]).unwrap();
+                    }
+                }
+            },
+        );
+        let import_object = imports! {
+            MEMORY_TRACING_IMPORT_MODULE => {
+                MEMORY_TRACING_IMPORT_FIELD => on_memory_access,
+            },
+        };
+        let instance = Instance::new(&module, &import_object).unwrap();
+        *env.fill.lock().unwrap() = Some(instance.exports.get_function("fill").unwrap().clone());
+        let copy = instance
+            .exports
+            .get_function("copy")
+            .unwrap()
+            .native::<(i32, i32, i32), ()>()
+            .unwrap();
+
+        copy.call(100, 200, 10).unwrap();
+        assert_eq!(
+            *env.accesses.accesses.lock().unwrap(),
+            vec![
+                (2, 200, 10, MemoryAccessKind::Read),
+                (4, 300, 5, MemoryAccessKind::Write),
+                (2, 100, 10, MemoryAccessKind::Write),
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_by_address_range() {
+        let (instance, env) = instantiate(MemoryTracing::new().with_address_range(18..19));
+        let store_and_load = instance
+            .exports
+            .get_function("store_and_load")
+            .unwrap()
+            .native::<(i32, i64), i32>()
+            .unwrap();
+
+        store_and_load.call(16, 1).unwrap();
+        store_and_load.call(12, 1).unwrap();
+        assert_eq!(
+            *env.accesses.lock().unwrap(),
+            vec![(0, 16, 8, MemoryAccessKind::Write)]
+        );
+    }
+
+    #[test]
+    fn filters_by_function() {
+        let (instance, env) =
+            instantiate(MemoryTracing::new().with_function_filter(|index, _| index.as_u32() == 1));
+        let store_and_load = instance
+            .exports
+            .get_function("store_and_load")
+            .unwrap()
+            .native::<(i32, i64), i32>()
+            .unwrap();
+
+        assert_eq!(store_and_load.call(16, 1).unwrap(), 0);
+        assert_eq!(
+            *env.accesses.lock().unwrap(),
+            vec![(1, 16, 1, MemoryAccessKind::Read)]
+        );
+    }
+
+    #[test]
+    fn rejects_64_bit_memories() {
+        let error = compile(
+            MemoryTracing::new(),
+            r#"
+            (module
+            (memory i64 1)
+            (func (param $address i64) (result i32)
+                local.get $address
+                i32.load))
+            "#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("accesses to 64-bit memories cannot be traced"));
+
+        // Untraced functions may still access the memory.
+        compile(
+            MemoryTracing::new().with_function_filter(|_, _| false),
+            r#"
+            (module
+            (memory i64 1)
+            (func (param $address i64) (result i32)
+                local.get $address
+                i32.load))
+            "#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn rejects_accesses_to_other_memories() {
+        let error = compile(
+            MemoryTracing::new(),
+            r#"
+            (module
+            (memory $first 1)
+            (memory $second 1)
+            (func (param $address i32) (result i32)
+                local.get $address
+                i32.load $first
+                local.get $address
+                i32.load $second
+                i32.add))
+            "#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("accesses to memory 1 cannot be traced"));
+    }
+}
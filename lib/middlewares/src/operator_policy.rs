@@ -0,0 +1,587 @@
+//! `operator_policy` is a middleware rejecting, at compile time, the
+//! modules using operators forbidden by a policy. Operators can be
+//! allowed or denied one by one, by the name of their
+//! `wasmparser::Operator` variant (e.g. `"MemoryGrow"`), or by class
+//! (e.g. [`OperatorClass::Float`]).
+//!
+//! Unlike `Features`, which enables or disables whole proposals, this
+//! makes it possible to forbid e.g. floating point operators for
+//! deterministic execution, or `memory.grow` for modules running with
+//! a fixed amount of memory.
+//!
+//! A rejected operator makes the compilation fail with a
+//! `MiddlewareError` naming the function and the offset in the module
+//! of the operator.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use wasmer::CompilerConfig;
+//! use wasmer_middlewares::{operator_policy::OperatorClass, OperatorPolicy};
+//!
+//! fn create_operator_policy_middleware(compiler_config: &mut dyn CompilerConfig) {
+//!     // Reject non-deterministic operators.
+//!     let operator_policy = Arc::new(
+//!         OperatorPolicy::allow_all()
+//!             .deny_class(OperatorClass::Float)
+//!             .deny_class(OperatorClass::Simd)
+//!             .deny_class(OperatorClass::Atomic),
+//!     );
+//!
+//!     compiler_config.push_middleware(operator_policy);
+//! }
+//! ```
+
+use loupe::MemoryUsage;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use wasmer::wasmparser::Operator;
+use wasmer::{
+    FunctionMiddleware, LocalFunctionIndex, MiddlewareError, MiddlewareReaderState,
+    ModuleMiddleware,
+};
+use wasmer_types::entity::EntityRef;
+use wasmer_types::{FunctionIndex, ModuleInfo};
+
+/// The name of the middleware, as reported in the errors.
+const MIDDLEWARE_NAME: &str = "operator_policy";
+
+/// A class of operators, to be allowed or denied as a whole by an
+/// [`OperatorPolicy`].
+///
+/// An operator may belong to several classes, e.g. `f32x4.add` is
+/// both a [`OperatorClass::Float`] and a [`OperatorClass::Simd`]
+/// operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, MemoryUsage)]
+pub enum OperatorClass {
+    /// Structured control flow, branches, direct calls, `drop` and
+    /// `select`.
+    Control,
+
+    /// Accesses to locals and globals.
+    Variable,
+
+    /// Operators taking or producing `f32` or `f64` values, including
+    /// the conversions and the floating point SIMD operators.
+    Float,
+
+    /// `v128` operators.
+    Simd,
+
+    /// Atomic memory operators, including `memory.atomic.wait` and
+    /// `memory.atomic.notify`.
+    Atomic,
+
+    /// `memory.grow`.
+    MemoryGrow,
+
+    /// `call_indirect` and `return_call_indirect`.
+    CallIndirect,
+
+    /// Bulk memory and table operators: `memory.copy`, `memory.fill`,
+    /// `memory.init`, `data.drop`, `table.copy`, `table.init` and
+    /// `elem.drop`.
+    BulkMemory,
+}
+
+impl OperatorClass {
+    /// Whether `operator`, named `name`, belongs to this class.
+    fn contains(self, operator: &Operator, name: &str) -> bool {
+        match self {
+            Self::Control => matches!(
+                operator,
+                Operator::Unreachable
+                    | Operator::Nop
+                    | Operator::Block { .. }
+                    | Operator::Loop { .. }
+                    | Operator::If { .. }
+                    | Operator::Else
+                    | Operator::Try { .. }
+                    | Operator::Catch { .. }
+                    | Operator::Throw { .. }
+                    | Operator::Rethrow { .. }
+                    | Operator::Delegate { .. }
+                    | Operator::CatchAll
+                    | Operator::End
+                    | Operator::Br { .. }
+                    | Operator::BrIf { .. }
+                    | Operator::BrTable { .. }
+                    | Operator::Return
+                    | Operator::Call { .. }
+                    | Operator::ReturnCall { .. }
+                    | Operator::Drop
+                    | Operator::Select
+                    | Operator::TypedSelect { .. }
+            ),
+            Self::Variable => matches!(
+                operator,
+                Operator::LocalGet { .. }
+                    | Operator::LocalSet { .. }
+                    | Operator::LocalTee { .. }
+                    | Operator::GlobalGet { .. }
+                    | Operator::GlobalSet { .. }
+            ),
+            Self::Float => name.contains("F32") || name.contains("F64"),
+            Self::Simd => {
+                name.starts_with("V128")
+                    || ["I8x16", "I16x8", "I32x4", "I64x2", "F32x4", "F64x2"]
+                        .iter()
+                        .any(|prefix| name.contains(prefix))
+            }
+            Self::Atomic => name.contains("Atomic"),
+            Self::MemoryGrow => matches!(operator, Operator::MemoryGrow { .. }),
+            Self::CallIndirect => matches!(
+                operator,
+                Operator::CallIndirect { .. } | Operator::ReturnCallIndirect { .. }
+            ),
+            Self::BulkMemory => matches!(
+                operator,
+                Operator::MemoryCopy { .. }
+                    | Operator::MemoryFill { .. }
+                    | Operator::MemoryInit { .. }
+                    | Operator::DataDrop { .. }
+                    | Operator::TableCopy { .. }
+                    | Operator::TableInit { .. }
+                    | Operator::ElemDrop { .. }
+            ),
+        }
+    }
+}
+
+/// The module-level operator policy middleware.
+///
+/// Whether an operator is allowed is decided by, in order:
+///
+/// 1. the rule given for this operator with
+///    [`OperatorPolicy::allow_operator`] or
+///    [`OperatorPolicy::deny_operator`], if any,
+/// 2. the rules given for the classes of this operator, if any; an
+///    operator is denied as soon as one of its classes is denied,
+/// 3. the default verdict of the policy.
+///
+/// The same `OperatorPolicy` can be used for several modules. The
+/// function names reported in the errors are the ones of the module
+/// being compiled.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use wasmer::CompilerConfig;
+/// use wasmer_middlewares::{operator_policy::OperatorClass, OperatorPolicy};
+///
+/// fn create_operator_policy_middleware(compiler_config: &mut dyn CompilerConfig) {
+///     // Only allow integer arithmetic on locals, and memory loads.
+///     let operator_policy = Arc::new(
+///         OperatorPolicy::deny_all()
+///             .allow_class(OperatorClass::Control)
+///             .allow_class(OperatorClass::Variable)
+///             .allow_operator("I32Const")
+///             .allow_operator("I32Add")
+///             .allow_operator("I32Load"),
+///     );
+///
+///     compiler_config.push_middleware(operator_policy);
+/// }
+/// ```
+#[derive(Debug, MemoryUsage)]
+pub struct OperatorPolicy {
+    /// The verdict for the operators without any matching rule.
+    allow_by_default: bool,
+
+    /// The verdict for each class with a rule.
+    classes: Vec<(OperatorClass, bool)>,
+
+    /// The verdict for each operator with a rule, by name.
+    operators: HashMap<String, bool>,
+
+    /// The names of the functions of the module being compiled.
+    function_names: Mutex<Option<FunctionNames>>,
+}
+
+#[derive(Clone, Debug, MemoryUsage)]
+struct FunctionNames {
+    /// The number of imported functions of the module.
+    num_imported_functions: usize,
+
+    /// The names from the `name` custom section.
+    names: HashMap<FunctionIndex, String>,
+}
+
+/// The function-level operator policy middleware.
+#[derive(Debug)]
+pub struct FunctionOperatorPolicy {
+    /// The index of the current function.
+    function_index: FunctionIndex,
+
+    /// The name of the current function, if any.
+    function_name: Option<String>,
+
+    /// See [`OperatorPolicy::allow_by_default`].
+    allow_by_default: bool,
+
+    /// See [`OperatorPolicy::classes`].
+    classes: Vec<(OperatorClass, bool)>,
+
+    /// See [`OperatorPolicy::operators`].
+    operators: HashMap<String, bool>,
+}
+
+impl OperatorPolicy {
+    /// Creates a policy allowing all the operators but the ones
+    /// explicitly denied.
+    pub fn allow_all() -> Self {
+        Self::new(true)
+    }
+
+    /// Creates a policy denying all the operators but the ones
+    /// explicitly allowed.
+    ///
+    /// Note that the control operators, such as the final `end` of
+    /// every function, must be allowed for any function to compile.
+    pub fn deny_all() -> Self {
+        Self::new(false)
+    }
+
+    fn new(allow_by_default: bool) -> Self {
+        Self {
+            allow_by_default,
+            classes: vec![],
+            operators: HashMap::new(),
+            function_names: Mutex::new(None),
+        }
+    }
+
+    /// Allows the operators of `class`, unless they belong to a denied
+    /// class.
+    pub fn allow_class(self, class: OperatorClass) -> Self {
+        self.with_class(class, true)
+    }
+
+    /// Denies the operators of `class`.
+    pub fn deny_class(self, class: OperatorClass) -> Self {
+        self.with_class(class, false)
+    }
+
+    fn with_class(mut self, class: OperatorClass, allowed: bool) -> Self {
+        self.classes.retain(|(existing, _)| *existing != class);
+        self.classes.push((class, allowed));
+        self
+    }
+
+    /// Allows the operator named `name`, whatever its classes.
+    ///
+    /// The name is the one of the `wasmparser::Operator` variant,
+    /// e.g. `"I32Add"` or `"MemoryGrow"`.
+    pub fn allow_operator(mut self, name: &str) -> Self {
+        self.operators.insert(name.to_string(), true);
+        self
+    }
+
+    /// Denies the operator named `name`, whatever its classes.
+    ///
+    /// The name is the one of the `wasmparser::Operator` variant,
+    /// e.g. `"I32Add"` or `"MemoryGrow"`.
+    pub fn deny_operator(mut self, name: &str) -> Self {
+        self.operators.insert(name.to_string(), false);
+        self
+    }
+}
+
+impl ModuleMiddleware for OperatorPolicy {
+    /// Generates a `FunctionMiddleware` for a given function.
+    fn generate_function_middleware(
+        &self,
+        local_function_index: LocalFunctionIndex,
+    ) -> Box<dyn FunctionMiddleware> {
+        let function_names = self.function_names.lock().unwrap();
+        let function_names = function_names
+            .as_ref()
+            .expect("OperatorPolicy::generate_function_middleware: The module has not been transformed by the middleware");
+        let function_index = FunctionIndex::new(
+            function_names.num_imported_functions + local_function_index.index(),
+        );
+
+        Box::new(FunctionOperatorPolicy {
+            function_index,
+            function_name: function_names.names.get(&function_index).cloned(),
+            allow_by_default: self.allow_by_default,
+            classes: self.classes.clone(),
+            operators: self.operators.clone(),
+        })
+    }
+
+    /// Transforms a `ModuleInfo` struct in-place. This is called before application on functions begins.
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        *self.function_names.lock().unwrap() = Some(FunctionNames {
+            num_imported_functions: module_info.num_imported_functions,
+            names: module_info.function_names.clone(),
+        });
+    }
+}
+
+impl FunctionOperatorPolicy {
+    /// Whether `operator`, named `name`, is allowed.
+    fn is_allowed(&self, operator: &Operator, name: &str) -> bool {
+        if let Some(allowed) = self.operators.get(name) {
+            return *allowed;
+        }
+
+        let mut verdict = None;
+        for (class, allowed) in &self.classes {
+            if class.contains(operator, name) {
+                verdict = Some(verdict.unwrap_or(true) && *allowed);
+            }
+        }
+
+        verdict.unwrap_or(self.allow_by_default)
+    }
+}
+
+impl FunctionMiddleware for FunctionOperatorPolicy {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        let name = operator_name(&operator);
+
+        if !self.is_allowed(&operator, name) {
+            let function = match &self.function_name {
+                Some(function_name) => {
+                    format!("{} (`{}`)", self.function_index.index(), function_name)
+                }
+                None => self.function_index.index().to_string(),
+            };
+
+            return Err(MiddlewareError::new(
+                MIDDLEWARE_NAME,
+                format!(
+                    "operator `{}` is not allowed, in function {} at offset {:#x}",
+                    name,
+                    function,
+                    state.operator_offset()
+                ),
+            ));
+        }
+
+        state.push_operator(operator);
+
+        Ok(())
+    }
+}
+
+/// Returns the name of the `Operator` variant of `operator`, e.g.
+/// `"I32Add"`, as used in the rules of an [`OperatorPolicy`].
+pub fn operator_name(operator: &Operator) -> &'static str {
+    macro_rules! operator_names {
+        ($($name:ident)*) => {
+            match operator {
+                $(Operator::$name { .. } => stringify!($name),)*
+            }
+        };
+    }
+
+    operator_names! {
+        Unreachable Nop Block Loop If Else Try Catch Throw Rethrow End Br BrIf BrTable Return Call
+        CallIndirect ReturnCall ReturnCallIndirect Delegate CatchAll Drop Select TypedSelect
+        LocalGet LocalSet LocalTee GlobalGet GlobalSet I32Load I64Load F32Load F64Load I32Load8S
+        I32Load8U I32Load16S I32Load16U I64Load8S I64Load8U I64Load16S I64Load16U I64Load32S
+        I64Load32U I32Store I64Store F32Store F64Store I32Store8 I32Store16 I64Store8 I64Store16
+        I64Store32 MemorySize MemoryGrow I32Const I64Const F32Const F64Const RefNull RefIsNull
+        RefFunc I32Eqz I32Eq I32Ne I32LtS I32LtU I32GtS I32GtU I32LeS I32LeU I32GeS I32GeU I64Eqz
+        I64Eq I64Ne I64LtS I64LtU I64GtS I64GtU I64LeS I64LeU I64GeS I64GeU F32Eq F32Ne F32Lt F32Gt
+        F32Le F32Ge F64Eq F64Ne F64Lt F64Gt F64Le F64Ge I32Clz I32Ctz I32Popcnt I32Add I32Sub
+        I32Mul I32DivS I32DivU I32RemS I32RemU I32And I32Or I32Xor I32Shl I32ShrS I32ShrU I32Rotl
+        I32Rotr I64Clz I64Ctz I64Popcnt I64Add I64Sub I64Mul I64DivS I64DivU I64RemS I64RemU I64And
+        I64Or I64Xor I64Shl I64ShrS I64ShrU I64Rotl I64Rotr F32Abs F32Neg F32Ceil F32Floor F32Trunc
+        F32Nearest F32Sqrt F32Add F32Sub F32Mul F32Div F32Min F32Max F32Copysign F64Abs F64Neg
+        F64Ceil F64Floor F64Trunc F64Nearest F64Sqrt F64Add F64Sub F64Mul F64Div F64Min F64Max
+        F64Copysign I32WrapI64 I32TruncF32S I32TruncF32U I32TruncF64S I32TruncF64U I64ExtendI32S
+        I64ExtendI32U I64TruncF32S I64TruncF32U I64TruncF64S I64TruncF64U F32ConvertI32S
+        F32ConvertI32U F32ConvertI64S F32ConvertI64U F32DemoteF64 F64ConvertI32S F64ConvertI32U
+        F64ConvertI64S F64ConvertI64U F64PromoteF32 I32ReinterpretF32 I64ReinterpretF64
+        F32ReinterpretI32 F64ReinterpretI64 I32Extend8S I32Extend16S I64Extend8S I64Extend16S
+        I64Extend32S I32TruncSatF32S I32TruncSatF32U I32TruncSatF64S I32TruncSatF64U
+        I64TruncSatF32S I64TruncSatF32U I64TruncSatF64S I64TruncSatF64U MemoryInit DataDrop
+        MemoryCopy MemoryFill TableInit ElemDrop TableCopy TableFill TableGet TableSet TableGrow
+        TableSize MemoryAtomicNotify MemoryAtomicWait32 MemoryAtomicWait64 AtomicFence
+        I32AtomicLoad I64AtomicLoad I32AtomicLoad8U I32AtomicLoad16U I64AtomicLoad8U
+        I64AtomicLoad16U I64AtomicLoad32U I32AtomicStore I64AtomicStore I32AtomicStore8
+        I32AtomicStore16 I64AtomicStore8 I64AtomicStore16 I64AtomicStore32 I32AtomicRmwAdd
+        I64AtomicRmwAdd I32AtomicRmw8AddU I32AtomicRmw16AddU I64AtomicRmw8AddU I64AtomicRmw16AddU
+        I64AtomicRmw32AddU I32AtomicRmwSub I64AtomicRmwSub I32AtomicRmw8SubU I32AtomicRmw16SubU
+        I64AtomicRmw8SubU I64AtomicRmw16SubU I64AtomicRmw32SubU I32AtomicRmwAnd I64AtomicRmwAnd
+        I32AtomicRmw8AndU I32AtomicRmw16AndU I64AtomicRmw8AndU I64AtomicRmw16AndU
+        I64AtomicRmw32AndU I32AtomicRmwOr I64AtomicRmwOr I32AtomicRmw8OrU I32AtomicRmw16OrU
+        I64AtomicRmw8OrU I64AtomicRmw16OrU I64AtomicRmw32OrU I32AtomicRmwXor I64AtomicRmwXor
+        I32AtomicRmw8XorU I32AtomicRmw16XorU I64AtomicRmw8XorU I64AtomicRmw16XorU
+        I64AtomicRmw32XorU I32AtomicRmwXchg I64AtomicRmwXchg I32AtomicRmw8XchgU I32AtomicRmw16XchgU
+        I64AtomicRmw8XchgU I64AtomicRmw16XchgU I64AtomicRmw32XchgU I32AtomicRmwCmpxchg
+        I64AtomicRmwCmpxchg I32AtomicRmw8CmpxchgU I32AtomicRmw16CmpxchgU I64AtomicRmw8CmpxchgU
+        I64AtomicRmw16CmpxchgU I64AtomicRmw32CmpxchgU V128Load V128Load8x8S V128Load8x8U
+        V128Load16x4S V128Load16x4U V128Load32x2S V128Load32x2U V128Load8Splat V128Load16Splat
+        V128Load32Splat V128Load64Splat V128Load32Zero V128Load64Zero V128Store V128Load8Lane
+        V128Load16Lane V128Load32Lane V128Load64Lane V128Store8Lane V128Store16Lane V128Store32Lane
+        V128Store64Lane V128Const I8x16Shuffle I8x16ExtractLaneS I8x16ExtractLaneU I8x16ReplaceLane
+        I16x8ExtractLaneS I16x8ExtractLaneU I16x8ReplaceLane I32x4ExtractLane I32x4ReplaceLane
+        I64x2ExtractLane I64x2ReplaceLane F32x4ExtractLane F32x4ReplaceLane F64x2ExtractLane
+        F64x2ReplaceLane I8x16Swizzle I8x16Splat I16x8Splat I32x4Splat I64x2Splat F32x4Splat
+        F64x2Splat I8x16Eq I8x16Ne I8x16LtS I8x16LtU I8x16GtS I8x16GtU I8x16LeS I8x16LeU I8x16GeS
+        I8x16GeU I16x8Eq I16x8Ne I16x8LtS I16x8LtU I16x8GtS I16x8GtU I16x8LeS I16x8LeU I16x8GeS
+        I16x8GeU I32x4Eq I32x4Ne I32x4LtS I32x4LtU I32x4GtS I32x4GtU I32x4LeS I32x4LeU I32x4GeS
+        I32x4GeU I64x2Eq I64x2Ne I64x2LtS I64x2GtS I64x2LeS I64x2GeS F32x4Eq F32x4Ne F32x4Lt
+        F32x4Gt F32x4Le F32x4Ge F64x2Eq F64x2Ne F64x2Lt F64x2Gt F64x2Le F64x2Ge V128Not V128And
+        V128AndNot V128Or V128Xor V128Bitselect V128AnyTrue I8x16Abs I8x16Neg I8x16Popcnt
+        I8x16AllTrue I8x16Bitmask I8x16NarrowI16x8S I8x16NarrowI16x8U I8x16Shl I8x16ShrS I8x16ShrU
+        I8x16Add I8x16AddSatS I8x16AddSatU I8x16Sub I8x16SubSatS I8x16SubSatU I8x16MinS I8x16MinU
+        I8x16MaxS I8x16MaxU I8x16RoundingAverageU I16x8ExtAddPairwiseI8x16S
+        I16x8ExtAddPairwiseI8x16U I16x8Abs I16x8Neg I16x8Q15MulrSatS I16x8AllTrue I16x8Bitmask
+        I16x8NarrowI32x4S I16x8NarrowI32x4U I16x8ExtendLowI8x16S I16x8ExtendHighI8x16S
+        I16x8ExtendLowI8x16U I16x8ExtendHighI8x16U I16x8Shl I16x8ShrS I16x8ShrU I16x8Add
+        I16x8AddSatS I16x8AddSatU I16x8Sub I16x8SubSatS I16x8SubSatU I16x8Mul I16x8MinS I16x8MinU
+        I16x8MaxS I16x8MaxU I16x8RoundingAverageU I16x8ExtMulLowI8x16S I16x8ExtMulHighI8x16S
+        I16x8ExtMulLowI8x16U I16x8ExtMulHighI8x16U I32x4ExtAddPairwiseI16x8S
+        I32x4ExtAddPairwiseI16x8U I32x4Abs I32x4Neg I32x4AllTrue I32x4Bitmask I32x4ExtendLowI16x8S
+        I32x4ExtendHighI16x8S I32x4ExtendLowI16x8U I32x4ExtendHighI16x8U I32x4Shl I32x4ShrS
+        I32x4ShrU I32x4Add I32x4Sub I32x4Mul I32x4MinS I32x4MinU I32x4MaxS I32x4MaxU I32x4DotI16x8S
+        I32x4ExtMulLowI16x8S I32x4ExtMulHighI16x8S I32x4ExtMulLowI16x8U I32x4ExtMulHighI16x8U
+        I64x2Abs I64x2Neg I64x2AllTrue I64x2Bitmask I64x2ExtendLowI32x4S I64x2ExtendHighI32x4S
+        I64x2ExtendLowI32x4U I64x2ExtendHighI32x4U I64x2Shl I64x2ShrS I64x2ShrU I64x2Add I64x2Sub
+        I64x2Mul I64x2ExtMulLowI32x4S I64x2ExtMulHighI32x4S I64x2ExtMulLowI32x4U
+        I64x2ExtMulHighI32x4U F32x4Ceil F32x4Floor F32x4Trunc F32x4Nearest F32x4Abs F32x4Neg
+        F32x4Sqrt F32x4Add F32x4Sub F32x4Mul F32x4Div F32x4Min F32x4Max F32x4PMin F32x4PMax
+        F64x2Ceil F64x2Floor F64x2Trunc F64x2Nearest F64x2Abs F64x2Neg F64x2Sqrt F64x2Add F64x2Sub
+        F64x2Mul F64x2Div F64x2Min F64x2Max F64x2PMin F64x2PMax I32x4TruncSatF32x4S
+        I32x4TruncSatF32x4U F32x4ConvertI32x4S F32x4ConvertI32x4U I32x4TruncSatF64x2SZero
+        I32x4TruncSatF64x2UZero F64x2ConvertLowI32x4S F64x2ConvertLowI32x4U F32x4DemoteF64x2Zero
+        F64x2PromoteLowF32x4 I8x16RelaxedSwizzle I32x4RelaxedTruncSatF32x4S
+        I32x4RelaxedTruncSatF32x4U I32x4RelaxedTruncSatF64x2SZero I32x4RelaxedTruncSatF64x2UZero
+        F32x4Fma F32x4Fms F64x2Fma F64x2Fms I8x16LaneSelect I16x8LaneSelect I32x4LaneSelect
+        I64x2LaneSelect F32x4RelaxedMin F32x4RelaxedMax F64x2RelaxedMin F64x2RelaxedMax
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use wasmer::{wat2wasm, CompileError, CompilerConfig, Cranelift, Module, Store, Universal};
+
+    fn compile(operator_policy: OperatorPolicy, wat: &str) -> Result<Module, CompileError> {
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(Arc::new(operator_policy));
+        let store = Store::new(&Universal::new(compiler_config).engine());
+        Module::new(&store, wat2wasm(wat.as_bytes()).unwrap())
+    }
+
+    fn error_message(result: Result<Module, CompileError>) -> String {
+        match result {
+            Err(CompileError::Wasm(wasmer::WasmError::Middleware(error))) => {
+                assert_eq!(error.name, MIDDLEWARE_NAME);
+                error.message
+            }
+            other => panic!("unexpected compilation result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    const WAT: &str = r#"
+        (module
+        (memory 1)
+        (func $add (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.add)
+        (func $average (param f32 f32) (result f32)
+            local.get 0
+            local.get 1
+            f32.add
+            f32.const 2
+            f32.div)
+        (func $grow (result i32)
+            i32.const 1
+            memory.grow))
+        "#;
+
+    #[test]
+    fn operator_names() {
+        assert_eq!(operator_name(&Operator::I32Add), "I32Add");
+        assert_eq!(operator_name(&Operator::I32Const { value: 7 }), "I32Const");
+        assert_eq!(
+            operator_name(&Operator::MemoryGrow {
+                mem: 0,
+                mem_byte: 0
+            }),
+            "MemoryGrow"
+        );
+    }
+
+    #[test]
+    fn denies_classes() {
+        assert!(compile(OperatorPolicy::allow_all(), WAT).is_ok());
+
+        let message = error_message(compile(
+            OperatorPolicy::allow_all().deny_class(OperatorClass::Float),
+            WAT,
+        ));
+        assert!(message
+            .starts_with("operator `F32Add` is not allowed, in function 1 (`average`) at offset "));
+
+        let message = error_message(compile(
+            OperatorPolicy::allow_all().deny_class(OperatorClass::MemoryGrow),
+            WAT,
+        ));
+        assert!(message.contains("`MemoryGrow`"));
+        assert!(message.contains("function 2 (`grow`)"));
+    }
+
+    #[test]
+    fn reports_offsets() {
+        let wasm = wat2wasm(WAT.as_bytes()).unwrap();
+        // `memory.grow` is encoded as `0x40 0x00`, after `i32.const 1`
+        // (`0x41 0x01`) at the end of the last function.
+        let offset = wasm
+            .windows(4)
+            .rposition(|w| w == [0x41, 0x01, 0x40, 0x00])
+            .unwrap()
+            + 2;
+
+        let message = error_message(compile(
+            OperatorPolicy::allow_all().deny_operator("MemoryGrow"),
+            WAT,
+        ));
+        assert!(message.ends_with(&format!("at offset {:#x}", offset)));
+    }
+
+    #[test]
+    fn allows_operators_over_classes() {
+        let integers_only = || {
+            OperatorPolicy::deny_all()
+                .allow_class(OperatorClass::Control)
+                .allow_class(OperatorClass::Variable)
+                .allow_operator("I32Add")
+                .allow_operator("I32Const")
+        };
+
+        let message = error_message(compile(integers_only(), WAT));
+        assert!(message.contains("`F32Add`"));
+
+        let message = error_message(compile(
+            integers_only()
+                .allow_class(OperatorClass::Float)
+                .deny_operator("F32Div"),
+            WAT,
+        ));
+        assert!(message.contains("`F32Div`"));
+
+        assert!(compile(
+            integers_only()
+                .allow_class(OperatorClass::Float)
+                .allow_operator("MemoryGrow"),
+            WAT
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,28 @@
+//! Helpers shared by the middlewares that need to rewrite the module
+//! they are applied on.
+
+use std::sync::Arc;
+use wasmer::{GlobalInit, GlobalType, Mutability, Type};
+use wasmer_types::{FunctionIndex, GlobalIndex, ModuleInfo, V128};
+
+/// Decides which functions are instrumented, given their index in the
+/// original module and their name, if any.
+pub(crate) type FunctionFilter = Arc<dyn Fn(FunctionIndex, Option<&str>) -> bool + Send + Sync>;
+
+/// Appends a mutable global of type `ty`, initialized to zero (or
+/// `ref.null`), to be used as a scratch register by the instrumented
+/// code.
+pub(crate) fn push_scratch_global(module_info: &mut ModuleInfo, ty: Type) -> GlobalIndex {
+    module_info.global_initializers.push(match ty {
+        Type::I32 => GlobalInit::I32Const(0),
+        Type::I64 => GlobalInit::I64Const(0),
+        Type::F32 => GlobalInit::F32Const(0.0),
+        Type::F64 => GlobalInit::F64Const(0.0),
+        Type::V128 => GlobalInit::V128Const(V128::from([0; 16])),
+        Type::ExternRef | Type::FuncRef => GlobalInit::RefNullConst,
+    });
+
+    module_info
+        .globals
+        .push(GlobalType::new(ty, Mutability::Var))
+}
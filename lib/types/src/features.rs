@@ -112,13 +112,18 @@ impl Features {
     /// modules.
     ///
     /// This feature gates items such as the `v128` type and all of its
-    /// operators being in a module.
+    /// operators being in a module. Note that disabling the SIMD feature
+    /// will also disable the relaxed SIMD feature.
     ///
     /// This is `false` by default.
     ///
     /// [proposal]: https://github.com/webassembly/simd
     pub fn simd(&mut self, enable: bool) -> &mut Self {
         self.simd = enable;
+        // In case is false, we disable relaxed SIMD since it depends on SIMD
+        if !enable {
+            self.relaxed_simd(false);
+        }
         self
     }
 
@@ -172,6 +177,12 @@ impl Features {
     ///
     /// This feature gates tail-call functions in WebAssembly.
     ///
+    /// Only Cranelift supports it, and it emulates tail calls rather than
+    /// jumping to the callee: a function that makes tail calls makes a
+    /// libcall on entry, and every tail call goes through a libcall and a
+    /// call of the callee through a trampoline. Tail calls to functions
+    /// with more than 1000 parameters or results are rejected.
+    ///
     /// This is `false` by default.
     ///
     /// [proposal]: https://github.com/webassembly/tail-call
@@ -210,6 +221,8 @@ impl Features {
     /// This feature adds the ability to use multiple memories within a
     /// single Wasm module.
     ///
+    /// Only Cranelift supports it.
+    ///
     /// This is `false` by default.
     ///
     /// [proposal]: https://github.com/WebAssembly/multi-memory
@@ -229,6 +242,8 @@ impl Features {
     /// This feature gates support for linear memory of sizes larger than
     /// 2^32 bits.
     ///
+    /// Only Cranelift supports it.
+    ///
     /// This is `false` by default.
     ///
     /// [proposal]: https://github.com/WebAssembly/memory64
@@ -236,6 +251,77 @@ impl Features {
         self.memory64 = enable;
         self
     }
+
+    /// Configures whether the WebAssembly exception-handling proposal will
+    /// be enabled.
+    ///
+    /// The [WebAssembly exception-handling proposal][proposal] is not
+    /// currently fully standardized and is undergoing development.
+    /// Support for this feature can be enabled through this method for
+    /// appropriate WebAssembly modules.
+    ///
+    /// This feature gates tags and the `try`, `throw` and `rethrow`
+    /// instructions in WebAssembly.
+    ///
+    /// Only Cranelift supports it. Exceptions don't unwind the native
+    /// stack: every call of a module compiled with this feature checks
+    /// whether an exception is pending, which makes calls slower.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [proposal]: https://github.com/WebAssembly/exception-handling
+    pub fn exceptions(&mut self, enable: bool) -> &mut Self {
+        self.exceptions = enable;
+        self
+    }
+
+    /// Configures whether the WebAssembly relaxed SIMD proposal will be
+    /// enabled.
+    ///
+    /// The [WebAssembly relaxed SIMD proposal][proposal] is not currently
+    /// fully standardized and is undergoing development. Support for this
+    /// feature can be enabled through this method for appropriate WebAssembly
+    /// modules.
+    ///
+    /// This feature gates the SIMD operators whose results may depend on
+    /// the platform, such as `f32x4.fma`. Note that enabling the relaxed
+    /// SIMD feature will also enable the SIMD feature.
+    ///
+    /// Only the prototype encoding of the operators, `0xfd 0xa2` to
+    /// `0xfd 0xee`, is supported. Modules using the final encoding of the
+    /// proposal, `0xfd 0x100` to `0xfd 0x113`, fail validation with an
+    /// unknown `0xfd` subopcode error.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [proposal]: https://github.com/WebAssembly/relaxed-simd
+    pub fn relaxed_simd(&mut self, enable: bool) -> &mut Self {
+        self.relaxed_simd = enable;
+        // The relaxed SIMD proposal depends on the SIMD proposal
+        if enable {
+            self.simd(true);
+        }
+        self
+    }
+
+    /// Configures whether the WebAssembly extended constant expressions
+    /// proposal will be enabled.
+    ///
+    /// The [WebAssembly extended constant expressions proposal][proposal]
+    /// is not currently fully standardized and is undergoing development.
+    /// Support for this feature can be enabled through this method for
+    /// appropriate WebAssembly modules.
+    ///
+    /// This feature gates the `add`, `sub` and `mul` integer operators in
+    /// the initializers of globals and the offsets of segments.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [proposal]: https://github.com/WebAssembly/extended-const
+    pub fn extended_const(&mut self, enable: bool) -> &mut Self {
+        self.extended_const = enable;
+        self
+    }
 }
 
 impl Default for Features {
@@ -344,4 +430,26 @@ mod test_features {
         features.memory64(true);
         assert!(features.memory64);
     }
+
+    #[test]
+    fn enable_exceptions() {
+        let mut features = Features::new();
+        features.exceptions(true);
+        assert!(features.exceptions);
+    }
+
+    #[test]
+    fn enable_relaxed_simd() {
+        let mut features = Features::new();
+        features.simd(false).relaxed_simd(true);
+        assert!(features.relaxed_simd);
+        assert!(features.simd);
+    }
+
+    #[test]
+    fn enable_extended_const() {
+        let mut features = Features::new();
+        features.extended_const(true);
+        assert!(features.extended_const);
+    }
 }
@@ -51,6 +51,12 @@ entity_impl!(LocalGlobalIndex);
 #[cfg(feature = "enable-rkyv")]
 entity_impl!(ArchivedLocalGlobalIndex);
 
+/// Index type of a tag defined locally inside the WebAssembly module.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, MemoryUsage)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub struct LocalTagIndex(u32);
+entity_impl!(LocalTagIndex);
+
 /// Index type of a function (imported or local) inside the WebAssembly module.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, MemoryUsage)]
 #[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
@@ -115,6 +121,22 @@ entity_impl!(MemoryIndex);
 #[cfg(feature = "enable-rkyv")]
 entity_impl!(ArchivedMemoryIndex);
 
+/// Index type of an exception tag (imported or local) inside the WebAssembly module.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, MemoryUsage)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "enable-rkyv",
+    derive(RkyvSerialize, RkyvDeserialize, Archive)
+)]
+#[cfg_attr(
+    feature = "enable-rkyv",
+    archive_attr(derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug))
+)]
+pub struct TagIndex(u32);
+entity_impl!(TagIndex);
+#[cfg(feature = "enable-rkyv")]
+entity_impl!(ArchivedTagIndex);
+
 /// Index type of a signature (imported or local) inside the WebAssembly module.
 #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, MemoryUsage)]
 #[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
@@ -199,6 +221,8 @@ pub enum ExportIndex {
     Memory(MemoryIndex),
     /// Global export.
     Global(GlobalIndex),
+    /// Tag export.
+    Tag(TagIndex),
 }
 
 /// An entity to import.
@@ -221,4 +245,6 @@ pub enum ImportIndex {
     Memory(MemoryIndex),
     /// Global import.
     Global(GlobalIndex),
+    /// Tag import.
+    Tag(TagIndex),
 }
@@ -46,6 +46,12 @@ pub enum LibCall {
     /// memory.size for imported functions
     ImportedMemory32Size,
 
+    /// memory.size for local 64-bit memories
+    Memory64Size,
+
+    /// memory.size for imported 64-bit memories
+    ImportedMemory64Size,
+
     /// table.copy
     TableCopy,
 
@@ -100,12 +106,69 @@ pub enum LibCall {
     /// memory.init
     Memory32Init,
 
+    /// memory.copy for local 64-bit memories
+    Memory64Copy,
+
+    /// memory.copy for imported 64-bit memories
+    ImportedMemory64Copy,
+
+    /// memory.fill for local 64-bit memories
+    Memory64Fill,
+
+    /// memory.fill for imported 64-bit memories
+    ImportedMemory64Fill,
+
+    /// memory.init for 64-bit memories
+    Memory64Init,
+
+    /// memory.copy between two different memories
+    MemoryCopyBetween,
+
     /// data.drop
     DataDrop,
 
     /// A custom trap
     RaiseTrap,
 
+    /// memory.atomic.wait32
+    Memory32AtomicWait32,
+
+    /// memory.atomic.wait64
+    Memory32AtomicWait64,
+
+    /// memory.atomic.notify
+    Memory32AtomicNotify,
+
+    /// return_call and return_call_indirect
+    TailCall,
+
+    /// The entry check of functions that make tail calls
+    TailCallEntered,
+
+    /// throw
+    Throw,
+
+    /// Whether an exception is being thrown
+    ExceptionThrown,
+
+    /// Catches the exception being thrown
+    ExceptionCatch,
+
+    /// Whether a caught exception has a given tag
+    ExceptionMatches,
+
+    /// The values of a caught exception
+    ExceptionPayload,
+
+    /// rethrow
+    Rethrow,
+
+    /// Frees a caught exception
+    ExceptionDrop,
+
+    /// The stack limit of the current thread
+    StackLimit,
+
     /// probe for stack overflow. These are emitted for functions which need
     /// when the `enable_probestack` setting is true.
     Probestack,
@@ -125,6 +188,8 @@ impl LibCall {
             Self::TruncF64 => "wasmer_vm_f64_trunc",
             Self::Memory32Size => "wasmer_vm_memory32_size",
             Self::ImportedMemory32Size => "wasmer_vm_imported_memory32_size",
+            Self::Memory64Size => "wasmer_vm_memory64_size",
+            Self::ImportedMemory64Size => "wasmer_vm_imported_memory64_size",
             Self::TableCopy => "wasmer_vm_table_copy",
             Self::TableInit => "wasmer_vm_table_init",
             Self::TableFill => "wasmer_vm_table_fill",
@@ -143,8 +208,27 @@ impl LibCall {
             Self::Memory32Fill => "wasmer_vm_memory32_fill",
             Self::ImportedMemory32Fill => "wasmer_vm_imported_memory32_fill",
             Self::Memory32Init => "wasmer_vm_memory32_init",
+            Self::Memory64Copy => "wasmer_vm_memory64_copy",
+            Self::ImportedMemory64Copy => "wasmer_vm_imported_memory64_copy",
+            Self::Memory64Fill => "wasmer_vm_memory64_fill",
+            Self::ImportedMemory64Fill => "wasmer_vm_imported_memory64_fill",
+            Self::Memory64Init => "wasmer_vm_memory64_init",
+            Self::MemoryCopyBetween => "wasmer_vm_memory_copy_between",
             Self::DataDrop => "wasmer_vm_data_drop",
             Self::RaiseTrap => "wasmer_vm_raise_trap",
+            Self::Memory32AtomicWait32 => "wasmer_vm_memory32_atomic_wait32",
+            Self::Memory32AtomicWait64 => "wasmer_vm_memory32_atomic_wait64",
+            Self::Memory32AtomicNotify => "wasmer_vm_memory32_atomic_notify",
+            Self::TailCall => "wasmer_vm_tail_call",
+            Self::TailCallEntered => "wasmer_vm_tail_call_entered",
+            Self::Throw => "wasmer_vm_throw",
+            Self::ExceptionThrown => "wasmer_vm_exception_thrown",
+            Self::ExceptionCatch => "wasmer_vm_exception_catch",
+            Self::ExceptionMatches => "wasmer_vm_exception_matches",
+            Self::ExceptionPayload => "wasmer_vm_exception_payload",
+            Self::Rethrow => "wasmer_vm_rethrow",
+            Self::ExceptionDrop => "wasmer_vm_exception_drop",
+            Self::StackLimit => "wasmer_vm_stack_limit",
             // We have to do this because macOS requires a leading `_` and it's not
             // a normal function, it's a static variable, so we have to do it manually.
             #[cfg(target_vendor = "apple")]
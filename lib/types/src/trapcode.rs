@@ -65,6 +65,12 @@ pub enum TrapCode {
 
     /// An atomic memory access was attempted with an unaligned pointer.
     UnalignedAtomic = 11,
+
+    /// Execution was interrupted by the host, or ran past its epoch deadline.
+    Interrupt = 12,
+
+    /// A `memory.atomic.wait` was attempted on a memory that isn't shared.
+    AtomicWaitOnUnsharedMemory = 13,
 }
 
 impl TrapCode {
@@ -83,6 +89,8 @@ impl TrapCode {
             Self::BadConversionToInteger => "invalid conversion to integer",
             Self::UnreachableCodeReached => "unreachable",
             Self::UnalignedAtomic => "unaligned atomic access",
+            Self::Interrupt => "interrupted",
+            Self::AtomicWaitOnUnsharedMemory => "expected shared memory",
         }
     }
 }
@@ -102,6 +110,8 @@ impl Display for TrapCode {
             Self::BadConversionToInteger => "bad_toint",
             Self::UnreachableCodeReached => "unreachable",
             Self::UnalignedAtomic => "unalign_atom",
+            Self::Interrupt => "interrupt",
+            Self::AtomicWaitOnUnsharedMemory => "wait_unshared",
         };
         f.write_str(identifier)
     }
@@ -124,6 +134,8 @@ impl FromStr for TrapCode {
             "bad_toint" => Ok(TrapCode::BadConversionToInteger),
             "unreachable" => Ok(TrapCode::UnreachableCodeReached),
             "unalign_atom" => Ok(TrapCode::UnalignedAtomic),
+            "interrupt" => Ok(TrapCode::Interrupt),
+            "wait_unshared" => Ok(TrapCode::AtomicWaitOnUnsharedMemory),
             _ => Err(()),
         }
     }
@@ -134,7 +146,7 @@ mod tests {
     use super::*;
 
     // Everything but user-defined codes.
-    const CODES: [TrapCode; 12] = [
+    const CODES: [TrapCode; 14] = [
         TrapCode::StackOverflow,
         TrapCode::HeapAccessOutOfBounds,
         TrapCode::HeapMisaligned,
@@ -147,6 +159,8 @@ mod tests {
         TrapCode::BadConversionToInteger,
         TrapCode::UnreachableCodeReached,
         TrapCode::UnalignedAtomic,
+        TrapCode::Interrupt,
+        TrapCode::AtomicWaitOnUnsharedMemory,
     ];
 
     #[test]
@@ -1,10 +1,11 @@
 use crate::indexes::{FunctionIndex, GlobalIndex};
 use crate::lib::std::borrow::ToOwned;
+use crate::lib::std::boxed::Box;
 use crate::lib::std::fmt;
 use crate::lib::std::format;
 use crate::lib::std::string::{String, ToString};
 use crate::lib::std::vec::Vec;
-use crate::units::Pages;
+use crate::units::{Pages, WASM_MAX_PAGES64};
 use crate::values::{Value, WasmValueType};
 use loupe::{MemoryUsage, MemoryUsageTracker};
 
@@ -132,6 +133,8 @@ pub enum ExternType {
     Table(TableType),
     /// This external type is the type of a WebAssembly memory.
     Memory(MemoryType),
+    /// This external type is the type of a WebAssembly exception tag.
+    Tag(TagType),
 }
 
 fn is_global_compatible(exported: GlobalType, imported: GlobalType) -> bool {
@@ -178,11 +181,13 @@ fn is_memory_compatible(exported: &MemoryType, imported: &MemoryType) -> bool {
         minimum: exported_minimum,
         maximum: exported_maximum,
         shared: exported_shared,
+        memory64: exported_memory64,
     } = exported;
     let MemoryType {
         minimum: imported_minimum,
         maximum: imported_maximum,
         shared: imported_shared,
+        memory64: imported_memory64,
     } = imported;
 
     imported_minimum <= exported_minimum
@@ -190,6 +195,7 @@ fn is_memory_compatible(exported: &MemoryType, imported: &MemoryType) -> bool {
             || (!exported_maximum.is_none()
                 && imported_maximum.unwrap() >= exported_maximum.unwrap()))
         && exported_shared == imported_shared
+        && exported_memory64 == imported_memory64
 }
 
 macro_rules! accessors {
@@ -222,6 +228,7 @@ impl ExternType {
         (Global(GlobalType) global unwrap_global)
         (Table(TableType) table unwrap_table)
         (Memory(MemoryType) memory unwrap_memory)
+        (Tag(TagType) tag unwrap_tag)
     }
     /// Check if two externs are compatible
     pub fn is_compatible_with(&self, other: &Self) -> bool {
@@ -230,6 +237,7 @@ impl ExternType {
             (Self::Global(a), Self::Global(b)) => is_global_compatible(*a, *b),
             (Self::Table(a), Self::Table(b)) => is_table_compatible(a, b),
             (Self::Memory(a), Self::Memory(b)) => is_memory_compatible(a, b),
+            (Self::Tag(a), Self::Tag(b)) => a == b,
             // The rest of possibilities, are not compatible
             _ => false,
         }
@@ -418,7 +426,7 @@ impl fmt::Display for GlobalType {
 }
 
 /// Globals are initialized via the `const` operators or by referring to another import.
-#[derive(Debug, Clone, Copy, MemoryUsage, PartialEq)]
+#[derive(Debug, Clone, MemoryUsage, PartialEq)]
 #[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
 #[cfg_attr(
     feature = "enable-rkyv",
@@ -444,6 +452,9 @@ pub enum GlobalInit {
     RefNullConst,
     /// A `ref.func <index>`.
     RefFunc(FunctionIndex),
+    /// A constant expression of several operators, from the extended
+    /// constant expressions proposal.
+    Expr(Box<[ConstOperator]>),
 }
 
 impl Eq for GlobalInit {}
@@ -471,6 +482,75 @@ impl GlobalInit {
     }
 }
 
+/// An operator of an extended constant expression, which operates on a
+/// stack of `i32` and `i64` values.
+#[derive(Debug, Clone, Copy, Hash, MemoryUsage, PartialEq, Eq)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "enable-rkyv",
+    derive(RkyvSerialize, RkyvDeserialize, Archive)
+)]
+pub enum ConstOperator {
+    /// An `i32.const`.
+    I32Const(i32),
+    /// An `i64.const`.
+    I64Const(i64),
+    /// A `global.get` of an `i32` or `i64` global.
+    GetGlobal(GlobalIndex),
+    /// An `i32.add`.
+    I32Add,
+    /// An `i32.sub`.
+    I32Sub,
+    /// An `i32.mul`.
+    I32Mul,
+    /// An `i64.add`.
+    I64Add,
+    /// An `i64.sub`.
+    I64Sub,
+    /// An `i64.mul`.
+    I64Mul,
+}
+
+impl ConstOperator {
+    /// Evaluates the constant expression made of `ops`, reading the globals
+    /// it refers to with `global`.
+    ///
+    /// The `i32` values, including the ones `global` returns, are
+    /// zero-extended to 64 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ops` isn't a valid constant expression.
+    pub fn eval(ops: &[Self], mut global: impl FnMut(GlobalIndex) -> u64) -> u64 {
+        let mut stack: Vec<u64> = Vec::with_capacity(ops.len());
+        for op in ops {
+            let value = match *op {
+                Self::I32Const(value) => u64::from(value as u32),
+                Self::I64Const(value) => value as u64,
+                Self::GetGlobal(index) => global(index),
+                binop => {
+                    let b = stack.pop().expect("invalid constant expression");
+                    let a = stack.pop().expect("invalid constant expression");
+                    let (a32, b32) = (a as u32, b as u32);
+                    match binop {
+                        Self::I32Add => u64::from(a32.wrapping_add(b32)),
+                        Self::I32Sub => u64::from(a32.wrapping_sub(b32)),
+                        Self::I32Mul => u64::from(a32.wrapping_mul(b32)),
+                        Self::I64Add => a.wrapping_add(b),
+                        Self::I64Sub => a.wrapping_sub(b),
+                        Self::I64Mul => a.wrapping_mul(b),
+                        Self::I32Const(_) | Self::I64Const(_) | Self::GetGlobal(_) => {
+                            unreachable!()
+                        }
+                    }
+                }
+            };
+            stack.push(value);
+        }
+        stack.pop().expect("invalid constant expression")
+    }
+}
+
 // Table Types
 
 /// A descriptor for a table in a WebAssembly module.
@@ -534,6 +614,12 @@ pub struct MemoryType {
     pub maximum: Option<Pages>,
     /// Whether the memory may be shared between multiple threads.
     pub shared: bool,
+    /// Whether the memory is indexed with 64-bit addresses.
+    ///
+    /// This field was added with the memory64 proposal, so struct literals
+    /// written without it no longer compile: use [`MemoryType::new`] or
+    /// [`MemoryType::new64`] instead.
+    pub memory64: bool,
 }
 
 impl MemoryType {
@@ -547,6 +633,28 @@ impl MemoryType {
             minimum: minimum.into(),
             maximum: maximum.map(Into::into),
             shared,
+            memory64: false,
+        }
+    }
+
+    /// Creates a new descriptor for a WebAssembly memory indexed with 64-bit
+    /// addresses, as defined by the memory64 proposal.
+    pub fn new64<IntoPages>(minimum: IntoPages, maximum: Option<IntoPages>, shared: bool) -> Self
+    where
+        IntoPages: Into<Pages>,
+    {
+        Self {
+            memory64: true,
+            ..Self::new(minimum, maximum, shared)
+        }
+    }
+
+    /// Returns the largest number of pages a memory of this type can have.
+    pub fn max_pages(&self) -> Pages {
+        if self.memory64 {
+            Pages(WASM_MAX_PAGES64)
+        } else {
+            Pages::max_value()
         }
     }
 }
@@ -562,6 +670,49 @@ impl fmt::Display for MemoryType {
     }
 }
 
+// Tag Types
+
+/// A descriptor for a WebAssembly exception tag.
+///
+/// Tags identify the kinds of exceptions that can be thrown and caught,
+/// and describe the values carried by those exceptions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, MemoryUsage)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub struct TagType {
+    /// The types of the values carried by exceptions of the tag.
+    params: Box<[Type]>,
+}
+
+impl TagType {
+    /// Creates a new descriptor for a tag whose exceptions carry values of
+    /// the given types.
+    pub fn new<Params>(params: Params) -> Self
+    where
+        Params: Into<Box<[Type]>>,
+    {
+        Self {
+            params: params.into(),
+        }
+    }
+
+    /// The types of the values carried by exceptions of the tag.
+    pub fn params(&self) -> &[Type] {
+        &self.params
+    }
+}
+
+impl fmt::Display for TagType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let params = self
+            .params
+            .iter()
+            .map(|p| format!("{:?}", p))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "[{}]", params)
+    }
+}
+
 // Import Types
 
 /// A descriptor for an imported value into a wasm module.
@@ -677,4 +828,26 @@ mod tests {
         assert_eq!(ty.params().len(), 9);
         assert_eq!(ty.results().len(), 9);
     }
+
+    #[test]
+    fn eval_const_operators() {
+        use crate::entity::EntityRef;
+        use ConstOperator::*;
+        let globals = |index: GlobalIndex| [u64::from(u32::MAX), 1 << 40][index.index()];
+
+        // 32-bit arithmetic wraps around.
+        let ops = [GetGlobal(GlobalIndex::new(0)), I32Const(2), I32Add];
+        assert_eq!(ConstOperator::eval(&ops, globals), 1);
+        let ops = [I32Const(1), I32Const(2), I32Sub];
+        assert_eq!(ConstOperator::eval(&ops, globals), u64::from(u32::MAX));
+
+        let ops = [
+            GetGlobal(GlobalIndex::new(1)),
+            I64Const(3),
+            I64Mul,
+            I64Const(-1),
+            I64Add,
+        ];
+        assert_eq!(ConstOperator::eval(&ops, globals), (3 << 40) - 1);
+    }
 }
@@ -18,6 +18,14 @@ pub const WASM_PAGE_SIZE: usize = 0x10000;
 /// The number of pages we can have before we run out of byte index space.
 pub const WASM_MAX_PAGES: u32 = 0x10000;
 
+/// The number of pages a 64-bit memory can have before its size no longer
+/// fits in [`Pages`].
+///
+/// The memory64 proposal allows up to 2^48 pages, but [`Pages`] is a `u32`,
+/// so 64-bit memories are limited to just under 256 TiB here. Modules that
+/// declare larger limits are rejected as unsupported when they are compiled.
+pub const WASM_MAX_PAGES64: u32 = u32::MAX;
+
 /// The minimum number of pages allowed.
 pub const WASM_MIN_PAGES: u32 = 0x100;
 
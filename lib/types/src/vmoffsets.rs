@@ -124,9 +124,113 @@ impl VMBuiltinFunctionIndex {
     pub const fn get_externref_dec_index() -> Self {
         Self(25)
     }
+    /// Returns an index for wasm's `memory.atomic.wait32` instruction.
+    pub const fn get_memory_atomic_wait32_index() -> Self {
+        Self(26)
+    }
+    /// Returns an index for wasm's `memory.atomic.wait64` instruction.
+    pub const fn get_memory_atomic_wait64_index() -> Self {
+        Self(27)
+    }
+    /// Returns an index for wasm's `memory.atomic.notify` instruction.
+    pub const fn get_memory_atomic_notify_index() -> Self {
+        Self(28)
+    }
+    /// Returns an index for wasm's `memory.grow` builtin function for
+    /// 64-bit memories.
+    pub const fn get_memory64_grow_index() -> Self {
+        Self(29)
+    }
+    /// Returns an index for wasm's imported `memory.grow` builtin function
+    /// for 64-bit memories.
+    pub const fn get_imported_memory64_grow_index() -> Self {
+        Self(30)
+    }
+    /// Returns an index for wasm's `memory.size` builtin function for 64-bit
+    /// memories.
+    pub const fn get_memory64_size_index() -> Self {
+        Self(31)
+    }
+    /// Returns an index for wasm's imported `memory.size` builtin function
+    /// for 64-bit memories.
+    pub const fn get_imported_memory64_size_index() -> Self {
+        Self(32)
+    }
+    /// Returns an index for wasm's `memory.copy` for locally defined 64-bit
+    /// memories.
+    pub const fn get_memory64_copy_index() -> Self {
+        Self(33)
+    }
+    /// Returns an index for wasm's `memory.copy` for imported 64-bit
+    /// memories.
+    pub const fn get_imported_memory64_copy_index() -> Self {
+        Self(34)
+    }
+    /// Returns an index for wasm's `memory.fill` for locally defined 64-bit
+    /// memories.
+    pub const fn get_memory64_fill_index() -> Self {
+        Self(35)
+    }
+    /// Returns an index for wasm's `memory.fill` for imported 64-bit
+    /// memories.
+    pub const fn get_imported_memory64_fill_index() -> Self {
+        Self(36)
+    }
+    /// Returns an index for wasm's `memory.init` instruction for 64-bit
+    /// memories.
+    pub const fn get_memory64_init_index() -> Self {
+        Self(37)
+    }
+    /// Returns an index for wasm's `memory.copy` between two different
+    /// memories.
+    pub const fn get_memory_copy_between_index() -> Self {
+        Self(38)
+    }
+    /// Returns an index for wasm's `return_call` and
+    /// `return_call_indirect` instructions.
+    pub const fn get_tail_call_index() -> Self {
+        Self(39)
+    }
+    /// Returns an index for the entry check of functions that make tail
+    /// calls.
+    pub const fn get_tail_call_entered_index() -> Self {
+        Self(40)
+    }
+    /// Returns an index for wasm's `throw` instruction.
+    pub const fn get_throw_index() -> Self {
+        Self(41)
+    }
+    /// Returns an index for checking whether an exception is being thrown.
+    pub const fn get_exception_thrown_index() -> Self {
+        Self(42)
+    }
+    /// Returns an index for catching the exception being thrown.
+    pub const fn get_exception_catch_index() -> Self {
+        Self(43)
+    }
+    /// Returns an index for checking the tag of a caught exception.
+    pub const fn get_exception_matches_index() -> Self {
+        Self(44)
+    }
+    /// Returns an index for reading the values of a caught exception.
+    pub const fn get_exception_payload_index() -> Self {
+        Self(45)
+    }
+    /// Returns an index for wasm's `rethrow` instruction.
+    pub const fn get_rethrow_index() -> Self {
+        Self(46)
+    }
+    /// Returns an index for freeing a caught exception.
+    pub const fn get_exception_drop_index() -> Self {
+        Self(47)
+    }
+    /// Returns an index for reading the stack limit of the current thread.
+    pub const fn get_stack_limit_index() -> Self {
+        Self(48)
+    }
     /// Returns the total number of builtin functions.
     pub const fn builtin_functions_total_number() -> u32 {
-        26
+        49
     }
 
     /// Return the index as an u32 number.
@@ -426,6 +530,29 @@ impl VMOffsets {
     }
 }
 
+/// Offsets for `VMInterrupts`.
+impl VMOffsets {
+    /// The offset of the `epoch` field.
+    pub const fn vminterrupts_epoch(&self) -> u8 {
+        0
+    }
+
+    /// The offset of the `epoch_deadline` field.
+    pub const fn vminterrupts_epoch_deadline(&self) -> u8 {
+        8
+    }
+
+    /// The offset of the `thrown_exceptions` field.
+    pub const fn vminterrupts_thrown_exceptions(&self) -> u8 {
+        16
+    }
+
+    /// Return the size of `VMInterrupts`.
+    pub const fn size_of_vminterrupts(&self) -> u8 {
+        24
+    }
+}
+
 /// Offsets for `VMContext`.
 impl VMOffsets {
     /// The offset of the `signature_ids` array.
@@ -525,8 +652,8 @@ impl VMOffsets {
             .unwrap()
     }
 
-    /// Return the size of the `VMContext` allocation.
-    pub fn size_of_vmctx(&self) -> u32 {
+    /// The offset of the pointer to the `VMInterrupts`.
+    pub fn vmctx_interrupts(&self) -> u32 {
         self.vmctx_builtin_functions_begin()
             .checked_add(
                 VMBuiltinFunctionIndex::builtin_functions_total_number()
@@ -536,6 +663,21 @@ impl VMOffsets {
             .unwrap()
     }
 
+    /// The offset of the pointer to the number of exceptions being thrown
+    /// by the code of the store, across all threads.
+    pub fn vmctx_thrown_exceptions(&self) -> u32 {
+        self.vmctx_interrupts()
+            .checked_add(u32::from(self.pointer_size))
+            .unwrap()
+    }
+
+    /// Return the size of the `VMContext` allocation.
+    pub fn size_of_vmctx(&self) -> u32 {
+        self.vmctx_thrown_exceptions()
+            .checked_add(u32::from(self.pointer_size))
+            .unwrap()
+    }
+
     /// Return the offset to `VMSharedSignatureIndex` index `index`.
     pub fn vmctx_vmshared_signature_id(&self, index: SignatureIndex) -> u32 {
         assert_lt!(index.as_u32(), self.num_signature_ids);
@@ -55,8 +55,14 @@ impl UniversalArtifactBuild {
 
         // We try to apply the middleware first
         let mut module = translation.module;
+        // SAFETY: Calling `unwrap` is correct since
+        // `environ.translate()` above will write some data into
+        // `module_translation_state`.
+        let mut module_translation_state = translation.module_translation_state.unwrap();
         let middlewares = compiler.get_middlewares();
-        middlewares.apply_on_module_info(&mut module);
+        middlewares.apply_on_module_info_with_state(&mut module, &mut module_translation_state);
+        let mut function_body_inputs = translation.function_body_inputs;
+        module_translation_state.push_middleware_function_bodies(&mut function_body_inputs);
 
         let compile_info = CompileModuleInfo {
             module: Arc::new(module),
@@ -69,11 +75,8 @@ impl UniversalArtifactBuild {
         let compilation = compiler.compile_module(
             target,
             &compile_info,
-            // SAFETY: Calling `unwrap` is correct since
-            // `environ.translate()` above will write some data into
-            // `module_translation_state`.
-            translation.module_translation_state.as_ref().unwrap(),
-            translation.function_body_inputs,
+            &module_translation_state,
+            function_body_inputs,
         )?;
         let function_call_trampolines = compilation.get_function_call_trampolines();
         let dynamic_function_trampolines = compilation.get_dynamic_function_trampolines();
@@ -0,0 +1,161 @@
+//! Exceptions, from the exception-handling proposal.
+//!
+//! Compiled code doesn't unwind the native stack to throw an exception.
+//! Instead, `throw` leaves the exception pending for the current thread
+//! and branches to the innermost handler of the function, or returns if
+//! there is none. After every call, the caller checks whether an
+//! exception is pending and branches to its own handler if so.
+//!
+//! The check is a load of the count of pending exceptions of the store,
+//! which compiled code reaches through its `VMContext`: only when it isn't
+//! zero is the thread-local state consulted. The count is kept per store,
+//! so that exceptions thrown by other stores don't slow down the calls of
+//! this one. A call chain only runs the code of a single store between
+//! two calls from the host, and an exception doesn't cross those, so the
+//! count of the store of the caller is the one to check.
+//!
+//! An exception still pending when the outermost Wasm frame returns is
+//! turned into a [`Trap::Exception`](crate::Trap::Exception).
+//!
+//! Unwinding the native stack through the unwind information the engines
+//! register would make calls free, but it needs a personality routine
+//! and landing pads in the code of every compiler, which this doesn't.
+//! The cost instead is a load and a branch after every call of a module
+//! compiled with exceptions enabled, and a libcall after every call while
+//! an exception of the store is pending.
+
+use crate::tag::Tag;
+use crate::vmcontext::VMInterrupts;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// An exception: a tag and the values it was thrown with.
+#[derive(Clone)]
+pub struct VMException {
+    tag: Arc<Tag>,
+    values: Box<[u128]>,
+}
+
+impl VMException {
+    /// Create a new exception of `tag` carrying `values`, one slot per
+    /// parameter of the tag.
+    pub fn new(tag: Arc<Tag>, values: Box<[u128]>) -> Self {
+        debug_assert_eq!(tag.ty().params().len(), values.len());
+        Self { tag, values }
+    }
+
+    /// The tag the exception was thrown with.
+    pub fn tag(&self) -> &Arc<Tag> {
+        &self.tag
+    }
+
+    /// The raw values the exception carries, one slot per parameter of
+    /// its tag.
+    pub fn payload(&self) -> &[u128] {
+        &self.values
+    }
+}
+
+impl fmt::Debug for VMException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VMException")
+            .field("tag", self.tag.ty())
+            .finish()
+    }
+}
+
+/// An exception pending for a thread.
+struct Pending {
+    exception: VMException,
+    /// The interrupts of the store whose count of thrown exceptions
+    /// includes this one.
+    interrupts: Arc<VMInterrupts>,
+}
+
+impl Pending {
+    fn thrown(&self) -> &AtomicUsize {
+        &self.interrupts.thrown_exceptions
+    }
+}
+
+thread_local! {
+    static PENDING: RefCell<Option<Pending>> = RefCell::new(None);
+}
+
+/// Leaves `exception`, thrown by the code of the store of `interrupts`,
+/// pending for the current thread.
+pub(crate) fn throw(exception: VMException, interrupts: &Arc<VMInterrupts>) {
+    let thrown = Pending {
+        exception,
+        interrupts: interrupts.clone(),
+    };
+    thrown.thrown().fetch_add(1, Ordering::SeqCst);
+    PENDING.with(|pending| {
+        if let Some(previous) = pending.borrow_mut().replace(thrown) {
+            previous.thrown().fetch_sub(1, Ordering::SeqCst);
+        }
+    });
+}
+
+/// Returns whether an exception is pending for the current thread.
+pub(crate) fn thrown() -> bool {
+    PENDING.with(|pending| pending.borrow().is_some())
+}
+
+/// Takes the exception pending for the current thread, if any.
+pub(crate) fn take() -> Option<VMException> {
+    PENDING.with(|pending| {
+        let pending = pending.borrow_mut().take()?;
+        pending.thrown().fetch_sub(1, Ordering::SeqCst);
+        Some(pending.exception)
+    })
+}
+
+/// Forgets the exception pending for the current thread, after a trap
+/// unwound the frames that would have handled it.
+pub(crate) fn reset() {
+    take();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmer_types::{TagType, Type};
+
+    fn exception() -> VMException {
+        let tag = Arc::new(Tag::new(TagType::new(vec![Type::I32])));
+        VMException::new(tag, vec![42].into())
+    }
+
+    #[test]
+    fn counts_thrown_exceptions_per_store() {
+        let first = Arc::new(VMInterrupts::new());
+        let second = Arc::new(VMInterrupts::new());
+        let count = |interrupts: &VMInterrupts| interrupts.thrown_exceptions.load(Ordering::SeqCst);
+
+        throw(exception(), &first);
+        assert!(thrown());
+        assert_eq!((count(&first), count(&second)), (1, 0));
+
+        // Another thread throwing for the same store.
+        let other = first.clone();
+        std::thread::spawn(move || {
+            throw(exception(), &other);
+            assert_eq!(count(&other), 2);
+            assert_eq!(take().unwrap().payload(), &[42]);
+        })
+        .join()
+        .unwrap();
+        assert_eq!(count(&first), 1);
+
+        // A new exception replaces the pending one.
+        throw(exception(), &second);
+        assert_eq!((count(&first), count(&second)), (0, 1));
+
+        assert!(take().is_some());
+        assert!(!thrown());
+        assert_eq!((count(&first), count(&second)), (0, 0));
+    }
+}
@@ -2,13 +2,16 @@
 // Attributions: https://github.com/wasmerio/wasmer/blob/master/ATTRIBUTIONS.md
 
 use crate::global::Global;
-use crate::instance::WeakOrStrongInstanceRef;
-use crate::memory::Memory;
-use crate::table::Table;
+use crate::instance::{InstanceRef, WeakOrStrongInstanceRef};
+use crate::limiter::ResourceLimitError;
+use crate::memory::{Memory, MemoryError};
+use crate::table::{Table, TableElement};
+use crate::tag::Tag;
 use crate::vmcontext::{VMFunctionBody, VMFunctionEnvironment, VMFunctionKind, VMTrampoline};
 use loupe::MemoryUsage;
+use std::convert::TryFrom;
 use std::sync::Arc;
-use wasmer_types::{FunctionType, MemoryStyle, MemoryType, TableStyle, TableType};
+use wasmer_types::{FunctionType, MemoryStyle, MemoryType, Pages, TableStyle, TableType, TagType};
 
 /// The value of an export passed from one instance to another.
 #[derive(Debug)]
@@ -24,6 +27,9 @@ pub enum VMExtern {
 
     /// A global export value.
     Global(VMGlobal),
+
+    /// A tag export value.
+    Tag(VMTag),
 }
 
 /// A function export value.
@@ -119,6 +125,31 @@ impl VMTable {
         Arc::ptr_eq(&self.from, &other.from)
     }
 
+    /// Grows the table by `delta` elements.
+    ///
+    /// The growth is charged to the resource limiter of the instance
+    /// that exported the table, if any. Returns `None` if the table
+    /// can't be grown by that many elements, and an error if the
+    /// resource limiter denied it.
+    pub fn grow(
+        &self,
+        delta: u32,
+        init_value: TableElement,
+    ) -> Result<Option<u32>, ResourceLimitError> {
+        match self.instance() {
+            Some(instance) => {
+                instance
+                    .as_ref()
+                    .exported_table_grow(self.from.as_ref(), delta, init_value)
+            }
+            None => Ok(self.from.grow(delta, init_value)),
+        }
+    }
+
+    fn instance(&self) -> Option<InstanceRef> {
+        InstanceRef::try_from(self.instance_ref.clone()?).ok()
+    }
+
     /// Converts the stored instance ref into a strong `InstanceRef` if it is weak.
     /// Returns None if it cannot be upgraded.
     pub fn upgrade_instance_ref(&mut self) -> Option<()> {
@@ -174,6 +205,23 @@ impl VMMemory {
         Arc::ptr_eq(&self.from, &other.from)
     }
 
+    /// Grows the memory by `delta` pages.
+    ///
+    /// The growth is charged to the resource limiter of the instance
+    /// that exported the memory, if any.
+    pub fn grow(&self, delta: Pages) -> Result<Pages, MemoryError> {
+        match self.instance() {
+            Some(instance) => instance
+                .as_ref()
+                .exported_memory_grow(self.from.as_ref(), delta),
+            None => self.from.grow(delta),
+        }
+    }
+
+    fn instance(&self) -> Option<InstanceRef> {
+        InstanceRef::try_from(self.instance_ref.clone()?).ok()
+    }
+
     /// Converts the stored instance ref into a strong `InstanceRef` if it is weak.
     /// Returns None if it cannot be upgraded.
     pub fn upgrade_instance_ref(&mut self) -> Option<()> {
@@ -234,3 +282,41 @@ impl From<VMGlobal> for VMExtern {
         Self::Global(global)
     }
 }
+
+/// A tag export value.
+#[derive(Debug, Clone, MemoryUsage)]
+pub struct VMTag {
+    /// The tag, compared by identity.
+    pub from: Arc<Tag>,
+
+    /// A “reference” to the instance through the
+    /// `InstanceRef`. `None` if it is a host tag.
+    pub instance_ref: Option<WeakOrStrongInstanceRef>,
+}
+
+impl VMTag {
+    /// Get the type for this exported tag
+    pub fn ty(&self) -> &TagType {
+        self.from.ty()
+    }
+
+    /// Returns whether or not the two `VMTag`s refer to the same Tag.
+    pub fn same(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.from, &other.from)
+    }
+
+    /// Converts the stored instance ref into a strong `InstanceRef` if it is weak.
+    /// Returns None if it cannot be upgraded.
+    pub fn upgrade_instance_ref(&mut self) -> Option<()> {
+        if let Some(ref mut ir) = self.instance_ref {
+            *ir = ir.upgrade()?;
+        }
+        Some(())
+    }
+}
+
+impl From<VMTag> for VMExtern {
+    fn from(tag: VMTag) -> Self {
+        Self::Tag(tag)
+    }
+}
@@ -4,7 +4,9 @@ use std::cell::UnsafeCell;
 use std::ptr::NonNull;
 use std::sync::Mutex;
 use thiserror::Error;
-use wasmer_types::{GlobalType, Mutability, Type, Value, WasmValueType};
+use wasmer_types::{
+    GlobalType, Mutability, NativeWasmType, Type, VMExternRef, Value, WasmValueType,
+};
 
 #[derive(Debug, MemoryUsage)]
 /// A Global instance
@@ -92,6 +94,53 @@ impl Global {
         }
     }
 
+    /// Get the value of the global as a native type, without converting it
+    /// to a `Value`.
+    ///
+    /// # Safety
+    /// `T::WASM_TYPE` must be the type of the global.
+    pub unsafe fn get_native<T: NativeWasmType>(&self) -> T {
+        let _global_guard = self.lock.lock().unwrap();
+        let definition = &*self.vm_global_definition.get();
+        T::from_binary(match T::WASM_TYPE {
+            Type::I32 => definition.to_i32().into(),
+            Type::I64 => definition.to_i64().into(),
+            Type::F32 => definition.to_f32().to_bits().into(),
+            Type::F64 => definition.to_f64().to_bits().into(),
+            Type::V128 => definition.to_u128() as i128,
+            Type::ExternRef => definition.to_externref().ref_clone().to_binary(),
+            Type::FuncRef => unreachable!("funcrefs have no native type"),
+        })
+    }
+
+    /// Set the value of the global from a native type, without converting
+    /// it from a `Value`.
+    ///
+    /// # Safety
+    /// `T::WASM_TYPE` must be the type of the global.
+    pub unsafe fn set_native<T: NativeWasmType>(&self, val: T) -> Result<(), GlobalError> {
+        let _global_guard = self.lock.lock().unwrap();
+        if self.ty().mutability != Mutability::Var {
+            return Err(GlobalError::ImmutableGlobalCannotBeSet);
+        }
+        let definition = &mut *self.vm_global_definition.get();
+        let bits = val.to_binary();
+        match T::WASM_TYPE {
+            Type::I32 => *definition.as_i32_mut() = bits as i32,
+            Type::I64 => *definition.as_i64_mut() = bits as i64,
+            Type::F32 => *definition.as_f32_mut() = f32::from_bits(bits as u32),
+            Type::F64 => *definition.as_f64_mut() = f64::from_bits(bits as u64),
+            Type::V128 => *definition.as_u128_mut() = bits as u128,
+            Type::ExternRef => {
+                let extern_ref = definition.as_externref_mut();
+                extern_ref.ref_drop();
+                *extern_ref = VMExternRef::from_binary(bits);
+            }
+            Type::FuncRef => unreachable!("funcrefs have no native type"),
+        }
+        Ok(())
+    }
+
     /// Set a value for the global.
     ///
     /// # Safety
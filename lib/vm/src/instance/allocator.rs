@@ -1,10 +1,12 @@
 use super::{Instance, InstanceRef};
+use crate::mmap::{Mmap, MmapPool};
 use crate::vmcontext::{VMMemoryDefinition, VMTableDefinition};
 use crate::VMOffsets;
 use std::alloc::{self, Layout};
 use std::convert::TryFrom;
 use std::mem;
 use std::ptr::{self, NonNull};
+use std::sync::Arc;
 use wasmer_types::entity::EntityRef;
 use wasmer_types::{LocalMemoryIndex, LocalTableIndex, ModuleInfo};
 
@@ -33,6 +35,10 @@ pub struct InstanceAllocator {
     /// The layout of the `instance_ptr` buffer.
     instance_layout: Layout,
 
+    /// The slot of a pool that contains the `instance_ptr` buffer, if it
+    /// is not allocated with `alloc`.
+    slot: Option<Mmap>,
+
     /// Information about the offsets into the `instance_ptr` buffer for
     /// the dynamic fields.
     offsets: VMOffsets,
@@ -45,9 +51,10 @@ pub struct InstanceAllocator {
 
 impl Drop for InstanceAllocator {
     fn drop(&mut self) {
-        if !self.consumed {
+        if !self.consumed && self.slot.is_none() {
             // If `consumed` has not been set, then we still have ownership
-            // over the buffer and must free it.
+            // over the buffer and must free it. A slot is given back to
+            // its pool when it is dropped.
             let instance_ptr = self.instance_ptr.as_ptr();
 
             unsafe {
@@ -86,9 +93,61 @@ impl InstanceAllocator {
             alloc::handle_alloc_error(instance_layout);
         };
 
+        Self::with_buffer(instance_ptr, instance_layout, None, offsets)
+    }
+
+    /// Allocates instance data for use with [`InstanceHandle::new`] in a
+    /// slot of `pool`, like [`InstanceAllocator::new`].
+    ///
+    /// [`InstanceHandle::new`]: super::InstanceHandle::new
+    pub(crate) fn from_pool(
+        module: &ModuleInfo,
+        pool: &Arc<MmapPool>,
+    ) -> Result<
+        (
+            Self,
+            Vec<NonNull<VMMemoryDefinition>>,
+            Vec<NonNull<VMTableDefinition>>,
+        ),
+        String,
+    > {
+        let offsets = VMOffsets::new(mem::size_of::<usize>() as u8, module);
+        let instance_layout = Self::instance_layout(&offsets);
+        if instance_layout.size() > pool.slot_size() {
+            return Err(format!(
+                "the instance needs {} bytes but the slots of the pool have {} bytes",
+                instance_layout.size(),
+                pool.slot_size()
+            ));
+        }
+
+        // Slots are page-aligned, which is enough for `Instance`.
+        let mut slot = pool.allocate(instance_layout.size())?;
+        #[allow(clippy::cast_ptr_alignment)]
+        let instance_ptr = NonNull::new(slot.as_mut_ptr() as *mut Instance).unwrap();
+
+        Ok(Self::with_buffer(
+            instance_ptr,
+            instance_layout,
+            Some(slot),
+            offsets,
+        ))
+    }
+
+    fn with_buffer(
+        instance_ptr: NonNull<Instance>,
+        instance_layout: Layout,
+        slot: Option<Mmap>,
+        offsets: VMOffsets,
+    ) -> (
+        Self,
+        Vec<NonNull<VMMemoryDefinition>>,
+        Vec<NonNull<VMTableDefinition>>,
+    ) {
         let allocator = Self {
             instance_ptr,
             instance_layout,
+            slot,
             offsets,
             consumed: false,
         };
@@ -201,10 +260,11 @@ impl InstanceAllocator {
         }
         let instance = self.instance_ptr;
         let instance_layout = self.instance_layout;
+        let slot = self.slot.take();
 
         // This is correct because of the invariants of `Self` and
         // because we write `Instance` to the pointer in this function.
-        unsafe { InstanceRef::new(instance, instance_layout) }
+        unsafe { InstanceRef::new(instance, instance_layout, slot) }
     }
 
     /// Get the [`VMOffsets`] for the allocated buffer.
@@ -13,21 +13,27 @@ mod r#ref;
 pub use allocator::InstanceAllocator;
 pub use r#ref::{InstanceRef, WeakInstanceRef, WeakOrStrongInstanceRef};
 
+use crate::exception::{self, VMException};
 use crate::export::VMExtern;
 use crate::func_data_registry::VMFuncRef;
 use crate::global::Global;
 use crate::imports::Imports;
+use crate::limiter::{InstanceLimits, ResourceLimitError, ResourceLimiter};
 use crate::memory::{Memory, MemoryError};
+use crate::memory_image::ModuleMemoryImages;
 use crate::table::{Table, TableElement};
+use crate::tag::Tag;
+use crate::tail_call::{self, TailCall};
 use crate::trap::{catch_traps, Trap, TrapCode, TrapHandler};
 use crate::vmcontext::{
     VMBuiltinFunctionsArray, VMCallerCheckedAnyfunc, VMContext, VMFunctionBody,
     VMFunctionEnvironment, VMFunctionImport, VMFunctionKind, VMGlobalDefinition, VMGlobalImport,
-    VMMemoryDefinition, VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition, VMTableImport,
-    VMTrampoline,
+    VMInterrupts, VMMemoryDefinition, VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition,
+    VMTableImport, VMTrampoline,
 };
+use crate::wait_queue::WAIT_QUEUE;
 use crate::{FunctionBodyPtr, VMOffsets};
-use crate::{VMFunction, VMGlobal, VMMemory, VMTable};
+use crate::{VMFunction, VMGlobal, VMMemory, VMTable, VMTag};
 use loupe::{MemoryUsage, MemoryUsageTracker};
 use memoffset::offset_of;
 use more_asserts::assert_lt;
@@ -40,12 +46,14 @@ use std::fmt;
 use std::mem;
 use std::ptr::{self, NonNull};
 use std::slice;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use wasmer_types::entity::{packed_option::ReservedValue, BoxedSlice, EntityRef, PrimaryMap};
 use wasmer_types::{
-    DataIndex, DataInitializer, ElemIndex, ExportIndex, FunctionIndex, GlobalIndex, GlobalInit,
-    LocalFunctionIndex, LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex, MemoryIndex,
-    ModuleInfo, Pages, SignatureIndex, TableIndex, TableInitializer,
+    ConstOperator, DataIndex, DataInitializer, ElemIndex, ExportIndex, FunctionIndex, GlobalIndex,
+    GlobalInit, LocalFunctionIndex, LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex,
+    MemoryIndex, ModuleInfo, Pages, SignatureIndex, TableIndex, TableInitializer, TagIndex, Type,
 };
 
 /// The function pointer to call with data and an [`Instance`] pointer to
@@ -77,6 +85,9 @@ pub(crate) struct Instance {
     /// WebAssembly global data.
     globals: BoxedSlice<LocalGlobalIndex, Arc<Global>>,
 
+    /// WebAssembly tags, imported and locally-defined.
+    tags: BoxedSlice<TagIndex, Arc<Tag>>,
+
     /// Pointers to functions in executable memory.
     functions: BoxedSlice<LocalFunctionIndex, FunctionBodyPtr>,
 
@@ -107,6 +118,14 @@ pub(crate) struct Instance {
     /// functions from other Wasm modules.
     imported_function_envs: BoxedSlice<FunctionIndex, ImportFunctionEnv>,
 
+    /// The interruption state checked by compiled WebAssembly code,
+    /// usually shared with the other instances of the store.
+    interrupts: Arc<VMInterrupts>,
+
+    /// The resources granted by the resource limiter of the store, if any.
+    #[loupe(skip)]
+    limits: Option<InstanceLimits>,
+
     /// Additional context used by compiled WebAssembly code. This
     /// field is last, and represents a dynamically-sized array that
     /// extends beyond the nominal end of the struct (similar to a
@@ -378,6 +397,16 @@ impl Instance {
         unsafe { self.vmctx_plus_offset(self.offsets.vmctx_builtin_functions_begin()) }
     }
 
+    /// Return a pointer to the pointer to the `VMInterrupts`.
+    fn interrupts_ptr(&self) -> *mut *const VMInterrupts {
+        unsafe { self.vmctx_plus_offset(self.offsets.vmctx_interrupts()) }
+    }
+
+    /// Return a pointer to the pointer to the count of pending exceptions.
+    fn thrown_exceptions_ptr(&self) -> *mut *const AtomicUsize {
+        unsafe { self.vmctx_plus_offset(self.offsets.vmctx_thrown_exceptions()) }
+    }
+
     /// Return a reference to the vmctx used by compiled wasm code.
     fn vmctx(&self) -> &VMContext {
         &self.vmctx
@@ -481,7 +510,8 @@ impl Instance {
             .memories
             .get(memory_index)
             .unwrap_or_else(|| panic!("no memory for index {}", memory_index.index()));
-        mem.grow(delta.into())
+        let index = self.module.memory_index(memory_index);
+        self.limited_memory_grow(index, mem.as_ref(), delta.into())
     }
 
     /// Grow imported memory by the specified amount of pages.
@@ -502,7 +532,42 @@ impl Instance {
     {
         let import = self.imported_memory(memory_index);
         let from = import.from.as_ref();
-        from.grow(delta.into())
+        self.limited_memory_grow(memory_index, from, delta.into())
+    }
+
+    /// Grows `memory`, the memory `index` of this instance, charging the
+    /// resource limiter if any.
+    fn limited_memory_grow(
+        &self,
+        index: MemoryIndex,
+        memory: &dyn Memory,
+        delta: Pages,
+    ) -> Result<Pages, MemoryError> {
+        match &self.limits {
+            Some(limits) => limits.memory_grow(index, memory, delta),
+            None => memory.grow(delta),
+        }
+    }
+
+    /// Grows `memory`, a memory exported by this instance, on behalf of
+    /// the host.
+    pub(crate) fn exported_memory_grow(
+        &self,
+        memory: &dyn Memory,
+        delta: Pages,
+    ) -> Result<Pages, MemoryError> {
+        let definition = memory.vmmemory();
+        let index = self.module.memories.keys().find(|&index| {
+            let from = match self.module.local_memory_index(index) {
+                Some(local_index) => self.memories[local_index].as_ref(),
+                None => self.imported_memory(index).from.as_ref(),
+            };
+            from.vmmemory() == definition
+        });
+        match index {
+            Some(index) => self.limited_memory_grow(index, memory, delta),
+            None => memory.grow(delta),
+        }
     }
 
     /// Returns the number of allocated wasm pages.
@@ -545,20 +610,19 @@ impl Instance {
     /// Grow table by the specified amount of elements.
     ///
     /// Returns `None` if table can't be grown by the specified amount
-    /// of elements.
+    /// of elements, and an error if the resource limiter denied it.
     pub(crate) fn table_grow(
         &self,
         table_index: LocalTableIndex,
         delta: u32,
         init_value: TableElement,
-    ) -> Option<u32> {
-        let result = self
+    ) -> Result<Option<u32>, ResourceLimitError> {
+        let table = self
             .tables
             .get(table_index)
-            .unwrap_or_else(|| panic!("no table for index {}", table_index.index()))
-            .grow(delta, init_value);
-
-        result
+            .unwrap_or_else(|| panic!("no table for index {}", table_index.index()));
+        let index = self.module.table_index(table_index);
+        self.limited_table_grow(index, table.as_ref(), delta, init_value)
     }
 
     /// Grow table by the specified amount of elements.
@@ -570,10 +634,47 @@ impl Instance {
         table_index: TableIndex,
         delta: u32,
         init_value: TableElement,
-    ) -> Option<u32> {
+    ) -> Result<Option<u32>, ResourceLimitError> {
         let import = self.imported_table(table_index);
         let from = import.from.as_ref();
-        from.grow(delta, init_value)
+        self.limited_table_grow(table_index, from, delta, init_value)
+    }
+
+    /// Grows `table`, the table `index` of this instance, charging the
+    /// resource limiter if any.
+    fn limited_table_grow(
+        &self,
+        index: TableIndex,
+        table: &dyn Table,
+        delta: u32,
+        init_value: TableElement,
+    ) -> Result<Option<u32>, ResourceLimitError> {
+        match &self.limits {
+            Some(limits) => limits.table_grow(index, table, delta, init_value),
+            None => Ok(table.grow(delta, init_value)),
+        }
+    }
+
+    /// Grows `table`, a table exported by this instance, on behalf of the
+    /// host.
+    pub(crate) fn exported_table_grow(
+        &self,
+        table: &dyn Table,
+        delta: u32,
+        init_value: TableElement,
+    ) -> Result<Option<u32>, ResourceLimitError> {
+        let definition = table.vmtable();
+        let index = self.module.tables.keys().find(|&index| {
+            let from = match self.module.local_table_index(index) {
+                Some(local_index) => self.tables[local_index].as_ref(),
+                None => self.imported_table(index).from.as_ref(),
+            };
+            from.vmtable() == definition
+        });
+        match index {
+            Some(index) => self.limited_table_grow(index, table, delta, init_value),
+            None => Ok(table.grow(delta, init_value)),
+        }
     }
 
     /// Get table element by index.
@@ -734,9 +835,9 @@ impl Instance {
     pub(crate) fn local_memory_copy(
         &self,
         memory_index: LocalMemoryIndex,
-        dst: u32,
-        src: u32,
-        len: u32,
+        dst: u64,
+        src: u64,
+        len: u64,
     ) -> Result<(), Trap> {
         // https://webassembly.github.io/reference-types/core/exec/instructions.html#exec-memory-copy
 
@@ -749,9 +850,9 @@ impl Instance {
     pub(crate) fn imported_memory_copy(
         &self,
         memory_index: MemoryIndex,
-        dst: u32,
-        src: u32,
-        len: u32,
+        dst: u64,
+        src: u64,
+        len: u64,
     ) -> Result<(), Trap> {
         let import = self.imported_memory(memory_index);
         let memory = unsafe { import.definition.as_ref() };
@@ -759,6 +860,26 @@ impl Instance {
         unsafe { memory.memory_copy(dst, src, len) }
     }
 
+    /// Perform a `memory.copy` from one memory into a different one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Trap` error when the source or destination ranges are out of
+    /// bounds.
+    pub(crate) fn memory_copy_between(
+        &self,
+        dst_memory_index: MemoryIndex,
+        src_memory_index: MemoryIndex,
+        dst: u64,
+        src: u64,
+        len: u64,
+    ) -> Result<(), Trap> {
+        let dst_memory = self.get_memory(dst_memory_index);
+        let src_memory = self.get_memory(src_memory_index);
+        // The following memory copy is not synchronized and is not atomic:
+        unsafe { dst_memory.memory_copy_from(&src_memory, dst, src, len) }
+    }
+
     /// Perform the `memory.fill` operation on a locally defined memory.
     ///
     /// # Errors
@@ -767,9 +888,9 @@ impl Instance {
     pub(crate) fn local_memory_fill(
         &self,
         memory_index: LocalMemoryIndex,
-        dst: u32,
+        dst: u64,
         val: u32,
-        len: u32,
+        len: u64,
     ) -> Result<(), Trap> {
         let memory = self.memory(memory_index);
         // The following memory fill is not synchronized and is not atomic:
@@ -784,9 +905,9 @@ impl Instance {
     pub(crate) fn imported_memory_fill(
         &self,
         memory_index: MemoryIndex,
-        dst: u32,
+        dst: u64,
         val: u32,
-        len: u32,
+        len: u64,
     ) -> Result<(), Trap> {
         let import = self.imported_memory(memory_index);
         let memory = unsafe { import.definition.as_ref() };
@@ -805,7 +926,7 @@ impl Instance {
         &self,
         memory_index: MemoryIndex,
         data_index: DataIndex,
-        dst: u32,
+        dst: u64,
         src: u32,
         len: u32,
     ) -> Result<(), Trap> {
@@ -818,9 +939,9 @@ impl Instance {
         if src
             .checked_add(len)
             .map_or(true, |n| n as usize > data.len())
-            || dst.checked_add(len).map_or(true, |m| {
-                usize::try_from(m).unwrap() > memory.current_length
-            })
+            || dst
+                .checked_add(len.into())
+                .map_or(true, |m| m > memory.current_length as u64)
         {
             return Err(Trap::lib(TrapCode::HeapAccessOutOfBounds));
         }
@@ -828,7 +949,7 @@ impl Instance {
         let src_slice = &data[src as usize..(src + len) as usize];
 
         unsafe {
-            let dst_start = memory.base.add(dst as usize);
+            let dst_start = memory.base.add(usize::try_from(dst).unwrap());
             let dst_slice = slice::from_raw_parts_mut(dst_start, len as usize);
             dst_slice.copy_from_slice(src_slice);
         }
@@ -842,6 +963,185 @@ impl Instance {
         passive_data.remove(&data_index);
     }
 
+    /// Get a locally defined or imported memory, and whether it's shared.
+    fn get_memory_and_sharing(&self, index: MemoryIndex) -> (VMMemoryDefinition, bool) {
+        if let Some(local_index) = self.module.local_memory_index(index) {
+            (
+                self.memory(local_index),
+                self.memories[local_index].ty().shared,
+            )
+        } else {
+            let import = self.imported_memory(index);
+            (
+                unsafe { *import.definition.as_ref() },
+                import.from.ty().shared,
+            )
+        }
+    }
+
+    /// Returns the host address of the `size` bytes at `dst` in `memory`,
+    /// checking that they are in bounds and naturally aligned.
+    fn atomic_address(memory: &VMMemoryDefinition, dst: u64, size: u64) -> Result<usize, Trap> {
+        if dst
+            .checked_add(size)
+            .map_or(true, |end| end > memory.current_length as u64)
+        {
+            return Err(Trap::lib(TrapCode::HeapAccessOutOfBounds));
+        }
+        if dst % size != 0 {
+            return Err(Trap::lib(TrapCode::UnalignedAtomic));
+        }
+        Ok(memory.base as usize + dst as usize)
+    }
+
+    /// Performs the `memory.atomic.wait32` operation: parks the thread until
+    /// the 32-bit value at `dst` is notified, unless it isn't `expected`.
+    ///
+    /// A negative `timeout` (in nanoseconds) waits forever. The wait is
+    /// abandoned when the epoch deadline of the instance is reached, even if
+    /// its code wasn't compiled with interrupt checks.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Trap` error if `dst` is out of bounds or misaligned, if
+    /// the memory isn't shared, or if the wait is interrupted.
+    pub(crate) fn memory_atomic_wait32(
+        &self,
+        memory_index: MemoryIndex,
+        dst: u64,
+        expected: u32,
+        timeout: i64,
+    ) -> Result<u32, Trap> {
+        let (memory, shared) = self.get_memory_and_sharing(memory_index);
+        let address = Self::atomic_address(&memory, dst, 4)?;
+        if !shared {
+            return Err(Trap::lib(TrapCode::AtomicWaitOnUnsharedMemory));
+        }
+        let result = WAIT_QUEUE.wait(
+            address,
+            // Safety: the address is in bounds and aligned.
+            || unsafe { (*(address as *const AtomicU32)).load(Ordering::SeqCst) == expected },
+            wait_timeout(timeout),
+            || self.interrupts.deadline_reached(),
+        );
+        match result {
+            Some(result) => Ok(result as u32),
+            None => Err(Trap::lib(TrapCode::Interrupt)),
+        }
+    }
+
+    /// Performs the `memory.atomic.wait64` operation, like
+    /// [`Instance::memory_atomic_wait32`] for a 64-bit value.
+    pub(crate) fn memory_atomic_wait64(
+        &self,
+        memory_index: MemoryIndex,
+        dst: u64,
+        expected: u64,
+        timeout: i64,
+    ) -> Result<u32, Trap> {
+        let (memory, shared) = self.get_memory_and_sharing(memory_index);
+        let address = Self::atomic_address(&memory, dst, 8)?;
+        if !shared {
+            return Err(Trap::lib(TrapCode::AtomicWaitOnUnsharedMemory));
+        }
+        let result = WAIT_QUEUE.wait(
+            address,
+            // Safety: the address is in bounds and aligned.
+            || unsafe { (*(address as *const AtomicU64)).load(Ordering::SeqCst) == expected },
+            wait_timeout(timeout),
+            || self.interrupts.deadline_reached(),
+        );
+        match result {
+            Some(result) => Ok(result as u32),
+            None => Err(Trap::lib(TrapCode::Interrupt)),
+        }
+    }
+
+    /// Performs the `memory.atomic.notify` operation: wakes up to `count`
+    /// threads waiting on `dst`, and returns how many were woken.
+    ///
+    /// Nothing can wait on an unshared memory, so notifying one always
+    /// returns 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Trap` error if `dst` is out of bounds or misaligned.
+    pub(crate) fn memory_atomic_notify(
+        &self,
+        memory_index: MemoryIndex,
+        dst: u64,
+        count: u32,
+    ) -> Result<u32, Trap> {
+        let (memory, shared) = self.get_memory_and_sharing(memory_index);
+        let address = Self::atomic_address(&memory, dst, 4)?;
+        if !shared {
+            return Ok(0);
+        }
+        Ok(WAIT_QUEUE.notify(address, count))
+    }
+
+    /// Perform a tail call to `callee`, of the signature `sig_index`.
+    ///
+    /// `values` holds the arguments of the call. If the chain the call is
+    /// part of ends here, it receives the results of the chain.
+    ///
+    /// # Safety
+    ///
+    /// `callee` and `callee_vmctx` must be a function of signature
+    /// `sig_index` and its environment, and `values` must have room for
+    /// both the arguments and the results of the signature.
+    pub(crate) unsafe fn tail_call(
+        &self,
+        sig_index: SignatureIndex,
+        callee: *const VMFunctionBody,
+        callee_vmctx: *mut VMContext,
+        values: *mut u128,
+        driven: bool,
+    ) {
+        let signature = &self.module.signatures[sig_index];
+        let num_params = signature.params().len();
+        let num_results = signature.results().len();
+        let call = TailCall {
+            trampoline: self.function_call_trampolines[sig_index],
+            callee,
+            callee_vmctx,
+        };
+        if driven {
+            tail_call::leave_pending(call, slice::from_raw_parts(values, num_params));
+        } else {
+            tail_call::drive(call, values, num_params.max(num_results), num_results);
+        }
+    }
+
+    /// Get a tag by index, whether it is locally-defined or imported.
+    pub(crate) fn get_tag(&self, tag_index: TagIndex) -> &Arc<Tag> {
+        &self.tags[tag_index]
+    }
+
+    /// Throw an exception of the tag `tag_index`, whose values are read
+    /// from `values`.
+    ///
+    /// # Safety
+    ///
+    /// `values` must hold one value per parameter of the tag.
+    pub(crate) unsafe fn throw(&self, tag_index: TagIndex, values: *const u128) {
+        let tag = self.get_tag(tag_index).clone();
+        let num_values = tag.ty().params().len();
+        let values = slice::from_raw_parts(values, num_values).into();
+        exception::throw(VMException::new(tag, values), &self.interrupts);
+    }
+
+    /// Throw `exception` again, for a `rethrow` or the end of a `try` that
+    /// caught nothing.
+    pub(crate) fn rethrow(&self, exception: &VMException) {
+        exception::throw(exception.clone(), &self.interrupts);
+    }
+
+    /// Returns whether `exception` was thrown with the tag `tag_index`.
+    pub(crate) fn exception_matches(&self, exception: &VMException, tag_index: TagIndex) -> bool {
+        Arc::ptr_eq(exception.tag(), self.get_tag(tag_index))
+    }
+
     /// Get a table by index regardless of whether it is locally-defined or an
     /// imported, foreign table.
     pub(crate) fn get_table(&self, table_index: TableIndex) -> &dyn Table {
@@ -910,13 +1210,31 @@ impl InstanceHandle {
         vmshared_signatures: BoxedSlice<SignatureIndex, VMSharedSignatureIndex>,
         host_state: Box<dyn Any>,
         imported_function_envs: BoxedSlice<FunctionIndex, ImportFunctionEnv>,
-    ) -> Result<Self, Trap> {
+        interrupts: Arc<VMInterrupts>,
+        limiter: Option<Arc<dyn ResourceLimiter>>,
+    ) -> Result<Self, ResourceLimitError> {
+        let limits = limiter
+            .map(|limiter| {
+                InstanceLimits::new(limiter, &module, &finished_memories, &finished_tables)
+            })
+            .transpose()?;
+
         let vmctx_globals = finished_globals
             .values()
             .map(|m| m.vmglobal())
             .collect::<PrimaryMap<LocalGlobalIndex, _>>()
             .into_boxed_slice();
         let passive_data = RefCell::new(module.passive_data.clone());
+        let tags = imports
+            .tags
+            .values()
+            .cloned()
+            .chain(
+                (module.num_imported_tags..module.tags.len())
+                    .map(|index| Arc::new(Tag::new(module.tag_type(TagIndex::new(index))))),
+            )
+            .collect::<PrimaryMap<TagIndex, _>>()
+            .into_boxed_slice();
 
         let handle = {
             let offsets = allocator.offsets().clone();
@@ -929,6 +1247,7 @@ impl InstanceHandle {
                 memories: finished_memories,
                 tables: finished_tables,
                 globals: finished_globals,
+                tags,
                 functions: finished_functions,
                 function_call_trampolines: finished_function_call_trampolines,
                 passive_elements: Default::default(),
@@ -936,6 +1255,8 @@ impl InstanceHandle {
                 host_state,
                 funcrefs,
                 imported_function_envs,
+                interrupts,
+                limits,
                 vmctx: VMContext {},
             };
 
@@ -997,6 +1318,11 @@ impl InstanceHandle {
             instance.builtin_functions_ptr() as *mut VMBuiltinFunctionsArray,
             VMBuiltinFunctionsArray::initialized(),
         );
+        ptr::write(instance.interrupts_ptr(), Arc::as_ptr(&instance.interrupts));
+        ptr::write(
+            instance.thrown_exceptions_ptr(),
+            &instance.interrupts.thrown_exceptions,
+        );
 
         // Perform infallible initialization in this constructor, while fallible
         // initialization is deferred to the `initialize` method.
@@ -1013,6 +1339,10 @@ impl InstanceHandle {
 
     /// Finishes the instantiation process started by `Instance::new`.
     ///
+    /// The local memories are initialized with `memory_images` when they
+    /// are given, and support them. The data initializers are copied into
+    /// the other memories.
+    ///
     /// # Safety
     ///
     /// Only safe to call immediately after instantiation.
@@ -1020,12 +1350,13 @@ impl InstanceHandle {
         &self,
         trap_handler: &(dyn TrapHandler + 'static),
         data_initializers: &[DataInitializer<'_>],
+        memory_images: Option<&ModuleMemoryImages>,
     ) -> Result<(), Trap> {
         let instance = self.instance().as_ref();
 
         // Apply the initializers.
         initialize_tables(instance)?;
-        initialize_memories(instance, data_initializers)?;
+        initialize_memories(instance, data_initializers, memory_images)?;
 
         // The WebAssembly spec specifies that the start function is
         // invoked automatically at instantiation time.
@@ -1033,6 +1364,55 @@ impl InstanceHandle {
         Ok(())
     }
 
+    /// Restores the instance to the state it had right after its
+    /// instantiation, without creating a new instance.
+    ///
+    /// The local memories and tables shrink back to their minimum size
+    /// and are initialized again, like the local globals and the passive
+    /// data and element segments, and the start function is invoked
+    /// again. The imported memories, tables and globals are not reset.
+    ///
+    /// # Safety
+    ///
+    /// `data_initializers` and `memory_images` must be the ones given to
+    /// [`InstanceHandle::finish_instantiation`]. The instance must not be
+    /// executing, and its local memories and tables must not be in use
+    /// elsewhere, since they are shrunk and overwritten in place.
+    pub unsafe fn reset(
+        &self,
+        trap_handler: &(dyn TrapHandler + 'static),
+        data_initializers: &[DataInitializer<'_>],
+        memory_images: Option<&ModuleMemoryImages>,
+    ) -> Result<(), Trap> {
+        let instance = self.instance().as_ref();
+        let module = &instance.module;
+        let limits = instance.limits.as_ref();
+
+        for (index, memory) in instance.memories.iter() {
+            let size = memory.size();
+            memory
+                .reset()
+                .map_err(|error| Trap::User(Box::new(error)))?;
+            if let Some(limits) = limits {
+                limits.memory_shrunk(module.memory_index(index), Pages(size.0 - memory.size().0));
+            }
+        }
+        for (index, table) in instance.tables.iter() {
+            let size = table.size();
+            table.reset().map_err(|error| Trap::User(error.into()))?;
+            if let Some(limits) = limits {
+                limits.table_shrunk(module.table_index(index), size - table.size());
+            }
+        }
+
+        *instance.passive_data.borrow_mut() = instance.module.passive_data.clone();
+        instance.passive_elements.borrow_mut().clear();
+        initialize_passive_elements(instance);
+        initialize_globals(instance);
+
+        self.finish_instantiation(trap_handler, data_initializers, memory_images)
+    }
+
     /// Return a reference to the vmctx used by compiled wasm code.
     pub fn vmctx(&self) -> &VMContext {
         self.instance().as_ref().vmctx()
@@ -1148,6 +1528,11 @@ impl InstanceHandle {
                 }
                 .into()
             }
+            ExportIndex::Tag(index) => VMTag {
+                from: instance_ref.tags[*index].clone(),
+                instance_ref: Some(WeakOrStrongInstanceRef::Strong(instance)),
+            }
+            .into(),
         }
     }
 
@@ -1203,6 +1588,7 @@ impl InstanceHandle {
         self.instance()
             .as_ref()
             .table_grow(table_index, delta, init_value)
+            .unwrap_or(None)
     }
 
     /// Get table element reference.
@@ -1266,19 +1652,40 @@ impl InstanceHandle {
     }
 }
 
+/// Converts the timeout operand of `memory.atomic.wait`, in nanoseconds,
+/// where a negative value means no timeout.
+fn wait_timeout(timeout: i64) -> Option<Duration> {
+    u64::try_from(timeout).ok().map(Duration::from_nanos)
+}
+
 /// Compute the offset for a memory data initializer.
 fn get_memory_init_start(init: &DataInitializer<'_>, instance: &Instance) -> usize {
     let mut start = init.location.offset;
 
     if let Some(base) = init.location.base {
+        // The offset of a 64-bit memory comes from an `i64` global.
+        let memory64 = instance.module.memories[init.location.memory_index].memory64;
+        let offset = |global: &VMGlobalDefinition| {
+            if memory64 {
+                global.to_u64()
+            } else {
+                global.to_u32().into()
+            }
+        };
         let val = unsafe {
             if let Some(def_index) = instance.module.local_global_index(base) {
-                instance.global(def_index).to_u32()
+                offset(&instance.global(def_index))
             } else {
-                instance.imported_global(base).definition.as_ref().to_u32()
+                offset(instance.imported_global(base).definition.as_ref())
             }
         };
-        start += usize::try_from(val).unwrap();
+        // The offset wraps around in the index type of the memory.
+        let val = if memory64 {
+            val.wrapping_add(start as u64)
+        } else {
+            u64::from((val as u32).wrapping_add(start as u32))
+        };
+        start = usize::try_from(val).unwrap();
     }
 
     start
@@ -1314,7 +1721,7 @@ fn get_table_init_start(init: &TableInitializer, instance: &Instance) -> usize {
                 instance.imported_global(base).definition.as_ref().to_u32()
             }
         };
-        start += usize::try_from(val).unwrap();
+        start = usize::try_from(val.wrapping_add(start as u32)).unwrap();
     }
 
     start
@@ -1380,8 +1787,27 @@ fn initialize_passive_elements(instance: &Instance) {
 fn initialize_memories(
     instance: &Instance,
     data_initializers: &[DataInitializer<'_>],
+    memory_images: Option<&ModuleMemoryImages>,
 ) -> Result<(), Trap> {
+    let mut mapped = vec![false; instance.memories.len()];
+    if let Some(images) = memory_images {
+        for (index, memory) in instance.memories.iter() {
+            if let Some(image) = images.get(index) {
+                mapped[index.index()] = memory
+                    .map_image(image)
+                    .map_err(|error| Trap::User(Box::new(error)))?;
+            }
+        }
+    }
+
     for init in data_initializers {
+        let module = instance.module_ref();
+        if module
+            .local_memory_index(init.location.memory_index)
+            .map_or(false, |index| mapped[index.index()])
+        {
+            continue;
+        }
         let memory = instance.get_memory(init.location.memory_index);
 
         let start = get_memory_init_start(init, instance);
@@ -1428,6 +1854,24 @@ fn initialize_globals(instance: &Instance) {
                     let funcref = instance.func_ref(*func_idx).unwrap();
                     *(*to).as_funcref_mut() = funcref;
                 }
+                GlobalInit::Expr(ops) => {
+                    let val = ConstOperator::eval(ops, |x| {
+                        let from: VMGlobalDefinition =
+                            if let Some(def_x) = module.local_global_index(x) {
+                                instance.global(def_x)
+                            } else {
+                                instance.imported_global(x).definition.as_ref().clone()
+                            };
+                        match module.globals[x].ty {
+                            Type::I64 => from.to_u64(),
+                            _ => u64::from(from.to_u32()),
+                        }
+                    });
+                    match module.globals[module.global_index(index)].ty {
+                        Type::I64 => *(*to).as_u64_mut() = val,
+                        _ => *(*to).as_u32_mut() = val as u32,
+                    }
+                }
             }
         }
     }
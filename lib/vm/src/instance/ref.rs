@@ -1,4 +1,5 @@
 use super::Instance;
+use crate::mmap::Mmap;
 use loupe::{MemoryUsage, MemoryUsageTracker};
 use std::alloc::Layout;
 use std::convert::TryFrom;
@@ -21,6 +22,10 @@ struct InstanceInner {
     /// The layout of `Instance` (which can vary).
     instance_layout: Layout,
 
+    /// The slot of a pool in which `Instance` is allocated, if it is
+    /// not allocated with `alloc`.
+    slot: Option<Mmap>,
+
     /// The `Instance` itself. It must be the last field of
     /// `InstanceRef` since `Instance` is dyamically-sized.
     ///
@@ -45,7 +50,10 @@ impl InstanceInner {
         let instance_ptr = self.instance.as_ptr();
 
         ptr::drop_in_place(instance_ptr);
-        std::alloc::dealloc(instance_ptr as *mut u8, self.instance_layout);
+        // A slot is given back to its pool when it is dropped.
+        if self.slot.take().is_none() {
+            std::alloc::dealloc(instance_ptr as *mut u8, self.instance_layout);
+        }
     }
 
     /// Get a reference to the `Instance`.
@@ -124,9 +132,14 @@ impl InstanceRef {
     /// and correctly initialized pointer to `Instance`. See
     /// [`InstanceAllocator`] for an example of how to correctly use
     /// this API.
-    pub(super) unsafe fn new(instance: NonNull<Instance>, instance_layout: Layout) -> Self {
+    pub(super) unsafe fn new(
+        instance: NonNull<Instance>,
+        instance_layout: Layout,
+        slot: Option<Mmap>,
+    ) -> Self {
         Self(Arc::new(InstanceInner {
             instance_layout,
+            slot,
             instance,
         }))
     }
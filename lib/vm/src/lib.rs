@@ -20,21 +20,29 @@
     )
 )]
 
+mod exception;
 mod export;
 mod func_data_registry;
 mod global;
 mod imports;
 mod instance;
+mod limiter;
 mod memory;
+mod memory_image;
 mod mmap;
+mod pooling;
 mod probestack;
 mod sig_registry;
 mod table;
+mod tag;
+mod tail_call;
 mod trap;
 mod vmcontext;
+mod wait_queue;
 
 pub mod libcalls;
 
+pub use crate::exception::VMException;
 pub use crate::export::*;
 pub use crate::func_data_registry::{FuncDataRegistry, VMFuncRef};
 pub use crate::global::*;
@@ -43,16 +51,22 @@ pub use crate::instance::{
     ImportFunctionEnv, ImportInitializerFuncPtr, InstanceAllocator, InstanceHandle,
     WeakOrStrongInstanceRef,
 };
-pub use crate::memory::{LinearMemory, Memory, MemoryError};
+pub use crate::limiter::{ResourceLimitError, ResourceLimiter};
+pub use crate::memory::{LinearMemory, Memory, MemoryAccessError, MemoryError};
+pub use crate::memory_image::{MemoryImage, ModuleMemoryImages};
 pub use crate::mmap::Mmap;
+pub use crate::pooling::{PoolingAllocator, PoolingConfig};
 pub use crate::probestack::PROBESTACK;
 pub use crate::sig_registry::SignatureRegistry;
 pub use crate::table::{LinearTable, Table, TableElement};
+pub use crate::tail_call::MAX_TAIL_CALL_VALUES;
+pub use crate::tag::Tag;
 pub use crate::trap::*;
 pub use crate::vmcontext::{
     VMCallerCheckedAnyfunc, VMContext, VMDynamicFunctionContext, VMFunctionEnvironment,
-    VMFunctionImport, VMFunctionKind, VMGlobalDefinition, VMGlobalImport, VMMemoryDefinition,
-    VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition, VMTableImport, VMTrampoline,
+    VMFunctionImport, VMFunctionKind, VMGlobalDefinition, VMGlobalImport, VMInterrupts,
+    VMMemoryDefinition, VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition, VMTableImport,
+    VMTrampoline,
 };
 pub use wasmer_artifact::{FunctionBodyPtr, VMFunctionBody};
 pub use wasmer_types::LibCall;
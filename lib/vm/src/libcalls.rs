@@ -37,16 +37,23 @@
 
 #![allow(missing_docs)] // For some reason lint fails saying that `LibCall` is not documented, when it actually is
 
+use crate::exception::{self, VMException};
 use crate::func_data_registry::VMFuncRef;
+use crate::limiter::ResourceLimitError;
+use crate::memory::MemoryError;
 use crate::probestack::PROBESTACK;
 use crate::table::{RawTableElement, TableElement};
-use crate::trap::{raise_lib_trap, Trap, TrapCode};
-use crate::vmcontext::VMContext;
+use crate::tail_call;
+use crate::trap::{self, raise_lib_trap, raise_user_trap, Trap, TrapCode};
+use crate::vmcontext::{VMContext, VMFunctionBody};
 use crate::{on_host_stack, VMExternRef};
+use backtrace::Backtrace;
+use std::convert::TryFrom;
+use std::ptr;
 pub use wasmer_types::LibCall;
 use wasmer_types::{
-    DataIndex, ElemIndex, FunctionIndex, LocalMemoryIndex, LocalTableIndex, MemoryIndex,
-    TableIndex, Type,
+    DataIndex, ElemIndex, FunctionIndex, LocalMemoryIndex, LocalTableIndex, MemoryIndex, Pages,
+    SignatureIndex, TableIndex, TagIndex, Type,
 };
 
 /// Implementation of f32.ceil
@@ -150,15 +157,13 @@ pub unsafe extern "C" fn wasmer_vm_memory32_grow(
     delta: u32,
     memory_index: u32,
 ) -> u32 {
-    on_host_stack(|| {
+    let result = on_host_stack(|| {
         let instance = (&*vmctx).instance();
         let memory_index = LocalMemoryIndex::from_u32(memory_index);
 
-        instance
-            .memory_grow(memory_index, delta)
-            .map(|pages| pages.0)
-            .unwrap_or(u32::max_value())
-    })
+        instance.memory_grow(memory_index, delta)
+    });
+    memory_grow_result(result).map_or(u32::max_value(), |pages| pages.0)
 }
 
 /// Implementation of memory.grow for imported 32-bit memories.
@@ -172,15 +177,74 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_grow(
     delta: u32,
     memory_index: u32,
 ) -> u32 {
-    on_host_stack(|| {
+    let result = on_host_stack(|| {
         let instance = (&*vmctx).instance();
         let memory_index = MemoryIndex::from_u32(memory_index);
 
-        instance
-            .imported_memory_grow(memory_index, delta)
-            .map(|pages| pages.0)
-            .unwrap_or(u32::max_value())
-    })
+        instance.imported_memory_grow(memory_index, delta)
+    });
+    memory_grow_result(result).map_or(u32::max_value(), |pages| pages.0)
+}
+
+/// Implementation of memory.grow for locally-defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_grow(
+    vmctx: *mut VMContext,
+    delta: u64,
+    memory_index: u32,
+) -> u64 {
+    let delta = match u32::try_from(delta) {
+        Ok(delta) => delta,
+        Err(_) => return u64::max_value(),
+    };
+    let result = on_host_stack(|| {
+        let instance = (&*vmctx).instance();
+        let memory_index = LocalMemoryIndex::from_u32(memory_index);
+
+        instance.memory_grow(memory_index, delta)
+    });
+    memory_grow_result(result).map_or(u64::max_value(), |pages| pages.0.into())
+}
+
+/// Implementation of memory.grow for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_grow(
+    vmctx: *mut VMContext,
+    delta: u64,
+    memory_index: u32,
+) -> u64 {
+    let delta = match u32::try_from(delta) {
+        Ok(delta) => delta,
+        Err(_) => return u64::max_value(),
+    };
+    let result = on_host_stack(|| {
+        let instance = (&*vmctx).instance();
+        let memory_index = MemoryIndex::from_u32(memory_index);
+
+        instance.imported_memory_grow(memory_index, delta)
+    });
+    memory_grow_result(result).map_or(u64::max_value(), |pages| pages.0.into())
+}
+
+/// Converts the result of growing a memory into the previous size of the
+/// memory, or `None` if it couldn't grow, trapping if the resource limiter
+/// asked for it.
+unsafe fn memory_grow_result(result: Result<Pages, MemoryError>) -> Option<Pages> {
+    match result {
+        Ok(pages) => Some(pages),
+        Err(MemoryError::ResourceLimit(error @ ResourceLimitError::Trap(_))) => {
+            raise_user_trap(Box::new(error))
+        }
+        Err(_) => None,
+    }
 }
 
 /// Implementation of memory.size for locally-defined 32-bit memories.
@@ -212,6 +276,35 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_size(
     instance.imported_memory_size(memory_index).0
 }
 
+/// Implementation of memory.size for locally-defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_size(vmctx: *mut VMContext, memory_index: u32) -> u64 {
+    let instance = (&*vmctx).instance();
+    let memory_index = LocalMemoryIndex::from_u32(memory_index);
+
+    instance.memory_size(memory_index).0.into()
+}
+
+/// Implementation of memory.size for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_size(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+) -> u64 {
+    let instance = (&*vmctx).instance();
+    let memory_index = MemoryIndex::from_u32(memory_index);
+
+    instance.imported_memory_size(memory_index).0.into()
+}
+
 /// Implementation of `table.copy`.
 ///
 /// # Safety
@@ -439,7 +532,7 @@ pub unsafe extern "C" fn wasmer_vm_table_grow(
     delta: u32,
     table_index: u32,
 ) -> u32 {
-    on_host_stack(|| {
+    let result = on_host_stack(|| {
         let instance = (&*vmctx).instance();
         let table_index = LocalTableIndex::from_u32(table_index);
 
@@ -449,10 +542,9 @@ pub unsafe extern "C" fn wasmer_vm_table_grow(
             _ => panic!("Unrecognized table type: does not contain references"),
         };
 
-        instance
-            .table_grow(table_index, delta, init_value)
-            .unwrap_or(u32::max_value())
-    })
+        instance.table_grow(table_index, delta, init_value)
+    });
+    table_grow_result(result)
 }
 
 /// Implementation of `table.grow` for imported tables.
@@ -467,7 +559,7 @@ pub unsafe extern "C" fn wasmer_vm_imported_table_grow(
     delta: u32,
     table_index: u32,
 ) -> u32 {
-    on_host_stack(|| {
+    let result = on_host_stack(|| {
         let instance = (&*vmctx).instance();
         let table_index = TableIndex::from_u32(table_index);
         let init_value = match instance.get_table(table_index).ty().ty {
@@ -476,10 +568,19 @@ pub unsafe extern "C" fn wasmer_vm_imported_table_grow(
             _ => panic!("Unrecognized table type: does not contain references"),
         };
 
-        instance
-            .imported_table_grow(table_index, delta, init_value)
-            .unwrap_or(u32::max_value())
-    })
+        instance.imported_table_grow(table_index, delta, init_value)
+    });
+    table_grow_result(result)
+}
+
+/// Converts the result of growing a table into the result of
+/// `table.grow`, trapping if the resource limiter asked for it.
+unsafe fn table_grow_result(result: Result<Option<u32>, ResourceLimitError>) -> u32 {
+    match result {
+        Ok(size) => size.unwrap_or(u32::max_value()),
+        Err(ResourceLimitError::Fail) => u32::max_value(),
+        Err(error) => raise_user_trap(Box::new(error)),
+    }
 }
 
 /// Implementation of `func.ref`.
@@ -553,7 +654,7 @@ pub unsafe extern "C" fn wasmer_vm_memory32_copy(
     let result = {
         let memory_index = LocalMemoryIndex::from_u32(memory_index);
         let instance = (&*vmctx).instance();
-        instance.local_memory_copy(memory_index, dst, src, len)
+        instance.local_memory_copy(memory_index, dst.into(), src.into(), len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -576,7 +677,7 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_copy(
     let result = {
         let memory_index = MemoryIndex::from_u32(memory_index);
         let instance = (&*vmctx).instance();
-        instance.imported_memory_copy(memory_index, dst, src, len)
+        instance.imported_memory_copy(memory_index, dst.into(), src.into(), len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -599,7 +700,7 @@ pub unsafe extern "C" fn wasmer_vm_memory32_fill(
     let result = {
         let memory_index = LocalMemoryIndex::from_u32(memory_index);
         let instance = (&*vmctx).instance();
-        instance.local_memory_fill(memory_index, dst, val, len)
+        instance.local_memory_fill(memory_index, dst.into(), val, len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -622,7 +723,7 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_fill(
     let result = {
         let memory_index = MemoryIndex::from_u32(memory_index);
         let instance = (&*vmctx).instance();
-        instance.imported_memory_fill(memory_index, dst, val, len)
+        instance.imported_memory_fill(memory_index, dst.into(), val, len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -642,6 +743,123 @@ pub unsafe extern "C" fn wasmer_vm_memory32_init(
     dst: u32,
     src: u32,
     len: u32,
+) {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let data_index = DataIndex::from_u32(data_index);
+        let instance = (&*vmctx).instance();
+        instance.memory_init(memory_index, data_index, dst.into(), src, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.copy` for locally defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_copy(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    src: u64,
+    len: u64,
+) {
+    let result = {
+        let memory_index = LocalMemoryIndex::from_u32(memory_index);
+        let instance = (&*vmctx).instance();
+        instance.local_memory_copy(memory_index, dst, src, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.copy` for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_copy(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    src: u64,
+    len: u64,
+) {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let instance = (&*vmctx).instance();
+        instance.imported_memory_copy(memory_index, dst, src, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.fill` for locally defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_fill(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    val: u32,
+    len: u64,
+) {
+    let result = {
+        let memory_index = LocalMemoryIndex::from_u32(memory_index);
+        let instance = (&*vmctx).instance();
+        instance.local_memory_fill(memory_index, dst, val, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.fill` for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_fill(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    val: u32,
+    len: u64,
+) {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let instance = (&*vmctx).instance();
+        instance.imported_memory_fill(memory_index, dst, val, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.init` for 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_init(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    data_index: u32,
+    dst: u64,
+    src: u32,
+    len: u32,
 ) {
     let result = {
         let memory_index = MemoryIndex::from_u32(memory_index);
@@ -654,6 +872,33 @@ pub unsafe extern "C" fn wasmer_vm_memory32_init(
     }
 }
 
+/// Implementation of `memory.copy` between two different memories, which may
+/// each be local or imported. 32-bit addresses and lengths are passed
+/// zero-extended.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory_copy_between(
+    vmctx: *mut VMContext,
+    dst_memory_index: u32,
+    src_memory_index: u32,
+    dst: u64,
+    src: u64,
+    len: u64,
+) {
+    let result = {
+        let dst_memory_index = MemoryIndex::from_u32(dst_memory_index);
+        let src_memory_index = MemoryIndex::from_u32(src_memory_index);
+        let instance = (&*vmctx).instance();
+        instance.memory_copy_between(dst_memory_index, src_memory_index, dst, src, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
 /// Implementation of `data.drop`.
 ///
 /// # Safety
@@ -668,6 +913,228 @@ pub unsafe extern "C" fn wasmer_vm_data_drop(vmctx: *mut VMContext, data_index:
     })
 }
 
+/// Implementation of `memory.atomic.wait32`.
+///
+/// `dst` is the effective address, with the offset of the instruction
+/// already added.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory32_atomic_wait32(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    expected: u32,
+    timeout: i64,
+) -> u32 {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let instance = (&*vmctx).instance();
+        on_host_stack(|| instance.memory_atomic_wait32(memory_index, dst, expected, timeout))
+    };
+    match result {
+        Ok(result) => result,
+        Err(trap) => raise_lib_trap(trap),
+    }
+}
+
+/// Implementation of `memory.atomic.wait64`.
+///
+/// `dst` is the effective address, with the offset of the instruction
+/// already added.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory32_atomic_wait64(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    expected: u64,
+    timeout: i64,
+) -> u32 {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let instance = (&*vmctx).instance();
+        on_host_stack(|| instance.memory_atomic_wait64(memory_index, dst, expected, timeout))
+    };
+    match result {
+        Ok(result) => result,
+        Err(trap) => raise_lib_trap(trap),
+    }
+}
+
+/// Implementation of `memory.atomic.notify`.
+///
+/// `dst` is the effective address, with the offset of the instruction
+/// already added.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory32_atomic_notify(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    count: u32,
+) -> u32 {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let instance = (&*vmctx).instance();
+        instance.memory_atomic_notify(memory_index, dst, count)
+    };
+    match result {
+        Ok(result) => result,
+        Err(trap) => raise_lib_trap(trap),
+    }
+}
+
+/// Implementation of `return_call` and `return_call_indirect`.
+///
+/// `values` holds the arguments of the call to `callee`, of signature
+/// `sig_index`. `driven` is the value `wasmer_vm_tail_call_entered`
+/// returned on entry to the calling function. If the chain of tail calls
+/// ends here, `values` receives its results, which the calling function
+/// then returns.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable, `callee` and `callee_vmctx` must be a
+/// function of signature `sig_index` and its environment, and `values`
+/// must have room for both the arguments and the results of the signature.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_tail_call(
+    vmctx: *mut VMContext,
+    sig_index: u32,
+    callee: *const VMFunctionBody,
+    callee_vmctx: *mut VMContext,
+    values: *mut u128,
+    driven: u32,
+) {
+    let sig_index = SignatureIndex::from_u32(sig_index);
+    let instance = (&*vmctx).instance();
+    instance.tail_call(sig_index, callee, callee_vmctx, values, driven != 0);
+}
+
+/// Returns whether the function with `body`, which calls this first thing
+/// on entry, was called by a tail call.
+///
+/// # Safety
+///
+/// Only safe to call from the entry of the function with `body`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_tail_call_entered(body: *const VMFunctionBody) -> u32 {
+    tail_call::entered(body) as u32
+}
+
+/// Implementation of `throw`: leaves an exception of the tag `tag_index`,
+/// carrying the values read from `values`, pending.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable and `values` must hold one value per
+/// parameter of the tag.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_throw(
+    vmctx: *mut VMContext,
+    tag_index: u32,
+    values: *const u128,
+) {
+    let tag_index = TagIndex::from_u32(tag_index);
+    let instance = (&*vmctx).instance();
+    instance.throw(tag_index, values);
+}
+
+/// Returns whether an exception is pending for the current thread.
+///
+/// Compiled code calls this after a call only when the count of pending
+/// exceptions of its store isn't zero.
+#[no_mangle]
+pub extern "C" fn wasmer_vm_exception_thrown() -> u32 {
+    exception::thrown() as u32
+}
+
+/// Takes the pending exception, for a `try` that is about to test its
+/// tags. The exception must be released with `wasmer_vm_exception_drop`.
+///
+/// # Safety
+///
+/// Only safe to call when an exception is pending.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_exception_catch() -> *mut VMException {
+    let exception = exception::take().expect("no pending exception to catch");
+    Box::into_raw(Box::new(exception))
+}
+
+/// Returns whether `exception` was thrown with the tag `tag_index`.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable and `exception` must have been returned
+/// by `wasmer_vm_exception_catch` and not yet dropped.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_exception_matches(
+    vmctx: *mut VMContext,
+    exception: *const VMException,
+    tag_index: u32,
+) -> u32 {
+    let tag_index = TagIndex::from_u32(tag_index);
+    let instance = (&*vmctx).instance();
+    instance.exception_matches(&*exception, tag_index) as u32
+}
+
+/// Writes the values `exception` carries to `values`.
+///
+/// # Safety
+///
+/// `exception` must have been returned by `wasmer_vm_exception_catch` and
+/// not yet dropped, and `values` must have room for one value per
+/// parameter of its tag.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_exception_payload(
+    exception: *const VMException,
+    values: *mut u128,
+) {
+    let payload = (&*exception).payload();
+    ptr::copy_nonoverlapping(payload.as_ptr(), values, payload.len());
+}
+
+/// Implementation of `rethrow`, and of the end of a `try` that caught
+/// nothing: leaves `exception` pending again.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable and `exception` must have been returned
+/// by `wasmer_vm_exception_catch` and not yet dropped.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_rethrow(vmctx: *mut VMContext, exception: *const VMException) {
+    let instance = (&*vmctx).instance();
+    instance.rethrow(&*exception);
+}
+
+/// Releases an exception returned by `wasmer_vm_exception_catch`, when
+/// control leaves the handler of the `try` that caught it.
+///
+/// # Safety
+///
+/// `exception` must have been returned by `wasmer_vm_exception_catch` and
+/// not yet dropped.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_exception_drop(exception: *mut VMException) {
+    drop(Box::from_raw(exception));
+}
+
+/// Returns the lowest address of the stack of the call running on this
+/// thread, for the explicit stack checks of compiled code.
+#[no_mangle]
+pub extern "C" fn wasmer_vm_stack_limit() -> usize {
+    trap::stack_limit()
+}
+
 /// Implementation for raising a trap
 ///
 /// # Safety
@@ -676,7 +1143,17 @@ pub unsafe extern "C" fn wasmer_vm_data_drop(vmctx: *mut VMContext, data_index:
 /// `wasmer_call_trampoline` must have been previously called.
 #[no_mangle]
 pub unsafe extern "C" fn wasmer_vm_raise_trap(trap_code: TrapCode) -> ! {
-    let trap = Trap::lib(trap_code);
+    // Like for the stack overflows caught by the signal handler, don't
+    // capture a backtrace for the ones caught by explicit stack checks:
+    // there is little stack left to capture it on.
+    let trap = if trap_code == TrapCode::StackOverflow {
+        Trap::Lib {
+            trap_code,
+            backtrace: Backtrace::from(vec![]),
+        }
+    } else {
+        Trap::lib(trap_code)
+    };
     raise_lib_trap(trap)
 }
 
@@ -702,6 +1179,8 @@ pub fn function_pointer(libcall: LibCall) -> usize {
         LibCall::TruncF64 => wasmer_vm_f64_trunc as usize,
         LibCall::Memory32Size => wasmer_vm_memory32_size as usize,
         LibCall::ImportedMemory32Size => wasmer_vm_imported_memory32_size as usize,
+        LibCall::Memory64Size => wasmer_vm_memory64_size as usize,
+        LibCall::ImportedMemory64Size => wasmer_vm_imported_memory64_size as usize,
         LibCall::TableCopy => wasmer_vm_table_copy as usize,
         LibCall::TableInit => wasmer_vm_table_init as usize,
         LibCall::TableFill => wasmer_vm_table_fill as usize,
@@ -720,8 +1199,27 @@ pub fn function_pointer(libcall: LibCall) -> usize {
         LibCall::Memory32Fill => wasmer_vm_memory32_fill as usize,
         LibCall::ImportedMemory32Fill => wasmer_vm_memory32_fill as usize,
         LibCall::Memory32Init => wasmer_vm_memory32_init as usize,
+        LibCall::Memory64Copy => wasmer_vm_memory64_copy as usize,
+        LibCall::ImportedMemory64Copy => wasmer_vm_imported_memory64_copy as usize,
+        LibCall::Memory64Fill => wasmer_vm_memory64_fill as usize,
+        LibCall::ImportedMemory64Fill => wasmer_vm_imported_memory64_fill as usize,
+        LibCall::Memory64Init => wasmer_vm_memory64_init as usize,
+        LibCall::MemoryCopyBetween => wasmer_vm_memory_copy_between as usize,
         LibCall::DataDrop => wasmer_vm_data_drop as usize,
+        LibCall::Memory32AtomicWait32 => wasmer_vm_memory32_atomic_wait32 as usize,
+        LibCall::Memory32AtomicWait64 => wasmer_vm_memory32_atomic_wait64 as usize,
+        LibCall::Memory32AtomicNotify => wasmer_vm_memory32_atomic_notify as usize,
+        LibCall::TailCall => wasmer_vm_tail_call as usize,
+        LibCall::TailCallEntered => wasmer_vm_tail_call_entered as usize,
+        LibCall::Throw => wasmer_vm_throw as usize,
+        LibCall::ExceptionThrown => wasmer_vm_exception_thrown as usize,
+        LibCall::ExceptionCatch => wasmer_vm_exception_catch as usize,
+        LibCall::ExceptionMatches => wasmer_vm_exception_matches as usize,
+        LibCall::ExceptionPayload => wasmer_vm_exception_payload as usize,
+        LibCall::Rethrow => wasmer_vm_rethrow as usize,
+        LibCall::ExceptionDrop => wasmer_vm_exception_drop as usize,
         LibCall::Probestack => wasmer_vm_probestack as usize,
         LibCall::RaiseTrap => wasmer_vm_raise_trap as usize,
+        LibCall::StackLimit => wasmer_vm_stack_limit as usize,
     }
 }
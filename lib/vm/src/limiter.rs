@@ -0,0 +1,239 @@
+//! Limits on the resources used by the instances of a store.
+
+use crate::memory::{Memory, MemoryError};
+use crate::table::{Table, TableElement};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+use wasmer_types::entity::{BoxedSlice, PrimaryMap};
+use wasmer_types::{LocalMemoryIndex, LocalTableIndex, MemoryIndex, ModuleInfo, Pages, TableIndex};
+
+/// Error returned by a [`ResourceLimiter`] to deny a request.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceLimitError {
+    /// Denies the request without trapping: `memory.grow` and
+    /// `table.grow` return `-1`, and instantiation fails.
+    #[error("resource limit exceeded")]
+    Fail,
+    /// Denies the request and traps with the given message.
+    #[error("{0}")]
+    Trap(String),
+}
+
+/// Limits the memories, tables and instances of a store.
+///
+/// The limiter is consulted every time an instance is created, with the
+/// initial sizes of the memories and tables it defines, and every time
+/// WebAssembly code executes `memory.grow` or `table.grow`, or the host
+/// grows a memory or table exported by an instance. The resources granted
+/// to an instance are released when it is dropped, which lets a limiter
+/// track the aggregate usage of all the instances of the store.
+///
+/// The memories and tables created by the host are not accounted for,
+/// except for what the instances importing them grow them by.
+pub trait ResourceLimiter: Send + Sync {
+    /// Called before a memory grows from `current` to `desired` pages.
+    ///
+    /// `current` is 0 when the memory is created by an instantiation.
+    fn memory_growing(
+        &self,
+        current: Pages,
+        desired: Pages,
+        maximum: Option<Pages>,
+    ) -> Result<(), ResourceLimitError>;
+
+    /// Called before a table grows from `current` to `desired` elements.
+    ///
+    /// `current` is 0 when the table is created by an instantiation.
+    fn table_growing(
+        &self,
+        current: u32,
+        desired: u32,
+        maximum: Option<u32>,
+    ) -> Result<(), ResourceLimitError>;
+
+    /// Called before an instance is created.
+    fn instance_creating(&self) -> Result<(), ResourceLimitError> {
+        Ok(())
+    }
+
+    /// Called when `pages` pages of memory that were granted are released,
+    /// because the memory could not grow or because its instance is dropped.
+    fn memory_released(&self, _pages: Pages) {}
+
+    /// Called when `elements` table elements that were granted are
+    /// released, because the table could not grow or because its instance
+    /// is dropped.
+    fn table_released(&self, _elements: u32) {}
+
+    /// Called when an instance is dropped.
+    fn instance_released(&self) {}
+}
+
+/// The resources that a [`ResourceLimiter`] granted to an instance.
+///
+/// The grants are recorded for each memory and table of the instance,
+/// imported or not, and are released when the instance is dropped. The
+/// memories and tables that an instance exports are charged to it
+/// whoever grows them, and the ones it imports are charged to it for
+/// what it grows them by.
+pub(crate) struct InstanceLimits {
+    limiter: Arc<dyn ResourceLimiter>,
+    memories: BoxedSlice<MemoryIndex, AtomicU32>,
+    tables: BoxedSlice<TableIndex, AtomicU32>,
+}
+
+impl InstanceLimits {
+    /// Asks `limiter` for the resources of a new instance of `module`
+    /// that defines `memories` and `tables`, and releases the resources
+    /// granted so far if one of them is denied.
+    pub(crate) fn new(
+        limiter: Arc<dyn ResourceLimiter>,
+        module: &ModuleInfo,
+        memories: &BoxedSlice<LocalMemoryIndex, Arc<dyn Memory>>,
+        tables: &BoxedSlice<LocalTableIndex, Arc<dyn Table>>,
+    ) -> Result<Self, ResourceLimitError> {
+        limiter.instance_creating()?;
+        let limits = Self {
+            memories: module
+                .memories
+                .keys()
+                .map(|_| AtomicU32::new(0))
+                .collect::<PrimaryMap<MemoryIndex, _>>()
+                .into_boxed_slice(),
+            tables: module
+                .tables
+                .keys()
+                .map(|_| AtomicU32::new(0))
+                .collect::<PrimaryMap<TableIndex, _>>()
+                .into_boxed_slice(),
+            limiter,
+        };
+
+        // Dropping `limits` releases what it was granted so far.
+        for (index, memory) in memories.iter() {
+            let size = memory.size();
+            limits
+                .limiter
+                .memory_growing(Pages(0), size, memory.ty().maximum)?;
+            limits.memories[module.memory_index(index)].store(size.0, Ordering::SeqCst);
+        }
+        for (index, table) in tables.iter() {
+            let size = table.size();
+            limits.limiter.table_growing(0, size, table.ty().maximum)?;
+            limits.tables[module.table_index(index)].store(size, Ordering::SeqCst);
+        }
+
+        Ok(limits)
+    }
+
+    /// Grows `memory`, the memory `index` of the instance, by `delta`
+    /// pages if the limiter allows it.
+    pub(crate) fn memory_grow(
+        &self,
+        index: MemoryIndex,
+        memory: &dyn Memory,
+        delta: Pages,
+    ) -> Result<Pages, MemoryError> {
+        let current = memory.size();
+        let ty = memory.ty();
+        let maximum = ty.maximum;
+        // `Pages::checked_add` stops at the 32-bit limit, which 64-bit
+        // memories can grow past.
+        let desired = match current.0.checked_add(delta.0).map(Pages) {
+            Some(desired) if desired <= maximum.unwrap_or_else(|| ty.max_pages()) => desired,
+            _ => {
+                return Err(MemoryError::CouldNotGrow {
+                    current,
+                    attempted_delta: delta,
+                })
+            }
+        };
+        self.limiter
+            .memory_growing(current, desired, maximum)
+            .map_err(MemoryError::ResourceLimit)?;
+
+        let result = memory.grow(delta);
+        match result {
+            Ok(_) => {
+                self.memories[index].fetch_add(delta.0, Ordering::SeqCst);
+            }
+            Err(_) => self.limiter.memory_released(delta),
+        }
+        result
+    }
+
+    /// Grows `table`, the table `index` of the instance, by `delta`
+    /// elements if the limiter allows it.
+    pub(crate) fn table_grow(
+        &self,
+        index: TableIndex,
+        table: &dyn Table,
+        delta: u32,
+        init_value: TableElement,
+    ) -> Result<Option<u32>, ResourceLimitError> {
+        let current = table.size();
+        let maximum = table.ty().maximum;
+        let desired = match current.checked_add(delta) {
+            Some(desired) if desired <= maximum.unwrap_or(u32::MAX) => desired,
+            _ => return Ok(None),
+        };
+        self.limiter.table_growing(current, desired, maximum)?;
+
+        let result = table.grow(delta, init_value);
+        match result {
+            Some(_) => {
+                self.tables[index].fetch_add(delta, Ordering::SeqCst);
+            }
+            None => self.limiter.table_released(delta),
+        }
+        Ok(result)
+    }
+
+    /// Releases up to `pages` of the pages granted for the memory `index`,
+    /// after it shrank by that much.
+    pub(crate) fn memory_shrunk(&self, index: MemoryIndex, pages: Pages) {
+        let released = release(&self.memories[index], pages.0);
+        if released > 0 {
+            self.limiter.memory_released(Pages(released));
+        }
+    }
+
+    /// Releases up to `elements` of the elements granted for the table
+    /// `index`, after it shrank by that much.
+    pub(crate) fn table_shrunk(&self, index: TableIndex, elements: u32) {
+        let released = release(&self.tables[index], elements);
+        if released > 0 {
+            self.limiter.table_released(released);
+        }
+    }
+}
+
+impl Drop for InstanceLimits {
+    fn drop(&mut self) {
+        for granted in self.memories.values() {
+            let pages = granted.load(Ordering::SeqCst);
+            if pages > 0 {
+                self.limiter.memory_released(Pages(pages));
+            }
+        }
+        for granted in self.tables.values() {
+            let elements = granted.load(Ordering::SeqCst);
+            if elements > 0 {
+                self.limiter.table_released(elements);
+            }
+        }
+        self.limiter.instance_released();
+    }
+}
+
+/// Subtracts up to `amount` from `granted`, and returns how much was
+/// subtracted.
+fn release(granted: &AtomicU32, amount: u32) -> u32 {
+    let previous = granted
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |granted| {
+            Some(granted.saturating_sub(amount))
+        })
+        .unwrap();
+    previous.min(amount)
+}
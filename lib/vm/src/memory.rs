@@ -5,16 +5,18 @@
 //!
 //! `LinearMemory` is to WebAssembly linear memories what `Table` is to WebAssembly tables.
 
-use crate::mmap::Mmap;
+use crate::limiter::ResourceLimitError;
+use crate::memory_image::MemoryImage;
+use crate::mmap::{Mmap, MmapPool};
 use crate::vmcontext::VMMemoryDefinition;
 use loupe::MemoryUsage;
 use more_asserts::assert_ge;
 use std::borrow::BorrowMut;
 use std::cell::UnsafeCell;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
-use std::ptr::NonNull;
-use std::sync::Mutex;
+use std::ptr::{self, NonNull};
+use std::sync::{Arc, Mutex};
 use thiserror::Error;
 use wasmer_types::{Bytes, MemoryStyle, MemoryType, Pages};
 
@@ -55,11 +57,43 @@ pub enum MemoryError {
         /// The number of pages requested as the maximum amount of memory.
         max_allowed: Pages,
     },
+    /// The resource limiter of the store denied the operation.
+    #[error("The resource limiter denied the operation: {0}")]
+    ResourceLimit(ResourceLimitError),
     /// A user defined error value, used for error cases not listed above.
     #[error("A user-defined error occurred: {0}")]
     Generic(String),
 }
 
+/// Error type describing an invalid access to the contents of a Wasm Memory.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryAccessError {
+    /// The access is out of the bounds of the memory.
+    #[error("out of bounds memory access")]
+    HeapOutOfBounds,
+    /// The address or the length of the access overflowed.
+    #[error("address calculation overflow")]
+    Overflow,
+    /// The bytes read from the memory are not a valid UTF-8 string.
+    #[error("string is not valid utf-8")]
+    NonUtf8String,
+}
+
+/// Returns a pointer to the `len` bytes at `offset` of the memory described
+/// by `definition`, if they are in bounds.
+fn checked_range(
+    definition: &VMMemoryDefinition,
+    offset: u64,
+    len: usize,
+) -> Result<*mut u8, MemoryAccessError> {
+    let len = u64::try_from(len).map_err(|_| MemoryAccessError::Overflow)?;
+    let end = offset.checked_add(len).ok_or(MemoryAccessError::Overflow)?;
+    if end > definition.current_length as u64 {
+        return Err(MemoryAccessError::HeapOutOfBounds);
+    }
+    Ok(unsafe { definition.base.add(offset as usize) })
+}
+
 /// Trait for implementing Wasm Memory used by Wasmer.
 pub trait Memory: fmt::Debug + Send + Sync + MemoryUsage {
     /// Returns the memory type for this memory.
@@ -78,6 +112,37 @@ pub trait Memory: fmt::Debug + Send + Sync + MemoryUsage {
     ///
     /// The pointer returned in [`VMMemoryDefinition`] must be valid for the lifetime of this memory.
     fn vmmemory(&self) -> NonNull<VMMemoryDefinition>;
+
+    /// Maps `image` copy-on-write at the start of the memory, which must
+    /// not have been written to since its creation.
+    ///
+    /// Returns `false` if the memory doesn't support images, in which case
+    /// the data segments are copied into it instead.
+    fn map_image(&self, _image: &MemoryImage) -> Result<bool, MemoryError> {
+        Ok(false)
+    }
+
+    /// Shrinks the memory back to its minimum size, zeroed, as if it was
+    /// just created.
+    ///
+    /// Memories that don't support it return an error.
+    fn reset(&self) -> Result<(), MemoryError> {
+        Err(MemoryError::Generic(
+            "the memory does not support being reset".to_string(),
+        ))
+    }
+
+    /// Copies the bytes at `offset` of the memory into `buf`.
+    ///
+    /// Implementations must synchronize with `grow`, since the memory
+    /// may move or change size while it grows.
+    fn read(&self, offset: u64, buf: &mut [u8]) -> Result<(), MemoryAccessError>;
+
+    /// Copies `data` into the memory at `offset`.
+    ///
+    /// Implementations must synchronize with `grow`, since the memory
+    /// may move or change size while it grows.
+    fn write(&self, offset: u64, data: &[u8]) -> Result<(), MemoryAccessError>;
 }
 
 /// A linear memory instance.
@@ -146,7 +211,7 @@ impl LinearMemory {
     /// This creates a `LinearMemory` with owned metadata: this can be used to create a memory
     /// that will be imported into Wasm modules.
     pub fn new(memory: &MemoryType, style: &MemoryStyle) -> Result<Self, MemoryError> {
-        unsafe { Self::new_internal(memory, style, None) }
+        unsafe { Self::new_internal(memory, style, None, None) }
     }
 
     /// Create a new linear memory instance with specified minimum and maximum number of wasm pages.
@@ -161,7 +226,21 @@ impl LinearMemory {
         style: &MemoryStyle,
         vm_memory_location: NonNull<VMMemoryDefinition>,
     ) -> Result<Self, MemoryError> {
-        Self::new_internal(memory, style, Some(vm_memory_location))
+        Self::new_internal(memory, style, Some(vm_memory_location), None)
+    }
+
+    /// Create a new linear memory instance with VM owned metadata, in a
+    /// slot of `pool`.
+    ///
+    /// # Safety
+    /// - `vm_memory_location` must point to a valid location in VM memory.
+    pub(crate) unsafe fn from_pool(
+        memory: &MemoryType,
+        style: &MemoryStyle,
+        vm_memory_location: NonNull<VMMemoryDefinition>,
+        pool: &Arc<MmapPool>,
+    ) -> Result<Self, MemoryError> {
+        Self::new_internal(memory, style, Some(vm_memory_location), Some(pool))
     }
 
     /// Build a `LinearMemory` with either self-owned or VM owned metadata.
@@ -169,19 +248,21 @@ impl LinearMemory {
         memory: &MemoryType,
         style: &MemoryStyle,
         vm_memory_location: Option<NonNull<VMMemoryDefinition>>,
+        pool: Option<&Arc<MmapPool>>,
     ) -> Result<Self, MemoryError> {
-        if memory.minimum > Pages::max_value() {
+        if memory.minimum > memory.max_pages() {
             return Err(MemoryError::MinimumMemoryTooLarge {
                 min_requested: memory.minimum,
-                max_allowed: Pages::max_value(),
+                max_allowed: memory.max_pages(),
             });
         }
-        // `maximum` cannot be set to more than `65536` pages.
+        // `maximum` cannot be set to more than `65536` pages, or more than
+        // `Pages` can count for 64-bit memories.
         if let Some(max) = memory.maximum {
-            if max > Pages::max_value() {
+            if max > memory.max_pages() {
                 return Err(MemoryError::MaximumMemoryTooLarge {
                     max_requested: max,
-                    max_allowed: Pages::max_value(),
+                    max_allowed: memory.max_pages(),
                 });
             }
             if max < memory.minimum {
@@ -193,6 +274,11 @@ impl LinearMemory {
                 });
             }
         }
+        if memory.shared && memory.maximum.is_none() {
+            return Err(MemoryError::InvalidMemory {
+                reason: "shared memories must have a maximum size".to_string(),
+            });
+        }
 
         let offset_guard_bytes = style.offset_guard_size() as usize;
 
@@ -206,6 +292,9 @@ impl LinearMemory {
             };
 
         let minimum_pages = match style {
+            // A shared memory reserves its maximum up front, so that it never
+            // moves while other threads access it.
+            MemoryStyle::Dynamic { .. } if memory.shared => memory.maximum.unwrap(),
             MemoryStyle::Dynamic { .. } => memory.minimum,
             MemoryStyle::Static { bound, .. } => {
                 assert_ge!(*bound, memory.minimum);
@@ -217,9 +306,19 @@ impl LinearMemory {
         let mapped_pages = memory.minimum;
         let mapped_bytes = mapped_pages.bytes();
 
+        let alloc = match pool {
+            Some(pool) if request_bytes > pool.slot_size() => {
+                return Err(MemoryError::Region(format!(
+                    "the memory needs {} bytes but the slots of the pool have {} bytes",
+                    request_bytes,
+                    pool.slot_size()
+                )))
+            }
+            Some(pool) => pool.allocate(mapped_bytes.0),
+            None => Mmap::accessible_reserved(mapped_bytes.0, request_bytes),
+        };
         let mut mmap = WasmMmap {
-            alloc: Mmap::accessible_reserved(mapped_bytes.0, request_bytes)
-                .map_err(MemoryError::Region)?,
+            alloc: alloc.map_err(MemoryError::Region)?,
             size: memory.minimum,
         };
 
@@ -303,13 +402,15 @@ impl Memory for LinearMemory {
             return Ok(mmap.size);
         }
 
-        let new_pages = mmap
-            .size
-            .checked_add(delta)
-            .ok_or(MemoryError::CouldNotGrow {
-                current: mmap.size,
-                attempted_delta: delta,
-            })?;
+        let new_pages =
+            mmap.size
+                .0
+                .checked_add(delta.0)
+                .map(Pages)
+                .ok_or(MemoryError::CouldNotGrow {
+                    current: mmap.size,
+                    attempted_delta: delta,
+                })?;
         let prev_pages = mmap.size;
 
         if let Some(maximum) = self.maximum {
@@ -324,7 +425,7 @@ impl Memory for LinearMemory {
         // Wasm linear memories are never allowed to grow beyond what is
         // indexable. If the memory has no maximum, enforce the greatest
         // limit here.
-        if new_pages >= Pages::max_value() {
+        if new_pages >= self.memory.max_pages() {
             // Linear memory size would exceed the index range.
             return Err(MemoryError::CouldNotGrow {
                 current: mmap.size,
@@ -380,4 +481,73 @@ impl Memory for LinearMemory {
         let _mmap_guard = self.mmap.lock().unwrap();
         unsafe { self.get_vm_memory_definition() }
     }
+
+    /// Maps `image` copy-on-write at the start of the memory.
+    fn map_image(&self, image: &MemoryImage) -> Result<bool, MemoryError> {
+        let mut mmap_guard = self.mmap.lock().unwrap();
+        let mmap = mmap_guard.borrow_mut();
+        if image.len() > mmap.size.bytes().0 {
+            return Err(MemoryError::InvalidMemory {
+                reason: format!(
+                    "the image ({} bytes) is larger than the memory ({} pages)",
+                    image.len(),
+                    mmap.size.0
+                ),
+            });
+        }
+        unsafe { image.map_at(mmap.alloc.as_mut_ptr()) }.map_err(MemoryError::Region)?;
+        Ok(true)
+    }
+
+    /// Shrinks the memory back to its minimum size, and discards its
+    /// contents.
+    ///
+    /// The pages beyond the minimum size are made inaccessible again, and
+    /// a memory that moved when it grew keeps its new allocation.
+    fn reset(&self) -> Result<(), MemoryError> {
+        if self.memory.shared {
+            return Err(MemoryError::Generic(
+                "shared memories can't be reset".to_string(),
+            ));
+        }
+        let mut mmap_guard = self.mmap.lock().unwrap();
+        let mmap = mmap_guard.borrow_mut();
+        let minimum = self.memory.minimum;
+        mmap.alloc
+            .reset(minimum.bytes().0)
+            .map_err(MemoryError::Region)?;
+        mmap.size = minimum;
+
+        // update memory definition
+        unsafe {
+            let mut md_ptr = self.get_vm_memory_definition();
+            let md = md_ptr.as_mut();
+            md.current_length = minimum.bytes().0.try_into().unwrap();
+            md.base = mmap.alloc.as_mut_ptr() as _;
+        }
+
+        Ok(())
+    }
+
+    /// Copies the bytes at `offset` of the memory into `buf`, while the
+    /// memory can't grow.
+    fn read(&self, offset: u64, buf: &mut [u8]) -> Result<(), MemoryAccessError> {
+        let _mmap_guard = self.mmap.lock().unwrap();
+        unsafe {
+            let src = checked_range(self.get_vm_memory_definition().as_ref(), offset, buf.len())?;
+            ptr::copy(src, buf.as_mut_ptr(), buf.len());
+        }
+        Ok(())
+    }
+
+    /// Copies `data` into the memory at `offset`, while the memory can't
+    /// grow.
+    fn write(&self, offset: u64, data: &[u8]) -> Result<(), MemoryAccessError> {
+        let _mmap_guard = self.mmap.lock().unwrap();
+        unsafe {
+            let dst = checked_range(self.get_vm_memory_definition().as_ref(), offset, data.len())?;
+            ptr::copy(data.as_ptr(), dst, data.len());
+        }
+        Ok(())
+    }
 }
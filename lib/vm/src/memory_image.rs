@@ -0,0 +1,229 @@
+//! Copy-on-write images of the initial contents of linear memories.
+//!
+//! Instead of copying the data segments of a module into its memories at
+//! every instantiation, the segments can be written once into an image,
+//! which is then mapped copy-on-write at the start of the memories. Pages
+//! are only copied when an instance writes to them.
+//!
+//! Images are only supported on Linux, where they are backed by a memfd.
+
+use crate::mmap::round_up_to_page_size;
+use std::fmt;
+use wasmer_types::entity::{EntityRef, PrimaryMap};
+use wasmer_types::{DataInitializer, LocalMemoryIndex, ModuleInfo};
+
+/// The initial contents of a linear memory, that can be mapped
+/// copy-on-write at its start.
+pub struct MemoryImage {
+    #[cfg(target_os = "linux")]
+    file: std::fs::File,
+    len: usize,
+}
+
+impl MemoryImage {
+    /// Creates an image of `len` bytes, a multiple of the page size,
+    /// initialized with `segments`.
+    #[cfg(target_os = "linux")]
+    fn new<'a>(
+        len: usize,
+        segments: impl Iterator<Item = &'a DataInitializer<'a>>,
+    ) -> Result<Option<Self>, String> {
+        use std::ffi::CStr;
+        use std::io;
+        use std::os::unix::fs::FileExt;
+        use std::os::unix::io::FromRawFd;
+
+        let name = CStr::from_bytes_with_nul(b"wasmer_memory_image\0").unwrap();
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+        if fd == -1 {
+            return Err(io::Error::last_os_error().to_string());
+        }
+        let file = unsafe { std::fs::File::from_raw_fd(fd) };
+        file.set_len(len as u64).map_err(|e| e.to_string())?;
+        for segment in segments {
+            file.write_all_at(segment.data, segment.location.offset as u64)
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(Some(Self { file, len }))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new<'a>(
+        _len: usize,
+        _segments: impl Iterator<Item = &'a DataInitializer<'a>>,
+    ) -> Result<Option<Self>, String> {
+        Ok(None)
+    }
+
+    /// Returns the size of the image in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the image is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maps the image copy-on-write at `base`, replacing the pages there.
+    ///
+    /// # Safety
+    /// - `base` must be page-aligned and point to at least `self.len()`
+    ///   accessible bytes, owned by the caller.
+    #[cfg(target_os = "linux")]
+    pub(crate) unsafe fn map_at(&self, base: *mut u8) -> Result<(), String> {
+        use std::os::unix::io::AsRawFd;
+
+        if self.len == 0 {
+            return Ok(());
+        }
+        let ptr = libc::mmap(
+            base as *mut libc::c_void,
+            self.len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_FIXED,
+            self.file.as_raw_fd(),
+            0,
+        );
+        if ptr as isize == -1_isize {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) unsafe fn map_at(&self, _base: *mut u8) -> Result<(), String> {
+        Err("memory images are not supported on this platform".to_string())
+    }
+}
+
+impl fmt::Debug for MemoryImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryImage")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// The memory images of the local memories of a module.
+#[derive(Debug)]
+pub struct ModuleMemoryImages {
+    images: PrimaryMap<LocalMemoryIndex, Option<MemoryImage>>,
+}
+
+impl ModuleMemoryImages {
+    /// Creates the memory images of a module from its data initializers.
+    ///
+    /// Returns `None` if the memories of the module can't be initialized
+    /// with images, because the platform doesn't support them or because
+    /// a data segment is not at a constant offset within the initial size
+    /// of a local memory. Such modules are initialized by copying their
+    /// data segments, which keeps the order of the writes and the traps
+    /// of out-of-bounds segments.
+    pub fn new(
+        module: &ModuleInfo,
+        data_initializers: &[DataInitializer<'_>],
+    ) -> Result<Option<Self>, String> {
+        let num_local_memories = module.memories.len() - module.num_imported_memories;
+        let mut lengths = vec![0; num_local_memories];
+
+        for init in data_initializers {
+            let index = match module.local_memory_index(init.location.memory_index) {
+                Some(index) if init.location.base.is_none() => index,
+                _ => return Ok(None),
+            };
+            let minimum = module.memories[init.location.memory_index].minimum;
+            match init.location.offset.checked_add(init.data.len()) {
+                Some(end) if end <= minimum.bytes().0 => {
+                    let length = &mut lengths[index.index()];
+                    *length = (*length).max(end);
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        let page_size = region::page::size();
+        let mut images = PrimaryMap::with_capacity(num_local_memories);
+        for (index, length) in lengths.into_iter().enumerate() {
+            if length == 0 {
+                images.push(None);
+                continue;
+            }
+            let segments = data_initializers.iter().filter(|init| {
+                module.local_memory_index(init.location.memory_index)
+                    == Some(LocalMemoryIndex::new(index))
+            });
+            match MemoryImage::new(round_up_to_page_size(length, page_size), segments)? {
+                Some(image) => images.push(Some(image)),
+                None => return Ok(None),
+            };
+        }
+
+        Ok(Some(Self { images }))
+    }
+
+    /// Returns the image of a local memory, if it has initial contents.
+    pub fn get(&self, index: LocalMemoryIndex) -> Option<&MemoryImage> {
+        self.images.get(index)?.as_ref()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::mmap::Mmap;
+    use wasmer_types::{DataInitializerLocation, MemoryIndex, MemoryType};
+
+    fn module(memories: usize) -> ModuleInfo {
+        let mut module = ModuleInfo::new();
+        for _ in 0..memories {
+            module.memories.push(MemoryType::new(1, None, false));
+        }
+        module
+    }
+
+    fn init(memory: usize, offset: usize, data: &[u8]) -> DataInitializer<'_> {
+        DataInitializer {
+            location: DataInitializerLocation {
+                memory_index: MemoryIndex::new(memory),
+                base: None,
+                offset,
+            },
+            data,
+        }
+    }
+
+    #[test]
+    fn images_are_mapped_copy_on_write() {
+        let module = module(2);
+        let inits = [init(0, 10, b"hello"), init(0, 12, b"LLO")];
+        let images = ModuleMemoryImages::new(&module, &inits).unwrap().unwrap();
+        assert!(images.get(LocalMemoryIndex::new(1)).is_none());
+        let image = images.get(LocalMemoryIndex::new(0)).unwrap();
+        assert_eq!(image.len(), region::page::size());
+
+        let mut first = Mmap::with_at_least(image.len()).unwrap();
+        let mut second = Mmap::with_at_least(image.len()).unwrap();
+        unsafe {
+            image.map_at(first.as_mut_ptr()).unwrap();
+            image.map_at(second.as_mut_ptr()).unwrap();
+        }
+        assert_eq!(&first.as_slice()[10..15], b"heLLO");
+        first.as_mut_slice()[10] = b'j';
+        assert_eq!(&first.as_slice()[10..15], b"jeLLO");
+        assert_eq!(&second.as_slice()[10..15], b"heLLO");
+    }
+
+    #[test]
+    fn out_of_bounds_segments_have_no_images() {
+        let module = module(1);
+        let page = vec![0; 0x1_0000];
+        assert!(ModuleMemoryImages::new(&module, &[init(0, 1, &page)])
+            .unwrap()
+            .is_none());
+        assert!(ModuleMemoryImages::new(&module, &[init(0, 0, &page)])
+            .unwrap()
+            .is_some());
+    }
+}
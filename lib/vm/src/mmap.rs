@@ -6,13 +6,13 @@
 
 use loupe::{MemoryUsage, MemoryUsageTracker};
 use more_asserts::assert_le;
-use more_asserts::assert_lt;
 use std::io;
 use std::ptr;
 use std::slice;
+use std::sync::{Arc, Mutex};
 
 /// Round `size` up to the nearest multiple of `page_size`.
-fn round_up_to_page_size(size: usize, page_size: usize) -> usize {
+pub(crate) fn round_up_to_page_size(size: usize, page_size: usize) -> usize {
     (size + (page_size - 1)) & !(page_size - 1)
 }
 
@@ -26,6 +26,9 @@ pub struct Mmap {
     // the coordination all happens at the OS layer.
     ptr: usize,
     len: usize,
+    // The pool this mapping is a slot of, and the index of the slot. Pooled
+    // mappings are given back to their pool instead of being unmapped.
+    pool: Option<(Arc<MmapPool>, usize)>,
 }
 
 impl Mmap {
@@ -38,6 +41,7 @@ impl Mmap {
         Self {
             ptr: empty.as_ptr() as usize,
             len: 0,
+            pool: None,
         }
     }
 
@@ -86,6 +90,7 @@ impl Mmap {
             Self {
                 ptr: ptr as usize,
                 len: mapping_size,
+                pool: None,
             }
         } else {
             // Reserve the mapping size.
@@ -106,6 +111,7 @@ impl Mmap {
             let mut result = Self {
                 ptr: ptr as usize,
                 len: mapping_size,
+                pool: None,
             };
 
             if accessible_size != 0 {
@@ -156,6 +162,7 @@ impl Mmap {
             Self {
                 ptr: ptr as usize,
                 len: mapping_size,
+                pool: None,
             }
         } else {
             // Reserve the mapping size.
@@ -168,6 +175,7 @@ impl Mmap {
             let mut result = Self {
                 ptr: ptr as usize,
                 len: mapping_size,
+                pool: None,
             };
 
             if accessible_size != 0 {
@@ -187,8 +195,8 @@ impl Mmap {
         let page_size = region::page::size();
         assert_eq!(start & (page_size - 1), 0);
         assert_eq!(len & (page_size - 1), 0);
-        assert_lt!(len, self.len);
-        assert_lt!(start, self.len - len);
+        assert_le!(len, self.len);
+        assert_le!(start, self.len - len);
 
         // Commit the accessible size.
         let ptr = self.ptr as *const u8;
@@ -207,8 +215,8 @@ impl Mmap {
         let page_size = region::page::size();
         assert_eq!(start & (page_size - 1), 0);
         assert_eq!(len & (page_size - 1), 0);
-        assert_lt!(len, self.len);
-        assert_lt!(start, self.len - len);
+        assert_le!(len, self.len);
+        assert_le!(start, self.len - len);
 
         // Commit the accessible size.
         let ptr = self.ptr as *const u8;
@@ -228,6 +236,18 @@ impl Mmap {
         Ok(())
     }
 
+    /// Discards the contents of the memory, and makes only its first
+    /// `accessible_size` bytes accessible, zeroed. `accessible_size` must be
+    /// a native page-size multiple.
+    pub fn reset(&mut self, accessible_size: usize) -> Result<(), String> {
+        assert_le!(accessible_size, self.len);
+        unsafe { decommit(self.ptr, self.len) }?;
+        if accessible_size != 0 {
+            self.make_accessible(0, accessible_size)?;
+        }
+        Ok(())
+    }
+
     /// Return the allocated memory as a slice of u8.
     pub fn as_slice(&self) -> &[u8] {
         unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
@@ -262,7 +282,9 @@ impl Mmap {
 impl Drop for Mmap {
     #[cfg(not(target_os = "windows"))]
     fn drop(&mut self) {
-        if self.len != 0 {
+        if let Some((pool, index)) = self.pool.take() {
+            pool.release(index);
+        } else if self.len != 0 {
             let r = unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
             assert_eq!(r, 0, "munmap failed: {}", io::Error::last_os_error());
         }
@@ -270,7 +292,9 @@ impl Drop for Mmap {
 
     #[cfg(target_os = "windows")]
     fn drop(&mut self) {
-        if self.len != 0 {
+        if let Some((pool, index)) = self.pool.take() {
+            pool.release(index);
+        } else if self.len != 0 {
             use winapi::ctypes::c_void;
             use winapi::um::memoryapi::VirtualFree;
             use winapi::um::winnt::MEM_RELEASE;
@@ -288,6 +312,129 @@ impl MemoryUsage for Mmap {
     }
 }
 
+/// A reservation of memory divided in slots of the same size.
+///
+/// The slots are handed out as `Mmap`s, which give their slot back to the
+/// pool when they are dropped. A slot that is given back is made
+/// inaccessible and zeroed again, so that it can be reused without the
+/// cost of reserving new memory.
+#[derive(Debug)]
+pub(crate) struct MmapPool {
+    reservation: Mmap,
+    slot_size: usize,
+    free: Mutex<Vec<usize>>,
+}
+
+impl MmapPool {
+    /// Reserves `slots` slots of at least `slot_size` bytes.
+    pub(crate) fn new(slots: usize, slot_size: usize) -> Result<Arc<Self>, String> {
+        let slot_size = round_up_to_page_size(slot_size, region::page::size());
+        let reservation_size = slots
+            .checked_mul(slot_size)
+            .ok_or_else(|| "the pool reservation is too large".to_string())?;
+
+        Ok(Arc::new(Self {
+            reservation: Mmap::accessible_reserved(0, reservation_size)?,
+            slot_size,
+            free: Mutex::new((0..slots).rev().collect()),
+        }))
+    }
+
+    /// Returns the size in bytes of a slot.
+    pub(crate) fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    /// Takes a free slot, and makes its first `accessible_size` bytes
+    /// accessible.
+    pub(crate) fn allocate(self: &Arc<Self>, accessible_size: usize) -> Result<Mmap, String> {
+        let accessible_size = round_up_to_page_size(accessible_size, region::page::size());
+        if accessible_size > self.slot_size {
+            return Err(format!(
+                "{} bytes don't fit in a slot of {} bytes",
+                accessible_size, self.slot_size
+            ));
+        }
+        let index = self
+            .free
+            .lock()
+            .unwrap()
+            .pop()
+            .ok_or_else(|| "all the slots of the pool are in use".to_string())?;
+
+        let mut mmap = Mmap {
+            ptr: self.reservation.ptr + index * self.slot_size,
+            len: self.slot_size,
+            pool: Some((self.clone(), index)),
+        };
+        if accessible_size != 0 {
+            mmap.make_accessible(0, accessible_size)?;
+        }
+        Ok(mmap)
+    }
+
+    /// Returns the number of slots that are not in use.
+    pub(crate) fn available(&self) -> usize {
+        self.free.lock().unwrap().len()
+    }
+
+    /// Zeroes and decommits a slot, and gives it back to the pool.
+    fn release(&self, index: usize) {
+        let ptr = self.reservation.ptr + index * self.slot_size;
+        if let Err(error) = unsafe { decommit(ptr, self.slot_size) } {
+            panic!("failed to release a slot of the pool: {}", error);
+        }
+        self.free.lock().unwrap().push(index);
+    }
+}
+
+/// Discards the contents of the `len` bytes at `ptr`, including any file
+/// mapped there, and makes them inaccessible. They read as zeroes when
+/// they are made accessible again.
+///
+/// # Safety
+/// - `ptr` and `len` must be native page-size multiples and describe a
+///   range of a mapping owned by the caller.
+#[cfg(not(target_os = "windows"))]
+unsafe fn decommit(ptr: usize, len: usize) -> Result<(), String> {
+    if len == 0 {
+        return Ok(());
+    }
+    // Mapping fresh pages over the range discards its contents.
+    let ptr = libc::mmap(
+        ptr as *mut libc::c_void,
+        len,
+        libc::PROT_NONE,
+        libc::MAP_PRIVATE | libc::MAP_ANON | libc::MAP_FIXED,
+        -1,
+        0,
+    );
+    if ptr as isize == -1_isize {
+        return Err(io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+/// Discards the contents of the `len` bytes at `ptr` and makes them
+/// inaccessible. They read as zeroes when they are made accessible again.
+///
+/// # Safety
+/// - `ptr` and `len` must be native page-size multiples and describe a
+///   range of a mapping owned by the caller.
+#[cfg(target_os = "windows")]
+unsafe fn decommit(ptr: usize, len: usize) -> Result<(), String> {
+    use winapi::ctypes::c_void;
+    use winapi::um::memoryapi::VirtualFree;
+    use winapi::um::winnt::MEM_DECOMMIT;
+    if len == 0 {
+        return Ok(());
+    }
+    if VirtualFree(ptr as *mut c_void, len, MEM_DECOMMIT) == 0 {
+        return Err(io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
 fn _assert() {
     fn _assert_send_sync<T: Send + Sync>() {}
     _assert_send_sync::<Mmap>();
@@ -304,4 +451,42 @@ mod tests {
         assert_eq!(round_up_to_page_size(4096, 4096), 4096);
         assert_eq!(round_up_to_page_size(4097, 4096), 8192);
     }
+
+    #[test]
+    fn pool_slots_are_reused_zeroed() {
+        let page_size = region::page::size();
+        let pool = MmapPool::new(2, page_size * 2).unwrap();
+        assert_eq!(pool.slot_size(), page_size * 2);
+
+        let mut first = pool.allocate(page_size).unwrap();
+        let second = pool.allocate(0).unwrap();
+        assert_eq!(first.len(), page_size * 2);
+        assert_ne!(first.as_ptr(), second.as_ptr());
+        assert!(pool.allocate(page_size).is_err());
+        assert!(pool.allocate(page_size * 3).is_err());
+
+        first.as_mut_slice()[..page_size].fill(1);
+        let ptr = first.as_ptr();
+        drop(first);
+        assert_eq!(pool.available(), 1);
+
+        let first = pool.allocate(page_size * 2).unwrap();
+        assert_eq!(first.as_ptr(), ptr);
+        assert!(first.as_slice().iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn reset_zeroes_and_shrinks() {
+        let page_size = region::page::size();
+        let mut mmap = Mmap::accessible_reserved(page_size * 2, page_size * 4).unwrap();
+        mmap.as_mut_slice()[..page_size * 2].fill(1);
+
+        mmap.reset(page_size).unwrap();
+        assert!(mmap.as_slice()[..page_size].iter().all(|&byte| byte == 0));
+
+        mmap.make_accessible(page_size, page_size).unwrap();
+        assert!(mmap.as_slice()[..page_size * 2]
+            .iter()
+            .all(|&byte| byte == 0));
+    }
 }
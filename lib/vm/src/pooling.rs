@@ -0,0 +1,188 @@
+//! A pooling allocator for instances, memories and tables.
+//!
+//! The pooling allocator reserves, up front, a fixed number of slots for
+//! instances, linear memories and tables. Creating an instance takes free
+//! slots instead of allocating and mapping new memory, and dropping it
+//! gives them back, zeroed.
+
+use crate::instance::InstanceAllocator;
+use crate::memory::{LinearMemory, MemoryError};
+use crate::mmap::MmapPool;
+use crate::table::{LinearTable, RawTableElement};
+use crate::vmcontext::{VMMemoryDefinition, VMTableDefinition};
+use std::fmt;
+use std::ptr::NonNull;
+use std::sync::Arc;
+use wasmer_types::{MemoryStyle, MemoryType, ModuleInfo, Pages, TableStyle, TableType};
+
+/// The configuration of a [`PoolingAllocator`].
+#[derive(Debug, Clone)]
+pub struct PoolingConfig {
+    /// The maximum number of instances alive at the same time.
+    pub max_instances: u32,
+
+    /// The maximum size in bytes of an instance, including its `VMContext`.
+    pub max_instance_size: usize,
+
+    /// The maximum number of local memories alive at the same time.
+    pub max_memories: u32,
+
+    /// The number of wasm pages reserved for each memory.
+    pub memory_pages: Pages,
+
+    /// The size in bytes of the offset guard reserved after each memory.
+    pub memory_offset_guard_size: u64,
+
+    /// The maximum number of local tables alive at the same time.
+    pub max_tables: u32,
+
+    /// The number of elements reserved for each table.
+    pub table_elements: u32,
+
+    /// Whether to initialize the memories of instances copy-on-write from
+    /// images of their data segments, instead of copying the data segments.
+    ///
+    /// Images are only supported on Linux.
+    pub memory_init_cow: bool,
+}
+
+impl Default for PoolingConfig {
+    fn default() -> Self {
+        Self {
+            max_instances: 100,
+            max_instance_size: 0x10_0000,
+            max_memories: 100,
+            memory_pages: Pages(160),
+            memory_offset_guard_size: 0x1_0000,
+            max_tables: 100,
+            table_elements: 10_000,
+            memory_init_cow: true,
+        }
+    }
+}
+
+/// An allocator that takes instances, memories and tables from slots
+/// reserved when it is created.
+pub struct PoolingAllocator {
+    config: PoolingConfig,
+    instances: Arc<MmapPool>,
+    memories: Arc<MmapPool>,
+    tables: Arc<MmapPool>,
+}
+
+impl PoolingAllocator {
+    /// Reserves the slots described by `config`.
+    ///
+    /// The slots are reserved address space: they don't use memory until
+    /// they are used.
+    pub fn new(config: PoolingConfig) -> Result<Self, String> {
+        let memory_slot_size = config
+            .memory_pages
+            .bytes()
+            .0
+            .checked_add(config.memory_offset_guard_size as usize)
+            .ok_or_else(|| "the memory slots are too large".to_string())?;
+        let table_slot_size = (config.table_elements as usize)
+            .checked_mul(std::mem::size_of::<RawTableElement>())
+            .ok_or_else(|| "the table slots are too large".to_string())?;
+
+        Ok(Self {
+            instances: MmapPool::new(config.max_instances as usize, config.max_instance_size)?,
+            memories: MmapPool::new(config.max_memories as usize, memory_slot_size)?,
+            tables: MmapPool::new(config.max_tables as usize, table_slot_size)?,
+            config,
+        })
+    }
+
+    /// Returns the configuration of the allocator.
+    pub fn config(&self) -> &PoolingConfig {
+        &self.config
+    }
+
+    /// Returns the number of instance slots that are not in use.
+    pub fn available_instances(&self) -> usize {
+        self.instances.available()
+    }
+
+    /// Returns the number of memory slots that are not in use.
+    pub fn available_memories(&self) -> usize {
+        self.memories.available()
+    }
+
+    /// Returns the number of table slots that are not in use.
+    pub fn available_tables(&self) -> usize {
+        self.tables.available()
+    }
+
+    /// Returns the memory style of memories allocated in the pool.
+    ///
+    /// Memories whose maximum fits in a slot are static: they never move.
+    /// The others, and 64-bit memories, are dynamic, and move out of the
+    /// pool if they grow beyond their slot.
+    pub fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        let maximum = memory.maximum.unwrap_or_else(Pages::max_value);
+        if maximum <= self.config.memory_pages && !memory.memory64 {
+            MemoryStyle::Static {
+                bound: self.config.memory_pages,
+                offset_guard_size: self.config.memory_offset_guard_size,
+            }
+        } else {
+            MemoryStyle::Dynamic {
+                offset_guard_size: self.config.memory_offset_guard_size,
+            }
+        }
+    }
+
+    /// Allocates instance data in a slot of the pool, like
+    /// [`InstanceAllocator::new`].
+    pub fn allocate_instance(
+        &self,
+        module: &ModuleInfo,
+    ) -> Result<
+        (
+            InstanceAllocator,
+            Vec<NonNull<VMMemoryDefinition>>,
+            Vec<NonNull<VMTableDefinition>>,
+        ),
+        String,
+    > {
+        InstanceAllocator::from_pool(module, &self.instances)
+    }
+
+    /// Creates a memory owned by the VM in a slot of the pool.
+    ///
+    /// # Safety
+    /// - `vm_definition_location` must point to a valid location in VM memory.
+    pub unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<LinearMemory, MemoryError> {
+        LinearMemory::from_pool(ty, style, vm_definition_location, &self.memories)
+    }
+
+    /// Creates a table owned by the VM in a slot of the pool.
+    ///
+    /// # Safety
+    /// - `vm_definition_location` must point to a valid location in VM memory.
+    pub unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<LinearTable, String> {
+        LinearTable::from_pool(ty, style, vm_definition_location, &self.tables)
+    }
+}
+
+impl fmt::Debug for PoolingAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PoolingAllocator")
+            .field("config", &self.config)
+            .field("available_instances", &self.available_instances())
+            .field("available_memories", &self.available_memories())
+            .field("available_tables", &self.available_tables())
+            .finish()
+    }
+}
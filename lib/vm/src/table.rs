@@ -6,6 +6,7 @@
 //! `Table` is to WebAssembly tables what `LinearMemory` is to WebAssembly linear memories.
 
 use crate::func_data_registry::VMFuncRef;
+use crate::mmap::{Mmap, MmapPool};
 use crate::vmcontext::VMTableDefinition;
 use crate::Trap;
 use crate::VMExternRef;
@@ -14,8 +15,10 @@ use std::borrow::{Borrow, BorrowMut};
 use std::cell::UnsafeCell;
 use std::convert::TryFrom;
 use std::fmt;
+use std::mem;
 use std::ptr::NonNull;
-use std::sync::Mutex;
+use std::slice;
+use std::sync::{Arc, Mutex};
 use wasmer_types::{ExternRef, TableStyle, TableType, TrapCode, Type as ValType};
 
 /// Trait for implementing the interface of a Wasm table.
@@ -50,6 +53,14 @@ pub trait Table: fmt::Debug + Send + Sync + MemoryUsage {
     /// Return a `VMTableDefinition` for exposing the table to compiled wasm code.
     fn vmtable(&self) -> NonNull<VMTableDefinition>;
 
+    /// Shrinks the table back to its minimum size, with null elements, as
+    /// if it was just created.
+    ///
+    /// Tables that don't support it return an error.
+    fn reset(&self) -> Result<(), String> {
+        Err("the table does not support being reset".to_string())
+    }
+
     /// Copy `len` elements from `src_table[src_index..]` into `dst_table[dst_index..]`.
     ///
     /// # Errors
@@ -160,11 +171,71 @@ impl Default for TableElement {
     }
 }
 
+/// The elements of a `LinearTable`, either on the heap or in a slot of a
+/// pool.
+#[derive(Debug, MemoryUsage)]
+enum TableElements {
+    Vec(Vec<RawTableElement>),
+    Pooled { mmap: Mmap, len: usize },
+}
+
+impl TableElements {
+    fn as_slice(&self) -> &[RawTableElement] {
+        match self {
+            Self::Vec(vec) => vec,
+            Self::Pooled { mmap, len } => unsafe {
+                slice::from_raw_parts(mmap.as_ptr() as *const RawTableElement, *len)
+            },
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [RawTableElement] {
+        match self {
+            Self::Vec(vec) => vec,
+            Self::Pooled { mmap, len } => unsafe {
+                slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut RawTableElement, *len)
+            },
+        }
+    }
+
+    /// Returns whether `len` elements fit without reallocating, for pooled
+    /// elements which can't be reallocated.
+    fn can_grow_to(&self, len: usize) -> bool {
+        match self {
+            Self::Vec(_) => true,
+            Self::Pooled { mmap, .. } => len <= mmap.len() / mem::size_of::<RawTableElement>(),
+        }
+    }
+
+    /// Nulls the elements, and truncates them to `new_len` elements.
+    fn reset(&mut self, new_len: usize) {
+        self.as_mut_slice().fill(RawTableElement::default());
+        match self {
+            Self::Vec(vec) => vec.truncate(new_len),
+            Self::Pooled { len, .. } => *len = new_len,
+        }
+    }
+
+    fn resize(&mut self, new_len: usize, element: RawTableElement) {
+        match self {
+            Self::Vec(vec) => vec.resize(new_len, element),
+            Self::Pooled { .. } => {
+                assert!(self.can_grow_to(new_len));
+                let old_len = self.as_slice().len();
+                if let Self::Pooled { len, .. } = self {
+                    *len = new_len;
+                }
+                self.as_mut_slice()[old_len..].fill(element);
+            }
+        }
+    }
+}
+
 /// A table instance.
 #[derive(Debug, MemoryUsage)]
 pub struct LinearTable {
     // TODO: we can remove the mutex by using atomic swaps and preallocating the max table size
-    vec: Mutex<Vec<RawTableElement>>,
+    vec: Mutex<TableElements>,
     maximum: Option<u32>,
     /// The WebAssembly table description.
     table: TableType,
@@ -197,7 +268,7 @@ impl LinearTable {
     /// This creates a `LinearTable` with metadata owned by a VM, pointed to by
     /// `vm_table_location`: this can be used to create a local table.
     pub fn new(table: &TableType, style: &TableStyle) -> Result<Self, String> {
-        unsafe { Self::new_inner(table, style, None) }
+        unsafe { Self::new_inner(table, style, None, None) }
     }
 
     /// Create a new linear table instance with specified minimum and maximum number of elements.
@@ -212,7 +283,21 @@ impl LinearTable {
         style: &TableStyle,
         vm_table_location: NonNull<VMTableDefinition>,
     ) -> Result<Self, String> {
-        Self::new_inner(table, style, Some(vm_table_location))
+        Self::new_inner(table, style, Some(vm_table_location), None)
+    }
+
+    /// Create a new linear table instance with VM owned metadata, in a slot
+    /// of `pool`.
+    ///
+    /// # Safety
+    /// - `vm_table_location` must point to a valid location in VM memory.
+    pub(crate) unsafe fn from_pool(
+        table: &TableType,
+        style: &TableStyle,
+        vm_table_location: NonNull<VMTableDefinition>,
+        pool: &Arc<MmapPool>,
+    ) -> Result<Self, String> {
+        Self::new_inner(table, style, Some(vm_table_location), Some(pool))
     }
 
     /// Create a new `LinearTable` with either self-owned or VM owned metadata.
@@ -220,6 +305,7 @@ impl LinearTable {
         table: &TableType,
         style: &TableStyle,
         vm_table_location: Option<NonNull<VMTableDefinition>>,
+        pool: Option<&Arc<MmapPool>>,
     ) -> Result<Self, String> {
         match table.ty {
             ValType::FuncRef | ValType::ExternRef => (),
@@ -240,8 +326,26 @@ impl LinearTable {
         }
         let table_minimum = usize::try_from(table.minimum)
             .map_err(|_| "Table minimum is bigger than usize".to_string())?;
-        let mut vec = vec![RawTableElement::default(); table_minimum];
-        let base = vec.as_mut_ptr();
+        let mut vec = match pool {
+            Some(pool) => {
+                // The pages of a slot are zeroed, which are null references.
+                let mut elements = TableElements::Pooled {
+                    mmap: pool.allocate(pool.slot_size())?,
+                    len: 0,
+                };
+                if !elements.can_grow_to(table_minimum) {
+                    return Err(format!(
+                        "the table needs {} elements but the slots of the pool have room for {}",
+                        table_minimum,
+                        pool.slot_size() / mem::size_of::<RawTableElement>()
+                    ));
+                }
+                elements.resize(table_minimum, RawTableElement::default());
+                elements
+            }
+            None => TableElements::Vec(vec![RawTableElement::default(); table_minimum]),
+        };
+        let base = vec.as_mut_slice().as_mut_ptr();
         match style {
             TableStyle::CallerChecksSignature => Ok(Self {
                 vec: Mutex::new(vec),
@@ -320,6 +424,9 @@ impl Table for LinearTable {
             debug_assert_eq!(delta, 0);
             return Some(size);
         }
+        if !vec.can_grow_to(usize::try_from(new_len).unwrap()) {
+            return None;
+        }
 
         // Update the ref count
         let element = match init_value {
@@ -342,7 +449,7 @@ impl Table for LinearTable {
             let mut td_ptr = self.get_vm_table_definition();
             let td = td_ptr.as_mut();
             td.current_elements = new_len;
-            td.base = vec.as_mut_ptr() as _;
+            td.base = vec.as_mut_slice().as_mut_ptr() as _;
         }
         Some(size)
     }
@@ -352,7 +459,7 @@ impl Table for LinearTable {
     /// Returns `None` if the index is out of bounds.
     fn get(&self, index: u32) -> Option<TableElement> {
         let vec_guard = self.vec.lock().unwrap();
-        let raw_data = vec_guard.borrow().get(index as usize).cloned()?;
+        let raw_data = vec_guard.borrow().as_slice().get(index as usize).cloned()?;
         Some(match self.table.ty {
             ValType::ExternRef => {
                 TableElement::ExternRef(unsafe { raw_data.extern_ref.ref_clone() }.into())
@@ -370,7 +477,7 @@ impl Table for LinearTable {
     fn set(&self, index: u32, reference: TableElement) -> Result<(), Trap> {
         let mut vec_guard = self.vec.lock().unwrap();
         let vec = vec_guard.borrow_mut();
-        match vec.get_mut(index as usize) {
+        match vec.as_mut_slice().get_mut(index as usize) {
             Some(slot) => {
                 match (self.table.ty, reference) {
                     (ValType::ExternRef, TableElement::ExternRef(extern_ref)) => {
@@ -406,4 +513,26 @@ impl Table for LinearTable {
         let _vec_guard = self.vec.lock().unwrap();
         unsafe { self.get_vm_table_definition() }
     }
+
+    /// Shrinks the table back to its minimum size, with null elements.
+    fn reset(&self) -> Result<(), String> {
+        let mut vec_guard = self.vec.lock().unwrap();
+        let vec = vec_guard.borrow_mut();
+        if self.table.ty == ValType::ExternRef {
+            for element in vec.as_mut_slice() {
+                unsafe { element.extern_ref.ref_drop() };
+            }
+        }
+        let minimum = usize::try_from(self.table.minimum).unwrap();
+        vec.reset(minimum);
+
+        // update table definition
+        unsafe {
+            let mut td_ptr = self.get_vm_table_definition();
+            let td = td_ptr.as_mut();
+            td.current_elements = self.table.minimum;
+            td.base = vec.as_mut_slice().as_mut_ptr() as _;
+        }
+        Ok(())
+    }
 }
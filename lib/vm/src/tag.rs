@@ -0,0 +1,24 @@
+use loupe::MemoryUsage;
+use wasmer_types::TagType;
+
+/// A tag instance, from the exception-handling proposal.
+///
+/// Tags have no state: they are compared by identity, so two tags of the
+/// same type are still distinct, and an exception is caught by a `catch`
+/// only if it was thrown with the very same tag.
+#[derive(Debug, MemoryUsage)]
+pub struct Tag {
+    ty: TagType,
+}
+
+impl Tag {
+    /// Create a new tag from a [`TagType`].
+    pub fn new(ty: TagType) -> Self {
+        Self { ty }
+    }
+
+    /// Get the type of the tag.
+    pub fn ty(&self) -> &TagType {
+        &self.ty
+    }
+}
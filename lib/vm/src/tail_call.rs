@@ -0,0 +1,168 @@
+//! Guaranteed tail calls, for `return_call` and `return_call_indirect`.
+//!
+//! Compiled code doesn't jump to the callee of a tail call. Instead, a
+//! function that makes a tail call hands it to the runtime:
+//!
+//! - If the function was itself called by a tail call, the call is left
+//!   pending and the function returns right away.
+//! - Otherwise the function drives the chain: it calls the callee, then
+//!   every call the callees leave pending, until one of them returns
+//!   normally. Its results are the results of the chain.
+//!
+//! Either way, the stack doesn't grow with the length of the chain.
+//!
+//! This is an emulation, not a jump to the callee, and it has a cost: a
+//! function that makes tail calls checks in with a libcall on entry, and
+//! every tail call goes through a libcall, a return, and a call of the
+//! callee through the trampoline of its signature.
+//!
+//! A function knows it was called by a tail call by checking, first thing
+//! on entry, whether its body is the one the driver is about to call.
+//!
+//! Nothing is allocated per call, so that a trap can unwind a chain
+//! without leaking. The driver passes the values of the calls in the
+//! buffer of the function that started the chain, or, if a call of the
+//! chain has more arguments than that buffer holds, in a buffer on its own
+//! stack. A pending call is copied to a buffer of the thread, which is
+//! only used between the return of the function that leaves the call
+//! pending and the driver picking it up.
+
+use crate::vmcontext::{VMContext, VMFunctionBody, VMTrampoline};
+use std::cell::RefCell;
+use std::ptr;
+
+/// The largest number of parameters or results of the callee of a tail
+/// call. The compilers reject tail calls with more.
+pub const MAX_TAIL_CALL_VALUES: usize = 1000;
+
+/// A tail call: a callee, without its arguments.
+pub(crate) struct TailCall {
+    /// The call trampoline of the signature of the callee.
+    pub(crate) trampoline: VMTrampoline,
+    /// The body of the callee.
+    pub(crate) callee: *const VMFunctionBody,
+    /// The `VMContext` or host environment of the callee.
+    pub(crate) callee_vmctx: *mut VMContext,
+}
+
+struct State {
+    /// The body the driver is calling, until it checks in.
+    entering: *const VMFunctionBody,
+    /// The call left by the last function of the chain.
+    pending: Option<TailCall>,
+    /// The arguments of the pending call.
+    pending_values: Vec<u128>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State {
+        entering: ptr::null(),
+        pending: None,
+        pending_values: Vec::new(),
+    });
+}
+
+/// Returns whether the function with `body`, which was just entered, was
+/// called by the driver of a tail-call chain.
+pub(crate) fn entered(body: *const VMFunctionBody) -> bool {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let entered = !body.is_null() && state.entering == body;
+        if entered {
+            state.entering = ptr::null();
+        }
+        entered
+    })
+}
+
+/// Leaves `call`, with the arguments `params`, pending for the driver of
+/// the chain. The caller must return right away.
+pub(crate) fn leave_pending(call: TailCall, params: &[u128]) {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.pending_values.clear();
+        state.pending_values.extend_from_slice(params);
+        state.pending = Some(call);
+    });
+}
+
+/// Calls `call` and then the calls it leaves pending, until one of them
+/// returns normally.
+///
+/// `values` holds the arguments of `call` and has room for `capacity`
+/// values. It receives the `num_results` results of the chain.
+///
+/// # Safety
+///
+/// `call.callee` and `call.callee_vmctx` must be a function and its
+/// environment of the signature of `call.trampoline`, and `capacity` must
+/// be at least its number of parameters and of results.
+pub(crate) unsafe fn drive(
+    mut call: TailCall,
+    values: *mut u128,
+    capacity: usize,
+    num_results: usize,
+) {
+    loop {
+        call = match call_once(call, values) {
+            Some(next) => next,
+            None => return,
+        };
+        if !take_pending_values(values, capacity) {
+            return drive_on_stack(call, values, num_results);
+        }
+    }
+}
+
+/// Drives the rest of a chain whose calls don't all fit in the buffer of
+/// the function that started it, with a buffer on the stack.
+#[inline(never)]
+unsafe fn drive_on_stack(mut call: TailCall, results: *mut u128, num_results: usize) {
+    let mut buffer = [0u128; MAX_TAIL_CALL_VALUES];
+    let values = buffer.as_mut_ptr();
+    let fits = take_pending_values(values, MAX_TAIL_CALL_VALUES);
+    debug_assert!(fits);
+    loop {
+        call = match call_once(call, values) {
+            Some(next) => next,
+            None => break,
+        };
+        let fits = take_pending_values(values, MAX_TAIL_CALL_VALUES);
+        debug_assert!(fits);
+    }
+    ptr::copy_nonoverlapping(values, results, num_results);
+}
+
+/// Calls `call` with `values`, and returns the call it left pending.
+unsafe fn call_once(call: TailCall, values: *mut u128) -> Option<TailCall> {
+    STATE.with(|state| state.borrow_mut().entering = call.callee);
+    (call.trampoline)(call.callee_vmctx, call.callee, values);
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.entering = ptr::null();
+        state.pending.take()
+    })
+}
+
+/// Copies the arguments of the pending call to `values`, if they fit in
+/// its `capacity`.
+unsafe fn take_pending_values(values: *mut u128, capacity: usize) -> bool {
+    STATE.with(|state| {
+        let state = state.borrow();
+        let params = &state.pending_values;
+        if params.len() > capacity {
+            return false;
+        }
+        ptr::copy_nonoverlapping(params.as_ptr(), values, params.len());
+        true
+    })
+}
+
+/// Forgets the chain that was being driven, after a trap unwound it.
+pub(crate) fn reset() {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.entering = ptr::null();
+        state.pending = None;
+    });
+}
@@ -8,8 +8,10 @@ mod traphandlers;
 
 pub use trap::Trap;
 pub use traphandlers::{
-    catch_traps, on_host_stack, raise_lib_trap, raise_user_trap, wasmer_call_trampoline,
-    TrapHandler, TrapHandlerFn,
+    block_on_host_future, catch_traps, catch_traps_async, on_host_stack, raise_lib_trap,
+    raise_user_trap, wasmer_call_trampoline, wasmer_call_trampoline_async, AsyncWasmCall,
+    TrapHandler, TrapHandlerFn, DEFAULT_STACK_SIZE,
 };
 pub use traphandlers::{init_traps, resume_panic};
+pub(crate) use traphandlers::stack_limit;
 pub use wasmer_types::TrapCode;
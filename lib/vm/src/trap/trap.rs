@@ -1,3 +1,4 @@
+use crate::exception::VMException;
 use backtrace::Backtrace;
 use std::error::Error;
 use wasmer_types::TrapCode;
@@ -37,6 +38,14 @@ pub enum Trap {
         /// Native stack backtrace at the time the OOM occurred
         backtrace: Backtrace,
     },
+
+    /// An exception thrown by Wasm code that no handler caught.
+    Exception {
+        /// The exception.
+        exception: VMException,
+        /// Native stack backtrace at the time the exception left Wasm code
+        backtrace: Backtrace,
+    },
 }
 
 impl Trap {
@@ -4,24 +4,29 @@
 //! WebAssembly trap handling, which is built on top of the lower-level
 //! signalhandling mechanisms.
 
+use crate::exception::{self, VMException};
+use crate::tail_call;
 use crate::vmcontext::{VMFunctionBody, VMFunctionEnvironment, VMTrampoline};
 use crate::Trap;
 use backtrace::Backtrace;
 use core::ptr::{read, read_unaligned};
-use corosensei::stack::DefaultStack;
+use corosensei::stack::{DefaultStack, Stack};
 use corosensei::trap::{CoroutineTrapHandler, TrapHandlerRegs};
 use corosensei::{CoroutineResult, ScopedCoroutine, Yielder};
 use scopeguard::defer;
 use std::any::Any;
 use std::cell::Cell;
 use std::error::Error;
+use std::future::Future;
 use std::io;
 use std::mem;
 #[cfg(unix)]
 use std::mem::MaybeUninit;
+use std::pin::Pin;
 use std::ptr::{self, NonNull};
 use std::sync::atomic::{compiler_fence, AtomicPtr, Ordering};
 use std::sync::{Mutex, Once};
+use std::task::{Context, Poll};
 use wasmer_types::TrapCode;
 
 // TrapInformation can be stored in the "Undefined Instruction" itself.
@@ -82,6 +87,8 @@ unsafe fn process_illegal_op(addr: usize) -> Option<TrapCode> {
             9 => Some(TrapCode::BadConversionToInteger),
             10 => Some(TrapCode::UnreachableCodeReached),
             11 => Some(TrapCode::UnalignedAtomic),
+            12 => Some(TrapCode::Interrupt),
+            13 => Some(TrapCode::AtomicWaitOnUnsharedMemory),
             _ => None,
         },
     }
@@ -98,8 +105,21 @@ pub unsafe trait TrapHandler {
     ///
     /// Returns `true` if `call` returns true, otherwise returns `false`.
     fn custom_trap_handler(&self, call: &dyn Fn(&TrapHandlerFn) -> bool) -> bool;
+
+    /// Returns the size in bytes of the stack that the calls made with
+    /// this trap handler run on.
+    fn stack_size(&self) -> usize {
+        DEFAULT_STACK_SIZE
+    }
 }
 
+/// The default size in bytes of the stack that WebAssembly runs on.
+///
+/// Every call from the host into WebAssembly switches to a separately
+/// allocated stack of this size, followed by a guard page, so that deep
+/// recursion behaves the same whichever host thread makes the call.
+pub const DEFAULT_STACK_SIZE: usize = 1024 * 1024;
+
 cfg_if::cfg_if! {
     if #[cfg(unix)] {
         static mut PREV_SIGSEGV: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
@@ -639,17 +659,270 @@ where
     on_wasm_stack(trap_handler, closure).map_err(UnwindReason::to_trap)
 }
 
-// We need two separate thread-local variables here:
+/// Call the wasm function pointed to by `callee` on a separate stack that
+/// can be suspended, returning a future that completes when the call
+/// returns.
+///
+/// This is the asynchronous counterpart of [`wasmer_call_trampoline`]: see
+/// its documentation for the meaning of the arguments.
+///
+/// # Safety
+///
+/// Wildly unsafe because it calls raw function pointers and reads/writes raw
+/// function pointers. `values_vec` must stay valid until the returned future
+/// completes or is dropped.
+pub unsafe fn wasmer_call_trampoline_async<'a>(
+    trap_handler: &'a (impl TrapHandler + 'static),
+    vmctx: VMFunctionEnvironment,
+    trampoline: VMTrampoline,
+    callee: *const VMFunctionBody,
+    values_vec: *mut u8,
+) -> AsyncWasmCall<'a, ()> {
+    catch_traps_async(trap_handler, move || {
+        mem::transmute::<_, extern "C" fn(VMFunctionEnvironment, *const VMFunctionBody, *mut u8)>(
+            trampoline,
+        )(vmctx, callee, values_vec);
+    })
+}
+
+/// Runs `closure` on a separate stack that can be suspended, catching any
+/// wasm traps that happen within its execution.
+///
+/// Nothing runs until the returned future is polled. While it runs,
+/// `closure` can wait for a host future with [`block_on_host_future`],
+/// which suspends the stack and makes the returned future pending until
+/// the host future is ready.
+///
+/// # Safety
+///
+/// Highly unsafe since `closure` won't have any dtors run.
+pub unsafe fn catch_traps_async<'a, F, R>(
+    trap_handler: &'a (dyn TrapHandler + 'static),
+    closure: F,
+) -> AsyncWasmCall<'a, R>
+where
+    F: FnOnce() -> R + 'a,
+{
+    let stack_size = trap_handler.stack_size();
+    let stack = match allocate_stack(stack_size) {
+        Ok(stack) => stack,
+        Err(trap) => {
+            return AsyncWasmCall {
+                trap_handler,
+                stack_size,
+                stack_limit: 0,
+                coro: None,
+                error: Some(trap),
+            }
+        }
+    };
+    let stack_limit = stack.base().get() - stack_size;
+    let coro = ScopedCoroutine::with_stack(stack, move |yielder, ()| {
+        // Save the yielder to TLS so that it can be used later.
+        YIELDER.with(|cell| cell.set(Some(yielder.into())));
+
+        uncaught_exception(closure())
+    });
+
+    AsyncWasmCall {
+        trap_handler,
+        stack_size,
+        stack_limit,
+        coro: Some(coro),
+        error: None,
+    }
+}
+
+/// A call running on its own stack, created by [`catch_traps_async`].
+///
+/// Polling the future resumes the call until it completes or waits for a
+/// host future that isn't ready.
+///
+/// The future is not `Send`: a suspended call has host frames on its stack,
+/// which may hold thread-bound values and must be resumed on the thread
+/// that started them.
+pub struct AsyncWasmCall<'a, R> {
+    trap_handler: &'a (dyn TrapHandler + 'static),
+    stack_size: usize,
+    /// The lowest address of the stack of the call.
+    stack_limit: usize,
+    #[allow(clippy::type_complexity)]
+    coro: Option<ScopedCoroutine<'a, (), UnwindReason, Result<R, UnwindReason>, DefaultStack>>,
+    /// The trap of a call that couldn't start, because its stack couldn't
+    /// be allocated.
+    error: Option<Trap>,
+}
+
+impl<'a, R> AsyncWasmCall<'a, R> {
+    /// Resumes the coroutine with `cx` as the task context seen by
+    /// [`block_on_host_future`]. A null `cx` makes it fail, which is how a
+    /// suspended call gets cancelled.
+    fn resume(&mut self, cx: *mut Context<'static>) -> Poll<Result<R, UnwindReason>> {
+        let (trap_handler, stack_limit) = (self.trap_handler, self.stack_limit);
+        let coro = self
+            .coro
+            .as_mut()
+            .expect("`AsyncWasmCall` polled after completion");
+
+        // Restore the thread-local state of the caller once the coroutine
+        // is suspended or done: the call may be polled from a host function
+        // of another call.
+        let prev_yielder = YIELDER.with(|cell| cell.get());
+        let prev_cx = ASYNC_CX.with(|cell| cell.replace(cx));
+        defer! {
+            YIELDER.with(|cell| cell.set(prev_yielder));
+            ASYNC_CX.with(|cell| cell.set(prev_cx));
+        }
+
+        let result = TrapHandlerContext::install(trap_handler, coro.trap_handler(), || {
+            match with_stack_limit(stack_limit, || coro.resume(())) {
+                CoroutineResult::Yield(UnwindReason::Suspend) => Poll::Pending,
+                CoroutineResult::Yield(trap) => {
+                    // This came from unwind_with which requires that there be
+                    // only Wasm code on the stack.
+                    unsafe {
+                        coro.force_reset();
+                    }
+                    Poll::Ready(Err(trap))
+                }
+                CoroutineResult::Return(result) => Poll::Ready(result),
+            }
+        });
+
+        if result.is_ready() {
+            let stack = self.coro.take().unwrap().into_stack();
+            release_stack(self.stack_size, stack);
+        }
+        result
+    }
+}
+
+impl<'a, R> Future for AsyncWasmCall<'a, R> {
+    type Output = Result<R, Trap>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Ensure that per-thread initialization is done. The future can't
+        // move to another thread, but it may be polled for the first time
+        // on a thread that never ran Wasm code.
+        if let Err(trap) = lazy_per_thread_init() {
+            return Poll::Ready(Err(trap));
+        }
+
+        let this = self.get_mut();
+        if let Some(trap) = this.error.take() {
+            return Poll::Ready(Err(trap));
+        }
+
+        let cx = cx as *mut Context<'_> as *mut Context<'static>;
+        this.resume(cx)
+            .map(|result| result.map_err(UnwindReason::to_trap))
+    }
+}
+
+impl<'a, R> Drop for AsyncWasmCall<'a, R> {
+    fn drop(&mut self) {
+        // A call suspended in `block_on_host_future` is resumed without a
+        // task context, so that it unwinds with a trap. This drops the host
+        // future and everything else the host function has on the stack.
+        if let Some(coro) = &self.coro {
+            if coro.started() && !coro.done() && lazy_per_thread_init().is_ok() {
+                let _ = self.resume(ptr::null_mut());
+            }
+        }
+    }
+}
+
+/// Drives `future` to completion from a host function called by a call
+/// running in [`catch_traps_async`].
+///
+/// The future is polled on the host stack. Whenever it is pending, the
+/// Wasm stack is suspended, and it is polled again once the call is
+/// resumed.
+///
+/// Returns `None` if the current call can't be suspended, e.g. when it
+/// was started with [`catch_traps`], or when the suspended call is being
+/// cancelled.
+pub fn block_on_host_future<F: Future>(mut future: Pin<&mut F>) -> Option<F::Output> {
+    loop {
+        let yielder = YIELDER.with(|cell| cell.get())?;
+        let cx = ASYNC_CX.with(|cell| cell.get());
+        if cx.is_null() {
+            return None;
+        }
+
+        if let Poll::Ready(output) = on_host_stack(|| future.as_mut().poll(unsafe { &mut *cx })) {
+            return Some(output);
+        }
+
+        // Suspend the coroutine until the call is polled again. The yielder
+        // is taken out of TLS in the meantime since other calls may run on
+        // this thread.
+        YIELDER.with(|cell| cell.set(None));
+        unsafe {
+            yielder.as_ref().suspend(UnwindReason::Suspend);
+        }
+        YIELDER.with(|cell| cell.set(Some(yielder)));
+    }
+}
+
+// We need three separate thread-local variables here:
 // - YIELDER is set within the new stack and is used to unwind back to the root
 //   of the stack from inside it.
 // - TRAP_HANDLER is set from outside the new stack and is solely used from
 //   signal handlers. It must be atomic since it is used by signal handlers.
+// - ASYNC_CX is set from outside the new stack while an `AsyncWasmCall` is
+//   polled, and holds the task context of the poll. It is null for
+//   synchronous calls.
 //
 // We also do per-thread signal stack initialization on the first time
 // TRAP_HANDLER is accessed.
 thread_local! {
     static YIELDER: Cell<Option<NonNull<Yielder<(), UnwindReason>>>> = Cell::new(None);
     static TRAP_HANDLER: AtomicPtr<TrapHandlerContext> = AtomicPtr::new(ptr::null_mut());
+    static ASYNC_CX: Cell<*mut Context<'static>> = Cell::new(ptr::null_mut());
+    /// The lowest address of the stack of the call running on this thread,
+    /// or 0 outside of calls.
+    static STACK_LIMIT: Cell<usize> = Cell::new(0);
+}
+
+// Allocating a new stack is pretty expensive since it involves several
+// system calls. We therefore keep a cache of pre-allocated stacks which
+// allows them to be reused multiple times.
+// Only the stacks of the default size are kept, and the cache is bounded
+// so that a burst of concurrent calls doesn't pin their stacks forever.
+// FIXME(Amanieu): We should refactor this to avoid the lock.
+lazy_static::lazy_static! {
+    static ref STACK_POOL: Mutex<Vec<DefaultStack>> = Mutex::new(vec![]);
+}
+
+/// The maximum number of stacks kept in `STACK_POOL`.
+const STACK_POOL_MAX_STACKS: usize = 64;
+
+/// The maximum number of bytes of the stacks kept in `STACK_POOL`.
+const STACK_POOL_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Takes a stack of `size` bytes from the pool, or allocates a new one.
+fn allocate_stack(size: usize) -> Result<DefaultStack, Trap> {
+    if size == DEFAULT_STACK_SIZE {
+        if let Some(stack) = STACK_POOL.lock().unwrap().pop() {
+            return Ok(stack);
+        }
+    }
+    DefaultStack::new(size).map_err(|_| Trap::oom())
+}
+
+/// Gives a stack of `size` bytes back to the pool, or frees it if the
+/// pool doesn't keep it.
+fn release_stack(size: usize, stack: DefaultStack) {
+    if size != DEFAULT_STACK_SIZE {
+        return;
+    }
+    let mut pool = STACK_POOL.lock().unwrap();
+    if pool.len() < STACK_POOL_MAX_STACKS
+        && (pool.len() + 1) * DEFAULT_STACK_SIZE <= STACK_POOL_MAX_BYTES
+    {
+        pool.push(stack);
+    }
 }
 
 /// Read-only information that is used by signal handlers to handle and recover
@@ -821,10 +1094,18 @@ enum UnwindReason {
         pc: usize,
         signal_trap: Option<TrapCode>,
     },
+    /// The call is waiting for a host future, see `block_on_host_future`
+    Suspend,
+    /// An exception no handler caught
+    Exception(VMException),
 }
 
 impl UnwindReason {
     fn to_trap(self) -> Trap {
+        // The unwinding skipped the drivers of any tail calls being made.
+        tail_call::reset();
+        // Nor will the frames that would have handled a pending exception.
+        exception::reset();
         match self {
             UnwindReason::UserTrap(data) => Trap::User(data),
             UnwindReason::LibTrap(trap) => trap,
@@ -834,10 +1115,24 @@ impl UnwindReason {
                 signal_trap,
             } => Trap::wasm(pc, backtrace, signal_trap),
             UnwindReason::Panic(panic) => std::panic::resume_unwind(panic),
+            UnwindReason::Exception(exception) => Trap::Exception {
+                exception,
+                backtrace: Backtrace::new_unresolved(),
+            },
+            UnwindReason::Suspend => unreachable!("suspended calls are not traps"),
         }
     }
 }
 
+/// Returns the `result` of a call, or the exception its outermost frame
+/// returned with, if it threw one that no handler caught.
+fn uncaught_exception<T>(result: T) -> Result<T, UnwindReason> {
+    match exception::take() {
+        Some(exception) => Err(UnwindReason::Exception(exception)),
+        None => Ok(result),
+    }
+}
+
 unsafe fn unwind_with(reason: UnwindReason) -> ! {
     let yielder = YIELDER
         .with(|cell| cell.replace(None))
@@ -856,22 +1151,17 @@ fn on_wasm_stack<F: FnOnce() -> T, T>(
     trap_handler: &(dyn TrapHandler + 'static),
     f: F,
 ) -> Result<T, UnwindReason> {
-    // Allocating a new stack is pretty expensive since it involves several
-    // system calls. We therefore keep a cache of pre-allocated stacks which
-    // allows them to be reused multiple times.
-    // FIXME(Amanieu): We should refactor this to avoid the lock.
-    lazy_static::lazy_static! {
-        static ref STACK_POOL: Mutex<Vec<DefaultStack>> = Mutex::new(vec![]);
-    }
-    let stack = STACK_POOL.lock().unwrap().pop().unwrap_or_default();
-    let mut stack = scopeguard::guard(stack, |stack| STACK_POOL.lock().unwrap().push(stack));
+    let stack_size = trap_handler.stack_size();
+    let stack = allocate_stack(stack_size).map_err(UnwindReason::LibTrap)?;
+    let stack_limit = stack.base().get() - stack_size;
+    let mut stack = scopeguard::guard(stack, |stack| release_stack(stack_size, stack));
 
     // Create a coroutine with a new stack to run the function on.
     let mut coro = ScopedCoroutine::with_stack(&mut *stack, move |yielder, ()| {
         // Save the yielder to TLS so that it can be used later.
         YIELDER.with(|cell| cell.set(Some(yielder.into())));
 
-        Ok(f())
+        uncaught_exception(f())
     });
 
     // Ensure that YIELDER is reset on exit even if the coroutine panics,
@@ -879,10 +1169,17 @@ fn on_wasm_stack<F: FnOnce() -> T, T>(
         YIELDER.with(|cell| cell.set(None));
     }
 
+    // A synchronous call can't be suspended, even if it is made from a host
+    // function of an asynchronous call.
+    let prev_cx = ASYNC_CX.with(|cell| cell.replace(ptr::null_mut()));
+    defer! {
+        ASYNC_CX.with(|cell| cell.set(prev_cx));
+    }
+
     // Set up metadata for the trap handler for the duration of the coroutine
     // execution. This is restored to its previous value afterwards.
     TrapHandlerContext::install(trap_handler, coro.trap_handler(), || {
-        match coro.resume(()) {
+        match with_stack_limit(stack_limit, || coro.resume(())) {
             CoroutineResult::Yield(trap) => {
                 // This came from unwind_with which requires that there be only
                 // Wasm code on the stack.
@@ -896,6 +1193,25 @@ fn on_wasm_stack<F: FnOnce() -> T, T>(
     })
 }
 
+/// Runs `f`, which resumes a call on its stack, with the stack limit of the
+/// thread set to `stack_limit`, the lowest address of that stack.
+///
+/// The previous limit is restored afterwards, since the call may have been
+/// made or resumed from a host function of another call.
+fn with_stack_limit<R>(stack_limit: usize, f: impl FnOnce() -> R) -> R {
+    let prev = STACK_LIMIT.with(|cell| cell.replace(stack_limit));
+    defer! {
+        STACK_LIMIT.with(|cell| cell.set(prev));
+    }
+    f()
+}
+
+/// Returns the lowest address of the stack of the call running on this
+/// thread, for the compiled code that checks the stack explicitly.
+pub(crate) fn stack_limit() -> usize {
+    STACK_LIMIT.with(|cell| cell.get())
+}
+
 /// When executing on the Wasm stack, temporarily switch back to the host stack
 /// to perform an operation that should not be constrainted by the Wasm stack
 /// limits.
@@ -18,6 +18,7 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::mem;
 use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::u32;
 pub use wasmer_artifact::VMFunctionBody;
@@ -364,26 +365,48 @@ impl VMMemoryDefinition {
     /// # Safety
     /// The memory is not copied atomically and is not synchronized: it's the
     /// caller's responsibility to synchronize.
-    pub(crate) unsafe fn memory_copy(&self, dst: u32, src: u32, len: u32) -> Result<(), Trap> {
+    pub(crate) unsafe fn memory_copy(&self, dst: u64, src: u64, len: u64) -> Result<(), Trap> {
+        self.memory_copy_from(self, dst, src, len)
+    }
+
+    /// Do an unsynchronized, non-atomic `memory.copy` from `src_memory` into
+    /// this memory. The two memories may be the same.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Trap` error when the source or destination ranges are out of
+    /// bounds.
+    ///
+    /// # Safety
+    /// The memory is not copied atomically and is not synchronized: it's the
+    /// caller's responsibility to synchronize.
+    pub(crate) unsafe fn memory_copy_from(
+        &self,
+        src_memory: &Self,
+        dst: u64,
+        src: u64,
+        len: u64,
+    ) -> Result<(), Trap> {
         // https://webassembly.github.io/reference-types/core/exec/instructions.html#exec-memory-copy
         if src
             .checked_add(len)
-            .map_or(true, |n| usize::try_from(n).unwrap() > self.current_length)
+            .map_or(true, |n| n > src_memory.current_length as u64)
             || dst
                 .checked_add(len)
-                .map_or(true, |m| usize::try_from(m).unwrap() > self.current_length)
+                .map_or(true, |m| m > self.current_length as u64)
         {
             return Err(Trap::lib(TrapCode::HeapAccessOutOfBounds));
         }
 
         let dst = usize::try_from(dst).unwrap();
         let src = usize::try_from(src).unwrap();
+        let len = usize::try_from(len).unwrap();
 
         // Bounds and casts are checked above, by this point we know that
         // everything is safe.
         let dst = self.base.add(dst);
-        let src = self.base.add(src);
-        ptr::copy(src, dst, len as usize);
+        let src = src_memory.base.add(src);
+        ptr::copy(src, dst, len);
 
         Ok(())
     }
@@ -398,21 +421,22 @@ impl VMMemoryDefinition {
     /// # Safety
     /// The memory is not filled atomically and is not synchronized: it's the
     /// caller's responsibility to synchronize.
-    pub(crate) unsafe fn memory_fill(&self, dst: u32, val: u32, len: u32) -> Result<(), Trap> {
+    pub(crate) unsafe fn memory_fill(&self, dst: u64, val: u32, len: u64) -> Result<(), Trap> {
         if dst
             .checked_add(len)
-            .map_or(true, |m| usize::try_from(m).unwrap() > self.current_length)
+            .map_or(true, |m| m > self.current_length as u64)
         {
             return Err(Trap::lib(TrapCode::HeapAccessOutOfBounds));
         }
 
         let dst = isize::try_from(dst).unwrap();
         let val = val as u8;
+        let len = usize::try_from(len).unwrap();
 
         // Bounds and casts are checked above, by this point we know that
         // everything is safe.
         let dst = self.base.offset(dst);
-        ptr::write_bytes(dst, val, len as usize);
+        ptr::write_bytes(dst, val, len);
 
         Ok(())
     }
@@ -922,8 +946,54 @@ impl VMBuiltinFunctionsArray {
             wasmer_vm_memory32_init as usize;
         ptrs[VMBuiltinFunctionIndex::get_data_drop_index().index() as usize] =
             wasmer_vm_data_drop as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory_atomic_wait32_index().index() as usize] =
+            wasmer_vm_memory32_atomic_wait32 as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory_atomic_wait64_index().index() as usize] =
+            wasmer_vm_memory32_atomic_wait64 as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory_atomic_notify_index().index() as usize] =
+            wasmer_vm_memory32_atomic_notify as usize;
+
+        ptrs[VMBuiltinFunctionIndex::get_memory64_grow_index().index() as usize] =
+            wasmer_vm_memory64_grow as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_grow_index().index() as usize] =
+            wasmer_vm_imported_memory64_grow as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_size_index().index() as usize] =
+            wasmer_vm_memory64_size as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_size_index().index() as usize] =
+            wasmer_vm_imported_memory64_size as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_copy_index().index() as usize] =
+            wasmer_vm_memory64_copy as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_copy_index().index() as usize] =
+            wasmer_vm_imported_memory64_copy as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_fill_index().index() as usize] =
+            wasmer_vm_memory64_fill as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_fill_index().index() as usize] =
+            wasmer_vm_imported_memory64_fill as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_init_index().index() as usize] =
+            wasmer_vm_memory64_init as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory_copy_between_index().index() as usize] =
+            wasmer_vm_memory_copy_between as usize;
+        ptrs[VMBuiltinFunctionIndex::get_tail_call_index().index() as usize] =
+            wasmer_vm_tail_call as usize;
+        ptrs[VMBuiltinFunctionIndex::get_tail_call_entered_index().index() as usize] =
+            wasmer_vm_tail_call_entered as usize;
+        ptrs[VMBuiltinFunctionIndex::get_throw_index().index() as usize] = wasmer_vm_throw as usize;
+        ptrs[VMBuiltinFunctionIndex::get_exception_thrown_index().index() as usize] =
+            wasmer_vm_exception_thrown as usize;
+        ptrs[VMBuiltinFunctionIndex::get_exception_catch_index().index() as usize] =
+            wasmer_vm_exception_catch as usize;
+        ptrs[VMBuiltinFunctionIndex::get_exception_matches_index().index() as usize] =
+            wasmer_vm_exception_matches as usize;
+        ptrs[VMBuiltinFunctionIndex::get_exception_payload_index().index() as usize] =
+            wasmer_vm_exception_payload as usize;
+        ptrs[VMBuiltinFunctionIndex::get_rethrow_index().index() as usize] =
+            wasmer_vm_rethrow as usize;
+        ptrs[VMBuiltinFunctionIndex::get_exception_drop_index().index() as usize] =
+            wasmer_vm_exception_drop as usize;
         ptrs[VMBuiltinFunctionIndex::get_raise_trap_index().index() as usize] =
             wasmer_vm_raise_trap as usize;
+        ptrs[VMBuiltinFunctionIndex::get_stack_limit_index().index() as usize] =
+            wasmer_vm_stack_limit as usize;
         ptrs[VMBuiltinFunctionIndex::get_table_size_index().index() as usize] =
             wasmer_vm_table_size as usize;
         ptrs[VMBuiltinFunctionIndex::get_imported_table_size_index().index() as usize] =
@@ -955,6 +1025,128 @@ impl VMBuiltinFunctionsArray {
     }
 }
 
+/// The interruption state shared by the instances of a store, which
+/// compiled wasm code checks at function entries and loop headers.
+///
+/// Wasm code traps with [`TrapCode::Interrupt`] as soon as `epoch`
+/// reaches `epoch_deadline`.
+#[derive(Debug, MemoryUsage)]
+#[repr(C)]
+pub struct VMInterrupts {
+    /// The current epoch, incremented by the host.
+    #[loupe(skip)]
+    pub epoch: AtomicU64,
+    /// The epoch at which running wasm code traps.
+    #[loupe(skip)]
+    pub epoch_deadline: AtomicU64,
+    /// The number of exceptions being thrown by the code of the store,
+    /// across all threads. Compiled code only looks for the exception
+    /// pending on its thread after a call when this isn't zero.
+    #[loupe(skip)]
+    pub thrown_exceptions: AtomicUsize,
+    // If more elements are added here, remember to add offset_of tests below!
+}
+
+impl VMInterrupts {
+    /// Creates interrupts in the initial epoch, without a deadline.
+    pub const fn new() -> Self {
+        Self {
+            epoch: AtomicU64::new(0),
+            epoch_deadline: AtomicU64::new(u64::MAX),
+            thrown_exceptions: AtomicUsize::new(0),
+        }
+    }
+
+    /// Makes running and future wasm code trap, until a new deadline is
+    /// set.
+    pub fn interrupt(&self) {
+        self.epoch_deadline.store(0, Ordering::Relaxed);
+    }
+
+    /// Increments the current epoch.
+    pub fn increment_epoch(&self) {
+        self.epoch.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Sets the deadline `ticks` epochs after the current one.
+    pub fn set_epoch_deadline(&self, ticks: u64) {
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        self.epoch_deadline
+            .store(epoch.saturating_add(ticks), Ordering::Relaxed);
+    }
+
+    /// Removes the deadline, including the one set by `interrupt`.
+    pub fn clear_epoch_deadline(&self) {
+        self.epoch_deadline.store(u64::MAX, Ordering::Relaxed);
+    }
+
+    /// Whether the current epoch has reached the deadline, which is the
+    /// check compiled wasm code performs.
+    pub fn deadline_reached(&self) -> bool {
+        self.epoch.load(Ordering::Relaxed) >= self.epoch_deadline.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for VMInterrupts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_vminterrupts {
+    use super::VMInterrupts;
+    use crate::VMOffsets;
+    use memoffset::offset_of;
+    use std::mem::size_of;
+    use std::sync::atomic::Ordering;
+    use wasmer_types::ModuleInfo;
+
+    #[test]
+    fn check_vminterrupts_offsets() {
+        let module = ModuleInfo::new();
+        let offsets = VMOffsets::new(size_of::<*mut u8>() as u8, &module);
+        assert_eq!(
+            size_of::<VMInterrupts>(),
+            usize::from(offsets.size_of_vminterrupts())
+        );
+        assert_eq!(
+            offset_of!(VMInterrupts, epoch),
+            usize::from(offsets.vminterrupts_epoch())
+        );
+        assert_eq!(
+            offset_of!(VMInterrupts, epoch_deadline),
+            usize::from(offsets.vminterrupts_epoch_deadline())
+        );
+        assert_eq!(
+            offset_of!(VMInterrupts, thrown_exceptions),
+            usize::from(offsets.vminterrupts_thrown_exceptions())
+        );
+    }
+
+    #[test]
+    fn epoch_deadlines() {
+        let interrupts = VMInterrupts::new();
+        interrupts.increment_epoch();
+        interrupts.set_epoch_deadline(2);
+        assert_eq!(interrupts.epoch_deadline.load(Ordering::Relaxed), 3);
+        assert!(!interrupts.deadline_reached());
+        interrupts.increment_epoch();
+        interrupts.increment_epoch();
+        assert!(interrupts.deadline_reached());
+
+        interrupts.interrupt();
+        assert_eq!(interrupts.epoch_deadline.load(Ordering::Relaxed), 0);
+
+        interrupts.set_epoch_deadline(u64::MAX);
+        assert_eq!(interrupts.epoch_deadline.load(Ordering::Relaxed), u64::MAX);
+
+        interrupts.interrupt();
+        interrupts.clear_epoch_deadline();
+        assert_eq!(interrupts.epoch_deadline.load(Ordering::Relaxed), u64::MAX);
+    }
+}
+
 /// The VM "context", which is pointed to by the `vmctx` arg in the compiler.
 /// This has information about globals, memories, tables, and other runtime
 /// state associated with the current instance.
@@ -0,0 +1,221 @@
+//! The wait queue of `memory.atomic.wait` and `memory.atomic.notify`.
+//!
+//! Waiters are parked in a queue keyed by the host address they wait on.
+//! A shared memory never moves, so the address of a location is the same in
+//! every instance that imports the memory, whichever thread it runs on.
+//!
+//! The queue is split in shards selected by address, so that threads
+//! waiting on unrelated locations don't contend on the same lock.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// The result of a [`WaitQueue::wait`], as returned by
+/// `memory.atomic.wait`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub(crate) enum WaitResult {
+    /// The waiter was woken by a notification.
+    Ok = 0,
+    /// The value in memory wasn't the expected one.
+    NotEqual = 1,
+    /// The timeout expired.
+    TimedOut = 2,
+}
+
+#[derive(Default)]
+struct Waiter {
+    /// Only modified with the queue locked.
+    notified: AtomicBool,
+    condvar: Condvar,
+}
+
+type Waiters = HashMap<usize, VecDeque<Arc<Waiter>>>;
+
+/// The number of shards of a [`WaitQueue`].
+const SHARDS: usize = 64;
+
+/// How often a parked thread checks whether it was interrupted.
+const INTERRUPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Parked waiters, keyed by address.
+pub(crate) struct WaitQueue {
+    shards: Box<[Mutex<Waiters>]>,
+}
+
+impl Default for WaitQueue {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::default()).collect(),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// The wait queue of all the shared memories of the process.
+    pub(crate) static ref WAIT_QUEUE: WaitQueue = WaitQueue::default();
+}
+
+impl WaitQueue {
+    /// The shard holding the waiters of `address`.
+    fn shard(&self, address: usize) -> &Mutex<Waiters> {
+        // Atomic locations are at least 4-byte aligned.
+        &self.shards[(address >> 2) % SHARDS]
+    }
+
+    /// Parks the current thread on `address` until it's notified, or until
+    /// `timeout` expires.
+    ///
+    /// `validate` is called with the queue locked, so that a notification
+    /// can't be missed between the check and the wait: the thread doesn't
+    /// wait if it returns `false`.
+    ///
+    /// `interrupted` is polled while the thread is parked, and the wait is
+    /// abandoned with `None` as soon as it returns `true`.
+    pub(crate) fn wait(
+        &self,
+        address: usize,
+        validate: impl FnOnce() -> bool,
+        timeout: Option<Duration>,
+        interrupted: impl Fn() -> bool,
+    ) -> Option<WaitResult> {
+        let mut waiters = self.shard(address).lock().unwrap();
+        if !validate() {
+            return Some(WaitResult::NotEqual);
+        }
+
+        let waiter = Arc::new(Waiter::default());
+        waiters
+            .entry(address)
+            .or_default()
+            .push_back(waiter.clone());
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            if waiter.notified.load(Ordering::Relaxed) {
+                return Some(WaitResult::Ok);
+            }
+            if interrupted() {
+                Self::remove(&mut waiters, address, &waiter);
+                return None;
+            }
+            let mut slice = INTERRUPT_POLL_INTERVAL;
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    Self::remove(&mut waiters, address, &waiter);
+                    return Some(WaitResult::TimedOut);
+                }
+                slice = slice.min(deadline - now);
+            }
+            waiters = waiter.condvar.wait_timeout(waiters, slice).unwrap().0;
+        }
+    }
+
+    /// Wakes up to `count` threads parked on `address`, in the order they
+    /// started waiting, and returns how many were woken.
+    pub(crate) fn notify(&self, address: usize, count: u32) -> u32 {
+        let mut waiters = self.shard(address).lock().unwrap();
+        let queue = match waiters.get_mut(&address) {
+            Some(queue) => queue,
+            None => return 0,
+        };
+
+        let mut woken = 0;
+        while woken < count {
+            let waiter = match queue.pop_front() {
+                Some(waiter) => waiter,
+                None => break,
+            };
+            waiter.notified.store(true, Ordering::Relaxed);
+            waiter.condvar.notify_one();
+            woken += 1;
+        }
+        if queue.is_empty() {
+            waiters.remove(&address);
+        }
+        woken
+    }
+
+    fn remove(waiters: &mut Waiters, address: usize, waiter: &Arc<Waiter>) {
+        if let Some(queue) = waiters.get_mut(&address) {
+            queue.retain(|w| !Arc::ptr_eq(w, waiter));
+            if queue.is_empty() {
+                waiters.remove(&address);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    use std::thread;
+
+    #[test]
+    fn wait_not_equal_and_timeout() {
+        let queue = WaitQueue::default();
+        assert_eq!(
+            queue.wait(8, || false, None, || false),
+            Some(WaitResult::NotEqual)
+        );
+        assert_eq!(
+            queue.wait(8, || true, Some(Duration::from_millis(10)), || false),
+            Some(WaitResult::TimedOut)
+        );
+        assert_eq!(queue.notify(8, 1), 0);
+    }
+
+    #[test]
+    fn interrupted_wait() {
+        let queue = Arc::new(WaitQueue::default());
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let queue = queue.clone();
+            let interrupted = interrupted.clone();
+            thread::spawn(move || {
+                queue.wait(8, || true, None, || interrupted.load(Ordering::SeqCst))
+            })
+        };
+
+        interrupted.store(true, Ordering::SeqCst);
+        assert_eq!(waiter.join().unwrap(), None);
+        assert_eq!(queue.notify(8, 1), 0);
+    }
+
+    #[test]
+    fn notify_wakes_waiters() {
+        let queue = Arc::new(WaitQueue::default());
+        let parked = Arc::new(AtomicU32::new(0));
+        let threads = (0..3)
+            .map(|_| {
+                let queue = queue.clone();
+                let parked = parked.clone();
+                thread::spawn(move || {
+                    queue.wait(
+                        16,
+                        || {
+                            parked.fetch_add(1, Ordering::SeqCst);
+                            true
+                        },
+                        None,
+                        || false,
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        while parked.load(Ordering::SeqCst) < 3 {
+            thread::yield_now();
+        }
+        assert_eq!(queue.notify(32, 3), 0);
+        assert_eq!(queue.notify(16, 2), 2);
+        assert_eq!(queue.notify(16, 2), 1);
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), Some(WaitResult::Ok));
+        }
+    }
+}
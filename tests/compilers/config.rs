@@ -21,6 +21,8 @@ pub struct Config {
     pub features: Option<Features>,
     pub middlewares: Vec<Arc<dyn ModuleMiddleware>>,
     pub canonicalize_nans: bool,
+    pub interruptible: bool,
+    pub explicit_stack_checks: bool,
 }
 
 impl Config {
@@ -30,6 +32,8 @@ impl Config {
             engine,
             features: None,
             canonicalize_nans: false,
+            interruptible: false,
+            explicit_stack_checks: false,
             middlewares: vec![],
         }
     }
@@ -46,6 +50,16 @@ impl Config {
         self.canonicalize_nans = canonicalize_nans;
     }
 
+    pub fn set_interruptible(&mut self, interruptible: bool) {
+        self.interruptible = interruptible;
+    }
+
+    /// Enables the explicit stack checks of Singlepass, which the other
+    /// compilers don't have.
+    pub fn set_explicit_stack_checks(&mut self, explicit_stack_checks: bool) {
+        self.explicit_stack_checks = explicit_stack_checks;
+    }
+
     pub fn store(&self) -> Store {
         let compiler_config = self.compiler_config(self.canonicalize_nans);
         let engine = self.engine(compiler_config);
@@ -105,6 +119,7 @@ impl Config {
             Compiler::Cranelift => {
                 let mut compiler = wasmer_compiler_cranelift::Cranelift::new();
                 compiler.canonicalize_nans(canonicalize_nans);
+                compiler.interruptible(self.interruptible);
                 compiler.enable_verifier();
                 self.add_middlewares(&mut compiler);
                 Box::new(compiler)
@@ -113,6 +128,7 @@ impl Config {
             Compiler::LLVM => {
                 let mut compiler = wasmer_compiler_llvm::LLVM::new();
                 compiler.canonicalize_nans(canonicalize_nans);
+                compiler.interruptible(self.interruptible);
                 compiler.enable_verifier();
                 self.add_middlewares(&mut compiler);
                 Box::new(compiler)
@@ -121,6 +137,8 @@ impl Config {
             Compiler::Singlepass => {
                 let mut compiler = wasmer_compiler_singlepass::Singlepass::new();
                 compiler.canonicalize_nans(canonicalize_nans);
+                compiler.explicit_stack_checks(self.explicit_stack_checks);
+                compiler.interruptible(self.interruptible);
                 compiler.enable_verifier();
                 self.add_middlewares(&mut compiler);
                 Box::new(compiler)
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use wasmer::*;
+
+fn exceptions_store(mut config: crate::Config) -> Store {
+    let mut features = Features::default();
+    features.exceptions(true);
+    config.set_features(features);
+    config.store()
+}
+
+#[compiler_test(exceptions)]
+fn uncaught_exception_reaches_the_host(config: crate::Config) -> Result<()> {
+    if config.compiler != crate::Compiler::Cranelift {
+        return Ok(());
+    }
+    let store = exceptions_store(config);
+    let module = Module::new(
+        &store,
+        r#"
+        (module
+          (tag $e (export "e") (param i32 i64))
+          (tag $other (export "other"))
+          (func (export "throw") (param i32)
+            (throw $e (local.get 0) (i64.const -1)))
+        )
+        "#,
+    )?;
+    let instance = Instance::new(&module, &imports! {})?;
+    let e = instance.exports.get_tag("e")?;
+    let other = instance.exports.get_tag("other")?;
+    assert_eq!(e.ty().params(), &[Type::I32, Type::I64]);
+    let throw = instance.exports.get_function("throw")?;
+
+    let error = throw.call(&[Val::I32(7)]).unwrap_err();
+    assert!(error.is_exception());
+    assert_eq!(
+        e.exception_payload(&error),
+        Some(vec![Val::I32(7), Val::I64(-1)])
+    );
+    assert_eq!(other.exception_payload(&error), None);
+
+    // Nothing is left pending for the next call.
+    let error = throw.call(&[Val::I32(8)]).unwrap_err();
+    assert_eq!(
+        e.exception_payload(&error),
+        Some(vec![Val::I32(8), Val::I64(-1)])
+    );
+    Ok(())
+}
+
+#[compiler_test(exceptions)]
+fn tags_are_compared_by_identity(config: crate::Config) -> Result<()> {
+    if config.compiler != crate::Compiler::Cranelift {
+        return Ok(());
+    }
+    let store = exceptions_store(config);
+    let thrower = Module::new(
+        &store,
+        r#"
+        (module
+          (import "host" "tag" (tag $e (param i32)))
+          (func (export "throw") (param i32)
+            (throw $e (local.get 0)))
+        )
+        "#,
+    )?;
+    let catcher = Module::new(
+        &store,
+        r#"
+        (module
+          (import "host" "tag" (tag $e (param i32)))
+          (import "thrower" "throw" (func $throw (param i32)))
+          (func (export "catch") (param i32) (result i32)
+            try (result i32)
+              (call $throw (local.get 0))
+              (i32.const -1)
+            catch $e
+            end)
+        )
+        "#,
+    )?;
+
+    // The tag of the catcher is the one of the thrower, so it catches.
+    let tag = Tag::new(&store, TagType::new([Type::I32]));
+    let thrower_instance =
+        Instance::new(&thrower, &imports! { "host" => { "tag" => tag.clone() } })?;
+    let throw = thrower_instance.exports.get_function("throw")?;
+    let catcher_instance = Instance::new(
+        &catcher,
+        &imports! {
+            "host" => { "tag" => tag.clone() },
+            "thrower" => { "throw" => throw.clone() },
+        },
+    )?;
+    let catch = catcher_instance.exports.get_function("catch")?;
+    assert_eq!(catch.call(&[Val::I32(5)])?.into_vec(), vec![Val::I32(5)]);
+
+    // Another tag of the same type doesn't catch it.
+    let other_tag = Tag::new(&store, TagType::new([Type::I32]));
+    let catcher_instance = Instance::new(
+        &catcher,
+        &imports! {
+            "host" => { "tag" => other_tag },
+            "thrower" => { "throw" => throw.clone() },
+        },
+    )?;
+    let catch = catcher_instance.exports.get_function("catch")?;
+    let error = catch.call(&[Val::I32(6)]).unwrap_err();
+    assert_eq!(tag.exception_payload(&error), Some(vec![Val::I32(6)]));
+    Ok(())
+}
+
+#[compiler_test(exceptions)]
+fn tag_imports_are_type_checked(config: crate::Config) -> Result<()> {
+    if config.compiler != crate::Compiler::Cranelift {
+        return Ok(());
+    }
+    let store = exceptions_store(config);
+    let module = Module::new(
+        &store,
+        r#"(module (import "host" "tag" (tag (param i32))))"#,
+    )?;
+    let tag = Tag::new(&store, TagType::new([Type::I64]));
+    let result = Instance::new(&module, &imports! { "host" => { "tag" => tag } });
+    assert!(matches!(result, Err(InstantiationError::Link(_))));
+    Ok(())
+}
@@ -432,3 +432,47 @@ fn instance_local_memory_lifetime(config: crate::Config) -> Result<()> {
 
     Ok(())
 }
+
+#[compiler_test(imports)]
+fn dynamic_function_with_v128(config: crate::Config) -> Result<()> {
+    let store = config.store();
+    let wat = r#"(module
+    (import "host" "fn" (func $host (param f32 v128 i32 i64 i64 i64 v128 f64) (result v128)))
+    (func (export "main") (param v128 v128) (result v128)
+      (call $host
+        (f32.const 1)
+        (local.get 0)
+        (i32.const 2)
+        (i64.const 3)
+        (i64.const 4)
+        (i64.const 5)
+        (local.get 1)
+        (f64.const 6)))
+)"#;
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(
+        &module,
+        &imports! {
+            "host" => {
+                "fn" => Function::new(&store, FunctionType::new(vec![ValType::F32, ValType::V128, ValType::I32, ValType::I64, ValType::I64, ValType::I64, ValType::V128, ValType::F64], vec![ValType::V128]), |values| {
+                    assert_eq!(values[0], Value::F32(1.0));
+                    assert_eq!(values[2], Value::I32(2));
+                    assert_eq!(values[3], Value::I64(3));
+                    assert_eq!(values[4], Value::I64(4));
+                    assert_eq!(values[5], Value::I64(5));
+                    assert_eq!(values[7], Value::F64(6.0));
+                    Ok(vec![Value::V128(values[1].unwrap_v128() ^ values[6].unwrap_v128())])
+                }),
+            },
+        },
+    )?;
+    let main = instance.exports.get_function("main")?;
+    let a = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+    let b = 0xffff_0000_ffff_0000_0000_ffff_0000_ffff;
+    assert_eq!(
+        main.call(&[Value::V128(a), Value::V128(b)])?.to_vec(),
+        vec![Value::V128(a ^ b)]
+    );
+
+    Ok(())
+}
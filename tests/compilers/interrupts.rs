@@ -0,0 +1,111 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use wasmer::*;
+
+const WAT: &str = r#"
+(module
+  (import "host" "tick" (func $tick))
+  (func (export "add") (param i32 i32) (result i32)
+    (i32.add (local.get 0) (local.get 1)))
+  (func (export "spin")
+    (loop $l
+      br $l))
+  (func (export "tick_forever")
+    (loop $l
+      call $tick
+      br $l)))
+"#;
+
+fn instance(store: &Store, ticks: Arc<AtomicUsize>) -> Result<Instance> {
+    let module = Module::new(store, WAT)?;
+    let handle = store.interrupt_handle();
+    let tick = Function::new(store, FunctionType::new(vec![], vec![]), move |_| {
+        ticks.fetch_add(1, Ordering::SeqCst);
+        handle.increment_epoch();
+        Ok(vec![])
+    });
+    let import_object = imports! {
+        "host" => {
+            "tick" => tick,
+        },
+    };
+
+    Ok(Instance::new(&module, &import_object)?)
+}
+
+#[compiler_test(interrupts)]
+fn interrupt_from_another_thread(mut config: crate::Config) -> Result<()> {
+    config.set_interruptible(true);
+    let store = config.store();
+    let instance = instance(&store, Arc::new(AtomicUsize::new(0)))?;
+
+    let handle = instance.interrupt_handle();
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        handle.interrupt();
+    });
+
+    let spin = instance.exports.get_function("spin")?;
+    let error = spin.call(&[]).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::Interrupt));
+    interrupter.join().unwrap();
+
+    Ok(())
+}
+
+#[compiler_test(interrupts)]
+fn interrupt_is_sticky_until_cleared(mut config: crate::Config) -> Result<()> {
+    config.set_interruptible(true);
+    let store = config.store();
+    let instance = instance(&store, Arc::new(AtomicUsize::new(0)))?;
+    let add: NativeFunc<(i32, i32), i32> = instance.exports.get_native_function("add")?;
+
+    store.interrupt_handle().interrupt();
+    let error = add.call(1, 2).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::Interrupt));
+    let error = add.call(1, 2).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::Interrupt));
+
+    store.clear_epoch_deadline();
+    assert_eq!(add.call(1, 2)?, 3);
+
+    Ok(())
+}
+
+#[compiler_test(interrupts)]
+fn epoch_deadline(mut config: crate::Config) -> Result<()> {
+    config.set_interruptible(true);
+    let store = config.store();
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let instance = instance(&store, ticks.clone())?;
+    let tick_forever = instance.exports.get_function("tick_forever")?;
+
+    store.set_epoch_deadline(3);
+    let error = tick_forever.call(&[]).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::Interrupt));
+    assert_eq!(ticks.load(Ordering::SeqCst), 3);
+
+    // The deadline is relative to the current epoch.
+    store.set_epoch_deadline(2);
+    let error = tick_forever.call(&[]).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::Interrupt));
+    assert_eq!(ticks.load(Ordering::SeqCst), 5);
+
+    Ok(())
+}
+
+#[compiler_test(interrupts)]
+fn interrupt_checks_are_opt_in(config: crate::Config) -> Result<()> {
+    let store = config.store();
+    let instance = instance(&store, Arc::new(AtomicUsize::new(0)))?;
+    let add: NativeFunc<(i32, i32), i32> = instance.exports.get_native_function("add")?;
+
+    // Without the interrupt checks, the code runs to completion.
+    store.interrupt_handle().interrupt();
+    assert_eq!(add.call(1, 2)?, 3);
+
+    Ok(())
+}
@@ -7,13 +7,21 @@ extern crate compiler_test_derive;
 
 mod config;
 mod deterministic;
+mod exceptions;
 mod imports;
+mod interrupts;
 mod issues;
+mod memory64;
 mod metering;
 mod middlewares;
+mod module_linking;
 // mod multi_value_imports;
 mod native_functions;
+#[cfg(feature = "cranelift")]
+mod relaxed_simd;
 mod serialize;
+mod tail_calls;
+mod threads;
 mod traps;
 mod wasi;
 mod wast;
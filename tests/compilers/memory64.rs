@@ -0,0 +1,123 @@
+use anyhow::Result;
+use wasmer::*;
+
+const MEMORY64_WAT: &str = r#"
+    (module
+      (memory (export "memory") i64 1 3)
+      (data (i64.const 8) "\2a")
+      (func (export "load") (param i64) (result i32)
+        (i32.load8_u (local.get 0)))
+      (func (export "store") (param i64 i32)
+        (i32.store (local.get 0) (local.get 1)))
+      (func (export "load_offset") (param i64) (result i32)
+        (i32.load8_u offset=0x100000000 (local.get 0)))
+      (func (export "size") (result i64)
+        (memory.size))
+      (func (export "grow") (param i64) (result i64)
+        (memory.grow (local.get 0)))
+      (func (export "fill") (param i64 i32 i64)
+        (memory.fill (local.get 0) (local.get 1) (local.get 2)))
+      (func (export "copy") (param i64 i64 i64)
+        (memory.copy (local.get 0) (local.get 1) (local.get 2)))
+    )
+"#;
+
+fn memory64_store(mut config: crate::Config) -> Store {
+    let mut features = Features::default();
+    features.memory64(true);
+    if config.compiler == crate::Compiler::Singlepass {
+        features.multi_value(false);
+    }
+    config.set_features(features);
+    config.store()
+}
+
+#[compiler_test(memory64)]
+fn memory64_addressing(config: crate::Config) -> Result<()> {
+    let is_singlepass = config.compiler == crate::Compiler::Singlepass;
+    let store = memory64_store(config);
+    let module = Module::new(&store, MEMORY64_WAT);
+    if is_singlepass {
+        assert!(module.is_err());
+        return Ok(());
+    }
+    let module = module?;
+    let memory_type = module.exports().memories().next().unwrap().ty().clone();
+    assert!(memory_type.memory64);
+    let instance = Instance::new(&module, &imports! {})?;
+
+    let load = instance.exports.get_function("load")?;
+    let store_value = instance.exports.get_function("store")?;
+    assert_eq!(load.call(&[Value::I64(8)])?.to_vec(), vec![Value::I32(42)]);
+
+    store_value.call(&[Value::I64(0xfffc), Value::I32(7)])?;
+    assert_eq!(
+        load.call(&[Value::I64(0xfffc)])?.to_vec(),
+        vec![Value::I32(7)]
+    );
+
+    // Addresses past the end of the memory, including ones that don't fit
+    // in 32 bits, trap.
+    for address in &[0x10000, 0x1_0000_0000, -1] {
+        let e = load.call(&[Value::I64(*address)]).unwrap_err();
+        assert_eq!(e.to_trap(), Some(TrapCode::HeapAccessOutOfBounds));
+    }
+    let e = store_value
+        .call(&[Value::I64(0xfffd), Value::I32(0)])
+        .unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::HeapAccessOutOfBounds));
+    let e = instance
+        .exports
+        .get_function("load_offset")?
+        .call(&[Value::I64(0)])
+        .unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::HeapAccessOutOfBounds));
+
+    Ok(())
+}
+
+#[compiler_test(memory64)]
+fn memory64_bulk_operations(config: crate::Config) -> Result<()> {
+    if config.compiler == crate::Compiler::Singlepass {
+        return Ok(());
+    }
+    let store = memory64_store(config);
+    let module = Module::new(&store, MEMORY64_WAT)?;
+    let instance = Instance::new(&module, &imports! {})?;
+
+    let size = instance.exports.get_function("size")?;
+    let grow = instance.exports.get_function("grow")?;
+    assert_eq!(size.call(&[])?.to_vec(), vec![Value::I64(1)]);
+    assert_eq!(grow.call(&[Value::I64(1)])?.to_vec(), vec![Value::I64(1)]);
+    assert_eq!(size.call(&[])?.to_vec(), vec![Value::I64(2)]);
+    assert_eq!(grow.call(&[Value::I64(2)])?.to_vec(), vec![Value::I64(-1)]);
+    assert_eq!(
+        grow.call(&[Value::I64(1 << 40)])?.to_vec(),
+        vec![Value::I64(-1)]
+    );
+
+    let load = instance.exports.get_function("load")?;
+    let fill = instance.exports.get_function("fill")?;
+    let copy = instance.exports.get_function("copy")?;
+    fill.call(&[Value::I64(0x1_0000), Value::I32(5), Value::I64(4)])?;
+    copy.call(&[Value::I64(0x1_0010), Value::I64(8), Value::I64(1)])?;
+    assert_eq!(
+        load.call(&[Value::I64(0x1_0003)])?.to_vec(),
+        vec![Value::I32(5)]
+    );
+    assert_eq!(
+        load.call(&[Value::I64(0x1_0010)])?.to_vec(),
+        vec![Value::I32(42)]
+    );
+
+    let e = fill
+        .call(&[Value::I64(-1), Value::I32(0), Value::I64(2)])
+        .unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::HeapAccessOutOfBounds));
+    let e = copy
+        .call(&[Value::I64(0), Value::I64(1 << 40), Value::I64(1)])
+        .unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::HeapAccessOutOfBounds));
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 
 use loupe::MemoryUsage;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use wasmer::wasmparser::Operator;
 use wasmer::*;
 
@@ -88,6 +88,57 @@ impl FunctionMiddleware for Fusion {
     }
 }
 
+/// Adds a `host.log` import and an exported `answer` function logging
+/// and returning 42.
+#[derive(Debug, MemoryUsage)]
+struct AnswerGen {
+    indexes: Mutex<Option<(FunctionIndex, LocalFunctionIndex)>>,
+}
+
+#[derive(Debug)]
+struct Answer {
+    log: Option<FunctionIndex>,
+}
+
+impl ModuleMiddleware for AnswerGen {
+    fn generate_function_middleware(
+        &self,
+        local_function_index: LocalFunctionIndex,
+    ) -> Box<dyn FunctionMiddleware> {
+        let (log, answer) = self.indexes.lock().unwrap().unwrap();
+        Box::new(Answer {
+            log: Some(log).filter(|_| local_function_index == answer),
+        })
+    }
+
+    fn transform_module(&self, module: &mut ModuleRewriter) {
+        let log =
+            module.push_function_import("host", "log", FunctionType::new(vec![Type::I32], vec![]));
+        let answer = module.push_function(FunctionType::new(vec![], vec![Type::I32]), &[]);
+        let answer_index = module.module_info().func_index(answer);
+        module.push_export("answer", ExportIndex::Function(answer_index));
+        *self.indexes.lock().unwrap() = Some((log, answer));
+    }
+}
+
+impl FunctionMiddleware for Answer {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        if let (Some(log), Operator::End) = (self.log, &operator) {
+            state.push_operator(Operator::I32Const { value: 42 });
+            state.push_operator(Operator::Call {
+                function_index: log.as_u32(),
+            });
+            state.push_operator(Operator::I32Const { value: 42 });
+        }
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
 #[compiler_test(middlewares)]
 fn middleware_basic(mut config: crate::Config) -> Result<()> {
     config.set_middlewares(vec![
@@ -205,3 +256,50 @@ fn middleware_chain_order_2(mut config: crate::Config) -> Result<()> {
     assert_eq!(result, 48);
     Ok(())
 }
+
+#[compiler_test(middlewares)]
+fn middleware_rewrite_module(mut config: crate::Config) -> Result<()> {
+    config.set_middlewares(vec![
+        Arc::new(AnswerGen {
+            indexes: Mutex::new(None),
+        }) as Arc<dyn ModuleMiddleware>,
+        Arc::new(Add2MulGen { value_off: 0 }) as Arc<dyn ModuleMiddleware>,
+    ]);
+    let store = config.store();
+    let wat = r#"(module
+        (func $add (param i32 i32) (result i32)
+           (i32.add (local.get 0)
+                    (local.get 1)))
+        (func (export "add") (param i32 i32) (result i32)
+           (call $add (local.get 0) (local.get 1)))
+)"#;
+    let module = Module::new(&store, wat).unwrap();
+
+    let logged = Arc::new(Mutex::new(vec![]));
+    let log = {
+        let logged = logged.clone();
+        Function::new(
+            &store,
+            FunctionType::new(vec![Type::I32], vec![]),
+            move |args| {
+                logged.lock().unwrap().push(args[0].unwrap_i32());
+                Ok(vec![])
+            },
+        )
+    };
+    let import_object = imports! {
+        "host" => {
+            "log" => log,
+        },
+    };
+
+    let instance = Instance::new(&module, &import_object)?;
+
+    let add: NativeFunc<(i32, i32), i32> = instance.exports.get_native_function("add")?;
+    assert_eq!(add.call(4, 6)?, 24);
+
+    let answer: NativeFunc<(), i32> = instance.exports.get_native_function("answer")?;
+    assert_eq!(answer.call()?, 42);
+    assert_eq!(*logged.lock().unwrap(), vec![42]);
+    Ok(())
+}
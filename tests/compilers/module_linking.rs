@@ -0,0 +1,33 @@
+use anyhow::Result;
+use wasmer::*;
+
+#[compiler_test(module_linking)]
+fn module_linking_is_a_compile_error(mut config: crate::Config) -> Result<()> {
+    let mut features = Features::default();
+    features.module_linking(true);
+    config.set_features(features);
+    let store = config.store();
+
+    let wasms: [&[u8]; 2] = [
+        // A type section with an instance type.
+        &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x03, 0x01, 0x62, 0x00,
+        ],
+        // A module section with an empty nested module.
+        &[
+            0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x0e, 0x0a, 0x01, 0x08, 0x00, 0x61,
+            0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        ],
+    ];
+    for wasm in wasms {
+        match Module::new(&store, wasm) {
+            Err(CompileError::Wasm(WasmError::Unsupported(feature))) => {
+                assert_eq!(feature, "module linking")
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("module linking should not be supported"),
+        }
+    }
+
+    Ok(())
+}
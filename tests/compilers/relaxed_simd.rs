@@ -0,0 +1,100 @@
+use anyhow::Result;
+use wasmer::*;
+
+fn f32x4(lanes: [f32; 4]) -> Val {
+    let bits = lanes
+        .iter()
+        .rev()
+        .fold(0, |bits, lane| bits << 32 | u128::from(lane.to_bits()));
+    Val::V128(bits)
+}
+
+fn f32x4_lanes(value: &Val) -> [f32; 4] {
+    let bits = value.unwrap_v128();
+    let lane = |i: u32| f32::from_bits((bits >> (32 * i)) as u32);
+    [lane(0), lane(1), lane(2), lane(3)]
+}
+
+#[compiler_test(relaxed_simd)]
+fn deterministic_relaxed_simd(mut config: crate::Config) -> Result<()> {
+    if config.compiler != crate::Compiler::Cranelift {
+        return Ok(());
+    }
+    let mut features = Features::default();
+    features.relaxed_simd(true);
+    config.set_features(features);
+    let mut compiler = wasmer_compiler_cranelift::Cranelift::new();
+    compiler.relaxed_simd_deterministic(true);
+    let store = Store::new(&*config.engine(Box::new(compiler)));
+    let module = Module::new(
+        &store,
+        r#"
+        (module
+          (func (export "min") (param v128 v128) (result v128)
+            (f32x4.relaxed_min (local.get 0) (local.get 1)))
+          (func (export "max") (param v128 v128) (result v128)
+            (f32x4.relaxed_max (local.get 0) (local.get 1)))
+        )
+        "#,
+    )?;
+    let instance = Instance::new(&module, &imports! {})?;
+    let min = instance.exports.get_function("min")?;
+    let max = instance.exports.get_function("max")?;
+
+    // The results are the ones of `f32x4.min` and `f32x4.max`, whatever
+    // the target: a NaN if either operand is one, and `-0 < 0`.
+    let a = f32x4([f32::NAN, 1.0, 0.0, -0.0]);
+    let b = f32x4([1.0, f32::NAN, -0.0, 0.0]);
+    let result = f32x4_lanes(&min.call(&[a.clone(), b.clone()])?[0]);
+    assert!(result[0].is_nan() && result[1].is_nan());
+    assert_eq!(result[2].to_bits(), (-0.0f32).to_bits());
+    assert_eq!(result[3].to_bits(), (-0.0f32).to_bits());
+    let result = f32x4_lanes(&max.call(&[a, b])?[0]);
+    assert!(result[0].is_nan() && result[1].is_nan());
+    assert_eq!(result[2].to_bits(), 0.0f32.to_bits());
+    assert_eq!(result[3].to_bits(), 0.0f32.to_bits());
+    Ok(())
+}
+
+#[compiler_test(relaxed_simd)]
+fn relaxed_simd_is_rejected_by_llvm(mut config: crate::Config) -> Result<()> {
+    if config.compiler != crate::Compiler::LLVM {
+        return Ok(());
+    }
+    let mut features = Features::default();
+    features.relaxed_simd(true);
+    config.set_features(features);
+    let store = config.store();
+
+    let error = Module::new(&store, "(module)").unwrap_err();
+    assert!(matches!(
+        error,
+        CompileError::UnsupportedFeature(feature) if feature == "relaxed SIMD"
+    ));
+    Ok(())
+}
+
+#[compiler_test(relaxed_simd)]
+fn final_relaxed_simd_encoding_is_rejected(mut config: crate::Config) -> Result<()> {
+    let mut features = Features::default();
+    features.relaxed_simd(true);
+    config.set_features(features);
+    let store = config.store();
+
+    // `f32x4.relaxed_min` with the final encoding of the proposal, `0xfd
+    // 0x10d`, where the prototype one that wasmparser knows is `0xfd 0xb4`.
+    let wasm = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+        0x01, 0x07, 0x01, 0x60, 0x02, 0x7b, 0x7b, 0x01, 0x7b, // type
+        0x03, 0x02, 0x01, 0x00, // function
+        0x0a, 0x0b, 0x01, 0x09, 0x00, // code
+        0x20, 0x00, 0x20, 0x01, 0xfd, 0x8d, 0x02, 0x0b,
+    ];
+    let error = Module::new(&store, &wasm[..]).unwrap_err();
+    assert!(
+        error.to_string().contains("Unknown 0xfd subopcode: 0x10d"),
+        "unexpected error: {}",
+        error
+    );
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use anyhow::Result;
+use wasmer::*;
+
+fn tail_call_store(mut config: crate::Config) -> Store {
+    let mut features = Features::default();
+    features.tail_call(true);
+    config.set_features(features);
+    config.store()
+}
+
+#[compiler_test(tail_calls)]
+fn tail_calls_across_instances_and_to_the_host(config: crate::Config) -> Result<()> {
+    if config.compiler == crate::Compiler::Singlepass {
+        return Ok(());
+    }
+    let store = tail_call_store(config);
+
+    // `count` and `relay` tail call each other across two instances, and
+    // the last call of the chain goes to the host.
+    let first = Module::new(
+        &store,
+        r#"
+        (module
+          (import "host" "done" (func $done (param i64) (result i64 i64)))
+          (import "second" "relay" (func $relay (param i64) (result i64 i64)))
+          (func $count (export "count") (param i64) (result i64 i64)
+            (if (result i64 i64) (i64.eqz (local.get 0))
+              (then (return_call $done (i64.const 42)))
+              (else (return_call $relay (i64.sub (local.get 0) (i64.const 1))))))
+        )
+        "#,
+    )?;
+    let second = Module::new(
+        &store,
+        r#"
+        (module
+          (type $t (func (param i64) (result i64 i64)))
+          (table 1 funcref)
+          (func (export "relay") (param i64) (result i64 i64)
+            (return_call_indirect (type $t) (local.get 0) (i32.const 0)))
+          (export "table" (table 0))
+        )
+        "#,
+    )?;
+
+    let done = Function::new_native(&store, |value: i64| (value, -value));
+    let second_instance = Instance::new(&second, &imports! {})?;
+    let relay = second_instance.exports.get_function("relay")?;
+    let first_instance = Instance::new(
+        &first,
+        &imports! {
+            "host" => { "done" => done },
+            "second" => { "relay" => relay.clone() },
+        },
+    )?;
+    let count = first_instance.exports.get_function("count")?;
+    second_instance
+        .exports
+        .get_table("table")?
+        .set(0, Val::FuncRef(Some(count.clone())))?;
+
+    assert_eq!(
+        count.call(&[Value::I64(100_000)])?.to_vec(),
+        vec![Value::I64(42), Value::I64(-42)]
+    );
+    assert_eq!(
+        relay.call(&[Value::I64(3)])?.to_vec(),
+        vec![Value::I64(42), Value::I64(-42)]
+    );
+
+    Ok(())
+}
+
+#[compiler_test(tail_calls)]
+fn tail_calls_after_a_trap(config: crate::Config) -> Result<()> {
+    if config.compiler == crate::Compiler::Singlepass {
+        return Ok(());
+    }
+    let store = tail_call_store(config);
+    let module = Module::new(
+        &store,
+        r#"
+        (module
+          (func $count (export "count") (param i32) (result i32)
+            (if (result i32) (i32.eqz (local.get 0))
+              (then (i32.const 7))
+              (else (return_call $check (i32.sub (local.get 0) (i32.const 1))))))
+          (func $check (param i32) (result i32)
+            (if (i32.eq (local.get 0) (i32.const 500))
+              (then unreachable))
+            (return_call $count (local.get 0)))
+        )
+        "#,
+    )?;
+    let instance = Instance::new(&module, &imports! {})?;
+    let count = instance.exports.get_function("count")?;
+
+    let e = count.call(&[Value::I32(1_000)]).unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::UnreachableCodeReached));
+
+    // The chain that trapped doesn't leak into the next calls.
+    assert_eq!(
+        count.call(&[Value::I32(400)])?.to_vec(),
+        vec![Value::I32(7)]
+    );
+    assert_eq!(count.call(&[Value::I32(0)])?.to_vec(), vec![Value::I32(7)]);
+
+    Ok(())
+}
+
+#[compiler_test(tail_calls)]
+fn tail_calls_with_more_arguments_than_the_caller(config: crate::Config) -> Result<()> {
+    if config.compiler == crate::Compiler::Singlepass {
+        return Ok(());
+    }
+    let store = tail_call_store(config);
+
+    // `start` has a single value of storage for its arguments and results,
+    // while `sum` takes eight arguments.
+    let module = Module::new(
+        &store,
+        r#"
+        (module
+          (func (export "start") (param i64) (result i64)
+            (return_call $sum
+              (local.get 0) (i64.const 1) (i64.const 2) (i64.const 3)
+              (i64.const 4) (i64.const 5) (i64.const 6) (i64.const 7)))
+          (func $sum (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+            (if (result i64) (i64.eqz (local.get 0))
+              (then
+                (i64.add (i64.add (i64.add (local.get 1) (local.get 2))
+                                  (i64.add (local.get 3) (local.get 4)))
+                         (i64.add (i64.add (local.get 5) (local.get 6))
+                                  (local.get 7))))
+              (else
+                (return_call $sum
+                  (i64.sub (local.get 0) (i64.const 1))
+                  (local.get 2) (local.get 3) (local.get 4) (local.get 5)
+                  (local.get 6) (local.get 7) (local.get 1)))))
+        )
+        "#,
+    )?;
+    let instance = Instance::new(&module, &imports! {})?;
+    let start = instance.exports.get_function("start")?;
+
+    assert_eq!(
+        start.call(&[Value::I64(1_000)])?.to_vec(),
+        vec![Value::I64(28)]
+    );
+
+    Ok(())
+}
+
+#[compiler_test(tail_calls)]
+fn tail_calls_with_too_many_arguments_are_rejected(config: crate::Config) -> Result<()> {
+    if config.compiler == crate::Compiler::Singlepass {
+        return Ok(());
+    }
+    let store = tail_call_store(config);
+
+    // Even without validation, the parser rejects signatures with more than
+    // 1000 parameters, so no tail call can pass more values than the runtime
+    // holds.
+    let wat = format!(
+        r#"
+        (module
+          (func $callee (param {}))
+          (func (export "start") (return_call $callee {})))
+        "#,
+        "i32 ".repeat(1001),
+        "(i32.const 0) ".repeat(1001)
+    );
+    let wasm = wat2wasm(wat.as_bytes())?;
+    let e = unsafe { Module::from_binary_unchecked(&store, &wasm) }.unwrap_err();
+    assert!(
+        e.to_string()
+            .contains("function params size is out of bound"),
+        "unexpected error: {}",
+        e
+    );
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use std::thread;
+use std::time::Duration;
+use wasmer::*;
+
+const WAIT_NOTIFY_WAT: &str = r#"
+    (module
+      (memory (export "memory") 1 1 shared)
+      (func (export "wait") (param i64) (result i32)
+        (memory.atomic.wait32 (i32.const 0) (i32.const 0) (local.get 0)))
+      (func (export "wait_unaligned") (result i32)
+        (memory.atomic.wait32 (i32.const 1) (i32.const 0) (i64.const 0)))
+      (func (export "notify") (result i32)
+        (memory.atomic.notify (i32.const 0) (i32.const 1)))
+      (func (export "store") (param i32)
+        (i32.atomic.store (i32.const 0) (local.get 0)))
+    )
+"#;
+
+fn threads_store(mut config: crate::Config) -> Store {
+    let mut features = Features::default();
+    features.threads(true);
+    if config.compiler == crate::Compiler::Singlepass {
+        features.multi_value(false);
+    }
+    config.set_features(features);
+    config.store()
+}
+
+#[compiler_test(threads)]
+fn wait_returns_without_a_notify(config: crate::Config) -> Result<()> {
+    let store = threads_store(config);
+    let module = Module::new(&store, WAIT_NOTIFY_WAT)?;
+    let instance = Instance::new(&module, &imports! {})?;
+
+    let wait = instance.exports.get_function("wait")?;
+    let store_value = instance.exports.get_function("store")?;
+
+    // The timeout is in nanoseconds.
+    assert_eq!(
+        wait.call(&[Value::I64(1_000)])?.to_vec(),
+        vec![Value::I32(2)]
+    );
+
+    store_value.call(&[Value::I32(1)])?;
+    assert_eq!(wait.call(&[Value::I64(-1)])?.to_vec(), vec![Value::I32(1)]);
+
+    let notify = instance.exports.get_function("notify")?;
+    assert_eq!(notify.call(&[])?.to_vec(), vec![Value::I32(0)]);
+
+    let e = instance
+        .exports
+        .get_function("wait_unaligned")?
+        .call(&[])
+        .unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::UnalignedAtomic));
+
+    Ok(())
+}
+
+#[compiler_test(threads)]
+fn notify_wakes_a_waiting_thread(config: crate::Config) -> Result<()> {
+    let store = threads_store(config);
+    let module = Module::new(&store, WAIT_NOTIFY_WAT)?;
+    let instance = Instance::new(&module, &imports! {})?;
+
+    let wait = instance.exports.get_function("wait")?.clone();
+    let waiter = thread::spawn(move || {
+        wait.call(&[Value::I64(-1)])
+            .map(|results| results[0].unwrap_i32())
+    });
+
+    // Keep notifying until the other thread has started waiting.
+    let notify = instance.exports.get_function("notify")?;
+    while notify.call(&[])?.to_vec() != vec![Value::I32(1)] {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(waiter.join().unwrap()?, 0);
+
+    Ok(())
+}
+
+#[compiler_test(threads)]
+fn wait_on_unshared_memory_traps(config: crate::Config) -> Result<()> {
+    let store = threads_store(config);
+    let wat = r#"
+        (module
+          (memory 1)
+          (func (export "wait") (result i32)
+            (memory.atomic.wait32 (i32.const 0) (i32.const 0) (i64.const 0)))
+          (func (export "notify") (result i32)
+            (memory.atomic.notify (i32.const 0) (i32.const 1)))
+        )
+    "#;
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&module, &imports! {})?;
+
+    let e = instance
+        .exports
+        .get_function("wait")?
+        .call(&[])
+        .unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::AtomicWaitOnUnsharedMemory));
+
+    let notify = instance.exports.get_function("notify")?;
+    assert_eq!(notify.call(&[])?.to_vec(), vec![Value::I32(0)]);
+
+    Ok(())
+}
+
+#[compiler_test(threads)]
+fn interrupt_wakes_a_waiting_thread(config: crate::Config) -> Result<()> {
+    let store = threads_store(config);
+    let module = Module::new(&store, WAIT_NOTIFY_WAT)?;
+    let instance = Instance::new(&module, &imports! {})?;
+
+    let wait = instance.exports.get_function("wait")?.clone();
+    let waiter = thread::spawn(move || wait.call(&[Value::I64(-1)]).map(|_| ()));
+
+    thread::sleep(Duration::from_millis(50));
+    store.interrupt_handle().interrupt();
+
+    let e = waiter.join().unwrap().unwrap_err();
+    assert_eq!(e.to_trap(), Some(TrapCode::Interrupt));
+
+    // Nobody is left waiting.
+    store.clear_epoch_deadline();
+    let notify = instance.exports.get_function("notify")?;
+    assert_eq!(notify.call(&[])?.to_vec(), vec![Value::I32(0)]);
+
+    Ok(())
+}
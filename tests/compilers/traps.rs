@@ -143,6 +143,158 @@ fn test_trap_stack_overflow(config: crate::Config) -> Result<()> {
     Ok(())
 }
 
+#[cfg_attr(target_env = "musl", ignore)]
+#[compiler_test(traps)]
+fn test_stack_size(config: crate::Config) -> Result<()> {
+    let store = config.store();
+    let wat = r#"
+        (module
+            (func $count (export "count") (param $n i32) (result i32)
+                local.get $n
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get $n
+                    i32.const 1
+                    i32.sub
+                    call $count
+                    i32.const 1
+                    i32.add
+                end)
+        )
+    "#;
+
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&module, &imports! {})?;
+    let count: NativeFunc<i32, i32> = instance.exports.get_native_function("count")?;
+    let dynamic_count = instance.exports.get_function("count")?;
+    let depth = 100_000;
+    let big_stack_size = 32 << 20;
+
+    store.set_stack_size(64 << 10);
+    assert_eq!(store.stack_size(), 64 << 10);
+    let e = count.call(depth).unwrap_err();
+    assert!(e.message().contains("call stack exhausted"));
+    assert!(dynamic_count.call(&[Value::I32(depth)]).is_err());
+
+    // The stack size of a single call overrides the one of the store.
+    assert_eq!(count.with_stack_size(big_stack_size).call(depth)?, depth);
+    assert_eq!(
+        dynamic_count
+            .with_stack_size(big_stack_size)
+            .call(&[Value::I32(depth)])?
+            .to_vec(),
+        vec![Value::I32(depth)]
+    );
+    assert!(count.call(depth).is_err());
+
+    // The stack doesn't depend on the host thread making the call.
+    store.set_stack_size(big_stack_size);
+    let result = std::thread::Builder::new()
+        .stack_size(256 << 10)
+        .spawn(move || count.call(depth).map_err(|e| e.message()))?
+        .join()
+        .unwrap();
+    assert_eq!(result, Ok(depth));
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[cfg_attr(target_env = "musl", ignore)]
+#[compiler_test(traps)]
+fn test_stack_overflow_without_signals(mut config: crate::Config) -> Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    config.set_explicit_stack_checks(true);
+    let store = config.store();
+    let signals = Arc::new(AtomicUsize::new(0));
+    let counter = signals.clone();
+    store.set_trap_handler(Some(Box::new(move |_, _, _| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        false
+    })));
+
+    // `big` has a frame of several pages, which must not be written to
+    // before the stack is checked.
+    let wat = format!(
+        r#"
+        (module
+            (func $run (export "run") (call $run))
+            (func $big (export "big") (local {}) (call $big))
+        )
+    "#,
+        "i64 ".repeat(4096)
+    );
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&module, &imports! {})?;
+    for name in ["run", "big"] {
+        let e = instance.exports.get_function(name)?.call(&[]).unwrap_err();
+        assert!(e.message().contains("call stack exhausted"));
+    }
+
+    // With explicit stack checks, Singlepass checks the stack itself, so the
+    // overflows don't go through the signal handler.
+    if config.compiler == crate::Compiler::Singlepass {
+        assert_eq!(signals.load(Ordering::SeqCst), 0);
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[cfg_attr(target_env = "musl", ignore)]
+#[compiler_test(traps)]
+fn test_stack_overflow_on_several_threads(mut config: crate::Config) -> Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    config.set_explicit_stack_checks(true);
+    let store = config.store();
+    let signals = Arc::new(AtomicUsize::new(0));
+    let counter = signals.clone();
+    store.set_trap_handler(Some(Box::new(move |_, _, _| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        false
+    })));
+
+    let wat = r#"
+        (module
+            (func $run (export "run") (call $run))
+        )
+    "#;
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&module, &imports! {})?;
+    let run: NativeFunc<(), ()> = instance.exports.get_native_function("run")?;
+
+    // Each call checks the stack against the limit of its own stack, while
+    // the other threads run on theirs.
+    let threads = (0..4)
+        .map(|i| {
+            let run = run.with_stack_size((64 << 10) << i);
+            std::thread::spawn(move || {
+                (0..20).all(|_| {
+                    run.call()
+                        .unwrap_err()
+                        .message()
+                        .contains("call stack exhausted")
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        assert!(thread.join().unwrap());
+    }
+
+    if config.compiler == crate::Compiler::Singlepass {
+        assert_eq!(signals.load(Ordering::SeqCst), 0);
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(target_env = "musl", ignore)]
 #[compiler_test(traps)]
 fn trap_display_pretty(config: crate::Config) -> Result<()> {
@@ -255,7 +407,8 @@ fn trap_start_function_import(config: crate::Config) -> Result<()> {
     match err {
         InstantiationError::Link(_)
         | InstantiationError::HostEnvInitialization(_)
-        | InstantiationError::CpuFeature(_) => {
+        | InstantiationError::CpuFeature(_)
+        | InstantiationError::ResourceLimit(_) => {
             panic!("It should be a start error")
         }
         InstantiationError::Start(err) => {
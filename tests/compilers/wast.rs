@@ -22,12 +22,36 @@ pub fn run_wast(mut config: crate::Config, wast_path: &str) -> anyhow::Result<()
     let mut features = Features::default();
     let is_bulkmemory = wast_path.contains("bulk-memory");
     let is_simd = wast_path.contains("simd");
+    let is_threads = wast_path.contains("threads");
+    let is_multi_memory = wast_path.contains("multi-memory");
+    let is_tail_call = wast_path.contains("tail-call");
+    let is_exceptions = wast_path.contains("exceptions");
+    let is_extended_const = wast_path.contains("extended-const");
+    let is_relaxed_simd = wast_path.contains("relaxed-simd");
     if is_bulkmemory {
         features.bulk_memory(true);
     }
     if is_simd {
         features.simd(true);
     }
+    if is_threads {
+        features.threads(true);
+    }
+    if is_multi_memory {
+        features.multi_memory(true);
+    }
+    if is_tail_call {
+        features.tail_call(true);
+    }
+    if is_exceptions {
+        features.exceptions(true);
+    }
+    if is_extended_const {
+        features.extended_const(true);
+    }
+    if is_relaxed_simd {
+        features.relaxed_simd(true);
+    }
     if config.compiler == crate::Compiler::Singlepass {
         features.multi_value(false);
     }
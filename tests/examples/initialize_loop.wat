@@ -0,0 +1,12 @@
+(module
+    (func $initialize
+        (loop $forever
+            (br $forever)
+        )
+    )
+
+    (func $main)
+
+    (export "_initialize" (func $initialize))
+    (export "_start" (func $main))
+)
@@ -12,6 +12,14 @@ fn test_no_imports_wat_path() -> String {
     format!("{}/{}", ASSET_PATH, "fib.wat")
 }
 
+fn test_add_wat_path() -> String {
+    format!("{}/{}", ASSET_PATH, "add.wat")
+}
+
+fn test_initialize_loop_wat_path() -> String {
+    format!("{}/{}", ASSET_PATH, "initialize_loop.wat")
+}
+
 fn test_no_start_wat_path() -> String {
     format!("{}/{}", ASSET_PATH, "no_start.wat")
 }
@@ -75,3 +83,99 @@ fn run_no_start_wasm_report_error() -> anyhow::Result<()> {
     assert_eq!(result.contains("Can not find any export functions."), true);
     Ok(())
 }
+
+#[test]
+fn run_with_fuel_reports_consumed_points() -> anyhow::Result<()> {
+    let output = Command::new(WASMER_PATH)
+        .arg("run")
+        .arg(test_add_wat_path())
+        .arg("--invoke")
+        .arg("add")
+        .arg("--fuel")
+        .arg("100")
+        .arg("1")
+        .arg("2")
+        .output()?;
+
+    if !output.status.success() {
+        bail!(
+            "running with fuel failed with: stdout: {}\n\nstderr: {}",
+            std::str::from_utf8(&output.stdout)
+                .expect("stdout is not utf8! need to handle arbitrary bytes"),
+            std::str::from_utf8(&output.stderr)
+                .expect("stderr is not utf8! need to handle arbitrary bytes")
+        );
+    }
+
+    assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), "3\n");
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(stderr.contains("Fuel consumed: "));
+    assert!(stderr.contains(" of 100 points"));
+    Ok(())
+}
+
+#[test]
+fn run_with_exhausted_fuel_reports_error() -> anyhow::Result<()> {
+    let output = Command::new(WASMER_PATH)
+        .arg("run")
+        .arg(test_no_imports_wat_path())
+        .arg("--fuel")
+        .arg("1000")
+        .output()?;
+
+    assert_eq!(output.status.code(), Some(124));
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(stderr.contains("all 1000 points of fuel were consumed"));
+    Ok(())
+}
+
+#[test]
+fn run_precompiled_with_exhausted_fuel_reports_error() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let artifact_path = temp_dir.path().join("fib.wjit");
+    let output = Command::new(WASMER_PATH)
+        .arg("compile")
+        .arg(test_no_imports_wat_path())
+        .arg("--universal")
+        .arg("--fuel")
+        .arg("1000")
+        .arg("-o")
+        .arg(&artifact_path)
+        .output()?;
+
+    if !output.status.success() {
+        bail!(
+            "wasmer compile failed with: stdout: {}\n\nstderr: {}",
+            std::str::from_utf8(&output.stdout)
+                .expect("stdout is not utf8! need to handle arbitrary bytes"),
+            std::str::from_utf8(&output.stderr)
+                .expect("stderr is not utf8! need to handle arbitrary bytes")
+        );
+    }
+
+    // The fuel is in the artifact, so `run` doesn't get `--fuel`.
+    let output = Command::new(WASMER_PATH)
+        .arg("run")
+        .arg(&artifact_path)
+        .output()?;
+
+    assert_eq!(output.status.code(), Some(124));
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(stderr.contains("all 1000 points of fuel were consumed"));
+    Ok(())
+}
+
+#[test]
+fn run_with_fuel_exhausted_in_initialize_reports_error() -> anyhow::Result<()> {
+    let output = Command::new(WASMER_PATH)
+        .arg("run")
+        .arg(test_initialize_loop_wat_path())
+        .arg("--fuel")
+        .arg("1000")
+        .output()?;
+
+    assert_eq!(output.status.code(), Some(124));
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(stderr.contains("all 1000 points of fuel were consumed"));
+    Ok(())
+}
@@ -149,6 +149,17 @@ impl Wast {
         bail!("expected '{}', got '{}'", expected, actual)
     }
 
+    fn assert_exception(&self, result: Result<Vec<Val>>) -> Result<()> {
+        let error = match result {
+            Ok(values) => bail!("expected exception, got {:?}", values),
+            Err(e) => e,
+        };
+        match error.downcast_ref::<RuntimeError>() {
+            Some(e) if e.is_exception() => Ok(()),
+            _ => bail!("expected exception, got '{}'", error),
+        }
+    }
+
     fn run_directive(&mut self, test: &Path, directive: wast::WastDirective) -> Result<()> {
         use wast::WastDirective::*;
 
@@ -220,8 +231,9 @@ impl Wast {
             QuoteModule { .. } => {
                 // Do nothing
             }
-            AssertException { .. } => {
-                // Do nothing for now
+            AssertException { span: _, exec } => {
+                let result = self.perform_execute(exec);
+                self.assert_exception(result)?;
             }
             AssertMalformed {
                 module,
@@ -0,0 +1,213 @@
+;; Throwing and catching exceptions, within a function and across calls,
+;; instances and handlers.
+
+(module $thrower
+  (tag $e (export "e") (param i32))
+  (func (export "throw") (param i32)
+    (throw $e (local.get 0)))
+)
+
+(register "thrower" $thrower)
+
+(module
+  (import "thrower" "e" (tag $imported (param i32)))
+  (import "thrower" "throw" (func $throw_imported (param i32)))
+  (tag $e0)
+  (tag $e1 (param i32))
+  (tag $e2 (param i64 f64))
+  (tag $v (param v128))
+
+  (func $throw_e0 (throw $e0))
+  (func $throw_e1 (param i32) (throw $e1 (local.get 0)))
+  (func $throw_if (param i32) (result i32)
+    (if (local.get 0) (then (throw $e1 (i32.const 7))))
+    (i32.const 3))
+
+  (func (export "catch-same-function") (result i32)
+    try (result i32)
+      (throw $e1 (i32.const 42))
+    catch $e1
+    end)
+
+  (func (export "catch-from-call") (param i32) (result i32)
+    try (result i32)
+      (call $throw_if (local.get 0))
+    catch $e1
+      (i32.add (i32.const 100))
+    end)
+
+  (func (export "catch-second-clause") (result i32)
+    try (result i32)
+      (call $throw_e0)
+      (i32.const 0)
+    catch $e1
+    catch $e0
+      (i32.const 2)
+    end)
+
+  (func (export "catch-all") (result i32)
+    try (result i32)
+      (call $throw_e1 (i32.const 1))
+      (i32.const 0)
+    catch $e0
+      (i32.const 1)
+    catch_all
+      (i32.const 2)
+    end)
+
+  (func (export "payload") (result i64 f64)
+    try (result i64 f64)
+      (throw $e2 (i64.const -5) (f64.const 2.5))
+    catch $e2
+    end)
+
+  (func (export "payload-v128") (result i32)
+    try (result i32)
+      (throw $v (v128.const i32x4 1 2 3 4))
+    catch $v
+      (i32x4.extract_lane 2)
+    end)
+
+  (func (export "uncaught") (call $throw_e0))
+
+  (func (export "uncaught-tag") (result i32)
+    try (result i32)
+      (call $throw_e0)
+      (i32.const 0)
+    catch $e1
+    end)
+
+  (func (export "nested") (result i32)
+    try (result i32)
+      try (result i32)
+        (call $throw_e0)
+        (i32.const 0)
+      catch $e1
+      end
+    catch $e0
+      (i32.const 5)
+    end)
+
+  (func (export "throw-in-handler") (result i32)
+    try (result i32)
+      try (result i32)
+        (call $throw_e0)
+        (i32.const 0)
+      catch $e0
+        (call $throw_e1 (i32.const 9))
+        (i32.const 0)
+      end
+    catch $e1
+    end)
+
+  (func (export "rethrow") (result i32)
+    try (result i32)
+      try (result i32)
+        (call $throw_e1 (i32.const 11))
+        (i32.const 0)
+      catch_all
+        rethrow 0
+      end
+    catch $e1
+    end)
+
+  (func (export "rethrow-outer") (result i32)
+    try (result i32)
+      try (result i32)
+        (call $throw_e1 (i32.const 12))
+        (i32.const 0)
+      catch $e1
+        drop
+        try (result i32)
+          (call $throw_e0)
+          (i32.const 0)
+        catch $e0
+          rethrow 1
+        end
+      end
+    catch $e1
+    end)
+
+  (func (export "delegate") (result i32)
+    try $outer (result i32)
+      try (result i32)
+        try (result i32)
+          (call $throw_e1 (i32.const 13))
+          (i32.const 0)
+        delegate $outer
+      catch $e1
+        drop
+        (i32.const 0)
+      end
+    catch $e1
+    end)
+
+  (func (export "delegate-to-caller")
+    try
+      (call $throw_e0)
+    delegate 0)
+
+  (func (export "branch-out-of-handler") (param i32) (result i32)
+    (block $done (result i32)
+      try (result i32)
+        (call $throw_e1 (local.get 0))
+        (i32.const 0)
+      catch $e1
+        (br_if $done (local.get 0))
+        drop
+        (i32.const -1)
+      end))
+
+  (func (export "loop-of-catches") (param i32) (result i32)
+    (local $sum i32)
+    (loop $again
+      try
+        (call $throw_e1 (local.get 0))
+      catch $e1
+        (local.set $sum (i32.add (local.get $sum)))
+      end
+      (br_if $again (local.tee 0 (i32.sub (local.get 0) (i32.const 1)))))
+    (local.get $sum))
+
+  (func (export "catch-imported") (result i32)
+    try (result i32)
+      (call $throw_imported (i32.const 21))
+      (i32.const 0)
+    catch $imported
+    end)
+
+  (func (export "no-throw") (result i32)
+    try (result i32)
+      (call $throw_if (i32.const 0))
+    catch_all
+      (i32.const -1)
+    end)
+)
+
+(assert_return (invoke "catch-same-function") (i32.const 42))
+(assert_return (invoke "catch-from-call" (i32.const 1)) (i32.const 107))
+(assert_return (invoke "catch-from-call" (i32.const 0)) (i32.const 3))
+(assert_return (invoke "catch-second-clause") (i32.const 2))
+(assert_return (invoke "catch-all") (i32.const 2))
+(assert_return (invoke "payload") (i64.const -5) (f64.const 2.5))
+(assert_return (invoke "payload-v128") (i32.const 3))
+(assert_exception (invoke "uncaught"))
+(assert_exception (invoke "uncaught-tag"))
+(assert_return (invoke "nested") (i32.const 5))
+(assert_return (invoke "throw-in-handler") (i32.const 9))
+(assert_return (invoke "rethrow") (i32.const 11))
+(assert_return (invoke "rethrow-outer") (i32.const 12))
+(assert_return (invoke "delegate") (i32.const 13))
+(assert_exception (invoke "delegate-to-caller"))
+(assert_return (invoke "branch-out-of-handler" (i32.const 8)) (i32.const 8))
+(assert_return (invoke "branch-out-of-handler" (i32.const 0)) (i32.const -1))
+(assert_return (invoke "loop-of-catches" (i32.const 4)) (i32.const 10))
+(assert_return (invoke "catch-imported") (i32.const 21))
+(assert_return (invoke "no-throw") (i32.const 3))
+
+;; The exceptions that reached the host don't linger after it.
+(assert_return (invoke "catch-from-call" (i32.const 0)) (i32.const 3))
+
+(assert_invalid
+  (module (func (throw 0)))
+  "unknown tag")
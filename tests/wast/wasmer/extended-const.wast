@@ -0,0 +1,74 @@
+;; Arithmetic in the constant expressions of globals and segment offsets.
+
+(module $base
+  (global (export "i32") i32 (i32.const 10))
+  (global (export "i64") i64 (i64.const 0x1_0000_0000))
+)
+
+(register "base" $base)
+
+(module
+  (import "base" "i32" (global $i32 i32))
+  (import "base" "i64" (global $i64 i64))
+
+  (global (export "add") i32 (i32.add (global.get $i32) (i32.const 5)))
+  (global (export "sub") i32 (i32.sub (i32.const 5) (global.get $i32)))
+  (global (export "mul") i32 (i32.mul (global.get $i32) (global.get $i32)))
+  (global (export "wrap") i32 (i32.add (i32.const 0x7fff_ffff) (i32.const 1)))
+  (global (export "nested") i32
+    (i32.sub (i32.mul (i32.add (global.get $i32) (i32.const 2)) (i32.const 3)) (i32.const 6)))
+  (global (export "add64") i64 (i64.add (global.get $i64) (i64.const -1)))
+  (global (export "mul64") i64 (i64.mul (global.get $i64) (i64.const 3)))
+
+  (memory 1)
+  (data (i32.add (global.get $i32) (i32.const 6)) "\01\02")
+  (data (i32.sub (global.get $i32) (i32.const 2)) "\03")
+  (data (i32.mul (i32.const 4) (i32.const 8)) "\04")
+
+  (table 20 funcref)
+  (elem (i32.add (global.get $i32) (i32.const 1)) $one)
+  (elem (i32.sub (i32.const 20) (i32.const 1)) $two)
+
+  (func $one (result i32) (i32.const 1))
+  (func $two (result i32) (i32.const 2))
+
+  (func (export "load") (param i32) (result i32)
+    (i32.load8_u (local.get 0)))
+  (func (export "call") (param i32) (result i32)
+    (call_indirect (result i32) (local.get 0)))
+)
+
+(assert_return (get "add") (i32.const 15))
+(assert_return (get "sub") (i32.const -5))
+(assert_return (get "mul") (i32.const 100))
+(assert_return (get "wrap") (i32.const 0x8000_0000))
+(assert_return (get "nested") (i32.const 30))
+(assert_return (get "add64") (i64.const 0xffff_ffff))
+(assert_return (get "mul64") (i64.const 0x3_0000_0000))
+
+(assert_return (invoke "load" (i32.const 16)) (i32.const 1))
+(assert_return (invoke "load" (i32.const 17)) (i32.const 2))
+(assert_return (invoke "load" (i32.const 8)) (i32.const 3))
+(assert_return (invoke "load" (i32.const 32)) (i32.const 4))
+(assert_return (invoke "call" (i32.const 11)) (i32.const 1))
+(assert_return (invoke "call" (i32.const 19)) (i32.const 2))
+
+;; A segment offset wraps around in 32 bits, like the arithmetic it uses.
+(module
+  (import "base" "i32" (global $i32 i32))
+  (memory 1)
+  (data (i32.add (global.get $i32) (i32.const -4)) "\05")
+  (func (export "load") (param i32) (result i32)
+    (i32.load8_u (local.get 0)))
+)
+
+(assert_return (invoke "load" (i32.const 6)) (i32.const 5))
+
+(assert_trap
+  (module
+    (import "base" "i32" (global $i32 i32))
+    (memory 1)
+    (data (i32.sub (global.get $i32) (i32.const 11)) "\06")
+  )
+  "out of bounds memory access"
+)
@@ -0,0 +1,62 @@
+;; memory.copy between and within memories
+
+(module
+  (memory $mem0 1)
+  (memory $mem1 1)
+  (memory $mem2 2)
+  (data (memory $mem1) (i32.const 0) "\01\02\03\04\05\06\07\08")
+
+  (func (export "copy_1_to_0") (param i32 i32 i32)
+    (memory.copy $mem0 $mem1 (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "copy_1_to_2") (param i32 i32 i32)
+    (memory.copy $mem2 $mem1 (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "copy_2_to_1") (param i32 i32 i32)
+    (memory.copy $mem1 $mem2 (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "copy_within_1") (param i32 i32 i32)
+    (memory.copy $mem1 $mem1 (local.get 0) (local.get 1) (local.get 2)))
+
+  (func (export "load0") (param i32) (result i32) (i32.load8_u $mem0 (local.get 0)))
+  (func (export "load1") (param i32) (result i32) (i32.load8_u $mem1 (local.get 0)))
+  (func (export "load2") (param i32) (result i32) (i32.load8_u $mem2 (local.get 0)))
+)
+
+(assert_return (invoke "copy_1_to_0" (i32.const 16) (i32.const 0) (i32.const 8)))
+(assert_return (invoke "load0" (i32.const 15)) (i32.const 0))
+(assert_return (invoke "load0" (i32.const 16)) (i32.const 1))
+(assert_return (invoke "load0" (i32.const 23)) (i32.const 8))
+(assert_return (invoke "load0" (i32.const 24)) (i32.const 0))
+(assert_return (invoke "load1" (i32.const 16)) (i32.const 0))
+(assert_return (invoke "load2" (i32.const 16)) (i32.const 0))
+
+;; The destination is bounds checked against its own, larger, memory.
+(assert_return (invoke "copy_1_to_2" (i32.const 0x1fffc) (i32.const 4) (i32.const 4)))
+(assert_return (invoke "load2" (i32.const 0x1fffc)) (i32.const 5))
+(assert_return (invoke "load2" (i32.const 0x1ffff)) (i32.const 8))
+(assert_trap (invoke "copy_1_to_0" (i32.const 0xfffd) (i32.const 0) (i32.const 4)) "out of bounds memory access")
+(assert_trap (invoke "copy_1_to_2" (i32.const 0x1fffd) (i32.const 0) (i32.const 4)) "out of bounds memory access")
+
+;; So is the source.
+(assert_return (invoke "copy_2_to_1" (i32.const 0x100) (i32.const 0x1fffe) (i32.const 2)))
+(assert_return (invoke "load1" (i32.const 0x101)) (i32.const 8))
+(assert_trap (invoke "copy_2_to_1" (i32.const 0) (i32.const 0x1ffff) (i32.const 2)) "out of bounds memory access")
+(assert_trap (invoke "copy_2_to_1" (i32.const 0xffff) (i32.const 0) (i32.const 2)) "out of bounds memory access")
+
+;; Overlapping copies within a memory.
+(assert_return (invoke "copy_within_1" (i32.const 1) (i32.const 0) (i32.const 4)))
+(assert_return (invoke "load1" (i32.const 0)) (i32.const 1))
+(assert_return (invoke "load1" (i32.const 1)) (i32.const 1))
+(assert_return (invoke "load1" (i32.const 4)) (i32.const 4))
+(assert_return (invoke "load1" (i32.const 5)) (i32.const 6))
+
+;; Copies of length zero are allowed at the end of either memory.
+(assert_return (invoke "copy_1_to_2" (i32.const 0x20000) (i32.const 0x10000) (i32.const 0)))
+(assert_trap (invoke "copy_1_to_2" (i32.const 0x20001) (i32.const 0) (i32.const 0)) "out of bounds memory access")
+(assert_trap (invoke "copy_1_to_2" (i32.const 0) (i32.const 0x10001) (i32.const 0)) "out of bounds memory access")
+
+(assert_invalid
+  (module
+    (memory 1)
+    (func (memory.copy 0 1 (i32.const 0) (i32.const 0) (i32.const 0)))
+  )
+  "unknown memory"
+)
@@ -0,0 +1,70 @@
+;; Modules with several memories, imported and defined
+
+(module $exporter
+  (memory (export "mem-a") 1 3)
+  (memory (export "mem-b") 2)
+  (data (memory 0) (i32.const 0) "\0a")
+  (data (memory 1) (i32.const 0) "\0b")
+)
+(register "exporter" $exporter)
+
+(module
+  (import "exporter" "mem-a" (memory $a 1))
+  (import "exporter" "mem-b" (memory $b 2))
+  (memory $c 1)
+  (data (memory $c) (i32.const 0) "\0c")
+
+  (func (export "load") (param i32) (result i32)
+    (i32.add
+      (i32.add
+        (i32.load8_u $a (local.get 0))
+        (i32.shl (i32.load8_u $b (local.get 0)) (i32.const 8)))
+      (i32.shl (i32.load8_u $c (local.get 0)) (i32.const 16))))
+  (func (export "grow-a") (param i32) (result i32) (memory.grow $a (local.get 0)))
+  (func (export "size-a") (result i32) (memory.size $a))
+  (func (export "size-b") (result i32) (memory.size $b))
+  (func (export "size-c") (result i32) (memory.size $c))
+)
+
+(assert_return (invoke "load" (i32.const 0)) (i32.const 0x0c0b0a))
+(assert_return (invoke "size-a") (i32.const 1))
+(assert_return (invoke "size-b") (i32.const 2))
+(assert_return (invoke "size-c") (i32.const 1))
+(assert_return (invoke "grow-a" (i32.const 2)) (i32.const 1))
+(assert_return (invoke "grow-a" (i32.const 1)) (i32.const -1))
+(assert_return (invoke "size-a") (i32.const 3))
+(assert_return (invoke "size-b") (i32.const 2))
+
+;; Import limits are checked for each memory.
+(assert_unlinkable
+  (module
+    (import "exporter" "mem-a" (memory 1))
+    (import "exporter" "mem-b" (memory 3))
+  )
+  "incompatible import type"
+)
+(assert_unlinkable
+  (module
+    (import "exporter" "mem-b" (memory 1))
+    (import "exporter" "mem-a" (memory 1 2))
+  )
+  "incompatible import type"
+)
+
+;; Data segments of a failed instantiation that were already applied stay
+;; in the memories they target.
+(assert_trap
+  (module
+    (import "exporter" "mem-a" (memory $a 1))
+    (import "exporter" "mem-b" (memory $b 1))
+    (data (memory $b) (i32.const 1) "\2a")
+    (data (memory $a) (i32.const 0x40000) "\2a")
+  )
+  "out of bounds memory access"
+)
+
+(module
+  (import "exporter" "mem-b" (memory $b 1))
+  (func (export "load-b") (param i32) (result i32) (i32.load8_u $b (local.get 0)))
+)
+(assert_return (invoke "load-b" (i32.const 1)) (i32.const 0x2a))
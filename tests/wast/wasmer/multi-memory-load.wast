@@ -0,0 +1,114 @@
+;; Multiple memories
+
+(module
+  (memory $mem1 1)
+  (memory $mem2 1)
+
+  (func (export "load1") (param i32) (result i64)
+    (i64.load $mem1 (local.get 0))
+  )
+  (func (export "load2") (param i32) (result i64)
+    (i64.load $mem2 (local.get 0))
+  )
+
+  (data (memory $mem1) (i32.const 0) "\01")
+  (data (memory $mem2) (i32.const 0) "\02")
+)
+
+(assert_return (invoke "load1" (i32.const 0)) (i64.const 1))
+(assert_return (invoke "load2" (i32.const 0)) (i64.const 2))
+
+
+(module $M1
+  (memory (export "mem") 1)
+
+  (func (export "read") (param i32) (result i64)
+    (i64.load (local.get 0))
+  )
+  (data (i32.const 0) "\01")
+)
+(register "M1")
+
+(module $M2
+  (memory (export "mem") 1)
+
+  (func (export "read") (param i32) (result i64)
+    (i64.load (local.get 0))
+  )
+  (data (i32.const 0) "\02")
+)
+(register "M2")
+
+(module
+  (memory $mem1 (import "M1" "mem") 1)
+  (memory $mem2 (import "M2" "mem") 1)
+
+  (func (export "read1") (param i32) (result i64)
+    (i64.load $mem1 (local.get 0))
+  )
+  (func (export "read2") (param i32) (result i64)
+    (i64.load $mem2 (local.get 0))
+  )
+)
+
+(assert_return (invoke $M1 "read" (i32.const 0)) (i64.const 1))
+(assert_return (invoke $M2 "read" (i32.const 0)) (i64.const 2))
+(assert_return (invoke "read1" (i32.const 0)) (i64.const 1))
+(assert_return (invoke "read2" (i32.const 0)) (i64.const 2))
+
+
+;; Offsets, sizes and bounds are those of the memory being accessed.
+
+(module
+  (memory $small 1)
+  (memory $big 2)
+
+  (func (export "small.i32_load8_u") (param i32) (result i32)
+    (i32.load8_u $small offset=1 (local.get 0))
+  )
+  (func (export "big.i32_load8_u") (param i32) (result i32)
+    (i32.load8_u $big offset=1 (local.get 0))
+  )
+  (func (export "small.i32_load16_s") (param i32) (result i32)
+    (i32.load16_s $small (local.get 0))
+  )
+  (func (export "big.i32_load16_s") (param i32) (result i32)
+    (i32.load16_s $big (local.get 0))
+  )
+  (func (export "small.f64_load") (param i32) (result f64)
+    (f64.load $small (local.get 0))
+  )
+  (func (export "big.f64_load") (param i32) (result f64)
+    (f64.load $big (local.get 0))
+  )
+
+  (data (memory $small) (i32.const 0) "\00\ff\ff")
+  (data (memory $big) (i32.const 0x1fffd) "\7f\80\ff")
+)
+
+(assert_return (invoke "small.i32_load8_u" (i32.const 0)) (i32.const 0xff))
+(assert_return (invoke "big.i32_load8_u" (i32.const 0)) (i32.const 0))
+(assert_return (invoke "small.i32_load16_s" (i32.const 1)) (i32.const -1))
+(assert_return (invoke "big.i32_load16_s" (i32.const 0x1fffe)) (i32.const -128))
+(assert_return (invoke "big.i32_load8_u" (i32.const 0x1fffe)) (i32.const 0xff))
+(assert_trap (invoke "small.i32_load8_u" (i32.const 0xffff)) "out of bounds memory access")
+(assert_trap (invoke "big.i32_load8_u" (i32.const 0x1ffff)) "out of bounds memory access")
+(assert_trap (invoke "small.i32_load16_s" (i32.const 0xffff)) "out of bounds memory access")
+(assert_return (invoke "small.f64_load" (i32.const 0xfff8)) (f64.const 0))
+(assert_trap (invoke "small.f64_load" (i32.const 0xfff9)) "out of bounds memory access")
+(assert_return (invoke "big.f64_load" (i32.const 0xfff9)) (f64.const 0))
+(assert_trap (invoke "big.f64_load" (i32.const 0x1fff9)) "out of bounds memory access")
+
+
+(assert_invalid
+  (module (memory 1) (func (drop (i32.load 1 (i32.const 0)))))
+  "unknown memory"
+)
+(assert_invalid
+  (module
+    (memory 1)
+    (memory 1)
+    (func (drop (i64.load 2 (i32.const 0))))
+  )
+  "unknown memory"
+)
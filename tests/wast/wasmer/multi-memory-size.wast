@@ -0,0 +1,56 @@
+(module
+  (memory $mem0 0)
+  (memory $mem1 1)
+  (memory $mem2 0 2)
+  (memory $mem3 3 8)
+
+  (func (export "size0") (result i32) (memory.size $mem0))
+  (func (export "size1") (result i32) (memory.size $mem1))
+  (func (export "size2") (result i32) (memory.size $mem2))
+  (func (export "size3") (result i32) (memory.size $mem3))
+
+  (func (export "grow0") (param $sz i32) (drop (memory.grow $mem0 (local.get $sz))))
+  (func (export "grow2") (param $sz i32) (drop (memory.grow $mem2 (local.get $sz))))
+  (func (export "grow3") (param $sz i32) (drop (memory.grow $mem3 (local.get $sz))))
+)
+
+(assert_return (invoke "size0") (i32.const 0))
+(assert_return (invoke "size1") (i32.const 1))
+(assert_return (invoke "size2") (i32.const 0))
+(assert_return (invoke "size3") (i32.const 3))
+
+(assert_return (invoke "grow0" (i32.const 1)))
+(assert_return (invoke "size0") (i32.const 1))
+(assert_return (invoke "size1") (i32.const 1))
+(assert_return (invoke "size2") (i32.const 0))
+
+(assert_return (invoke "grow2" (i32.const 1)))
+(assert_return (invoke "size2") (i32.const 1))
+(assert_return (invoke "grow2" (i32.const 2)))
+(assert_return (invoke "size2") (i32.const 1))
+(assert_return (invoke "grow2" (i32.const 1)))
+(assert_return (invoke "size2") (i32.const 2))
+
+(assert_return (invoke "grow3" (i32.const 5)))
+(assert_return (invoke "size3") (i32.const 8))
+(assert_return (invoke "grow3" (i32.const 1)))
+(assert_return (invoke "size3") (i32.const 8))
+(assert_return (invoke "size0") (i32.const 1))
+(assert_return (invoke "size1") (i32.const 1))
+(assert_return (invoke "size2") (i32.const 2))
+
+
+(assert_invalid
+  (module
+    (memory 1)
+    (func $type-unknown-memory (result i32) (memory.size 1))
+  )
+  "unknown memory"
+)
+(assert_invalid
+  (module
+    (memory 1)
+    (func $type-unknown-memory (result i32) (memory.grow 1 (i32.const 0)))
+  )
+  "unknown memory"
+)
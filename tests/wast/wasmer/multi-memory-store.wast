@@ -0,0 +1,119 @@
+;; Multiple memories
+
+(module
+  (memory $mem1 1)
+  (memory $mem2 1)
+
+  (func (export "load1") (param i32) (result i64)
+    (i64.load $mem1 (local.get 0))
+  )
+  (func (export "load2") (param i32) (result i64)
+    (i64.load $mem2 (local.get 0))
+  )
+
+  (func (export "store1") (param i32 i64)
+    (i64.store $mem1 (local.get 0) (local.get 1))
+  )
+  (func (export "store2") (param i32 i64)
+    (i64.store $mem2 (local.get 0) (local.get 1))
+  )
+)
+
+(invoke "store1" (i32.const 0) (i64.const 1))
+(invoke "store2" (i32.const 0) (i64.const 2))
+(assert_return (invoke "load1" (i32.const 0)) (i64.const 1))
+(assert_return (invoke "load2" (i32.const 0)) (i64.const 2))
+
+
+(module $M1
+  (memory (export "mem") 1)
+
+  (func (export "load") (param i32) (result i64)
+    (i64.load (local.get 0))
+  )
+  (func (export "store") (param i32 i64)
+    (i64.store (local.get 0) (local.get 1))
+  )
+)
+(register "M1")
+
+(module $M2
+  (memory (export "mem") 1)
+
+  (func (export "load") (param i32) (result i64)
+    (i64.load (local.get 0))
+  )
+  (func (export "store") (param i32 i64)
+    (i64.store (local.get 0) (local.get 1))
+  )
+)
+(register "M2")
+
+(invoke $M1 "store" (i32.const 0) (i64.const 1))
+(invoke $M2 "store" (i32.const 0) (i64.const 2))
+(assert_return (invoke $M1 "load" (i32.const 0)) (i64.const 1))
+(assert_return (invoke $M2 "load" (i32.const 0)) (i64.const 2))
+
+(module
+  (memory $mem1 (import "M1" "mem") 1)
+  (memory $mem2 (import "M2" "mem") 1)
+
+  (func (export "load1") (param i32) (result i64)
+    (i64.load $mem1 (local.get 0))
+  )
+  (func (export "load2") (param i32) (result i64)
+    (i64.load $mem2 (local.get 0))
+  )
+
+  (func (export "store1") (param i32 i64)
+    (i64.store $mem1 (local.get 0) (local.get 1))
+  )
+  (func (export "store2") (param i32 i64)
+    (i64.store $mem2 (local.get 0) (local.get 1))
+  )
+)
+
+(invoke "store1" (i32.const 0) (i64.const 1))
+(invoke "store2" (i32.const 0) (i64.const 2))
+(assert_return (invoke "load1" (i32.const 0)) (i64.const 1))
+(assert_return (invoke "load2" (i32.const 0)) (i64.const 2))
+(assert_return (invoke $M1 "load" (i32.const 0)) (i64.const 1))
+(assert_return (invoke $M2 "load" (i32.const 0)) (i64.const 2))
+
+
+;; Stores are bounds checked against the memory they write to.
+
+(module
+  (memory $small 1)
+  (memory $big 2)
+
+  (func (export "small.store8") (param i32 i32)
+    (i32.store8 $small (local.get 0) (local.get 1))
+  )
+  (func (export "big.store8") (param i32 i32)
+    (i32.store8 $big (local.get 0) (local.get 1))
+  )
+  (func (export "big.store32") (param i32 i32)
+    (i32.store $big offset=4 (local.get 0) (local.get 1))
+  )
+  (func (export "small.load8") (param i32) (result i32)
+    (i32.load8_u $small (local.get 0))
+  )
+  (func (export "big.load8") (param i32) (result i32)
+    (i32.load8_u $big (local.get 0))
+  )
+)
+
+(assert_return (invoke "big.store8" (i32.const 0x1ffff) (i32.const 0x2a)))
+(assert_return (invoke "big.load8" (i32.const 0x1ffff)) (i32.const 0x2a))
+(assert_trap (invoke "small.store8" (i32.const 0x10000) (i32.const 1)) "out of bounds memory access")
+(assert_trap (invoke "big.store8" (i32.const 0x20000) (i32.const 1)) "out of bounds memory access")
+(assert_return (invoke "big.store32" (i32.const 0x1fff8) (i32.const 0x01020304)))
+(assert_return (invoke "big.load8" (i32.const 0x1fffc)) (i32.const 4))
+(assert_trap (invoke "big.store32" (i32.const 0x1fff9) (i32.const 0)) "out of bounds memory access")
+(assert_return (invoke "small.load8" (i32.const 0xffff)) (i32.const 0))
+
+(assert_invalid
+  (module (memory 1) (func (i32.store 1 (i32.const 0) (i32.const 0))))
+  "unknown memory"
+)
@@ -0,0 +1,104 @@
+;; Loads, stores and bulk operations on modules with several memories.
+
+(module
+  (import "spectest" "memory" (memory $imported 1 2))
+  (memory $a 1)
+  (memory $b 2 3)
+  (data (memory $a) (i32.const 0) "\01\02\03\04")
+  (data (memory $b) (i32.const 8) "\0a\0b\0c\0d")
+  (data $passive "\aa\bb")
+
+  (func (export "load_a") (param i32) (result i32)
+    (i32.load8_u $a (local.get 0)))
+  (func (export "load_b") (param i32) (result i32)
+    (i32.load8_u $b (local.get 0)))
+  (func (export "load_imported") (param i32) (result i32)
+    (i32.load8_u $imported (local.get 0)))
+  (func (export "store_b") (param i32 i32)
+    (i32.store $b (local.get 0) (local.get 1)))
+  (func (export "store_imported") (param i32 i32)
+    (i32.store8 $imported (local.get 0) (local.get 1)))
+
+  (func (export "size_a") (result i32) (memory.size $a))
+  (func (export "size_b") (result i32) (memory.size $b))
+  (func (export "grow_a") (param i32) (result i32)
+    (memory.grow $a (local.get 0)))
+  (func (export "grow_b") (param i32) (result i32)
+    (memory.grow $b (local.get 0)))
+
+  (func (export "copy_a_to_b") (param i32 i32 i32)
+    (memory.copy $b $a (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "copy_b_to_imported") (param i32 i32 i32)
+    (memory.copy $imported $b (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "copy_within_b") (param i32 i32 i32)
+    (memory.copy $b $b (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "fill_b") (param i32 i32 i32)
+    (memory.fill $b (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "init_b") (param i32 i32 i32)
+    (memory.init $b $passive (local.get 0) (local.get 1) (local.get 2)))
+)
+
+;; Active data segments are written to the memory they name.
+(assert_return (invoke "load_a" (i32.const 0)) (i32.const 1))
+(assert_return (invoke "load_a" (i32.const 8)) (i32.const 0))
+(assert_return (invoke "load_b" (i32.const 0)) (i32.const 0))
+(assert_return (invoke "load_b" (i32.const 8)) (i32.const 10))
+
+;; Every memory has its own bounds.
+(assert_return (invoke "load_b" (i32.const 0x1ffff)) (i32.const 0))
+(assert_trap (invoke "load_a" (i32.const 0x10000)) "out of bounds memory access")
+(assert_trap (invoke "load_b" (i32.const 0x20000)) "out of bounds memory access")
+(assert_return (invoke "store_b" (i32.const 0x1fffc) (i32.const 0x05060708)))
+(assert_return (invoke "load_b" (i32.const 0x1fffc)) (i32.const 8))
+(assert_trap (invoke "store_b" (i32.const 0x1fffd) (i32.const 0)) "out of bounds memory access")
+
+;; Memories are sized and grown independently.
+(assert_return (invoke "size_a") (i32.const 1))
+(assert_return (invoke "size_b") (i32.const 2))
+(assert_return (invoke "grow_b" (i32.const 1)) (i32.const 2))
+(assert_return (invoke "grow_b" (i32.const 1)) (i32.const -1))
+(assert_return (invoke "size_a") (i32.const 1))
+(assert_return (invoke "size_b") (i32.const 3))
+(assert_return (invoke "load_b" (i32.const 0x2ffff)) (i32.const 0))
+(assert_return (invoke "grow_a" (i32.const 1)) (i32.const 1))
+(assert_return (invoke "load_a" (i32.const 0x10000)) (i32.const 0))
+
+;; Bulk operations between and within memories.
+(assert_return (invoke "copy_a_to_b" (i32.const 0x100) (i32.const 0) (i32.const 4)))
+(assert_return (invoke "load_b" (i32.const 0x103)) (i32.const 4))
+(assert_return (invoke "load_a" (i32.const 0x103)) (i32.const 0))
+(assert_trap (invoke "copy_a_to_b" (i32.const 0) (i32.const 0x1ffff) (i32.const 2)) "out of bounds memory access")
+(assert_trap (invoke "copy_a_to_b" (i32.const 0x2ffff) (i32.const 0) (i32.const 2)) "out of bounds memory access")
+(assert_return (invoke "copy_b_to_imported" (i32.const 0x20) (i32.const 8) (i32.const 4)))
+(assert_return (invoke "load_imported" (i32.const 0x21)) (i32.const 11))
+(assert_return (invoke "copy_within_b" (i32.const 9) (i32.const 8) (i32.const 4)))
+(assert_return (invoke "load_b" (i32.const 12)) (i32.const 13))
+(assert_return (invoke "fill_b" (i32.const 0x200) (i32.const 0x55) (i32.const 2)))
+(assert_return (invoke "load_b" (i32.const 0x201)) (i32.const 0x55))
+(assert_return (invoke "load_a" (i32.const 0x201)) (i32.const 0))
+(assert_return (invoke "init_b" (i32.const 0x300) (i32.const 0) (i32.const 2)))
+(assert_return (invoke "load_b" (i32.const 0x301)) (i32.const 0xbb))
+(assert_trap (invoke "init_b" (i32.const 0x2ffff) (i32.const 0) (i32.const 2)) "out of bounds memory access")
+
+;; Stores to the imported memory are visible through it.
+(assert_return (invoke "store_imported" (i32.const 0x30) (i32.const 0x7f)))
+(assert_return (invoke "load_imported" (i32.const 0x30)) (i32.const 0x7f))
+
+;; Several memories can be exported and imported by another module.
+(module $exporter
+  (memory (export "first") 1)
+  (memory (export "second") 1)
+  (data (memory 1) (i32.const 0) "\2a")
+)
+(register "exporter" $exporter)
+
+(module
+  (import "exporter" "first" (memory $first 1))
+  (import "exporter" "second" (memory $second 1))
+  (func (export "read_second") (result i32)
+    (i32.load8_u $second (i32.const 0)))
+  (func (export "read_first") (result i32)
+    (i32.load8_u $first (i32.const 0)))
+)
+(assert_return (invoke "read_second") (i32.const 42))
+(assert_return (invoke "read_first") (i32.const 0))
@@ -0,0 +1,111 @@
+;; The relaxed SIMD operators, on inputs where every result they are
+;; allowed to return is the same.
+
+(module
+  (func (export "f32x4.fma") (param v128 v128 v128) (result v128)
+    (f32x4.fma (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "f32x4.fms") (param v128 v128 v128) (result v128)
+    (f32x4.fms (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "f64x2.fma") (param v128 v128 v128) (result v128)
+    (f64x2.fma (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "f64x2.fms") (param v128 v128 v128) (result v128)
+    (f64x2.fms (local.get 0) (local.get 1) (local.get 2)))
+
+  (func (export "i8x16.laneselect") (param v128 v128 v128) (result v128)
+    (i8x16.laneselect (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "i16x8.laneselect") (param v128 v128 v128) (result v128)
+    (i16x8.laneselect (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "i32x4.laneselect") (param v128 v128 v128) (result v128)
+    (i32x4.laneselect (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "i64x2.laneselect") (param v128 v128 v128) (result v128)
+    (i64x2.laneselect (local.get 0) (local.get 1) (local.get 2)))
+
+  (func (export "i8x16.relaxed_swizzle") (param v128 v128) (result v128)
+    (i8x16.relaxed_swizzle (local.get 0) (local.get 1)))
+
+  (func (export "f32x4.relaxed_min") (param v128 v128) (result v128)
+    (f32x4.relaxed_min (local.get 0) (local.get 1)))
+  (func (export "f32x4.relaxed_max") (param v128 v128) (result v128)
+    (f32x4.relaxed_max (local.get 0) (local.get 1)))
+  (func (export "f64x2.relaxed_min") (param v128 v128) (result v128)
+    (f64x2.relaxed_min (local.get 0) (local.get 1)))
+  (func (export "f64x2.relaxed_max") (param v128 v128) (result v128)
+    (f64x2.relaxed_max (local.get 0) (local.get 1)))
+)
+
+(assert_return
+  (invoke "f32x4.fma"
+    (v128.const f32x4 1 2 -3 0.5)
+    (v128.const f32x4 4 5 6 8)
+    (v128.const f32x4 1 -10 0 0.25))
+  (v128.const f32x4 5 0 -18 4.25))
+(assert_return
+  (invoke "f32x4.fms"
+    (v128.const f32x4 1 2 -3 0.5)
+    (v128.const f32x4 4 5 6 8)
+    (v128.const f32x4 1 -10 0 0.25))
+  (v128.const f32x4 -3 -20 18 -3.75))
+(assert_return
+  (invoke "f64x2.fma"
+    (v128.const f64x2 3 -0.5)
+    (v128.const f64x2 7 4)
+    (v128.const f64x2 1 2))
+  (v128.const f64x2 22 0))
+(assert_return
+  (invoke "f64x2.fms"
+    (v128.const f64x2 3 -0.5)
+    (v128.const f64x2 7 4)
+    (v128.const f64x2 1 2))
+  (v128.const f64x2 -20 4))
+
+(assert_return
+  (invoke "i8x16.laneselect"
+    (v128.const i8x16 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16)
+    (v128.const i8x16 -1 -2 -3 -4 -5 -6 -7 -8 -9 -10 -11 -12 -13 -14 -15 -16)
+    (v128.const i8x16 -1 0 -1 0 -1 0 -1 0 0 0 0 0 -1 -1 -1 -1))
+  (v128.const i8x16 1 -2 3 -4 5 -6 7 -8 -9 -10 -11 -12 13 14 15 16))
+(assert_return
+  (invoke "i16x8.laneselect"
+    (v128.const i16x8 1 2 3 4 5 6 7 8)
+    (v128.const i16x8 -1 -2 -3 -4 -5 -6 -7 -8)
+    (v128.const i16x8 0 -1 0 -1 -1 -1 0 0))
+  (v128.const i16x8 -1 2 -3 4 5 6 -7 -8))
+(assert_return
+  (invoke "i32x4.laneselect"
+    (v128.const i32x4 1 2 3 4)
+    (v128.const i32x4 -1 -2 -3 -4)
+    (v128.const i32x4 -1 0 0 -1))
+  (v128.const i32x4 1 -2 -3 4))
+(assert_return
+  (invoke "i64x2.laneselect"
+    (v128.const i64x2 1 2)
+    (v128.const i64x2 -1 -2)
+    (v128.const i64x2 0 -1))
+  (v128.const i64x2 -1 2))
+
+(assert_return
+  (invoke "i8x16.relaxed_swizzle"
+    (v128.const i8x16 0 10 20 30 40 50 60 70 80 90 100 110 120 -126 -116 -106)
+    (v128.const i8x16 15 14 13 12 11 10 9 8 0 1 2 3 4 5 6 7))
+  (v128.const i8x16 -106 -116 -126 120 110 100 90 80 0 10 20 30 40 50 60 70))
+
+(assert_return
+  (invoke "f32x4.relaxed_min"
+    (v128.const f32x4 1 -2 3.5 -inf)
+    (v128.const f32x4 2 -3 3 100))
+  (v128.const f32x4 1 -3 3 -inf))
+(assert_return
+  (invoke "f32x4.relaxed_max"
+    (v128.const f32x4 1 -2 3.5 -inf)
+    (v128.const f32x4 2 -3 3 100))
+  (v128.const f32x4 2 -2 3.5 100))
+(assert_return
+  (invoke "f64x2.relaxed_min"
+    (v128.const f64x2 1 inf)
+    (v128.const f64x2 -1 2))
+  (v128.const f64x2 -1 2))
+(assert_return
+  (invoke "f64x2.relaxed_max"
+    (v128.const f64x2 1 inf)
+    (v128.const f64x2 -1 2))
+  (v128.const f64x2 1 inf))
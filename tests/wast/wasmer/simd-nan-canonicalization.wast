@@ -0,0 +1,107 @@
+;; NaN canonicalization of the SIMD operators.
+;;
+;; The inputs are NaNs with a payload and/or a sign bit, all the NaNs
+;; produced by the operators must be the positive canonical NaN.
+
+(module
+  (func (export "f32x4.add") (param v128 v128) (result v128)
+    (f32x4.add (local.get 0) (local.get 1)))
+  (func (export "f32x4.sub") (param v128 v128) (result v128)
+    (f32x4.sub (local.get 0) (local.get 1)))
+  (func (export "f32x4.mul") (param v128 v128) (result v128)
+    (f32x4.mul (local.get 0) (local.get 1)))
+  (func (export "f32x4.div") (param v128 v128) (result v128)
+    (f32x4.div (local.get 0) (local.get 1)))
+  (func (export "f32x4.min") (param v128 v128) (result v128)
+    (f32x4.min (local.get 0) (local.get 1)))
+  (func (export "f32x4.max") (param v128 v128) (result v128)
+    (f32x4.max (local.get 0) (local.get 1)))
+  (func (export "f32x4.sqrt") (param v128) (result v128)
+    (f32x4.sqrt (local.get 0)))
+  (func (export "f32x4.ceil") (param v128) (result v128)
+    (f32x4.ceil (local.get 0)))
+  (func (export "f32x4.nearest") (param v128) (result v128)
+    (f32x4.nearest (local.get 0)))
+  (func (export "f64x2.add") (param v128 v128) (result v128)
+    (f64x2.add (local.get 0) (local.get 1)))
+  (func (export "f64x2.min") (param v128 v128) (result v128)
+    (f64x2.min (local.get 0) (local.get 1)))
+  (func (export "f64x2.max") (param v128 v128) (result v128)
+    (f64x2.max (local.get 0) (local.get 1)))
+  (func (export "f64x2.sqrt") (param v128) (result v128)
+    (f64x2.sqrt (local.get 0)))
+
+  ;; Scalars computed before being moved into a vector
+  (func (export "f32x4.splat") (param i32) (result v128)
+    (f32x4.splat (f32.add (f32.reinterpret_i32 (local.get 0)) (f32.const 0))))
+  (func (export "f64x2.replace_lane") (param i64) (result v128)
+    (f64x2.replace_lane 1
+      (v128.const i64x2 0 0)
+      (f64.add (f64.reinterpret_i64 (local.get 0)) (f64.const 0))))
+)
+
+(assert_return
+  (invoke "f32x4.add"
+    (v128.const i32x4 0x7fc00001 0xffc00000 0xff800001 0x3f800000)
+    (v128.const f32x4 1 1 1 1))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x7fc00000 0x40000000))
+(assert_return
+  (invoke "f32x4.sub"
+    (v128.const f32x4 1 1 1 inf)
+    (v128.const i32x4 0x7fc00001 0xffc00000 0x3f800000 0x7f800000))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0 0x7fc00000))
+(assert_return
+  (invoke "f32x4.mul"
+    (v128.const i32x4 0xffc00000 0x7f800001 0 0x7f800000)
+    (v128.const f32x4 2 2 2 0))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0 0x7fc00000))
+(assert_return
+  (invoke "f32x4.div"
+    (v128.const i32x4 0x7fc00001 0 0x40000000 0xff800000)
+    (v128.const f32x4 1 0 2 -inf))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x3f800000 0x7fc00000))
+(assert_return
+  (invoke "f32x4.min"
+    (v128.const i32x4 0x7fc00001 0x3f800000 0xffc00000 0x3f800000)
+    (v128.const i32x4 0x3f800000 0xffc00001 0x7fc00000 0x40000000))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x7fc00000 0x3f800000))
+(assert_return
+  (invoke "f32x4.max"
+    (v128.const i32x4 0x7fc00001 0x3f800000 0xffc00000 0x3f800000)
+    (v128.const i32x4 0x3f800000 0xffc00001 0x7fc00000 0x40000000))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x7fc00000 0x40000000))
+(assert_return
+  (invoke "f32x4.sqrt" (v128.const i32x4 0x7fc00001 0xbf800000 0xffc00000 0x40800000))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x7fc00000 0x40000000))
+(assert_return
+  (invoke "f32x4.ceil" (v128.const i32x4 0x7fc00001 0xffc00000 0x3fc00000 0xff800001))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x40000000 0x7fc00000))
+(assert_return
+  (invoke "f32x4.nearest" (v128.const i32x4 0x7fc00001 0xffc00000 0x3fc00000 0xff800001))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x40000000 0x7fc00000))
+
+(assert_return
+  (invoke "f64x2.add"
+    (v128.const i64x2 0x7ff8000000000001 0xfff8000000000000)
+    (v128.const f64x2 1 1))
+  (v128.const i64x2 0x7ff8000000000000 0x7ff8000000000000))
+(assert_return
+  (invoke "f64x2.min"
+    (v128.const i64x2 0x7ff8000000000001 0x3ff0000000000000)
+    (v128.const i64x2 0x3ff0000000000000 0xfff8000000000000))
+  (v128.const i64x2 0x7ff8000000000000 0x7ff8000000000000))
+(assert_return
+  (invoke "f64x2.max"
+    (v128.const i64x2 0x7ff8000000000001 0x3ff0000000000000)
+    (v128.const i64x2 0x3ff0000000000000 0x4000000000000000))
+  (v128.const i64x2 0x7ff8000000000000 0x4000000000000000))
+(assert_return
+  (invoke "f64x2.sqrt" (v128.const i64x2 0xfff8000000000001 0xbff0000000000000))
+  (v128.const i64x2 0x7ff8000000000000 0x7ff8000000000000))
+
+(assert_return
+  (invoke "f32x4.splat" (i32.const 0xffc00001))
+  (v128.const i32x4 0x7fc00000 0x7fc00000 0x7fc00000 0x7fc00000))
+(assert_return
+  (invoke "f64x2.replace_lane" (i64.const 0xfff8000000000001))
+  (v128.const i64x2 0 0x7ff8000000000000))